@@ -11,8 +11,8 @@ use std::{
 use anyhow::{Context, Result};
 use glob::glob;
 use syn::{
-    parse_quote, Attribute, Ident, Item, ItemEnum, ItemFn, ItemImpl, ItemMacro, ItemMod,
-    ItemStruct, ItemTrait, TraitItem, TraitItemMethod,
+    parse_quote, punctuated::Punctuated, Attribute, Ident, Item, ItemEnum, ItemFn, ItemImpl,
+    ItemMacro, ItemMod, ItemStruct, ItemTrait, Meta, Token, TraitItem, TraitItemMethod,
 };
 use turbo_tasks_macros_shared::{
     get_impl_function_ident, get_native_function_ident, get_path_ident,
@@ -221,7 +221,17 @@ impl<'a> RegisterContext<'a> {
     }
 
     fn process_fn(&mut self, fn_item: ItemFn) -> Result<()> {
-        if has_attribute(&fn_item.attrs, "function") {
+        if let Some(attr) = fn_item.attrs.iter().find(|a| is_attribute(a, "function")) {
+            // Local functions are executed by their caller and have no native
+            // function to register
+            if is_local_function(attr) {
+                return Ok(());
+            }
+            // Instances of generic functions are registered when they are
+            // first called
+            if !fn_item.sig.generics.params.is_empty() {
+                return Ok(());
+            }
             let ident = &fn_item.sig.ident;
             let type_ident = get_native_function_ident(ident);
 
@@ -518,6 +528,15 @@ fn is_attribute(attr: &Attribute, name: &str) -> bool {
     }
 }
 
+fn is_local_function(attr: &Attribute) -> bool {
+    if attr.tokens.is_empty() {
+        return false;
+    }
+    attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+        .map(|args| args.iter().any(|meta| meta.path().is_ident("local")))
+        .unwrap_or(false)
+}
+
 fn parse_attr_args<T>(attr: &Attribute) -> syn::Result<Option<T>>
 where
     T: syn::parse::Parse,
@@ -0,0 +1,179 @@
+use std::{
+    fs, io,
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use turbo_tasks::trace::TraceRawVcs;
+
+/// Bumped whenever the layout of a saved hash cache changes. Caches with a
+/// different version are ignored.
+const HASH_CACHE_VERSION: u32 = 1;
+
+/// Files modified less than this long ago might be modified again without
+/// their modification time changing, depending on the resolution of the
+/// file system's timestamps. Their hashes are never cached.
+const RACY_WINDOW: Duration = Duration::from_secs(2);
+
+/// The hash function used for
+/// [DiskFileSystem::content_hash](crate::DiskFileSystem::content_hash).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize, TraceRawVcs)]
+pub enum ContentHashAlgorithm {
+    /// xxHash3, the fastest option and the hash used for [Rope]s.
+    ///
+    /// [Rope]: crate::rope::Rope
+    #[default]
+    Xxh3,
+    /// BLAKE3, which makes collisions practically impossible. Only the first
+    /// 64 bits of the digest are used.
+    Blake3,
+}
+
+/// The observed state of a file. A file with the same stamp as when it was
+/// hashed is assumed to have the same content.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct FileStamp {
+    mtime: u128,
+    size: u64,
+    inode: u64,
+}
+
+impl FileStamp {
+    /// Returns `None` when the stamp of the file can't be trusted, because
+    /// the platform doesn't report modification times or the file was
+    /// modified too recently.
+    pub(crate) fn new(meta: &fs::Metadata) -> Option<Self> {
+        let modified = meta.modified().ok()?;
+        let age = SystemTime::now().duration_since(modified).ok()?;
+        if age < RACY_WINDOW {
+            return None;
+        }
+        #[cfg(unix)]
+        let inode = std::os::unix::fs::MetadataExt::ino(meta);
+        #[cfg(not(unix))]
+        let inode = 0;
+        Some(FileStamp {
+            mtime: modified.duration_since(UNIX_EPOCH).ok()?.as_nanos(),
+            size: meta.len(),
+            inode,
+        })
+    }
+}
+
+/// Content hashes of files by their path relative to the root of the file
+/// system, together with the stamp the files had when they were hashed.
+#[derive(Default)]
+pub(crate) struct HashCache {
+    entries: DashMap<String, (FileStamp, u64)>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedHashCache {
+    version: u32,
+    algorithm: ContentHashAlgorithm,
+    entries: Vec<(String, FileStamp, u64)>,
+}
+
+impl HashCache {
+    pub(crate) fn get(&self, key: &str, stamp: FileStamp) -> Option<u64> {
+        let entry = self.entries.get(key)?;
+        let (cached_stamp, hash) = *entry;
+        (cached_stamp == stamp).then_some(hash)
+    }
+
+    pub(crate) fn insert(&self, key: String, stamp: FileStamp, hash: u64) {
+        self.entries.insert(key, (stamp, hash));
+    }
+
+    pub(crate) fn remove(&self, key: &str) {
+        self.entries.remove(key);
+    }
+
+    pub(crate) fn clear(&self) {
+        self.entries.clear();
+    }
+
+    /// Writes the cache to `path`. Returns the number of saved entries.
+    pub(crate) fn save(&self, path: &Path, algorithm: ContentHashAlgorithm) -> Result<usize> {
+        let saved = SavedHashCache {
+            version: HASH_CACHE_VERSION,
+            algorithm,
+            entries: self
+                .entries
+                .iter()
+                .map(|entry| {
+                    let (stamp, hash) = *entry.value();
+                    (entry.key().clone(), stamp, hash)
+                })
+                .collect(),
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Write to a temporary file first so a crash never leaves a partial
+        // cache behind
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, serde_json::to_vec(&saved)?)
+            .and_then(|()| fs::rename(&tmp_path, path))
+            .with_context(|| format!("failed to write hash cache {}", path.display()))?;
+        Ok(saved.entries.len())
+    }
+
+    /// Adds the entries saved at `path`. A missing, outdated or corrupted
+    /// cache and a cache of another algorithm are ignored. Returns the number
+    /// of loaded entries.
+    pub(crate) fn load(&self, path: &Path, algorithm: ContentHashAlgorithm) -> Result<usize> {
+        let contents = match fs::read(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(err) => {
+                return Err(err)
+                    .with_context(|| format!("failed to read hash cache {}", path.display()))
+            }
+        };
+        let saved = match serde_json::from_slice::<SavedHashCache>(&contents) {
+            Ok(saved) if saved.version == HASH_CACHE_VERSION && saved.algorithm == algorithm => {
+                saved
+            }
+            _ => return Ok(0),
+        };
+        let count = saved.entries.len();
+        for (key, stamp, hash) in saved.entries {
+            self.entries.insert(key, (stamp, hash));
+        }
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ContentHashAlgorithm, FileStamp, HashCache};
+
+    #[test]
+    fn save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hash-cache.json");
+        let stamp = FileStamp {
+            mtime: 1,
+            size: 2,
+            inode: 3,
+        };
+
+        let cache = HashCache::default();
+        cache.insert("a.txt".to_string(), stamp, 42);
+        assert_eq!(cache.get("a.txt", stamp), Some(42));
+        assert_eq!(cache.get("a.txt", FileStamp { size: 5, ..stamp }), None);
+        assert_eq!(cache.save(&path, ContentHashAlgorithm::Xxh3).unwrap(), 1);
+
+        let cache = HashCache::default();
+        assert_eq!(cache.load(&path, ContentHashAlgorithm::Blake3).unwrap(), 0);
+        assert_eq!(cache.load(&path, ContentHashAlgorithm::Xxh3).unwrap(), 1);
+        assert_eq!(cache.get("a.txt", stamp), Some(42));
+
+        let missing = dir.path().join("missing.json");
+        assert_eq!(cache.load(&missing, ContentHashAlgorithm::Xxh3).unwrap(), 0);
+    }
+}
@@ -9,10 +9,12 @@
 pub mod attach;
 pub mod embed;
 pub mod glob;
+mod hash_cache;
 mod invalidation;
 mod invalidator_map;
 pub mod json;
 mod mutex_map;
+mod overlay_fs;
 mod read_glob;
 mod retry;
 pub mod rope;
@@ -25,6 +27,7 @@ use std::{
     borrow::Cow,
     cmp::min,
     collections::HashSet,
+    ffi::OsString,
     fmt::{
         Debug, Display, Formatter, {self},
     },
@@ -34,24 +37,30 @@ use std::{
     },
     mem::take,
     path::{Path, PathBuf, MAIN_SEPARATOR},
+    pin::pin,
     sync::Arc,
 };
 
 use anyhow::{anyhow, bail, Context, Result};
 use auto_hash_map::AutoMap;
 use bitflags::bitflags;
+use bytes::{Bytes, BytesMut};
 use dunce::simplified;
+use futures::{Stream, StreamExt, TryStreamExt};
 use glob::Glob;
+pub use hash_cache::ContentHashAlgorithm;
+use hash_cache::{FileStamp, HashCache};
 use invalidator_map::InvalidatorMap;
 use jsonc_parser::{parse_to_serde_value, ParseOptions};
 use mime::Mime;
+pub use overlay_fs::OverlayFileSystem;
 use read_glob::read_glob;
 pub use read_glob::ReadGlobResult;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use tokio::{
     fs,
-    io::{AsyncBufReadExt, AsyncReadExt, BufReader},
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
     sync::{RwLock, RwLockReadGuard},
 };
 use tracing::Instrument;
@@ -59,7 +68,9 @@ use turbo_tasks::{
     mark_stateful, trace::TraceRawVcs, Completion, InvalidationReason, Invalidator, ReadRef,
     ValueToString, Vc,
 };
-use turbo_tasks_hash::{hash_xxh3_hash64, DeterministicHash, DeterministicHasher};
+use turbo_tasks_hash::{
+    hash_xxh3_hash64, DeterministicHash, DeterministicHasher, Xxh3Hash64Hasher,
+};
 use util::{extract_disk_access, join_path, normalize_path, sys_to_unix, unix_to_sys};
 pub use virtual_fs::VirtualFileSystem;
 use watcher::DiskWatcher;
@@ -92,6 +103,12 @@ pub trait FileSystem: ValueToString {
         target: Vc<LinkContent>,
     ) -> Vc<Completion>;
     fn metadata(self: Vc<Self>, fs_path: Vc<FileSystemPath>) -> Vc<FileMeta>;
+    /// Returns `fs_path` with the casing the entries have on the file system.
+    /// File systems that compare paths case-sensitively return `fs_path`
+    /// unchanged.
+    fn canonical_case(self: Vc<Self>, fs_path: Vc<FileSystemPath>) -> Vc<FileSystemPath> {
+        fs_path
+    }
 }
 
 #[turbo_tasks::value(cell = "new", eq = "manual")]
@@ -112,6 +129,11 @@ pub struct DiskFileSystem {
     invalidation_lock: Arc<RwLock<()>>,
     #[turbo_tasks(debug_ignore, trace_ignore)]
     watcher: Arc<DiskWatcher>,
+    content_hash_algorithm: ContentHashAlgorithm,
+    trust_file_stamps: bool,
+    #[turbo_tasks(debug_ignore, trace_ignore)]
+    #[serde(skip)]
+    hash_cache: Arc<HashCache>,
 }
 
 impl DiskFileSystem {
@@ -123,8 +145,12 @@ impl DiskFileSystem {
     /// registers the path as an invalidator for the current task,
     /// has to be called within a turbo-tasks function
     fn register_invalidator(&self, path: &Path) -> Result<()> {
+        if self.watcher.is_ignored(path, self.root_path()) {
+            return Ok(());
+        }
         let invalidator = turbo_tasks::get_invalidator();
-        self.invalidator_map.insert(path_to_key(path), invalidator);
+        self.invalidator_map
+            .insert(self.watcher.path_to_key(path), invalidator);
         #[cfg(not(any(target_os = "macos", target_os = "windows")))]
         if let Some(dir) = path.parent() {
             self.watcher.ensure_watching(dir, self.root_path())?;
@@ -138,7 +164,8 @@ impl DiskFileSystem {
     fn register_sole_invalidator(&self, path: &Path) -> Result<HashSet<Invalidator>> {
         let invalidator = turbo_tasks::get_invalidator();
         let mut invalidator_map = self.invalidator_map.lock().unwrap();
-        let old_invalidators = invalidator_map.insert(path_to_key(path), [invalidator].into());
+        let old_invalidators =
+            invalidator_map.insert(self.watcher.path_to_key(path), [invalidator].into());
         #[cfg(not(any(target_os = "macos", target_os = "windows")))]
         if let Some(dir) = path.parent() {
             self.watcher.ensure_watching(dir, self.root_path())?;
@@ -149,9 +176,12 @@ impl DiskFileSystem {
     /// registers the path as an invalidator for the current task,
     /// has to be called within a turbo-tasks function
     fn register_dir_invalidator(&self, path: &Path) -> Result<()> {
+        if self.watcher.is_ignored(path, self.root_path()) {
+            return Ok(());
+        }
         let invalidator = turbo_tasks::get_invalidator();
         self.dir_invalidator_map
-            .insert(path_to_key(path), invalidator);
+            .insert(self.watcher.path_to_key(path), invalidator);
         #[cfg(not(any(target_os = "macos", target_os = "windows")))]
         self.watcher.ensure_watching(path, self.root_path())?;
         Ok(())
@@ -229,6 +259,122 @@ impl DiskFileSystem {
         })
     }
 
+    /// Opens the file at `full_path` for reading. Returns `None` when the file
+    /// doesn't exist.
+    async fn open_file(&self, full_path: &Path) -> Result<Option<fs::File>> {
+        match retry_future(|| fs::File::open(full_path)).await {
+            Ok(file) => Ok(Some(file)),
+            Err(e) if e.kind() == ErrorKind::NotFound || e.kind() == ErrorKind::InvalidFilename => {
+                Ok(None)
+            }
+            Err(e) => Err(anyhow!(e).context(format!("reading file {}", full_path.display()))),
+        }
+    }
+
+    /// Reads the file at `fs_path` in chunks of up to `chunk_size` bytes, so
+    /// large files never need to be held in memory at once. Returns `None`
+    /// when the file doesn't exist.
+    ///
+    /// The current task is invalidated when the file changes, so this has to
+    /// be called within a turbo-tasks function.
+    pub async fn read_chunks(
+        &self,
+        fs_path: Vc<FileSystemPath>,
+        chunk_size: usize,
+    ) -> Result<Option<impl Stream<Item = io::Result<Bytes>> + Send + 'static>> {
+        let full_path = self.to_sys_path(fs_path).await?;
+        self.register_invalidator(&full_path)?;
+        let file = self.open_file(&full_path).await?;
+        Ok(file.map(|file| read_chunks(file, chunk_size)))
+    }
+
+    /// Writes the chunks of `stream` to the file at `fs_path` without
+    /// collecting them in memory first. Readers of the file are invalidated.
+    ///
+    /// The chunks are written to a temporary file next to the target, which
+    /// replaces the target only once the stream is complete. When the stream
+    /// or a write fails, the file at `fs_path` is left untouched.
+    ///
+    /// Unlike [FileSystem::write] this is not a turbo-tasks function. The file
+    /// is written immediately and the write isn't cached.
+    pub async fn write_stream<E>(
+        &self,
+        fs_path: Vc<FileSystemPath>,
+        stream: impl Stream<Item = Result<Bytes, E>>,
+    ) -> Result<()>
+    where
+        anyhow::Error: From<E>,
+    {
+        let full_path = self.to_sys_path(fs_path).await?;
+        let _lock = self.lock_path(&full_path).await;
+        if let Some(parent) = full_path.parent() {
+            retry_future(|| fs::create_dir_all(parent))
+                .await
+                .with_context(|| {
+                    format!(
+                        "failed to create directory {} for write to {}",
+                        parent.display(),
+                        full_path.display()
+                    )
+                })?;
+        }
+        let mut temp_name = OsString::from(".");
+        temp_name.push(full_path.file_name().context("path has no file name")?);
+        temp_name.push(format!(".{}.tmp", std::process::id()));
+        let temp_path = full_path.with_file_name(temp_name);
+        let written = async {
+            let mut file = retry_future(|| fs::File::create(&temp_path))
+                .await
+                .with_context(|| format!("failed to create file {}", temp_path.display()))?;
+            let mut stream = pin!(stream);
+            while let Some(chunk) = stream.next().await {
+                file.write_all(&chunk?)
+                    .await
+                    .with_context(|| format!("failed to write to {}", temp_path.display()))?;
+            }
+            file.flush().await?;
+            drop(file);
+            retry_future(|| fs::rename(&temp_path, &full_path))
+                .await
+                .with_context(|| format!("failed to write to {}", full_path.display()))
+        }
+        .await;
+        if written.is_err() {
+            let _ = fs::remove_file(&temp_path).await;
+        }
+        written?;
+
+        let invalidators = self
+            .invalidator_map
+            .lock()
+            .unwrap()
+            .remove(&self.watcher.path_to_key(&full_path))
+            .unwrap_or_default();
+        self.invalidate_from_write(&full_path, invalidators);
+        Ok(())
+    }
+
+    /// Adds the content hashes saved with [DiskFileSystem::save_hash_cache]
+    /// to the cache used by [DiskFileSystem::content_hash]. Call this before
+    /// hashing any files. A missing or outdated cache file and a cache of
+    /// another [ContentHashAlgorithm] are ignored. Returns the number of
+    /// loaded hashes.
+    pub fn load_hash_cache(&self, path: &Path) -> Result<usize> {
+        self.hash_cache.load(path, self.content_hash_algorithm)
+    }
+
+    /// Writes the content hashes cached by [DiskFileSystem::content_hash] to
+    /// `path`. Returns the number of saved hashes.
+    pub fn save_hash_cache(&self, path: &Path) -> Result<usize> {
+        self.hash_cache.save(path, self.content_hash_algorithm)
+    }
+
+    /// Forgets all cached content hashes, e.g. after files were modified in a
+    /// way that doesn't change their modification times.
+    pub fn clear_hash_cache(&self) {
+        self.hash_cache.clear();
+    }
+
     fn invalidate_from_write(&self, full_path: &Path, invalidators: HashSet<Invalidator>) {
         if !invalidators.is_empty() {
             if let Some(path) = format_absolute_fs_path(full_path, &self.name, self.root_path()) {
@@ -269,6 +415,25 @@ fn format_absolute_fs_path(path: &Path, name: &str, root_path: &Path) -> Option<
     path
 }
 
+/// The size of the chunks large files are read in.
+pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Reads `file` in chunks of up to `chunk_size` bytes.
+fn read_chunks(file: fs::File, chunk_size: usize) -> impl Stream<Item = io::Result<Bytes>> {
+    futures::stream::try_unfold(file, move |mut file| async move {
+        let mut chunk = BytesMut::with_capacity(chunk_size);
+        while chunk.len() < chunk_size {
+            if file.read_buf(&mut chunk).await? == 0 {
+                break;
+            }
+        }
+        if chunk.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some((chunk.freeze(), file)))
+    })
+}
+
 pub fn path_to_key(path: impl AsRef<Path>) -> String {
     path.as_ref().to_string_lossy().to_string()
 }
@@ -285,12 +450,52 @@ impl DiskFileSystem {
     ///   root & project dir is different and requires to ignore specific
     ///   subpaths from each.
     #[turbo_tasks::function]
-    pub async fn new(
+    pub fn new(name: String, root: String, ignored_subpaths: Vec<String>) -> Vc<Self> {
+        Self::new_with_ignore_globs(name, root, ignored_subpaths, Vec::new())
+    }
+
+    /// Create a new instance of `DiskFileSystem` that excludes paths matching
+    /// `ignore_globs`, e.g. `**/node_modules` or `.git`.
+    ///
+    /// The globs are matched against paths relative to the root. A path is
+    /// excluded when it or one of its parent directories matches. Excluded
+    /// paths are not watched and reads of them are never invalidated, so they
+    /// are treated as immutable.
+    #[turbo_tasks::function]
+    pub fn new_with_ignore_globs(
         name: String,
         root: String,
         ignored_subpaths: Vec<String>,
+        ignore_globs: Vec<String>,
+    ) -> Vc<Self> {
+        Self::new_with_options(
+            name,
+            root,
+            ignored_subpaths,
+            DiskFileSystemOptions {
+                ignore_globs,
+                ..Default::default()
+            }
+            .cell(),
+        )
+    }
+
+    /// Create a new instance of `DiskFileSystem` configured by `options`. See
+    /// [DiskFileSystemOptions] for the available options.
+    #[turbo_tasks::function]
+    pub async fn new_with_options(
+        name: String,
+        root: String,
+        ignored_subpaths: Vec<String>,
+        options: Vc<DiskFileSystemOptions>,
     ) -> Result<Vc<Self>> {
         mark_stateful();
+        let options = options.await?;
+        let ignore_globs = options
+            .ignore_globs
+            .iter()
+            .map(|glob| Glob::parse(glob))
+            .collect::<Result<Vec<_>>>()?;
         // create the directory for the filesystem on disk, if it doesn't exist
         fs::create_dir_all(&root).await?;
 
@@ -303,11 +508,118 @@ impl DiskFileSystem {
             dir_invalidator_map: Arc::new(InvalidatorMap::new()),
             watcher: Arc::new(DiskWatcher::new(
                 ignored_subpaths.into_iter().map(PathBuf::from).collect(),
+                ignore_globs,
+                options.case_insensitive,
             )),
+            content_hash_algorithm: options.content_hash_algorithm,
+            trust_file_stamps: options.trust_file_stamps,
+            hash_cache: Default::default(),
         };
 
         Ok(Self::cell(instance))
     }
+
+    /// Hashes the content of the file at `fs_path` while reading it in
+    /// chunks, so large files never need to be held in memory at once. With
+    /// [ContentHashAlgorithm::Xxh3] the hash equals the hash of the file's
+    /// [Rope] content. `None` when the file doesn't exist.
+    ///
+    /// With [DiskFileSystemOptions::trust_file_stamps] the file isn't read
+    /// when its modification time, size and inode are unchanged since it was
+    /// hashed.
+    #[turbo_tasks::function]
+    pub async fn content_hash(&self, fs_path: Vc<FileSystemPath>) -> Result<Vc<ContentHashOption>> {
+        let full_path = self.to_sys_path(fs_path).await?;
+        self.register_invalidator(&full_path)?;
+        let fs_path = fs_path.await?;
+        let key = &fs_path.path;
+
+        let _lock = self.lock_path(&full_path).await;
+        let meta = match retry_future(|| fs::metadata(&full_path)).await {
+            Ok(meta) => meta,
+            Err(e) if e.kind() == ErrorKind::NotFound || e.kind() == ErrorKind::InvalidFilename => {
+                self.hash_cache.remove(key);
+                return Ok(Vc::cell(None));
+            }
+            Err(e) => {
+                bail!(anyhow!(e).context(format!("reading metadata for {}", full_path.display())))
+            }
+        };
+        let stamp = FileStamp::new(&meta);
+        if let (true, Some(stamp)) = (self.trust_file_stamps, stamp) {
+            if let Some(hash) = self.hash_cache.get(key, stamp) {
+                return Ok(Vc::cell(Some(hash)));
+            }
+        }
+
+        let Some(file) = self.open_file(&full_path).await? else {
+            self.hash_cache.remove(key);
+            return Ok(Vc::cell(None));
+        };
+        let chunks = read_chunks(file, DEFAULT_CHUNK_SIZE)
+            .map_err(|e| anyhow!(e).context(format!("reading file {}", full_path.display())));
+        let hash = hash_chunks(self.content_hash_algorithm, meta.len(), chunks).await?;
+        match stamp {
+            Some(stamp) => self.hash_cache.insert(key.clone(), stamp, hash),
+            None => self.hash_cache.remove(key),
+        }
+        Ok(Vc::cell(Some(hash)))
+    }
+}
+
+/// Hashes a file of `len` bytes that is read in `chunks`.
+async fn hash_chunks(
+    algorithm: ContentHashAlgorithm,
+    len: u64,
+    chunks: impl Stream<Item = Result<Bytes>>,
+) -> Result<u64> {
+    let mut chunks = pin!(chunks);
+    Ok(match algorithm {
+        ContentHashAlgorithm::Xxh3 => {
+            // Matches the hash of a Rope, which starts with its length
+            let mut hasher = Xxh3Hash64Hasher::new();
+            hasher.write_usize(len as usize);
+            while let Some(chunk) = chunks.try_next().await? {
+                hasher.write_bytes(&chunk);
+            }
+            hasher.finish()
+        }
+        ContentHashAlgorithm::Blake3 => {
+            let mut hasher = blake3::Hasher::new();
+            while let Some(chunk) = chunks.try_next().await? {
+                hasher.update(&chunk);
+            }
+            let digest = hasher.finalize();
+            u64::from_le_bytes(digest.as_bytes()[..8].try_into().unwrap())
+        }
+    })
+}
+
+#[turbo_tasks::value(transparent)]
+pub struct ContentHashOption(Option<u64>);
+
+/// Options for [DiskFileSystem::new_with_options].
+#[turbo_tasks::value(shared)]
+#[derive(Clone, Debug, Default)]
+pub struct DiskFileSystemOptions {
+    /// Globs of paths that are excluded, see
+    /// [DiskFileSystem::new_with_ignore_globs].
+    pub ignore_globs: Vec<String>,
+    /// Treat paths that only differ in case as the same path, like the
+    /// default file systems of macOS and Windows do.
+    ///
+    /// Reads are invalidated by changes to the path in any casing, and
+    /// [FileSystemPath::canonical_case] maps paths to the casing they have
+    /// on disk, so e.g. case-mismatched imports can share one cache entry.
+    pub case_insensitive: bool,
+    /// The hash function of [DiskFileSystem::content_hash].
+    pub content_hash_algorithm: ContentHashAlgorithm,
+    /// Reuse content hashes of files whose modification time, size and inode
+    /// are unchanged since they were hashed, e.g. from a cache loaded with
+    /// [DiskFileSystem::load_hash_cache]. This speeds up cold scans of large
+    /// trees, but misses changes by tools that restore modification times.
+    /// Files modified within the last seconds are always hashed.
+    pub trust_file_stamps: bool,
 }
 
 impl Debug for DiskFileSystem {
@@ -524,7 +836,7 @@ impl FileSystem for DiskFileSystem {
             .await?;
         if compare == FileComparison::Equal {
             if !old_invalidators.is_empty() {
-                let key = path_to_key(&full_path);
+                let key = self.watcher.path_to_key(&full_path);
                 for i in old_invalidators {
                     self.invalidator_map.insert(key.clone(), i);
                 }
@@ -713,6 +1025,33 @@ impl FileSystem for DiskFileSystem {
 
         Ok(FileMeta::cell(meta.into()))
     }
+
+    #[turbo_tasks::function]
+    async fn canonical_case(&self, fs_path: Vc<FileSystemPath>) -> Result<Vc<FileSystemPath>> {
+        if !self.watcher.is_case_insensitive() {
+            return Ok(fs_path);
+        }
+        let this = fs_path.await?;
+        if this.is_root() {
+            return Ok(fs_path);
+        }
+        let parent = fs_path.parent().canonical_case();
+        let name = this.file_name();
+        // Prefer an exact match, there can be multiple entries that only
+        // differ in case on case-sensitive volumes
+        if let DirectoryContent::Entries(entries) = &*parent.read_dir().await? {
+            if !entries.contains_key(name) {
+                let lowercase_name = name.to_lowercase();
+                if let Some(entry_name) = entries
+                    .keys()
+                    .find(|entry_name| entry_name.to_lowercase() == lowercase_name)
+                {
+                    return Ok(parent.join(entry_name.clone()));
+                }
+            }
+        }
+        Ok(parent.join(name.to_string()))
+    }
 }
 
 #[turbo_tasks::value_impl]
@@ -1146,6 +1485,12 @@ impl FileSystemPath {
         self.realpath_with_links().path()
     }
 
+    /// Returns the path with the casing it has on the file system. See
+    /// [FileSystem::canonical_case].
+    pub fn canonical_case(self: Vc<Self>) -> Vc<FileSystemPath> {
+        self.fs().canonical_case(self)
+    }
+
     pub fn rebase(
         fs_path: Vc<FileSystemPath>,
         old_base: Vc<FileSystemPath>,
@@ -1470,6 +1815,12 @@ impl File {
         }
     }
 
+    /// Creates a [File] from the chunks of a stream. The chunks are kept in
+    /// a [Rope] instead of being copied into one contiguous buffer.
+    pub async fn from_stream<E>(stream: impl Stream<Item = Result<Bytes, E>>) -> Result<Self, E> {
+        Ok(File::from_rope(Rope::from_stream(stream).await?))
+    }
+
     /// Returns the content type associated with this file.
     pub fn content_type(&self) -> Option<&Mime> {
         self.meta.content_type.as_ref()
@@ -2008,4 +2359,184 @@ mod tests {
         .await
         .unwrap()
     }
+
+    #[tokio::test]
+    async fn canonical_case() {
+        crate::register();
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("Foo")).unwrap();
+        std::fs::write(dir.path().join("Foo/Bar.txt"), "bar").unwrap();
+        let root = dir.path().to_string_lossy().to_string();
+
+        let tt = turbo_tasks::TurboTasks::new(turbo_tasks_memory::MemoryBackend::default());
+        tt.run_once(async move {
+            let options = DiskFileSystemOptions {
+                case_insensitive: true,
+                ..Default::default()
+            };
+            let fs = DiskFileSystem::new_with_options(
+                "disk".to_string(),
+                root.clone(),
+                Vec::new(),
+                options.cell(),
+            );
+            let fs_root = Vc::upcast::<Box<dyn FileSystem>>(fs).root();
+
+            let path = fs_root.join("foo/bar.TXT".to_string()).canonical_case();
+            assert_eq!(path.await?.path, "Foo/Bar.txt");
+            assert_eq!(
+                path,
+                fs_root.join("FOO/Bar.txt".to_string()).canonical_case()
+            );
+            // Missing entries keep their casing
+            let path = fs_root.join("foo/Missing".to_string()).canonical_case();
+            assert_eq!(path.await?.path, "Foo/Missing");
+
+            // Case-sensitive file systems don't change paths
+            let fs = DiskFileSystem::new("sensitive".to_string(), root, Vec::new());
+            let fs_root = Vc::upcast::<Box<dyn FileSystem>>(fs).root();
+            let path = fs_root.join("foo/bar.TXT".to_string()).canonical_case();
+            assert_eq!(path.await?.path, "foo/bar.TXT");
+
+            anyhow::Ok(())
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn streaming() {
+        crate::register();
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_string_lossy().to_string();
+
+        let tt = turbo_tasks::TurboTasks::new(turbo_tasks_memory::MemoryBackend::default());
+        tt.run_once(async move {
+            let fs = DiskFileSystem::new("disk".to_string(), root, Vec::new());
+            let fs_root = Vc::upcast::<Box<dyn FileSystem>>(fs).root();
+            let path = fs_root.join("dir/large.bin".to_string());
+            assert_eq!(*fs.content_hash(path).await?, None);
+
+            let chunks = (0..10u8).map(|i| Ok::<_, io::Error>(Bytes::from(vec![i; 1000])));
+            fs.await?
+                .write_stream(path, futures::stream::iter(chunks))
+                .await?;
+
+            let stream = fs.await?.read_chunks(path, 4096).await?.unwrap();
+            let chunks = stream.try_collect::<Vec<_>>().await?;
+            assert_eq!(chunks.iter().map(|chunk| chunk.len()).sum::<usize>(), 10000);
+            assert!(chunks.iter().all(|chunk| chunk.len() <= 4096));
+
+            let file = File::from_stream(futures::stream::iter(
+                chunks.into_iter().map(Ok::<_, io::Error>),
+            ))
+            .await?;
+            assert_eq!(
+                *fs.content_hash(path).strongly_consistent().await?,
+                Some(hash_xxh3_hash64(file.content()))
+            );
+
+            anyhow::Ok(())
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn streaming_error() {
+        crate::register();
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("large.bin");
+        std::fs::write(&file_path, "previous").unwrap();
+        let root = dir.path().to_string_lossy().to_string();
+
+        let tt = turbo_tasks::TurboTasks::new(turbo_tasks_memory::MemoryBackend::default());
+        tt.run_once(async move {
+            let fs = DiskFileSystem::new("disk".to_string(), root, Vec::new());
+            let fs_root = Vc::upcast::<Box<dyn FileSystem>>(fs).root();
+            let path = fs_root.join("large.bin".to_string());
+
+            let chunks = vec![
+                Ok(Bytes::from(vec![0; 1000])),
+                Err(io::Error::new(ErrorKind::Other, "stream failed")),
+            ];
+            let result = fs
+                .await?
+                .write_stream(path, futures::stream::iter(chunks))
+                .await;
+            assert!(result.is_err());
+
+            anyhow::Ok(())
+        })
+        .await
+        .unwrap();
+
+        // The previous content is kept and the temporary file is removed
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "previous");
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn hash_cache() {
+        crate::register();
+        let dir = tempfile::tempdir().unwrap();
+        let file_path = dir.path().join("root/a.txt");
+        let cache_path = dir.path().join("hash-cache.json");
+        let root = dir.path().join("root").to_string_lossy().to_string();
+        std::fs::create_dir(&root).unwrap();
+        let modified = std::time::SystemTime::now() - std::time::Duration::from_secs(3600);
+        let write = move |content: &str| {
+            std::fs::write(&file_path, content).unwrap();
+            let file = std::fs::File::options()
+                .write(true)
+                .open(&file_path)
+                .unwrap();
+            file.set_modified(modified).unwrap();
+        };
+        write("aaa");
+
+        let tt = turbo_tasks::TurboTasks::new(turbo_tasks_memory::MemoryBackend::default());
+        tt.run_once(async move {
+            let disk_fs = |name: &str, trust_file_stamps: bool| {
+                let options = DiskFileSystemOptions {
+                    content_hash_algorithm: ContentHashAlgorithm::Blake3,
+                    trust_file_stamps,
+                    ..Default::default()
+                };
+                DiskFileSystem::new_with_options(
+                    name.to_string(),
+                    root.clone(),
+                    Vec::new(),
+                    options.cell(),
+                )
+            };
+            // The hash is only requested once the cache is loaded, as the cache is
+            // meant to be loaded before hashing any files
+            let hash = |fs: Vc<DiskFileSystem>| {
+                let path = Vc::upcast::<Box<dyn FileSystem>>(fs)
+                    .root()
+                    .join("a.txt".to_string());
+                fs.content_hash(path)
+            };
+
+            let fs = disk_fs("first", true);
+            let original_hash = hash(fs).await?.unwrap();
+            assert_eq!(fs.await?.save_hash_cache(&cache_path)?, 1);
+
+            // A change that keeps the modification time and size is only
+            // noticed when file stamps aren't trusted
+            write("bbb");
+            let fs = disk_fs("second", true);
+            assert_eq!(fs.await?.load_hash_cache(&cache_path)?, 1);
+            assert_eq!(*hash(fs).await?, Some(original_hash));
+
+            let fs = disk_fs("third", false);
+            assert_eq!(fs.await?.load_hash_cache(&cache_path)?, 1);
+            assert_ne!(*hash(fs).await?, Some(original_hash));
+
+            anyhow::Ok(())
+        })
+        .await
+        .unwrap()
+    }
 }
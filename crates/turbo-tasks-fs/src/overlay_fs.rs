@@ -0,0 +1,406 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Debug, Formatter},
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{bail, Result};
+use auto_hash_map::AutoMap;
+use turbo_tasks::{Completion, ValueToString, Vc};
+
+use crate::{
+    invalidator_map::InvalidatorMap, DirectoryContent, DirectoryEntry, File, FileContent, FileMeta,
+    FileSystem, FileSystemPath, LinkContent,
+};
+
+/// An entry of the overlay that hides the file of the base file system at
+/// the same path.
+#[derive(Clone, PartialEq)]
+enum OverlayEntry {
+    File(File),
+    Removed,
+}
+
+/// A [FileSystem] that layers in-memory files over a base [FileSystem]. Reads
+/// see the overlay files first and fall back to the base file system, and
+/// writes only go to the overlay, so nothing is written to the base.
+///
+/// Changing an overlay file invalidates the tasks that read it, as well as
+/// the tasks that read the directories containing it. This allows "what-if"
+/// compilations and tests that inject files without touching the disk.
+#[turbo_tasks::value(serialization = "none", cell = "new", eq = "manual")]
+pub struct OverlayFileSystem {
+    name: String,
+    base: Vc<Box<dyn FileSystem>>,
+    /// Overlay entries by their path.
+    #[turbo_tasks(debug_ignore, trace_ignore)]
+    overlay: Arc<Mutex<HashMap<String, OverlayEntry>>>,
+    #[turbo_tasks(debug_ignore, trace_ignore)]
+    invalidator_map: Arc<InvalidatorMap>,
+    #[turbo_tasks(debug_ignore, trace_ignore)]
+    dir_invalidator_map: Arc<InvalidatorMap>,
+}
+
+impl OverlayFileSystem {
+    /// Creates a new [`Vc<OverlayFileSystem>`] over `base`.
+    ///
+    /// NOTE: This function is not a `turbo_tasks::function`, so every call
+    /// creates a separate overlay.
+    pub fn new(name: String, base: Vc<Box<dyn FileSystem>>) -> Vc<Self> {
+        Self::cell(OverlayFileSystem {
+            name,
+            base,
+            overlay: Default::default(),
+            invalidator_map: Arc::new(InvalidatorMap::new()),
+            dir_invalidator_map: Arc::new(InvalidatorMap::new()),
+        })
+    }
+
+    /// Sets the content of the file at `path`, relative to the root, hiding
+    /// the file of the base file system.
+    pub fn set_file(&self, path: &str, file: File) {
+        self.set_entry(path, Some(OverlayEntry::File(file)));
+    }
+
+    /// Hides the file of the base file system at `path`, so it reads as not
+    /// found.
+    pub fn remove_file(&self, path: &str) {
+        self.set_entry(path, Some(OverlayEntry::Removed));
+    }
+
+    /// Removes the overlay at `path`, so the file of the base file system is
+    /// visible again.
+    pub fn reset_file(&self, path: &str) {
+        self.set_entry(path, None);
+    }
+
+    /// Removes all overlay files.
+    pub fn clear(&self) {
+        let paths = self
+            .overlay
+            .lock()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        for path in paths {
+            self.reset_file(&path);
+        }
+    }
+
+    fn entry(&self, path: &str) -> Option<OverlayEntry> {
+        self.overlay.lock().unwrap().get(path).cloned()
+    }
+
+    fn set_entry(&self, path: &str, entry: Option<OverlayEntry>) {
+        let path = path.trim_matches('/');
+        let changed = {
+            let mut overlay = self.overlay.lock().unwrap();
+            match entry {
+                Some(entry) => {
+                    let changed = overlay.get(path) != Some(&entry);
+                    if changed {
+                        overlay.insert(path.to_string(), entry);
+                    }
+                    changed
+                }
+                None => overlay.remove(path).is_some(),
+            }
+        };
+        if changed {
+            self.invalidate_path(path);
+        }
+    }
+
+    /// Invalidates the readers of `path` and of all directories containing
+    /// it, as the entry might create or remove directories.
+    fn invalidate_path(&self, path: &str) {
+        let invalidators = self.invalidator_map.lock().unwrap().remove(path);
+        invalidators
+            .into_iter()
+            .flatten()
+            .for_each(|invalidator| invalidator.invalidate());
+        let mut dir_invalidator_map = self.dir_invalidator_map.lock().unwrap();
+        let dirs = path
+            .match_indices('/')
+            .map(|(index, _)| &path[..index])
+            .chain([""]);
+        for dir in dirs {
+            if let Some(invalidators) = dir_invalidator_map.remove(dir) {
+                invalidators
+                    .into_iter()
+                    .for_each(|invalidator| invalidator.invalidate());
+            }
+        }
+    }
+
+    /// Registers the current task as reader of `path`. Has to be called
+    /// within a turbo-tasks function.
+    fn register_invalidator(&self, path: &str) {
+        self.invalidator_map
+            .insert(path.to_string(), turbo_tasks::get_invalidator());
+    }
+
+    /// Registers the current task as reader of the directory `path`. Has to
+    /// be called within a turbo-tasks function.
+    fn register_dir_invalidator(&self, path: &str) {
+        self.dir_invalidator_map
+            .insert(path.to_string(), turbo_tasks::get_invalidator());
+    }
+
+    /// The overlay entries directly within the directory `path`. Directories
+    /// that only exist because of deeper overlay files are listed as `None`.
+    fn dir_entries(&self, path: &str) -> HashMap<String, Option<OverlayEntry>> {
+        let mut entries = HashMap::new();
+        for (entry_path, entry) in self.overlay.lock().unwrap().iter() {
+            let relative = if path.is_empty() {
+                entry_path.as_str()
+            } else if let Some(relative) = entry_path
+                .strip_prefix(path)
+                .and_then(|relative| relative.strip_prefix('/'))
+            {
+                relative
+            } else {
+                continue;
+            };
+            match relative.split_once('/') {
+                Some((dir, _)) => {
+                    if !matches!(entry, OverlayEntry::Removed) {
+                        entries.entry(dir.to_string()).or_insert(None);
+                    }
+                }
+                None => {
+                    entries.insert(relative.to_string(), Some(entry.clone()));
+                }
+            }
+        }
+        entries
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl OverlayFileSystem {
+    /// The path on the base file system that corresponds to `path` on this
+    /// file system.
+    #[turbo_tasks::function]
+    async fn base_path(self: Vc<Self>, path: Vc<FileSystemPath>) -> Result<Vc<FileSystemPath>> {
+        let this = self.await?;
+        let path = path.await?;
+        let self_fs: Vc<Box<dyn FileSystem>> = Vc::upcast(self);
+        if path.fs != self_fs {
+            bail!(
+                "path fs does not match (expected {}, got {})",
+                self_fs.to_string().await?,
+                path.fs.to_string().await?
+            )
+        }
+        Ok(this.base.root().join(path.path.clone()))
+    }
+}
+
+impl Debug for OverlayFileSystem {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "name: {}", self.name)
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl FileSystem for OverlayFileSystem {
+    #[turbo_tasks::function]
+    async fn read(self: Vc<Self>, path: Vc<FileSystemPath>) -> Result<Vc<FileContent>> {
+        let this = self.await?;
+        let path_value = path.await?;
+        this.register_invalidator(&path_value.path);
+        Ok(match this.entry(&path_value.path) {
+            Some(OverlayEntry::File(file)) => FileContent::Content(file).cell(),
+            Some(OverlayEntry::Removed) => FileContent::NotFound.cell(),
+            None => self.base_path(path).read(),
+        })
+    }
+
+    #[turbo_tasks::function]
+    async fn read_link(self: Vc<Self>, path: Vc<FileSystemPath>) -> Result<Vc<LinkContent>> {
+        let this = self.await?;
+        let path_value = path.await?;
+        this.register_invalidator(&path_value.path);
+        Ok(match this.entry(&path_value.path) {
+            Some(OverlayEntry::File(_)) => LinkContent::Invalid.cell(),
+            Some(OverlayEntry::Removed) => LinkContent::NotFound.cell(),
+            None => self.base_path(path).read_link(),
+        })
+    }
+
+    #[turbo_tasks::function]
+    async fn read_dir(self: Vc<Self>, path: Vc<FileSystemPath>) -> Result<Vc<DirectoryContent>> {
+        let this = self.await?;
+        let path_value = path.await?;
+        this.register_dir_invalidator(&path_value.path);
+        let overlay_entries = this.dir_entries(&path_value.path);
+        let base_content = self.base_path(path).read_dir().await?;
+        let root = self.root();
+
+        let mut entries = AutoMap::new();
+        if let DirectoryContent::Entries(base_entries) = &*base_content {
+            for (name, entry) in base_entries {
+                use DirectoryEntry::*;
+
+                let convert = |base_path: Vc<FileSystemPath>| async move {
+                    Ok::<_, anyhow::Error>(root.join(base_path.await?.path.clone()))
+                };
+                let entry = match *entry {
+                    File(path) => File(convert(path).await?),
+                    Directory(path) => Directory(convert(path).await?),
+                    Symlink(path) => Symlink(convert(path).await?),
+                    Other(path) => Other(convert(path).await?),
+                    Error => Error,
+                };
+                entries.insert(name.clone(), entry);
+            }
+        } else if overlay_entries.is_empty() {
+            return Ok(DirectoryContent::not_found());
+        }
+
+        for (name, entry) in overlay_entries {
+            let entry_path = path.join(name.clone());
+            match entry {
+                Some(OverlayEntry::File(_)) => {
+                    entries.insert(name, DirectoryEntry::File(entry_path));
+                }
+                Some(OverlayEntry::Removed) => {
+                    entries.remove(&name);
+                }
+                None => {
+                    entries
+                        .entry(name)
+                        .or_insert(DirectoryEntry::Directory(entry_path));
+                }
+            }
+        }
+        Ok(DirectoryContent::new(entries))
+    }
+
+    #[turbo_tasks::function]
+    async fn track(self: Vc<Self>, path: Vc<FileSystemPath>) -> Result<Vc<Completion>> {
+        let this = self.await?;
+        let path_value = path.await?;
+        this.register_invalidator(&path_value.path);
+        Ok(match this.entry(&path_value.path) {
+            Some(_) => Completion::new(),
+            None => self.base_path(path).track(),
+        })
+    }
+
+    #[turbo_tasks::function]
+    async fn write(
+        self: Vc<Self>,
+        path: Vc<FileSystemPath>,
+        content: Vc<FileContent>,
+    ) -> Result<Vc<Completion>> {
+        let this = self.await?;
+        let path = path.await?;
+        match &*content.await? {
+            FileContent::Content(file) => this.set_file(&path.path, file.clone()),
+            FileContent::NotFound => this.remove_file(&path.path),
+        }
+        Ok(Completion::new())
+    }
+
+    #[turbo_tasks::function]
+    fn write_link(
+        &self,
+        _path: Vc<FileSystemPath>,
+        _target: Vc<LinkContent>,
+    ) -> Result<Vc<Completion>> {
+        bail!("Writing links is not possible on the overlay file system")
+    }
+
+    #[turbo_tasks::function]
+    async fn metadata(self: Vc<Self>, path: Vc<FileSystemPath>) -> Result<Vc<FileMeta>> {
+        let this = self.await?;
+        let path_value = path.await?;
+        this.register_invalidator(&path_value.path);
+        Ok(match this.entry(&path_value.path) {
+            Some(OverlayEntry::File(file)) => file.meta().clone().cell(),
+            Some(OverlayEntry::Removed) => bail!("{} was removed", path_value.path),
+            None => self.base_path(path).metadata(),
+        })
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl ValueToString for OverlayFileSystem {
+    #[turbo_tasks::function]
+    fn to_string(&self) -> Vc<String> {
+        Vc::cell(self.name.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use turbo_tasks::{TurboTasks, Vc};
+    use turbo_tasks_memory::MemoryBackend;
+
+    use super::OverlayFileSystem;
+    use crate::{
+        DirectoryContent, DirectoryEntry, DiskFileSystem, File, FileContent, FileSystem,
+        FileSystemPath,
+    };
+
+    async fn read(path: Vc<FileSystemPath>) -> Result<Option<String>> {
+        Ok(match &*path.read().strongly_consistent().await? {
+            FileContent::Content(file) => Some(file.content().to_str()?.to_string()),
+            FileContent::NotFound => None,
+        })
+    }
+
+    #[tokio::test]
+    async fn overlay() {
+        crate::register();
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "disk").unwrap();
+        let root = dir.path().to_string_lossy().to_string();
+
+        let tt = TurboTasks::new(MemoryBackend::default());
+        tt.run_once(async move {
+            let disk = DiskFileSystem::new("disk".to_string(), root, Vec::new());
+            let overlay = OverlayFileSystem::new("overlay".to_string(), Vc::upcast(disk));
+            let overlay_root = Vc::upcast::<Box<dyn FileSystem>>(overlay).root();
+            let a = overlay_root.join("a.txt".to_string());
+            assert_eq!(read(a).await?.as_deref(), Some("disk"));
+
+            overlay.await?.set_file("a.txt", File::from("overlay"));
+            assert_eq!(read(a).await?.as_deref(), Some("overlay"));
+
+            overlay.await?.remove_file("a.txt");
+            assert_eq!(read(a).await?, None);
+
+            overlay.await?.reset_file("a.txt");
+            assert_eq!(read(a).await?.as_deref(), Some("disk"));
+
+            // Overlay files create the directories containing them
+            overlay.await?.set_file("b/c.txt", File::from("virtual"));
+            let DirectoryContent::Entries(entries) =
+                &*overlay_root.read_dir().strongly_consistent().await?
+            else {
+                panic!("root not found");
+            };
+            assert!(matches!(
+                entries.get("a.txt"),
+                Some(DirectoryEntry::File(_))
+            ));
+            assert!(matches!(
+                entries.get("b"),
+                Some(DirectoryEntry::Directory(_))
+            ));
+            let c = overlay_root.join("b/c.txt".to_string());
+            assert_eq!(read(c).await?.as_deref(), Some("virtual"));
+
+            // Nothing is written to disk
+            assert!(!dir.path().join("b").exists());
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+}
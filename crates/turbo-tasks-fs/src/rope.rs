@@ -12,7 +12,7 @@ use std::{
 
 use anyhow::{Context, Result};
 use bytes::{Buf, Bytes};
-use futures::Stream;
+use futures::{Stream, StreamExt};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use tokio::io::{AsyncRead, ReadBuf};
 use turbo_tasks_hash::{DeterministicHash, DeterministicHasher};
@@ -114,6 +114,17 @@ impl Rope {
     pub fn to_bytes(&self) -> Result<Cow<'_, [u8]>> {
         self.data.to_bytes(self.length)
     }
+
+    /// Collects the chunks of a stream into a Rope. The chunks are kept as
+    /// they are instead of being copied into one contiguous buffer.
+    pub async fn from_stream<E>(stream: impl Stream<Item = Result<Bytes, E>>) -> Result<Rope, E> {
+        let mut builder = RopeBuilder::default();
+        let mut stream = std::pin::pin!(stream);
+        while let Some(chunk) = stream.next().await {
+            builder.push_chunk(chunk?);
+        }
+        Ok(builder.build())
+    }
 }
 
 impl<T: Into<Bytes>> From<T> for Rope {
@@ -167,6 +178,27 @@ impl RopeBuilder {
         self.committed.push(Local(bytes.into()));
     }
 
+    /// Push a chunk of bytes into the Rope without copying it.
+    ///
+    /// Use this for large chunks of bytes, e.g. read from a file or a socket.
+    /// Small chunks are copied, like with [push_bytes], so they can be merged
+    /// with neighbouring pushes.
+    pub fn push_chunk(&mut self, bytes: Bytes) {
+        if bytes.is_empty() {
+            return;
+        }
+
+        if bytes.len() < mem::size_of::<Bytes>() {
+            return self.uncommitted.push_bytes(&bytes);
+        }
+
+        // We may have pending bytes from a prior push.
+        self.finish();
+
+        self.length += bytes.len();
+        self.committed.push(Local(bytes));
+    }
+
     /// Concatenate another Rope instance into our builder.
     ///
     /// This is much more efficient than pushing actual bytes, since we can
@@ -721,6 +753,7 @@ mod test {
     };
 
     use anyhow::Result;
+    use bytes::Bytes;
 
     use super::{InnerRope, Rope, RopeBuilder, RopeElem};
 
@@ -791,6 +824,31 @@ mod test {
         assert!(reader.next().is_none());
     }
 
+    #[test]
+    fn empty_build_with_empty_chunk_push() {
+        let mut builder = RopeBuilder::default();
+        builder.push_chunk(Bytes::new());
+
+        let empty = builder.build();
+        let mut reader = empty.read();
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn chunks_are_not_copied() {
+        let chunk = Bytes::from("a chunk that is longer than a Bytes");
+        let mut builder = RopeBuilder::default();
+        builder += "a";
+        builder.push_chunk(chunk.clone());
+        builder.push_chunk(Bytes::from("b"));
+
+        let rope = builder.build();
+        assert_eq!(rope.len(), chunk.len() + 2);
+        let chunks = rope.read().collect::<Vec<_>>();
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[1].as_ptr(), chunk.as_ptr());
+    }
+
     #[test]
     fn empty_build_with_empty_concat() {
         let mut builder = RopeBuilder::default();
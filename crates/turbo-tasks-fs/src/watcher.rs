@@ -22,9 +22,11 @@ use turbo_tasks::{spawn_thread, Invalidator};
 
 use crate::{
     format_absolute_fs_path,
+    glob::Glob,
     invalidation::{WatchChange, WatchStart},
     invalidator_map::InvalidatorMap,
     path_to_key,
+    util::sys_to_unix,
 };
 
 #[derive(Default, Serialize, Deserialize)]
@@ -38,6 +40,14 @@ pub(crate) struct DiskWatcher {
     /// invalidate.
     ignored_subpaths: Vec<PathBuf>,
 
+    /// Globs of paths, relative to the root, that are neither watched nor
+    /// invalidated. A path is ignored when it or one of its parent
+    /// directories matches.
+    ignore_globs: Vec<Glob>,
+
+    /// Paths that only differ in case are the same path.
+    case_insensitive: bool,
+
     /// Keeps track of which directories are currently watched. This is only
     /// used on OSs that doesn't support recursive watching.
     #[cfg(not(any(target_os = "macos", target_os = "windows")))]
@@ -46,13 +56,48 @@ pub(crate) struct DiskWatcher {
 }
 
 impl DiskWatcher {
-    pub(crate) fn new(ignored_subpaths: Vec<PathBuf>) -> Self {
+    pub(crate) fn new(
+        ignored_subpaths: Vec<PathBuf>,
+        ignore_globs: Vec<Glob>,
+        case_insensitive: bool,
+    ) -> Self {
         Self {
             ignored_subpaths,
+            ignore_globs,
+            case_insensitive,
             ..Default::default()
         }
     }
 
+    pub(crate) fn is_case_insensitive(&self) -> bool {
+        self.case_insensitive
+    }
+
+    /// The key of `path` in the invalidator maps. Paths that only differ in
+    /// case share a key on case-insensitive file systems.
+    pub(crate) fn path_to_key(&self, path: &Path) -> String {
+        let key = path_to_key(path);
+        if self.case_insensitive {
+            key.to_lowercase()
+        } else {
+            key
+        }
+    }
+
+    /// Whether `path` matches one of the ignore globs.
+    pub(crate) fn is_ignored(&self, path: &Path, root_path: &Path) -> bool {
+        if self.ignore_globs.is_empty() {
+            return false;
+        }
+        let Ok(relative) = path.strip_prefix(root_path) else {
+            return false;
+        };
+        is_ignored_by_globs(
+            &self.ignore_globs,
+            &sys_to_unix(&relative.to_string_lossy()),
+        )
+    }
+
     #[cfg(not(any(target_os = "macos", target_os = "windows")))]
     pub(crate) fn restore_if_watching(&self, dir_path: &Path, root_path: &Path) -> Result<()> {
         if self.watching.contains(dir_path) {
@@ -64,7 +109,7 @@ impl DiskWatcher {
 
     #[cfg(not(any(target_os = "macos", target_os = "windows")))]
     pub(crate) fn ensure_watching(&self, dir_path: &Path, root_path: &Path) -> Result<()> {
-        if self.watching.contains(dir_path) {
+        if self.watching.contains(dir_path) || self.is_ignored(dir_path, root_path) {
             return Ok(());
         }
         let mut watcher = self.watcher.lock().unwrap();
@@ -241,6 +286,7 @@ impl DiskWatcher {
                                             .ignored_subpaths
                                             .iter()
                                             .any(|ignored| p.starts_with(ignored))
+                                            && !self.is_ignored(p, &root_path)
                                     })
                                     .cloned()
                                     .collect();
@@ -399,11 +445,13 @@ impl DiskWatcher {
             {
                 let mut invalidator_map = invalidator_map.lock().unwrap();
                 invalidate_path(
+                    self,
                     &report_invalidation_reason,
                     &mut invalidator_map,
                     batched_invalidate_path.drain(),
                 );
                 invalidate_path_and_children_execute(
+                    self,
                     &report_invalidation_reason,
                     &mut invalidator_map,
                     batched_invalidate_path_and_children.drain(),
@@ -412,11 +460,13 @@ impl DiskWatcher {
             {
                 let mut dir_invalidator_map = dir_invalidator_map.lock().unwrap();
                 invalidate_path(
+                    self,
                     &report_invalidation_reason,
                     &mut dir_invalidator_map,
                     batched_invalidate_path_dir.drain(),
                 );
                 invalidate_path_and_children_execute(
+                    self,
                     &report_invalidation_reason,
                     &mut dir_invalidator_map,
                     batched_invalidate_path_and_children_dir.drain(),
@@ -442,12 +492,13 @@ fn invalidate(
 }
 
 fn invalidate_path(
+    watcher: &DiskWatcher,
     report_invalidation_reason: &Option<(String, PathBuf)>,
     invalidator_map: &mut HashMap<String, HashSet<Invalidator>>,
     paths: impl Iterator<Item = PathBuf>,
 ) {
     for path in paths {
-        let key = path_to_key(&path);
+        let key = watcher.path_to_key(&path);
         if let Some(invalidators) = invalidator_map.remove(&key) {
             invalidators
                 .into_iter()
@@ -457,12 +508,13 @@ fn invalidate_path(
 }
 
 fn invalidate_path_and_children_execute(
+    watcher: &DiskWatcher,
     report_invalidation_reason: &Option<(String, PathBuf)>,
     invalidator_map: &mut HashMap<String, HashSet<Invalidator>>,
     paths: impl Iterator<Item = PathBuf>,
 ) {
     for path in paths {
-        let path_key = path_to_key(&path);
+        let path_key = watcher.path_to_key(&path);
         for (_, invalidators) in invalidator_map.extract_if(|key, _| key.starts_with(&path_key)) {
             invalidators
                 .into_iter()
@@ -470,3 +522,38 @@ fn invalidate_path_and_children_execute(
         }
     }
 }
+
+/// Whether the relative unix `path` or one of its parent directories matches
+/// one of the `globs`.
+pub(crate) fn is_ignored_by_globs(globs: &[Glob], path: &str) -> bool {
+    let path = path.trim_end_matches('/');
+    if path.is_empty() {
+        return false;
+    }
+    path.match_indices('/')
+        .map(|(index, _)| &path[..index])
+        .chain([path])
+        .any(|path| globs.iter().any(|glob| glob.execute(path)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_ignored_by_globs;
+    use crate::glob::Glob;
+
+    #[test]
+    fn ignore_globs() {
+        let globs = ["**/node_modules", ".git", "target/**"]
+            .into_iter()
+            .map(|glob| Glob::parse(glob).unwrap())
+            .collect::<Vec<_>>();
+        assert!(is_ignored_by_globs(&globs, "node_modules"));
+        assert!(is_ignored_by_globs(&globs, "node_modules/react/index.js"));
+        assert!(is_ignored_by_globs(&globs, "packages/a/node_modules/b.js"));
+        assert!(is_ignored_by_globs(&globs, ".git/HEAD"));
+        assert!(is_ignored_by_globs(&globs, "target/debug"));
+        assert!(!is_ignored_by_globs(&globs, ""));
+        assert!(!is_ignored_by_globs(&globs, "src/index.js"));
+        assert!(!is_ignored_by_globs(&globs, "packages/a/.git"));
+    }
+}
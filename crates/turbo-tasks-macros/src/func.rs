@@ -1,7 +1,9 @@
 use proc_macro2::Ident;
+use quote::quote;
 use syn::{
-    parse_quote, punctuated::Punctuated, spanned::Spanned, Block, Expr, ExprPath, FnArg, Pat,
-    PatIdent, PatType, Path, Receiver, ReturnType, Signature, Token, Type,
+    parse_quote, punctuated::Punctuated, spanned::Spanned, Block, Expr, ExprPath, FnArg,
+    GenericParam, Generics, LitInt, Pat, PatIdent, PatType, Path, Receiver, ReturnType, Signature,
+    Token, Type,
 };
 
 #[derive(Debug)]
@@ -9,6 +11,7 @@ pub struct TurboFn {
     // signature: Signature,
     // block: Block,
     ident: Ident,
+    generics: Generics,
     output: Type,
     inputs: Vec<Input>,
 }
@@ -24,7 +27,16 @@ impl TurboFn {
         original_signature: &Signature,
         definition_context: DefinitionContext,
     ) -> Option<TurboFn> {
-        if !original_signature.generics.params.is_empty() {
+        // Naked functions can have type parameters, every instance is
+        // registered as a separate function
+        let supports_generics = definition_context == DefinitionContext::NakedFn
+            && original_signature
+                .generics
+                .params
+                .iter()
+                .all(|param| matches!(param, GenericParam::Type(_)));
+
+        if !supports_generics && !original_signature.generics.params.is_empty() {
             original_signature
                 .generics
                 .span()
@@ -37,7 +49,7 @@ impl TurboFn {
             return None;
         }
 
-        if original_signature.generics.where_clause.is_some() {
+        if !supports_generics && original_signature.generics.where_clause.is_some() {
             original_signature
                 .generics
                 .where_clause
@@ -229,6 +241,7 @@ impl TurboFn {
 
         Some(TurboFn {
             ident: original_signature.ident.clone(),
+            generics: original_signature.generics.clone(),
             output,
             inputs,
         })
@@ -258,9 +271,12 @@ impl TurboFn {
 
         let ident = &self.ident;
         let output = &self.output;
+        let generics = &self.generics;
+        let where_clause = &self.generics.where_clause;
 
         parse_quote! {
-            fn #ident(#exposed_inputs) -> <#output as turbo_tasks::task::TaskOutput>::Return
+            fn #ident #generics(#exposed_inputs) -> <#output as turbo_tasks::task::TaskOutput>::Return
+            #where_clause
         }
     }
 
@@ -302,6 +318,96 @@ impl TurboFn {
         }
     }
 
+    pub fn is_generic(&self) -> bool {
+        !self.generics.params.is_empty()
+    }
+
+    /// The block of the exposed function of a generic function. Every set of
+    /// type arguments is registered as a separate function on first use.
+    pub fn generic_block(
+        &self,
+        inline_function_ident: &Ident,
+        max_concurrency: Option<&LitInt>,
+    ) -> Block {
+        let ident = &self.ident;
+        let max_concurrency = max_concurrency.map(|max_concurrency| {
+            quote! { .with_max_concurrency(#max_concurrency) }
+        });
+        let output = &self.output;
+        let converted_inputs = self.converted_inputs();
+        let type_params: Vec<&Ident> = self
+            .generics
+            .type_params()
+            .map(|param| &param.ident)
+            .collect();
+        parse_quote! {
+            {
+                static INSTANCES: turbo_tasks::macro_helpers::GenericFunctionInstances =
+                    turbo_tasks::macro_helpers::GenericFunctionInstances::new();
+                let function_id = INSTANCES.get::<(#(#type_params,)*)>(
+                    concat!(module_path!(), "::", stringify!(#ident)),
+                    || {
+                        #[allow(deprecated)]
+                        turbo_tasks::NativeFunction::new(
+                            format!(
+                                "{}<{}>",
+                                stringify!(#ident),
+                                std::any::type_name::<(#(#type_params,)*)>(),
+                            ),
+                            #inline_function_ident::<#(#type_params),*>,
+                        )
+                        #max_concurrency
+                    },
+                );
+                <#output as turbo_tasks::task::TaskOutput>::try_from_raw_vc(
+                    turbo_tasks::dynamic_call(function_id, vec![#converted_inputs])
+                )
+            }
+        }
+    }
+
+    /// The signature of the exposed function of a local function. It's async
+    /// and always returns a [Result], as the function is executed by the
+    /// caller.
+    pub fn local_signature(&self) -> Signature {
+        let signature = self.signature();
+        let output = &self.output;
+        let inputs = &signature.inputs;
+        let ident = &self.ident;
+        let generics = &self.generics;
+        let where_clause = &self.generics.where_clause;
+        parse_quote! {
+            async fn #ident #generics(#inputs) -> turbo_tasks::Result<
+                <#output as turbo_tasks::task::TaskOutput>::Return
+            >
+            #where_clause
+        }
+    }
+
+    /// The block of the exposed function of a local function, which executes
+    /// the inline function within the calling task.
+    pub fn local_block(&self, inline_function_ident: &Ident, is_async: bool) -> Block {
+        let output = &self.output;
+        let inputs: Punctuated<&Ident, Token![,]> =
+            self.inputs.iter().map(|input| &input.ident).collect();
+        let type_params: Vec<&Ident> = self
+            .generics
+            .type_params()
+            .map(|param| &param.ident)
+            .collect();
+        let call: Expr = if is_async {
+            parse_quote! { #inline_function_ident::<#(#type_params),*>(#inputs).await }
+        } else {
+            parse_quote! { #inline_function_ident::<#(#type_params),*>(#inputs) }
+        };
+        parse_quote! {
+            {
+                let raw_vc = turbo_tasks::task::TaskOutput::try_into_raw_vc(#call)?;
+                Ok(<#output as turbo_tasks::task::TaskOutput>::try_from_raw_vc(raw_vc))
+            }
+        }
+    }
+
     /// The block of the exposed function for a static dispatch call to the
     /// given native function.
     pub fn static_block(&self, native_function_id_ident: &Ident) -> Block {
@@ -355,6 +461,7 @@ impl DefinitionContext {
 pub struct NativeFn {
     function_path_string: String,
     function_path: ExprPath,
+    max_concurrency: Option<LitInt>,
 }
 
 impl NativeFn {
@@ -362,9 +469,16 @@ impl NativeFn {
         NativeFn {
             function_path_string: function_path_string.to_owned(),
             function_path: function_path.clone(),
+            max_concurrency: None,
         }
     }
 
+    /// Limits how many tasks of the function execute at the same time.
+    pub fn with_max_concurrency(mut self, max_concurrency: Option<LitInt>) -> NativeFn {
+        self.max_concurrency = max_concurrency;
+        self
+    }
+
     pub fn ty(&self) -> Type {
         parse_quote! { turbo_tasks::macro_helpers::Lazy<turbo_tasks::NativeFunction> }
     }
@@ -373,12 +487,17 @@ impl NativeFn {
         let Self {
             function_path_string,
             function_path,
+            max_concurrency,
         } = self;
+        let max_concurrency = max_concurrency.as_ref().map(|max_concurrency| {
+            quote! { .with_max_concurrency(#max_concurrency) }
+        });
 
         parse_quote! {
             turbo_tasks::macro_helpers::Lazy::new(|| {
                 #[allow(deprecated)]
                 turbo_tasks::NativeFunction::new(#function_path_string.to_owned(), #function_path)
+                    #max_concurrency
             })
         }
     }
@@ -1,13 +1,62 @@
 use proc_macro::TokenStream;
 use proc_macro2::Ident;
 use quote::quote;
-use syn::{parse_macro_input, parse_quote, ExprPath, ItemFn};
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input, parse_quote,
+    punctuated::Punctuated,
+    Error, Expr, ExprLit, ExprPath, ItemFn, Lit, LitInt, Meta, Result, Token,
+};
 use turbo_tasks_macros_shared::{get_native_function_id_ident, get_native_function_ident};
 
 use crate::func::{DefinitionContext, NativeFn, TurboFn};
 
-pub fn function(_args: TokenStream, input: TokenStream) -> TokenStream {
+#[derive(Default)]
+struct FunctionArguments {
+    local: bool,
+    max_concurrency: Option<LitInt>,
+}
+
+impl Parse for FunctionArguments {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut result = FunctionArguments::default();
+        let punctuated: Punctuated<Meta, Token![,]> = input.parse_terminated(Meta::parse)?;
+        for meta in punctuated {
+            match &meta {
+                Meta::Path(path) if path.is_ident("local") => {
+                    result.local = true;
+                }
+                Meta::NameValue(name_value) if name_value.path.is_ident("max_concurrency") => {
+                    let Expr::Lit(ExprLit {
+                        lit: Lit::Int(value),
+                        ..
+                    }) = &name_value.value
+                    else {
+                        return Err(Error::new_spanned(
+                            &name_value.value,
+                            "expected an integer literal",
+                        ));
+                    };
+                    if value.base10_parse::<usize>()? == 0 {
+                        return Err(Error::new_spanned(value, "must be at least 1"));
+                    }
+                    result.max_concurrency = Some(value.clone());
+                }
+                _ => {
+                    return Err(Error::new_spanned(
+                        &meta,
+                        "unexpected token, expected: \"local\" or \"max_concurrency\"",
+                    ))
+                }
+            }
+        }
+        Ok(result)
+    }
+}
+
+pub fn function(args: TokenStream, input: TokenStream) -> TokenStream {
     let item = parse_macro_input!(input as ItemFn);
+    let args = parse_macro_input!(args as FunctionArguments);
 
     let ItemFn {
         attrs,
@@ -28,9 +77,51 @@ pub fn function(_args: TokenStream, input: TokenStream) -> TokenStream {
     let inline_function_ident = Ident::new(&format!("{ident}_inline_function"), ident.span());
     let inline_function_path: ExprPath = parse_quote! { #inline_function_ident };
     let mut inline_signature = sig.clone();
-    inline_signature.ident = inline_function_ident;
+    inline_signature.ident = inline_function_ident.clone();
+
+    if args.local {
+        if let Some(max_concurrency) = &args.max_concurrency {
+            return Error::new_spanned(
+                max_concurrency,
+                "local functions are executed by the caller and can't limit their concurrency",
+            )
+            .to_compile_error()
+            .into();
+        }
+        let exposed_signature = turbo_fn.local_signature();
+        let exposed_block = turbo_fn.local_block(&inline_function_ident, sig.asyncness.is_some());
+
+        return quote! {
+            #(#attrs)*
+            #vis #exposed_signature #exposed_block
+
+            #(#attrs)*
+            #[doc(hidden)]
+            #inline_signature #block
+        }
+        .into();
+    }
+
+    if turbo_fn.is_generic() {
+        // Generic functions have no static native function, their instances are
+        // registered when they are first called
+        let exposed_signature = turbo_fn.signature();
+        let exposed_block =
+            turbo_fn.generic_block(&inline_function_ident, args.max_concurrency.as_ref());
+
+        return quote! {
+            #(#attrs)*
+            #vis #exposed_signature #exposed_block
+
+            #(#attrs)*
+            #[doc(hidden)]
+            #inline_signature #block
+        }
+        .into();
+    }
 
-    let native_fn = NativeFn::new(&ident.to_string(), &inline_function_path);
+    let native_fn = NativeFn::new(&ident.to_string(), &inline_function_path)
+        .with_max_concurrency(args.max_concurrency);
     let native_function_ident = get_native_function_ident(ident);
     let native_function_ty = native_fn.ty();
     let native_function_def = native_fn.definition();
@@ -60,6 +60,8 @@ pub fn derive_task_input(input: TokenStream) -> TokenStream {
 /// dependent tasks.
 /// - "shared" (default): Compares with the existing value in the cell, before
 /// overriding it. Requires Value to implement [Eq].
+/// - "interned": Like "shared", but structurally equal values are stored only
+/// once, even across tasks. Requires Value to implement [Eq] and [Hash].
 ///
 /// ### `eq`
 ///
@@ -129,6 +131,38 @@ pub fn value_trait(args: TokenStream, input: TokenStream) -> TokenStream {
     value_trait_macro::value_trait(args, input)
 }
 
+/// Turns a function into a task function. Calling it creates or reuses a
+/// cached task, that executes the function and is re-executed when something
+/// it read changed.
+///
+/// Naked functions can have type parameters. Every set of type arguments is
+/// registered as a separate function when it's first called, so the type
+/// arguments need to be `'static`.
+///
+/// ## Arguments
+///
+/// Example: `#[turbo_tasks::function(local)]`
+///
+/// ### `local`
+///
+/// The function is executed within the calling task instead of as a task of
+/// its own. Its result is never cached, and cells and reads are attributed to
+/// the caller. This avoids the overhead of a task for cheap orchestration
+/// helpers.
+///
+/// A local function is async and returns a `Result`, so it needs to be awaited
+/// by the caller. Only supported on naked functions.
+///
+/// No value.
+///
+/// ### `max_concurrency`
+///
+/// Limits how many tasks of the function execute at the same time, e.g. for
+/// functions that run a memory-hungry external tool. Other tasks wait until a
+/// task finishes or waits for a task it reads. Only supported on naked
+/// functions that are not `local`.
+///
+/// Example: `#[turbo_tasks::function(max_concurrency = 2)]`
 #[allow_internal_unstable(min_specialization, into_future, trivial_bounds)]
 #[proc_macro_error]
 #[proc_macro_attribute]
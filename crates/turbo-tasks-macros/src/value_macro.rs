@@ -46,6 +46,7 @@ impl TryFrom<LitStr> for IntoMode {
 enum CellMode {
     New,
     Shared,
+    Interned,
 }
 
 impl Parse for CellMode {
@@ -62,7 +63,11 @@ impl TryFrom<LitStr> for CellMode {
         match lit.value().as_str() {
             "new" => Ok(CellMode::New),
             "shared" => Ok(CellMode::Shared),
-            _ => Err(Error::new_spanned(&lit, "expected \"new\" or \"shared\"")),
+            "interned" => Ok(CellMode::Interned),
+            _ => Err(Error::new_spanned(
+                &lit,
+                "expected \"new\", \"shared\" or \"interned\"",
+            )),
         }
     }
 }
@@ -232,6 +237,9 @@ pub fn value(args: TokenStream, input: TokenStream) -> TokenStream {
         CellMode::Shared => quote! {
             turbo_tasks::VcCellSharedMode<#ident>
         },
+        CellMode::Interned => quote! {
+            turbo_tasks::VcCellInternedMode<#ident>
+        },
     };
 
     let (cell_prefix, cell_access_content, read) = if let Some(inner_type) = inner_type {
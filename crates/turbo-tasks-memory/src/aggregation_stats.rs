@@ -0,0 +1,146 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use serde::Serialize;
+
+use crate::aggregation_tree::AggregationEvent;
+
+/// Counts the maintenance work done by the task aggregation tree, to find out
+/// whether tuning the [AggregationTreeConfig](crate::AggregationTreeConfig)
+/// pays off.
+///
+/// Counting is off until [Self::enable] is called. A change is applied every
+/// time a task's dirty state, unfinished count or collectibles change, so
+/// while disabled the aggregation context skips the counters entirely instead
+/// of contending on them.
+#[derive(Default)]
+pub struct AggregationStatsCollector {
+    enabled: AtomicBool,
+    changes: AtomicU64,
+    propagated_changes: AtomicU64,
+    top_trees_created: AtomicU64,
+    bottom_trees_created: AtomicU64,
+    rebalances: AtomicU64,
+    following_added: AtomicU64,
+}
+
+/// The counters at the time of [AggregationStatsCollector::snapshot].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct AggregationStats {
+    /// How often a change was applied to an aggregated node.
+    pub changes: u64,
+    /// How many of these changes had to be propagated to the next level.
+    pub propagated_changes: u64,
+    pub top_trees_created: u64,
+    pub bottom_trees_created: u64,
+    /// How often an item exceeded the fan-out threshold and formed a new
+    /// bottom tree.
+    pub rebalances: u64,
+    /// How many children became following children of a bottom tree.
+    pub following_added: u64,
+}
+
+impl AggregationStatsCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn enable(&self) {
+        self.enabled.store(true, Ordering::Release);
+    }
+
+    /// Stops collecting. Counters recorded so far are kept.
+    pub fn disable(&self) {
+        self.enabled.store(false, Ordering::Release);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Acquire)
+    }
+
+    /// Resets all counters.
+    pub fn reset(&self) {
+        for counter in self.counters() {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+
+    pub fn snapshot(&self) -> AggregationStats {
+        let [changes, propagated_changes, top_trees_created, bottom_trees_created, rebalances, following_added] =
+            self.counters()
+                .map(|counter| counter.load(Ordering::Relaxed));
+        AggregationStats {
+            changes,
+            propagated_changes,
+            top_trees_created,
+            bottom_trees_created,
+            rebalances,
+            following_added,
+        }
+    }
+
+    fn counters(&self) -> [&AtomicU64; 6] {
+        [
+            &self.changes,
+            &self.propagated_changes,
+            &self.top_trees_created,
+            &self.bottom_trees_created,
+            &self.rebalances,
+            &self.following_added,
+        ]
+    }
+
+    pub(crate) fn record_change(&self, propagated: bool) {
+        if !self.is_enabled() {
+            return;
+        }
+        self.changes.fetch_add(1, Ordering::Relaxed);
+        if propagated {
+            self.propagated_changes.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn record_event(&self, event: AggregationEvent) {
+        if !self.is_enabled() {
+            return;
+        }
+        let (counter, count) = match event {
+            AggregationEvent::TopTreeCreated => (&self.top_trees_created, 1),
+            AggregationEvent::BottomTreeCreated { .. } => (&self.bottom_trees_created, 1),
+            AggregationEvent::Rebalanced => (&self.rebalances, 1),
+            AggregationEvent::FollowingAdded { count } => (&self.following_added, count as u64),
+        };
+        counter.fetch_add(count, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AggregationStats, AggregationStatsCollector};
+    use crate::aggregation_tree::AggregationEvent;
+
+    #[test]
+    fn test_record() {
+        let stats = AggregationStatsCollector::new();
+        stats.record_change(true);
+        assert_eq!(stats.snapshot(), AggregationStats::default());
+
+        stats.enable();
+        stats.record_change(true);
+        stats.record_change(false);
+        stats.record_event(AggregationEvent::BottomTreeCreated { height: 2 });
+        stats.record_event(AggregationEvent::FollowingAdded { count: 3 });
+        assert_eq!(
+            stats.snapshot(),
+            AggregationStats {
+                changes: 2,
+                propagated_changes: 1,
+                bottom_trees_created: 1,
+                following_added: 3,
+                ..Default::default()
+            }
+        );
+
+        stats.reset();
+        assert_eq!(stats.snapshot(), AggregationStats::default());
+    }
+}
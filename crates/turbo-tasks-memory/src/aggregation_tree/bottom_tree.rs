@@ -12,7 +12,7 @@ use super::{
         remove_left_upper_from_item,
     },
     top_tree::TopTree,
-    AggregationContext, StackVec, CHILDREN_INNER_THRESHOLD, CONNECTIVITY_LIMIT,
+    AggregationContext, AggregationEvent, StackVec,
 };
 use crate::count_hash_set::{CountHashSet, RemoveIfEntryResult};
 
@@ -70,7 +70,7 @@ impl<T, I: Clone + Eq + Hash + IsEnabled> BottomTree<T, I> {
                 // this means white children are inner children of this node
                 // and blue children need to propagate up
                 let mut children = children.into_iter().collect();
-                if nesting_level > CONNECTIVITY_LIMIT {
+                if nesting_level > aggregation_context.config().connectivity_limit {
                     self.add_children_of_child_following(aggregation_context, children);
                     return;
                 }
@@ -96,6 +96,9 @@ impl<T, I: Clone + Eq + Hash + IsEnabled> BottomTree<T, I> {
         if children.is_empty() {
             return;
         }
+        aggregation_context.on_event(AggregationEvent::FollowingAdded {
+            count: children.len(),
+        });
         let buttom_uppers = state.bottom_upper.as_cloned_uppers();
         let top_upper = state.top_upper.iter().cloned().collect::<Vec<_>>();
         drop(state);
@@ -152,7 +155,7 @@ impl<T, I: Clone + Eq + Hash + IsEnabled> BottomTree<T, I> {
                 self.add_child_of_child_inner(aggregation_context, child_of_child, nesting_level);
             }
             ChildLocation::Inner => {
-                if nesting_level <= CONNECTIVITY_LIMIT {
+                if nesting_level <= aggregation_context.config().connectivity_limit {
                     // the inner child has a new child
                     // but it's not a blue node and we are not too deep
                     // this means it's a inner child of this node
@@ -189,6 +192,7 @@ impl<T, I: Clone + Eq + Hash + IsEnabled> BottomTree<T, I> {
             // Already connect, nothing more to do
             return;
         }
+        aggregation_context.on_event(AggregationEvent::FollowingAdded { count: 1 });
 
         propagate_new_following_to_uppers(state, aggregation_context, child_of_child);
     }
@@ -430,7 +434,8 @@ impl<T, I: Clone + Eq + Hash + IsEnabled> BottomTree<T, I> {
         let BottomConnection::Inner(inner) = &mut state.bottom_upper else {
             return false;
         };
-        if inner.len() * number_of_following > CHILDREN_INNER_THRESHOLD {
+        if inner.len() * number_of_following > aggregation_context.config().children_inner_threshold
+        {
             return false;
         };
         let new = inner.add_clonable(BottomRef::ref_cast(upper), nesting_level);
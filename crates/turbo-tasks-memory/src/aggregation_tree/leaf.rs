@@ -10,7 +10,7 @@ use super::{
     bottom_tree::BottomTree,
     inner_refs::{BottomRef, ChildLocation},
     top_tree::TopTree,
-    AggregationContext, AggregationItemLock, LargeStackVec, CHILDREN_INNER_THRESHOLD,
+    AggregationContext, AggregationEvent, AggregationItemLock, LargeStackVec,
 };
 
 /// The leaf of the aggregation tree. It's usually stored inside of the nodes
@@ -178,6 +178,7 @@ pub fn top_tree<C: AggregationContext>(
         }
         tree.clone()
     };
+    aggregation_context.on_event(AggregationEvent::TopTreeCreated);
     let bottom_tree = bottom_tree(aggregation_context, reference, depth + 4);
     bottom_tree.add_top_tree_upper(aggregation_context, &new_top_tree);
     new_top_tree
@@ -201,6 +202,7 @@ pub fn bottom_tree<C: AggregationContext>(
             return tree.clone();
         }
         new_bottom_tree = tree.clone();
+        aggregation_context.on_event(AggregationEvent::BottomTreeCreated { height });
         _span = (height > 2).then(|| tracing::trace_span!("bottom_tree", height).entered());
 
         if height == 0 {
@@ -234,7 +236,8 @@ pub fn add_inner_upper_to_item<C: AggregationContext>(
         let BottomConnection::Inner(inner) = &mut leaf.upper else {
             return false;
         };
-        if inner.len() * number_of_children > CHILDREN_INNER_THRESHOLD {
+        if inner.len() * number_of_children > aggregation_context.config().children_inner_threshold
+        {
             return false;
         }
         let new = inner.add_clonable(BottomRef::ref_cast(upper), nesting_level);
@@ -376,7 +379,10 @@ pub fn ensure_thresholds<'a, C: AggregationContext>(
     let reference = item.reference().clone();
     let leaf = item.leaf();
     if let BottomConnection::Inner(list) = &leaf.upper {
-        if list.len() * number_of_total_children > CHILDREN_INNER_THRESHOLD {
+        if list.len() * number_of_total_children
+            > aggregation_context.config().children_inner_threshold
+        {
+            aggregation_context.on_event(AggregationEvent::Rebalanced);
             let (tree, new) = get_or_create_in_vec(&mut leaf.bottom_trees, 0, || {
                 Arc::new(BottomTree::new(reference.clone(), 0))
             });
@@ -43,13 +43,52 @@ pub use self::{
     top_tree::AggregationInfoGuard,
 };
 
-/// The maximum connectivity of one layer of bottom tree.
+/// The default maximum connectivity of one layer of bottom tree.
 const CONNECTIVITY_LIMIT: u8 = 7;
 
-/// The maximum of number of children muliplied by number of upper bottom trees.
-/// When reached the parent of the children will form a new bottom tree.
+/// The default maximum of number of children muliplied by number of upper
+/// bottom trees. When reached the parent of the children will form a new
+/// bottom tree.
 const CHILDREN_INNER_THRESHOLD: usize = 2000;
 
+/// Tuning parameters of the aggregation tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AggregationTreeConfig {
+    /// The maximum connectivity of one layer of bottom tree. Children nested
+    /// deeper are propagated to the upper trees as following children
+    /// instead. Higher values aggregate more items per bottom tree, at the
+    /// cost of more work per update.
+    pub connectivity_limit: u8,
+    /// The maximum of number of children muliplied by number of upper bottom
+    /// trees (the fan-out). When reached the parent of the children will form
+    /// a new bottom tree, which rebalances the tree.
+    pub children_inner_threshold: usize,
+}
+
+impl Default for AggregationTreeConfig {
+    fn default() -> Self {
+        Self {
+            connectivity_limit: CONNECTIVITY_LIMIT,
+            children_inner_threshold: CHILDREN_INNER_THRESHOLD,
+        }
+    }
+}
+
+/// Maintenance work done by the aggregation tree. Reported to
+/// [AggregationContext::on_event].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggregationEvent {
+    /// A new top tree was created.
+    TopTreeCreated,
+    /// A new bottom tree was created.
+    BottomTreeCreated { height: u8 },
+    /// An item exceeded the fan-out threshold and formed a new bottom tree.
+    Rebalanced,
+    /// Children became following children of a bottom tree, as they couldn't
+    /// be inner children.
+    FollowingAdded { count: usize },
+}
+
 type StackVec<I> = SmallVec<[I; 16]>;
 type LargeStackVec<I> = SmallVec<[I; 32]>;
 
@@ -103,6 +142,17 @@ pub trait AggregationContext {
         root_info: &mut Self::RootInfo,
         other: Self::RootInfo,
     ) -> ControlFlow<()>;
+
+    /// The tuning parameters of the aggregation tree. They must not change
+    /// while the tree is in use.
+    fn config(&self) -> AggregationTreeConfig {
+        AggregationTreeConfig::default()
+    }
+
+    /// Called for maintenance work done by the aggregation tree. Can be used
+    /// for instrumentation.
+    #[allow(unused_variables)]
+    fn on_event(&self, event: AggregationEvent) {}
 }
 
 /// A lock on a single item.
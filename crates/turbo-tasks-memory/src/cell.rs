@@ -1,6 +1,6 @@
 use std::{
     fmt::Debug,
-    mem::{replace, take},
+    mem::{replace, size_of, take},
 };
 
 use auto_hash_map::AutoSet;
@@ -10,7 +10,11 @@ use turbo_tasks::{
     TaskId, TaskIdSet, TurboTasksBackendApi,
 };
 
-use crate::MemoryBackend;
+use crate::{
+    invalidation_trace::{notify_tasks, InvalidationCause},
+    memory_stats::estimate_content_size,
+    MemoryBackend,
+};
 
 #[derive(Default, Debug)]
 pub(crate) enum Cell {
@@ -47,6 +51,25 @@ pub struct RecomputingCell {
 }
 
 impl Cell {
+    /// Estimates the memory used by this cell, including its content. See
+    /// [estimate_content_size].
+    pub fn memory_usage(&self) -> usize {
+        let (dependent_tasks, content) = match self {
+            Cell::Empty => (0, None),
+            Cell::TrackedValueless { dependent_tasks }
+            | Cell::Recomputing {
+                dependent_tasks, ..
+            } => (dependent_tasks.len(), None),
+            Cell::Value {
+                dependent_tasks,
+                content,
+            } => (dependent_tasks.len(), Some(content)),
+        };
+        size_of::<Cell>()
+            + dependent_tasks * size_of::<TaskId>()
+            + content.map_or(0, estimate_content_size)
+    }
+
     /// Returns true if the cell has a value.
     pub fn has_value(&self) -> bool {
         match self {
@@ -57,6 +80,29 @@ impl Cell {
         }
     }
 
+    /// Adds a task to the list of dependent tasks, so it's invalidated when
+    /// the content is assigned.
+    pub fn add_dependent_task(&mut self, task: TaskId) {
+        match self {
+            Cell::Empty => {
+                *self = Cell::TrackedValueless {
+                    dependent_tasks: [task].into_iter().collect(),
+                };
+            }
+            Cell::Value {
+                dependent_tasks, ..
+            }
+            | Cell::TrackedValueless {
+                dependent_tasks, ..
+            }
+            | Cell::Recomputing {
+                dependent_tasks, ..
+            } => {
+                dependent_tasks.insert(task);
+            }
+        }
+    }
+
     /// Removes a task from the list of dependent tasks.
     pub fn remove_dependent_task(&mut self, task: TaskId) {
         match self {
@@ -206,6 +252,7 @@ impl Cell {
     pub fn assign(
         &mut self,
         content: CellContent,
+        cause: InvalidationCause,
         turbo_tasks: &dyn TurboTasksBackendApi<MemoryBackend>,
     ) {
         match self {
@@ -231,7 +278,7 @@ impl Cell {
                 // Assigning to a cell will invalidate all dependent tasks as the content might
                 // have changed.
                 if !dependent_tasks.is_empty() {
-                    turbo_tasks.schedule_notify_tasks_set(dependent_tasks);
+                    notify_tasks(dependent_tasks, cause, turbo_tasks);
                 }
                 *self = Cell::Value {
                     content,
@@ -244,7 +291,7 @@ impl Cell {
             } => {
                 if content != *cell_content {
                     if !dependent_tasks.is_empty() {
-                        turbo_tasks.schedule_notify_tasks_set(dependent_tasks);
+                        notify_tasks(dependent_tasks, cause, turbo_tasks);
                         dependent_tasks.clear();
                     }
                     *cell_content = content;
@@ -0,0 +1,183 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use dashmap::DashSet;
+use parking_lot::Mutex;
+use tracing::warn;
+use turbo_tasks::{backend::CellContent, registry, CellId, FunctionId, RawVc, TaskId};
+
+/// What differed between two executions of a task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difference {
+    /// The task returned a different value.
+    Output,
+    /// The task wrote a different value to a cell.
+    Cell(CellId),
+}
+
+/// A function that produced a different result when it was re-executed with
+/// identical inputs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Nondeterminism {
+    pub function: &'static str,
+    pub task: TaskId,
+    pub difference: Difference,
+}
+
+/// Re-executes a sample of finished tasks and compares the results with those
+/// of the previous execution. Functions that return different results for
+/// identical inputs poison the cache, as which result is cached depends on
+/// timing.
+///
+/// Cell contents are compared by their serialized form, so values that are
+/// not serializable are never flagged. A dependency that changes while a task
+/// is being verified can cause false positives.
+#[derive(Default)]
+pub struct DeterminismVerifier {
+    /// Every n-th finished execution is verified. 0 disables verification.
+    sample_interval: AtomicUsize,
+    executions: AtomicUsize,
+    verifying: DashSet<TaskId>,
+    reports: Mutex<Vec<Nondeterminism>>,
+}
+
+impl DeterminismVerifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts verifying every `sample_interval`-th finished task execution.
+    pub fn enable(&self, sample_interval: usize) {
+        self.sample_interval
+            .store(sample_interval, Ordering::Release);
+    }
+
+    pub fn disable(&self) {
+        self.sample_interval.store(0, Ordering::Release);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.sample_interval.load(Ordering::Acquire) != 0
+    }
+
+    /// All differences found so far.
+    pub fn reports(&self) -> Vec<Nondeterminism> {
+        self.reports.lock().clone()
+    }
+
+    /// Decides whether the task that just finished should be re-executed for
+    /// verification.
+    pub(crate) fn sample(&self, task: TaskId) -> bool {
+        let sample_interval = self.sample_interval.load(Ordering::Acquire);
+        if sample_interval == 0 {
+            return false;
+        }
+        let execution = self.executions.fetch_add(1, Ordering::Relaxed);
+        if execution % sample_interval != 0 {
+            return false;
+        }
+        self.verifying.insert(task)
+    }
+
+    pub(crate) fn is_verifying(&self, task: TaskId) -> bool {
+        !self.verifying.is_empty() && self.verifying.contains(&task)
+    }
+
+    /// Ends the verification of `task`. Returns whether it was being verified.
+    pub(crate) fn finish(&self, task: TaskId) -> bool {
+        !self.verifying.is_empty() && self.verifying.remove(&task).is_some()
+    }
+
+    pub(crate) fn compare_output(
+        &self,
+        task: TaskId,
+        function: FunctionId,
+        old: Option<RawVc>,
+        new: RawVc,
+    ) {
+        if old.is_some_and(|old| old != new) {
+            self.report(task, function, Difference::Output);
+        }
+    }
+
+    pub(crate) fn compare_cell(
+        &self,
+        task: TaskId,
+        function: FunctionId,
+        cell: CellId,
+        old: &CellContent,
+        new: &CellContent,
+    ) {
+        if same_content(old, new) == Some(false) {
+            self.report(task, function, Difference::Cell(cell));
+        }
+    }
+
+    fn report(&self, task: TaskId, function: FunctionId, difference: Difference) {
+        let function = registry::get_function(function).name.as_str();
+        warn!(
+            "{function} produced a different result when re-executed with identical inputs \
+             ({difference:?})"
+        );
+        self.reports.lock().push(Nondeterminism {
+            function,
+            task,
+            difference,
+        });
+    }
+}
+
+/// Compares cell contents by value. Returns `None` when that's not possible.
+fn same_content(old: &CellContent, new: &CellContent) -> Option<bool> {
+    match (&old.0, &new.0) {
+        (Some(old), Some(new)) => {
+            if old == new {
+                return Some(true);
+            }
+            let old = serde_json::to_vec(old).ok()?;
+            let new = serde_json::to_vec(new).ok()?;
+            Some(old == new)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use turbo_tasks::{backend::CellContent, SharedReference, TaskId};
+
+    use super::{same_content, DeterminismVerifier};
+
+    #[test]
+    fn test_sample() {
+        let verifier = DeterminismVerifier::new();
+        assert!(!verifier.sample(TaskId::from(1)));
+
+        verifier.enable(2);
+        assert!(verifier.sample(TaskId::from(1)));
+        assert!(!verifier.sample(TaskId::from(2)));
+        assert!(verifier.is_verifying(TaskId::from(1)));
+        assert!(!verifier.is_verifying(TaskId::from(2)));
+        assert!(verifier.finish(TaskId::from(1)));
+        assert!(!verifier.finish(TaskId::from(1)));
+    }
+
+    #[test]
+    fn test_same_content() {
+        let value = SharedReference(None, Arc::new(42u32));
+        assert_eq!(
+            same_content(&CellContent(Some(value.clone())), &CellContent(Some(value))),
+            Some(true)
+        );
+        // Untyped values can't be serialized, so they can't be compared
+        assert_eq!(
+            same_content(
+                &CellContent(Some(SharedReference(None, Arc::new(1u32)))),
+                &CellContent(Some(SharedReference(None, Arc::new(2u32))))
+            ),
+            None
+        );
+        assert_eq!(same_content(&CellContent(None), &CellContent(None)), None);
+    }
+}
@@ -0,0 +1,746 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet},
+    fs, io,
+    path::PathBuf,
+};
+
+use anyhow::{Context, Result};
+use parking_lot::{Mutex, MutexGuard};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use turbo_tasks::{
+    backend::PersistentTaskType,
+    persisted_graph::{
+        ActivateResult, DeactivateResult, PersistResult, PersistTaskState, PersistedGraph,
+        PersistedGraphApi, ReadTaskState, TaskData,
+    },
+    with_task_id_mapping, IdMapping, RawVc, TaskId,
+};
+
+/// Bumped whenever the snapshot layout changes. Snapshots with a different
+/// version are ignored.
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// A [PersistedGraph] that keeps task cells, dependency edges and dirty state
+/// in memory and writes them to a snapshot file when turbo-tasks stops. The
+/// next process restores the snapshot on startup, so it resumes with warm
+/// incremental state instead of recomputing everything.
+///
+/// Use it with [crate::MemoryBackendWithPersistedGraph].
+///
+/// Task ids are only valid within a process, so the snapshot refers to tasks
+/// by their [PersistentTaskType] and they are assigned fresh ids when the
+/// snapshot is restored. Tasks with cell values that can't be serialized are
+/// left out of the snapshot and recomputed.
+pub struct DiskPersistedGraph {
+    path: PathBuf,
+    state: Mutex<State>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    version: u32,
+    /// Serialized [PersistentTaskType]s, indexed by the ids used for tasks
+    /// within the snapshot.
+    task_types: Vec<Value>,
+    tasks: Vec<Value>,
+}
+
+#[derive(Serialize)]
+struct SnapshotTaskRef<'a> {
+    id: TaskId,
+    data: &'a TaskData,
+    dirty: bool,
+    externally_active: bool,
+}
+
+#[derive(Deserialize)]
+struct SnapshotTask {
+    id: TaskId,
+    data: TaskData,
+    dirty: bool,
+    externally_active: bool,
+}
+
+struct PersistedTask {
+    data: TaskData,
+    dirty: bool,
+    /// Kept alive by the memory graph
+    externally_active: bool,
+    /// `externally_active` was set by this process rather than restored from
+    /// the snapshot
+    renewed: bool,
+    /// Number of active persisted tasks that have this task as a child
+    active_parents: usize,
+    active: bool,
+}
+
+#[derive(Default)]
+struct State {
+    /// Snapshot read from disk that hasn't been restored yet. Restoring needs
+    /// a [PersistedGraphApi], so it happens on first use.
+    pending: Option<Snapshot>,
+    tasks: HashMap<TaskId, PersistedTask>,
+    dependents: HashMap<RawVc, HashSet<TaskId>>,
+    /// Children of active persisted tasks that only live in memory, with the
+    /// number of active persisted parents they have.
+    external_active_children: HashMap<TaskId, usize>,
+}
+
+impl DiskPersistedGraph {
+    pub fn new(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let pending = match fs::read(&path) {
+            Ok(contents) => match serde_json::from_slice::<Snapshot>(&contents) {
+                Ok(snapshot) if snapshot.version == SNAPSHOT_VERSION => Some(snapshot),
+                // An outdated or corrupted snapshot only means starting cold
+                _ => None,
+            },
+            Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+            Err(err) => {
+                return Err(err)
+                    .with_context(|| format!("failed to read snapshot {}", path.display()))
+            }
+        };
+        Ok(Self {
+            path,
+            state: Mutex::new(State {
+                pending,
+                ..Default::default()
+            }),
+        })
+    }
+
+    fn state(&self, api: &dyn PersistedGraphApi) -> MutexGuard<'_, State> {
+        let mut state = self.state.lock();
+        if let Some(snapshot) = state.pending.take() {
+            state.restore(snapshot, api);
+        }
+        state
+    }
+
+    fn save(&self, state: &State, api: &dyn PersistedGraphApi) -> Result<()> {
+        let mapping = SaveMapping::new();
+        let snapshot = with_task_id_mapping(&mapping, || {
+            let tasks = state
+                .tasks
+                .iter()
+                .filter_map(|(&id, task)| {
+                    serde_json::to_value(SnapshotTaskRef {
+                        id,
+                        data: &task.data,
+                        dirty: task.dirty,
+                        externally_active: task.externally_active,
+                    })
+                    .ok()
+                })
+                .collect::<Vec<_>>();
+
+            // Serializing a task type can discover more tasks, so this can't
+            // be a simple iterator
+            let mut task_types = Vec::new();
+            while task_types.len() < mapping.order.borrow().len() {
+                let task = mapping.order.borrow()[task_types.len()];
+                let task_type = serde_json::to_value(api.lookup_task_type(task));
+                task_types.push(task_type.unwrap_or(Value::Null));
+            }
+
+            Snapshot {
+                version: SNAPSHOT_VERSION,
+                task_types,
+                tasks,
+            }
+        });
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Write to a temporary file first so a crash never leaves a partial
+        // snapshot behind
+        let tmp_path = self.path.with_extension("tmp");
+        fs::write(&tmp_path, serde_json::to_vec(&snapshot)?)?;
+        fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+}
+
+impl State {
+    fn restore(&mut self, snapshot: Snapshot, api: &dyn PersistedGraphApi) {
+        let mapping = RestoreMapping::new(api, &snapshot.task_types);
+        let tasks = with_task_id_mapping(&mapping, || {
+            snapshot
+                .tasks
+                .iter()
+                .filter_map(|task| mapping.deserialize::<SnapshotTask>(task))
+                .collect::<Vec<_>>()
+        });
+
+        for task in tasks {
+            for dependency in task.data.dependencies.iter() {
+                self.dependents
+                    .entry(*dependency)
+                    .or_default()
+                    .insert(task.id);
+            }
+            self.tasks.insert(
+                task.id,
+                PersistedTask {
+                    data: task.data,
+                    dirty: task.dirty,
+                    externally_active: task.externally_active,
+                    renewed: false,
+                    active_parents: 0,
+                    active: false,
+                },
+            );
+        }
+
+        let mut queue = self
+            .tasks
+            .iter()
+            .filter(|(_, task)| task.externally_active)
+            .map(|(&id, _)| id)
+            .collect::<Vec<_>>();
+        while let Some(id) = queue.pop() {
+            let Some(task) = self.tasks.get_mut(&id) else {
+                continue;
+            };
+            if task.active {
+                continue;
+            }
+            task.active = true;
+            let children = task.data.children.clone();
+            self.add_active_parent(&children, &mut queue);
+        }
+    }
+
+    /// Counts an active parent for each of `children`, collecting the ones
+    /// that need to be activated.
+    fn add_active_parent(&mut self, children: &[TaskId], to_activate: &mut Vec<TaskId>) {
+        for &child in children {
+            if let Some(task) = self.tasks.get_mut(&child) {
+                task.active_parents += 1;
+                if !task.active {
+                    to_activate.push(child);
+                }
+            } else {
+                let count = self.external_active_children.entry(child).or_default();
+                *count += 1;
+                if *count == 1 {
+                    to_activate.push(child);
+                }
+            }
+        }
+    }
+
+    /// Removes an active parent from each of `children`, collecting the ones
+    /// that need to be deactivated.
+    fn remove_active_parent(&mut self, children: &[TaskId], to_deactivate: &mut Vec<TaskId>) {
+        for &child in children {
+            if let Some(task) = self.tasks.get_mut(&child) {
+                task.active_parents = task.active_parents.saturating_sub(1);
+                if task.active && task.active_parents == 0 && !task.externally_active {
+                    to_deactivate.push(child);
+                }
+            } else if let Some(count) = self.external_active_children.get_mut(&child) {
+                *count -= 1;
+                if *count == 0 {
+                    self.external_active_children.remove(&child);
+                    to_deactivate.push(child);
+                }
+            }
+        }
+    }
+}
+
+impl PersistedGraph for DiskPersistedGraph {
+    fn read(
+        &self,
+        task: TaskId,
+        api: &dyn PersistedGraphApi,
+    ) -> Result<Option<(TaskData, ReadTaskState)>> {
+        let state = self.state(api);
+        Ok(state.tasks.get(&task).map(|task| {
+            (
+                task.data.clone(),
+                ReadTaskState {
+                    clean: !task.dirty,
+                    keeps_external_active: task.active_parents > 0,
+                },
+            )
+        }))
+    }
+
+    fn lookup(
+        &self,
+        _partial_task_type: &PersistentTaskType,
+        api: &dyn PersistedGraphApi,
+    ) -> Result<bool> {
+        // Restoring the snapshot registers every persisted task type, so the
+        // cache is always complete afterwards
+        drop(self.state(api));
+        Ok(true)
+    }
+
+    fn lookup_one(
+        &self,
+        _task_type: &PersistentTaskType,
+        _api: &dyn PersistedGraphApi,
+    ) -> Result<Option<TaskId>> {
+        Ok(None)
+    }
+
+    fn is_persisted(&self, task: TaskId, api: &dyn PersistedGraphApi) -> Result<bool> {
+        Ok(self.state(api).tasks.contains_key(&task))
+    }
+
+    fn persist(
+        &self,
+        task: TaskId,
+        data: TaskData,
+        persist_state: PersistTaskState,
+        api: &dyn PersistedGraphApi,
+    ) -> Result<Option<PersistResult>> {
+        let mut state = self.state(api);
+        let (was_active, old_children, active_parents) = match state.tasks.remove(&task) {
+            Some(old) => {
+                for dependency in old.data.dependencies.iter() {
+                    if let Some(dependents) = state.dependents.get_mut(dependency) {
+                        dependents.remove(&task);
+                    }
+                }
+                (old.active, old.data.children, old.active_parents)
+            }
+            // A task moving from memory into the persisted graph takes over
+            // the active count it had as an external child
+            None => (
+                false,
+                Vec::new(),
+                state.external_active_children.remove(&task).unwrap_or(0),
+            ),
+        };
+        let active = persist_state.externally_active || active_parents > 0;
+
+        let old_children = if was_active {
+            old_children.into_iter().collect()
+        } else {
+            HashSet::new()
+        };
+        let new_children: HashSet<TaskId> = if active {
+            data.children.iter().copied().collect()
+        } else {
+            HashSet::new()
+        };
+        let added = new_children
+            .difference(&old_children)
+            .copied()
+            .collect::<Vec<_>>();
+        let removed = old_children
+            .difference(&new_children)
+            .copied()
+            .collect::<Vec<_>>();
+        let mut tasks_to_activate = Vec::new();
+        let mut tasks_to_deactivate = Vec::new();
+        state.add_active_parent(&added, &mut tasks_to_activate);
+        state.remove_active_parent(&removed, &mut tasks_to_deactivate);
+
+        for dependency in data.dependencies.iter() {
+            state
+                .dependents
+                .entry(*dependency)
+                .or_default()
+                .insert(task);
+        }
+        state.tasks.insert(
+            task,
+            PersistedTask {
+                data,
+                dirty: false,
+                externally_active: persist_state.externally_active,
+                renewed: true,
+                active_parents,
+                active,
+            },
+        );
+
+        Ok(Some(PersistResult {
+            tasks_to_activate,
+            tasks_to_deactivate,
+        }))
+    }
+
+    fn activate_when_needed(
+        &self,
+        task: TaskId,
+        api: &dyn PersistedGraphApi,
+    ) -> Result<Option<ActivateResult>> {
+        let mut state = self.state(api);
+        let Some(entry) = state.tasks.get_mut(&task) else {
+            return Ok(Some(ActivateResult {
+                keeps_external_active: state.external_active_children.contains_key(&task),
+                external: true,
+                dirty: false,
+                more_tasks_to_activate: Vec::new(),
+            }));
+        };
+        let keeps_external_active = entry.active_parents > 0;
+        let needed = entry.externally_active || entry.active_parents > 0;
+        let dirty = needed && entry.dirty;
+        let mut more_tasks_to_activate = Vec::new();
+        if needed && !entry.active {
+            entry.active = true;
+            let children = entry.data.children.clone();
+            state.add_active_parent(&children, &mut more_tasks_to_activate);
+        }
+        Ok(Some(ActivateResult {
+            keeps_external_active,
+            external: false,
+            dirty,
+            more_tasks_to_activate,
+        }))
+    }
+
+    fn deactivate_when_needed(
+        &self,
+        task: TaskId,
+        api: &dyn PersistedGraphApi,
+    ) -> Result<Option<DeactivateResult>> {
+        let mut state = self.state(api);
+        let Some(entry) = state.tasks.get_mut(&task) else {
+            let inactive = !state.external_active_children.contains_key(&task);
+            return Ok(inactive.then(|| DeactivateResult {
+                more_tasks_to_deactivate: Vec::new(),
+            }));
+        };
+        if !entry.active || entry.externally_active || entry.active_parents > 0 {
+            return Ok(None);
+        }
+        entry.active = false;
+        let children = entry.data.children.clone();
+        let mut more_tasks_to_deactivate = Vec::new();
+        state.remove_active_parent(&children, &mut more_tasks_to_deactivate);
+        Ok(Some(DeactivateResult {
+            more_tasks_to_deactivate,
+        }))
+    }
+
+    fn set_externally_active(&self, task: TaskId, api: &dyn PersistedGraphApi) -> Result<bool> {
+        let mut state = self.state(api);
+        Ok(state.tasks.get_mut(&task).map_or(false, |task| {
+            task.externally_active = true;
+            task.renewed = true;
+            !task.active
+        }))
+    }
+
+    fn unset_externally_active(&self, task: TaskId, api: &dyn PersistedGraphApi) -> Result<bool> {
+        let mut state = self.state(api);
+        Ok(state.tasks.get_mut(&task).map_or(false, |task| {
+            task.externally_active = false;
+            task.active && task.active_parents == 0
+        }))
+    }
+
+    fn remove_outdated_externally_active(
+        &self,
+        api: &dyn PersistedGraphApi,
+    ) -> Result<Vec<TaskId>> {
+        let mut state = self.state(api);
+        Ok(state
+            .tasks
+            .iter_mut()
+            .filter(|(_, task)| task.externally_active && !task.renewed)
+            .filter_map(|(&id, task)| {
+                task.externally_active = false;
+                (task.active && task.active_parents == 0).then_some(id)
+            })
+            .collect())
+    }
+
+    fn make_dirty(&self, task: TaskId, api: &dyn PersistedGraphApi) -> Result<bool> {
+        let mut state = self.state(api);
+        Ok(state.tasks.get_mut(&task).map_or(false, |task| {
+            task.dirty = true;
+            task.active
+        }))
+    }
+
+    fn make_clean(&self, task: TaskId, api: &dyn PersistedGraphApi) -> Result<()> {
+        let mut state = self.state(api);
+        if let Some(task) = state.tasks.get_mut(&task) {
+            task.dirty = false;
+        }
+        Ok(())
+    }
+
+    fn make_dependent_dirty(&self, vc: RawVc, api: &dyn PersistedGraphApi) -> Result<Vec<TaskId>> {
+        let mut state = self.state(api);
+        let Some(dependents) = state.dependents.get(&vc) else {
+            return Ok(Vec::new());
+        };
+        let dependents = dependents.iter().copied().collect::<Vec<_>>();
+        let mut to_schedule = Vec::new();
+        for id in dependents {
+            if let Some(task) = state.tasks.get_mut(&id) {
+                if !task.dirty {
+                    task.dirty = true;
+                    if task.active {
+                        to_schedule.push(id);
+                    }
+                }
+            }
+        }
+        Ok(to_schedule)
+    }
+
+    fn get_active_external_tasks(&self, api: &dyn PersistedGraphApi) -> Result<Vec<TaskId>> {
+        let state = self.state(api);
+        Ok(state.external_active_children.keys().copied().collect())
+    }
+
+    fn get_dirty_active_tasks(&self, api: &dyn PersistedGraphApi) -> Result<Vec<TaskId>> {
+        let state = self.state(api);
+        Ok(state
+            .tasks
+            .iter()
+            .filter(|(_, task)| task.active && task.dirty)
+            .map(|(&id, _)| id)
+            .collect())
+    }
+
+    fn get_pending_active_update(
+        &self,
+        _api: &dyn PersistedGraphApi,
+    ) -> Result<(Vec<TaskId>, Vec<TaskId>)> {
+        // Activation is propagated eagerly, so nothing is ever left pending
+        Ok((Vec::new(), Vec::new()))
+    }
+
+    fn stop(&self, api: &dyn PersistedGraphApi) -> Result<()> {
+        let state = self.state(api);
+        self.save(&state, api)
+            .with_context(|| format!("failed to write snapshot {}", self.path.display()))
+    }
+}
+
+/// Assigns snapshot ids to tasks in the order they are serialized.
+pub(crate) struct SaveMapping {
+    ids: RefCell<HashMap<TaskId, usize>>,
+    pub(crate) order: RefCell<Vec<TaskId>>,
+}
+
+impl SaveMapping {
+    pub(crate) fn new() -> Self {
+        Self {
+            ids: RefCell::new(HashMap::new()),
+            order: RefCell::new(Vec::new()),
+        }
+    }
+}
+
+impl IdMapping<TaskId> for SaveMapping {
+    fn forward(&self, task: TaskId) -> usize {
+        let mut ids = self.ids.borrow_mut();
+        let next = ids.len();
+        *ids.entry(task).or_insert_with(|| {
+            self.order.borrow_mut().push(task);
+            next
+        })
+    }
+
+    fn backward(&self, _id: usize) -> TaskId {
+        unreachable!("snapshots are only serialized while saving")
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Resolution {
+    Unresolved,
+    Resolving,
+    Resolved(TaskId),
+    Failed,
+}
+
+/// Resolves snapshot ids to task ids of the current process by registering
+/// their task types with the backend.
+pub(crate) struct RestoreMapping<'a> {
+    api: &'a dyn PersistedGraphApi,
+    task_types: &'a [Value],
+    resolved: RefCell<Vec<Resolution>>,
+    /// Set when a task type couldn't be restored, e.g. because the function
+    /// it calls no longer exists
+    failed: Cell<bool>,
+}
+
+impl<'a> RestoreMapping<'a> {
+    pub(crate) fn new(api: &'a dyn PersistedGraphApi, task_types: &'a [Value]) -> Self {
+        Self {
+            api,
+            task_types,
+            resolved: RefCell::new(vec![Resolution::Unresolved; task_types.len()]),
+            failed: Cell::new(false),
+        }
+    }
+
+    /// Deserializes a value that refers to tasks. Must be called within
+    /// [with_task_id_mapping] for this mapping. Returns `None` when the value
+    /// refers to a task type that no longer exists, as it has to be
+    /// recomputed.
+    pub(crate) fn deserialize<T: DeserializeOwned>(&self, value: &Value) -> Option<T> {
+        self.failed.set(false);
+        let value = T::deserialize(value).ok()?;
+        (!self.failed.get()).then_some(value)
+    }
+
+    fn resolve(&self, id: usize) -> Option<TaskId> {
+        match self.resolved.borrow().get(id)? {
+            Resolution::Resolved(task) => return Some(*task),
+            Resolution::Unresolved => {}
+            Resolution::Resolving | Resolution::Failed => return None,
+        }
+        self.resolved.borrow_mut()[id] = Resolution::Resolving;
+
+        // Task types can refer to other tasks, which are resolved recursively
+        let outer_failed = self.failed.replace(false);
+        let task_type = PersistentTaskType::deserialize(&self.task_types[id])
+            .ok()
+            .filter(|_| !self.failed.get());
+        self.failed.set(outer_failed);
+
+        let resolution = match task_type {
+            Some(task_type) => Resolution::Resolved(self.api.get_or_create_task_type(task_type)),
+            None => Resolution::Failed,
+        };
+        self.resolved.borrow_mut()[id] = resolution;
+        match resolution {
+            Resolution::Resolved(task) => Some(task),
+            _ => None,
+        }
+    }
+}
+
+impl IdMapping<TaskId> for RestoreMapping<'_> {
+    fn forward(&self, _task: TaskId) -> usize {
+        unreachable!("snapshots are only deserialized while restoring")
+    }
+
+    fn backward(&self, id: usize) -> TaskId {
+        self.resolve(id).unwrap_or_else(|| {
+            self.failed.set(true);
+            // Never used, the task referring to it is dropped
+            TaskId::from(usize::MAX)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use turbo_tasks::{
+        backend::PersistentTaskType,
+        persisted_graph::{PersistTaskState, PersistedGraph, PersistedGraphApi, TaskData},
+        RawVc, TaskId,
+    };
+
+    use super::DiskPersistedGraph;
+
+    struct NoApi;
+
+    impl PersistedGraphApi for NoApi {
+        fn get_or_create_task_type(&self, _ty: PersistentTaskType) -> TaskId {
+            unreachable!("no snapshot is restored")
+        }
+
+        fn lookup_task_type(&self, _id: TaskId) -> &PersistentTaskType {
+            unreachable!("no snapshot is saved")
+        }
+    }
+
+    fn task_data(task: usize, children: &[usize], dependencies: &[usize]) -> TaskData {
+        TaskData {
+            children: children.iter().map(|&id| TaskId::from(id)).collect(),
+            dependencies: dependencies
+                .iter()
+                .map(|&id| RawVc::TaskOutput(TaskId::from(id)))
+                .collect(),
+            cells: Vec::new(),
+            output: RawVc::TaskOutput(TaskId::from(task)),
+        }
+    }
+
+    #[test]
+    fn test_activation() {
+        let api = &NoApi;
+        let graph =
+            DiskPersistedGraph::new(std::env::temp_dir().join("missing-turbo-tasks-snapshot"))
+                .unwrap();
+        let (parent, child, memory_child, dependency) = (
+            TaskId::from(1),
+            TaskId::from(2),
+            TaskId::from(3),
+            TaskId::from(4),
+        );
+
+        let result = graph
+            .persist(
+                child,
+                task_data(2, &[], &[4]),
+                PersistTaskState {
+                    externally_active: false,
+                },
+                api,
+            )
+            .unwrap()
+            .unwrap();
+        assert!(result.tasks_to_activate.is_empty());
+
+        let mut result = graph
+            .persist(
+                parent,
+                task_data(1, &[2, 3], &[]),
+                PersistTaskState {
+                    externally_active: true,
+                },
+                api,
+            )
+            .unwrap()
+            .unwrap();
+        result.tasks_to_activate.sort();
+        assert_eq!(result.tasks_to_activate, vec![child, memory_child]);
+
+        let activated = graph.activate_when_needed(child, api).unwrap().unwrap();
+        assert!(activated.keeps_external_active);
+        assert!(!activated.external);
+        let activated = graph
+            .activate_when_needed(memory_child, api)
+            .unwrap()
+            .unwrap();
+        assert!(activated.keeps_external_active);
+        assert!(activated.external);
+        assert_eq!(
+            graph.get_active_external_tasks(api).unwrap(),
+            vec![memory_child]
+        );
+
+        assert_eq!(
+            graph
+                .make_dependent_dirty(RawVc::TaskOutput(dependency), api)
+                .unwrap(),
+            vec![child]
+        );
+        assert_eq!(graph.get_dirty_active_tasks(api).unwrap(), vec![child]);
+        let (_, read_state) = graph.read(child, api).unwrap().unwrap();
+        assert!(!read_state.clean);
+
+        assert!(graph.unset_externally_active(parent, api).unwrap());
+        let mut deactivated = graph.deactivate_when_needed(parent, api).unwrap().unwrap();
+        deactivated.more_tasks_to_deactivate.sort();
+        assert_eq!(
+            deactivated.more_tasks_to_deactivate,
+            vec![child, memory_child]
+        );
+        assert!(graph.deactivate_when_needed(child, api).unwrap().is_some());
+        assert!(graph.get_active_external_tasks(api).unwrap().is_empty());
+        assert!(graph.get_dirty_active_tasks(api).unwrap().is_empty());
+    }
+}
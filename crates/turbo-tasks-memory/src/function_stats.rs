@@ -0,0 +1,155 @@
+use std::{
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
+    time::Duration,
+};
+
+use dashmap::DashMap;
+use serde::{Serialize, Serializer};
+use turbo_tasks::{registry, FunctionId};
+
+/// Collects call counts, cache hit rates and execution durations per
+/// `#[turbo_tasks::function]`.
+///
+/// Collection is disabled by default. It's cheap to leave disabled, as every
+/// recording site only checks a flag.
+#[derive(Default)]
+pub struct FunctionStatsCollector {
+    enabled: AtomicBool,
+    functions: DashMap<FunctionId, FunctionCounters>,
+}
+
+#[derive(Default)]
+struct FunctionCounters {
+    calls: AtomicU64,
+    cache_hits: AtomicU64,
+    executions: AtomicU64,
+    total_duration_nanos: AtomicU64,
+    max_duration_nanos: AtomicU64,
+}
+
+/// The statistics of a single function at the time of
+/// [FunctionStatsCollector::snapshot].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FunctionStats {
+    pub name: &'static str,
+    /// How often the function was called. Every call either hits the task
+    /// cache or creates a new task.
+    pub calls: u64,
+    pub cache_hits: u64,
+    /// The share of calls that were served from the task cache, between 0 and
+    /// 1.
+    pub cache_hit_rate: f64,
+    /// How often a task of the function was executed, including
+    /// re-executions after invalidation.
+    pub executions: u64,
+    #[serde(serialize_with = "serialize_millis")]
+    pub total_duration: Duration,
+    #[serde(serialize_with = "serialize_millis")]
+    pub max_duration: Duration,
+}
+
+impl FunctionStats {
+    pub fn average_duration(&self) -> Duration {
+        if self.executions == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_nanos((self.total_duration.as_nanos() / self.executions as u128) as u64)
+        }
+    }
+}
+
+fn serialize_millis<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64() * 1000.0)
+}
+
+impl FunctionStatsCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn enable(&self) {
+        self.enabled.store(true, Ordering::Release);
+    }
+
+    /// Stops collecting. Statistics recorded so far are kept.
+    pub fn disable(&self) {
+        self.enabled.store(false, Ordering::Release);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Acquire)
+    }
+
+    /// Forgets all statistics recorded so far.
+    pub fn reset(&self) {
+        self.functions.clear();
+    }
+
+    pub(crate) fn record_call(&self, function: FunctionId, cache_hit: bool) {
+        if !self.is_enabled() {
+            return;
+        }
+        let counters = self.functions.entry(function).or_default();
+        counters.calls.fetch_add(1, Ordering::Relaxed);
+        if cache_hit {
+            counters.cache_hits.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn record_execution(&self, function: FunctionId, duration: Duration) {
+        if !self.is_enabled() {
+            return;
+        }
+        let nanos = duration.as_nanos().try_into().unwrap_or(u64::MAX);
+        let counters = self.functions.entry(function).or_default();
+        counters.executions.fetch_add(1, Ordering::Relaxed);
+        counters
+            .total_duration_nanos
+            .fetch_add(nanos, Ordering::Relaxed);
+        counters
+            .max_duration_nanos
+            .fetch_max(nanos, Ordering::Relaxed);
+    }
+
+    /// Returns the statistics of all functions that were recorded, sorted by
+    /// total execution duration, longest first.
+    pub fn snapshot(&self) -> Vec<FunctionStats> {
+        let mut stats = self
+            .functions
+            .iter()
+            .map(|entry| {
+                let counters = entry.value();
+                let calls = counters.calls.load(Ordering::Relaxed);
+                let cache_hits = counters.cache_hits.load(Ordering::Relaxed);
+                FunctionStats {
+                    name: registry::get_function(*entry.key()).name.as_str(),
+                    calls,
+                    cache_hits,
+                    cache_hit_rate: if calls == 0 {
+                        0.0
+                    } else {
+                        cache_hits as f64 / calls as f64
+                    },
+                    executions: counters.executions.load(Ordering::Relaxed),
+                    total_duration: Duration::from_nanos(
+                        counters.total_duration_nanos.load(Ordering::Relaxed),
+                    ),
+                    max_duration: Duration::from_nanos(
+                        counters.max_duration_nanos.load(Ordering::Relaxed),
+                    ),
+                }
+            })
+            .collect::<Vec<_>>();
+        stats.sort_by(|a, b| {
+            b.total_duration
+                .cmp(&a.total_duration)
+                .then_with(|| a.name.cmp(b.name))
+        });
+        stats
+    }
+
+    /// Serializes [Self::snapshot] as JSON. Durations are in milliseconds.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&self.snapshot())
+    }
+}
@@ -1,5 +1,5 @@
 use std::{
-    cmp::Reverse,
+    cmp::{max, min, Reverse},
     collections::HashMap,
     time::{Duration, Instant},
 };
@@ -70,6 +70,87 @@ pub struct GcStats {
     pub no_gc_possible: usize,
 }
 
+/// Controls when and how aggressively the [MemoryBackend] collects garbage.
+#[derive(Debug, Clone, Copy)]
+pub struct GcConfig {
+    /// The memory usage in bytes that should not be exceeded. `usize::MAX`
+    /// disables collection based on memory usage.
+    pub memory_limit: usize,
+    /// The memory usage in bytes collection works towards while tasks are
+    /// executing. Defaults to 7/8 of the `memory_limit`.
+    pub target_memory: Option<usize>,
+    /// The memory usage in bytes collection works towards while idle.
+    /// Defaults to 3/4 of the `memory_limit`.
+    pub idle_target_memory: Option<usize>,
+    /// Inactive tasks are only unloaded once they haven't executed for at
+    /// least this long.
+    pub unload_idle_age: SmallDuration<1_000_000_000>,
+    /// Cells that are still read by other tasks are only emptied once their
+    /// task hasn't executed for at least this long.
+    pub empty_cells_idle_age: SmallDuration<1_000_000_000>,
+    /// The number of tasks holding collectable state above which collection
+    /// runs, independent of memory usage.
+    pub max_tasks: Option<usize>,
+}
+
+impl Default for GcConfig {
+    fn default() -> Self {
+        Self {
+            memory_limit: usize::MAX,
+            target_memory: None,
+            idle_target_memory: None,
+            unload_idle_age: SmallDuration::ZERO,
+            empty_cells_idle_age: SmallDuration::ZERO,
+            max_tasks: None,
+        }
+    }
+}
+
+impl GcConfig {
+    pub fn with_memory_limit(memory_limit: usize) -> Self {
+        Self {
+            memory_limit,
+            ..Default::default()
+        }
+    }
+
+    /// Whether garbage collection needs to track tasks at all.
+    pub fn is_enabled(&self) -> bool {
+        self.memory_limit != usize::MAX || self.max_tasks.is_some()
+    }
+
+    /// The memory usage collection works towards, clamped to the limit.
+    pub fn target_memory(&self, idle: bool) -> usize {
+        let target = if idle {
+            self.idle_target_memory.unwrap_or(self.memory_limit / 4 * 3)
+        } else {
+            self.target_memory.unwrap_or(self.memory_limit / 8 * 7)
+        };
+        min(target, self.memory_limit)
+    }
+
+    /// How much collection work is needed for the given memory usage and
+    /// number of collectable tasks, on the scale of [GcQueue::run_gc]'s
+    /// `factor`.
+    pub fn collect_factor(&self, idle: bool, memory_usage: usize, tasks: usize) -> u8 {
+        let mut factor = 0;
+        let target = self.target_memory(idle);
+        if memory_usage > target {
+            let range = max(self.memory_limit - target, 1);
+            factor = min(
+                u8::MAX as usize,
+                (memory_usage - target).saturating_mul(u8::MAX as usize) / range,
+            );
+        }
+        if let Some(max_tasks) = self.max_tasks {
+            if tasks > max_tasks {
+                factor = max(factor, (tasks - max_tasks) * u8::MAX as usize / tasks);
+            }
+        }
+        factor as u8
+    }
+}
+
 /// State about garbage collection for a task.
 #[derive(Debug, Default)]
 pub struct GcTaskState {
@@ -92,6 +173,11 @@ impl GcQueue {
         }
     }
 
+    /// The number of tasks that are tracked for garbage collection.
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
     /// Notify the GC queue that a task has been executed.
     pub fn task_executed(&self, task: TaskId, duration: Duration) {
         // A freshly executed task will start on EmptyUnusedCells, even while we are not
@@ -222,3 +308,27 @@ pub fn to_exp_u8(value: u64) -> u8 {
         .unwrap_or(0x7000_0000_0000_0000)
         .trailing_zeros() as u8
 }
+
+#[cfg(test)]
+mod tests {
+    use super::GcConfig;
+
+    #[test]
+    fn test_collect_factor() {
+        let config = GcConfig::with_memory_limit(1000);
+        assert_eq!(config.target_memory(false), 875);
+        assert_eq!(config.target_memory(true), 750);
+        assert_eq!(config.collect_factor(false, 800, 0), 0);
+        assert_eq!(config.collect_factor(true, 800, 0), 51);
+        assert_eq!(config.collect_factor(false, 2000, 0), u8::MAX);
+
+        let config = GcConfig {
+            max_tasks: Some(100),
+            ..Default::default()
+        };
+        assert!(config.is_enabled());
+        assert_eq!(config.collect_factor(false, 1 << 30, 100), 0);
+        assert_eq!(config.collect_factor(false, 0, 200), 127);
+        assert!(!GcConfig::default().is_enabled());
+    }
+}
@@ -0,0 +1,34 @@
+use std::time::Duration;
+
+use turbo_tasks::{CellId, TaskId};
+
+use crate::GcStats;
+
+/// Callbacks for events in the [MemoryBackend](crate::MemoryBackend), so
+/// embedders can collect their own metrics or traces. Install it with
+/// [MemoryBackend::with_instrumentation](crate::MemoryBackend::with_instrumentation).
+///
+/// All methods do nothing by default. They are called synchronously on the
+/// hot paths of the backend, often while the affected task is locked, so
+/// implementations need to be cheap and must not call back into the backend.
+pub trait BackendInstrumentation: Send + Sync {
+    /// A task starts executing.
+    fn on_task_started(&self, _task: TaskId) {}
+
+    /// A task finished executing after `duration`.
+    fn on_task_finished(&self, _task: TaskId, _duration: Duration) {}
+
+    /// A cell of `task` is read. `reader` is `None` for untracked reads.
+    fn on_cell_read(&self, _task: TaskId, _cell: CellId, _reader: Option<TaskId>) {}
+
+    /// A cell of `task` is written. This is also called when the new value
+    /// equals the old one.
+    fn on_cell_written(&self, _task: TaskId, _cell: CellId) {}
+
+    /// A task is invalidated, either directly or because something it read
+    /// changed.
+    fn on_task_invalidated(&self, _task: TaskId) {}
+
+    /// A garbage collection run finished after `duration`.
+    fn on_gc(&self, _stats: &GcStats, _duration: Duration) {}
+}
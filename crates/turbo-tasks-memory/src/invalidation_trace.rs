@@ -0,0 +1,271 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use serde::Serialize;
+use turbo_tasks::{CellId, TaskId, TaskIdSet, TurboTasksBackendApi};
+
+use crate::MemoryBackend;
+
+/// The maximum number of re-executions that are kept. Older ones are dropped.
+const MAX_REEXECUTIONS: usize = 10_000;
+/// The maximum length of a chain. Protects against cycles in the causes.
+const MAX_CHAIN_LENGTH: usize = 256;
+
+/// Why a task was invalidated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidationCause {
+    /// A cell the task read changed its value.
+    Cell { task: TaskId, cell: CellId },
+    /// The output of a task the task read changed.
+    Output { task: TaskId },
+    /// A task the task read was unloaded by garbage collection.
+    Unloaded { task: TaskId },
+    /// The task was invalidated directly, e.g. by a file watcher.
+    External,
+}
+
+impl InvalidationCause {
+    /// The task whose change caused the invalidation, if any.
+    pub fn source_task(&self) -> Option<TaskId> {
+        match *self {
+            InvalidationCause::Cell { task, .. }
+            | InvalidationCause::Output { task }
+            | InvalidationCause::Unloaded { task } => Some(task),
+            InvalidationCause::External => None,
+        }
+    }
+}
+
+/// One link in an invalidation chain: `task` was invalidated because of
+/// `cause`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidationStep {
+    pub task: TaskId,
+    pub cause: InvalidationCause,
+}
+
+/// A re-execution of a task together with the chain of invalidations that
+/// led to it, starting at the re-executed task and ending at the change that
+/// started it all.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reexecution {
+    pub task: TaskId,
+    pub chain: Vec<InvalidationStep>,
+}
+
+struct RecordedCause {
+    cause: InvalidationCause,
+    /// Whether a re-execution for this cause has already been recorded.
+    reported: bool,
+}
+
+/// Records why tasks re-execute. This is a debugging aid and is disabled by
+/// default, as it keeps an entry for every task that was ever invalidated.
+#[derive(Default)]
+pub struct InvalidationTracer {
+    enabled: AtomicBool,
+    causes: DashMap<TaskId, RecordedCause>,
+    reexecutions: Mutex<VecDeque<Reexecution>>,
+}
+
+impl InvalidationTracer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn enable(&self) {
+        self.enabled.store(true, Ordering::Release);
+    }
+
+    /// Stops tracing and forgets everything that was recorded.
+    pub fn disable(&self) {
+        self.enabled.store(false, Ordering::Release);
+        self.causes.clear();
+        self.reexecutions.lock().clear();
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn record(&self, tasks: impl IntoIterator<Item = TaskId>, cause: InvalidationCause) {
+        if !self.is_enabled() {
+            return;
+        }
+        for task in tasks {
+            self.causes.insert(
+                task,
+                RecordedCause {
+                    cause,
+                    reported: false,
+                },
+            );
+        }
+    }
+
+    /// Called when a task starts executing. Records a re-execution when the
+    /// task was invalidated since it was last reported.
+    pub(crate) fn task_started(&self, task: TaskId) {
+        if !self.is_enabled() {
+            return;
+        }
+        {
+            let Some(mut recorded) = self.causes.get_mut(&task) else {
+                // First execution
+                return;
+            };
+            if recorded.reported {
+                return;
+            }
+            recorded.reported = true;
+        }
+        let chain = self.chain(task);
+        let mut reexecutions = self.reexecutions.lock();
+        if reexecutions.len() >= MAX_REEXECUTIONS {
+            reexecutions.pop_front();
+        }
+        reexecutions.push_back(Reexecution { task, chain });
+    }
+
+    /// The chain of invalidations that led to the last invalidation of `task`.
+    pub fn chain(&self, task: TaskId) -> Vec<InvalidationStep> {
+        let mut chain = Vec::new();
+        let mut visited = HashSet::new();
+        let mut current = task;
+        while chain.len() < MAX_CHAIN_LENGTH && visited.insert(current) {
+            let Some(cause) = self.causes.get(&current).map(|recorded| recorded.cause) else {
+                break;
+            };
+            chain.push(InvalidationStep {
+                task: current,
+                cause,
+            });
+            let Some(source) = cause.source_task() else {
+                break;
+            };
+            current = source;
+        }
+        chain
+    }
+
+    /// The recorded re-executions, oldest first.
+    pub fn reexecutions(&self) -> Vec<Reexecution> {
+        self.reexecutions.lock().iter().cloned().collect()
+    }
+
+    /// Serializes [Self::reexecutions] as JSON, with tasks replaced by their
+    /// descriptions.
+    pub fn to_json(&self, backend: &MemoryBackend) -> serde_json::Result<String> {
+        #[derive(Serialize)]
+        struct JsonReexecution {
+            task: String,
+            chain: Vec<JsonStep>,
+        }
+
+        #[derive(Serialize)]
+        struct JsonStep {
+            task: String,
+            cause: &'static str,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            source: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            cell: Option<String>,
+        }
+
+        let describe = |task: TaskId| backend.with_task(task, |task| task.get_description());
+        let reexecutions = self
+            .reexecutions()
+            .into_iter()
+            .map(|reexecution| JsonReexecution {
+                task: describe(reexecution.task),
+                chain: reexecution
+                    .chain
+                    .into_iter()
+                    .map(|step| JsonStep {
+                        task: describe(step.task),
+                        cause: match step.cause {
+                            InvalidationCause::Cell { .. } => "cell",
+                            InvalidationCause::Output { .. } => "output",
+                            InvalidationCause::Unloaded { .. } => "unloaded",
+                            InvalidationCause::External => "external",
+                        },
+                        source: step.cause.source_task().map(describe),
+                        cell: match step.cause {
+                            InvalidationCause::Cell { cell, .. } => Some(cell.to_string()),
+                            _ => None,
+                        },
+                    })
+                    .collect(),
+            })
+            .collect::<Vec<_>>();
+        serde_json::to_string_pretty(&reexecutions)
+    }
+}
+
+/// Records `cause` for `tasks` and schedules them to be notified.
+pub(crate) fn notify_tasks(
+    tasks: &TaskIdSet,
+    cause: InvalidationCause,
+    turbo_tasks: &dyn TurboTasksBackendApi<MemoryBackend>,
+) {
+    turbo_tasks
+        .backend()
+        .invalidation_tracer()
+        .record(tasks.iter().copied(), cause);
+    turbo_tasks.schedule_notify_tasks_set(tasks);
+}
+
+#[cfg(test)]
+mod tests {
+    use turbo_tasks::TaskId;
+
+    use super::{InvalidationCause, InvalidationStep, InvalidationTracer};
+
+    fn id(id: usize) -> TaskId {
+        TaskId::from(id)
+    }
+
+    #[test]
+    fn test_chain() {
+        let tracer = InvalidationTracer::new();
+        tracer.record([id(1)], InvalidationCause::External);
+        tracer.task_started(id(1));
+        assert!(tracer.reexecutions().is_empty());
+
+        tracer.enable();
+        tracer.record([id(1)], InvalidationCause::External);
+        tracer.record([id(2), id(3)], InvalidationCause::Output { task: id(1) });
+        tracer.record([id(4)], InvalidationCause::Unloaded { task: id(3) });
+
+        tracer.task_started(id(4));
+        tracer.task_started(id(4));
+        tracer.task_started(id(5));
+        let reexecutions = tracer.reexecutions();
+        assert_eq!(reexecutions.len(), 1);
+        assert_eq!(
+            reexecutions[0].chain,
+            vec![
+                InvalidationStep {
+                    task: id(4),
+                    cause: InvalidationCause::Unloaded { task: id(3) }
+                },
+                InvalidationStep {
+                    task: id(3),
+                    cause: InvalidationCause::Output { task: id(1) }
+                },
+                InvalidationStep {
+                    task: id(1),
+                    cause: InvalidationCause::External
+                },
+            ]
+        );
+
+        // Cycles end the chain
+        tracer.record([id(1)], InvalidationCause::Output { task: id(4) });
+        assert_eq!(tracer.chain(id(4)).len(), 3);
+    }
+}
@@ -7,18 +7,29 @@
 #![feature(impl_trait_in_assoc_type)]
 #![deny(unsafe_op_in_unsafe_fn)]
 
+pub mod aggregation_stats;
 mod aggregation_tree;
 mod cell;
 mod concurrent_priority_queue;
 mod count_hash_set;
+pub mod determinism;
+mod disk_persisted_graph;
+pub mod function_stats;
 mod gc;
+pub mod instrumentation;
+pub mod invalidation_trace;
 mod map_guard;
 mod memory_backend;
 mod memory_backend_with_pg;
+pub mod memory_stats;
 mod output;
+pub mod snapshot;
 pub mod stats;
 mod task;
 pub mod viz;
 
+pub use aggregation_tree::AggregationTreeConfig;
+pub use disk_persisted_graph::DiskPersistedGraph;
+pub use gc::{GcConfig, GcStats};
 pub use memory_backend::MemoryBackend;
 pub use memory_backend_with_pg::MemoryBackendWithPersistedGraph;
@@ -25,13 +25,19 @@ use turbo_tasks::{
     },
     event::EventListener,
     util::{IdFactory, NoMoveVec},
-    CellId, RawVc, TaskId, TaskIdSet, TraitTypeId, TurboTasksBackendApi, Unused,
+    CellId, FunctionId, RawVc, TaskId, TaskIdSet, TraitTypeId, TurboTasksBackendApi, Unused,
 };
 
 use crate::{
+    aggregation_stats::AggregationStatsCollector,
+    aggregation_tree::AggregationTreeConfig,
     cell::RecomputingCell,
-    gc::GcQueue,
-    output::Output,
+    determinism::DeterminismVerifier,
+    function_stats::FunctionStatsCollector,
+    gc::{GcConfig, GcQueue},
+    instrumentation::BackendInstrumentation,
+    invalidation_trace::{InvalidationCause, InvalidationTracer},
+    output::{Output, OutputContent},
     task::{Task, TaskDependency, TaskDependencySet, DEPENDENCIES_TO_TRACK},
 };
 
@@ -40,9 +46,16 @@ pub struct MemoryBackend {
     backend_jobs: NoMoveVec<Job>,
     backend_job_id_factory: IdFactory<BackendJobId>,
     task_cache: DashMap<Arc<PersistentTaskType>, TaskId, BuildHasherDefault<FxHasher>>,
-    memory_limit: usize,
+    gc_config: GcConfig,
     gc_queue: Option<GcQueue>,
     idle_gc_active: AtomicBool,
+    gc_requested: AtomicBool,
+    function_stats: FunctionStatsCollector,
+    invalidation_tracer: InvalidationTracer,
+    determinism_verifier: DeterminismVerifier,
+    aggregation_config: AggregationTreeConfig,
+    aggregation_stats: AggregationStatsCollector,
+    instrumentation: Option<Arc<dyn BackendInstrumentation>>,
 }
 
 impl Default for MemoryBackend {
@@ -53,6 +66,10 @@ impl Default for MemoryBackend {
 
 impl MemoryBackend {
     pub fn new(memory_limit: usize) -> Self {
+        Self::with_gc_config(GcConfig::with_memory_limit(memory_limit))
+    }
+
+    pub fn with_gc_config(gc_config: GcConfig) -> Self {
         Self {
             memory_tasks: NoMoveVec::new(),
             backend_jobs: NoMoveVec::new(),
@@ -62,9 +79,40 @@ impl MemoryBackend {
                 (std::thread::available_parallelism().map_or(1, usize::from) * 32)
                     .next_power_of_two(),
             ),
-            memory_limit,
-            gc_queue: (memory_limit != usize::MAX).then(GcQueue::new),
+            gc_queue: gc_config.is_enabled().then(GcQueue::new),
+            gc_config,
             idle_gc_active: AtomicBool::new(false),
+            gc_requested: AtomicBool::new(false),
+            function_stats: FunctionStatsCollector::new(),
+            invalidation_tracer: InvalidationTracer::new(),
+            determinism_verifier: DeterminismVerifier::new(),
+            aggregation_config: AggregationTreeConfig::default(),
+            aggregation_stats: AggregationStatsCollector::new(),
+            instrumentation: None,
+        }
+    }
+
+    /// Tunes the task aggregation tree. The defaults work well for most
+    /// graphs. See [Self::aggregation_stats] to measure the effect.
+    pub fn with_aggregation_config(mut self, aggregation_config: AggregationTreeConfig) -> Self {
+        self.aggregation_config = aggregation_config;
+        self
+    }
+
+    /// Reports backend events to `instrumentation`, e.g. to collect custom
+    /// metrics.
+    pub fn with_instrumentation(
+        mut self,
+        instrumentation: Arc<dyn BackendInstrumentation>,
+    ) -> Self {
+        self.instrumentation = Some(instrumentation);
+        self
+    }
+
+    #[inline(always)]
+    pub(crate) fn instrument(&self, func: impl FnOnce(&dyn BackendInstrumentation)) {
+        if let Some(instrumentation) = &self.instrumentation {
+            func(&**instrumentation);
         }
     }
 
@@ -130,19 +178,60 @@ impl MemoryBackend {
         }
     }
 
+    /// Per-function statistics. Collection needs to be enabled with
+    /// [FunctionStatsCollector::enable].
+    pub fn function_stats(&self) -> &FunctionStatsCollector {
+        &self.function_stats
+    }
+
+    /// Records why tasks re-execute. Tracing needs to be enabled with
+    /// [InvalidationTracer::enable].
+    pub fn invalidation_tracer(&self) -> &InvalidationTracer {
+        &self.invalidation_tracer
+    }
+
+    /// Re-executes a sample of tasks to find functions that aren't
+    /// deterministic. Verification needs to be enabled with
+    /// [DeterminismVerifier::enable].
+    pub fn determinism_verifier(&self) -> &DeterminismVerifier {
+        &self.determinism_verifier
+    }
+
+    pub fn gc_config(&self) -> &GcConfig {
+        &self.gc_config
+    }
+
+    pub fn aggregation_config(&self) -> &AggregationTreeConfig {
+        &self.aggregation_config
+    }
+
+    /// Counters for the maintenance of the task aggregation tree. Collection
+    /// needs to be enabled with [AggregationStatsCollector::enable].
+    pub fn aggregation_stats(&self) -> &AggregationStatsCollector {
+        &self.aggregation_stats
+    }
+
+    /// Requests a garbage collection regardless of the configured targets,
+    /// e.g. when the allocator or the OS reports memory pressure. The
+    /// collection runs as a background job. Does nothing when garbage
+    /// collection is disabled.
+    pub fn collect_garbage(&self, turbo_tasks: &dyn TurboTasksBackendApi<MemoryBackend>) {
+        if self.gc_queue.is_some() && !self.gc_requested.swap(true, Ordering::AcqRel) {
+            let job = self.create_backend_job(Job::GarbageCollection);
+            turbo_tasks.schedule_backend_background_job(job);
+        }
+    }
+
     pub fn run_gc(&self, idle: bool, turbo_tasks: &dyn TurboTasksBackendApi<MemoryBackend>) {
         if let Some(gc_queue) = &self.gc_queue {
             const MAX_COLLECT_FACTOR: u8 = u8::MAX / 8;
 
-            let mem_limit = self.memory_limit;
-
             let usage = turbo_tasks_malloc::TurboMalloc::memory_usage();
-            let target = if idle {
-                mem_limit * 3 / 4
-            } else {
-                mem_limit * 7 / 8
-            };
-            if usage < target {
+            let mut collect_factor = self.gc_config.collect_factor(idle, usage, gc_queue.len());
+            if self.gc_requested.swap(false, Ordering::AcqRel) {
+                collect_factor = MAX_COLLECT_FACTOR;
+            }
+            if collect_factor == 0 {
                 if idle {
                     // Always run propagation when idle
                     gc_queue.run_gc(0, self, turbo_tasks);
@@ -150,12 +239,13 @@ impl MemoryBackend {
                 return;
             }
 
-            let collect_factor = min(
-                MAX_COLLECT_FACTOR as usize,
-                (usage - target) * u8::MAX as usize / (mem_limit - target),
-            ) as u8;
+            let collect_factor = min(MAX_COLLECT_FACTOR, collect_factor);
 
+            let start = self.instrumentation.is_some().then(Instant::now);
             let collected = gc_queue.run_gc(collect_factor, self, turbo_tasks);
+            if let (Some(start), Some((_, _, stats))) = (start, &collected) {
+                self.instrument(|i| i.on_gc(stats, start.elapsed()));
+            }
 
             if idle {
                 if let Some((_collected, _count, _stats)) = collected {
@@ -200,6 +290,45 @@ impl MemoryBackend {
         result_task
     }
 
+    /// Looks up the task for `task_type` or creates it, without connecting it
+    /// to a parent. Returns whether the task was created.
+    pub(crate) fn get_or_create_unconnected_persistent_task(
+        &self,
+        mut task_type: PersistentTaskType,
+        turbo_tasks: &dyn TurboTasksBackendApi<MemoryBackend>,
+    ) -> (TaskId, bool) {
+        if let Some(task) = self.task_cache.get(&task_type) {
+            return (*task, false);
+        }
+        task_type.shrink_to_fit();
+        let task_type = Arc::new(task_type);
+        let id = turbo_tasks.get_fresh_task_id();
+        let task = Task::new_persistent(
+            // Safety: That task will hold the value, but we are still in
+            // control of the task
+            *unsafe { id.get_unchecked() },
+            task_type.clone(),
+            turbo_tasks.stats_type(),
+        );
+        let id = id.into();
+        // Safety: We have a fresh task id that nobody knows about yet
+        unsafe { self.memory_tasks.insert(*id, task) };
+        match self.task_cache.entry(task_type) {
+            Entry::Vacant(entry) => {
+                entry.insert(id);
+                (id, true)
+            }
+            Entry::Occupied(entry) => {
+                // Safety: We have a fresh task id that nobody knows about yet
+                unsafe {
+                    self.memory_tasks.remove(*id);
+                    turbo_tasks.reuse_task_id(Unused::new_unchecked(id));
+                }
+                (*entry.get(), false)
+            }
+        }
+    }
+
     fn lookup_and_connect_task<K: Hash + Eq, Q, H: BuildHasher + Clone>(
         &self,
         parent_task: TaskId,
@@ -244,6 +373,9 @@ impl Backend for MemoryBackend {
     }
 
     fn invalidate_task(&self, task: TaskId, turbo_tasks: &dyn TurboTasksBackendApi<MemoryBackend>) {
+        self.invalidation_tracer
+            .record([task], InvalidationCause::External);
+        self.instrument(|i| i.on_task_invalidated(task));
         self.with_task(task, |task| task.invalidate(self, turbo_tasks));
     }
 
@@ -253,6 +385,7 @@ impl Backend for MemoryBackend {
         turbo_tasks: &dyn TurboTasksBackendApi<MemoryBackend>,
     ) {
         for &task in tasks {
+            self.instrument(|i| i.on_task_invalidated(task));
             self.with_task(task, |task| {
                 task.invalidate(self, turbo_tasks);
             });
@@ -265,6 +398,7 @@ impl Backend for MemoryBackend {
         turbo_tasks: &dyn TurboTasksBackendApi<MemoryBackend>,
     ) {
         for &task in tasks {
+            self.instrument(|i| i.on_task_invalidated(task));
             self.with_task(task, |task| {
                 task.invalidate(self, turbo_tasks);
             });
@@ -275,6 +409,10 @@ impl Backend for MemoryBackend {
         self.with_task(task, |task| task.get_description())
     }
 
+    fn get_task_function(&self, task: TaskId) -> Option<FunctionId> {
+        self.with_task(task, |task| task.function_id())
+    }
+
     type ExecutionScopeFuture<T: Future<Output = Result<()>> + Send + 'static> =
         TaskLocalFuture<RefCell<TaskDependencySet>, T>;
     fn execution_scope<T: Future<Output = Result<()>> + Send + 'static>(
@@ -290,7 +428,12 @@ impl Backend for MemoryBackend {
         task: TaskId,
         turbo_tasks: &dyn TurboTasksBackendApi<MemoryBackend>,
     ) -> Option<TaskExecutionSpec> {
-        self.with_task(task, |task| task.execute(self, turbo_tasks))
+        let spec = self.with_task(task, |task| task.execute(self, turbo_tasks));
+        if spec.is_some() {
+            self.invalidation_tracer.task_started(task);
+            self.instrument(|i| i.on_task_started(task));
+        }
+        spec
     }
 
     fn task_execution_result(
@@ -306,6 +449,20 @@ impl Backend for MemoryBackend {
                     panic!("Task {} returned itself as output", task.get_description());
                 }
             }
+            if self.determinism_verifier.is_verifying(task_id) {
+                if let (Some(function), Ok(Ok(new))) = (task.function_id(), result.as_ref()) {
+                    let old = task.with_output_mut_if_available(|output| match output.content {
+                        OutputContent::Link(old) => Some(old),
+                        _ => None,
+                    });
+                    self.determinism_verifier.compare_output(
+                        task_id,
+                        function,
+                        old.flatten(),
+                        *new,
+                    );
+                }
+            }
             task.execution_result(result, self, turbo_tasks);
         })
     }
@@ -318,10 +475,22 @@ impl Backend for MemoryBackend {
         stateful: bool,
         turbo_tasks: &dyn TurboTasksBackendApi<MemoryBackend>,
     ) -> bool {
+        self.instrument(|i| i.on_task_finished(task_id, duration));
         let reexecute = self.with_task(task_id, |task| {
+            if let Some(function) = task.function_id() {
+                self.function_stats.record_execution(function, duration);
+            }
             task.execution_completed(duration, instant, stateful, self, turbo_tasks)
         });
         if !reexecute {
+            let verified = self.determinism_verifier.finish(task_id);
+            if !verified && !stateful && self.determinism_verifier.is_enabled() {
+                self.with_task(task_id, |task| {
+                    if task.function_id().is_some() && self.determinism_verifier.sample(task_id) {
+                        task.invalidate(self, turbo_tasks);
+                    }
+                });
+            }
             self.run_gc(false, turbo_tasks);
             if let Some(gc_queue) = &self.gc_queue {
                 gc_queue.task_executed(task_id, duration);
@@ -330,6 +499,19 @@ impl Backend for MemoryBackend {
         reexecute
     }
 
+    fn task_execution_cancelled(
+        &self,
+        task_id: TaskId,
+        duration: Duration,
+        instant: Instant,
+        stateful: bool,
+        turbo_tasks: &dyn TurboTasksBackendApi<MemoryBackend>,
+    ) {
+        self.with_task(task_id, |task| {
+            task.execution_cancelled(duration, instant, stateful, self, turbo_tasks)
+        });
+    }
+
     fn try_read_task_output(
         &self,
         task: TaskId,
@@ -352,6 +534,14 @@ impl Backend for MemoryBackend {
         )
     }
 
+    fn get_unfinished_tasks(
+        &self,
+        task: TaskId,
+        turbo_tasks: &dyn TurboTasksBackendApi<MemoryBackend>,
+    ) -> Option<usize> {
+        Some(self.with_task(task, |task| task.get_unfinished_tasks(self, turbo_tasks)))
+    }
+
     fn try_read_task_output_untracked(
         &self,
         task: TaskId,
@@ -374,6 +564,7 @@ impl Backend for MemoryBackend {
         reader: TaskId,
         turbo_tasks: &dyn TurboTasksBackendApi<MemoryBackend>,
     ) -> Result<Result<CellContent, EventListener>> {
+        self.instrument(|i| i.on_cell_read(task_id, index, Some(reader)));
         if task_id == reader {
             Ok(Ok(self.with_task(task_id, |task| {
                 task.with_cell(index, |cell| cell.read_own_content_untracked())
@@ -417,6 +608,7 @@ impl Backend for MemoryBackend {
         index: CellId,
         turbo_tasks: &dyn TurboTasksBackendApi<MemoryBackend>,
     ) -> Result<Result<CellContent, EventListener>> {
+        self.instrument(|i| i.on_cell_read(task_id, index, None));
         self.with_task(task_id, |task| {
             match task.with_cell_mut(index, |cell| {
                 cell.read_content_untracked(
@@ -445,6 +637,17 @@ impl Backend for MemoryBackend {
         Task::read_collectibles(id, trait_id, reader, self, turbo_tasks)
     }
 
+    fn read_task_collectibles_filtered(
+        &self,
+        id: TaskId,
+        trait_id: TraitTypeId,
+        reader: TaskId,
+        filter: &mut dyn FnMut(RawVc) -> bool,
+        turbo_tasks: &dyn TurboTasksBackendApi<MemoryBackend>,
+    ) -> AutoMap<RawVc, i32> {
+        Task::read_collectibles_filtered(id, trait_id, reader, filter, self, turbo_tasks)
+    }
+
     fn emit_collectible(
         &self,
         trait_type: TraitTypeId,
@@ -477,8 +680,23 @@ impl Backend for MemoryBackend {
         content: CellContent,
         turbo_tasks: &dyn TurboTasksBackendApi<MemoryBackend>,
     ) {
-        self.with_task(task, |task| {
-            task.with_cell_mut(index, |cell| cell.assign(content, turbo_tasks))
+        self.instrument(|i| i.on_cell_written(task, index));
+        let cause = InvalidationCause::Cell { task, cell: index };
+        let verifying = self.determinism_verifier.is_verifying(task);
+        self.with_task(task, |task_ref| {
+            let function = task_ref.function_id().filter(|_| verifying);
+            task_ref.with_cell_mut(index, |cell| {
+                if let Some(function) = function {
+                    self.determinism_verifier.compare_cell(
+                        task,
+                        function,
+                        index,
+                        &cell.read_own_content_untracked(),
+                        &content,
+                    );
+                }
+                cell.assign(content, cause, turbo_tasks)
+            })
         })
     }
 
@@ -508,9 +726,12 @@ impl Backend for MemoryBackend {
         parent_task: TaskId,
         turbo_tasks: &dyn TurboTasksBackendApi<MemoryBackend>,
     ) -> TaskId {
-        if let Some(task) =
-            self.lookup_and_connect_task(parent_task, &self.task_cache, &task_type, turbo_tasks)
-        {
+        let lookup =
+            self.lookup_and_connect_task(parent_task, &self.task_cache, &task_type, turbo_tasks);
+        if let PersistentTaskType::Native(function, _) = &task_type {
+            self.function_stats.record_call(*function, lookup.is_some());
+        }
+        if let Some(task) = lookup {
             // fast pass without creating a new task
             task
         } else {
@@ -0,0 +1,192 @@
+use std::{collections::HashMap, io};
+
+use serde::Serialize;
+use turbo_tasks::{backend::CellContent, registry, TaskId, ValueTypeId};
+
+use crate::MemoryBackend;
+
+/// How many of the largest instances are kept per function and cell type.
+const MAX_LARGEST: usize = 10;
+
+/// Memory used by the instances of a function or cell type.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MemoryStatsEntry {
+    pub count: usize,
+    pub bytes: usize,
+    /// The largest instances with their size, largest first. Instances are
+    /// identified by the task that owns them.
+    pub largest: Vec<(TaskId, usize)>,
+}
+
+impl MemoryStatsEntry {
+    fn add(&mut self, task: TaskId, bytes: usize) {
+        self.count += 1;
+        self.bytes += bytes;
+        if self.largest.len() == MAX_LARGEST
+            && self.largest.last().is_some_and(|&(_, min)| min >= bytes)
+        {
+            return;
+        }
+        let index = self.largest.partition_point(|&(_, size)| size >= bytes);
+        self.largest.insert(index, (task, bytes));
+        self.largest.truncate(MAX_LARGEST);
+    }
+}
+
+/// Estimated memory usage of the backend, per task function and per cell type.
+///
+/// The sizes are estimates: tasks and cells are measured by the size of their
+/// structs and collections, and cell contents by their serialized size. The
+/// contents of cells that can't be serialized are not included.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStats {
+    /// Keyed by the task type, e.g. the function name. Includes the cells of
+    /// the tasks.
+    pub functions: HashMap<String, MemoryStatsEntry>,
+    pub cell_types: HashMap<ValueTypeId, MemoryStatsEntry>,
+}
+
+impl MemoryStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Collects the memory usage of all cached tasks of `backend`.
+    pub fn collect(backend: &MemoryBackend) -> Self {
+        let mut stats = Self::new();
+        backend.with_all_cached_tasks(|task| stats.add_id(backend, task));
+        stats
+    }
+
+    pub fn add_id(&mut self, backend: &MemoryBackend, id: TaskId) {
+        backend.with_task(id, |task| {
+            let mut bytes_of_cells = 0;
+            let mut bytes = task.memory_usage(|ty, cell_bytes| {
+                bytes_of_cells += cell_bytes;
+                self.cell_types.entry(ty).or_default().add(id, cell_bytes);
+            });
+            bytes += bytes_of_cells;
+            self.functions
+                .entry(task.get_stats_type().to_string())
+                .or_default()
+                .add(id, bytes);
+        });
+    }
+
+    pub fn total_bytes(&self) -> usize {
+        self.functions.values().map(|entry| entry.bytes).sum()
+    }
+
+    /// Serializes the statistics as JSON, sorted by size, largest first. Tasks
+    /// are replaced by their descriptions.
+    pub fn to_json(&self, backend: &MemoryBackend) -> serde_json::Result<String> {
+        #[derive(Serialize)]
+        struct JsonStats {
+            total_bytes: usize,
+            functions: Vec<JsonEntry>,
+            cell_types: Vec<JsonEntry>,
+        }
+
+        #[derive(Serialize)]
+        struct JsonEntry {
+            name: String,
+            count: usize,
+            bytes: usize,
+            largest: Vec<JsonInstance>,
+        }
+
+        #[derive(Serialize)]
+        struct JsonInstance {
+            task: String,
+            bytes: usize,
+        }
+
+        let entries = |entries: Vec<(String, &MemoryStatsEntry)>| {
+            let mut entries = entries
+                .into_iter()
+                .map(|(name, entry)| JsonEntry {
+                    name,
+                    count: entry.count,
+                    bytes: entry.bytes,
+                    largest: entry
+                        .largest
+                        .iter()
+                        .map(|&(task, bytes)| JsonInstance {
+                            task: backend.with_task(task, |task| task.get_description()),
+                            bytes,
+                        })
+                        .collect(),
+                })
+                .collect::<Vec<_>>();
+            entries.sort_by(|a, b| b.bytes.cmp(&a.bytes).then_with(|| a.name.cmp(&b.name)));
+            entries
+        };
+        serde_json::to_string_pretty(&JsonStats {
+            total_bytes: self.total_bytes(),
+            functions: entries(
+                self.functions
+                    .iter()
+                    .map(|(name, entry)| (name.clone(), entry))
+                    .collect(),
+            ),
+            cell_types: entries(
+                self.cell_types
+                    .iter()
+                    .map(|(ty, entry)| (registry::get_value_type(*ty).name.clone(), entry))
+                    .collect(),
+            ),
+        })
+    }
+}
+
+/// Counts the bytes written to it.
+struct CountingWriter(usize);
+
+impl io::Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Estimates the heap memory used by the content of a cell by its serialized
+/// size. Returns 0 for content that can't be serialized.
+pub(crate) fn estimate_content_size(content: &CellContent) -> usize {
+    let Some(value) = &content.0 else {
+        return 0;
+    };
+    let mut writer = CountingWriter(0);
+    match serde_json::to_writer(&mut writer, value) {
+        Ok(()) => writer.0,
+        Err(_) => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use turbo_tasks::TaskId;
+
+    use super::{MemoryStatsEntry, MAX_LARGEST};
+
+    #[test]
+    fn test_largest() {
+        let mut entry = MemoryStatsEntry::default();
+        for i in 0..100 {
+            entry.add(TaskId::from(i + 1), i * 7 % 100);
+        }
+        assert_eq!(entry.count, 100);
+        assert_eq!(entry.bytes, (0..100).sum());
+        assert_eq!(
+            entry
+                .largest
+                .iter()
+                .map(|&(_, bytes)| bytes)
+                .collect::<Vec<_>>(),
+            (100 - MAX_LARGEST..100).rev().collect::<Vec<_>>()
+        );
+    }
+}
@@ -61,7 +61,16 @@ impl Output {
     }
 
     pub fn error(&mut self, error: Error, turbo_tasks: &dyn TurboTasksBackendApi<MemoryBackend>) {
-        self.content = OutputContent::Error(SharedError::new(error));
+        let error = SharedError::new(error);
+        if let OutputContent::Error(old) = &self.content {
+            if old.is_same_structured_error(&error) {
+                // Dependent tasks would read the same error, with the same
+                // context, again
+                self.content = OutputContent::Error(error);
+                return;
+            }
+        }
+        self.content = OutputContent::Error(error);
         self.updates += 1;
         // notify
         if !self.dependent_tasks.is_empty() {
@@ -0,0 +1,294 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    fs, io,
+    path::Path,
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use turbo_tasks::{
+    backend::{CellContent, PersistentTaskType},
+    persisted_graph::PersistedGraphApi,
+    with_task_id_mapping, CellId, RawVc, TaskId, TraitTypeId, TurboTasksBackendApi,
+};
+
+use crate::{
+    disk_persisted_graph::{RestoreMapping, SaveMapping},
+    task::{Task, TaskDependency},
+    MemoryBackend,
+};
+
+/// Bumped whenever the snapshot layout changes. Snapshots with a different
+/// version are ignored.
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// How a task is included in a snapshot of a [MemoryBackend].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotMode {
+    /// The task is left out. Tasks that depend on it are executed again after
+    /// restoring.
+    Exclude,
+    /// The task is restored as it was.
+    Include,
+    /// The task is restored with its cells, but executed again after
+    /// restoring. Use this for tasks that read external state, like files,
+    /// that might have changed in the meantime. Tasks that depend on it are
+    /// only invalidated when it produces different values.
+    Revalidate,
+}
+
+/// The state of a task that is written to a snapshot.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct TaskSnapshot {
+    pub output: RawVc,
+    pub cells: Vec<(CellId, CellContent)>,
+    pub dependencies: Vec<TaskDependency>,
+    pub children: Vec<TaskId>,
+    pub collectibles: Vec<(TraitTypeId, RawVc, i32)>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    version: u32,
+    /// Serialized [PersistentTaskType]s, indexed by the ids used for tasks
+    /// within the snapshot.
+    task_types: Vec<Value>,
+    tasks: Vec<SnapshotEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SnapshotEntry {
+    /// A serialized `(TaskId, TaskSnapshot)`.
+    task: Value,
+    /// The task needs to be executed again after restoring.
+    dirty: bool,
+}
+
+impl MemoryBackend {
+    /// Writes the cached tasks to a snapshot file, which another process can
+    /// restore with [MemoryBackend::restore_snapshot] to start with warm
+    /// caches. `mode` decides how each task is included. Returns the number
+    /// of tasks written.
+    ///
+    /// Only tasks that finished successfully are written. Tasks that hold
+    /// state or have values that can't be serialized are left out, like
+    /// [SnapshotMode::Exclude]d tasks. Call this while no tasks are executing,
+    /// e.g. after stopping turbo-tasks, so the snapshot is consistent.
+    pub fn save_snapshot(
+        &self,
+        path: &Path,
+        mut mode: impl FnMut(&PersistentTaskType) -> SnapshotMode,
+    ) -> Result<usize> {
+        let mapping = SaveMapping::new();
+        let snapshot = with_task_id_mapping(&mapping, || {
+            let mut tasks = Vec::new();
+            self.with_all_cached_tasks(|id| {
+                let task = self.task(id);
+                let Some(task_type) = task.persistent_task_type() else {
+                    return;
+                };
+                let revalidate = match mode(task_type) {
+                    SnapshotMode::Exclude => return,
+                    SnapshotMode::Include => false,
+                    SnapshotMode::Revalidate => true,
+                };
+                let Some(snapshot) = task.snapshot() else {
+                    return;
+                };
+                let Ok(value) = serde_json::to_value((id, &snapshot)) else {
+                    return;
+                };
+                tasks.push((id, value, revalidate, snapshot.dependencies));
+            });
+
+            // Tasks that depend on tasks that are left out can't be restored
+            // as they were
+            let included = tasks.iter().map(|&(id, ..)| id).collect::<HashSet<_>>();
+            let tasks = tasks
+                .into_iter()
+                .map(|(_, task, revalidate, dependencies)| SnapshotEntry {
+                    task,
+                    dirty: revalidate || !all_restored(&dependencies, &included),
+                })
+                .collect();
+
+            // Serializing a task type can discover more tasks, so this can't
+            // be a simple iterator
+            let mut task_types = Vec::new();
+            while task_types.len() < mapping.order.borrow().len() {
+                let task = mapping.order.borrow()[task_types.len()];
+                let task_type = self
+                    .task(task)
+                    .persistent_task_type()
+                    .and_then(|task_type| serde_json::to_value(&**task_type).ok());
+                task_types.push(task_type.unwrap_or(Value::Null));
+            }
+
+            Snapshot {
+                version: SNAPSHOT_VERSION,
+                task_types,
+                tasks,
+            }
+        });
+        let count = snapshot.tasks.len();
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // Write to a temporary file first so a crash never leaves a partial
+        // snapshot behind
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, serde_json::to_vec(&snapshot)?)
+            .and_then(|()| fs::rename(&tmp_path, path))
+            .with_context(|| format!("failed to write snapshot {}", path.display()))?;
+        Ok(count)
+    }
+
+    /// Restores a snapshot written by [MemoryBackend::save_snapshot]. Call
+    /// this before any task is executed. Returns the number of restored
+    /// tasks.
+    ///
+    /// A missing, outdated or corrupted snapshot is ignored, so the process
+    /// only starts cold. Tasks that already exist and tasks of functions that
+    /// no longer exist are not restored.
+    pub fn restore_snapshot(
+        &self,
+        path: &Path,
+        turbo_tasks: &dyn TurboTasksBackendApi<MemoryBackend>,
+    ) -> Result<usize> {
+        let contents = match fs::read(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(0),
+            Err(err) => {
+                return Err(err)
+                    .with_context(|| format!("failed to read snapshot {}", path.display()))
+            }
+        };
+        let snapshot = match serde_json::from_slice::<Snapshot>(&contents) {
+            Ok(snapshot) if snapshot.version == SNAPSHOT_VERSION => snapshot,
+            _ => return Ok(0),
+        };
+
+        let api = RestoreApi {
+            backend: self,
+            turbo_tasks,
+            created: RefCell::new(HashSet::new()),
+        };
+        let mapping = RestoreMapping::new(&api, &snapshot.task_types);
+        let entries = with_task_id_mapping(&mapping, || {
+            snapshot
+                .tasks
+                .iter()
+                .filter_map(|entry| {
+                    let (id, task) = mapping.deserialize::<(TaskId, TaskSnapshot)>(&entry.task)?;
+                    Some((id, (task, entry.dirty)))
+                })
+                .collect::<Vec<_>>()
+        });
+        // Only tasks created while restoring are guaranteed to be unused
+        let created = api.created.into_inner();
+        let tasks = entries
+            .into_iter()
+            .filter(|(id, _)| created.contains(id))
+            .collect::<HashMap<_, _>>();
+        let restored = tasks.keys().copied().collect::<HashSet<_>>();
+
+        let mut edges = Vec::new();
+        for (id, (task, dirty)) in tasks {
+            let dirty = dirty || !all_restored(&task.dependencies, &restored);
+            let dependencies = if dirty {
+                Vec::new()
+            } else {
+                task.dependencies.clone()
+            };
+            let children = task
+                .children
+                .iter()
+                .copied()
+                .filter(|child| restored.contains(child))
+                .collect::<Vec<_>>();
+            self.task(id).restore(task, dirty);
+            edges.push((id, dependencies, children));
+        }
+        for (id, dependencies, children) in edges {
+            for dependency in dependencies {
+                Task::add_dependent(dependency, id, self);
+            }
+            for child in children {
+                self.task(id).connect_child(child, self, turbo_tasks);
+            }
+        }
+        Ok(restored.len())
+    }
+}
+
+/// Whether all `dependencies` are on tasks in `restored`. Dependencies on
+/// collectibles are never restored.
+fn all_restored(dependencies: &[TaskDependency], restored: &HashSet<TaskId>) -> bool {
+    dependencies.iter().all(|dependency| match dependency {
+        TaskDependency::Output(task) | TaskDependency::Cell(task, _) => restored.contains(task),
+        TaskDependency::Collectibles(..) => false,
+    })
+}
+
+/// Creates the tasks referred to by a snapshot, remembering which ones are
+/// new.
+struct RestoreApi<'a> {
+    backend: &'a MemoryBackend,
+    turbo_tasks: &'a dyn TurboTasksBackendApi<MemoryBackend>,
+    created: RefCell<HashSet<TaskId>>,
+}
+
+impl PersistedGraphApi for RestoreApi<'_> {
+    fn get_or_create_task_type(&self, ty: PersistentTaskType) -> TaskId {
+        let (task, created) = self
+            .backend
+            .get_or_create_unconnected_persistent_task(ty, self.turbo_tasks);
+        if created {
+            self.created.borrow_mut().insert(task);
+        }
+        task
+    }
+
+    fn lookup_task_type(&self, id: TaskId) -> &PersistentTaskType {
+        self.backend
+            .task(id)
+            .persistent_task_type()
+            .expect("only persistent tasks are restored")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use turbo_tasks::{CellId, TaskId, ValueTypeId};
+
+    use super::all_restored;
+    use crate::task::TaskDependency;
+
+    #[test]
+    fn test_all_restored() {
+        let restored = [TaskId::from(1), TaskId::from(2)]
+            .into_iter()
+            .collect::<HashSet<_>>();
+        let cell = CellId {
+            type_id: ValueTypeId::from(1),
+            index: 0,
+        };
+        assert!(all_restored(&[], &restored));
+        assert!(all_restored(
+            &[
+                TaskDependency::Output(TaskId::from(1)),
+                TaskDependency::Cell(TaskId::from(2), cell)
+            ],
+            &restored
+        ));
+        assert!(!all_restored(
+            &[TaskDependency::Cell(TaskId::from(3), cell)],
+            &restored
+        ));
+    }
+}
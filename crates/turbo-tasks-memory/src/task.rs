@@ -12,7 +12,7 @@ use std::{
     },
     future::Future,
     hash::{BuildHasherDefault, Hash},
-    mem::{replace, take},
+    mem::{replace, size_of, take},
     pin::Pin,
     sync::Arc,
     time::{Duration, Instant},
@@ -23,6 +23,7 @@ use auto_hash_map::{AutoMap, AutoSet};
 use nohash_hasher::BuildNoHashHasher;
 use parking_lot::{Mutex, RwLock};
 use rustc_hash::FxHasher;
+use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
 use stats::TaskStats;
 use tokio::task_local;
@@ -30,15 +31,17 @@ use tracing::Span;
 use turbo_tasks::{
     backend::{PersistentTaskType, TaskExecutionSpec},
     event::{Event, EventListener},
-    get_invalidator, registry, CellId, Invalidator, NativeFunction, RawVc, StatsType, TaskId,
-    TaskIdSet, TraitType, TraitTypeId, TurboTasksBackendApi, ValueTypeId,
+    get_invalidator, registry, CellId, FunctionId, Invalidator, NativeFunction, RawVc, StatsType,
+    TaskId, TaskIdSet, TraitType, TraitTypeId, TurboTasksBackendApi, ValueTypeId,
 };
 
 use crate::{
     aggregation_tree::{aggregation_info, ensure_thresholds, AggregationInfoGuard},
     cell::Cell,
     gc::{to_exp_u8, GcPriority, GcStats, GcTaskState},
+    invalidation_trace::InvalidationCause,
     output::{Output, OutputContent},
+    snapshot::TaskSnapshot,
     stats::{ReferenceType, StatsReferences, StatsTaskType},
     task::aggregation::{TaskAggregationContext, TaskChange},
     MemoryBackend,
@@ -47,7 +50,7 @@ use crate::{
 pub type NativeTaskFuture = Pin<Box<dyn Future<Output = Result<RawVc>> + Send>>;
 pub type NativeTaskFn = Box<dyn Fn() -> NativeTaskFuture + Send + Sync>;
 
-#[derive(Hash, Copy, Clone, PartialEq, Eq)]
+#[derive(Hash, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TaskDependency {
     Output(TaskId),
     Cell(TaskId, CellId),
@@ -545,6 +548,13 @@ impl Task {
         aggregation_context.apply_queued_updates();
     }
 
+    pub(crate) fn persistent_task_type(&self) -> Option<&Arc<PersistentTaskType>> {
+        match &self.ty {
+            TaskType::Persistent { ty } => Some(ty),
+            TaskType::Root(_) | TaskType::Once(_) => None,
+        }
+    }
+
     pub(crate) fn get_function_name(&self) -> Option<Cow<'static, str>> {
         if let TaskType::Persistent { ty, .. } = &self.ty {
             match &**ty {
@@ -933,6 +943,10 @@ impl Task {
                                 });
                             }
                         }
+                        backend.invalidation_tracer().record(
+                            state.output.dependent_tasks().iter().copied(),
+                            InvalidationCause::Output { task: self.id },
+                        );
                         state.output.link(result, turbo_tasks)
                     }
                 }
@@ -940,9 +954,19 @@ impl Task {
                     if let Some(name) = self.get_function_name() {
                         err = err.context(format!("Execution of {} failed", name));
                     }
+                    backend.invalidation_tracer().record(
+                        state.output.dependent_tasks().iter().copied(),
+                        InvalidationCause::Output { task: self.id },
+                    );
                     state.output.error(err, turbo_tasks)
                 }
-                Err(message) => state.output.panic(message, turbo_tasks),
+                Err(message) => {
+                    backend.invalidation_tracer().record(
+                        state.output.dependent_tasks().iter().copied(),
+                        InvalidationCause::Output { task: self.id },
+                    );
+                    state.output.panic(message, turbo_tasks)
+                }
             },
             InProgressDirty { .. } => {
                 // We don't want to assign the output cell here
@@ -1069,6 +1093,65 @@ impl Task {
         schedule_task
     }
 
+    /// Called instead of [Task::execution_completed] when the execution was
+    /// cancelled. The task has no new output, so it's left dirty and is only
+    /// scheduled again when it's read.
+    pub(crate) fn execution_cancelled(
+        &self,
+        duration: Duration,
+        instant: Instant,
+        stateful: bool,
+        backend: &MemoryBackend,
+        turbo_tasks: &dyn TurboTasksBackendApi<MemoryBackend>,
+    ) {
+        if let TaskType::Once(_) = self.ty {
+            // Once tasks can't become dirty, they finish without an output
+            let _ = self.execution_completed(duration, instant, stateful, backend, turbo_tasks);
+            return;
+        }
+        // Reverts what the execution changed so far and moves it to InProgressDirty
+        self.make_dirty(backend, turbo_tasks);
+
+        let mut aggregation_context = TaskAggregationContext::new(turbo_tasks, backend);
+        let dependencies = DEPENDENCIES_TO_TRACK.with(|deps| deps.take());
+        let event = {
+            let mut state = self.full_state_mut();
+            state
+                .stats
+                .register_execution(duration, turbo_tasks.program_duration_until(instant));
+            let InProgressDirty { ref mut event } = state.state_type else {
+                panic!(
+                    "Task execution cancelled in unexpected state {}",
+                    Task::state_string(&state)
+                )
+            };
+            let event = event.take();
+            let description = self.get_event_description();
+            state.state_type = Dirty {
+                event: Event::new(move || format!("TaskState({})::event", description())),
+                outdated_dependencies: Default::default(),
+            };
+            state.aggregation_leaf.change(
+                &aggregation_context,
+                &TaskChange {
+                    dirty_tasks_update: vec![(self.id, 1)],
+                    ..Default::default()
+                },
+            );
+            // An active root would schedule it right away, under a token that might be
+            // cancelled as well
+            aggregation_context.take_scheduled_dirty_task(self.id);
+            event
+        };
+        // Readers waiting for the cancelled execution read the task again, which
+        // schedules it
+        event.notify(usize::MAX);
+        if !dependencies.is_empty() {
+            self.clear_dependencies(dependencies, backend, turbo_tasks);
+        }
+        aggregation_context.apply_queued_updates();
+    }
+
     fn make_dirty(
         &self,
         backend: &MemoryBackend,
@@ -1302,6 +1385,117 @@ impl Task {
         self.make_dirty(backend, turbo_tasks)
     }
 
+    /// Captures the state needed to restore this task from a snapshot. Returns
+    /// `None` when the task can't be restored: when it's not done, failed or
+    /// holds state.
+    pub(crate) fn snapshot(&self) -> Option<TaskSnapshot> {
+        let mut state = self.state();
+        let state = state.as_full()?;
+        let Done { dependencies } = &state.state_type else {
+            return None;
+        };
+        let OutputContent::Link(output) = state.output.content else {
+            return None;
+        };
+        if state.stateful {
+            return None;
+        }
+        let cells = state
+            .cells
+            .iter()
+            .flat_map(|(&type_id, cells)| {
+                cells.iter().enumerate().filter_map(move |(index, cell)| {
+                    cell.has_value().then(|| {
+                        (
+                            CellId {
+                                type_id,
+                                index: index as u32,
+                            },
+                            cell.read_own_content_untracked(),
+                        )
+                    })
+                })
+            })
+            .collect();
+        let collectibles = state
+            .collectibles
+            .as_ref()
+            .map(|collectibles| {
+                collectibles
+                    .iter()
+                    .map(|(&(trait_type, collectible), &count)| (trait_type, collectible, count))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Some(TaskSnapshot {
+            output,
+            cells,
+            dependencies: dependencies.iter().copied().collect(),
+            children: state.children.iter().copied().collect(),
+            collectibles,
+        })
+    }
+
+    /// Restores the state of a fresh task from a snapshot. The task is
+    /// restored as done with `snapshot.dependencies`, or as dirty when `dirty`
+    /// is set. A dirty task keeps its cells until it's executed again. This
+    /// doesn't register the task with its dependencies or children, see
+    /// [Task::add_dependent] and [Task::connect_child].
+    pub(crate) fn restore(&self, snapshot: TaskSnapshot, dirty: bool) {
+        let mut state = self.full_state_mut();
+        for (index, content) in snapshot.cells {
+            let list = state.cells.entry(index.type_id).or_default();
+            let i = index.index as usize;
+            if list.len() <= i {
+                list.resize_with(i + 1, Default::default);
+            }
+            list[i] = Cell::Value {
+                content,
+                dependent_tasks: Default::default(),
+            };
+        }
+        state.output.content = OutputContent::Link(snapshot.output);
+        if !snapshot.collectibles.is_empty() {
+            let collectibles = state.collectibles.inner.get_or_insert_default();
+            for (trait_type, collectible, count) in snapshot.collectibles {
+                collectibles.insert((trait_type, collectible), count);
+            }
+        }
+        state.state_type = if dirty {
+            let description = self.get_event_description();
+            Dirty {
+                event: Event::new(move || format!("TaskState({})::event", description())),
+                outdated_dependencies: Default::default(),
+            }
+        } else {
+            Done {
+                dependencies: snapshot.dependencies.into_iter().collect(),
+            }
+        };
+    }
+
+    /// Registers `reader` as dependent of `dep`, as if `reader` had read it.
+    /// Dependencies on collectibles are not supported.
+    pub(crate) fn add_dependent(dep: TaskDependency, reader: TaskId, backend: &MemoryBackend) {
+        match dep {
+            TaskDependency::Output(task) => {
+                backend.with_task(task, |task| {
+                    task.with_output_mut_if_available(|output| {
+                        output.dependent_tasks.insert(reader);
+                    });
+                });
+            }
+            TaskDependency::Cell(task, index) => {
+                backend.with_task(task, |task| {
+                    task.with_cell_mut(index, |cell| cell.add_dependent_task(reader));
+                });
+            }
+            TaskDependency::Collectibles(..) => {
+                unreachable!("dependencies on collectibles are not restored")
+            }
+        }
+    }
+
     /// Called when the task need to be recomputed because a gc'ed cell was
     /// read.
     pub(crate) fn recompute(
@@ -1424,6 +1618,38 @@ impl Task {
         }
     }
 
+    /// Estimates the memory used by this task. Calls `cell` with the value
+    /// type and the estimated size of each of its cells. The returned size
+    /// doesn't include the cells.
+    pub(crate) fn memory_usage(&self, mut cell: impl FnMut(ValueTypeId, usize)) -> usize {
+        let mut bytes = size_of::<Task>();
+        if let TaskMetaStateReadGuard::Full(state) = self.state() {
+            bytes += size_of::<TaskState>();
+            bytes += state.children.len() * size_of::<TaskId>();
+            bytes += state.output.dependent_tasks.len() * size_of::<TaskId>();
+            if let Done { ref dependencies } = state.state_type {
+                bytes += dependencies.len() * size_of::<TaskDependency>();
+            }
+            for (&ty, cells) in state.cells.iter() {
+                for content in cells.iter() {
+                    cell(ty, content.memory_usage());
+                }
+            }
+        }
+        bytes
+    }
+
+    /// The function this task executes, if it's a native function task.
+    pub(crate) fn function_id(&self) -> Option<FunctionId> {
+        match &self.ty {
+            TaskType::Persistent { ty, .. } => match &**ty {
+                PersistentTaskType::Native(f, _) => Some(*f),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
     pub fn get_stats_type(self: &Task) -> StatsTaskType {
         match &self.ty {
             TaskType::Root(_) => StatsTaskType::Root(self.id),
@@ -1559,6 +1785,20 @@ impl Task {
         aggregation_context.apply_queued_updates();
     }
 
+    /// The number of unfinished tasks in the subgraph of this task.
+    pub(crate) fn get_unfinished_tasks(
+        &self,
+        backend: &MemoryBackend,
+        turbo_tasks: &dyn TurboTasksBackendApi<MemoryBackend>,
+    ) -> usize {
+        let mut aggregation_context = TaskAggregationContext::new(turbo_tasks, backend);
+        let unfinished = aggregation_info(&aggregation_context, &self.id)
+            .lock()
+            .unfinished;
+        aggregation_context.apply_queued_updates();
+        unfinished.max(0) as usize
+    }
+
     pub(crate) fn get_or_wait_output<T, F: FnOnce(&mut Output) -> Result<T>>(
         &self,
         strongly_consistent: bool,
@@ -1650,6 +1890,21 @@ impl Task {
             .read_collectibles(trait_type, reader)
     }
 
+    pub(crate) fn read_collectibles_filtered(
+        id: TaskId,
+        trait_type: TraitTypeId,
+        reader: TaskId,
+        filter: &mut dyn FnMut(RawVc) -> bool,
+        backend: &MemoryBackend,
+        turbo_tasks: &dyn TurboTasksBackendApi<MemoryBackend>,
+    ) -> AutoMap<RawVc, i32> {
+        let aggregation_context = TaskAggregationContext::new(turbo_tasks, backend);
+        aggregation_context
+            .aggregation_info(id)
+            .lock()
+            .read_collectibles_filtered(trait_type, reader, filter)
+    }
+
     pub(crate) fn emit_collectible(
         &self,
         trait_type: TraitTypeId,
@@ -1775,11 +2030,10 @@ impl Task {
                 let last_duration = state.stats.last_duration();
                 let compute_duration = last_duration.into();
 
-                let age = to_exp_u8(
-                    (now_relative_to_start
-                        .saturating_sub(state.stats.last_execution_relative_to_start()))
-                    .as_secs(),
-                );
+                let idle_duration = now_relative_to_start
+                    .saturating_sub(state.stats.last_execution_relative_to_start());
+                let age = to_exp_u8(idle_duration.as_secs());
+                let gc_config = backend.gc_config();
 
                 let min_prio_that_needs_total_duration = if active {
                     GcPriority::EmptyCells {
@@ -1896,7 +2150,9 @@ impl Task {
                                 age: Reverse(age),
                                 total_compute_duration: total_compute_duration_u8,
                             };
-                            if new_priority <= max_priority {
+                            if new_priority <= max_priority
+                                && idle_duration >= Duration::from(gc_config.unload_idle_age)
+                            {
                                 // Unload task
                                 if self.unload(state, backend, turbo_tasks) {
                                     stats.unloaded += 1;
@@ -1918,7 +2174,9 @@ impl Task {
                                 total_compute_duration: total_compute_duration_u8,
                                 age: Reverse(age),
                             };
-                            if new_priority <= max_priority {
+                            if new_priority <= max_priority
+                                && idle_duration >= Duration::from(gc_config.empty_cells_idle_age)
+                            {
                                 // Empty cells
                                 let cells = take(&mut state.cells);
                                 for cells in cells.into_values() {
@@ -2122,11 +2380,18 @@ impl Task {
         // Notify everyone that is listening on our output or cells.
         // This will mark everyone as dirty and will trigger a new execution when they
         // become active again.
+        let cause = InvalidationCause::Unloaded { task: self.id };
         for cells in cells.into_values() {
             for cell in cells {
+                backend
+                    .invalidation_tracer()
+                    .record(cell.dependent_tasks().iter().copied(), cause);
                 cell.gc_drop(turbo_tasks);
             }
         }
+        backend
+            .invalidation_tracer()
+            .record(output.dependent_tasks().iter().copied(), cause);
         output.gc_drop(turbo_tasks);
 
         // We can clear the dependencies as we are already marked as dirty
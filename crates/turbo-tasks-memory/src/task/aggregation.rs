@@ -12,8 +12,8 @@ use turbo_tasks::{event::Event, RawVc, TaskId, TaskIdSet, TraitTypeId, TurboTask
 use super::{meta_state::TaskMetaStateWriteGuard, TaskStateType};
 use crate::{
     aggregation_tree::{
-        aggregation_info, AggregationContext, AggregationInfoReference, AggregationItemLock,
-        AggregationTreeLeaf,
+        aggregation_info, AggregationContext, AggregationEvent, AggregationInfoReference,
+        AggregationItemLock, AggregationTreeConfig, AggregationTreeLeaf,
     },
     MemoryBackend,
 };
@@ -114,6 +114,23 @@ impl Aggregated {
             }
         }
     }
+
+    /// Like [Aggregated::read_collectibles], but only copies the collectibles
+    /// with a positive count for which `filter` returns true.
+    pub(crate) fn read_collectibles_filtered(
+        &mut self,
+        trait_type: TraitTypeId,
+        reader: TaskId,
+        filter: &mut dyn FnMut(RawVc) -> bool,
+    ) -> AutoMap<RawVc, i32> {
+        let info = self.collectibles.entry(trait_type).or_default();
+        info.dependent_tasks.insert(reader);
+        info.collectibles
+            .iter()
+            .filter(|&(&raw, &count)| count > 0 && filter(raw))
+            .map(|(&raw, &count)| (raw, count))
+            .collect()
+    }
 }
 
 #[derive(Default, Debug)]
@@ -302,11 +319,11 @@ impl<'a> AggregationContext for TaskAggregationContext<'a> {
             dirty_tasks_update: change.dirty_tasks_update.clone(),
             collectibles: change.collectibles.clone(),
         };
-        if new_change.is_empty() {
-            None
-        } else {
-            Some(new_change)
-        }
+        let new_change = (!new_change.is_empty()).then_some(new_change);
+        self.backend
+            .aggregation_stats()
+            .record_change(new_change.is_some());
+        new_change
     }
 
     fn info_to_add_change(&self, info: &Aggregated) -> Option<Self::ItemChange> {
@@ -361,6 +378,14 @@ impl<'a> AggregationContext for TaskAggregationContext<'a> {
         }
     }
 
+    fn config(&self) -> AggregationTreeConfig {
+        *self.backend.aggregation_config()
+    }
+
+    fn on_event(&self, event: AggregationEvent) {
+        self.backend.aggregation_stats().record_event(event);
+    }
+
     fn new_root_info(&self, _root_info_type: &RootInfoType) -> Self::RootInfo {
         false
     }
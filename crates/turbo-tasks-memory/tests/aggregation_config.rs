@@ -0,0 +1,45 @@
+#![feature(arbitrary_self_types)]
+
+use anyhow::Result;
+use turbo_tasks::{TryJoinIterExt, TurboTasks, Vc};
+use turbo_tasks_memory::{AggregationTreeConfig, MemoryBackend};
+use turbo_tasks_testing::register;
+
+register!();
+
+#[tokio::test]
+async fn small_aggregation_thresholds() {
+    *REGISTER;
+    let backend = MemoryBackend::default().with_aggregation_config(AggregationTreeConfig {
+        connectivity_limit: 1,
+        children_inner_threshold: 4,
+    });
+    backend.aggregation_stats().enable();
+    let tt = TurboTasks::new(backend);
+    tt.run_once(async {
+        let leaves = leaves(3, 5, 0);
+        leaves.strongly_consistent().await?;
+        assert_eq!(*leaves.await?, 5 * 5 * 5);
+        Ok(())
+    })
+    .await
+    .unwrap();
+
+    let stats = tt.backend().aggregation_stats().snapshot();
+    assert!(stats.changes > 0);
+    assert!(stats.propagated_changes <= stats.changes);
+    assert!(stats.bottom_trees_created > 0);
+}
+
+/// Counts the leaves of a tree with the given depth and fan-out.
+#[turbo_tasks::function]
+async fn leaves(depth: u32, fan_out: u32, id: u32) -> Result<Vc<u32>> {
+    if depth == 0 {
+        return Ok(Vc::cell(1));
+    }
+    let children = (0..fan_out)
+        .map(|i| leaves(depth - 1, fan_out, id * fan_out + i))
+        .try_join()
+        .await?;
+    Ok(Vc::cell(children.iter().map(|value| **value).sum()))
+}
@@ -0,0 +1,65 @@
+#![feature(arbitrary_self_types)]
+
+use std::{sync::Mutex, time::Duration};
+
+use anyhow::Result;
+use turbo_tasks::{get_invalidator, InvalidationBatcher, Invalidator, Vc};
+use turbo_tasks_testing::{register, run};
+
+register!();
+
+#[tokio::test]
+async fn batched_invalidation() {
+    run! {
+        let counter = Counter::cell(Counter { value: Mutex::new((0, Vec::new())) });
+        let batcher = InvalidationBatcher::new(Duration::from_millis(50));
+
+        assert_eq!(*counter.get_value(1).strongly_consistent().await?, 0);
+        assert_eq!(*counter.get_value(2).strongly_consistent().await?, 0);
+
+        // Both readers are invalidated once, together
+        counter.await?.incr(&batcher);
+        counter.await?.incr(&batcher);
+        assert_eq!(batcher.pending(), 2);
+        batcher.flush();
+        assert_eq!(batcher.pending(), 0);
+        assert_eq!(*counter.get_value(1).strongly_consistent().await?, 2);
+        assert_eq!(*counter.get_value(2).strongly_consistent().await?, 2);
+
+        // Pending invalidations are applied after the window
+        counter.await?.incr(&batcher);
+        assert_eq!(batcher.pending(), 2);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(batcher.pending(), 0);
+        assert_eq!(*counter.get_value(1).strongly_consistent().await?, 3);
+    }
+}
+
+#[turbo_tasks::value(transparent)]
+struct CounterValue(usize);
+
+#[turbo_tasks::value(serialization = "none", cell = "new", eq = "manual")]
+struct Counter {
+    #[turbo_tasks(debug_ignore, trace_ignore)]
+    value: Mutex<(usize, Vec<Invalidator>)>,
+}
+
+impl Counter {
+    fn incr(&self, batcher: &InvalidationBatcher) {
+        let mut lock = self.value.lock().unwrap();
+        lock.0 += 1;
+        for invalidator in lock.1.drain(..) {
+            batcher.invalidate(invalidator);
+        }
+    }
+}
+
+#[turbo_tasks::value_impl]
+impl Counter {
+    #[turbo_tasks::function]
+    async fn get_value(&self, _key: u32) -> Result<Vc<CounterValue>> {
+        let mut lock = self.value.lock().unwrap();
+        lock.1.push(get_invalidator());
+        Ok(Vc::cell(lock.0))
+    }
+}
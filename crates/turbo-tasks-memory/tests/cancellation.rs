@@ -0,0 +1,97 @@
+#![feature(arbitrary_self_types)]
+
+use std::{
+    sync::atomic::{AtomicU32, Ordering},
+    time::Duration,
+};
+
+use anyhow::Result;
+use lazy_static::lazy_static;
+use tokio::sync::Notify;
+use turbo_tasks::{
+    cancellation::CancellationToken, run_once, run_once_with_cancellation, TurboTasks, Vc,
+};
+use turbo_tasks_memory::MemoryBackend;
+use turbo_tasks_testing::register;
+
+register!();
+
+static EXECUTIONS: AtomicU32 = AtomicU32::new(0);
+static SUBGRAPH_EXECUTIONS: AtomicU32 = AtomicU32::new(0);
+
+lazy_static! {
+    static ref STARTED: Notify = Notify::new();
+    static ref SUBGRAPH_STARTED: Notify = Notify::new();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn reader_of_cancelled_task() {
+    *REGISTER;
+    let tt = TurboTasks::new(MemoryBackend::default());
+
+    // Schedules `slow` under the global token, which is cancelled below
+    let scheduler = tokio::spawn(run_once(tt.clone(), async {
+        slow(1).await?;
+        Ok(())
+    }));
+    STARTED.notified().await;
+
+    // The reader isn't cancelled itself, it only depends on the cancelled task
+    let reader = tokio::spawn(run_once_with_cancellation(
+        tt.clone(),
+        CancellationToken::new(),
+        async { Ok(*slow(1).await?) },
+    ));
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    tt.cancel_in_flight();
+
+    let value = tokio::time::timeout(Duration::from_secs(10), reader)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert_eq!(value, 42);
+    assert_eq!(EXECUTIONS.load(Ordering::SeqCst), 2);
+    scheduler.abort();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn cancelled_subgraph_is_not_executed_again() {
+    *REGISTER;
+    let tt = TurboTasks::new(MemoryBackend::default());
+
+    let token = CancellationToken::new();
+    let run = tokio::spawn(run_once_with_cancellation(
+        tt.clone(),
+        token.clone(),
+        async {
+            never_finishes().await?;
+            Ok(())
+        },
+    ));
+    SUBGRAPH_STARTED.notified().await;
+    token.cancel();
+    assert!(run.await.unwrap().is_err());
+
+    // A restarted execution would have run by now
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(SUBGRAPH_EXECUTIONS.load(Ordering::SeqCst), 1);
+}
+
+#[turbo_tasks::function]
+async fn slow(_key: u32) -> Result<Vc<u32>> {
+    if EXECUTIONS.fetch_add(1, Ordering::SeqCst) == 0 {
+        STARTED.notify_one();
+        // Only finishes by being cancelled
+        std::future::pending::<()>().await;
+    }
+    Ok(Vc::cell(42))
+}
+
+#[turbo_tasks::function]
+async fn never_finishes() -> Result<Vc<u32>> {
+    SUBGRAPH_EXECUTIONS.fetch_add(1, Ordering::SeqCst);
+    SUBGRAPH_STARTED.notify_one();
+    std::future::pending::<()>().await;
+    Ok(Vc::cell(0))
+}
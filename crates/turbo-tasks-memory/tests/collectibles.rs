@@ -114,6 +114,41 @@ async fn taking_collectibles_parallel() {
     }
 }
 
+#[tokio::test]
+async fn filtering_collectibles() {
+    run! {
+        let result = my_transitive_emitting_function("".to_string(), "filtered".to_string());
+        result.strongly_consistent().await?;
+        let mut large = HashSet::new();
+        for collectible in result.peek_collectibles::<Box<dyn ValueToString>>() {
+            if collectible.to_string().await?.as_str() == "123" {
+                large.insert(collectible);
+            }
+        }
+        let large = |collectible: Vc<Box<dyn ValueToString>>| large.contains(&collectible);
+        let list = result.peek_collectibles_filtered::<Box<dyn ValueToString>>(large);
+        assert_eq!(list.len(), 1);
+        assert_eq!(list.into_iter().next().unwrap().to_string().await?.as_str(), "123");
+
+        // Only the matching collectibles are taken
+        let list = result.take_collectibles_filtered::<Box<dyn ValueToString>>(large);
+        assert_eq!(list.len(), 1);
+        let list = result.peek_collectibles::<Box<dyn ValueToString>>();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list.into_iter().next().unwrap().to_string().await?.as_str(), "42");
+
+        // The predicate only sees the collectibles that are still emitted
+        let mut seen = Vec::new();
+        let list = result.peek_collectibles_filtered::<Box<dyn ValueToString>>(|collectible| {
+            seen.push(collectible);
+            true
+        });
+        assert_eq!(list.len(), 1);
+        assert_eq!(seen.len(), 1);
+        assert_eq!(seen[0].to_string().await?.as_str(), "42");
+    }
+}
+
 #[turbo_tasks::value(transparent)]
 struct Collectibles(AutoSet<Vc<Box<dyn ValueToString>>>);
 
@@ -0,0 +1,54 @@
+#![feature(arbitrary_self_types)]
+
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbo_tasks_testing::{register, run};
+
+register!();
+
+#[tokio::test]
+async fn vec_combinators() {
+    run! {
+        let numbers: Vc<Vec<Vc<u32>>> = Vc::cell((1..=5).map(Vc::cell).collect());
+
+        let doubled = numbers
+            .try_map(2, |number| async move { Ok(*number.await? * 2) })
+            .await?;
+        assert_eq!(doubled, vec![2, 4, 6, 8, 10]);
+
+        let odd = numbers
+            .try_filter_map(2, |number| async move {
+                let number = *number.await?;
+                Ok((number % 2 == 1).then_some(number))
+            })
+            .await?;
+        assert_eq!(odd, vec![1, 3, 5]);
+
+        let repeated = numbers
+            .try_flat_map(0, |number| async move {
+                let number = *number.await?;
+                Ok(vec![number; number as usize])
+            })
+            .await?;
+        assert_eq!(repeated.len(), 15);
+    }
+}
+
+#[tokio::test]
+async fn option_combinators() {
+    run! {
+        let some: Vc<Option<Vc<u32>>> = Vc::cell(Some(Vc::cell(21)));
+        let none: Vc<Option<Vc<u32>>> = Vc::cell(None);
+
+        let double = |number: Vc<u32>| async move { Ok(*number.await? * 2) };
+        assert_eq!(some.try_map(double).await?, Some(42));
+        assert_eq!(none.try_map(double).await?, None);
+
+        let large = |number: Vc<u32>| async move {
+            let number = *number.await?;
+            Ok((number > 100).then_some(number))
+        };
+        assert_eq!(some.try_and_then(large).await?, None);
+        assert_eq!(none.try_and_then(large).await?, None);
+    }
+}
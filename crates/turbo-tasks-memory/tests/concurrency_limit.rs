@@ -0,0 +1,74 @@
+#![feature(arbitrary_self_types)]
+
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+
+use anyhow::Result;
+use turbo_tasks::{TurboTasks, Vc};
+use turbo_tasks_memory::MemoryBackend;
+use turbo_tasks_testing::register;
+
+register!();
+
+static RUNNING: AtomicUsize = AtomicUsize::new(0);
+static MAX_RUNNING: AtomicUsize = AtomicUsize::new(0);
+
+#[tokio::test(flavor = "multi_thread")]
+async fn max_concurrency() {
+    *REGISTER;
+    let tt = TurboTasks::new(MemoryBackend::default());
+    tt.run_once(async {
+        assert_eq!(*sum(8).await?, 56);
+        Ok(())
+    })
+    .await
+    .unwrap();
+    assert!(MAX_RUNNING.load(Ordering::SeqCst) <= 2);
+}
+
+// Each task reads the next one while it holds a permit, so it would deadlock
+// if the permit weren't released while waiting
+#[tokio::test(flavor = "multi_thread")]
+async fn max_concurrency_recursion() {
+    *REGISTER;
+    let tt = TurboTasks::new(MemoryBackend::default());
+    tt.run_once(async {
+        let result =
+            tokio::time::timeout(Duration::from_secs(10), async { countdown(5).await }).await;
+        assert_eq!(*result.expect("recursion deadlocked")?, 15);
+        Ok(())
+    })
+    .await
+    .unwrap();
+}
+
+#[turbo_tasks::function]
+async fn sum(count: u32) -> Result<Vc<u32>> {
+    // All tasks are scheduled before any of them is awaited
+    let values = (0..count).map(expensive).collect::<Vec<_>>();
+    let mut sum = 0;
+    for value in values {
+        sum += *value.await?;
+    }
+    Ok(Vc::cell(sum))
+}
+
+#[turbo_tasks::function(max_concurrency = 2)]
+async fn expensive(value: u32) -> Result<Vc<u32>> {
+    let running = RUNNING.fetch_add(1, Ordering::SeqCst) + 1;
+    MAX_RUNNING.fetch_max(running, Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    RUNNING.fetch_sub(1, Ordering::SeqCst);
+    Ok(Vc::cell(value * 2))
+}
+
+#[turbo_tasks::function(max_concurrency = 1)]
+async fn countdown(value: u32) -> Result<Vc<u32>> {
+    if value == 0 {
+        return Ok(Vc::cell(0));
+    }
+    let rest = *countdown(value - 1).await?;
+    Ok(Vc::cell(value + rest))
+}
@@ -0,0 +1,145 @@
+#![feature(arbitrary_self_types)]
+
+use parking_lot::Mutex;
+use turbo_tasks::{
+    backend::PersistentTaskType,
+    persisted_graph::{PersistTaskState, PersistedGraph, PersistedGraphApi, TaskCell, TaskData},
+    RawVc, TaskId, Vc,
+};
+use turbo_tasks_memory::DiskPersistedGraph;
+use turbo_tasks_testing::register;
+
+register!();
+
+/// Task types of the process that saves the snapshot, task `n` has the
+/// type at index `n - 1`.
+struct SavingApi(Vec<PersistentTaskType>);
+
+impl PersistedGraphApi for SavingApi {
+    fn get_or_create_task_type(&self, _ty: PersistentTaskType) -> TaskId {
+        unreachable!("no snapshot is restored")
+    }
+
+    fn lookup_task_type(&self, id: TaskId) -> &PersistentTaskType {
+        &self.0[*id - 1]
+    }
+}
+
+/// Assigns task ids that differ from the saving process, starting at 100.
+#[derive(Default)]
+struct RestoringApi(Mutex<Vec<PersistentTaskType>>);
+
+impl RestoringApi {
+    fn id_of(&self, ty: &PersistentTaskType) -> TaskId {
+        let index = self.0.lock().iter().position(|t| t == ty).unwrap();
+        TaskId::from(100 + index)
+    }
+}
+
+impl PersistedGraphApi for RestoringApi {
+    fn get_or_create_task_type(&self, ty: PersistentTaskType) -> TaskId {
+        let mut types = self.0.lock();
+        let index = types.iter().position(|t| *t == ty).unwrap_or_else(|| {
+            types.push(ty);
+            types.len() - 1
+        });
+        TaskId::from(100 + index)
+    }
+
+    fn lookup_task_type(&self, _id: TaskId) -> &PersistentTaskType {
+        unreachable!("no snapshot is saved")
+    }
+}
+
+#[test]
+fn save_and_restore() {
+    *REGISTER;
+    let path = std::env::temp_dir().join(format!(
+        "turbo-tasks-disk-persisted-graph-{}.json",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+
+    let parent_type = PersistentTaskType::Native(*PARENT_FUNCTION_ID, Vec::new());
+    let child_type = PersistentTaskType::Native(*CHILD_FUNCTION_ID, Vec::new());
+    let api = &SavingApi(vec![parent_type.clone(), child_type.clone()]);
+    let (parent, child) = (TaskId::from(1), TaskId::from(2));
+
+    let graph = DiskPersistedGraph::new(&path).unwrap();
+    graph
+        .persist(
+            child,
+            TaskData {
+                children: Vec::new(),
+                dependencies: vec![RawVc::TaskOutput(parent)],
+                cells: vec![TaskCell::default()],
+                output: RawVc::TaskOutput(child),
+            },
+            PersistTaskState {
+                externally_active: false,
+            },
+            api,
+        )
+        .unwrap();
+    graph
+        .persist(
+            parent,
+            TaskData {
+                children: vec![child],
+                dependencies: Vec::new(),
+                cells: Vec::new(),
+                output: RawVc::TaskOutput(child),
+            },
+            PersistTaskState {
+                externally_active: true,
+            },
+            api,
+        )
+        .unwrap();
+    assert!(graph.make_dirty(child, api).unwrap());
+    graph.stop(api).unwrap();
+
+    // A new process restores the snapshot with its own task ids
+    let api = &RestoringApi::default();
+    let graph = DiskPersistedGraph::new(&path).unwrap();
+    assert!(graph.lookup(&parent_type, api).unwrap());
+    let (parent, child) = (api.id_of(&parent_type), api.id_of(&child_type));
+    assert_ne!(parent, child);
+
+    let (data, state) = graph.read(parent, api).unwrap().unwrap();
+    assert!(state.clean);
+    assert_eq!(data.children, vec![child]);
+    assert!(data.dependencies.is_empty());
+    assert_eq!(data.output, RawVc::TaskOutput(child));
+
+    let (data, state) = graph.read(child, api).unwrap().unwrap();
+    assert!(!state.clean);
+    assert!(state.keeps_external_active);
+    assert_eq!(data.dependencies, vec![RawVc::TaskOutput(parent)]);
+    assert_eq!(data.cells.len(), 1);
+    assert_eq!(data.output, RawVc::TaskOutput(child));
+
+    // Activation and dependency edges are restored as well
+    assert_eq!(graph.get_dirty_active_tasks(api).unwrap(), vec![child]);
+    assert!(graph.make_clean(child, api).is_ok());
+    assert_eq!(
+        graph
+            .make_dependent_dirty(RawVc::TaskOutput(parent), api)
+            .unwrap(),
+        vec![child]
+    );
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[turbo_tasks::function]
+#[allow(dead_code)]
+fn parent() -> Vc<u32> {
+    Vc::cell(1)
+}
+
+#[turbo_tasks::function]
+#[allow(dead_code)]
+fn child() -> Vc<u32> {
+    Vc::cell(2)
+}
@@ -0,0 +1,45 @@
+#![feature(arbitrary_self_types)]
+
+use anyhow::Result;
+use turbo_tasks::{TurboTasks, Vc};
+use turbo_tasks_memory::MemoryBackend;
+use turbo_tasks_testing::register;
+
+register!();
+
+#[tokio::test]
+async fn function_stats() {
+    *REGISTER;
+    let tt = TurboTasks::new(MemoryBackend::default());
+    tt.backend().function_stats().enable();
+    tt.run_once(async {
+        assert_eq!(*double(21).await?, 42);
+        assert_eq!(*double(21).await?, 42);
+        assert_eq!(*double(1).await?, 2);
+        Ok(())
+    })
+    .await
+    .unwrap();
+
+    let stats = tt.backend().function_stats().snapshot();
+    let double = stats
+        .iter()
+        .find(|stats| stats.name.ends_with("double"))
+        .unwrap();
+    assert_eq!(double.calls, 3);
+    assert_eq!(double.cache_hits, 1);
+    assert_eq!(double.executions, 2);
+
+    let json: serde_json::Value =
+        serde_json::from_str(&tt.backend().function_stats().to_json().unwrap()).unwrap();
+    assert!(json
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|entry| entry["calls"] == 3));
+}
+
+#[turbo_tasks::function]
+fn double(value: u32) -> Result<Vc<u32>> {
+    Ok(Vc::cell(value * 2))
+}
@@ -0,0 +1,41 @@
+#![feature(arbitrary_self_types)]
+
+use anyhow::Result;
+use turbo_tasks::{TurboTasks, Vc, VcValueType};
+use turbo_tasks_memory::MemoryBackend;
+use turbo_tasks_testing::register;
+
+register!();
+
+#[tokio::test]
+async fn generic_function() {
+    *REGISTER;
+    let tt = TurboTasks::new(MemoryBackend::default());
+    tt.backend().function_stats().enable();
+    tt.run_once(async {
+        let number = Vc::<u32>::cell(42);
+        assert_eq!(*identity(number).await?, 42);
+        assert_eq!(*identity(number).await?, 42);
+        assert_eq!(*identity(Vc::<String>::cell("a".to_string())).await?, "a");
+        Ok(())
+    })
+    .await
+    .unwrap();
+
+    // Every instance is a separate function
+    let stats = tt.backend().function_stats().snapshot();
+    let identity_u32 = stats
+        .iter()
+        .find(|stats| stats.name == "identity<(u32,)>")
+        .unwrap();
+    assert_eq!(identity_u32.calls, 2);
+    assert_eq!(identity_u32.cache_hits, 1);
+    assert!(stats
+        .iter()
+        .any(|stats| stats.name.starts_with("identity<(alloc::string::String,)>")));
+}
+
+#[turbo_tasks::function]
+fn identity<T: VcValueType>(value: Vc<T>) -> Result<Vc<T>> {
+    Ok(value)
+}
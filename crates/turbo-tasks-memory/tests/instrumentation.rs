@@ -0,0 +1,78 @@
+#![feature(arbitrary_self_types)]
+
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use anyhow::Result;
+use turbo_tasks::{CellId, TaskId, TurboTasks, Vc};
+use turbo_tasks_memory::{instrumentation::BackendInstrumentation, MemoryBackend};
+use turbo_tasks_testing::register;
+
+register!();
+
+#[derive(Default)]
+struct Counters {
+    started: AtomicUsize,
+    finished: AtomicUsize,
+    cell_reads: AtomicUsize,
+    cell_writes: AtomicUsize,
+}
+
+impl BackendInstrumentation for Counters {
+    fn on_task_started(&self, _task: TaskId) {
+        self.started.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn on_task_finished(&self, _task: TaskId, _duration: Duration) {
+        self.finished.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn on_cell_read(&self, _task: TaskId, _cell: CellId, _reader: Option<TaskId>) {
+        self.cell_reads.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn on_cell_written(&self, _task: TaskId, _cell: CellId) {
+        self.cell_writes.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[tokio::test]
+async fn instrumentation() {
+    *REGISTER;
+    let counters = Arc::new(Counters::default());
+    let tt = TurboTasks::new(MemoryBackend::default().with_instrumentation(counters.clone()));
+    tt.run_once(async {
+        assert_eq!(*sum(3).await?, 6);
+        Ok(())
+    })
+    .await
+    .unwrap();
+
+    // The root task, `sum` and three `double`s
+    assert!(counters.started.load(Ordering::SeqCst) >= 5);
+    assert_eq!(
+        counters.started.load(Ordering::SeqCst),
+        counters.finished.load(Ordering::SeqCst)
+    );
+    assert!(counters.cell_writes.load(Ordering::SeqCst) >= 4);
+    assert!(counters.cell_reads.load(Ordering::SeqCst) >= 4);
+}
+
+#[turbo_tasks::function]
+fn double(value: u32) -> Vc<u32> {
+    Vc::cell(value * 2)
+}
+
+#[turbo_tasks::function]
+async fn sum(count: u32) -> Result<Vc<u32>> {
+    let mut sum = 0;
+    for i in 0..count {
+        sum += *double(i).await?;
+    }
+    Ok(Vc::cell(sum))
+}
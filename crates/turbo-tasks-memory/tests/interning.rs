@@ -0,0 +1,28 @@
+#![feature(arbitrary_self_types)]
+
+use anyhow::Result;
+use turbo_tasks::Vc;
+use turbo_tasks_testing::{register, run};
+
+register!();
+
+#[tokio::test]
+async fn interned_cells() {
+    run! {
+        // Different tasks producing equal values share them
+        let a = concat("ab".to_string(), "c".to_string()).await?;
+        let b = concat("a".to_string(), "bc".to_string()).await?;
+        let c = concat("a".to_string(), "b".to_string()).await?;
+        assert_eq!(*a, "abc");
+        assert!(a.ptr_eq(&b));
+        assert!(!a.ptr_eq(&c));
+    }
+}
+
+#[turbo_tasks::value(transparent, cell = "interned")]
+struct Name(String);
+
+#[turbo_tasks::function]
+fn concat(prefix: String, suffix: String) -> Result<Vc<Name>> {
+    Ok(Vc::cell(format!("{prefix}{suffix}")))
+}
@@ -0,0 +1,41 @@
+#![feature(arbitrary_self_types)]
+
+use anyhow::Result;
+use turbo_tasks::{TurboTasks, Vc};
+use turbo_tasks_memory::MemoryBackend;
+use turbo_tasks_testing::register;
+
+register!();
+
+#[tokio::test]
+async fn local_function() {
+    *REGISTER;
+    let tt = TurboTasks::new(MemoryBackend::default());
+    tt.backend().function_stats().enable();
+    tt.run_once(async {
+        assert_eq!(*add_one_doubled(20).await?, 42);
+        assert_eq!(*double_local(Vc::cell(5)).await?.await?, 10);
+        Ok(())
+    })
+    .await
+    .unwrap();
+
+    // Local functions don't create tasks
+    let stats = tt.backend().function_stats().snapshot();
+    assert!(stats
+        .iter()
+        .any(|stats| stats.name.ends_with("add_one_doubled")));
+    assert!(!stats
+        .iter()
+        .any(|stats| stats.name.ends_with("double_local")));
+}
+
+#[turbo_tasks::function]
+async fn add_one_doubled(value: u32) -> Result<Vc<u32>> {
+    double_local(Vc::cell(value + 1)).await
+}
+
+#[turbo_tasks::function(local)]
+async fn double_local(value: Vc<u32>) -> Result<Vc<u32>> {
+    Ok(Vc::cell(*value.await? * 2))
+}
@@ -0,0 +1,42 @@
+#![feature(arbitrary_self_types)]
+
+use anyhow::Result;
+use turbo_tasks::{TurboTasks, Vc};
+use turbo_tasks_memory::{memory_stats::MemoryStats, MemoryBackend};
+use turbo_tasks_testing::register;
+
+register!();
+
+#[tokio::test]
+async fn memory_stats() {
+    *REGISTER;
+    let tt = TurboTasks::new(MemoryBackend::default());
+    tt.run_once(async {
+        assert_eq!(*repeat(1000).await?, "x".repeat(1000));
+        assert_eq!(*repeat(10).await?, "x".repeat(10));
+        Ok(())
+    })
+    .await
+    .unwrap();
+
+    let stats = MemoryStats::collect(tt.backend());
+    let (_, repeat) = stats
+        .functions
+        .iter()
+        .find(|(name, _)| name.ends_with("repeat"))
+        .unwrap();
+    assert_eq!(repeat.count, 2);
+    assert_eq!(repeat.largest.len(), 2);
+    assert!(repeat.largest[0].1 > repeat.largest[1].1 + 900);
+    assert!(repeat.bytes >= 1010);
+    assert!(stats.total_bytes() >= repeat.bytes);
+
+    let json: serde_json::Value =
+        serde_json::from_str(&stats.to_json(tt.backend()).unwrap()).unwrap();
+    assert!(!json["cell_types"].as_array().unwrap().is_empty());
+}
+
+#[turbo_tasks::function]
+fn repeat(count: usize) -> Result<Vc<String>> {
+    Ok(Vc::cell("x".repeat(count)))
+}
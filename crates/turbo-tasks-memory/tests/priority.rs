@@ -0,0 +1,96 @@
+#![feature(arbitrary_self_types)]
+
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use lazy_static::lazy_static;
+use tokio::sync::Notify;
+use turbo_tasks::{
+    current_task_priority, run_once, run_once_with_priority, Completion, TaskPriority, TurboTasks,
+    Vc,
+};
+use turbo_tasks_memory::MemoryBackend;
+use turbo_tasks_testing::register;
+
+register!();
+
+/// How long background tasks are deferred at most
+const MAX_DEFERRAL: Duration = Duration::from_millis(100);
+
+lazy_static! {
+    static ref BLOCKING: Notify = Notify::new();
+    static ref UNBLOCK: Notify = Notify::new();
+    static ref SCHEDULE_BACKGROUND: Notify = Notify::new();
+}
+
+#[tokio::test]
+async fn priority_is_inherited() {
+    *REGISTER;
+    let tt = TurboTasks::new(MemoryBackend::default());
+
+    let background = run_once_with_priority(tt.clone(), TaskPriority::Background, async {
+        Ok(*is_background(1).await?)
+    })
+    .await
+    .unwrap();
+    assert!(background);
+
+    let background = run_once(tt.clone(), async { Ok(*is_background(2).await?) })
+        .await
+        .unwrap();
+    assert!(!background);
+}
+
+#[turbo_tasks::function]
+fn is_background(_key: u32) -> Vc<bool> {
+    Vc::cell(current_task_priority() == TaskPriority::Background)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn user_blocking_read_promotes_background_task() {
+    *REGISTER;
+    let tt = TurboTasks::new(MemoryBackend::default());
+
+    // Started before anything is user-blocking, so the subgraph itself isn't
+    // deferred, only the task it schedules later on
+    let background = tokio::spawn(run_once_with_priority(
+        tt.clone(),
+        TaskPriority::Background,
+        async {
+            SCHEDULE_BACKGROUND.notified().await;
+            Ok(*deferred(1).await?)
+        },
+    ));
+    // Keeps a user-blocking task in flight, which defers background tasks
+    let blocker = tokio::spawn(run_once(tt.clone(), async {
+        block().await?;
+        Ok(())
+    }));
+    BLOCKING.notified().await;
+    SCHEDULE_BACKGROUND.notify_one();
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    let start = Instant::now();
+    let value = run_once(tt.clone(), async { Ok(*deferred(1).await?) })
+        .await
+        .unwrap();
+    assert_eq!(value, 42);
+    // Without the promotion, the task would only start once its deferral ends
+    assert!(start.elapsed() < MAX_DEFERRAL);
+
+    UNBLOCK.notify_one();
+    assert_eq!(background.await.unwrap().unwrap(), 42);
+    blocker.await.unwrap().unwrap();
+}
+
+#[turbo_tasks::function]
+fn deferred(_key: u32) -> Vc<u32> {
+    Vc::cell(42)
+}
+
+#[turbo_tasks::function]
+async fn block() -> Result<Vc<Completion>> {
+    BLOCKING.notify_one();
+    UNBLOCK.notified().await;
+    Ok(Completion::new())
+}
@@ -0,0 +1,41 @@
+#![feature(arbitrary_self_types)]
+
+use std::time::Duration;
+
+use anyhow::Result;
+use turbo_tasks::{ReadTimeoutError, Vc};
+use turbo_tasks_testing::{register, run};
+
+register!();
+
+#[tokio::test]
+async fn read_with_progress() {
+    run! {
+        let mut reports = Vec::new();
+        let value = slow(1, 300)
+            .strongly_consistent()
+            .with_progress(None, |unfinished| reports.push(unfinished))
+            .await?;
+        assert_eq!(*value, 1);
+        assert!(!reports.is_empty());
+    }
+}
+
+#[tokio::test]
+async fn read_timeout() {
+    run! {
+        let error = slow(2, 1000)
+            .strongly_consistent()
+            .with_progress(Some(Duration::from_millis(50)), |_| {})
+            .await
+            .unwrap_err();
+        let timeout = error.downcast_ref::<ReadTimeoutError>().unwrap();
+        assert_eq!(timeout.timeout, Duration::from_millis(50));
+    }
+}
+
+#[turbo_tasks::function]
+async fn slow(value: u32, millis: u64) -> Result<Vc<u32>> {
+    tokio::time::sleep(Duration::from_millis(millis)).await;
+    Ok(Vc::cell(value))
+}
@@ -0,0 +1,86 @@
+#![feature(arbitrary_self_types)]
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use anyhow::Result;
+use turbo_tasks::{TurboTasks, Vc};
+use turbo_tasks_memory::{snapshot::SnapshotMode, MemoryBackend};
+use turbo_tasks_testing::register;
+
+register!();
+
+static INPUT: AtomicU32 = AtomicU32::new(21);
+static INPUT_EXECUTIONS: AtomicU32 = AtomicU32::new(0);
+static DOUBLE_EXECUTIONS: AtomicU32 = AtomicU32::new(0);
+
+#[tokio::test]
+async fn snapshot_and_restore() {
+    *REGISTER;
+    let path = std::env::temp_dir().join(format!("turbo-tasks-snapshot-{}", std::process::id()));
+
+    let tt = TurboTasks::new(MemoryBackend::default());
+    tt.run_once(async {
+        assert_eq!(*double(1).await?, 42);
+        Ok(())
+    })
+    .await
+    .unwrap();
+    let saved = tt
+        .backend()
+        .save_snapshot(&path, |ty| {
+            if ty.to_string().ends_with("input") {
+                SnapshotMode::Revalidate
+            } else {
+                SnapshotMode::Include
+            }
+        })
+        .unwrap();
+    assert!(saved >= 2);
+    assert_eq!(INPUT_EXECUTIONS.load(Ordering::SeqCst), 1);
+    assert_eq!(DOUBLE_EXECUTIONS.load(Ordering::SeqCst), 1);
+
+    // The input is executed again, but produces the same value, so `double`
+    // is served from the snapshot
+    let tt = TurboTasks::new(MemoryBackend::default());
+    let restored = tt.backend().restore_snapshot(&path, &*tt).unwrap();
+    assert!(restored >= 2);
+    tt.run_once(async {
+        assert_eq!(*double(1).await?, 42);
+        Ok(())
+    })
+    .await
+    .unwrap();
+    assert_eq!(INPUT_EXECUTIONS.load(Ordering::SeqCst), 2);
+    assert_eq!(DOUBLE_EXECUTIONS.load(Ordering::SeqCst), 1);
+
+    // A changed input invalidates `double`
+    INPUT.store(5, Ordering::SeqCst);
+    let tt = TurboTasks::new(MemoryBackend::default());
+    tt.backend().restore_snapshot(&path, &*tt).unwrap();
+    tt.run_once(async {
+        assert_eq!(*double(1).await?, 10);
+        Ok(())
+    })
+    .await
+    .unwrap();
+    assert_eq!(DOUBLE_EXECUTIONS.load(Ordering::SeqCst), 2);
+
+    // A missing snapshot is not an error
+    std::fs::remove_file(&path).unwrap();
+    let tt = TurboTasks::new(MemoryBackend::default());
+    assert_eq!(tt.backend().restore_snapshot(&path, &*tt).unwrap(), 0);
+}
+
+/// Reads external state.
+#[turbo_tasks::function]
+fn input(key: u32) -> Vc<u32> {
+    INPUT_EXECUTIONS.fetch_add(1, Ordering::SeqCst);
+    Vc::cell(INPUT.load(Ordering::SeqCst) * key)
+}
+
+#[turbo_tasks::function]
+async fn double(key: u32) -> Result<Vc<u32>> {
+    DOUBLE_EXECUTIONS.fetch_add(1, Ordering::SeqCst);
+    let value = *input(key).await?;
+    Ok(Vc::cell(value * 2))
+}
@@ -0,0 +1,40 @@
+#![feature(arbitrary_self_types)]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::Result;
+use turbo_tasks::{TransientInstance, TransientState, Vc};
+use turbo_tasks_testing::{register, run};
+
+register!();
+
+static EXECUTIONS: AtomicUsize = AtomicUsize::new(0);
+
+#[tokio::test]
+async fn transient_state() {
+    run! {
+        let state = TransientState::new(1);
+        let doubled = double(TransientInstance::new(state.clone()));
+        assert_eq!(*doubled.strongly_consistent().await?, 2);
+        assert_eq!(EXECUTIONS.load(Ordering::SeqCst), 1);
+
+        state.set(2);
+        assert_eq!(*doubled.strongly_consistent().await?, 4);
+        assert_eq!(EXECUTIONS.load(Ordering::SeqCst), 2);
+
+        // Setting an equal value doesn't invalidate readers
+        state.set(2);
+        assert_eq!(*doubled.strongly_consistent().await?, 4);
+        assert_eq!(EXECUTIONS.load(Ordering::SeqCst), 2);
+
+        state.invalidate();
+        assert_eq!(*doubled.strongly_consistent().await?, 4);
+        assert_eq!(EXECUTIONS.load(Ordering::SeqCst), 3);
+    }
+}
+
+#[turbo_tasks::function]
+fn double(state: TransientInstance<TransientState<u32>>) -> Result<Vc<u32>> {
+    EXECUTIONS.fetch_add(1, Ordering::SeqCst);
+    Ok(Vc::cell(*state.get() * 2))
+}
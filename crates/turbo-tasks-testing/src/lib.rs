@@ -164,6 +164,10 @@ impl TurboTasksApi for VcStorage {
         self.try_read_task_output(task, strongly_consistent)
     }
 
+    fn get_unfinished_tasks(&self, _task: TaskId) -> Option<usize> {
+        None
+    }
+
     fn try_read_task_cell(
         &self,
         task: TaskId,
@@ -223,6 +227,15 @@ impl TurboTasksApi for VcStorage {
         unimplemented!()
     }
 
+    fn read_task_collectibles_filtered(
+        &self,
+        _task: TaskId,
+        _trait_id: TraitTypeId,
+        _filter: &mut dyn FnMut(RawVc) -> bool,
+    ) -> AutoMap<RawVc, i32> {
+        unimplemented!()
+    }
+
     fn read_own_task_cell(&self, task: TaskId, index: CellId) -> Result<CellContent> {
         let map = self.cells.lock().unwrap();
         if let Some(cell) = map.get(&(task, index)) {
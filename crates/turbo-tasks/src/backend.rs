@@ -200,6 +200,12 @@ pub trait Backend: Sync + Send {
 
     fn get_task_description(&self, task: TaskId) -> String;
 
+    /// The function `task` executes, if it executes one. Used by the scheduler
+    /// to enforce per-function concurrency limits.
+    fn get_task_function(&self, _task: TaskId) -> Option<FunctionId> {
+        None
+    }
+
     type ExecutionScopeFuture<T: Future<Output = Result<()>> + Send + 'static>: Future<Output = Result<()>>
         + Send
         + 'static;
@@ -232,6 +238,25 @@ pub trait Backend: Sync + Send {
         turbo_tasks: &dyn TurboTasksBackendApi<Self>,
     ) -> bool;
 
+    /// Called instead of [Backend::task_execution_result] and
+    /// [Backend::task_execution_completed] when the execution was cancelled.
+    /// The task has no result and should be left dirty, so it's only executed
+    /// again when it's read. By default it's invalidated and executed again
+    /// right away.
+    fn task_execution_cancelled(
+        &self,
+        task: TaskId,
+        duration: Duration,
+        instant: Instant,
+        stateful: bool,
+        turbo_tasks: &dyn TurboTasksBackendApi<Self>,
+    ) {
+        self.invalidate_task(task, turbo_tasks);
+        if self.task_execution_completed(task, duration, instant, stateful, turbo_tasks) {
+            turbo_tasks.schedule(task);
+        }
+    }
+
     fn run_backend_job<'a>(
         &'a self,
         id: BackendJobId,
@@ -246,6 +271,18 @@ pub trait Backend: Sync + Send {
         turbo_tasks: &dyn TurboTasksBackendApi<Self>,
     ) -> Result<Result<RawVc, EventListener>>;
 
+    /// The number of tasks that still need to finish before the output of
+    /// `task` is strongly consistent. `None` when the backend doesn't track
+    /// this.
+    #[allow(unused_variables)]
+    fn get_unfinished_tasks(
+        &self,
+        task: TaskId,
+        turbo_tasks: &dyn TurboTasksBackendApi<Self>,
+    ) -> Option<usize> {
+        None
+    }
+
     /// INVALIDATION: Be careful with this, it will not track dependencies, so
     /// using it could break cache invalidation.
     fn try_read_task_output_untracked(
@@ -294,6 +331,24 @@ pub trait Backend: Sync + Send {
         turbo_tasks: &dyn TurboTasksBackendApi<Self>,
     ) -> AutoMap<RawVc, i32>;
 
+    /// Like [Backend::read_task_collectibles], but only returns the
+    /// collectibles with a positive count for which `filter` returns true.
+    /// Backends can apply the filter while reading the aggregated
+    /// collectibles, instead of copying all of them first.
+    fn read_task_collectibles_filtered(
+        &self,
+        task: TaskId,
+        trait_id: TraitTypeId,
+        reader: TaskId,
+        filter: &mut dyn FnMut(RawVc) -> bool,
+        turbo_tasks: &dyn TurboTasksBackendApi<Self>,
+    ) -> AutoMap<RawVc, i32> {
+        self.read_task_collectibles(task, trait_id, reader, turbo_tasks)
+            .into_iter()
+            .filter(|&(raw, count)| count > 0 && filter(raw))
+            .collect()
+    }
+
     fn emit_collectible(
         &self,
         trait_type: TraitTypeId,
@@ -0,0 +1,130 @@
+use std::{
+    mem::take,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use indexmap::{map::Entry, IndexMap};
+use tokio::runtime::Handle;
+
+use crate::{util::StaticOrArc, InvalidationReason, Invalidator};
+
+/// Coalesces bursts of invalidations. Invalidations are collected for
+/// `window` after the first one and then applied together, so e.g. a mass of
+/// file events invalidates the affected tasks once instead of causing a
+/// recomputation wave per event.
+///
+/// Cloning is cheap, clones share the pending invalidations.
+#[derive(Clone)]
+pub struct InvalidationBatcher {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    window: Duration,
+    handle: Handle,
+    state: Mutex<BatchState>,
+}
+
+#[derive(Default)]
+struct BatchState {
+    /// Pending invalidations with the reason they were first invalidated for.
+    pending: IndexMap<Invalidator, Option<StaticOrArc<dyn InvalidationReason>>>,
+    /// Whether a flush is scheduled for the pending invalidations.
+    scheduled: bool,
+}
+
+impl InvalidationBatcher {
+    /// Creates a batcher that applies invalidations `window` after the first
+    /// one of a burst. Needs to be called within a tokio runtime.
+    pub fn new(window: Duration) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                window,
+                handle: Handle::current(),
+                state: Mutex::new(BatchState::default()),
+            }),
+        }
+    }
+
+    pub fn window(&self) -> Duration {
+        self.inner.window
+    }
+
+    pub fn invalidate(&self, invalidator: Invalidator) {
+        self.add(invalidator, None);
+    }
+
+    pub fn invalidate_with_reason<T: InvalidationReason>(
+        &self,
+        invalidator: Invalidator,
+        reason: T,
+    ) {
+        self.add(
+            invalidator,
+            Some((Arc::new(reason) as Arc<dyn InvalidationReason>).into()),
+        );
+    }
+
+    pub fn invalidate_with_static_reason<T: InvalidationReason>(
+        &self,
+        invalidator: Invalidator,
+        reason: &'static T,
+    ) {
+        self.add(
+            invalidator,
+            Some((reason as &'static dyn InvalidationReason).into()),
+        );
+    }
+
+    /// The number of tasks that wait to be invalidated.
+    pub fn pending(&self) -> usize {
+        self.inner.state.lock().unwrap().pending.len()
+    }
+
+    /// Applies all pending invalidations now.
+    pub fn flush(&self) {
+        self.inner.flush();
+    }
+
+    fn add(&self, invalidator: Invalidator, reason: Option<StaticOrArc<dyn InvalidationReason>>) {
+        if self.inner.window.is_zero() {
+            invalidator.invalidate_with_optional_reason(reason);
+            return;
+        }
+        let mut state = self.inner.state.lock().unwrap();
+        match state.pending.entry(invalidator) {
+            Entry::Occupied(mut entry) => {
+                // Keep a reason if the task was invalidated for one
+                if entry.get().is_none() {
+                    *entry.get_mut() = reason;
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(reason);
+            }
+        }
+        if !state.scheduled {
+            state.scheduled = true;
+            drop(state);
+            let inner = self.inner.clone();
+            self.inner.handle.spawn(async move {
+                tokio::time::sleep(inner.window).await;
+                inner.flush();
+            });
+        }
+    }
+}
+
+impl Inner {
+    fn flush(&self) {
+        let pending = {
+            let mut state = self.state.lock().unwrap();
+            state.scheduled = false;
+            take(&mut state.pending)
+        };
+        for (invalidator, reason) in pending {
+            invalidator.invalidate_with_optional_reason(reason);
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use std::{
+    fmt::{Debug, Formatter},
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, Weak,
+    },
+};
+
+use thiserror::Error;
+
+use crate::event::Event;
+
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+#[error("the operation was cancelled")]
+pub struct Cancelled;
+
+/// Cooperatively cancels task executions.
+///
+/// Task executions are polled against the token they were scheduled with, so
+/// cancelling it aborts them at their next await point instead of letting them
+/// run to completion only to be discarded. Aborted tasks are invalidated and
+/// recomputed the next time they are needed.
+///
+/// Cloning a token shares its state. Use [CancellationToken::child_token] to
+/// cancel a part of a subgraph separately.
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    cancelled: AtomicBool,
+    event: Event,
+    children: Mutex<Vec<Weak<Inner>>>,
+}
+
+impl Default for Inner {
+    fn default() -> Self {
+        Self {
+            cancelled: AtomicBool::new(false),
+            event: Event::new(|| "CancellationToken::event".to_string()),
+            children: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a token that is cancelled together with this one, but can also
+    /// be cancelled on its own.
+    pub fn child_token(&self) -> Self {
+        let child = Self::new();
+        {
+            let mut children = self.inner.children.lock().unwrap();
+            children.retain(|child| child.strong_count() > 0);
+            children.push(Arc::downgrade(&child.inner));
+        }
+        if self.is_cancelled() {
+            child.cancel();
+        }
+        child
+    }
+
+    pub fn cancel(&self) {
+        if self.inner.cancelled.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        self.inner.event.notify(usize::MAX);
+        let children = std::mem::take(&mut *self.inner.children.lock().unwrap());
+        for child in children.into_iter().filter_map(|child| child.upgrade()) {
+            CancellationToken { inner: child }.cancel();
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Resolves once the token is cancelled.
+    pub async fn cancelled(&self) {
+        loop {
+            if self.is_cancelled() {
+                return;
+            }
+            let listener = self.inner.event.listen();
+            // The token might have been cancelled before we started listening
+            if self.is_cancelled() {
+                return;
+            }
+            listener.await;
+        }
+    }
+
+    /// Polls `future` until it completes or the token is cancelled.
+    pub async fn run<T>(&self, future: impl Future<Output = T>) -> Result<T, Cancelled> {
+        tokio::select! {
+            biased;
+            _ = self.cancelled() => Err(Cancelled),
+            value = future => Ok(value),
+        }
+    }
+}
+
+impl Debug for CancellationToken {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CancellationToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{CancellationToken, Cancelled};
+
+    #[tokio::test]
+    async fn test_cancel_aborts_future() {
+        let token = CancellationToken::new();
+        let handle = tokio::spawn({
+            let token = token.clone();
+            async move { token.run(tokio::time::sleep(Duration::from_secs(60))).await }
+        });
+        token.cancel();
+        assert_eq!(handle.await.unwrap(), Err(Cancelled));
+        assert_eq!(token.run(async { 42 }).await, Err(Cancelled));
+    }
+
+    #[tokio::test]
+    async fn test_child_token() {
+        let parent = CancellationToken::new();
+        let child = parent.child_token();
+        let sibling = parent.child_token();
+
+        child.cancel();
+        assert!(!parent.is_cancelled());
+        assert!(!sibling.is_cancelled());
+        assert_eq!(sibling.run(async { 42 }).await, Ok(42));
+
+        parent.cancel();
+        assert!(sibling.is_cancelled());
+        assert!(parent.child_token().is_cancelled());
+    }
+}
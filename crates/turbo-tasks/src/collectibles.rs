@@ -5,4 +5,19 @@ use crate::{Vc, VcValueTrait};
 pub trait CollectiblesSource {
     fn take_collectibles<T: VcValueTrait + Send>(self) -> AutoSet<Vc<T>>;
     fn peek_collectibles<T: VcValueTrait + Send>(self) -> AutoSet<Vc<T>>;
+
+    /// Like [CollectiblesSource::take_collectibles], but only takes the
+    /// collectibles for which `predicate` returns true. The other collectibles
+    /// are left in place and continue to propagate up.
+    fn take_collectibles_filtered<T: VcValueTrait + Send>(
+        self,
+        predicate: impl FnMut(Vc<T>) -> bool,
+    ) -> AutoSet<Vc<T>>;
+
+    /// Like [CollectiblesSource::peek_collectibles], but only returns the
+    /// collectibles for which `predicate` returns true.
+    fn peek_collectibles_filtered<T: VcValueTrait + Send>(
+        self,
+        predicate: impl FnMut(Vc<T>) -> bool,
+    ) -> AutoSet<Vc<T>>;
 }
@@ -1,3 +1,5 @@
+use std::future::Future;
+
 use anyhow::Result;
 // This specific macro identifier is detected by turbo-tasks-build.
 use turbo_tasks_macros::generic_type as __turbo_tasks_internal_generic_type;
@@ -35,6 +37,35 @@ where
     pub fn is_some(self) -> Vc<bool> {
         option_is_some(Self::to_repr(self))
     }
+
+    /// Calls `f` with the value, if there is one. See [`Option::map`].
+    ///
+    /// This runs within the current task and doesn't create a task.
+    pub async fn try_map<U, F, Fut>(self, f: F) -> Result<Option<U>>
+    where
+        F: FnOnce(Vc<T>) -> Fut,
+        Fut: Future<Output = Result<U>>,
+    {
+        match *self.await? {
+            Some(value) => Ok(Some(f(value).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Calls `f` with the value, if there is one, and returns its result. See
+    /// [`Option::and_then`].
+    ///
+    /// This runs within the current task and doesn't create a task.
+    pub async fn try_and_then<U, F, Fut>(self, f: F) -> Result<Option<U>>
+    where
+        F: FnOnce(Vc<T>) -> Fut,
+        Fut: Future<Output = Result<Option<U>>>,
+    {
+        match *self.await? {
+            Some(value) => f(value).await,
+            None => Ok(None),
+        }
+    }
 }
 
 #[turbo_tasks::function]
@@ -1,4 +1,7 @@
+use std::future::Future;
+
 use anyhow::Result;
+use futures::{stream, StreamExt, TryStreamExt};
 // This specific macro identifier is detected by turbo-tasks-build.
 use turbo_tasks_macros::generic_type as __turbo_tasks_internal_generic_type;
 
@@ -35,6 +38,49 @@ where
     pub fn is_empty(self) -> Vc<bool> {
         vec_is_empty(Self::to_repr(self))
     }
+
+    /// Calls `f` for every item and collects the results in order. At most
+    /// `concurrency` calls are awaited at the same time.
+    ///
+    /// This runs within the current task, so unlike a task function it
+    /// doesn't create an intermediate task that holds all results.
+    pub async fn try_map<U, F, Fut>(self, concurrency: usize, f: F) -> Result<Vec<U>>
+    where
+        F: FnMut(Vc<T>) -> Fut,
+        Fut: Future<Output = Result<U>>,
+    {
+        let items = self.await?;
+        stream::iter(items.iter().copied().map(f))
+            .buffered(concurrency.max(1))
+            .try_collect()
+            .await
+    }
+
+    /// Like [`Self::try_map`], but skips the items for which `f` returns
+    /// `None`.
+    pub async fn try_filter_map<U, F, Fut>(self, concurrency: usize, f: F) -> Result<Vec<U>>
+    where
+        F: FnMut(Vc<T>) -> Fut,
+        Fut: Future<Output = Result<Option<U>>>,
+    {
+        let items = self.await?;
+        stream::iter(items.iter().copied().map(f))
+            .buffered(concurrency.max(1))
+            .try_filter_map(|item| async move { Ok(item) })
+            .try_collect()
+            .await
+    }
+
+    /// Like [`Self::try_map`], but flattens the results of `f`.
+    pub async fn try_flat_map<U, I, F, Fut>(self, concurrency: usize, f: F) -> Result<Vec<U>>
+    where
+        F: FnMut(Vc<T>) -> Fut,
+        Fut: Future<Output = Result<I>>,
+        I: IntoIterator<Item = U>,
+    {
+        let results: Vec<I> = self.try_map(concurrency, f).await?;
+        Ok(results.into_iter().flatten().collect())
+    }
 }
 
 #[turbo_tasks::function]
@@ -0,0 +1,135 @@
+use std::{
+    any::Any,
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Weak,
+    },
+};
+
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+
+use crate::ValueTypeId;
+
+/// The minimum number of insertions between two sweeps of dropped values.
+const MIN_SWEEP_INTERVAL: usize = 1024;
+
+type AnyArc = Arc<dyn Any + Send + Sync>;
+type AnyWeak = Weak<dyn Any + Send + Sync>;
+
+/// Stores structurally equal values only once. Values are held weakly, so an
+/// interned value is dropped as soon as no cell references it anymore.
+struct Interner {
+    /// Values by their type and hash.
+    values: DashMap<(ValueTypeId, u64), Vec<AnyWeak>>,
+    inserted: AtomicUsize,
+    /// The number of insertions after which dropped values are swept.
+    next_sweep: AtomicUsize,
+}
+
+static INTERNER: Lazy<Interner> = Lazy::new(Interner::new);
+
+/// Returns a shared reference to a value equal to `value`. Interning the same
+/// value twice returns the same [Arc].
+pub(crate) fn intern<T: Hash + Eq + Send + Sync + 'static>(ty: ValueTypeId, value: T) -> AnyArc {
+    INTERNER.intern(ty, value)
+}
+
+impl Interner {
+    fn new() -> Self {
+        Self {
+            values: DashMap::new(),
+            inserted: AtomicUsize::new(0),
+            next_sweep: AtomicUsize::new(MIN_SWEEP_INTERVAL),
+        }
+    }
+
+    fn intern<T: Hash + Eq + Send + Sync + 'static>(&self, ty: ValueTypeId, value: T) -> AnyArc {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        let hash = hasher.finish();
+        let arc = {
+            let mut bucket = self.values.entry((ty, hash)).or_default();
+            let mut found = None;
+            bucket.retain(|weak| {
+                let Some(existing) = weak.upgrade() else {
+                    return false;
+                };
+                if found.is_none() && existing.downcast_ref::<T>() == Some(&value) {
+                    found = Some(existing);
+                }
+                true
+            });
+            if let Some(existing) = found {
+                return existing;
+            }
+            let arc: AnyArc = Arc::new(value);
+            bucket.push(Arc::downgrade(&arc));
+            arc
+        };
+        let inserted = self.inserted.fetch_add(1, Ordering::Relaxed) + 1;
+        if inserted >= self.next_sweep.load(Ordering::Relaxed) {
+            self.sweep();
+        }
+        arc
+    }
+
+    /// Removes the entries of values that were dropped. The interval between
+    /// sweeps grows with the number of interned values, so sweeping takes
+    /// amortized constant time per insertion.
+    fn sweep(&self) {
+        let next_sweep = self.next_sweep.load(Ordering::Relaxed);
+        // Only one thread sweeps at a time
+        if self
+            .next_sweep
+            .compare_exchange(next_sweep, usize::MAX, Ordering::AcqRel, Ordering::Relaxed)
+            .is_err()
+        {
+            return;
+        }
+        self.values.retain(|_, bucket| {
+            bucket.retain(|weak| weak.strong_count() > 0);
+            !bucket.is_empty()
+        });
+        self.inserted.store(0, Ordering::Relaxed);
+        self.next_sweep
+            .store(self.values.len().max(MIN_SWEEP_INTERVAL), Ordering::Release);
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.values.iter().map(|bucket| bucket.len()).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::Interner;
+    use crate::ValueTypeId;
+
+    #[test]
+    fn test_intern() {
+        let interner = Interner::new();
+        let ty = ValueTypeId::from(1);
+        let a = interner.intern(ty, "a".to_string());
+        let also_a = interner.intern(ty, "a".to_string());
+        let b = interner.intern(ty, "b".to_string());
+        assert!(Arc::ptr_eq(&a, &also_a));
+        assert!(!Arc::ptr_eq(&a, &b));
+        assert_eq!(a.downcast_ref::<String>().unwrap(), "a");
+
+        // Values of different types are never shared
+        let other = interner.intern(ValueTypeId::from(2), "a".to_string());
+        assert!(!Arc::ptr_eq(&a, &other));
+
+        // Dropped values are swept
+        drop((a, also_a, b, other));
+        assert_eq!(interner.len(), 3);
+        interner.sweep();
+        assert_eq!(interner.len(), 0);
+    }
+}
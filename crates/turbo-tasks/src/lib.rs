@@ -35,6 +35,8 @@
 #![feature(never_type)]
 
 pub mod backend;
+mod batched_invalidation;
+pub mod cancellation;
 mod collectibles;
 mod completion;
 pub mod debug;
@@ -45,6 +47,7 @@ mod generics;
 pub mod graph;
 mod id;
 mod id_factory;
+mod interner;
 mod invalidation;
 mod join_iter_ext;
 mod keyed_cell;
@@ -57,12 +60,14 @@ mod no_move_vec;
 mod once_map;
 pub mod persisted_graph;
 pub mod primitives;
+mod priority;
 mod raw_vc;
 mod raw_vc_set;
 mod read_ref;
 pub mod registry;
 pub mod small_duration;
 mod state;
+mod structured_error;
 pub mod task;
 mod timed_future;
 pub mod trace;
@@ -74,6 +79,7 @@ mod vc;
 
 pub use anyhow::{Error, Result};
 use auto_hash_map::AutoSet;
+pub use batched_invalidation::InvalidationBatcher;
 pub use collectibles::CollectiblesSource;
 pub use completion::{Completion, Completions};
 pub use display::ValueToString;
@@ -87,16 +93,19 @@ pub use invalidation::{
 pub use join_iter_ext::{JoinIterExt, TryFlatJoinIterExt, TryJoinIterExt};
 pub use keyed_cell::{global_keyed_cell, keyed_cell};
 pub use manager::{
-    dynamic_call, emit, get_invalidator, mark_finished, mark_stateful, run_once,
+    current_cancellation_token, current_task_priority, dynamic_call, emit, get_invalidator,
+    mark_finished, mark_stateful, run_once, run_once_with_cancellation, run_once_with_priority,
     run_once_with_reason, spawn_blocking, spawn_thread, trait_call, turbo_tasks, CurrentCellRef,
     Invalidator, StatsType, TaskIdProvider, TurboTasks, TurboTasksApi, TurboTasksBackendApi,
     TurboTasksCallApi, Unused, UpdateInfo,
 };
 pub use native_function::NativeFunction;
 use nohash_hasher::BuildNoHashHasher;
-pub use raw_vc::{CellId, RawVc, ReadRawVcFuture, ResolveTypeError};
+pub use priority::TaskPriority;
+pub use raw_vc::{CellId, RawVc, ReadRawVcFuture, ReadTimeoutError, ResolveTypeError};
 pub use read_ref::ReadRef;
-pub use state::State;
+pub use state::{State, TransientState};
+pub use structured_error::{find_error, structured_error, StructuredError, TypedError};
 pub use task::{
     concrete_task_input::{ConcreteTaskInput, SharedReference, SharedValue},
     task_input::TaskInput,
@@ -106,8 +115,8 @@ pub use turbo_tasks_macros::{function, value, value_impl, value_trait, TaskInput
 pub use value::{TransientInstance, TransientValue, Value};
 pub use value_type::{TraitMethod, TraitType, ValueType};
 pub use vc::{
-    Dynamic, TypedForInput, Upcast, ValueDefault, Vc, VcCellNewMode, VcCellSharedMode,
-    VcDefaultRead, VcRead, VcTransparentRead, VcValueTrait, VcValueType,
+    Dynamic, TypedForInput, Upcast, ValueDefault, Vc, VcCellInternedMode, VcCellNewMode,
+    VcCellSharedMode, VcDefaultRead, VcRead, VcTransparentRead, VcValueTrait, VcValueType,
 };
 
 pub type TaskIdSet = AutoSet<TaskId, BuildNoHashHasher<TaskId>, 2>;
@@ -1,9 +1,12 @@
 //! Runtime helpers for [turbo-tasks-macro].
+use std::any::{type_name, TypeId};
+
+use dashmap::DashMap;
 pub use once_cell::sync::{Lazy, OnceCell};
 pub use tracing;
 
 pub use super::manager::{find_cell_by_type, notify_scheduled_tasks, spawn_detached};
-use crate::debug::ValueDebugFormatString;
+use crate::{debug::ValueDebugFormatString, registry, FunctionId, NativeFunction};
 
 #[inline(never)]
 pub async fn value_debug_format_field(value: ValueDebugFormatString<'_>) -> String {
@@ -15,3 +18,41 @@ pub async fn value_debug_format_field(value: ValueDebugFormatString<'_>) -> Stri
         Err(err) => format!("{0:?}", err),
     }
 }
+
+/// The instances of a generic `#[turbo_tasks::function]`. Every set of type
+/// arguments is registered as a separate function when it's first called.
+pub struct GenericFunctionInstances {
+    instances: Lazy<DashMap<TypeId, FunctionId>>,
+}
+
+impl GenericFunctionInstances {
+    #[allow(clippy::new_without_default)]
+    pub const fn new() -> Self {
+        Self {
+            instances: Lazy::new(DashMap::new),
+        }
+    }
+
+    /// Returns the function for the type arguments `TypeArgs`, creating and
+    /// registering it with `create` when it doesn't exist yet.
+    pub fn get<TypeArgs: 'static>(
+        &self,
+        global_name: &'static str,
+        create: impl FnOnce() -> NativeFunction,
+    ) -> FunctionId {
+        if let Some(id) = self.instances.get(&TypeId::of::<TypeArgs>()) {
+            return *id;
+        }
+        *self
+            .instances
+            .entry(TypeId::of::<TypeArgs>())
+            .or_insert_with(|| {
+                let function: &'static NativeFunction = Box::leak(Box::new(create()));
+                let global_name: &'static str = Box::leak(
+                    format!("{global_name}<{}>", type_name::<TypeArgs>()).into_boxed_str(),
+                );
+                function.register(global_name);
+                registry::get_function_id(function)
+            })
+    }
+}
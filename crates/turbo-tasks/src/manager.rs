@@ -1,15 +1,17 @@
 use std::{
     borrow::Cow,
     cell::RefCell,
-    future::Future,
+    collections::HashSet,
+    future::{poll_fn, Future},
     hash::Hash,
-    mem::take,
+    mem::{replace, take},
     panic::AssertUnwindSafe,
-    pin::Pin,
+    pin::{pin, Pin},
     sync::{
         atomic::{AtomicBool, AtomicUsize, Ordering},
         Arc, Mutex, Weak,
     },
+    task::Poll,
     thread,
     time::{Duration, Instant},
 };
@@ -19,14 +21,16 @@ use auto_hash_map::AutoMap;
 use futures::FutureExt;
 use nohash_hasher::BuildNoHashHasher;
 use serde::{de::Visitor, Deserialize, Serialize};
-use tokio::{runtime::Handle, select, task_local};
+use tokio::{runtime::Handle, select, sync::Semaphore, task_local};
 use tracing::{info_span, instrument, trace_span, Instrument, Level};
 
 use crate::{
     backend::{Backend, CellContent, PersistentTaskType, TaskExecutionSpec, TransientTaskType},
+    cancellation::CancellationToken,
     event::{Event, EventListener},
     id::{BackendJobId, FunctionId, TraitTypeId},
     id_factory::IdFactory,
+    interner::intern,
     raw_vc::{CellId, RawVc},
     registry,
     timed_future::{
@@ -35,7 +39,7 @@ use crate::{
     trace::TraceRawVcs,
     util::StaticOrArc,
     Completion, ConcreteTaskInput, InvalidationReason, InvalidationReasonSet, SharedReference,
-    TaskId, TaskIdSet, ValueTypeId, Vc, VcRead, VcValueTrait, VcValueType,
+    TaskId, TaskIdSet, TaskPriority, ValueTypeId, Vc, VcRead, VcValueTrait, VcValueType,
 };
 
 pub trait TurboTasksCallApi: Sync + Send {
@@ -87,6 +91,10 @@ pub trait TurboTasksApi: TurboTasksCallApi + Sync + Send {
         strongly_consistent: bool,
     ) -> Result<Result<RawVc, EventListener>>;
 
+    /// The number of tasks that still need to finish before the output of
+    /// `task` is strongly consistent, if known.
+    fn get_unfinished_tasks(&self, task: TaskId) -> Option<usize>;
+
     fn try_read_task_cell(
         &self,
         task: TaskId,
@@ -102,6 +110,15 @@ pub trait TurboTasksApi: TurboTasksCallApi + Sync + Send {
     ) -> Result<Result<CellContent, EventListener>>;
 
     fn read_task_collectibles(&self, task: TaskId, trait_id: TraitTypeId) -> AutoMap<RawVc, i32>;
+    /// Only reads the collectibles with a positive count for which `filter`
+    /// returns true. `filter` is called while the collectibles are locked, so
+    /// it must not access turbo tasks.
+    fn read_task_collectibles_filtered(
+        &self,
+        task: TaskId,
+        trait_id: TraitTypeId,
+        filter: &mut dyn FnMut(RawVc) -> bool,
+    ) -> AutoMap<RawVc, i32>;
 
     fn emit_collectible(&self, trait_type: TraitTypeId, collectible: RawVc);
     fn unemit_collectible(&self, trait_type: TraitTypeId, collectible: RawVc, count: u32);
@@ -277,6 +294,14 @@ pub struct TurboTasks<B: Backend + 'static> {
     // locking overhead.
     enable_full_stats: AtomicBool,
     program_start: Instant,
+    /// Task executions that weren't scheduled within a
+    /// [run_once_with_cancellation] are polled against this token
+    cancellation: Mutex<CancellationToken>,
+    currently_scheduled_user_blocking_tasks: AtomicUsize,
+    /// Background tasks that are waiting for user-blocking tasks to finish
+    deferred_background_tasks: Mutex<HashSet<TaskId>>,
+    deferred_background_tasks_count: AtomicUsize,
+    event_user_blocking: Event,
 }
 
 #[derive(Default)]
@@ -288,6 +313,9 @@ struct CurrentTaskState {
 
     // true, if the current task has state in cells
     stateful: bool,
+
+    // true, if the current task is waiting for a task or cell that it reads
+    waiting_for_read: bool,
 }
 
 // TODO implement our own thread pool and make these thread locals instead
@@ -300,6 +328,11 @@ task_local! {
     static CURRENT_TASK_ID: TaskId;
 
     static CURRENT_TASK_STATE: RefCell<CurrentTaskState>;
+
+    /// The token the current task execution is polled against
+    static CURRENT_CANCELLATION: CancellationToken;
+
+    static CURRENT_PRIORITY: TaskPriority;
 }
 
 impl<B: Backend + 'static> TurboTasks<B> {
@@ -328,6 +361,11 @@ impl<B: Backend + 'static> TurboTasks<B> {
             event_background: Event::new(|| "TurboTasks::event_background".to_string()),
             enable_full_stats: AtomicBool::new(false),
             program_start: Instant::now(),
+            cancellation: Mutex::new(CancellationToken::new()),
+            currently_scheduled_user_blocking_tasks: AtomicUsize::new(0),
+            deferred_background_tasks: Mutex::new(HashSet::new()),
+            deferred_background_tasks_count: AtomicUsize::new(0),
+            event_user_blocking: Event::new(|| "TurboTasks::event_user_blocking".to_string()),
         });
         this.backend.startup(&*this);
         this
@@ -450,6 +488,18 @@ impl<B: Backend + 'static> TurboTasks<B> {
         ))
     }
 
+    /// Aborts all task executions that are in flight, except those scheduled
+    /// within a [run_once_with_cancellation]. Aborted tasks don't publish a
+    /// result; they stay dirty until they are read again, so readers only ever
+    /// see the output of a complete execution.
+    pub fn cancel_in_flight(&self) {
+        let token = replace(
+            &mut *self.cancellation.lock().unwrap(),
+            CancellationToken::new(),
+        );
+        token.cancel();
+    }
+
     #[track_caller]
     pub(crate) fn schedule(&self, task_id: TaskId) {
         self.begin_primary_job();
@@ -458,8 +508,30 @@ impl<B: Backend + 'static> TurboTasks<B> {
         #[cfg(feature = "tokio_tracing")]
         let description = self.backend.get_task_description(task_id);
 
+        // Tasks scheduled from within a cancellable execution belong to the
+        // same subgraph. Tasks rescheduled after being cancelled start over.
+        let cancellation = CURRENT_CANCELLATION
+            .try_with(|token| token.clone())
+            .ok()
+            .filter(|token| !token.is_cancelled())
+            .unwrap_or_else(|| self.cancellation.lock().unwrap().clone());
+        let scope_cancellation = cancellation.clone();
+
+        let priority = current_task_priority();
+        if priority == TaskPriority::UserBlocking {
+            self.currently_scheduled_user_blocking_tasks
+                .fetch_add(1, Ordering::AcqRel);
+        }
+
         let this = self.pin();
         let future = async move {
+            if priority == TaskPriority::Background {
+                this.wait_for_user_blocking_tasks(task_id).await;
+            }
+            let concurrency_limit = this
+                .backend
+                .get_task_function(task_id)
+                .and_then(|function| registry::get_function(function).concurrency_limit());
             #[allow(clippy::blocks_in_if_conditions)]
             while CURRENT_TASK_STATE
                 .scope(Default::default(), async {
@@ -477,9 +549,21 @@ impl<B: Backend + 'static> TurboTasks<B> {
                             };
 
                             async {
-                                let (result, duration, instant) =
-                                    TimedFuture::new(AssertUnwindSafe(future).catch_unwind()).await;
-
+                                let future = with_concurrency_limit(concurrency_limit, future);
+                                let (result, duration, instant) = TimedFuture::new(
+                                    cancellation.run(AssertUnwindSafe(future).catch_unwind()),
+                                )
+                                .await;
+
+                                let Ok(result) = result else {
+                                    // A cancelled execution has no result, the task stays dirty
+                                    // until it's read again
+                                    let stateful = this.finish_current_task_state();
+                                    this.backend.task_execution_cancelled(
+                                        task_id, duration, instant, stateful, &*this,
+                                    );
+                                    return false;
+                                };
                                 let result = result.map_err(|any| match any.downcast::<String>() {
                                     Ok(owned) => Some(Cow::Owned(*owned)),
                                     Err(any) => match any.downcast::<&'static str>() {
@@ -500,6 +584,9 @@ impl<B: Backend + 'static> TurboTasks<B> {
                 })
                 .await
             {}
+            if priority == TaskPriority::UserBlocking {
+                this.finish_user_blocking_task();
+            }
             this.finish_primary_job();
             anyhow::Ok(())
         };
@@ -507,7 +594,14 @@ impl<B: Backend + 'static> TurboTasks<B> {
         let future = TURBO_TASKS
             .scope(
                 self.pin(),
-                CURRENT_TASK_ID.scope(task_id, self.backend.execution_scope(task_id, future)),
+                CURRENT_TASK_ID.scope(
+                    task_id,
+                    CURRENT_CANCELLATION.scope(
+                        scope_cancellation,
+                        CURRENT_PRIORITY
+                            .scope(priority, self.backend.execution_scope(task_id, future)),
+                    ),
+                ),
             )
             .in_current_span();
 
@@ -520,6 +614,80 @@ impl<B: Backend + 'static> TurboTasks<B> {
         tokio::task::spawn(future);
     }
 
+    /// Defers a background task execution until no user-blocking executions
+    /// are in flight, the task is promoted or the deferral times out.
+    async fn wait_for_user_blocking_tasks(&self, task_id: TaskId) {
+        /// Bounds the deferral, as user-blocking tasks might wait for the
+        /// background task indirectly.
+        const MAX_DEFERRAL: Duration = Duration::from_millis(100);
+
+        if self
+            .currently_scheduled_user_blocking_tasks
+            .load(Ordering::Acquire)
+            == 0
+        {
+            return;
+        }
+        self.deferred_background_tasks
+            .lock()
+            .unwrap()
+            .insert(task_id);
+        self.deferred_background_tasks_count
+            .fetch_add(1, Ordering::AcqRel);
+        let deadline = tokio::time::sleep(MAX_DEFERRAL);
+        tokio::pin!(deadline);
+        loop {
+            let listener = self.event_user_blocking.listen();
+            if self
+                .currently_scheduled_user_blocking_tasks
+                .load(Ordering::Acquire)
+                == 0
+                || !self
+                    .deferred_background_tasks
+                    .lock()
+                    .unwrap()
+                    .contains(&task_id)
+            {
+                break;
+            }
+            select! {
+                _ = listener => {}
+                _ = &mut deadline => break,
+            }
+        }
+        self.deferred_background_tasks
+            .lock()
+            .unwrap()
+            .remove(&task_id);
+        self.deferred_background_tasks_count
+            .fetch_sub(1, Ordering::AcqRel);
+    }
+
+    /// Starts a deferred background task right away, because a user-blocking
+    /// task needs it.
+    fn promote_deferred_task(&self, task_id: TaskId) {
+        if self.deferred_background_tasks_count.load(Ordering::Acquire) != 0
+            && current_task_priority() == TaskPriority::UserBlocking
+            && self
+                .deferred_background_tasks
+                .lock()
+                .unwrap()
+                .remove(&task_id)
+        {
+            self.event_user_blocking.notify(usize::MAX);
+        }
+    }
+
+    fn finish_user_blocking_task(&self) {
+        if self
+            .currently_scheduled_user_blocking_tasks
+            .fetch_sub(1, Ordering::AcqRel)
+            == 1
+        {
+            self.event_user_blocking.notify(usize::MAX);
+        }
+    }
+
     fn begin_primary_job(&self) {
         if self
             .currently_scheduled_tasks
@@ -802,6 +970,7 @@ impl<B: Backend + 'static> TurboTasks<B> {
             let CurrentTaskState {
                 tasks_to_notify,
                 stateful,
+                ..
             } = &mut *cell.borrow_mut();
             let tasks = take(tasks_to_notify);
             if !tasks.is_empty() {
@@ -911,6 +1080,7 @@ impl<B: Backend + 'static> TurboTasksApi for TurboTasks<B> {
         task: TaskId,
         strongly_consistent: bool,
     ) -> Result<Result<RawVc, EventListener>> {
+        self.promote_deferred_task(task);
         self.backend.try_read_task_output(
             task,
             current_task("reading Vcs"),
@@ -928,11 +1098,16 @@ impl<B: Backend + 'static> TurboTasksApi for TurboTasks<B> {
             .try_read_task_output_untracked(task, strongly_consistent, self)
     }
 
+    fn get_unfinished_tasks(&self, task: TaskId) -> Option<usize> {
+        self.backend.get_unfinished_tasks(task, self)
+    }
+
     fn try_read_task_cell(
         &self,
         task: TaskId,
         index: CellId,
     ) -> Result<Result<CellContent, EventListener>> {
+        self.promote_deferred_task(task);
         self.backend
             .try_read_task_cell(task, index, current_task("reading Vcs"), self)
     }
@@ -963,6 +1138,21 @@ impl<B: Backend + 'static> TurboTasksApi for TurboTasks<B> {
         )
     }
 
+    fn read_task_collectibles_filtered(
+        &self,
+        task: TaskId,
+        trait_id: TraitTypeId,
+        filter: &mut dyn FnMut(RawVc) -> bool,
+    ) -> AutoMap<RawVc, i32> {
+        self.backend.read_task_collectibles_filtered(
+            task,
+            trait_id,
+            current_task("reading collectibles"),
+            filter,
+            self,
+        )
+    }
+
     fn emit_collectible(&self, trait_type: TraitTypeId, collectible: RawVc) {
         self.backend.emit_collectible(
             trait_type,
@@ -1223,6 +1413,24 @@ impl Invalidator {
                 .invalidate_with_reason(task, (reason as &'static dyn InvalidationReason).into());
         }
     }
+
+    pub(crate) fn invalidate_with_optional_reason(
+        self,
+        reason: Option<StaticOrArc<dyn InvalidationReason>>,
+    ) {
+        let Invalidator {
+            task,
+            turbo_tasks,
+            handle,
+        } = self;
+        let _ = handle.enter();
+        if let Some(turbo_tasks) = turbo_tasks.upgrade() {
+            match reason {
+                Some(reason) => turbo_tasks.invalidate_with_reason(task, reason),
+                None => turbo_tasks.invalidate(task),
+            }
+        }
+    }
 }
 
 impl TraceRawVcs for Invalidator {
@@ -1292,6 +1500,82 @@ pub async fn run_once<T: Send + 'static>(
     Ok(rx.await?)
 }
 
+/// Like [run_once], but the task executions it schedules are polled against
+/// `token`, so cancelling it aborts the whole subgraph. Returns
+/// [Cancelled](crate::cancellation::Cancelled) as
+/// error when the token is cancelled before the future completes.
+pub async fn run_once_with_cancellation<T: Send + 'static>(
+    tt: Arc<dyn TurboTasksApi>,
+    token: CancellationToken,
+    future: impl Future<Output = Result<T>> + Send + 'static,
+) -> Result<T> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+
+    let task_id = CURRENT_CANCELLATION.sync_scope(token.clone(), || {
+        tt.run_once(Box::pin(async move {
+            let result = future.await?;
+            tx.send(result)
+                .map_err(|_| anyhow!("unable to send result"))?;
+            Ok(())
+        }))
+    });
+
+    token
+        .run(async {
+            // INVALIDATION: A Once task will never invalidate, therefore we don't need to
+            // track a dependency
+            let raw_result = read_task_output_untracked(&*tt, task_id, false).await?;
+            raw_result
+                .into_read_untracked_with_turbo_tasks::<Completion>(&*tt)
+                .await?;
+
+            Ok(rx.await?)
+        })
+        .await?
+}
+
+/// Like [run_once], but the task executions it schedules are scheduled with
+/// `priority`.
+pub async fn run_once_with_priority<T: Send + 'static>(
+    tt: Arc<dyn TurboTasksApi>,
+    priority: TaskPriority,
+    future: impl Future<Output = Result<T>> + Send + 'static,
+) -> Result<T> {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+
+    let task_id = CURRENT_PRIORITY.sync_scope(priority, || {
+        tt.run_once(Box::pin(async move {
+            let result = future.await?;
+            tx.send(result)
+                .map_err(|_| anyhow!("unable to send result"))?;
+            Ok(())
+        }))
+    });
+
+    // INVALIDATION: A Once task will never invalidate, therefore we don't need to
+    // track a dependency
+    let raw_result = read_task_output_untracked(&*tt, task_id, false).await?;
+    raw_result
+        .into_read_untracked_with_turbo_tasks::<Completion>(&*tt)
+        .await?;
+
+    Ok(rx.await?)
+}
+
+/// Returns the priority of the current task execution. Outside of task
+/// executions this is [TaskPriority::UserBlocking].
+pub fn current_task_priority() -> TaskPriority {
+    CURRENT_PRIORITY
+        .try_with(|priority| *priority)
+        .unwrap_or_default()
+}
+
+/// Returns the token the current task execution is polled against, for
+/// checking cancellation in long synchronous sections.
+pub fn current_cancellation_token() -> Option<CancellationToken> {
+    CURRENT_CANCELLATION.try_with(|token| token.clone()).ok()
+}
+
 pub async fn run_once_with_reason<T: Send + 'static>(
     tt: Arc<dyn TurboTasksApi>,
     reason: impl InvalidationReason,
@@ -1396,6 +1680,54 @@ pub fn mark_stateful() {
     })
 }
 
+/// Marks the current task as waiting for a task or cell that it reads, which
+/// releases its concurrency permit until it continues.
+pub(crate) fn mark_waiting_for_read() {
+    let _ = CURRENT_TASK_STATE.try_with(|cell| cell.borrow_mut().waiting_for_read = true);
+}
+
+fn take_waiting_for_read() -> bool {
+    CURRENT_TASK_STATE
+        .try_with(|cell| take(&mut cell.borrow_mut().waiting_for_read))
+        .unwrap_or_default()
+}
+
+/// Executes `future` while holding a permit of `semaphore`, if any. The permit
+/// is released while the task waits for the tasks it reads, so tasks of a
+/// limited function may read other tasks of the same function, e.g. when the
+/// function is recursive, without deadlocking.
+async fn with_concurrency_limit<F: Future>(
+    semaphore: Option<&'static Semaphore>,
+    future: F,
+) -> F::Output {
+    let Some(semaphore) = semaphore else {
+        return future.await;
+    };
+    let mut future = pin!(future);
+    loop {
+        // The semaphore is never closed
+        let permit = semaphore.acquire().await.ok();
+        let output = poll_fn(|cx| match future.as_mut().poll(cx) {
+            Poll::Ready(output) => Poll::Ready(Some(output)),
+            Poll::Pending if take_waiting_for_read() => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        })
+        .await;
+        drop(permit);
+        if let Some(output) = output {
+            return output;
+        }
+        // The read wakes this task once it can continue, and only then the task
+        // waits for a permit again
+        let mut woken = false;
+        poll_fn(|_| match replace(&mut woken, true) {
+            true => Poll::Ready(()),
+            false => Poll::Pending,
+        })
+        .await;
+    }
+}
+
 /// Notifies scheduled tasks for execution.
 pub fn notify_scheduled_tasks() {
     with_turbo_tasks(|tt| tt.notify_scheduled_tasks())
@@ -1530,6 +1862,16 @@ impl CurrentCellRef {
         )
     }
 
+    /// Updates the cell with an interned copy of `new_content`. As equal values
+    /// share the same reference, the cell is only updated when the value
+    /// changed.
+    pub fn update_interned<T: Hash + Eq + VcValueType + 'static>(&self, new_content: T) {
+        self.update_shared_reference(SharedReference(
+            Some(self.index.type_id),
+            intern(self.index.type_id, new_content),
+        ))
+    }
+
     pub fn update_shared_reference(&self, shared_ref: SharedReference) {
         let tt = turbo_tasks();
         let content = tt.read_own_task_cell(self.current_task, self.index).ok();
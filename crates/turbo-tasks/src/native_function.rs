@@ -1,5 +1,6 @@
 use std::{fmt::Debug, hash::Hash};
 
+use tokio::sync::Semaphore;
 use tracing::Span;
 
 use crate::{
@@ -19,6 +20,10 @@ pub struct NativeFunction {
     /// handles the task execution.
     #[turbo_tasks(debug_ignore, trace_ignore)]
     pub implementation: Box<dyn TaskFn + Send + Sync + 'static>,
+    /// Limits how many tasks of the function execute at the same time. Set
+    /// with `#[turbo_tasks::function(max_concurrency = N)]`.
+    #[turbo_tasks(debug_ignore, trace_ignore)]
+    concurrency_limit: Option<Semaphore>,
 }
 
 impl Debug for NativeFunction {
@@ -37,9 +42,26 @@ impl NativeFunction {
         Self {
             name,
             implementation: Box::new(implementation.into_task_fn()),
+            concurrency_limit: None,
         }
     }
 
+    /// Allows at most `max_concurrency` tasks of this function to execute at
+    /// the same time. Other tasks wait in the scheduler before they start.
+    ///
+    /// A task releases its permit while it waits for the tasks it reads, so
+    /// tasks may read other tasks of the same function.
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.concurrency_limit = Some(Semaphore::new(max_concurrency));
+        self
+    }
+
+    /// The semaphore that limits the concurrent executions of this function,
+    /// if any.
+    pub fn concurrency_limit(&'static self) -> Option<&'static Semaphore> {
+        self.concurrency_limit.as_ref()
+    }
+
     /// Creates a functor for execution from a fixed set of inputs.
     pub fn bind(&'static self, inputs: &[ConcreteTaskInput]) -> NativeTaskFn {
         match (self.implementation).functor(inputs) {
@@ -0,0 +1,28 @@
+use std::fmt::{Display, Formatter};
+
+/// A scheduling hint for task executions.
+///
+/// Task executions inherit the priority of the task that scheduled them.
+/// Background executions are deferred while user-blocking executions are in
+/// flight, so speculative work doesn't compete with work someone is waiting
+/// for. The deferral is bounded, and a background task is promoted as soon as
+/// a user-blocking task reads it, so it can't block user-blocking work
+/// indefinitely.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub enum TaskPriority {
+    // The order influences priority. Put the highest priority first.
+    /// Someone is waiting for the result, e.g. a page reload or HMR update.
+    #[default]
+    UserBlocking,
+    /// Speculative work that nobody is waiting for yet.
+    Background,
+}
+
+impl Display for TaskPriority {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaskPriority::UserBlocking => write!(f, "user-blocking"),
+            TaskPriority::Background => write!(f, "background"),
+        }
+    }
+}
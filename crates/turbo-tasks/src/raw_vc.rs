@@ -7,6 +7,7 @@ use std::{
     pin::Pin,
     sync::Arc,
     task::Poll,
+    time::{Duration, Instant},
 };
 
 use anyhow::Result;
@@ -251,6 +252,36 @@ impl CollectiblesSource for RawVc {
             .filter_map(|(raw, count)| (count > 0).then_some(raw.into()))
             .collect()
     }
+
+    fn peek_collectibles_filtered<T: VcValueTrait + Send>(
+        self,
+        mut predicate: impl FnMut(Vc<T>) -> bool,
+    ) -> AutoSet<Vc<T>> {
+        let tt = turbo_tasks();
+        tt.notify_scheduled_tasks();
+        let map = tt.read_task_collectibles_filtered(
+            self.get_task_id(),
+            T::get_trait_type_id(),
+            &mut |raw| predicate(raw.into()),
+        );
+        map.into_iter().map(|(raw, _)| raw.into()).collect()
+    }
+
+    fn take_collectibles_filtered<T: VcValueTrait + Send>(
+        self,
+        mut predicate: impl FnMut(Vc<T>) -> bool,
+    ) -> AutoSet<Vc<T>> {
+        let tt = turbo_tasks();
+        tt.notify_scheduled_tasks();
+        // Only the matching collectibles are unemitted
+        let matching = tt.read_task_collectibles_filtered(
+            self.get_task_id(),
+            T::get_trait_type_id(),
+            &mut |raw| predicate(raw.into()),
+        );
+        tt.unemit_collectibles(T::get_trait_type_id(), &matching);
+        matching.into_iter().map(|(raw, _)| raw.into()).collect()
+    }
 }
 
 impl Display for RawVc {
@@ -343,6 +374,60 @@ impl<T: ?Sized, Cast: VcCast> ReadRawVcFuture<T, Cast> {
     }
 }
 
+/// How often [ReadRawVcFuture::with_progress] reports progress.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A read didn't finish within the timeout passed to
+/// [ReadRawVcFuture::with_progress].
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq, Hash)]
+#[error("reading timed out after {timeout:?}")]
+pub struct ReadTimeoutError {
+    pub timeout: Duration,
+    /// The number of tasks that were still unfinished, if known.
+    pub unfinished_tasks: Option<usize>,
+}
+
+impl<T: ?Sized, Cast: VcCast> ReadRawVcFuture<T, Cast> {
+    /// Waits for the read, calling `progress` with the number of tasks that
+    /// still need to finish in regular intervals. Fails with a
+    /// [ReadTimeoutError] when the read doesn't finish within `timeout`.
+    ///
+    /// This is meant for strongly consistent reads, which can take a long time
+    /// when a lot needs to be recomputed.
+    pub async fn with_progress(
+        mut self,
+        timeout: Option<Duration>,
+        mut progress: impl FnMut(usize),
+    ) -> Result<Cast::Output> {
+        let task = self.current.get_task_id();
+        let deadline = timeout.map(|timeout| (timeout, Instant::now() + timeout));
+        loop {
+            let wait = match deadline {
+                Some((_, deadline)) => deadline
+                    .saturating_duration_since(Instant::now())
+                    .min(PROGRESS_INTERVAL),
+                None => PROGRESS_INTERVAL,
+            };
+            if let Ok(result) = tokio::time::timeout(wait, &mut self).await {
+                return result;
+            }
+            let unfinished_tasks = self.turbo_tasks.get_unfinished_tasks(task);
+            if let Some((timeout, deadline)) = deadline {
+                if Instant::now() >= deadline {
+                    return Err(ReadTimeoutError {
+                        timeout,
+                        unfinished_tasks,
+                    }
+                    .into());
+                }
+            }
+            if let Some(unfinished_tasks) = unfinished_tasks {
+                progress(unfinished_tasks);
+            }
+        }
+    }
+}
+
 impl<T: ?Sized, Cast: VcCast> Future for ReadRawVcFuture<T, Cast> {
     type Output = Result<Cast::Output>;
 
@@ -355,6 +440,7 @@ impl<T: ?Sized, Cast: VcCast> Future for ReadRawVcFuture<T, Cast> {
                 // SAFETY: listener is from previous pinned this
                 let listener = unsafe { Pin::new_unchecked(listener) };
                 if listener.poll(cx).is_pending() {
+                    crate::manager::mark_waiting_for_read();
                     return Poll::Pending;
                 }
                 this.listener = None;
@@ -402,6 +488,7 @@ impl<T: ?Sized, Cast: VcCast> Future for ReadRawVcFuture<T, Cast> {
                 Poll::Ready(_) => continue,
                 Poll::Pending => {
                     this.listener = Some(listener);
+                    crate::manager::mark_waiting_for_read();
                     return Poll::Pending;
                 }
             };
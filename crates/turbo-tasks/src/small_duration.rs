@@ -1,132 +1,316 @@
 use std::{
     fmt::{Debug, Display},
+    iter::Sum,
+    ops::{Add, AddAssign, Sub, SubAssign},
     time::Duration,
 };
 
-/// Stores a [`Duration`] in a given precision (in nanoseconds) in 4 bytes.
-///
-/// For instance, for `P = 10_000` (10 microseconds), this allows a for a total
-/// duration of 11.9 hours. Values smaller than 10 microseconds are stored as 10
-/// microseconds.
-#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Default)]
-pub struct SmallDuration<const P: u64>(u32);
-
-impl<const P: u64> SmallDuration<P> {
-    pub const ZERO: SmallDuration<P> = SmallDuration(0);
-    // TODO(alexkirsz) Figure out if MIN should be 0 or 1.
-    pub const MIN: SmallDuration<P> = SmallDuration(1);
-    pub const MAX: SmallDuration<P> = SmallDuration(u32::MAX);
-
-    pub const fn from_nanos(nanos: u64) -> Self {
-        if nanos == 0 {
-            return SmallDuration::ZERO;
-        }
-        if nanos <= P {
-            return SmallDuration::MIN;
-        }
-        let value = nanos / P;
-        if value > u32::MAX as u64 {
-            return SmallDuration::MAX;
-        }
-        SmallDuration(value as u32)
-    }
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// How to round a duration that falls between two multiples of the precision.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub enum Rounding {
+    /// Truncate towards zero. This is what the `From` conversions and the
+    /// `from_*` constructors do.
+    #[default]
+    Down,
+    /// Round to the nearest multiple, with ties rounding up.
+    Nearest,
+    /// Round up to the next multiple.
+    Up,
+}
 
-    pub const fn from_micros(micros: u64) -> Self {
-        if micros == 0 {
-            return SmallDuration::ZERO;
-        }
-        let micros_precision = P / 1_000;
-        if micros <= micros_precision {
-            return SmallDuration::MIN;
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+macro_rules! small_duration {
+    ($(#[$meta:meta])* $name:ident($repr:ty), $expected:literal) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Default)]
+        pub struct $name<const P: u64>($repr);
+
+        impl<const P: u64> $name<P> {
+            pub const ZERO: $name<P> = $name(0);
+            // TODO(alexkirsz) Figure out if MIN should be 0 or 1.
+            pub const MIN: $name<P> = $name(1);
+            pub const MAX: $name<P> = $name(<$repr>::MAX);
+
+            /// Like [`Self::from_nanos`], but with an explicit rounding mode.
+            /// Truncating systematically underestimates durations, which biases
+            /// aggregated statistics low.
+            pub const fn from_nanos_rounded(nanos: u64, rounding: Rounding) -> Self {
+                Self::from_nanos_u128(nanos as u128, rounding)
+            }
+
+            /// Like `Self::from(duration)`, but with an explicit rounding
+            /// mode.
+            pub const fn from_duration_rounded(duration: Duration, rounding: Rounding) -> Self {
+                Self::from_nanos_u128(duration.as_nanos(), rounding)
+            }
+
+            const fn from_nanos_u128(nanos: u128, rounding: Rounding) -> Self {
+                if nanos == 0 {
+                    return $name::ZERO;
+                }
+                let precision = P as u128;
+                let quotient = nanos / precision;
+                let remainder = nanos % precision;
+                let value = match rounding {
+                    Rounding::Down => quotient,
+                    Rounding::Nearest if remainder >= precision - remainder => quotient + 1,
+                    Rounding::Nearest => quotient,
+                    Rounding::Up if remainder > 0 => quotient + 1,
+                    Rounding::Up => quotient,
+                };
+                // Non-zero durations never round to zero.
+                if value == 0 {
+                    return $name::MIN;
+                }
+                if value > <$repr>::MAX as u128 {
+                    return $name::MAX;
+                }
+                $name(value as $repr)
+            }
+
+            pub const fn from_nanos(nanos: u64) -> Self {
+                if nanos == 0 {
+                    return $name::ZERO;
+                }
+                if nanos <= P {
+                    return $name::MIN;
+                }
+                let value = nanos / P;
+                if value as u128 > <$repr>::MAX as u128 {
+                    return $name::MAX;
+                }
+                $name(value as $repr)
+            }
+
+            pub const fn from_micros(micros: u64) -> Self {
+                if micros == 0 {
+                    return $name::ZERO;
+                }
+                let micros_precision = P / 1_000;
+                if micros <= micros_precision {
+                    return $name::MIN;
+                }
+                let value = micros * 1_000 / P;
+                if value as u128 > <$repr>::MAX as u128 {
+                    return $name::MAX;
+                }
+                $name(value as $repr)
+            }
+
+            pub const fn from_millis(millis: u64) -> Self {
+                if millis == 0 {
+                    return $name::ZERO;
+                }
+                let millis_precision = P / 1_000_000;
+                if millis <= millis_precision {
+                    return $name::MIN;
+                }
+                let value = millis * 1_000_000 / P;
+                if value as u128 > <$repr>::MAX as u128 {
+                    return $name::MAX;
+                }
+                $name(value as $repr)
+            }
+
+            pub const fn from_secs(secs: u64) -> Self {
+                if secs == 0 {
+                    return $name::ZERO;
+                }
+                let secs_precision = P / 1_000_000_000;
+                if secs <= secs_precision {
+                    return $name::MIN;
+                }
+                let value = secs * 1_000_000_000 / P;
+                if value as u128 > <$repr>::MAX as u128 {
+                    return $name::MAX;
+                }
+                $name(value as $repr)
+            }
+
+            /// Returns the raw value, in units of `P` nanoseconds.
+            pub const fn to_raw(self) -> $repr {
+                self.0
+            }
+
+            /// Creates a duration from a raw value, in units of `P` nanoseconds.
+            pub const fn from_raw(raw: $repr) -> Self {
+                $name(raw)
+            }
+
+            /// Adds two durations, returning `None` if the result would exceed
+            /// [`Self::MAX`].
+            pub const fn checked_add(self, rhs: Self) -> Option<Self> {
+                match self.0.checked_add(rhs.0) {
+                    Some(value) => Some($name(value)),
+                    None => None,
+                }
+            }
+
+            /// Subtracts `rhs` from `self`, returning `None` if the result would be
+            /// negative.
+            pub const fn checked_sub(self, rhs: Self) -> Option<Self> {
+                match self.0.checked_sub(rhs.0) {
+                    Some(value) => Some($name(value)),
+                    None => None,
+                }
+            }
+
+            /// Adds two durations, saturating at [`Self::MAX`].
+            pub const fn saturating_add(self, rhs: Self) -> Self {
+                $name(self.0.saturating_add(rhs.0))
+            }
+
+            /// Subtracts `rhs` from `self`, saturating at [`Self::ZERO`].
+            pub const fn saturating_sub(self, rhs: Self) -> Self {
+                $name(self.0.saturating_sub(rhs.0))
+            }
+
+            /// Scales the duration by `rhs`, saturating at [`Self::MAX`].
+            /// Negative and NaN factors result in [`Self::ZERO`].
+            pub fn mul_f64(self, rhs: f64) -> Self {
+                // Float to int `as` casts saturate, and map NaN to 0.
+                $name((self.0 as f64 * rhs) as $repr)
+            }
+
+            pub(self) fn to_duration(self) -> Duration {
+                let nanos = self.0 as u128 * P as u128;
+                let secs = nanos / NANOS_PER_SEC;
+                Duration::new(
+                    secs.try_into().unwrap_or(u64::MAX),
+                    (nanos % NANOS_PER_SEC) as u32,
+                )
+            }
         }
-        let value = micros * 1_000 / P;
-        if value > u32::MAX as u64 {
-            return SmallDuration::MAX;
+
+        /// Saturates at [`Self::MAX`] rather than panicking on overflow.
+        impl<const P: u64> Add for $name<P> {
+            type Output = Self;
+
+            fn add(self, rhs: Self) -> Self {
+                self.saturating_add(rhs)
+            }
         }
-        SmallDuration(value as u32)
-    }
 
-    pub const fn from_millis(millis: u64) -> Self {
-        if millis == 0 {
-            return SmallDuration::ZERO;
+        impl<const P: u64> AddAssign for $name<P> {
+            fn add_assign(&mut self, rhs: Self) {
+                *self = *self + rhs;
+            }
         }
-        let millis_precision = P / 1_000_000;
-        if millis <= millis_precision {
-            return SmallDuration::MIN;
+
+        /// Saturates at [`Self::ZERO`] rather than panicking on underflow.
+        impl<const P: u64> Sub for $name<P> {
+            type Output = Self;
+
+            fn sub(self, rhs: Self) -> Self {
+                self.saturating_sub(rhs)
+            }
         }
-        let value = millis * 1_000_000 / P;
-        if value > u32::MAX as u64 {
-            return SmallDuration::MAX;
+
+        impl<const P: u64> SubAssign for $name<P> {
+            fn sub_assign(&mut self, rhs: Self) {
+                *self = *self - rhs;
+            }
         }
-        SmallDuration(value as u32)
-    }
 
-    pub const fn from_secs(secs: u64) -> Self {
-        if secs == 0 {
-            return SmallDuration::ZERO;
+        impl<const P: u64> Sum for $name<P> {
+            fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+                iter.fold($name::ZERO, Add::add)
+            }
         }
-        let secs_precision = P / 1_000_000_000;
-        if secs <= secs_precision {
-            return SmallDuration::MIN;
+
+        impl<const P: u64> From<Duration> for $name<P> {
+            fn from(duration: Duration) -> Self {
+                if duration.is_zero() {
+                    return $name::ZERO;
+                }
+                let nanos = duration.as_nanos();
+                if nanos <= P as u128 {
+                    return $name::MIN;
+                }
+                (nanos / P as u128)
+                    .try_into()
+                    .map_or($name::MAX, $name)
+            }
         }
-        let value = secs * 1_000_000_000 / P;
-        if value > u32::MAX as u64 {
-            return SmallDuration::MAX;
+
+        impl<const P: u64> From<$name<P>> for Duration {
+            fn from(duration: $name<P>) -> Self {
+                duration.to_duration()
+            }
         }
-        SmallDuration(value as u32)
-    }
 
-    pub(self) fn to_duration(self) -> Duration {
-        Duration::from_nanos(self.0 as u64 * P)
-    }
-}
+        impl<const P: u64> Serialize for $name<P> {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                self.0.serialize(serializer)
+            }
+        }
 
-impl<const P: u64> From<Duration> for SmallDuration<P> {
-    fn from(duration: Duration) -> Self {
-        if duration.is_zero() {
-            return SmallDuration::ZERO;
+        impl<'de, const P: u64> Deserialize<'de> for $name<P> {
+            // The conversion is a no-op for the 64-bit variant.
+            #[allow(clippy::useless_conversion)]
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                // Accept any unsigned integer but reject values that don't fit rather
+                // than silently truncating them.
+                let raw = u64::deserialize(deserializer)?;
+                <$repr>::try_from(raw).map($name).map_err(|_| {
+                    de::Error::invalid_value(de::Unexpected::Unsigned(raw), &$expected)
+                })
+            }
         }
-        let nanos = duration.as_nanos();
-        if nanos <= P as u128 {
-            return SmallDuration::MIN;
+
+        impl<const P: u64> Display for $name<P> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let duration = Duration::from(*self);
+                duration.fmt(f)
+            }
         }
-        (nanos / P as u128)
-            .try_into()
-            .map_or(SmallDuration::MAX, SmallDuration)
-    }
-}
 
-impl<const P: u64> From<SmallDuration<P>> for Duration {
-    fn from(duration: SmallDuration<P>) -> Self {
-        duration.to_duration()
-    }
-}
+        impl<const P: u64> Debug for $name<P> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                let duration = Duration::from(*self);
+                duration.fmt(f)
+            }
+        }
 
-impl<const P: u64> Display for SmallDuration<P> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let duration = Duration::from(*self);
-        duration.fmt(f)
-    }
+        impl<const P: u64> PartialEq<Duration> for $name<P> {
+            fn eq(&self, other: &Duration) -> bool {
+                self.to_duration() == *other
+            }
+        }
+    };
 }
 
-impl<const P: u64> Debug for SmallDuration<P> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let duration = Duration::from(*self);
-        duration.fmt(f)
-    }
+small_duration! {
+    /// Stores a [`Duration`] in a given precision (in nanoseconds) in 4 bytes.
+    ///
+    /// For instance, for `P = 10_000` (10 microseconds), this allows a for a total
+    /// duration of 11.9 hours. Values smaller than 10 microseconds are stored as 10
+    /// microseconds.
+    ///
+    /// Serializes as the raw value. The precision is part of the type, so it
+    /// must match between the writer and the reader.
+    SmallDuration(u32), "a 32-bit duration"
 }
 
-impl<const P: u64> PartialEq<Duration> for SmallDuration<P> {
-    fn eq(&self, other: &Duration) -> bool {
-        self.to_duration() == *other
-    }
+small_duration! {
+    /// Like [`SmallDuration`], but stored in 8 bytes for when more range is
+    /// needed at the same precision.
+    ///
+    /// For instance, at `P = 10_000` (10 microseconds) [`SmallDuration`] only
+    /// covers 11.9 hours, whereas this covers 5.8 million years.
+    SmallDuration64(u64), "a 64-bit duration"
 }
 
 #[cfg(test)]
 mod tests {
     use std::time::Duration;
 
-    use super::SmallDuration;
+    use serde_test::{assert_de_tokens_error, assert_tokens, Token};
+
+    use super::{Rounding, SmallDuration, SmallDuration64};
 
     #[test]
     fn test_1_nano() {
@@ -191,6 +375,134 @@ mod tests {
         assert_eq!(Sd::from_secs(5_000_000), Sd::MAX);
     }
 
+    #[test]
+    fn test_arithmetic() {
+        type Sd = SmallDuration<1_000_000>;
+
+        assert_eq!(
+            Sd::from_millis(40) + Sd::from_millis(2),
+            Sd::from_millis(42)
+        );
+        assert_eq!(
+            Sd::from_millis(44) - Sd::from_millis(2),
+            Sd::from_millis(42)
+        );
+        assert_eq!(Sd::from_millis(2) - Sd::from_millis(44), Sd::ZERO);
+        assert_eq!(Sd::MAX + Sd::from_millis(1), Sd::MAX);
+
+        assert_eq!(
+            Sd::from_millis(40).checked_add(Sd::from_millis(2)),
+            Some(Sd::from_millis(42))
+        );
+        assert_eq!(Sd::MAX.checked_add(Sd::MIN), None);
+        assert_eq!(Sd::ZERO.checked_sub(Sd::MIN), None);
+
+        let mut total = Sd::ZERO;
+        total += Sd::from_millis(21);
+        total += Sd::from_millis(21);
+        assert_eq!(total, Sd::from_millis(42));
+        total -= Sd::from_millis(2);
+        assert_eq!(total, Sd::from_millis(40));
+
+        let sum: Sd = [1, 2, 3].into_iter().map(Sd::from_millis).sum();
+        assert_eq!(sum, Sd::from_millis(6));
+    }
+
+    #[test]
+    fn test_mul_f64() {
+        type Sd = SmallDuration<1_000_000>;
+
+        assert_eq!(Sd::from_millis(21).mul_f64(2.0), Sd::from_millis(42));
+        assert_eq!(Sd::from_millis(42).mul_f64(0.5), Sd::from_millis(21));
+        assert_eq!(Sd::from_millis(42).mul_f64(-1.0), Sd::ZERO);
+        assert_eq!(Sd::from_millis(42).mul_f64(f64::NAN), Sd::ZERO);
+        assert_eq!(Sd::from_secs(1).mul_f64(1e12), Sd::MAX);
+    }
+
+    #[test]
+    fn test_serde() {
+        type Sd = SmallDuration<10_000>;
+
+        assert_tokens(&Sd::from_micros(420), &[Token::U32(42)]);
+        assert_tokens(&Sd::MAX, &[Token::U32(u32::MAX)]);
+        assert_eq!(
+            Sd::from_raw(Sd::from_micros(420).to_raw()),
+            Sd::from_micros(420)
+        );
+
+        assert_de_tokens_error::<Sd>(
+            &[Token::U64(u32::MAX as u64 + 1)],
+            "invalid value: integer `4294967296`, expected a 32-bit duration",
+        );
+    }
+
+    #[test]
+    fn test_rounding() {
+        type Sd = SmallDuration<1_000>;
+
+        assert_eq!(
+            Sd::from_nanos_rounded(1_499, Rounding::Down),
+            Sd::from_micros(1)
+        );
+        assert_eq!(
+            Sd::from_nanos_rounded(1_499, Rounding::Nearest),
+            Sd::from_micros(1)
+        );
+        assert_eq!(
+            Sd::from_nanos_rounded(1_500, Rounding::Nearest),
+            Sd::from_micros(2)
+        );
+        assert_eq!(
+            Sd::from_nanos_rounded(1_001, Rounding::Up),
+            Sd::from_micros(2)
+        );
+        assert_eq!(
+            Sd::from_nanos_rounded(1_000, Rounding::Up),
+            Sd::from_micros(1)
+        );
+
+        // Non-zero durations stay non-zero, and zero stays zero.
+        assert_eq!(Sd::from_nanos_rounded(1, Rounding::Nearest), Sd::MIN);
+        assert_eq!(Sd::from_nanos_rounded(0, Rounding::Up), Sd::ZERO);
+
+        assert_eq!(
+            Sd::from_duration_rounded(Duration::from_nanos(41_600), Rounding::Nearest),
+            Sd::from_micros(42)
+        );
+        assert_eq!(
+            Sd::from_duration_rounded(Duration::from_secs(5_000), Rounding::Up),
+            Sd::MAX
+        );
+
+        // `Down` matches the `From` conversion.
+        assert_eq!(
+            Sd::from_duration_rounded(Duration::from_nanos(41_600), Rounding::Down),
+            Sd::from(Duration::from_nanos(41_600))
+        );
+    }
+
+    #[test]
+    fn test_64_bit() {
+        type Sd = SmallDuration<10_000>;
+        type Sd64 = SmallDuration64<10_000>;
+
+        // 10µs precision can only store up to ~11.9h in 4 bytes, but 8 bytes
+        // cover multi-day sessions.
+        let three_days = Duration::from_secs(3 * 24 * 60 * 60);
+        assert_eq!(Sd::from(three_days), Sd::MAX);
+        assert_eq!(Sd64::from(three_days), three_days);
+        assert_eq!(Sd64::from_secs(3 * 24 * 60 * 60), three_days);
+
+        assert_eq!(Sd64::from_nanos(1), Sd64::MIN);
+        assert_eq!(Sd64::MAX + Sd64::MIN, Sd64::MAX);
+        assert_eq!(
+            Duration::from(Sd64::MAX),
+            Duration::from_nanos(u64::MAX) * 10_000
+        );
+
+        assert_tokens(&Sd64::from_micros(420), &[Token::U64(42)]);
+    }
+
     #[test]
     fn test_1_sec() {
         type Sd = SmallDuration<1_000_000_000>;
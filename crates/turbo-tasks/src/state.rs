@@ -2,6 +2,7 @@ use std::{
     fmt::Debug,
     mem::take,
     ops::{Deref, DerefMut},
+    sync::Arc,
 };
 
 use auto_hash_map::AutoSet;
@@ -71,9 +72,7 @@ impl<'de, T> Deserialize<'de> for State<T> {
 impl<T> Drop for State<T> {
     fn drop(&mut self) {
         let mut inner = self.inner.lock();
-        for invalidator in take(&mut inner.invalidators) {
-            invalidator.invalidate();
-        }
+        inner.invalidate();
     }
 }
 
@@ -115,9 +114,7 @@ impl<T> State<T> {
     pub fn set_unconditionally(&self, value: T) {
         let mut inner = self.inner.lock();
         inner.value = value;
-        for invalidator in take(&mut inner.invalidators) {
-            invalidator.invalidate();
-        }
+        inner.invalidate();
     }
 
     /// Updates the current state with the `update` function. The `update`
@@ -129,9 +126,7 @@ impl<T> State<T> {
         if !update(&mut inner.value) {
             return;
         }
-        for invalidator in take(&mut inner.invalidators) {
-            invalidator.invalidate();
-        }
+        inner.invalidate();
     }
 }
 
@@ -144,12 +139,131 @@ impl<T: PartialEq> State<T> {
             return;
         }
         inner.value = value;
-        for invalidator in take(&mut inner.invalidators) {
+        inner.invalidate();
+    }
+}
+
+impl<T> StateInner<T> {
+    fn invalidate(&mut self) {
+        for invalidator in take(&mut self.invalidators) {
             invalidator.invalidate();
         }
     }
 }
 
+/// State that is owned by the embedder instead of a task, e.g. the current
+/// time, a random seed or the response of a network request. Tasks read it
+/// with [TransientState::get] and are invalidated when the embedder changes
+/// it or calls [TransientState::invalidate].
+///
+/// Cloning creates another handle to the same state. Pass it to a task with a
+/// [crate::TransientInstance].
+///
+/// Readers are only remembered until the next invalidation, so a state
+/// doesn't keep tasks alive that no longer read it.
+pub struct TransientState<T> {
+    inner: Arc<Mutex<StateInner<T>>>,
+}
+
+impl<T> Clone for TransientState<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T: Debug> Debug for TransientState<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TransientState")
+            .field("value", &self.inner.lock().value)
+            .finish()
+    }
+}
+
+impl<T> PartialEq for TransientState<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+impl<T> Eq for TransientState<T> {}
+
+impl<T: Default> Default for TransientState<T> {
+    fn default() -> Self {
+        Self::new(Default::default())
+    }
+}
+
+impl<T> TransientState<T> {
+    /// Creates a new state. Unlike [State::new] this can be called outside of
+    /// a task.
+    pub fn new(value: T) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(StateInner {
+                value,
+                invalidators: AutoSet::new(),
+            })),
+        }
+    }
+
+    /// Gets the current value of the state. The current task will be registered
+    /// as dependency of the state and will be invalidated when the state
+    /// changes or is invalidated.
+    pub fn get(&self) -> StateRef<'_, T> {
+        let invalidator = get_invalidator();
+        let mut inner = self.inner.lock();
+        inner.invalidators.insert(invalidator);
+        StateRef {
+            inner,
+            mutated: false,
+        }
+    }
+
+    /// Gets the current value of the state. Untracked.
+    pub fn get_untracked(&self) -> StateRef<'_, T> {
+        StateRef {
+            inner: self.inner.lock(),
+            mutated: false,
+        }
+    }
+
+    /// Invalidates all tasks that read the state, without changing it. Use
+    /// this for values that are computed on read, e.g. the current time.
+    pub fn invalidate(&self) {
+        self.inner.lock().invalidate();
+    }
+
+    /// Sets the current state without comparing it with the old value. This
+    /// should only be used if one is sure that the value has changed.
+    pub fn set_unconditionally(&self, value: T) {
+        let mut inner = self.inner.lock();
+        inner.value = value;
+        inner.invalidate();
+    }
+
+    /// Updates the current state with the `update` function. The `update`
+    /// function need to return `true` when the value was modified.
+    pub fn update_conditionally(&self, update: impl FnOnce(&mut T) -> bool) {
+        let mut inner = self.inner.lock();
+        if update(&mut inner.value) {
+            inner.invalidate();
+        }
+    }
+}
+
+impl<T: PartialEq> TransientState<T> {
+    /// Update the current state when the `value` is different from the current
+    /// value. Tasks that read the state are not invalidated otherwise.
+    pub fn set(&self, value: T) {
+        let mut inner = self.inner.lock();
+        if inner.value == value {
+            return;
+        }
+        inner.value = value;
+        inner.invalidate();
+    }
+}
+
 impl<'a, T> Deref for StateRef<'a, T> {
     type Target = T;
 
@@ -168,9 +282,7 @@ impl<'a, T> DerefMut for StateRef<'a, T> {
 impl<'a, T> Drop for StateRef<'a, T> {
     fn drop(&mut self) {
         if self.mutated {
-            for invalidator in take(&mut self.inner.invalidators) {
-                invalidator.invalidate();
-            }
+            self.inner.invalidate();
         }
     }
 }
@@ -0,0 +1,168 @@
+use std::{
+    error::Error as StdError,
+    fmt::{Debug, Display},
+    hash::Hash,
+    sync::Arc,
+};
+
+use crate::{invalidation::DynamicEqHash, util::SharedError};
+
+/// An error value that can be compared. Implemented for all errors that
+/// implement [Eq] and [Hash].
+pub trait TypedError: StdError + DynamicEqHash + Send + Sync + 'static {}
+
+impl<T: StdError + PartialEq + Eq + Hash + Send + Sync + 'static> TypedError for T {}
+
+/// Carries a [TypedError] through an [anyhow::Error]. Create one with
+/// [structured_error] and find it again with [find_error].
+///
+/// Unlike other errors, structured errors have an identity: when a task fails
+/// again with an equal structured error and the same context, the tasks that
+/// read it are not invalidated.
+#[derive(Clone)]
+pub struct StructuredError {
+    inner: Arc<dyn TypedError>,
+}
+
+impl StructuredError {
+    pub fn new<T: TypedError>(error: T) -> Self {
+        Self {
+            inner: Arc::new(error),
+        }
+    }
+
+    pub fn downcast_ref<T: TypedError>(&self) -> Option<&T> {
+        self.inner.as_any().downcast_ref()
+    }
+}
+
+impl StdError for StructuredError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.inner.source()
+    }
+}
+
+impl Display for StructuredError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&*self.inner, f)
+    }
+}
+
+impl Debug for StructuredError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Debug::fmt(&*self.inner, f)
+    }
+}
+
+impl PartialEq for StructuredError {
+    fn eq(&self, other: &Self) -> bool {
+        self.inner.dyn_eq(other.inner.as_any())
+    }
+}
+
+impl Eq for StructuredError {}
+
+/// Wraps `error` so it can be recovered with [find_error], even after it was
+/// returned from a task and read by another task.
+pub fn structured_error<T: TypedError>(error: T) -> anyhow::Error {
+    anyhow::Error::new(StructuredError::new(error))
+}
+
+/// Finds an error of type `T` in the chain of `error`. This looks through
+/// context that was added and through the errors of tasks that were read.
+pub fn find_error<T: StdError + Send + Sync + 'static>(error: &anyhow::Error) -> Option<&T> {
+    if let Some(found) = error.downcast_ref::<T>() {
+        return Some(found);
+    }
+    for cause in error.chain() {
+        if let Some(found) = cause.downcast_ref::<T>() {
+            return Some(found);
+        }
+        if let Some(structured) = cause.downcast_ref::<StructuredError>() {
+            if let Some(found) = structured.inner.as_any().downcast_ref::<T>() {
+                return Some(found);
+            }
+        }
+        if let Some(shared) = cause.downcast_ref::<SharedError>() {
+            if let Some(found) = find_error(shared) {
+                return Some(found);
+            }
+        }
+    }
+    None
+}
+
+impl SharedError {
+    /// The first [StructuredError] in the chain of this error.
+    pub fn structured(&self) -> Option<&StructuredError> {
+        find_error(self)
+    }
+
+    /// Whether both errors carry equal [StructuredError]s and render the same,
+    /// including the context around them. Failing again with the same error
+    /// doesn't need to invalidate dependent tasks.
+    pub fn is_same_structured_error(&self, other: &SharedError) -> bool {
+        match (self.structured(), other.structured()) {
+            (Some(a), Some(b)) => a == b && format!("{self:#}") == format!("{other:#}"),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Context;
+    use thiserror::Error;
+
+    use super::{find_error, structured_error};
+    use crate::util::SharedError;
+
+    #[derive(Debug, Error, PartialEq, Eq, Hash)]
+    #[error("module {0} not found")]
+    struct NotFound(String);
+
+    #[test]
+    fn test_find_error() {
+        let error = Err::<(), _>(structured_error(NotFound("a".to_string())))
+            .context("resolving")
+            .unwrap_err();
+        assert_eq!(
+            find_error::<NotFound>(&error),
+            Some(&NotFound("a".to_string()))
+        );
+
+        // Errors are shared when read from another task
+        let shared = SharedError::new(error);
+        let read = anyhow::Error::new(shared.clone()).context("reading");
+        assert_eq!(
+            find_error::<NotFound>(&read),
+            Some(&NotFound("a".to_string()))
+        );
+        assert_eq!(
+            read.chain().last().unwrap().to_string(),
+            "module a not found"
+        );
+    }
+
+    #[test]
+    fn test_identity() {
+        let a = SharedError::new(structured_error(NotFound("a".to_string())));
+        let also_a = SharedError::new(structured_error(NotFound("a".to_string())));
+        let b = SharedError::new(structured_error(NotFound("b".to_string())));
+        let untyped = SharedError::new(anyhow::anyhow!("module a not found"));
+        assert!(a.is_same_structured_error(&also_a));
+        assert!(!a.is_same_structured_error(&b));
+
+        // Dependent tasks would render the other context
+        let context = |context: &'static str| {
+            SharedError::new(
+                Err::<(), _>(structured_error(NotFound("a".to_string())))
+                    .context(context)
+                    .unwrap_err(),
+            )
+        };
+        assert!(context("resolving").is_same_structured_error(&context("resolving")));
+        assert!(!context("resolving").is_same_structured_error(&context("parsing")));
+        assert!(!untyped.is_same_structured_error(&untyped.clone()));
+    }
+}
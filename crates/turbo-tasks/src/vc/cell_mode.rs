@@ -1,4 +1,4 @@
-use std::marker::PhantomData;
+use std::{hash::Hash, marker::PhantomData};
 
 use super::{read::VcRead, traits::VcValueType};
 use crate::{manager::find_cell_by_type, Vc};
@@ -52,3 +52,25 @@ where
         }
     }
 }
+
+/// Mode that interns the value, so structurally equal values are stored only
+/// once, even when they are celled by different tasks. Like
+/// [VcCellSharedMode], the cell is only updated when the value changed.
+pub struct VcCellInternedMode<T> {
+    _phantom: PhantomData<T>,
+}
+
+impl<T> VcCellMode<T> for VcCellInternedMode<T>
+where
+    T: VcValueType,
+    <<T as VcValueType>::Read as VcRead<T>>::Repr: Hash + Eq,
+{
+    fn cell(inner: <T::Read as VcRead<T>>::Target) -> Vc<T> {
+        let cell = find_cell_by_type(T::get_value_type_id());
+        cell.update_interned(<T::Read as VcRead<T>>::target_to_repr(inner));
+        Vc {
+            node: cell.into(),
+            _t: PhantomData,
+        }
+    }
+}
@@ -13,7 +13,7 @@ use serde::{Deserialize, Serialize};
 use self::cell_mode::VcCellMode;
 pub use self::{
     cast::{VcValueTraitCast, VcValueTypeCast},
-    cell_mode::{VcCellNewMode, VcCellSharedMode},
+    cell_mode::{VcCellInternedMode, VcCellNewMode, VcCellSharedMode},
     default::ValueDefault,
     read::{VcDefaultRead, VcRead, VcTransparentRead},
     traits::{Dynamic, TypedForInput, Upcast, VcValueTrait, VcValueType},
@@ -466,6 +466,20 @@ where
     fn peek_collectibles<Vt: VcValueTrait + Send>(self) -> AutoSet<Vc<Vt>> {
         self.node.peek_collectibles()
     }
+
+    fn take_collectibles_filtered<Vt: VcValueTrait + Send>(
+        self,
+        predicate: impl FnMut(Vc<Vt>) -> bool,
+    ) -> AutoSet<Vc<Vt>> {
+        self.node.take_collectibles_filtered(predicate)
+    }
+
+    fn peek_collectibles_filtered<Vt: VcValueTrait + Send>(
+        self,
+        predicate: impl FnMut(Vc<Vt>) -> bool,
+    ) -> AutoSet<Vc<Vt>> {
+        self.node.peek_collectibles_filtered(predicate)
+    }
 }
 
 impl<T> From<RawVc> for Vc<T>
@@ -89,6 +89,11 @@ pub struct DevArguments {
     #[clap(long)]
     pub no_open: bool,
 
+    /// Record why tasks re-execute and write the invalidation chains as JSON
+    /// to the given file after every update.
+    #[clap(long, value_parser)]
+    pub trace_invalidations: Option<PathBuf>,
+
     // ==
     // = Inherited options from next-dev, need revisit later.
     // ==
@@ -340,6 +340,10 @@ pub async fn start_server(args: &DevArguments) -> Result<()> {
     };
     tt.set_stats_type(stats_type);
 
+    if args.trace_invalidations.is_some() {
+        tt.backend().invalidation_tracer().enable();
+    }
+
     let tt_clone = tt.clone();
 
     let mut server = TurbopackDevServerBuilder::new(tt, project_dir, root_dir)
@@ -417,6 +421,17 @@ pub async fn start_server(args: &DevArguments) -> Result<()> {
             }) = update_future.await
             {
                 progress_counter = 0;
+                if let Some(path) = &args.trace_invalidations {
+                    let backend = tt_clone.backend();
+                    if let Err(err) = backend
+                        .invalidation_tracer()
+                        .to_json(backend)
+                        .map_err(anyhow::Error::from)
+                        .and_then(|json| Ok(std::fs::write(path, json)?))
+                    {
+                        eprintln!("failed to write invalidation trace: {err}");
+                    }
+                }
                 match (args.common.log_detail, !reasons.is_empty()) {
                     (true, true) => {
                         println!(
@@ -1,10 +1,11 @@
 use std::{sync::Arc, time::Duration};
 
 use anyhow::{bail, Result};
-use mime::TEXT_HTML_UTF_8;
+use mime::{APPLICATION_JSON, TEXT_HTML_UTF_8};
 use turbo_tasks::{get_invalidator, TurboTasks, TurboTasksBackendApi, Value, Vc};
 use turbo_tasks_fs::File;
 use turbo_tasks_memory::{
+    memory_stats::MemoryStats,
     stats::{ReferenceType, Stats},
     viz, MemoryBackend,
 };
@@ -49,6 +50,11 @@ impl ContentSource for TurboTasksSource {
                 RouteType::Exact,
                 Vc::upcast(self),
             ),
+            RouteTree::new_route(
+                vec![BaseSegment::Static("memory".to_string())],
+                RouteType::Exact,
+                Vc::upcast(self),
+            ),
             RouteTree::new_route(
                 vec![BaseSegment::Static("reset".to_string())],
                 RouteType::Exact,
@@ -125,6 +131,14 @@ impl GetContentSourceContent for TurboTasksSource {
                 let table = viz::table::create_table(tree, tt.stats_type());
                 viz::table::wrap_html(&table)
             }
+            "memory" => {
+                let b = tt.backend();
+                let json = MemoryStats::collect(b).to_json(b)?;
+                return Ok(ContentSourceContent::static_content(
+                    AssetContent::file(File::from(json).with_content_type(APPLICATION_JSON).into())
+                        .versioned(),
+                ));
+            }
             "reset" => {
                 let b = tt.backend();
                 b.with_all_cached_tasks(|task| {
@@ -789,6 +789,23 @@ where
     ///
     /// This unemits the issues. They will not propagate up.
     async fn take_issues_with_path(self) -> Result<CapturedIssues>;
+
+    /// Returns the issues from `source` with a severity of at least
+    /// `min_severity`. When `path` is given, only issues in files inside or
+    /// equal to `path` are returned.
+    async fn peek_issues_filtered(
+        self,
+        min_severity: IssueSeverity,
+        path: Option<Vc<FileSystemPath>>,
+    ) -> Result<AutoSet<Vc<Box<dyn Issue>>>>;
+
+    /// Like [IssueDescriptionExt::peek_issues_filtered], but unemits the
+    /// returned issues. Issues that don't match continue to propagate up.
+    async fn take_issues_filtered(
+        self,
+        min_severity: IssueSeverity,
+        path: Option<Vc<FileSystemPath>>,
+    ) -> Result<AutoSet<Vc<Box<dyn Issue>>>>;
 }
 
 #[async_trait]
@@ -877,6 +894,57 @@ where
             )),
         })
     }
+
+    async fn peek_issues_filtered(
+        self,
+        min_severity: IssueSeverity,
+        path: Option<Vc<FileSystemPath>>,
+    ) -> Result<AutoSet<Vc<Box<dyn Issue>>>> {
+        let issues = self.peek_collectibles::<Box<dyn Issue>>();
+        let path = match path {
+            Some(path) => Some(path.await?),
+            None => None,
+        };
+        let matches = issues
+            .iter()
+            .map(|&issue| issue_matches(issue, min_severity, path.as_deref()))
+            .try_join()
+            .await?;
+        Ok(issues
+            .into_iter()
+            .zip(matches)
+            .filter_map(|(issue, matches)| matches.then_some(issue))
+            .collect())
+    }
+
+    async fn take_issues_filtered(
+        self,
+        min_severity: IssueSeverity,
+        path: Option<Vc<FileSystemPath>>,
+    ) -> Result<AutoSet<Vc<Box<dyn Issue>>>> {
+        let matching = self.peek_issues_filtered(min_severity, path).await?;
+        if matching.is_empty() {
+            return Ok(matching);
+        }
+        Ok(self.take_collectibles_filtered(|issue| matching.contains(&issue)))
+    }
+}
+
+/// Whether `issue` has a severity of at least `min_severity` and, when `path`
+/// is given, is located in a file inside or equal to `path`.
+async fn issue_matches(
+    issue: Vc<Box<dyn Issue>>,
+    min_severity: IssueSeverity,
+    path: Option<&FileSystemPath>,
+) -> Result<bool> {
+    // More severe issues come first
+    if *issue.severity().await? > min_severity {
+        return Ok(false);
+    }
+    if let Some(path) = path {
+        return Ok(issue.file_path().await?.is_inside_or_equal_ref(path));
+    }
+    Ok(true)
 }
 
 pub async fn handle_issues<T: Send>(
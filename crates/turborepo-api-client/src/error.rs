@@ -1,4 +1,4 @@
-use std::backtrace::Backtrace;
+use std::{backtrace::Backtrace, time::Duration};
 
 use reqwest::header::ToStrError;
 use thiserror::Error;
@@ -13,6 +13,14 @@ pub enum Error {
     TooManyFailures(#[from] Box<reqwest::Error>),
     #[error("Unable to set up TLS.")]
     TlsError(#[source] reqwest::Error),
+    #[error("Unable to read CA certificates from {path}: {err}")]
+    ReadCaCertificate { path: String, err: std::io::Error },
+    #[error("Invalid CA certificate in {path}: {err}")]
+    InvalidCaCertificate { path: String, err: reqwest::Error },
+    #[error("Unable to read client certificate from {path}: {err}")]
+    ReadClientCertificate { path: String, err: std::io::Error },
+    #[error("Invalid client certificate: {0}")]
+    InvalidClientCertificate(#[source] reqwest::Error),
     #[error("Error parsing header: {0}")]
     InvalidHeader(#[from] ToStrError),
     #[error("Error parsing '{url}' as URL: {err}")]
@@ -47,6 +55,13 @@ pub enum Error {
     },
     #[error("[HTTP 403] token is forbidden from accessing {url}")]
     ForbiddenToken { url: String },
+    #[error("Unknown preflight request type '{0}'. Expected one of 'reads', 'uploads' or 'api'.")]
+    InvalidPreflightRequest(String),
+    #[error("rate limited by {url}")]
+    RateLimited {
+        url: String,
+        retry_after: Option<Duration>,
+    },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
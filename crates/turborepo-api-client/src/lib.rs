@@ -2,9 +2,10 @@
 #![feature(error_generic_member_access)]
 #![deny(clippy::all)]
 
-use std::{backtrace::Backtrace, env};
+use std::{backtrace::Backtrace, env, time::Duration};
 
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use lazy_static::lazy_static;
 use regex::Regex;
 pub use reqwest::Response;
@@ -12,19 +13,30 @@ use reqwest::{Method, RequestBuilder, StatusCode};
 use serde::Deserialize;
 use turborepo_ci::{is_ci, Vendor};
 use turborepo_vercel_api::{
-    token::ResponseTokenMetadata, APIError, CachingStatus, CachingStatusResponse,
+    token::{OidcTokenResponse, ResponseTokenMetadata},
+    APIError, ArtifactsQueryRequest, ArtifactsQueryResponse, CachingStatus, CachingStatusResponse,
     PreflightResponse, SpacesResponse, Team, TeamsResponse, UserResponse, VerificationResponse,
     VerifiedSsoUser,
 };
 use url::Url;
 
-pub use crate::error::{Error, Result};
+pub use crate::{
+    error::{Error, Result},
+    retry::RetryConfig,
+    tls::TlsConfig,
+};
 
 pub mod analytics;
 mod error;
 mod retry;
 pub mod spaces;
 pub mod telemetry;
+mod tls;
+
+// How long an unused connection is kept around for reuse
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+// Keeps idle connections from being dropped by proxies and NATs mid-run
+const TCP_KEEPALIVE: Duration = Duration::from_secs(60);
 
 lazy_static! {
     static ref AUTHORIZATION_REGEX: Regex =
@@ -78,6 +90,15 @@ pub trait CacheClient {
         team_id: Option<&str>,
         team_slug: Option<&str>,
     ) -> Result<Option<Response>>;
+    /// Checks which of `hashes` exist in a single request. Returns `None` if
+    /// the server doesn't support querying artifacts in bulk.
+    async fn artifacts_exist(
+        &self,
+        hashes: &[String],
+        token: &str,
+        team_id: Option<&str>,
+        team_slug: Option<&str>,
+    ) -> Result<Option<ArtifactsQueryResponse>>;
     async fn get_caching_status(
         &self,
         token: &str,
@@ -90,6 +111,12 @@ pub trait CacheClient {
 pub trait TokenClient {
     async fn get_metadata(&self, token: &str) -> Result<ResponseTokenMetadata>;
     async fn delete_token(&self, token: &str) -> Result<()>;
+    async fn exchange_oidc_token(
+        &self,
+        oidc_token: &str,
+        team_id: Option<&str>,
+        team_slug: Option<&str>,
+    ) -> Result<OidcTokenResponse>;
 }
 
 #[derive(Clone)]
@@ -97,7 +124,56 @@ pub struct APIClient {
     client: reqwest::Client,
     base_url: String,
     user_agent: String,
-    use_preflight: bool,
+    preflight: PreflightConfig,
+    upload_timeout: Option<Duration>,
+    retry_config: RetryConfig,
+}
+
+/// Timeouts, in seconds, for the different kinds of requests made by the
+/// client. A value of 0 disables the timeout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timeouts {
+    /// Time allowed to establish a connection
+    pub connect: u64,
+    /// Time allowed for any request that isn't an artifact upload
+    pub read: u64,
+    /// Time allowed for uploading an artifact
+    pub upload: u64,
+}
+
+/// The kinds of requests that are preceded by a preflight `OPTIONS` request
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PreflightConfig {
+    /// Artifact downloads and existence checks
+    pub reads: bool,
+    /// Artifact uploads
+    pub uploads: bool,
+    /// Every other API request, e.g. analytics and run summaries
+    pub api: bool,
+}
+
+impl PreflightConfig {
+    pub fn all() -> Self {
+        Self {
+            reads: true,
+            uploads: true,
+            api: true,
+        }
+    }
+
+    /// Enables preflight requests for each of the named kinds of requests.
+    pub fn from_requests<'a>(requests: impl IntoIterator<Item = &'a str>) -> Result<Self> {
+        let mut config = Self::default();
+        for request in requests {
+            match request.trim() {
+                "reads" => config.reads = true,
+                "uploads" => config.uploads = true,
+                "api" => config.api = true,
+                other => return Err(Error::InvalidPreflightRequest(other.to_string())),
+            }
+        }
+        Ok(config)
+    }
 }
 
 #[derive(Clone)]
@@ -123,7 +199,7 @@ impl Client for APIClient {
             .header("User-Agent", self.user_agent.clone())
             .header("Authorization", format!("Bearer {}", token))
             .header("Content-Type", "application/json");
-        let response = retry::make_retryable_request(request_builder)
+        let response = retry::make_retryable_request(request_builder, &self.retry_config)
             .await?
             .error_for_status()?;
 
@@ -138,7 +214,7 @@ impl Client for APIClient {
             .header("Content-Type", "application/json")
             .header("Authorization", format!("Bearer {}", token));
 
-        let response = retry::make_retryable_request(request_builder)
+        let response = retry::make_retryable_request(request_builder, &self.retry_config)
             .await?
             .error_for_status()?;
 
@@ -183,7 +259,7 @@ impl Client for APIClient {
             .header("Content-Type", "application/json")
             .header("Authorization", format!("Bearer {}", token));
 
-        let response = retry::make_retryable_request(request_builder)
+        let response = retry::make_retryable_request(request_builder, &self.retry_config)
             .await?
             .error_for_status()?;
 
@@ -197,7 +273,7 @@ impl Client for APIClient {
             .query(&[("token", token), ("tokenName", token_name)])
             .header("User-Agent", self.user_agent.clone());
 
-        let response = retry::make_retryable_request(request_builder)
+        let response = retry::make_retryable_request(request_builder, &self.retry_config)
             .await?
             .error_for_status()?;
 
@@ -275,7 +351,7 @@ impl CacheClient for APIClient {
         let mut request_url = self.make_url(&format!("/v8/artifacts/{}", hash))?;
         let mut allow_auth = true;
 
-        if self.use_preflight {
+        if self.preflight.reads {
             let preflight_response = self
                 .do_preflight(
                     token,
@@ -300,7 +376,8 @@ impl CacheClient for APIClient {
 
         request_builder = Self::add_team_params(request_builder, team_id, team_slug);
 
-        let response = retry::make_retryable_request(request_builder).await?;
+        let response = retry::make_retryable_request(request_builder, &self.retry_config).await?;
+        let response = retry::check_rate_limit(response)?;
 
         match response.status() {
             StatusCode::FORBIDDEN => Err(Self::handle_403(response).await),
@@ -321,6 +398,58 @@ impl CacheClient for APIClient {
             .await
     }
 
+    #[tracing::instrument(skip_all)]
+    async fn artifacts_exist(
+        &self,
+        hashes: &[String],
+        token: &str,
+        team_id: Option<&str>,
+        team_slug: Option<&str>,
+    ) -> Result<Option<ArtifactsQueryResponse>> {
+        let mut request_url = self.make_url("/v8/artifacts")?;
+        let mut allow_auth = true;
+
+        if self.preflight.reads {
+            let preflight_response = self
+                .do_preflight(
+                    token,
+                    request_url.clone(),
+                    "POST",
+                    "Authorization, Content-Type, User-Agent",
+                )
+                .await?;
+
+            allow_auth = preflight_response.allow_authorization_header;
+            request_url = preflight_response.location;
+        }
+
+        let mut request_builder = self
+            .client
+            .post(request_url)
+            .header("User-Agent", self.user_agent.clone())
+            .json(&ArtifactsQueryRequest {
+                hashes: hashes.to_vec(),
+            });
+
+        if allow_auth {
+            request_builder = request_builder.header("Authorization", format!("Bearer {}", token));
+        }
+
+        request_builder = Self::add_team_params(request_builder, team_id, team_slug);
+
+        let response = retry::make_retryable_request(request_builder, &self.retry_config).await?;
+        let response = retry::check_rate_limit(response)?;
+
+        match response.status() {
+            // Self-hosted caches may only implement the per artifact endpoints
+            StatusCode::NOT_FOUND
+            | StatusCode::METHOD_NOT_ALLOWED
+            | StatusCode::NOT_IMPLEMENTED => Ok(None),
+            StatusCode::FORBIDDEN => Err(Self::handle_403(response).await),
+            _ => Ok(Some(response.error_for_status()?.json().await?)),
+        }
+    }
+
     #[tracing::instrument(skip_all)]
     async fn fetch_artifact(
         &self,
@@ -347,7 +476,7 @@ impl CacheClient for APIClient {
         let mut request_url = self.make_url(&format!("/v8/artifacts/{}", hash))?;
         let mut allow_auth = true;
 
-        if self.use_preflight {
+        if self.preflight.uploads {
             let preflight_response = self
                 .do_preflight(
                     token,
@@ -377,11 +506,16 @@ impl CacheClient for APIClient {
 
         request_builder = Self::add_ci_header(request_builder);
 
+        // Uploads can take far longer than other requests so they're limited
+        // separately from the client-wide timeout.
+        request_builder = request_builder.timeout(self.upload_timeout.unwrap_or(Duration::MAX));
+
         if let Some(tag) = tag {
             request_builder = request_builder.header("x-artifact-tag", tag);
         }
 
-        let response = retry::make_retryable_request(request_builder).await?;
+        let response = retry::make_retryable_request(request_builder, &self.retry_config).await?;
+        let response = retry::check_rate_limit(response)?;
 
         if response.status() == StatusCode::FORBIDDEN {
             return Err(Self::handle_403(response).await);
@@ -406,7 +540,7 @@ impl CacheClient for APIClient {
 
         let request_builder = Self::add_team_params(request_builder, team_id, team_slug);
 
-        let response = retry::make_retryable_request(request_builder)
+        let response = retry::make_retryable_request(request_builder, &self.retry_config)
             .await?
             .error_for_status()?;
 
@@ -442,7 +576,7 @@ impl TokenClient for APIClient {
             invalid_token: bool,
         }
 
-        let response = retry::make_retryable_request(request_builder).await?;
+        let response = retry::make_retryable_request(request_builder, &self.retry_config).await?;
         let status = response.status();
         // Give a better error message for invalid tokens. This endpoint returns the
         // following statuses:
@@ -494,7 +628,7 @@ impl TokenClient for APIClient {
             invalid_token: bool,
         }
 
-        let response = retry::make_retryable_request(request_builder).await?;
+        let response = retry::make_retryable_request(request_builder, &self.retry_config).await?;
         let status = response.status();
         // Give a better error message for invalid tokens. This endpoint returns the
         // following statuses:
@@ -523,6 +657,30 @@ impl TokenClient for APIClient {
             _ => Err(response.error_for_status().unwrap_err().into()),
         }
     }
+
+    /// Exchanges an OIDC token issued by a CI provider for a short-lived
+    /// token that can be used to access the remote cache.
+    async fn exchange_oidc_token(
+        &self,
+        oidc_token: &str,
+        team_id: Option<&str>,
+        team_slug: Option<&str>,
+    ) -> Result<OidcTokenResponse> {
+        let url = self.make_url("/v1/oidc/token")?;
+        let mut request_builder = self
+            .client
+            .post(url)
+            .header("User-Agent", self.user_agent.clone())
+            .header("Content-Type", "application/json")
+            .json(&serde_json::json!({ "token": oidc_token }));
+        request_builder = Self::add_team_params(request_builder, team_id, team_slug);
+
+        let response = retry::make_retryable_request(request_builder, &self.retry_config)
+            .await?
+            .error_for_status()?;
+
+        Ok(response.json().await?)
+    }
 }
 
 impl APIClient {
@@ -532,29 +690,88 @@ impl APIClient {
         version: &str,
         use_preflight: bool,
     ) -> Result<Self> {
-        let client_build = if timeout != 0 {
-            reqwest::Client::builder()
-                .timeout(std::time::Duration::from_secs(timeout))
-                .build()
+        let timeouts = Timeouts {
+            connect: 0,
+            read: timeout,
+            upload: timeout,
+        };
+        let preflight = if use_preflight {
+            PreflightConfig::all()
         } else {
-            reqwest::Client::builder().build()
+            PreflightConfig::default()
         };
+        Self::new_with_timeouts(
+            base_url,
+            timeouts,
+            &TlsConfig::from_env(),
+            version,
+            preflight,
+        )
+    }
+
+    pub fn new_with_timeouts(
+        base_url: impl AsRef<str>,
+        timeouts: Timeouts,
+        tls_config: &TlsConfig,
+        version: &str,
+        preflight: PreflightConfig,
+    ) -> Result<Self> {
+        // Runs can make thousands of requests to the same host so we hold on to
+        // connections between them, and use HTTP/2 when the server offers it so
+        // that they're multiplexed over a handful of connections.
+        let mut client_builder = tls_config
+            .client_builder()?
+            .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+            .tcp_keepalive(TCP_KEEPALIVE)
+            .http2_adaptive_window(true);
+        if timeouts.read != 0 {
+            client_builder = client_builder.timeout(Duration::from_secs(timeouts.read));
+        }
+        if timeouts.connect != 0 {
+            client_builder = client_builder.connect_timeout(Duration::from_secs(timeouts.connect));
+        }
 
-        let client = client_build.map_err(Error::TlsError)?;
+        let client = client_builder.build().map_err(Error::TlsError)?;
 
         let user_agent = build_user_agent(version);
         Ok(APIClient {
             client,
             base_url: base_url.as_ref().to_string(),
             user_agent,
-            use_preflight,
+            preflight,
+            upload_timeout: (timeouts.upload != 0).then(|| Duration::from_secs(timeouts.upload)),
+            retry_config: RetryConfig::default(),
         })
     }
 
+    pub fn with_retry_config(mut self, retry_config: RetryConfig) -> Self {
+        self.retry_config = retry_config;
+        self
+    }
+
     pub fn base_url(&self) -> &str {
         self.base_url.as_str()
     }
 
+    /// Returns the time of the server according to the `Date` header of its
+    /// response, if it sent one.
+    pub async fn server_time(&self) -> Result<Option<DateTime<Utc>>> {
+        let response = self
+            .client
+            .head(self.make_url("/v8/artifacts/status")?)
+            .header("User-Agent", self.user_agent.clone())
+            .send()
+            .await?;
+
+        let Some(date) = response.headers().get(reqwest::header::DATE) else {
+            return Ok(None);
+        };
+        // HTTP dates are a subset of RFC 2822 dates
+        Ok(DateTime::parse_from_rfc2822(date.to_str()?)
+            .ok()
+            .map(|date| date.with_timezone(&Utc)))
+    }
+
     async fn do_preflight(
         &self,
         token: &str,
@@ -570,7 +787,7 @@ impl APIClient {
             .header("Access-Control-Request-Headers", request_headers)
             .header("Authorization", format!("Bearer {}", token));
 
-        let response = retry::make_retryable_request(request_builder).await?;
+        let response = retry::make_retryable_request(request_builder, &self.retry_config).await?;
 
         let headers = response.headers();
         let location = if let Some(location) = headers.get("Location") {
@@ -627,7 +844,7 @@ impl APIClient {
             team_slug,
         } = api_auth;
 
-        if self.use_preflight {
+        if self.preflight.api {
             let preflight_response = self
                 .do_preflight(
                     token,
@@ -690,6 +907,7 @@ pub struct AnonAPIClient {
     client: reqwest::Client,
     base_url: String,
     user_agent: String,
+    retry_config: RetryConfig,
 }
 
 impl AnonAPIClient {
@@ -698,21 +916,19 @@ impl AnonAPIClient {
     }
 
     pub fn new(base_url: impl AsRef<str>, timeout: u64, version: &str) -> Result<Self> {
-        let client_build = if timeout != 0 {
-            reqwest::Client::builder()
-                .timeout(std::time::Duration::from_secs(timeout))
-                .build()
-        } else {
-            reqwest::Client::builder().build()
-        };
+        let mut client_builder = TlsConfig::from_env().client_builder()?;
+        if timeout != 0 {
+            client_builder = client_builder.timeout(Duration::from_secs(timeout));
+        }
 
-        let client = client_build.map_err(Error::TlsError)?;
+        let client = client_builder.build().map_err(Error::TlsError)?;
 
         let user_agent = build_user_agent(version);
         Ok(AnonAPIClient {
             client,
             base_url: base_url.as_ref().to_string(),
             user_agent,
+            retry_config: RetryConfig::default(),
         })
     }
 }
@@ -733,7 +949,24 @@ mod test {
     use turborepo_vercel_api_mock::start_test_server;
     use url::Url;
 
-    use crate::{APIClient, Client};
+    use crate::{APIClient, Client, PreflightConfig};
+
+    #[test]
+    fn test_preflight_config_from_requests() {
+        assert_eq!(
+            PreflightConfig::from_requests(["uploads", " api"]).unwrap(),
+            PreflightConfig {
+                reads: false,
+                uploads: true,
+                api: true,
+            }
+        );
+        assert_eq!(
+            PreflightConfig::from_requests([]).unwrap(),
+            PreflightConfig::default()
+        );
+        assert!(PreflightConfig::from_requests(["writes"]).is_err());
+    }
 
     #[tokio::test]
     async fn test_do_preflight() -> Result<()> {
@@ -793,6 +1026,26 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_server_time() -> Result<()> {
+        let port = port_scanner::request_open_port().unwrap();
+        let handle = tokio::spawn(start_test_server(port));
+        let base_url = format!("http://localhost:{}", port);
+
+        let client = APIClient::new(&base_url, 200, "2.0.0", false)?;
+        let server_time = client
+            .server_time()
+            .await?
+            .expect("server should send a date");
+
+        // The mock server runs on the same clock
+        let skew = chrono::Utc::now() - server_time;
+        assert!(skew.num_seconds().abs() < 5);
+
+        handle.abort();
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_handle_403_includes_text_on_invalid_json() {
         let response = reqwest::Response::from(
@@ -1,27 +1,96 @@
-use reqwest::{RequestBuilder, Response, StatusCode};
+use std::{
+    fmt::Display,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use reqwest::{
+    header::{HeaderMap, RETRY_AFTER},
+    Method, RequestBuilder, Response, StatusCode,
+};
 use tokio::time::sleep;
+use tracing::{debug, warn};
 
 use crate::Error;
 
 const MIN_SLEEP_TIME_SECS: u64 = 2;
 const MAX_SLEEP_TIME_SECS: u64 = 10;
 const RETRY_MAX: u32 = 2;
+const MAX_RETRY_AFTER_SECS: u64 = 60;
+
+// A flaky connection makes most requests of a run retry, so we only warn about
+// retries once per run and log the rest at debug level.
+static RETRY_WARNING_PRINTED: AtomicBool = AtomicBool::new(false);
+
+/// Controls how many times, and how far apart, failed requests are retried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryConfig {
+    /// Total number of attempts, including the first request.
+    pub max_attempts: u32,
+    /// Delay before the first retry. Doubled on every following retry.
+    pub min_backoff: Duration,
+    /// Upper bound for the delay between retries.
+    pub max_backoff: Duration,
+    /// Randomize delays so many clients don't retry in lockstep.
+    pub jitter: bool,
+    /// Longest delay requested by a `Retry-After` header that we'll wait for.
+    /// If the server asks for more the response is returned as is.
+    pub max_retry_after: Duration,
+}
 
-/// Retries a request until `RETRY_MAX` is reached, the `should_retry_request`
-/// function returns false, or the future succeeds. Uses an exponential backoff
-/// with a base of 2 to delay between retries.
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: RETRY_MAX,
+            min_backoff: Duration::from_secs(MIN_SLEEP_TIME_SECS),
+            max_backoff: Duration::from_secs(MAX_SLEEP_TIME_SECS),
+            jitter: true,
+            max_retry_after: Duration::from_secs(MAX_RETRY_AFTER_SECS),
+        }
+    }
+}
+
+impl RetryConfig {
+    /// The delay to wait before retrying after `retry_count` failures.
+    /// `jitter` is a value in `[0, 1]`, half of the delay is scaled by it.
+    fn backoff(&self, retry_count: u32, jitter: f64) -> Duration {
+        let delay = self
+            .min_backoff
+            .saturating_mul(2_u32.saturating_pow(retry_count))
+            .clamp(self.min_backoff, self.max_backoff.max(self.min_backoff));
+        if self.jitter {
+            delay / 2 + (delay / 2).mul_f64(jitter)
+        } else {
+            delay
+        }
+    }
+}
+
+/// Retries a request until `max_attempts` is reached, the request fails
+/// with an error that shouldn't be retried, or the request succeeds. Uses an
+/// exponential backoff with a base of 2 to delay between retries.
+///
+/// Only idempotent requests are retried when the server responds with a
+/// retryable status, any request is retried if we failed to connect. If the
+/// server responds with a `Retry-After` header we wait for as long as it asks
+/// instead of using the backoff.
 ///
 /// # Arguments
 ///
 /// * `request_builder`: The request builder with everything, i.e. headers and
 ///   body already set. NOTE: This must be cloneable, so no streams are allowed.
+/// * `config`: How many times and how far apart to retry.
 ///
 /// returns: Result<Response, Error>
 pub(crate) async fn make_retryable_request(
     request_builder: RequestBuilder,
+    config: &RetryConfig,
 ) -> Result<Response, Error> {
     let mut last_error = None;
-    for retry_count in 0..RETRY_MAX {
+    let max_attempts = config.max_attempts.max(1);
+    for retry_count in 0..max_attempts {
         // A request builder can fail to clone for two reasons:
         // - the URL given was given as a string and isn't a valid URL this can be
         //   mitigated by constructing requests with pre-parsed URLs via Url::parse
@@ -30,35 +99,202 @@ pub(crate) async fn make_retryable_request(
         let Some(builder) = request_builder.try_clone() else {
             return Ok(request_builder.send().await?);
         };
-        match builder.send().await {
-            Ok(value) => return Ok(value),
-            Err(err) => {
-                if !should_retry_request(&err) {
-                    return Err(err.into());
+        let (client, request) = builder.build_split();
+        let request = request?;
+        let idempotent = is_idempotent(request.method());
+        let url = request.url().clone();
+        let is_last_attempt = retry_count + 1 == max_attempts;
+        let mut delay = None;
+
+        match client.execute(request).await {
+            Ok(response)
+                if idempotent && !is_last_attempt && should_retry_status(response.status()) =>
+            {
+                match retry_after(response.headers()) {
+                    Some(retry_after) if retry_after > config.max_retry_after => {
+                        debug!("not retrying request to {url}, asked to wait {retry_after:?}");
+                        return Ok(response);
+                    }
+                    retry_after => delay = retry_after,
+                }
+                debug!("retrying request to {url}: {}", response.status());
+            }
+            Ok(response) => {
+                if retry_count > 0 {
+                    warn_once(
+                        &RETRY_WARNING_PRINTED,
+                        format_args!(
+                            "request to {url} needed {retry_count} {} to complete",
+                            if retry_count == 1 { "retry" } else { "retries" }
+                        ),
+                    );
                 }
+                return Ok(response);
+            }
+            Err(err) if should_retry_error(&err, idempotent) => {
+                debug!("retrying request to {url}: {err}");
                 last_error = Some(err);
             }
+            Err(err) => return Err(err.into()),
         }
 
-        let sleep_period = (2_u64)
-            .pow(retry_count)
-            .clamp(MIN_SLEEP_TIME_SECS, MAX_SLEEP_TIME_SECS);
-        sleep(std::time::Duration::from_secs(sleep_period)).await;
+        if !is_last_attempt {
+            let delay = delay.unwrap_or_else(|| {
+                let jitter = rand::thread_rng().gen_range(0.0..=1.0);
+                config.backoff(retry_count, jitter)
+            });
+            sleep(delay).await;
+        }
     }
 
-    Err(Error::TooManyFailures(Box::new(last_error.unwrap())))
+    let last_error = last_error.expect("all attempts failed with an error");
+    warn_once(
+        &RETRY_WARNING_PRINTED,
+        format_args!("giving up after {max_attempts} attempts: {last_error}"),
+    );
+    Err(Error::TooManyFailures(Box::new(last_error)))
 }
 
-fn should_retry_request(error: &reqwest::Error) -> bool {
-    if let Some(status) = error.status() {
-        if status == StatusCode::TOO_MANY_REQUESTS {
-            return true;
-        }
+/// Prints `message` as a warning the first time it's called for `printed`,
+/// later messages are only logged at debug level. Returns whether the message
+/// was printed as a warning.
+fn warn_once(printed: &AtomicBool, message: impl Display) -> bool {
+    let first = !printed.swap(true, Ordering::Relaxed);
+    if first {
+        warn!("{message}; further retries will not be reported");
+    } else {
+        debug!("{message}");
+    }
+    first
+}
 
-        if status.as_u16() >= 500 && status.as_u16() != 501 {
-            return true;
-        }
+/// Returns an error if the server is asking us to slow down.
+pub(crate) fn check_rate_limit(response: Response) -> Result<Response, Error> {
+    let status = response.status();
+    let headers = response.headers();
+    if status == StatusCode::TOO_MANY_REQUESTS
+        || (status == StatusCode::SERVICE_UNAVAILABLE && headers.contains_key(RETRY_AFTER))
+    {
+        return Err(Error::RateLimited {
+            url: response.url().to_string(),
+            retry_after: retry_after(headers),
+        });
+    }
+
+    Ok(response)
+}
+
+/// Parses a `Retry-After` header, which is either a number of seconds or an
+/// HTTP date.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
     }
 
-    false
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    // A date in the past means we can retry right away
+    Some(
+        (date.with_timezone(&Utc) - Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
+fn is_idempotent(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS
+    )
+}
+
+fn should_retry_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS
+        || (status.is_server_error() && status != StatusCode::NOT_IMPLEMENTED)
+}
+
+fn should_retry_error(error: &reqwest::Error, idempotent: bool) -> bool {
+    // If we never connected then the server never saw the request so it's always
+    // safe to send it again.
+    error.is_connect() || (idempotent && error.is_timeout())
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case(StatusCode::OK, false)]
+    #[test_case(StatusCode::NOT_FOUND, false)]
+    #[test_case(StatusCode::TOO_MANY_REQUESTS, true)]
+    #[test_case(StatusCode::INTERNAL_SERVER_ERROR, true)]
+    #[test_case(StatusCode::NOT_IMPLEMENTED, false)]
+    #[test_case(StatusCode::BAD_GATEWAY, true)]
+    fn test_should_retry_status(status: StatusCode, expected: bool) {
+        assert_eq!(should_retry_status(status), expected);
+    }
+
+    #[test]
+    fn test_backoff() {
+        let config = RetryConfig {
+            max_attempts: 5,
+            min_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(5),
+            jitter: false,
+            ..RetryConfig::default()
+        };
+        assert_eq!(config.backoff(0, 0.5), Duration::from_secs(1));
+        assert_eq!(config.backoff(1, 0.5), Duration::from_secs(2));
+        assert_eq!(config.backoff(2, 0.5), Duration::from_secs(4));
+        assert_eq!(config.backoff(3, 0.5), Duration::from_secs(5));
+        assert_eq!(config.backoff(40, 0.5), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_backoff_jitter() {
+        let config = RetryConfig {
+            max_attempts: 5,
+            min_backoff: Duration::from_secs(2),
+            max_backoff: Duration::from_secs(10),
+            jitter: true,
+            ..RetryConfig::default()
+        };
+        assert_eq!(config.backoff(1, 0.0), Duration::from_secs(2));
+        assert_eq!(config.backoff(1, 1.0), Duration::from_secs(4));
+        assert_eq!(config.backoff(1, 0.5), Duration::from_secs(3));
+    }
+
+    #[test]
+    fn test_warn_once() {
+        let printed = AtomicBool::new(false);
+        assert!(warn_once(&printed, "first retry"));
+        assert!(!warn_once(&printed, "second retry"));
+        assert!(!warn_once(&printed, "giving up"));
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(retry_after(&headers), None);
+
+        headers.insert(RETRY_AFTER, "120".parse().unwrap());
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(120)));
+
+        headers.insert(
+            RETRY_AFTER,
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+        );
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+
+        let in_a_minute = (Utc::now() + chrono::Duration::seconds(61)).to_rfc2822();
+        headers.insert(RETRY_AFTER, in_a_minute.parse().unwrap());
+        let delay = retry_after(&headers).unwrap();
+        assert!(delay > Duration::from_secs(55) && delay <= Duration::from_secs(61));
+
+        headers.insert(RETRY_AFTER, "soon".parse().unwrap());
+        assert_eq!(retry_after(&headers), None);
+    }
 }
@@ -0,0 +1,195 @@
+use std::{
+    env,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+    sync::Once,
+};
+
+use reqwest::ClientBuilder;
+use tracing::{debug, warn};
+
+use crate::Error;
+
+const CA_CERT_ENV: &str = "TURBO_CA_CERT";
+// Most JavaScript tooling already honors this so corporate environments tend
+// to have it set up.
+const NODE_CA_CERT_ENV: &str = "NODE_EXTRA_CA_CERTS";
+const INSECURE_ENV: &str = "TURBO_INSECURE_SKIP_TLS_VERIFY";
+
+const PEM_END: &str = "-----END CERTIFICATE-----";
+
+static INSECURE_WARNING: Once = Once::new();
+
+/// TLS settings shared by every client that talks to the network.
+///
+/// Proxies are configured through the standard `HTTP_PROXY`, `HTTPS_PROXY`,
+/// and `NO_PROXY` variables which are read by `reqwest` itself.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TlsConfig {
+    /// PEM bundle of certificate authorities to trust in addition to the
+    /// system's roots.
+    pub ca_file: Option<PathBuf>,
+    /// Accept any certificate presented by the server.
+    pub insecure: bool,
+    /// PEM encoded certificate to present to the server for mutual TLS.
+    pub client_cert: Option<PathBuf>,
+    /// PEM encoded private key for `client_cert`. If not set the key is
+    /// expected to be included in `client_cert`.
+    pub client_key: Option<PathBuf>,
+}
+
+impl TlsConfig {
+    pub fn from_env() -> Self {
+        Self::from_env_vars(|key| env::var_os(key))
+    }
+
+    fn from_env_vars(get: impl Fn(&str) -> Option<OsString>) -> Self {
+        let non_empty = |key: &str| get(key).filter(|value| !value.is_empty());
+        let ca_file = non_empty(CA_CERT_ENV)
+            .or_else(|| non_empty(NODE_CA_CERT_ENV))
+            .map(PathBuf::from);
+        let insecure = non_empty(INSECURE_ENV).map_or(false, |value| value != "0");
+
+        Self {
+            ca_file,
+            insecure,
+            ..Self::default()
+        }
+    }
+
+    /// Presents the given certificate to servers that request one.
+    pub fn with_client_identity(mut self, cert: PathBuf, key: Option<PathBuf>) -> Self {
+        self.client_cert = Some(cert);
+        self.client_key = key;
+        self
+    }
+
+    /// Creates a client builder with these settings applied.
+    pub fn client_builder(&self) -> Result<ClientBuilder, Error> {
+        let builder = reqwest::Client::builder();
+        self.apply(builder)
+    }
+
+    #[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+    fn apply(&self, mut builder: ClientBuilder) -> Result<ClientBuilder, Error> {
+        if let Some(ca_file) = &self.ca_file {
+            let bundle = fs::read_to_string(ca_file).map_err(|err| Error::ReadCaCertificate {
+                path: ca_file.display().to_string(),
+                err,
+            })?;
+            for certificate in split_pem_bundle(&bundle) {
+                let certificate =
+                    reqwest::Certificate::from_pem(certificate.as_bytes()).map_err(|err| {
+                        Error::InvalidCaCertificate {
+                            path: ca_file.display().to_string(),
+                            err,
+                        }
+                    })?;
+                builder = builder.add_root_certificate(certificate);
+            }
+            debug!("trusting certificates from {}", ca_file.display());
+        }
+
+        if self.insecure {
+            INSECURE_WARNING.call_once(|| {
+                warn!(
+                    "{INSECURE_ENV} is set, TLS certificates will not be verified. This allows \
+                     anyone on the network to read and tamper with your cache artifacts."
+                )
+            });
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+
+        if let Some(client_cert) = &self.client_cert {
+            builder = builder.identity(self.identity(client_cert)?);
+            debug!("using client certificate {}", client_cert.display());
+        }
+
+        Ok(builder)
+    }
+
+    // reqwest picks native-tls over rustls when both are enabled, the identity
+    // needs to match the backend that ends up being used.
+    #[cfg(feature = "native-tls")]
+    fn identity(&self, client_cert: &Path) -> Result<reqwest::Identity, Error> {
+        let cert = read_client_pem(client_cert)?;
+        let key = match &self.client_key {
+            Some(client_key) => read_client_pem(client_key)?,
+            None => cert.clone(),
+        };
+        reqwest::Identity::from_pkcs8_pem(&cert, &key).map_err(Error::InvalidClientCertificate)
+    }
+
+    #[cfg(all(feature = "rustls-tls", not(feature = "native-tls")))]
+    fn identity(&self, client_cert: &Path) -> Result<reqwest::Identity, Error> {
+        let mut pem = read_client_pem(client_cert)?;
+        if let Some(client_key) = &self.client_key {
+            pem.push(b'\n');
+            pem.extend(read_client_pem(client_key)?);
+        }
+        reqwest::Identity::from_pem(&pem).map_err(Error::InvalidClientCertificate)
+    }
+
+    // Without a TLS backend there are no certificates to configure
+    #[cfg(not(any(feature = "native-tls", feature = "rustls-tls")))]
+    fn apply(&self, builder: ClientBuilder) -> Result<ClientBuilder, Error> {
+        Ok(builder)
+    }
+}
+
+#[cfg(any(feature = "native-tls", feature = "rustls-tls"))]
+fn read_client_pem(path: &Path) -> Result<Vec<u8>, Error> {
+    fs::read(path).map_err(|err| Error::ReadClientCertificate {
+        path: path.display().to_string(),
+        err,
+    })
+}
+
+fn split_pem_bundle(bundle: &str) -> impl Iterator<Item = &str> {
+    bundle.split_inclusive(PEM_END).filter(|certificate| {
+        let certificate = certificate.trim();
+        certificate.ends_with(PEM_END)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn from_env(vars: &[(&str, &str)]) -> TlsConfig {
+        let vars: HashMap<_, _> = vars.iter().copied().collect();
+        TlsConfig::from_env_vars(|key| vars.get(key).map(OsString::from))
+    }
+
+    #[test]
+    fn test_from_env() {
+        assert_eq!(from_env(&[]), TlsConfig::default());
+        assert_eq!(
+            from_env(&[(NODE_CA_CERT_ENV, "/node.pem")]).ca_file,
+            Some(PathBuf::from("/node.pem"))
+        );
+        assert_eq!(
+            from_env(&[(CA_CERT_ENV, "/turbo.pem"), (NODE_CA_CERT_ENV, "/node.pem")]).ca_file,
+            Some(PathBuf::from("/turbo.pem"))
+        );
+        assert!(from_env(&[(INSECURE_ENV, "1")]).insecure);
+        assert!(!from_env(&[(INSECURE_ENV, "0")]).insecure);
+    }
+
+    #[test]
+    fn test_split_pem_bundle() {
+        let bundle = concat!(
+            "# comment\n",
+            "-----BEGIN CERTIFICATE-----\nA\n-----END CERTIFICATE-----\n",
+            "-----BEGIN CERTIFICATE-----\nB\n-----END CERTIFICATE-----\n",
+        );
+        let certificates = split_pem_bundle(bundle).collect::<Vec<_>>();
+        assert_eq!(certificates.len(), 2);
+        assert!(certificates[1]
+            .trim()
+            .starts_with("-----BEGIN CERTIFICATE-----\nB"));
+    }
+}
@@ -26,25 +26,32 @@ impl<T: TokenClient> LogoutOptions<T> {
             return Ok(());
         };
 
-        if self.invalidate {
-            match Token::from_file(path) {
-                Ok(token) => token.invalidate(&self.api_client).await?,
-                // If token doesn't exist, don't do anything.
-                Err(Error::TokenNotFound) => {}
-                Err(err) => return Err(err),
-            }
-        }
-
         // Attempt to deserialize the content into a serde_json::Value
         let mut data: serde_json::Value = serde_json::from_str(&content)?;
 
-        // Check if the data is an object and remove the "token" field if present
-        if let Some(obj) = data.as_object_mut() {
-            if obj.remove("token").is_none() {
-                return Ok(());
-            }
-        } else {
+        // Named profiles keep their token under `profiles.<name>`
+        let credentials = match &self.profile {
+            Some(profile) => data
+                .get_mut("profiles")
+                .and_then(|profiles| profiles.get_mut(profile)),
+            None => Some(&mut data),
+        };
+
+        // Check if the credentials are an object and remove the "token" field if
+        // present
+        let Some(token) = credentials
+            .and_then(|credentials| credentials.as_object_mut())
+            .and_then(|credentials| credentials.remove("token"))
+        else {
             return Ok(());
+        };
+
+        if self.invalidate {
+            if let Some(token) = token.as_str() {
+                Token::existing(token.to_owned())
+                    .invalidate(&self.api_client)
+                    .await?;
+            }
         }
 
         // Serialize the updated data back to a string
@@ -62,11 +69,14 @@ impl<T: TokenClient> LogoutOptions<T> {
             return self.try_remove_token(path).await;
         }
 
-        if let Some(vercel_config_dir) = vercel_config_dir()? {
-            self.try_remove_token(
-                &vercel_config_dir.join_components(&[VERCEL_TOKEN_DIR, VERCEL_TOKEN_FILE]),
-            )
-            .await?;
+        // Profiles are only ever stored in the turbo config
+        if self.profile.is_none() {
+            if let Some(vercel_config_dir) = vercel_config_dir()? {
+                self.try_remove_token(
+                    &vercel_config_dir.join_components(&[VERCEL_TOKEN_DIR, VERCEL_TOKEN_FILE]),
+                )
+                .await?;
+            }
         }
         if let Some(turbo_config_dir) = config_dir()? {
             self.try_remove_token(
@@ -145,6 +155,14 @@ mod tests {
 
     #[async_trait]
     impl TokenClient for MockApiClient {
+        async fn exchange_oidc_token(
+            &self,
+            _oidc_token: &str,
+            _team_id: Option<&str>,
+            _team_slug: Option<&str>,
+        ) -> turborepo_api_client::Result<turborepo_vercel_api::token::OidcTokenResponse> {
+            unimplemented!("exchange_oidc_token")
+        }
         async fn delete_token(&self, _token: &str) -> turborepo_api_client::Result<()> {
             if self.succeed_delete_request {
                 Ok(())
@@ -179,6 +197,7 @@ mod tests {
                 succeed_delete_request: true,
             },
             invalidate: false,
+            profile: None,
             path: Some(path.clone()),
         };
 
@@ -206,6 +225,7 @@ mod tests {
             api_client,
             path: Some(path.clone()),
             invalidate: true,
+            profile: None,
         };
 
         logout(&options).await.unwrap();
@@ -213,4 +233,33 @@ mod tests {
         let new_content = path.read_to_string().unwrap();
         assert_eq!(new_content, "{}");
     }
+
+    #[tokio::test]
+    async fn test_remove_profile_token() {
+        let tmp_dir = tempdir().unwrap();
+        let path = AbsoluteSystemPathBuf::try_from(tmp_dir.path().join("config.json"))
+            .expect("could not create path");
+        let content = r#"{"token":"some-token","profiles":{"work":{"token":"work-token","teamId":"team_work"}}}"#;
+        path.create_with_contents(content)
+            .expect("could not create file");
+
+        let logout_options = LogoutOptions {
+            ui: UI::new(false),
+            api_client: MockApiClient {
+                succeed_delete_request: true,
+            },
+            invalidate: false,
+            profile: Some("work".to_string()),
+            path: Some(path.clone()),
+        };
+
+        logout_options.remove_tokens().await.unwrap();
+
+        let new_content: serde_json::Value =
+            serde_json::from_str(&path.read_to_string().unwrap()).unwrap();
+        assert_eq!(
+            new_content,
+            serde_json::json!({"token": "some-token", "profiles": {"work": {"teamId": "team_work"}}})
+        );
+    }
 }
@@ -1,9 +1,11 @@
 mod login;
 mod logout;
+mod oidc;
 mod sso;
 
 pub use login::*;
 pub use logout::*;
+pub use oidc::*;
 pub use sso::*;
 use turbopath::AbsoluteSystemPathBuf;
 use turborepo_api_client::{CacheClient, Client, TokenClient};
@@ -49,6 +51,8 @@ pub struct LogoutOptions<T> {
     pub api_client: T,
     /// If we should invalidate the token on the server.
     pub invalidate: bool,
+    /// Only remove the token stored for this named profile
+    pub profile: Option<String>,
     /// Path override for testing
     #[cfg(test)]
     pub path: Option<AbsoluteSystemPathBuf>,
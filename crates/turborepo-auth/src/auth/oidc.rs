@@ -0,0 +1,169 @@
+use std::env;
+
+use reqwest::Url;
+use serde::Deserialize;
+use turborepo_api_client::{TlsConfig, TokenClient};
+use turborepo_vercel_api::token::OidcTokenResponse;
+
+use crate::Error;
+
+// Only present when the workflow has been granted `id-token: write`
+const GITHUB_REQUEST_URL: &str = "ACTIONS_ID_TOKEN_REQUEST_URL";
+const GITHUB_REQUEST_TOKEN: &str = "ACTIONS_ID_TOKEN_REQUEST_TOKEN";
+// GitLab (and any other provider that hands out the ID token directly) should
+// expose it under this name, e.g. via `id_tokens` in `.gitlab-ci.yml`.
+const TURBO_OIDC_TOKEN: &str = "TURBO_OIDC_TOKEN";
+
+/// A CI provider that is able to issue an OIDC ID token for the current job.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OidcProvider {
+    /// GitHub Actions requires requesting the ID token from the runner.
+    GitHubActions {
+        request_url: String,
+        request_token: String,
+    },
+    /// The ID token has already been issued and placed in the environment.
+    Env { id_token: String },
+}
+
+impl OidcProvider {
+    /// Detects an OIDC provider from the current environment.
+    pub fn detect() -> Option<Self> {
+        Self::from_env(|key| env::var(key).ok())
+    }
+
+    fn from_env(get: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        let non_empty = |key: &str| get(key).filter(|value| !value.is_empty());
+
+        if let Some(id_token) = non_empty(TURBO_OIDC_TOKEN) {
+            return Some(Self::Env { id_token });
+        }
+
+        match (
+            non_empty(GITHUB_REQUEST_URL),
+            non_empty(GITHUB_REQUEST_TOKEN),
+        ) {
+            (Some(request_url), Some(request_token)) => Some(Self::GitHubActions {
+                request_url,
+                request_token,
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::GitHubActions { .. } => "GitHub Actions",
+            Self::Env { .. } => TURBO_OIDC_TOKEN,
+        }
+    }
+
+    async fn id_token(&self, audience: &str) -> Result<String, Error> {
+        match self {
+            Self::Env { id_token } => Ok(id_token.clone()),
+            Self::GitHubActions {
+                request_url,
+                request_token,
+            } => {
+                #[derive(Deserialize)]
+                struct IdTokenResponse {
+                    value: String,
+                }
+
+                let mut url = Url::parse(request_url)?;
+                url.query_pairs_mut().append_pair("audience", audience);
+
+                let client = TlsConfig::from_env()
+                    .client_builder()
+                    .and_then(|builder| {
+                        builder
+                            .build()
+                            .map_err(turborepo_api_client::Error::TlsError)
+                    })
+                    .map_err(Error::FailedToExchangeOidcToken)?;
+                let response = client
+                    .get(url)
+                    .header("Authorization", format!("Bearer {request_token}"))
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status())
+                    .map_err(Error::FailedToRequestOidcToken)?;
+                let body = response
+                    .text()
+                    .await
+                    .map_err(Error::FailedToRequestOidcToken)?;
+
+                Ok(serde_json::from_str::<IdTokenResponse>(&body)?.value)
+            }
+        }
+    }
+}
+
+/// Exchanges the ID token issued by `provider` for a short-lived token that
+/// can access the remote cache. The returned token is never written to disk.
+///
+/// ## Arguments
+/// * `api_client` - The client to use for the exchange.
+/// * `provider` - The CI provider to request the ID token from.
+/// * `audience` - The audience the ID token should be issued for.
+/// * `team_id` / `team_slug` - The team the token should be scoped to.
+pub async fn exchange_oidc_token<T: TokenClient>(
+    api_client: &T,
+    provider: &OidcProvider,
+    audience: &str,
+    team_id: Option<&str>,
+    team_slug: Option<&str>,
+) -> Result<OidcTokenResponse, Error> {
+    let id_token = provider.id_token(audience).await?;
+
+    api_client
+        .exchange_oidc_token(&id_token, team_id, team_slug)
+        .await
+        .map_err(Error::FailedToExchangeOidcToken)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn detect(vars: &[(&str, &str)]) -> Option<OidcProvider> {
+        let vars: HashMap<_, _> = vars.iter().copied().collect();
+        OidcProvider::from_env(|key| vars.get(key).map(|value| value.to_string()))
+    }
+
+    #[test]
+    fn test_detect_github_actions() {
+        assert_eq!(
+            detect(&[
+                (GITHUB_REQUEST_URL, "https://token.actions.example/?a=b"),
+                (GITHUB_REQUEST_TOKEN, "request-token"),
+            ]),
+            Some(OidcProvider::GitHubActions {
+                request_url: "https://token.actions.example/?a=b".into(),
+                request_token: "request-token".into(),
+            })
+        );
+        // Without `id-token: write` only some of the variables are present
+        assert_eq!(
+            detect(&[(GITHUB_REQUEST_URL, "https://token.actions.example")]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_detect_env_token() {
+        assert_eq!(
+            detect(&[
+                (TURBO_OIDC_TOKEN, "id-token"),
+                (GITHUB_REQUEST_URL, "https://token.actions.example"),
+                (GITHUB_REQUEST_TOKEN, "request-token"),
+            ]),
+            Some(OidcProvider::Env {
+                id_token: "id-token".into()
+            })
+        );
+        assert_eq!(detect(&[(TURBO_OIDC_TOKEN, "")]), None);
+    }
+}
@@ -146,8 +146,8 @@ mod tests {
     use turborepo_api_client::Client;
     use turborepo_ui::UI;
     use turborepo_vercel_api::{
-        CachingStatus, CachingStatusResponse, Membership, Role, SpacesResponse, Team,
-        TeamsResponse, User, UserResponse, VerifiedSsoUser,
+        ArtifactsQueryResponse, CachingStatus, CachingStatusResponse, Membership, Role,
+        SpacesResponse, Team, TeamsResponse, User, UserResponse, VerifiedSsoUser,
     };
     use turborepo_vercel_api_mock::start_test_server;
 
@@ -271,6 +271,14 @@ mod tests {
 
     #[async_trait]
     impl TokenClient for MockApiClient {
+        async fn exchange_oidc_token(
+            &self,
+            _oidc_token: &str,
+            _team_id: Option<&str>,
+            _team_slug: Option<&str>,
+        ) -> turborepo_api_client::Result<turborepo_vercel_api::token::OidcTokenResponse> {
+            unimplemented!("exchange_oidc_token")
+        }
         async fn get_metadata(
             &self,
             token: &str,
@@ -342,6 +350,15 @@ mod tests {
         ) -> Result<Option<Response>, turborepo_api_client::Error> {
             unimplemented!("artifact_exists")
         }
+        async fn artifacts_exist(
+            &self,
+            _hashes: &[String],
+            _token: &str,
+            _team_id: Option<&str>,
+            _team_slug: Option<&str>,
+        ) -> Result<Option<ArtifactsQueryResponse>, turborepo_api_client::Error> {
+            unimplemented!("artifacts_exist")
+        }
         async fn get_caching_status(
             &self,
             _token: &str,
@@ -34,6 +34,10 @@ pub enum Error {
     SSOTeamNotFound(String),
     #[error("sso token expired for team: {0}")]
     SSOTokenExpired(String),
+    #[error("failed to request OIDC token from CI provider")]
+    FailedToRequestOidcToken(#[source] reqwest::Error),
+    #[error("failed to exchange OIDC token: {0}")]
+    FailedToExchangeOidcToken(#[source] turborepo_api_client::Error),
     #[error("token not found")]
     TokenNotFound,
     #[error("invalid token file format: {0}")]
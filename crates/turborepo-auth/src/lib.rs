@@ -270,7 +270,9 @@ mod tests {
     use reqwest::{Method, Response};
     use tempfile::tempdir;
     use turbopath::AbsoluteSystemPathBuf;
-    use turborepo_vercel_api::{token::Scope, CachingStatus, CachingStatusResponse};
+    use turborepo_vercel_api::{
+        token::Scope, ArtifactsQueryResponse, CachingStatus, CachingStatusResponse,
+    };
 
     use super::*;
 
@@ -442,6 +444,16 @@ mod tests {
             unimplemented!()
         }
 
+        async fn artifacts_exist(
+            &self,
+            _hashes: &[String],
+            _token: &str,
+            _team_id: Option<&str>,
+            _team_slug: Option<&str>,
+        ) -> Result<Option<ArtifactsQueryResponse>, turborepo_api_client::Error> {
+            unimplemented!()
+        }
+
         async fn get_caching_status(
             &self,
             _token: &str,
@@ -1,13 +1,20 @@
-use std::sync::{atomic::AtomicU8, Arc};
+use std::{
+    collections::HashSet,
+    future::Future,
+    sync::{atomic::AtomicU8, Arc, Mutex},
+    time::Duration,
+};
 
 use futures::{stream::FuturesUnordered, StreamExt};
-use tokio::sync::{mpsc, Semaphore};
+use tokio::sync::{mpsc, oneshot, Semaphore};
 use tracing::{warn, Instrument, Level};
 use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf, AnchoredSystemPathBuf};
 use turborepo_analytics::AnalyticsSender;
 use turborepo_api_client::{APIAuth, APIClient};
 
-use crate::{multiplexer::CacheMultiplexer, CacheError, CacheHitMetadata, CacheOpts};
+use crate::{
+    audit::AuditLog, multiplexer::CacheMultiplexer, CacheError, CacheHitMetadata, CacheOpts,
+};
 
 const WARNING_CUTOFF: u8 = 4;
 
@@ -15,6 +22,33 @@ const WARNING_CUTOFF: u8 = 4;
 pub struct AsyncCache {
     real_cache: Arc<CacheMultiplexer>,
     writer_sender: mpsc::Sender<WorkerRequest>,
+    audit_log: Option<AuditLog>,
+    writes: Arc<Mutex<Writes>>,
+}
+
+#[derive(Default)]
+struct Writes {
+    // Hashes of the writes that were requested but haven't finished yet
+    pending: Vec<String>,
+    failed: HashSet<String>,
+}
+
+/// What happened to the writes that were still pending when the cache was
+/// shut down.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ShutdownSummary {
+    pub persisted: Vec<String>,
+    pub failed: Vec<String>,
+    /// Writes that didn't finish in time and were cancelled. Nothing is left
+    /// behind for them.
+    pub cancelled: Vec<String>,
+}
+
+impl ShutdownSummary {
+    /// Whether every write that was pending at shutdown was persisted
+    pub fn all_persisted(&self) -> bool {
+        self.failed.is_empty() && self.cancelled.is_empty()
+    }
 }
 
 enum WorkerRequest {
@@ -25,7 +59,11 @@ enum WorkerRequest {
         files: Vec<AnchoredSystemPathBuf>,
     },
     Flush(tokio::sync::oneshot::Sender<()>),
-    Shutdown(tokio::sync::oneshot::Sender<()>),
+    Shutdown {
+        callback: oneshot::Sender<()>,
+        // Aborts the writes that are still running
+        cancel: oneshot::Receiver<()>,
+    },
 }
 
 impl AsyncCache {
@@ -37,24 +75,28 @@ impl AsyncCache {
         analytics_recorder: Option<AnalyticsSender>,
     ) -> Result<AsyncCache, CacheError> {
         let max_workers = opts.workers.try_into().expect("usize is smaller than u32");
+        let audit_log = opts.audit_log.as_deref().map(AuditLog::start).transpose()?;
         let real_cache = Arc::new(CacheMultiplexer::new(
             opts,
             repo_root,
             api_client,
             api_auth,
             analytics_recorder,
+            audit_log.clone(),
         )?);
         let (writer_sender, mut write_consumer) = mpsc::channel(1);
+        let writes = Arc::new(Mutex::new(Writes::default()));
 
         // start a task to manage workers
         let worker_real_cache = real_cache.clone();
+        let worker_writes = writes.clone();
         tokio::spawn(async move {
             let semaphore = Arc::new(Semaphore::new(max_workers));
             let mut workers = FuturesUnordered::new();
             let real_cache = worker_real_cache;
             let warnings = Arc::new(AtomicU8::new(0));
 
-            let mut shutdown_callback = None;
+            let mut shutdown = None;
             while let Some(request) = write_consumer.recv().await {
                 match request {
                     WorkerRequest::WriteRequest {
@@ -66,12 +108,16 @@ impl AsyncCache {
                         let permit = semaphore.clone().acquire_owned().await.unwrap();
                         let real_cache = real_cache.clone();
                         let warnings = warnings.clone();
+                        let writes = worker_writes.clone();
                         let worker_span = tracing::span!(Level::TRACE, "cache worker: cache PUT");
                         workers.push(tokio::spawn(
                             async move {
-                                if let Err(err) =
-                                    real_cache.put(&anchor, &key, &files, duration).await
-                                {
+                                let result = real_cache.put(&anchor, &key, &files, duration).await;
+                                writes
+                                    .lock()
+                                    .expect("lock poisoned")
+                                    .finish(&key, result.is_ok());
+                                if let Err(err) = result {
                                     let num_warnings =
                                         warnings.load(std::sync::atomic::Ordering::Acquire);
                                     if num_warnings <= WARNING_CUTOFF {
@@ -95,8 +141,8 @@ impl AsyncCache {
                         }
                         drop(callback);
                     }
-                    WorkerRequest::Shutdown(callback) => {
-                        shutdown_callback = Some(callback);
+                    WorkerRequest::Shutdown { callback, cancel } => {
+                        shutdown = Some((callback, cancel));
                         break;
                     }
                 };
@@ -104,18 +150,32 @@ impl AsyncCache {
             // Drop write consumer to immediately notify callers that cache is shutting down
             drop(write_consumer);
 
-            // wait for all writers to finish
-            while let Some(worker) = workers.next().await {
-                let _ = worker;
-            }
-            if let Some(callback) = shutdown_callback {
-                callback.send(()).ok();
+            let Some((callback, cancel)) = shutdown else {
+                // wait for all writers to finish
+                while workers.next().await.is_some() {}
+                return;
+            };
+            let cancelled = tokio::select! {
+                _ = async { while workers.next().await.is_some() {} } => false,
+                // The shutdown only gets cancelled on purpose, a dropped sender keeps waiting
+                Ok(()) = cancel => true,
+            };
+            if cancelled {
+                // Aborted writes never get to move their archive into place, so they don't
+                // leave truncated artifacts behind
+                for worker in workers.iter() {
+                    worker.abort();
+                }
+                while workers.next().await.is_some() {}
             }
+            callback.send(()).ok();
         });
 
         Ok(AsyncCache {
             real_cache,
             writer_sender,
+            audit_log,
+            writes,
         })
     }
 
@@ -127,23 +187,34 @@ impl AsyncCache {
         files: Vec<AnchoredSystemPathBuf>,
         duration: u64,
     ) -> Result<(), CacheError> {
+        self.writes
+            .lock()
+            .expect("lock poisoned")
+            .pending
+            .push(key.clone());
         if self
             .writer_sender
             .send(WorkerRequest::WriteRequest {
                 anchor,
-                key,
+                key: key.clone(),
                 duration,
                 files,
             })
             .await
             .is_err()
         {
+            self.writes.lock().expect("lock poisoned").remove(&key);
             Err(CacheError::CacheShuttingDown)
         } else {
             Ok(())
         }
     }
 
+    /// The number of cache writes that haven't finished yet
+    pub fn pending_writes(&self) -> usize {
+        self.writes.lock().expect("lock poisoned").pending.len()
+    }
+
     #[tracing::instrument(skip_all)]
     pub async fn exists(&self, key: &str) -> Result<Option<CacheHitMetadata>, CacheError> {
         self.real_cache.exists(key).await
@@ -169,24 +240,87 @@ impl AsyncCache {
             .map_err(|_| CacheError::CacheShuttingDown)?;
         // Wait until flush callback is finished
         rx.await.ok();
+        if let Some(audit_log) = &self.audit_log {
+            audit_log.flush().await;
+        }
         Ok(())
     }
 
     #[tracing::instrument(skip_all)]
     pub async fn shutdown(&self) -> Result<(), CacheError> {
-        let (tx, rx) = tokio::sync::oneshot::channel();
+        self.shutdown_within(Duration::MAX, futures::future::pending())
+            .await
+            .map(|_| ())
+    }
+
+    /// Shuts down the cache, waiting for the pending writes to finish for at
+    /// most `timeout`. Writes that are still running after that, or once
+    /// `cancel` resolves, are cancelled.
+    #[tracing::instrument(skip_all)]
+    pub async fn shutdown_within(
+        &self,
+        timeout: Duration,
+        cancel: impl Future<Output = ()>,
+    ) -> Result<ShutdownSummary, CacheError> {
+        let (tx, mut rx) = oneshot::channel();
+        let (cancel_tx, cancel_rx) = oneshot::channel();
         self.writer_sender
-            .send(WorkerRequest::Shutdown(tx))
+            .send(WorkerRequest::Shutdown {
+                callback: tx,
+                cancel: cancel_rx,
+            })
             .await
             .map_err(|_| CacheError::CacheShuttingDown)?;
-        rx.await.ok();
-        Ok(())
+        // Every write that was requested before the shutdown has been picked up by now
+        let pending = self.writes.lock().expect("lock poisoned").pending.clone();
+
+        let finished = tokio::select! {
+            _ = &mut rx => true,
+            _ = tokio::time::sleep(timeout) => false,
+            _ = cancel => false,
+        };
+        if !finished {
+            cancel_tx.send(()).ok();
+            rx.await.ok();
+        }
+        // Make sure every upload is in the audit log before we exit
+        if let Some(audit_log) = &self.audit_log {
+            audit_log.flush().await;
+        }
+
+        let writes = self.writes.lock().expect("lock poisoned");
+        let mut summary = ShutdownSummary::default();
+        for key in pending {
+            if writes.pending.contains(&key) {
+                summary.cancelled.push(key);
+            } else if writes.failed.contains(&key) {
+                summary.failed.push(key);
+            } else {
+                summary.persisted.push(key);
+            }
+        }
+        Ok(summary)
+    }
+}
+
+impl Writes {
+    fn remove(&mut self, key: &str) {
+        if let Some(index) = self.pending.iter().position(|pending| pending == key) {
+            self.pending.swap_remove(index);
+        }
+    }
+
+    fn finish(&mut self, key: &str, succeeded: bool) {
+        self.remove(key);
+        if !succeeded {
+            self.failed.insert(key.to_string());
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::assert_matches::assert_matches;
+    use std::{assert_matches::assert_matches, time::Duration};
 
     use anyhow::Result;
     use futures::future::try_join_all;
@@ -197,7 +331,7 @@ mod tests {
 
     use crate::{
         test_cases::{get_test_cases, TestCase},
-        AsyncCache, CacheHitMetadata, CacheOpts, CacheSource, RemoteCacheOpts,
+        AsyncCache, CacheHitMetadata, CacheOpts, CacheSource, RemoteCacheOpts, ShutdownSummary,
     };
 
     #[tokio::test]
@@ -222,12 +356,15 @@ mod tests {
         test_case.initialize(&repo_root_path)?;
 
         let hash = format!("{}-no-fs", test_case.hash);
+        let audit_log_path = repo_root_path.join_components(&[".turbo", "audit.jsonl"]);
 
         let opts = CacheOpts {
             override_dir: None,
             remote_cache_read_only: false,
             skip_remote: false,
             skip_filesystem: true,
+            fallback_on_rate_limit: false,
+            audit_log: Some(audit_log_path.clone()),
             workers: 10,
             remote_cache_opts: Some(RemoteCacheOpts {
                 unused_team_id: Some("my-team".to_string()),
@@ -265,6 +402,7 @@ mod tests {
 
         // Wait for async cache to process
         async_cache.wait().await.unwrap();
+        assert_eq!(async_cache.pending_writes(), 0);
 
         let fs_cache_path = repo_root_path.join_components(&[
             "node_modules",
@@ -276,6 +414,13 @@ mod tests {
         // Confirm that fs cache file does *not* exist
         assert!(!fs_cache_path.exists());
 
+        // Confirm that the upload was audited
+        let audit_log = audit_log_path.read_to_string()?;
+        let upload: serde_json::Value = serde_json::from_str(audit_log.trim_end())?;
+        assert_eq!(upload["action"], "upload");
+        assert_eq!(upload["hash"], hash.as_str());
+        assert_eq!(upload["team"], "my-team-id");
+
         let response = async_cache.exists(&hash).await?;
 
         // Confirm that we fetch from remote cache and not local.
@@ -308,6 +453,8 @@ mod tests {
             remote_cache_read_only: false,
             skip_remote: true,
             skip_filesystem: false,
+            fallback_on_rate_limit: false,
+            audit_log: None,
             workers: 10,
             remote_cache_opts: Some(RemoteCacheOpts {
                 unused_team_id: Some("my-team".to_string()),
@@ -398,6 +545,8 @@ mod tests {
             remote_cache_read_only: false,
             skip_remote: false,
             skip_filesystem: false,
+            fallback_on_rate_limit: false,
+            audit_log: None,
             workers: 10,
             remote_cache_opts: Some(RemoteCacheOpts {
                 unused_team_id: Some("my-team".to_string()),
@@ -479,4 +628,102 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_shutdown_within() -> Result<()> {
+        let test_case = &get_test_cases()[0];
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPathBuf::try_from(repo_root.path())?;
+        test_case.initialize(&repo_root_path)?;
+
+        let opts = CacheOpts {
+            skip_remote: true,
+            workers: 10,
+            ..CacheOpts::default()
+        };
+        let api_client = APIClient::new("http://example.com", 200, "2.0.0", true)?;
+        let files: Vec<_> = test_case
+            .files
+            .iter()
+            .map(|f| f.path().to_owned())
+            .collect();
+
+        // Writes that finish within the timeout are persisted
+        let async_cache = AsyncCache::new(&opts, &repo_root_path, api_client.clone(), None, None)?;
+        async_cache
+            .put(
+                repo_root_path.clone(),
+                "finished".to_string(),
+                files.clone(),
+                test_case.duration,
+            )
+            .await?;
+        let summary = async_cache
+            .shutdown_within(Duration::from_secs(30), futures::future::pending())
+            .await?;
+        assert_eq!(
+            summary,
+            ShutdownSummary {
+                persisted: vec!["finished".to_string()],
+                ..ShutdownSummary::default()
+            }
+        );
+
+        // A remote cache that accepts connections but never responds, so uploads
+        // only end when they get cancelled
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let port = listener.local_addr()?.port();
+        let server = tokio::spawn(async move {
+            let mut connections = Vec::new();
+            while let Ok((connection, _)) = listener.accept().await {
+                connections.push(connection);
+            }
+        });
+        let opts = CacheOpts {
+            skip_remote: false,
+            skip_filesystem: true,
+            remote_cache_opts: Some(RemoteCacheOpts {
+                unused_team_id: Some("my-team".to_string()),
+                signature: false,
+            }),
+            ..opts
+        };
+        let api_client = APIClient::new(format!("http://localhost:{}", port), 200, "2.0.0", true)?;
+        let api_auth = Some(APIAuth {
+            team_id: Some("my-team-id".to_string()),
+            token: "my-token".to_string(),
+            team_slug: None,
+        });
+        let async_cache = AsyncCache::new(&opts, &repo_root_path, api_client, api_auth, None)?;
+        async_cache
+            .put(
+                repo_root_path.clone(),
+                "cancelled".to_string(),
+                files,
+                test_case.duration,
+            )
+            .await?;
+        let summary = async_cache
+            .shutdown_within(Duration::from_secs(30), futures::future::ready(()))
+            .await?;
+        assert_eq!(
+            summary,
+            ShutdownSummary {
+                cancelled: vec!["cancelled".to_string()],
+                ..ShutdownSummary::default()
+            }
+        );
+        assert_eq!(async_cache.pending_writes(), 1);
+
+        // Cancelled writes don't leave an artifact, partial or not, behind
+        let cache_dir = repo_root_path.join_components(&["node_modules", ".cache", "turbo"]);
+        let mut archives = std::fs::read_dir(cache_dir.as_std_path())?
+            .map(|entry| entry.map(|entry| entry.file_name().to_string_lossy().into_owned()))
+            .collect::<Result<Vec<_>, _>>()?;
+        archives.retain(|name| !name.ends_with("-meta.json"));
+        assert_eq!(archives, vec!["finished.tar.zst"]);
+
+        server.abort();
+        Ok(())
+    }
 }
@@ -0,0 +1,160 @@
+use std::{fs::OpenOptions, io::Write};
+
+use serde::Serialize;
+use tokio::sync::{mpsc, oneshot};
+use tracing::warn;
+use turbopath::AbsoluteSystemPath;
+
+use crate::CacheError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditAction {
+    Upload,
+    Download,
+}
+
+/// A single line of the audit log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AuditEvent {
+    pub timestamp: String,
+    pub user: Option<String>,
+    pub team: Option<String>,
+    pub action: AuditAction,
+    pub hash: String,
+    pub size: usize,
+    pub url: String,
+}
+
+enum AuditMessage {
+    Event(AuditEvent),
+    Flush(oneshot::Sender<()>),
+}
+
+/// Appends a JSON line for every artifact uploaded to or downloaded from the
+/// remote cache. Events are written on a blocking thread so recording one
+/// never waits on the filesystem.
+#[derive(Clone)]
+pub struct AuditLog {
+    sender: mpsc::UnboundedSender<AuditMessage>,
+    user: Option<String>,
+}
+
+impl AuditLog {
+    pub fn start(path: &AbsoluteSystemPath) -> Result<Self, CacheError> {
+        path.ensure_dir()?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path.as_std_path())?;
+
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        tokio::task::spawn_blocking(move || {
+            while let Some(message) = receiver.blocking_recv() {
+                match message {
+                    AuditMessage::Event(event) => {
+                        let mut line =
+                            serde_json::to_vec(&event).expect("audit event is serializable");
+                        line.push(b'\n');
+                        // Written in one go so concurrent runs don't interleave lines
+                        if let Err(err) = file.write_all(&line) {
+                            warn!("failed to write to remote cache audit log: {err}");
+                        }
+                    }
+                    AuditMessage::Flush(callback) => {
+                        let _ = file.flush();
+                        let _ = callback.send(());
+                    }
+                }
+            }
+        });
+
+        let user = std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .ok();
+
+        Ok(Self { sender, user })
+    }
+
+    pub fn record(
+        &self,
+        action: AuditAction,
+        team: Option<&str>,
+        hash: &str,
+        size: usize,
+        url: String,
+    ) {
+        let event = AuditEvent {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            user: self.user.clone(),
+            team: team.map(|team| team.to_string()),
+            action,
+            hash: hash.to_string(),
+            size,
+            url,
+        };
+        // Failing to audit isn't worth failing the cache over
+        let _ = self.sender.send(AuditMessage::Event(event));
+    }
+
+    /// Waits for all events recorded so far to be written
+    pub async fn flush(&self) {
+        let (tx, rx) = oneshot::channel();
+        if self.sender.send(AuditMessage::Flush(tx)).is_ok() {
+            rx.await.ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tempfile::tempdir;
+    use turbopath::AbsoluteSystemPathBuf;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_audit_log() {
+        let tmp = tempdir().unwrap();
+        let path = AbsoluteSystemPathBuf::try_from(tmp.path().join("logs/audit.jsonl")).unwrap();
+        path.ensure_dir().unwrap();
+        path.create_with_contents("{\"existing\":true}\n").unwrap();
+
+        let audit_log = AuditLog::start(&path).unwrap();
+        audit_log.record(
+            AuditAction::Upload,
+            Some("my-team"),
+            "abc123",
+            42,
+            "https://cache.example.com/v8/artifacts/abc123".to_string(),
+        );
+        audit_log.record(
+            AuditAction::Download,
+            None,
+            "def456",
+            7,
+            "https://cache.example.com/v8/artifacts/def456".to_string(),
+        );
+        audit_log.flush().await;
+
+        let contents = path.read_to_string().unwrap();
+        let lines = contents.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], "{\"existing\":true}");
+
+        let upload: serde_json::Value = serde_json::from_str(lines[1]).unwrap();
+        assert_eq!(upload["action"], "upload");
+        assert_eq!(upload["team"], "my-team");
+        assert_eq!(upload["hash"], "abc123");
+        assert_eq!(upload["size"], 42);
+        assert!(upload["timestamp"].is_string());
+
+        let download: serde_json::Value = serde_json::from_str(lines[2]).unwrap();
+        assert_eq!(download["action"], "download");
+        assert_eq!(download["team"], serde_json::Value::Null);
+        assert_eq!(
+            download["url"],
+            "https://cache.example.com/v8/artifacts/def456"
+        );
+    }
+}
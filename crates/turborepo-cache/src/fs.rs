@@ -30,7 +30,7 @@ impl CacheMetadata {
 }
 
 impl FSCache {
-    fn resolve_cache_dir(
+    pub fn resolve_cache_dir(
         repo_root: &AbsoluteSystemPath,
         override_dir: Option<&Utf8Path>,
     ) -> AbsoluteSystemPathBuf {
@@ -152,12 +152,36 @@ impl FSCache {
         let cache_path = self
             .cache_directory
             .join_component(&format!("{}.tar.zst", hash));
+        // The archive is only moved into place once it's complete, so an interrupted
+        // write doesn't leave a truncated artifact behind
+        let partial_cache_path = self
+            .cache_directory
+            .join_component(&format!("{}.partial.tar.zst", hash));
+
+        let result = self
+            .write_partial(&partial_cache_path, anchor, hash, files, duration)
+            .and_then(|()| Ok(partial_cache_path.rename(&cache_path)?));
+        if result.is_err() {
+            // Don't leave the partial archive behind, nothing will ever pick it up
+            let _ = partial_cache_path.remove_file();
+        }
+        result
+    }
 
-        let mut cache_item = CacheWriter::create(&cache_path)?;
+    fn write_partial(
+        &self,
+        partial_cache_path: &AbsoluteSystemPath,
+        anchor: &AbsoluteSystemPath,
+        hash: &str,
+        files: &[AnchoredSystemPathBuf],
+        duration: u64,
+    ) -> Result<(), CacheError> {
+        let mut cache_item = CacheWriter::create(partial_cache_path)?;
 
         for file in files {
             cache_item.add_file(anchor, file)?;
         }
+        cache_item.finish()?;
 
         let metadata_path = self
             .cache_directory
@@ -236,6 +260,10 @@ mod test {
             .map(|f| f.path().to_owned())
             .collect();
         cache.put(repo_root_path, test_case.hash, &files, test_case.duration)?;
+        assert!(!cache
+            .cache_directory
+            .join_component(&format!("{}.partial.tar.zst", test_case.hash))
+            .exists());
 
         let (status, files) = cache.fetch(repo_root_path, test_case.hash)?.unwrap();
 
@@ -262,4 +290,24 @@ mod test {
         analytics_handle.close_with_timeout().await;
         Ok(())
     }
+
+    #[test]
+    fn test_failed_put_removes_partial_archive() -> Result<()> {
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPath::from_std_path(repo_root.path())?;
+        let cache = FSCache::new(None, repo_root_path, None)?;
+
+        let files = [AnchoredSystemPathBuf::from_raw("missing.txt")?];
+        assert!(cache.put(repo_root_path, "failed", &files, 10).is_err());
+        assert!(!cache
+            .cache_directory
+            .join_component("failed.partial.tar.zst")
+            .exists());
+        assert!(!cache
+            .cache_directory
+            .join_component("failed.tar.zst")
+            .exists());
+
+        Ok(())
+    }
 }
@@ -1,5 +1,14 @@
-use std::{backtrace::Backtrace, io::Write};
+use std::{
+    backtrace::Backtrace,
+    io::Write,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, OnceLock,
+    },
+    time::Duration,
+};
 
+use tokio::sync::{mpsc, oneshot};
 use tracing::debug;
 use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf, AnchoredSystemPathBuf};
 use turborepo_analytics::AnalyticsSender;
@@ -7,19 +16,35 @@ use turborepo_api_client::{
     analytics::{self, AnalyticsEvent},
     APIAuth, APIClient, CacheClient, Response,
 };
+use turborepo_vercel_api::ArtifactQueryResult;
 
 use crate::{
+    audit::{AuditAction, AuditLog},
     cache_archive::{CacheReader, CacheWriter},
     signature_authentication::ArtifactSignatureAuthenticator,
     CacheError, CacheHitMetadata, CacheOpts, CacheSource,
 };
 
+// How long to wait for more existence checks before sending a batch
+const EXISTS_BATCH_WINDOW: Duration = Duration::from_millis(10);
+const MAX_EXISTS_BATCH_SIZE: usize = 100;
+
+// `None` means the batch couldn't be checked and the hash should be checked
+// on its own
+type ExistsResponse = Option<Option<CacheHitMetadata>>;
+type ExistsRequest = (String, oneshot::Sender<ExistsResponse>);
+
 pub struct HTTPCache {
     client: APIClient,
     signer_verifier: Option<ArtifactSignatureAuthenticator>,
     repo_root: AbsoluteSystemPathBuf,
     api_auth: APIAuth,
     analytics_recorder: Option<AnalyticsSender>,
+    audit_log: Option<AuditLog>,
+    // Started on the first existence check so we're inside of a runtime
+    exists_batcher: OnceLock<mpsc::UnboundedSender<ExistsRequest>>,
+    // Cleared if the server doesn't support checking artifacts in bulk
+    supports_batching: Arc<AtomicBool>,
 }
 
 impl HTTPCache {
@@ -30,6 +55,7 @@ impl HTTPCache {
         repo_root: AbsoluteSystemPathBuf,
         api_auth: APIAuth,
         analytics_recorder: Option<AnalyticsSender>,
+        audit_log: Option<AuditLog>,
     ) -> HTTPCache {
         let signer_verifier = if opts
             .remote_cache_opts
@@ -55,6 +81,9 @@ impl HTTPCache {
             repo_root,
             api_auth,
             analytics_recorder,
+            audit_log,
+            exists_batcher: OnceLock::new(),
+            supports_batching: Arc::new(AtomicBool::new(true)),
         }
     }
 
@@ -87,9 +116,26 @@ impl HTTPCache {
             )
             .await?;
 
+        if let Some(audit_log) = &self.audit_log {
+            audit_log.record(
+                AuditAction::Upload,
+                self.team(),
+                hash,
+                artifact_body.len(),
+                format!("{}/v8/artifacts/{}", self.client.base_url(), hash),
+            );
+        }
+
         Ok(())
     }
 
+    fn team(&self) -> Option<&str> {
+        self.api_auth
+            .team_slug
+            .as_deref()
+            .or(self.api_auth.team_id.as_deref())
+    }
+
     #[tracing::instrument(skip_all)]
     async fn write(
         &self,
@@ -105,8 +151,97 @@ impl HTTPCache {
         Ok(())
     }
 
+    /// Checks if an artifact exists. Checks that happen at the same time are
+    /// sent to the server in batches.
     #[tracing::instrument(skip_all)]
     pub async fn exists(&self, hash: &str) -> Result<Option<CacheHitMetadata>, CacheError> {
+        if self.supports_batching.load(Ordering::Relaxed) {
+            let (tx, rx) = oneshot::channel();
+            if self.exists_batcher().send((hash.to_string(), tx)).is_ok() {
+                if let Ok(Some(hit)) = rx.await {
+                    return Ok(hit);
+                }
+            }
+        }
+
+        self.exists_single(hash).await
+    }
+
+    fn exists_batcher(&self) -> &mpsc::UnboundedSender<ExistsRequest> {
+        self.exists_batcher.get_or_init(|| {
+            let (tx, rx) = mpsc::unbounded_channel();
+            tokio::spawn(Self::batch_exists(
+                self.client.clone(),
+                self.api_auth.clone(),
+                self.supports_batching.clone(),
+                rx,
+            ));
+            tx
+        })
+    }
+
+    async fn batch_exists(
+        client: APIClient,
+        api_auth: APIAuth,
+        supports_batching: Arc<AtomicBool>,
+        mut requests: mpsc::UnboundedReceiver<ExistsRequest>,
+    ) {
+        while let Some(first) = requests.recv().await {
+            // Wait a moment to gather up any checks that come in at the same time
+            let mut batch = vec![first];
+            let window = tokio::time::sleep(EXISTS_BATCH_WINDOW);
+            tokio::pin!(window);
+            while batch.len() < MAX_EXISTS_BATCH_SIZE {
+                tokio::select! {
+                    request = requests.recv() => match request {
+                        Some(request) => batch.push(request),
+                        None => break,
+                    },
+                    _ = &mut window => break,
+                }
+            }
+
+            let hashes = batch
+                .iter()
+                .map(|(hash, _)| hash.clone())
+                .collect::<Vec<_>>();
+            let response = client
+                .artifacts_exist(
+                    &hashes,
+                    &api_auth.token,
+                    api_auth.team_id.as_deref(),
+                    api_auth.team_slug.as_deref(),
+                )
+                .await;
+            let results = match response {
+                Ok(Some(results)) => Some(results),
+                Ok(None) => {
+                    debug!("remote cache doesn't support checking artifacts in bulk");
+                    supports_batching.store(false, Ordering::Relaxed);
+                    None
+                }
+                Err(err) => {
+                    debug!("failed to check artifacts in bulk: {err}");
+                    None
+                }
+            };
+
+            for (hash, tx) in batch {
+                let response = results.as_ref().map(|results| match results.get(&hash) {
+                    Some(ArtifactQueryResult::Found(info)) => Some(CacheHitMetadata {
+                        source: CacheSource::Remote,
+                        time_saved: info.task_duration_ms,
+                    }),
+                    _ => None,
+                });
+                // The check might have been cancelled, that's fine
+                let _ = tx.send(response);
+            }
+        }
+    }
+
+    #[tracing::instrument(skip_all)]
+    async fn exists_single(&self, hash: &str) -> Result<Option<CacheHitMetadata>, CacheError> {
         let Some(response) = self
             .client
             .artifact_exists(
@@ -177,6 +312,7 @@ impl HTTPCache {
         };
 
         let duration = Self::get_duration_from_response(&response)?;
+        let url = response.url().to_string();
 
         let body = if let Some(signer_verifier) = &self.signer_verifier {
             let expected_tag = response
@@ -213,6 +349,10 @@ impl HTTPCache {
 
         let files = Self::restore_tar(&self.repo_root, &body)?;
 
+        if let Some(audit_log) = &self.audit_log {
+            audit_log.record(AuditAction::Download, self.team(), hash, body.len(), url);
+        }
+
         self.log_fetch(analytics::CacheEvent::Hit, hash, duration);
         Ok(Some((
             CacheHitMetadata {
@@ -235,6 +375,8 @@ impl HTTPCache {
 
 #[cfg(test)]
 mod test {
+    use std::sync::atomic::Ordering;
+
     use anyhow::Result;
     use futures::future::try_join_all;
     use tempfile::tempdir;
@@ -267,6 +409,57 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_exists_batch() -> Result<()> {
+        let port = port_scanner::request_open_port().unwrap();
+        let handle = tokio::spawn(start_test_server(port));
+        let test_cases = get_test_cases();
+
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPathBuf::try_from(repo_root.path())?;
+        let api_client = APIClient::new(format!("http://localhost:{}", port), 200, "2.0.0", true)?;
+        let api_auth = APIAuth {
+            team_id: Some("my-team".to_string()),
+            token: "my-token".to_string(),
+            team_slug: None,
+        };
+        let cache = HTTPCache::new(
+            api_client,
+            &CacheOpts::default(),
+            repo_root_path.to_owned(),
+            api_auth,
+            None,
+            None,
+        );
+
+        for test_case in &test_cases {
+            test_case.initialize(&repo_root_path)?;
+            let files: Vec<_> = test_case
+                .files
+                .iter()
+                .map(|f| f.path().to_owned())
+                .collect();
+            cache
+                .put(&repo_root_path, test_case.hash, &files, test_case.duration)
+                .await?;
+        }
+
+        let hits = try_join_all(
+            test_cases
+                .iter()
+                .map(|test_case| cache.exists(test_case.hash)),
+        )
+        .await?;
+        for (test_case, hit) in test_cases.iter().zip(hits) {
+            assert_eq!(hit.map(|hit| hit.time_saved), Some(test_case.duration));
+        }
+        assert!(cache.exists("this-hash-does-not-exist").await?.is_none());
+        assert!(cache.supports_batching.load(Ordering::Relaxed));
+
+        handle.abort();
+        Ok(())
+    }
+
     async fn round_trip_test(test_case: &TestCase, port: u16) -> Result<()> {
         let repo_root = tempdir()?;
         let repo_root_path = AbsoluteSystemPathBuf::try_from(repo_root.path())?;
@@ -292,6 +485,7 @@ mod test {
             repo_root_path.to_owned(),
             api_auth,
             Some(analytics_recorder),
+            None,
         );
 
         // Should be a cache miss at first
@@ -5,6 +5,8 @@
 
 /// A wrapper for the cache that uses a worker pool to perform cache operations
 mod async_cache;
+/// Local log of artifacts uploaded to and downloaded from the remote cache
+pub mod audit;
 /// The core cache creation and restoration logic.
 pub mod cache_archive;
 /// File system cache
@@ -22,10 +24,11 @@ mod test_cases;
 
 use std::{backtrace, backtrace::Backtrace};
 
-pub use async_cache::AsyncCache;
+pub use async_cache::{AsyncCache, ShutdownSummary};
 use camino::Utf8PathBuf;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use turbopath::AbsoluteSystemPathBuf;
 
 use crate::signature_authentication::SignatureError;
 
@@ -104,6 +107,11 @@ pub struct CacheOpts {
     pub remote_cache_read_only: bool,
     pub skip_remote: bool,
     pub skip_filesystem: bool,
+    /// Stop using the remote cache for the rest of the run once it starts
+    /// rate limiting us
+    pub fallback_on_rate_limit: bool,
+    /// File to append a record of every remote cache upload and download to
+    pub audit_log: Option<AbsoluteSystemPathBuf>,
     pub workers: u32,
     pub remote_cache_opts: Option<RemoteCacheOpts>,
 }
@@ -5,7 +5,9 @@ use turbopath::{AbsoluteSystemPath, AnchoredSystemPathBuf};
 use turborepo_analytics::AnalyticsSender;
 use turborepo_api_client::{APIAuth, APIClient};
 
-use crate::{fs::FSCache, http::HTTPCache, CacheError, CacheHitMetadata, CacheOpts};
+use crate::{
+    audit::AuditLog, fs::FSCache, http::HTTPCache, CacheError, CacheHitMetadata, CacheOpts,
+};
 
 pub struct CacheMultiplexer {
     // We use an `AtomicBool` instead of removing the cache because that would require
@@ -17,6 +19,7 @@ pub struct CacheMultiplexer {
     // being read-only
     should_print_skipping_remote_put: AtomicBool,
     remote_cache_read_only: bool,
+    fallback_on_rate_limit: bool,
     fs: Option<FSCache>,
     http: Option<HTTPCache>,
 }
@@ -29,6 +32,7 @@ impl CacheMultiplexer {
         api_client: APIClient,
         api_auth: Option<APIAuth>,
         analytics_recorder: Option<AnalyticsSender>,
+        audit_log: Option<AuditLog>,
     ) -> Result<Self, CacheError> {
         let use_fs_cache = !opts.skip_filesystem;
         let use_http_cache = !opts.skip_remote;
@@ -60,6 +64,7 @@ impl CacheMultiplexer {
                     repo_root.to_owned(),
                     api_auth,
                     analytics_recorder.clone(),
+                    audit_log,
                 )
             });
 
@@ -67,6 +72,7 @@ impl CacheMultiplexer {
             should_print_skipping_remote_put: AtomicBool::new(true),
             should_use_http_cache: AtomicBool::new(http_cache.is_some()),
             remote_cache_read_only: opts.remote_cache_read_only,
+            fallback_on_rate_limit: opts.fallback_on_rate_limit,
             fs: fs_cache,
             http: http_cache,
         })
@@ -82,6 +88,27 @@ impl CacheMultiplexer {
         }
     }
 
+    // Returns true if the error means we should stop using the http cache
+    // because it is rate limiting us
+    fn fallback_if_rate_limited(&self, err: &CacheError) -> bool {
+        let is_rate_limited = matches!(
+            err,
+            CacheError::ApiClientError(box turborepo_api_client::Error::RateLimited { .. }, ..)
+        );
+        if !is_rate_limited || !self.fallback_on_rate_limit {
+            return false;
+        }
+
+        // Warn once per build, not per task
+        if self.should_use_http_cache.swap(false, Ordering::Relaxed) {
+            warn!(
+                "remote cache is rate limiting requests, only using the local cache for the rest \
+                 of this run"
+            );
+        }
+        true
+    }
+
     #[tracing::instrument(skip_all)]
     pub async fn put(
         &self,
@@ -128,6 +155,7 @@ impl CacheMultiplexer {
                 self.should_use_http_cache.store(false, Ordering::Relaxed);
                 Ok(())
             }
+            Some(Err(e)) if self.fallback_if_rate_limited(&e) => Ok(()),
             Some(Err(e)) => Err(e),
             None | Some(Ok(())) => Ok(()),
         }
@@ -146,9 +174,11 @@ impl CacheMultiplexer {
         }
 
         if let Some(http) = self.get_http_cache() {
-            if let Ok(Some((CacheHitMetadata { source, time_saved }, files))) =
-                http.fetch(key).await
-            {
+            let response = http.fetch(key).await;
+            if let Err(err) = &response {
+                self.fallback_if_rate_limited(err);
+            }
+            if let Ok(Some((CacheHitMetadata { source, time_saved }, files))) = response {
                 // Store this into fs cache. We can ignore errors here because we know
                 // we have previously successfully stored in HTTP cache, and so the overall
                 // result is a success at fetching. Storing in lower-priority caches is an
@@ -182,7 +212,10 @@ impl CacheMultiplexer {
                     return cache_hit;
                 }
                 Ok(None) => {}
-                Err(err) => debug!("failed to check http cache: {:?}", err),
+                Err(err) => {
+                    self.fallback_if_rate_limited(&err);
+                    debug!("failed to check http cache: {:?}", err)
+                }
             }
         }
 
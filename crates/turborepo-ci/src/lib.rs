@@ -91,6 +91,13 @@ impl Vendor {
     pub fn get_constant() -> Option<&'static str> {
         Self::infer().map(|v| v.constant)
     }
+
+    /// Looks up a vendor by its constant, regardless of the environment
+    pub fn from_constant(constant: &str) -> Option<&'static Vendor> {
+        get_vendors()
+            .iter()
+            .find(|vendor| vendor.constant == constant)
+    }
 }
 
 #[cfg(test)]
@@ -204,4 +211,18 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_teamcity_group_names_are_escaped() {
+        let behavior = get_vendor("TeamCity").behavior.unwrap();
+        assert_eq!(
+            (behavior.group_prefix)("web#build ['dev']"),
+            "##teamcity[blockOpened name='web#build |[|'dev|'|]']\n"
+        );
+        assert_eq!(
+            (behavior.error_group_suffix.unwrap())("web#build"),
+            "##teamcity[blockClosed name='web#build']\n##teamcity[buildProblem \
+             description='web#build failed']\n"
+        );
+    }
 }
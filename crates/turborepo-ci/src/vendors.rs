@@ -145,7 +145,17 @@ pub(crate) fn get_vendors() -> &'static [Vendor] {
                     sha_env_var: None,
                     branch_env_var: None,
                     username_env_var: None,
-                    behavior: None,
+                    behavior: Some(
+                        VendorBehavior::new(
+                            |group_name| format!("--- {group_name}\n"),
+                            |_| String::new(),
+                        )
+                        // Expands the section of the failed task
+                        .with_error(
+                            |group_name| format!("--- {group_name}\n"),
+                            |_| String::from("^^^ +++\n"),
+                        ),
+                    ),
                 },
                 Vendor {
                     name: "CircleCI",
@@ -552,10 +562,38 @@ pub(crate) fn get_vendors() -> &'static [Vendor] {
                     sha_env_var: None,
                     branch_env_var: None,
                     username_env_var: None,
-                    behavior: Some(VendorBehavior::new(
-                        |group_name| format!("##teamcity[blockOpened name='{group_name}']"),
-                        |group_name| format!("##teamcity[blockClosed name='{group_name}']"),
-                    )),
+                    behavior: Some(
+                        VendorBehavior::new(
+                            |group_name| {
+                                format!(
+                                    "##teamcity[blockOpened name='{}']\n",
+                                    teamcity_escape(group_name)
+                                )
+                            },
+                            |group_name| {
+                                format!(
+                                    "##teamcity[blockClosed name='{}']\n",
+                                    teamcity_escape(group_name)
+                                )
+                            },
+                        )
+                        .with_error(
+                            |group_name| {
+                                format!(
+                                    "##teamcity[blockOpened name='{}']\n",
+                                    teamcity_escape(group_name)
+                                )
+                            },
+                            |group_name| {
+                                let group_name = teamcity_escape(group_name);
+                                format!(
+                                    "##teamcity[blockClosed \
+                                     name='{group_name}']\n##teamcity[buildProblem \
+                                     description='{group_name} failed']\n"
+                                )
+                            },
+                        ),
+                    ),
                 },
                 Vendor {
                     name: "Travis CI",
@@ -646,3 +684,20 @@ pub(crate) fn get_vendors() -> &'static [Vendor] {
         })
         .as_slice()
 }
+
+// Values of TeamCity service messages escape special characters with `|`
+fn teamcity_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '|' => escaped.push_str("||"),
+            '\'' => escaped.push_str("|'"),
+            '[' => escaped.push_str("|["),
+            ']' => escaped.push_str("|]"),
+            '\n' => escaped.push_str("|n"),
+            '\r' => escaped.push_str("|r"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+
+use crate::Error;
+
+// Parses the contents of a .env file. Supports comments, an optional `export`
+// prefix, single quoted values that are taken literally and double quoted
+// values with escape sequences. Quoted values can span multiple lines.
+// Variable references are not expanded.
+pub(crate) fn parse(contents: &str) -> Result<HashMap<String, String>, Error> {
+    let mut vars = HashMap::new();
+    let mut lines = contents.lines().enumerate();
+    while let Some((index, line)) = lines.next() {
+        let line_number = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").map_or(line, str::trim_start);
+        let (key, value) = line
+            .split_once('=')
+            .ok_or(Error::DotEnvSyntax(line_number))?;
+        let key = key.trim_end();
+        if key.is_empty()
+            || !key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+        {
+            return Err(Error::DotEnvSyntax(line_number));
+        }
+
+        let value = value.trim_start();
+        let value = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let mut raw = value[1..].to_string();
+                let end = loop {
+                    if let Some(end) = closing_quote(&raw, quote) {
+                        break end;
+                    }
+                    let (_, next) = lines.next().ok_or(Error::DotEnvSyntax(line_number))?;
+                    raw.push('\n');
+                    raw.push_str(next);
+                };
+                let rest = raw[end + 1..].trim_start();
+                if !rest.is_empty() && !rest.starts_with('#') {
+                    return Err(Error::DotEnvSyntax(line_number));
+                }
+                raw.truncate(end);
+                match quote {
+                    '"' => unescape(&raw),
+                    _ => raw,
+                }
+            }
+            // Unquoted values end at a comment
+            _ => match value.find(" #") {
+                Some(comment) => value[..comment].trim_end().to_string(),
+                None => value.to_string(),
+            },
+        };
+        vars.insert(key.to_string(), value);
+    }
+    Ok(vars)
+}
+
+fn closing_quote(value: &str, quote: char) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in value.char_indices() {
+        match c {
+            '\\' if quote == '"' && !escaped => escaped = true,
+            c if c == quote && !escaped => return Some(i),
+            _ => escaped = false,
+        }
+    }
+    None
+}
+
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some('t') => unescaped.push('\t'),
+            Some(c @ ('"' | '\\')) => unescaped.push(c),
+            Some(c) => {
+                unescaped.push('\\');
+                unescaped.push(c);
+            }
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+#[cfg(test)]
+mod tests {
+    use test_case::test_case;
+
+    use super::parse;
+    use crate::Error;
+
+    #[test_case("FOO=bar", "bar" ; "unquoted")]
+    #[test_case("  FOO = bar  ", "bar" ; "whitespace")]
+    #[test_case("export FOO=bar", "bar" ; "export")]
+    #[test_case("FOO=", "" ; "empty")]
+    #[test_case("FOO=bar # comment", "bar" ; "comment")]
+    #[test_case("FOO=bar#baz", "bar#baz" ; "hash in value")]
+    #[test_case("FOO=a=b", "a=b" ; "equals in value")]
+    #[test_case("FOO='a \\n # b'", "a \\n # b" ; "single quoted")]
+    #[test_case(r#"FOO="a\n\"b\" \\c""#, "a\n\"b\" \\c" ; "double quoted")]
+    #[test_case("FOO=\"a # b\" # comment", "a # b" ; "quoted with comment")]
+    #[test_case("FOO=\"line 1\nline 2\"", "line 1\nline 2" ; "multiline")]
+    fn test_parse_value(contents: &str, expected: &str) {
+        let vars = parse(contents).unwrap();
+        assert_eq!(vars.get("FOO").map(String::as_str), Some(expected));
+    }
+
+    #[test]
+    fn test_parse_file() {
+        let vars = parse("# comment\n\nFOO=1\nBAR=2\nFOO=3\n").unwrap();
+        assert_eq!(vars.len(), 2);
+        assert_eq!(vars["FOO"], "3");
+        assert_eq!(vars["BAR"], "2");
+    }
+
+    #[test_case("FOO" ; "missing equals")]
+    #[test_case("=bar" ; "missing key")]
+    #[test_case("FOO BAR=baz" ; "invalid key")]
+    #[test_case("FOO=\"bar" ; "unterminated quote")]
+    #[test_case("FOO='bar' baz" ; "text after quote")]
+    fn test_parse_error(contents: &str) {
+        assert!(matches!(parse(contents), Err(Error::DotEnvSyntax(1))));
+    }
+}
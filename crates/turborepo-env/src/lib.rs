@@ -1,5 +1,7 @@
 #![deny(clippy::all)]
 
+mod dot_env;
+
 use std::{
     collections::HashMap,
     env,
@@ -34,6 +36,8 @@ impl std::fmt::Display for ResolvedEnvMode {
 pub enum Error {
     #[error("Failed to parse regex: {0}")]
     Regex(#[from] regex::Error),
+    #[error("invalid .env syntax on line {0}")]
+    DotEnvSyntax(usize),
 }
 
 // TODO: Consider using immutable data structures here
@@ -157,6 +161,19 @@ impl EnvironmentVariableMap {
         }
     }
 
+    // Takes another EnvironmentVariableMap and adds the keys that don't exist
+    // in `self` yet.
+    pub fn union_missing(&mut self, another: &EnvironmentVariableMap) {
+        for (key, value) in &another.0 {
+            self.0.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+    }
+
+    // Parses the contents of a .env file into an EnvironmentVariableMap
+    pub fn from_dot_env(contents: &str) -> Result<Self, Error> {
+        Ok(EnvironmentVariableMap(dot_env::parse(contents)?))
+    }
+
     // Takes another EnvironmentVariableMap and removes matching keys
     // from `self`
     pub fn difference(&mut self, another: &EnvironmentVariableMap) {
@@ -5,7 +5,7 @@ use thiserror::Error;
 use turborepo_repository::package_graph;
 
 use crate::{
-    commands::{bin, generate, prune},
+    commands::{bin, clean, generate, init, link, prune, telemetry},
     daemon::DaemonError,
     rewrite_json::RewriteError,
     run,
@@ -14,38 +14,84 @@ use crate::{
 #[derive(Debug, Error, Diagnostic)]
 pub enum Error {
     #[error("No command specified")]
+    #[diagnostic(code(TURBO_CLI_008))]
     NoCommand(#[backtrace] backtrace::Backtrace),
     #[error("{0}")]
+    #[diagnostic(code(TURBO_CLI_009))]
     Bin(#[from] bin::Error, #[backtrace] backtrace::Backtrace),
     #[error(transparent)]
+    #[diagnostic(transparent)]
+    Clean(#[from] clean::Error),
+    #[error(transparent)]
+    #[diagnostic(code(TURBO_CLI_010))]
     Path(#[from] turbopath::PathError),
     #[error("at least one task must be specified")]
+    #[diagnostic(code(TURBO_CLI_011))]
     NoTasks(#[backtrace] backtrace::Backtrace),
     #[error(transparent)]
     #[diagnostic(transparent)]
     Config(#[from] crate::config::Error),
     #[error(transparent)]
+    #[diagnostic(code(TURBO_CLI_012))]
     ChromeTracing(#[from] crate::tracing::Error),
     #[error(transparent)]
+    #[diagnostic(transparent)]
     BuildPackageGraph(#[from] package_graph::builder::Error),
     #[error(transparent)]
+    #[diagnostic(code(TURBO_CLI_014))]
     Rewrite(#[from] RewriteError),
     #[error(transparent)]
+    #[diagnostic(code(TURBO_CLI_015))]
     Auth(#[from] turborepo_auth::Error),
     #[error(transparent)]
+    #[diagnostic(code(TURBO_CLI_016))]
     Daemon(#[from] DaemonError),
     #[error(transparent)]
+    #[diagnostic(code(TURBO_CLI_017))]
     Generate(#[from] generate::Error),
     #[error(transparent)]
+    #[diagnostic(code(TURBO_CLI_018))]
+    Init(#[from] init::Error),
+    #[error(transparent)]
     #[diagnostic(transparent)]
     Prune(#[from] prune::Error),
     #[error(transparent)]
+    #[diagnostic(transparent)]
     PackageJson(#[from] turborepo_repository::package_json::Error),
     #[error(transparent)]
+    #[diagnostic(transparent)]
     PackageManager(#[from] turborepo_repository::package_manager::Error),
     #[error(transparent)]
     #[diagnostic(transparent)]
     Run(#[from] run::Error),
     #[error(transparent)]
+    #[diagnostic(code(TURBO_CLI_021))]
     SerdeJson(#[from] serde_json::Error),
+    #[error("`turbo {command}` requires network access and can't be used with --offline")]
+    #[diagnostic(code(TURBO_CLI_022))]
+    Offline { command: &'static str },
+    #[error("--remote-only can't be used with --offline")]
+    #[diagnostic(code(TURBO_CLI_023))]
+    RemoteOnlyOffline,
+    #[error("--interactive needs a terminal to pick tasks and packages in")]
+    #[diagnostic(code(TURBO_CLI_024))]
+    InteractiveWithoutTerminal,
+    #[error("failed to pick tasks and packages: {0}")]
+    #[diagnostic(code(TURBO_CLI_025))]
+    PickerCanceled(#[source] std::io::Error),
+    #[error("`turbo run` doesn't support --json, use --dry=json or --summarize instead")]
+    #[diagnostic(code(TURBO_CLI_026))]
+    JsonRun,
+    #[error("`turbo {command}` doesn't support --json")]
+    #[diagnostic(code(TURBO_CLI_027))]
+    JsonUnsupported { command: &'static str },
+    #[error(transparent)]
+    #[diagnostic(code(TURBO_CLI_028))]
+    Link(#[from] link::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Telemetry(#[from] telemetry::Error),
+    #[error("no tasks were picked, so there is nothing to run")]
+    #[diagnostic(code(TURBO_CLI_029))]
+    NothingPicked,
 }
@@ -1,11 +1,11 @@
-use std::{backtrace, backtrace::Backtrace, env, fmt, fmt::Display, io, mem, process};
+use std::{backtrace, backtrace::Backtrace, env, fmt, fmt::Display, mem, process, str::FromStr};
 
 use camino::{Utf8Path, Utf8PathBuf};
 use clap::{
     builder::NonEmptyStringValueParser, ArgAction, ArgGroup, CommandFactory, Parser, Subcommand,
     ValueEnum,
 };
-use clap_complete::{generate, Shell};
+use clap_complete::Shell;
 pub use error::Error;
 use serde::{Deserialize, Serialize};
 use tracing::{debug, error};
@@ -24,9 +24,10 @@ use turborepo_ui::UI;
 
 use crate::{
     commands::{
-        bin, daemon, generate, info, link, login, logout, prune, run, telemetry, unlink,
-        CommandBase,
+        bin, clean, completion, daemon, doctor, generate, info, init, link, login, logout, picker,
+        prune, run, telemetry, unlink, CommandBase,
     },
+    config::ConfigurationOptions,
     get_version,
     shim::TurboState,
     tracing::TurboSubscriber,
@@ -75,6 +76,91 @@ impl Display for OutputLogsMode {
     }
 }
 
+/// The value of `--output-logs`: a mode for all tasks, or modes for the tasks
+/// matching a pattern, e.g. `web#build:full,*:errors-only`.
+///
+/// Patterns are `<package>#<task>` or `<task>`, where either part can be `*`.
+/// The first entry that matches a task is used. Tasks that don't match any
+/// entry keep the `outputMode` of their task definition.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct OutputLogsOverrides(Vec<(Option<String>, OutputLogsMode)>);
+
+impl OutputLogsOverrides {
+    pub fn mode_for(&self, package: &str, task: &str) -> Option<OutputLogsMode> {
+        self.0
+            .iter()
+            .find(|(pattern, _)| {
+                pattern
+                    .as_deref()
+                    .map_or(true, |pattern| task_matches(pattern, package, task))
+            })
+            .map(|(_, mode)| *mode)
+    }
+
+    // Patterns contain the names of packages and tasks
+    fn has_patterns(&self) -> bool {
+        self.0.iter().any(|(pattern, _)| pattern.is_some())
+    }
+}
+
+fn task_matches(pattern: &str, package: &str, task: &str) -> bool {
+    let (package_pattern, task_pattern) = pattern.split_once('#').unwrap_or(("*", pattern));
+    (package_pattern == "*" || package_pattern == package)
+        && (task_pattern == "*" || task_pattern == task)
+}
+
+impl From<OutputLogsMode> for OutputLogsOverrides {
+    fn from(mode: OutputLogsMode) -> Self {
+        Self(vec![(None, mode)])
+    }
+}
+
+impl FromStr for OutputLogsOverrides {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_mode = |mode: &str| <OutputLogsMode as ValueEnum>::from_str(mode, false);
+        s.split(',')
+            .map(|entry| {
+                if let Ok(mode) = parse_mode(entry) {
+                    return Ok((None, mode));
+                }
+                // Task names can contain colons, the mode can't
+                match entry.rsplit_once(':') {
+                    Some((pattern, mode)) if !pattern.is_empty() => parse_mode(mode)
+                        .map(|mode| (Some(pattern.to_string()), mode))
+                        .map_err(|_| {
+                            format!(
+                                "invalid mode \"{mode}\" for \"{pattern}\", expected one of full, \
+                                 none, hash-only, new-only, errors-only"
+                            )
+                        }),
+                    _ => Err(format!(
+                        "invalid value \"{entry}\", expected a mode or \"<task>:<mode>\", e.g. \
+                         \"web#build:full\""
+                    )),
+                }
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+impl Display for OutputLogsOverrides {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (pattern, mode)) in self.0.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            match pattern {
+                Some(pattern) => write!(f, "{pattern}:{mode}")?,
+                None => write!(f, "{mode}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Serialize, ValueEnum)]
 pub enum LogOrder {
     #[serde(rename = "auto")]
@@ -134,6 +220,23 @@ impl fmt::Display for EnvMode {
     }
 }
 
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Text,
+    Json,
+}
+
+impl fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LogFormat::Text => "text",
+            LogFormat::Json => "json",
+        })
+    }
+}
+
 #[derive(Parser, Clone, Default, Debug, PartialEq, Serialize)]
 #[clap(author, about = "The build system that makes ship happen", long_about = None)]
 #[clap(disable_help_subcommand = true)]
@@ -156,6 +259,9 @@ pub struct Args {
     /// Override the endpoint for API calls
     #[clap(long, global = true, value_parser)]
     pub api: Option<String>,
+    /// Use the credentials stored under this name in the global config
+    #[clap(long, value_name = "NAME", global = true, value_parser)]
+    pub auth_profile: Option<String>,
     /// Force color usage in the terminal
     #[clap(long, global = true)]
     pub color: bool,
@@ -168,19 +274,40 @@ pub struct Args {
     /// Specify a file to save a pprof heap profile
     #[clap(long, global = true, value_parser)]
     pub heap: Option<String>,
+    /// Print the result of the command as JSON instead of text
+    #[clap(long, global = true)]
+    pub json: bool,
     /// Override the login endpoint
     #[clap(long, global = true, value_parser)]
     pub login: Option<String>,
+    /// Format of the messages that turbo logs. Use "json" to log structured
+    /// events instead. Output of tasks isn't affected
+    #[clap(long, global = true, value_enum, value_name = "FORMAT", default_value_t = LogFormat::Text)]
+    pub log_format: LogFormat,
     /// Suppress color usage in the terminal
     #[clap(long, global = true)]
     pub no_color: bool,
+    /// Disable all network access. Commands that require the network will
+    /// fail
+    #[clap(long, global = true)]
+    pub offline: bool,
     /// When enabled, turbo will precede HTTP requests with an OPTIONS request
     /// for authorization
     #[clap(long, global = true)]
     pub preflight: bool,
-    /// Set a timeout for all HTTP requests.
+    /// Only send preflight requests before these kinds of requests, a comma
+    /// separated list of reads, uploads and api
+    #[clap(long, value_name = "REQUESTS", global = true, value_delimiter = ',')]
+    pub preflight_requests: Option<Vec<String>>,
+    /// Set a timeout for all HTTP requests other than artifact uploads.
     #[clap(long, value_name = "TIMEOUT", global = true, value_parser)]
     pub remote_cache_timeout: Option<u64>,
+    /// Set a timeout for establishing HTTP connections.
+    #[clap(long, value_name = "TIMEOUT", global = true, value_parser)]
+    pub remote_cache_connect_timeout: Option<u64>,
+    /// Set a timeout for uploading artifacts to the remote cache.
+    #[clap(long, value_name = "TIMEOUT", global = true, value_parser)]
+    pub remote_cache_upload_timeout: Option<u64>,
     /// Set the team slug for API calls
     #[clap(long, global = true, value_parser)]
     pub team: Option<String>,
@@ -236,7 +363,7 @@ impl From<Verbosity> for u8 {
     }
 }
 
-#[derive(Subcommand, Copy, Clone, Debug, Serialize, PartialEq)]
+#[derive(Subcommand, Clone, Debug, Serialize, PartialEq)]
 #[serde(tag = "command")]
 pub enum DaemonCommand {
     /// Restarts the turbo daemon
@@ -244,11 +371,7 @@ pub enum DaemonCommand {
     /// Ensures that the turbo daemon is running
     Start,
     /// Reports the status of the turbo daemon
-    Status {
-        /// Pass --json to report status in JSON format
-        #[clap(long)]
-        json: bool,
-    },
+    Status,
     /// Stops the turbo daemon
     Stop,
     /// Stops the turbo daemon if it is already running, and removes any stale
@@ -260,19 +383,43 @@ pub enum DaemonCommand {
     },
     /// Shows the daemon logs
     Logs,
+    /// Changes which events the running daemon logs, without restarting it
+    LogLevel {
+        /// The filter, in the syntax of TURBO_LOG_VERBOSITY, e.g.
+        /// "turborepo_filewatch=trace". Use "info" to restore the default
+        filter: String,
+    },
 }
 
 #[derive(Subcommand, Copy, Clone, Debug, Serialize, PartialEq)]
 #[serde(tag = "command")]
 pub enum TelemetryCommand {
     /// Enables anonymous telemetry
-    Enable,
+    Enable {
+        /// Only enable this category of events
+        category: Option<TelemetryCategory>,
+    },
     /// Disables anonymous telemetry
-    Disable,
+    Disable {
+        /// Only disable this category of events, and keep collecting the
+        /// others
+        category: Option<TelemetryCategory>,
+    },
     /// Reports the status of telemetry
     Status,
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum TelemetryCategory {
+    /// Commands, arguments and the shape of the repository
+    Usage,
+    /// Errors that turbo recovered from
+    Errors,
+    /// Timing, hardware and hashing strategies
+    Performance,
+}
+
 #[derive(Copy, Clone, Debug, PartialEq, Serialize, ValueEnum)]
 pub enum LinkTarget {
     RemoteCache,
@@ -380,15 +527,20 @@ impl Args {
         track_usage!(tel, self.no_update_notifier, |val| val);
         track_usage!(tel, self.color, |val| val);
         track_usage!(tel, self.no_color, |val| val);
+        track_usage!(tel, self.offline, |val| val);
         track_usage!(tel, self.preflight, |val| val);
+        track_usage!(tel, &self.preflight_requests, Option::is_some);
         track_usage!(tel, &self.login, Option::is_some);
+        track_usage!(tel, self.log_format, |val| val == LogFormat::Json);
         track_usage!(tel, &self.cwd, Option::is_some);
         track_usage!(tel, &self.heap, Option::is_some);
+        track_usage!(tel, self.json, |val| val);
         track_usage!(tel, &self.cpu_profile, Option::is_some);
         track_usage!(tel, &self.team, Option::is_some);
         track_usage!(tel, &self.token, Option::is_some);
         track_usage!(tel, &self.trace, Option::is_some);
         track_usage!(tel, &self.api, Option::is_some);
+        track_usage!(tel, &self.auth_profile, Option::is_some);
 
         // track values
         if let Some(remote_cache_timeout) = self.remote_cache_timeout {
@@ -398,6 +550,20 @@ impl Args {
                 turborepo_telemetry::events::EventType::NonSensitive,
             );
         }
+        if let Some(remote_cache_connect_timeout) = self.remote_cache_connect_timeout {
+            tel.track_arg_value(
+                "remote-cache-connect-timeout",
+                remote_cache_connect_timeout,
+                turborepo_telemetry::events::EventType::NonSensitive,
+            );
+        }
+        if let Some(remote_cache_upload_timeout) = self.remote_cache_upload_timeout {
+            tel.track_arg_value(
+                "remote-cache-upload-timeout",
+                remote_cache_upload_timeout,
+                turborepo_telemetry::events::EventType::NonSensitive,
+            );
+        }
         if self.verbosity.v > 0 {
             tel.track_arg_value(
                 "v",
@@ -424,9 +590,27 @@ pub enum Command {
     // them as `{ "Bin": {} }` instead of as `"Bin"`.
     /// Get the path to the Turbo binary
     Bin {},
+    /// Remove task outputs, the local cache and .turbo state
+    Clean {
+        /// Only remove the outputs and .turbo directories of the packages
+        /// matching the filter
+        #[clap(short = 'F', long)]
+        filter: Vec<String>,
+        /// List what would be removed without removing anything
+        #[clap(long)]
+        dry_run: bool,
+        /// Override the filesystem cache directory.
+        #[clap(long, value_parser = path_non_empty)]
+        cache_dir: Option<Utf8PathBuf>,
+    },
     /// Generate the autocompletion script for the specified shell
     #[serde(skip)]
     Completion { shell: Shell },
+    /// Print the candidates for a value that depends on the workspace. Used by
+    /// the completion scripts
+    #[clap(name = "__complete", hide = true)]
+    #[serde(skip)]
+    Complete { kind: CompletionKind },
     /// Runs the Turborepo background daemon
     Daemon {
         /// Set the idle timeout for turbod
@@ -436,6 +620,8 @@ pub enum Command {
         #[serde(flatten)]
         command: Option<DaemonCommand>,
     },
+    /// Check the environment turbo runs in and suggest fixes
+    Doctor {},
     /// Generate a new app / package
     #[clap(aliases = ["g", "gen"])]
     Generate {
@@ -466,11 +652,15 @@ pub enum Command {
         command: Option<TelemetryCommand>,
     },
     #[clap(hide = true)]
-    Info {
-        workspace: Option<String>,
-        // We output turbo info as json. Currently just for internal testing
+    Info { workspace: Option<String> },
+    /// Generate a turbo.json for an existing repository
+    Init {
+        /// Overwrite an existing turbo.json
         #[clap(long)]
-        json: bool,
+        force: bool,
+        /// Do not create or modify .gitignore (default false)
+        #[clap(long)]
+        no_gitignore: bool,
     },
     /// Link your local directory to a Vercel organization and enable remote
     /// caching.
@@ -479,6 +669,16 @@ pub enum Command {
         #[clap(long)]
         no_gitignore: bool,
 
+        /// The Vercel scope (team slug or username) to link to, skipping the
+        /// scope prompt
+        #[clap(long)]
+        scope: Option<String>,
+
+        /// Answer yes to all prompts. Links to your personal account unless
+        /// --scope is given
+        #[clap(long, short = 'y')]
+        yes: bool,
+
         /// Specify what should be linked (default "remote cache")
         #[clap(long, value_enum, default_value_t = LinkTarget::RemoteCache)]
         target: LinkTarget,
@@ -511,6 +711,17 @@ pub enum Command {
         scope_arg: Option<Vec<String>>,
         #[clap(long)]
         docker: bool,
+        /// Only include dependencies that are required in production,
+        /// devDependencies are removed from the pruned output
+        #[clap(long)]
+        prod: bool,
+        /// Write a multi-stage Dockerfile for the pruned output
+        #[clap(long, requires = "docker")]
+        emit_dockerfile: bool,
+        /// Write the source commit SHA, branch, and dirty state of the
+        /// repository to .turbo-prune-meta.json in the output directory
+        #[clap(long)]
+        emit_metadata: bool,
         #[clap(long = "out-dir", default_value_t = String::from(prune::DEFAULT_OUTPUT_DIR), value_parser)]
         output_dir: String,
     },
@@ -534,6 +745,43 @@ pub enum Command {
     },
 }
 
+impl Command {
+    /// The name of the command if it has no JSON output, so `--json` is
+    /// rejected instead of being ignored.
+    fn without_json_output(&self) -> Option<&'static str> {
+        match self {
+            Command::Bin { .. }
+            | Command::Clean { .. }
+            | Command::Doctor { .. }
+            | Command::Info { .. }
+            | Command::Link { .. }
+            | Command::Prune { .. }
+            | Command::Telemetry { .. }
+            | Command::Daemon {
+                command: Some(DaemonCommand::Status),
+                ..
+            } => None,
+            Command::Completion { .. } => Some("completion"),
+            Command::Complete { .. } => Some("__complete"),
+            Command::Daemon { command, .. } => Some(match command {
+                Some(DaemonCommand::Restart) => "daemon restart",
+                Some(DaemonCommand::Start) => "daemon start",
+                Some(DaemonCommand::Stop) => "daemon stop",
+                Some(DaemonCommand::Clean { .. }) => "daemon clean",
+                Some(DaemonCommand::Logs) => "daemon logs",
+                Some(DaemonCommand::LogLevel { .. }) => "daemon log-level",
+                Some(DaemonCommand::Status) | None => "daemon",
+            }),
+            Command::Generate { .. } => Some("generate"),
+            Command::Init { .. } => Some("init"),
+            Command::Login { .. } => Some("login"),
+            Command::Logout { .. } => Some("logout"),
+            Command::Run(_) => Some("run"),
+            Command::Unlink { .. } => Some("unlink"),
+        }
+    }
+}
+
 #[derive(Parser, Clone, Debug, Default, Serialize, PartialEq)]
 pub struct GenerateWorkspaceArgs {
     /// Name for the new workspace
@@ -629,6 +877,11 @@ pub struct RunArgs {
     /// Set the number of concurrent cache operations (default 10)
     #[clap(long, default_value_t = DEFAULT_NUM_WORKERS)]
     pub cache_workers: u32,
+    /// How long to wait for pending cache writes to finish when the run is
+    /// interrupted. Writes that are still running after that are cancelled,
+    /// use 0 to cancel them right away (default 30)
+    #[clap(long, env = "TURBO_CACHE_SHUTDOWN_TIMEOUT", value_name = "SECONDS")]
+    pub cache_shutdown_timeout: Option<u64>,
     /// Limit the concurrency of task execution. Use 1 for serial (i.e.
     /// one-at-a-time) execution.
     #[clap(long)]
@@ -727,9 +980,10 @@ pub struct RunArgs {
     /// all output. Use "hash-only" to show only turbo-computed
     /// task hashes. Use "new-only" to show only new output with
     /// only hashes for cached tasks. Use "none" to hide process
-    /// output. (default full)
-    #[clap(long, value_enum)]
-    pub output_logs: Option<OutputLogsMode>,
+    /// output. (default full) Prefix a mode with a task to only use it
+    /// for the matching tasks, e.g. "web#build:full,*:errors-only"
+    #[clap(long, value_parser = OutputLogsOverrides::from_str)]
+    pub output_logs: Option<OutputLogsOverrides>,
 
     /// Set type of task output order. Use "stream" to show
     /// output as soon as it is available. Use "grouped" to
@@ -766,6 +1020,14 @@ pub struct RunArgs {
     /// Generate a summary of the turbo run
     #[clap(long, env = "TURBO_RUN_SUMMARY", default_missing_value = "true")]
     pub summarize: Option<Option<bool>>,
+    /// Write a self-contained HTML report of the run, with a timeline of its
+    /// tasks, their cache statuses and the logs of failed tasks, to a file
+    #[clap(long, value_name = "FILE", value_parser = NonEmptyStringValueParser::new())]
+    pub report: Option<String>,
+    /// Write the results of the run to a file in the JUnit XML format, with a
+    /// test case per task, for CI systems that display test results
+    #[clap(long, value_name = "FILE", value_parser = NonEmptyStringValueParser::new())]
+    pub junit: Option<String>,
 
     /// Use "none" to remove prefixes from task logs. Use "task" to get task id
     /// prefixing. Use "auto" to let turbo decide how to prefix the logs
@@ -776,6 +1038,54 @@ pub struct RunArgs {
     /// auto)
     #[clap(long, value_enum, default_value_t = LogPrefix::Auto)]
     pub log_prefix: LogPrefix,
+    /// Additionally send the raw output of every task to a sink, regardless
+    /// of --log-order and --output-logs. Use "file:<dir>" to write a log file
+    /// per task into a directory, "unix:<path>" to stream the output as JSON
+    /// lines to a unix socket, or an http(s) URL to upload the log of each
+    /// task once it finished. Can be passed multiple times.
+    #[clap(long, value_name = "SINK")]
+    pub log_sink: Vec<String>,
+    /// Forward stdin to a persistent task, so interactive tools keep working
+    /// while several tasks run. Pass a task, e.g. "web#dev", to attach to it
+    /// once it starts. To switch tasks at runtime, type Ctrl-] followed by a
+    /// task and press enter.
+    #[clap(long, value_name = "TASK", num_args = 0..=1, default_missing_value = "")]
+    pub attach: Option<String>,
+    /// Check for files that tasks write outside of their declared outputs,
+    /// which silently don't get cached. Use "warn" to list them, or "error"
+    /// to fail the task.
+    #[clap(long, value_enum, value_name = "MODE")]
+    pub undeclared_outputs: Option<UndeclaredOutputsMode>,
+    /// Print the progress of the run whenever no output was printed for this
+    /// many seconds, so that CI systems don't stop runs for being inactive
+    #[clap(long, env = "TURBO_HEARTBEAT", value_name = "SECONDS", value_parser = clap::value_parser!(u64).range(1..))]
+    pub heartbeat: Option<u64>,
+    /// Show a status line with the progress of the run below the task logs.
+    /// Only shown when the output is a terminal
+    #[clap(long)]
+    pub status_bar: bool,
+    /// Pick the tasks and packages to run from searchable lists instead of
+    /// passing them as arguments
+    #[clap(long, conflicts_with = "tasks")]
+    pub interactive: bool,
+    /// Format the output for this CI system, regardless of the detected
+    /// environment. The logs of each task are folded into a section. "github"
+    /// also annotates errors in the output of failed tasks and writes a job
+    /// summary
+    #[clap(long, value_enum, value_name = "VENDOR")]
+    pub ci: Option<CiVendor>,
+    /// Don't use the package manager's lockfile. Dependencies are instead
+    /// hashed using the versions installed in node_modules
+    #[clap(long)]
+    pub ignore_lockfile: bool,
+    /// The signal that prints the state of the run to stderr without stopping
+    /// it, e.g. to debug a run that hangs. Not supported on Windows
+    #[clap(long, value_enum, value_name = "SIGNAL", default_value_t = DebugSignal::Quit)]
+    pub debug_signal: DebugSignal,
+    /// Skip the tasks that completed in the last interrupted or failed run, as
+    /// long as their inputs didn't change, even if they aren't cacheable
+    #[clap(long)]
+    pub resume: bool,
 
     // NOTE: The following two are hidden because clap displays them in the help text incorrectly:
     // > Usage: turbo [OPTIONS] [TASKS]... [-- <FORWARDED_ARGS>...] [COMMAND]
@@ -787,6 +1097,12 @@ pub struct RunArgs {
     // Pass a string to enable posting Run Summaries to Vercel
     #[clap(long, hide = true)]
     pub experimental_space_id: Option<String>,
+
+    // Dispatch tasks that miss the cache to remote worker agents instead of
+    // running them locally. Can be passed multiple times to spread tasks over
+    // several workers. Workers that aren't on this machine must use https.
+    #[clap(long, hide = true, value_name = "URL")]
+    pub experimental_remote_worker: Vec<String>,
 }
 
 impl RunArgs {
@@ -836,13 +1152,38 @@ impl RunArgs {
         track_usage!(telemetry, &self.pkg_inference_root, Option::is_some);
         track_usage!(telemetry, &self.anon_profile, Option::is_some);
         track_usage!(telemetry, &self.summarize, Option::is_some);
+        track_usage!(telemetry, &self.report, Option::is_some);
+        track_usage!(telemetry, &self.junit, Option::is_some);
+        track_usage!(telemetry, &self.heartbeat, Option::is_some);
+        track_usage!(telemetry, &self.cache_shutdown_timeout, Option::is_some);
+        track_usage!(telemetry, self.status_bar, |val| val);
+        track_usage!(telemetry, self.interactive, |val| val);
+        track_usage!(telemetry, self.ignore_lockfile, |val| val);
+        track_usage!(telemetry, self.resume, |val| val);
         track_usage!(telemetry, &self.experimental_space_id, Option::is_some);
+        track_usage!(telemetry, &self.attach, Option::is_some);
 
         // track values
         if let Some(dry_run) = &self.dry_run {
             telemetry.track_arg_value("dry-run", dry_run, EventType::NonSensitive);
         }
 
+        if let Some(undeclared_outputs) = &self.undeclared_outputs {
+            telemetry.track_arg_value(
+                "undeclared-outputs",
+                undeclared_outputs,
+                EventType::NonSensitive,
+            );
+        }
+
+        if let Some(ci) = &self.ci {
+            telemetry.track_arg_value("ci", ci, EventType::NonSensitive);
+        }
+
+        if self.debug_signal != DebugSignal::Quit {
+            telemetry.track_arg_value("debug-signal", self.debug_signal, EventType::NonSensitive);
+        }
+
         if self.cache_workers != DEFAULT_NUM_WORKERS {
             telemetry.track_arg_value("cache-workers", self.cache_workers, EventType::NonSensitive);
         }
@@ -866,7 +1207,11 @@ impl RunArgs {
         }
 
         if let Some(output_logs) = &self.output_logs {
-            telemetry.track_arg_value("output-logs", output_logs, EventType::NonSensitive);
+            let event_type = match output_logs.has_patterns() {
+                true => EventType::Sensitive,
+                false => EventType::NonSensitive,
+            };
+            telemetry.track_arg_value("output-logs", output_logs, event_type);
         }
 
         if self.log_order != LogOrder::default() {
@@ -889,6 +1234,22 @@ impl RunArgs {
         if !self.ignore.is_empty() {
             telemetry.track_arg_value("ignore:length", self.ignore.len(), EventType::NonSensitive);
         }
+
+        if !self.log_sink.is_empty() {
+            telemetry.track_arg_value(
+                "log-sink:length",
+                self.log_sink.len(),
+                EventType::NonSensitive,
+            );
+        }
+
+        if !self.experimental_remote_worker.is_empty() {
+            telemetry.track_arg_value(
+                "experimental-remote-worker:length",
+                self.experimental_remote_worker.len(),
+                EventType::NonSensitive,
+            );
+        }
     }
 }
 
@@ -918,6 +1279,81 @@ impl Display for LogPrefix {
     }
 }
 
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Serialize)]
+pub enum UndeclaredOutputsMode {
+    #[serde(rename = "warn")]
+    Warn,
+    #[serde(rename = "error")]
+    Error,
+}
+
+impl Display for UndeclaredOutputsMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UndeclaredOutputsMode::Warn => write!(f, "warn"),
+            UndeclaredOutputsMode::Error => write!(f, "error"),
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum CompletionKind {
+    Packages,
+    Tasks,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Serialize)]
+pub enum CiVendor {
+    #[serde(rename = "github")]
+    Github,
+    #[serde(rename = "teamcity")]
+    Teamcity,
+    #[serde(rename = "buildkite")]
+    Buildkite,
+}
+
+impl CiVendor {
+    /// The constant of the vendor in `turborepo_ci`
+    pub fn constant(&self) -> &'static str {
+        match self {
+            CiVendor::Github => "GITHUB_ACTIONS",
+            CiVendor::Teamcity => "TEAMCITY",
+            CiVendor::Buildkite => "BUILDKITE",
+        }
+    }
+}
+
+impl Display for CiVendor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CiVendor::Github => write!(f, "github"),
+            CiVendor::Teamcity => write!(f, "teamcity"),
+            CiVendor::Buildkite => write!(f, "buildkite"),
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub enum DebugSignal {
+    #[default]
+    #[serde(rename = "quit")]
+    Quit,
+    #[serde(rename = "usr1")]
+    Usr1,
+    #[serde(rename = "usr2")]
+    Usr2,
+}
+
+impl Display for DebugSignal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DebugSignal::Quit => write!(f, "SIGQUIT"),
+            DebugSignal::Usr1 => write!(f, "SIGUSR1"),
+            DebugSignal::Usr2 => write!(f, "SIGUSR2"),
+        }
+    }
+}
+
 /// Runs the CLI by parsing arguments with clap, then either calling Rust code
 /// directly or returning a payload for the Go code to use.
 ///
@@ -945,25 +1381,6 @@ pub async fn run(
     let mut cli_args = Args::new();
     let version = get_version();
 
-    // track telemetry handle to close at the end of the run
-    let mut telemetry_handle: Option<TelemetryHandle> = None;
-
-    // initialize telemetry
-    match AnonAPIClient::new("https://telemetry.vercel.com", 250, version) {
-        Ok(anonymous_api_client) => {
-            let handle = init_telemetry(anonymous_api_client, ui);
-            match handle {
-                Ok(h) => telemetry_handle = Some(h),
-                Err(error) => {
-                    debug!("failed to start telemetry: {:?}", error)
-                }
-            }
-        }
-        Err(error) => {
-            debug!("Failed to create AnonAPIClient: {:?}", error);
-        }
-    }
-
     // If there is no command, we set the command to `Command::Run` with
     // `self.parsed_args.run_args` as arguments.
     let mut command = if let Some(command) = mem::take(&mut cli_args.command) {
@@ -971,7 +1388,7 @@ pub async fn run(
     } else {
         let run_args = mem::take(&mut cli_args.run_args)
             .ok_or_else(|| Error::NoCommand(Backtrace::capture()))?;
-        if run_args.tasks.is_empty() {
+        if run_args.tasks.is_empty() && !run_args.interactive {
             let mut cmd = <Args as CommandFactory>::command();
             let _ = cmd.print_help();
             process::exit(1);
@@ -1031,6 +1448,41 @@ pub async fn run(
     cli_args.command = Some(command);
     cli_args.cwd = Some(repo_root.as_path().to_owned());
 
+    // track telemetry handle to close at the end of the run
+    let mut telemetry_handle: Option<TelemetryHandle> = None;
+
+    // initialize telemetry, which can be pointed at a self-hosted collector
+    let configured_endpoint = CommandBase::new(cli_args.clone(), repo_root.clone(), version, ui)
+        .config()
+        .map(|config| config.telemetry_endpoint().to_owned())
+        .ok();
+    let telemetry_endpoint = configured_endpoint.clone().unwrap_or_else(|| {
+        ConfigurationOptions::default()
+            .telemetry_endpoint()
+            .to_owned()
+    });
+    match AnonAPIClient::new(&telemetry_endpoint, 250, version) {
+        Ok(anonymous_api_client) => {
+            // In offline mode, events are queued on disk instead of being sent. Without
+            // config, the events queued for the configured endpoint are left alone.
+            let handle = init_telemetry(
+                anonymous_api_client,
+                configured_endpoint.as_deref(),
+                cli_args.offline,
+                ui,
+            );
+            match handle {
+                Ok(h) => telemetry_handle = Some(h),
+                Err(error) => {
+                    debug!("failed to start telemetry: {:?}", error)
+                }
+            }
+        }
+        Err(error) => {
+            debug!("Failed to create AnonAPIClient: {:?}", error);
+        }
+    }
+
     let root_telemetry = GenericEventBuilder::new();
     root_telemetry.track_start();
 
@@ -1041,15 +1493,60 @@ pub async fn run(
     // track args
     cli_args.track(&root_telemetry);
 
+    if cli_args.json {
+        match cli_args.command.as_ref() {
+            Some(Command::Run(_)) => return Err(Error::JsonRun),
+            Some(command) => {
+                if let Some(command) = command.without_json_output() {
+                    return Err(Error::JsonUnsupported { command });
+                }
+            }
+            None => {}
+        }
+    }
+
+    // The picked tasks and packages are run as if they were passed as arguments
+    if matches!(&cli_args.command, Some(Command::Run(args)) if args.interactive) {
+        let base = CommandBase::new(cli_args.clone(), repo_root.clone(), version, ui);
+        let picked = picker::pick(&base).await?;
+        if let Some(Command::Run(args)) = &mut cli_args.command {
+            args.tasks = picked.tasks;
+            args.filter.extend(picked.packages);
+        }
+    }
+
     let cli_result = match cli_args.command.as_ref().unwrap() {
         Command::Bin { .. } => {
             CommandEventBuilder::new("bin")
                 .with_parent(&root_telemetry)
                 .track_call();
-            bin::run()?;
+            bin::run(cli_args.json, ui)?;
 
             Ok(0)
         }
+        Command::Clean {
+            filter,
+            dry_run,
+            cache_dir,
+        } => {
+            let event = CommandEventBuilder::new("clean").with_parent(&root_telemetry);
+            event.track_call();
+            let filter = filter.clone();
+            let dry_run = *dry_run;
+            let cache_dir = cache_dir.clone();
+            let base = CommandBase::new(cli_args, repo_root, version, ui);
+            clean::clean(&base, &filter, dry_run, cache_dir.as_deref(), event.child()).await?;
+            Ok(0)
+        }
+        Command::Doctor {} => {
+            CommandEventBuilder::new("doctor")
+                .with_parent(&root_telemetry)
+                .track_call();
+            let offline = cli_args.offline;
+            let base = CommandBase::new(cli_args, repo_root, version, ui);
+
+            Ok(doctor::run(&base, offline).await)
+        }
         #[allow(unused_variables)]
         Command::Daemon { command, idle_time } => {
             CommandEventBuilder::new("daemon")
@@ -1090,27 +1587,49 @@ pub async fn run(
             event.track_call();
             let mut base = CommandBase::new(cli_args.clone(), repo_root, version, ui);
             let child_event = event.child();
-            telemetry::configure(command, &mut base, child_event);
+            if let Err(err) = telemetry::configure(command, &mut base, child_event) {
+                // JSON consumers need the failure in the output and the exit code
+                if base.json() {
+                    return Err(err.into());
+                }
+                telemetry::log_error(&err, &base);
+            }
             Ok(0)
         }
-        Command::Info { workspace, json } => {
+        Command::Info { workspace } => {
             CommandEventBuilder::new("info")
                 .with_parent(&root_telemetry)
                 .track_call();
-            let json = *json;
             let workspace = workspace.clone();
             let mut base = CommandBase::new(cli_args, repo_root, version, ui);
-            info::run(&mut base, workspace.as_deref(), json).await?;
+            info::run(&mut base, workspace.as_deref()).await?;
 
             Ok(0)
         }
+        Command::Init {
+            force,
+            no_gitignore,
+        } => {
+            let event = CommandEventBuilder::new("init").with_parent(&root_telemetry);
+            event.track_call();
+            let force = *force;
+            let no_gitignore = *no_gitignore;
+            let base = CommandBase::new(cli_args, repo_root, version, ui);
+            init::run(&base, force, no_gitignore, event.child()).await?;
+            Ok(0)
+        }
         Command::Link {
             no_gitignore,
+            scope,
+            yes,
             target,
         } => {
             CommandEventBuilder::new("link")
                 .with_parent(&root_telemetry)
                 .track_call();
+            if cli_args.offline {
+                return Err(Error::Offline { command: "link" });
+            }
             if cli_args.test_run {
                 println!("Link test run successful");
                 return Ok(0);
@@ -1118,9 +1637,17 @@ pub async fn run(
 
             let modify_gitignore = !*no_gitignore;
             let to = *target;
+            let yes = *yes;
+            let scope = scope.clone();
             let mut base = CommandBase::new(cli_args, repo_root, version, ui);
 
-            if let Err(err) = link::link(&mut base, modify_gitignore, to).await {
+            if let Err(err) =
+                link::link(&mut base, modify_gitignore, scope.as_deref(), yes, to).await
+            {
+                // JSON consumers need the failure in the output and the exit code
+                if base.json() {
+                    return Err(err.into());
+                }
                 error!("error: {}", err.to_string())
             }
 
@@ -1130,6 +1657,11 @@ pub async fn run(
             let event = CommandEventBuilder::new("logout").with_parent(&root_telemetry);
             event.track_call();
             let invalidate = *invalidate;
+            if invalidate && cli_args.offline {
+                return Err(Error::Offline {
+                    command: "logout --invalidate",
+                });
+            }
 
             let mut base = CommandBase::new(cli_args, repo_root, version, ui);
             let event_child = event.child();
@@ -1141,6 +1673,9 @@ pub async fn run(
         Command::Login { sso_team, force } => {
             let event = CommandEventBuilder::new("login").with_parent(&root_telemetry);
             event.track_call();
+            if cli_args.offline {
+                return Err(Error::Offline { command: "login" });
+            }
             if cli_args.test_run {
                 println!("Login test run successful");
                 return Ok(0);
@@ -1184,12 +1719,19 @@ pub async fn run(
             if args.tasks.is_empty() {
                 return Err(Error::NoTasks(backtrace::Backtrace::capture()));
             }
+            if args.remote_only && cli_args.offline {
+                return Err(Error::RemoteOnlyOffline);
+            }
 
             if let Some((file_path, include_args)) = args.profile_file_and_include_args() {
                 // TODO: Do we want to handle the result / error?
                 let _ = logger.enable_chrome_tracing(file_path, include_args);
             }
             let base = CommandBase::new(cli_args.clone(), repo_root, version, ui);
+            // Invalid configuration is reported once the run reads it
+            if base.config().is_ok_and(|config| config.debug_logs()) {
+                crate::run::debug_log::enable(logger, &base.repo_root);
+            }
 
             args.track(&event);
             event.track_run_code_path(CodePath::Rust);
@@ -1204,6 +1746,9 @@ pub async fn run(
             scope,
             scope_arg,
             docker,
+            prod,
+            emit_dockerfile,
+            emit_metadata,
             output_dir,
         } => {
             let event = CommandEventBuilder::new("prune").with_parent(&root_telemetry);
@@ -1214,17 +1759,35 @@ pub async fn run(
                 .cloned()
                 .unwrap_or_default();
             let docker = *docker;
+            let prod = *prod;
+            let emit_dockerfile = *emit_dockerfile;
+            let emit_metadata = *emit_metadata;
             let output_dir = output_dir.clone();
             let base = CommandBase::new(cli_args, repo_root, version, ui);
             let event_child = event.child();
-            prune::prune(&base, &scope, docker, &output_dir, event_child).await?;
+            prune::prune(
+                &base,
+                &scope,
+                docker,
+                prod,
+                emit_dockerfile,
+                emit_metadata,
+                &output_dir,
+                event_child,
+            )
+            .await?;
             Ok(0)
         }
         Command::Completion { shell } => {
             CommandEventBuilder::new("completion")
                 .with_parent(&root_telemetry)
                 .track_call();
-            generate(*shell, &mut Args::command(), "turbo", &mut io::stdout());
+            print!("{}", completion::script(*shell));
+            Ok(0)
+        }
+        Command::Complete { kind } => {
+            let base = CommandBase::new(cli_args.clone(), repo_root, version, ui);
+            completion::run(&base, *kind).await;
             Ok(0)
         }
     };
@@ -1304,7 +1867,8 @@ mod test {
     use anyhow::Result;
 
     use crate::cli::{
-        Args, Command, DryRunMode, EnvMode, LogOrder, LogPrefix, OutputLogsMode, RunArgs, Verbosity,
+        Args, Command, DaemonCommand, DryRunMode, EnvMode, LogOrder, LogPrefix, OutputLogsMode,
+        OutputLogsOverrides, RunArgs, Verbosity,
     };
 
     #[test_case::test_case(
@@ -1696,7 +2260,7 @@ mod test {
         Args {
             command: Some(Command::Run(Box::new(RunArgs {
                 tasks: vec!["build".to_string()],
-                output_logs: Some(OutputLogsMode::Full),
+                output_logs: Some(OutputLogsMode::Full.into()),
                 ..get_default_run_args()
             }))),
             ..Args::default()
@@ -1707,7 +2271,7 @@ mod test {
         Args {
             command: Some(Command::Run(Box::new(RunArgs {
                 tasks: vec!["build".to_string()],
-                output_logs: Some(OutputLogsMode::None),
+                output_logs: Some(OutputLogsMode::None.into()),
                 ..get_default_run_args()
             }))),
             ..Args::default()
@@ -1718,7 +2282,21 @@ mod test {
         Args {
             command: Some(Command::Run(Box::new(RunArgs {
                 tasks: vec!["build".to_string()],
-                output_logs: Some(OutputLogsMode::HashOnly),
+                output_logs: Some(OutputLogsMode::HashOnly.into()),
+                ..get_default_run_args()
+            }))),
+            ..Args::default()
+        }
+	)]
+    #[test_case::test_case(
+		&["turbo", "run", "build", "--output-logs", "web#build:full,errors-only"],
+        Args {
+            command: Some(Command::Run(Box::new(RunArgs {
+                tasks: vec!["build".to_string()],
+                output_logs: Some(OutputLogsOverrides(vec![
+                    (Some("web#build".to_string()), OutputLogsMode::Full),
+                    (None, OutputLogsMode::ErrorsOnly),
+                ])),
                 ..get_default_run_args()
             }))),
             ..Args::default()
@@ -1973,6 +2551,98 @@ mod test {
         .test();
     }
 
+    #[test]
+    fn test_parse_clean() {
+        assert_eq!(
+            Args::try_parse_from(["turbo", "clean"]).unwrap(),
+            Args {
+                command: Some(Command::Clean {
+                    filter: vec![],
+                    dry_run: false,
+                    cache_dir: None,
+                }),
+                ..Args::default()
+            }
+        );
+
+        assert_eq!(
+            Args::try_parse_from(["turbo", "clean", "-F", "web", "--dry-run"]).unwrap(),
+            Args {
+                command: Some(Command::Clean {
+                    filter: vec!["web".to_string()],
+                    dry_run: true,
+                    cache_dir: None,
+                }),
+                ..Args::default()
+            }
+        );
+
+        assert!(Args::try_parse_from(["turbo", "clean", "--cache-dir", ""]).is_err());
+    }
+
+    #[test]
+    fn test_output_logs_overrides() {
+        let overrides: OutputLogsOverrides = "web#build:full,docs#*:none,build:prod:hash-only,*:\
+                                              errors-only"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            overrides.mode_for("web", "build"),
+            Some(OutputLogsMode::Full)
+        );
+        assert_eq!(
+            overrides.mode_for("docs", "build"),
+            Some(OutputLogsMode::None)
+        );
+        assert_eq!(
+            overrides.mode_for("web", "build:prod"),
+            Some(OutputLogsMode::HashOnly)
+        );
+        assert_eq!(
+            overrides.mode_for("api", "build"),
+            Some(OutputLogsMode::ErrorsOnly)
+        );
+        assert_eq!(
+            overrides.to_string(),
+            "web#build:full,docs#*:none,build:prod:hash-only,*:errors-only"
+        );
+
+        let overrides: OutputLogsOverrides = "web#build:full".parse().unwrap();
+        assert_eq!(overrides.mode_for("web", "lint"), None);
+
+        assert!("web#build".parse::<OutputLogsOverrides>().is_err());
+        assert!("web#build:everything"
+            .parse::<OutputLogsOverrides>()
+            .is_err());
+        assert!(Args::try_parse_from(["turbo", "build", "--output-logs", ":full"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_json() {
+        assert_eq!(
+            Args::try_parse_from(["turbo", "daemon", "status", "--json"]).unwrap(),
+            Args {
+                json: true,
+                command: Some(Command::Daemon {
+                    command: Some(DaemonCommand::Status),
+                    idle_time: "4h0m0s".to_string(),
+                }),
+                ..Args::default()
+            }
+        );
+
+        assert_eq!(
+            Args::try_parse_from(["turbo", "--json", "info", "web"]).unwrap(),
+            Args {
+                json: true,
+                command: Some(Command::Info {
+                    workspace: Some("web".to_string()),
+                }),
+                ..Args::default()
+            }
+        );
+    }
+
     #[test]
     fn test_parse_login() {
         assert_eq!(
@@ -2073,6 +2743,9 @@ mod test {
             scope: None,
             scope_arg: Some(vec!["foo".into()]),
             docker: false,
+            prod: false,
+            emit_dockerfile: false,
+            emit_metadata: false,
             output_dir: "out".to_string(),
         };
 
@@ -2103,6 +2776,9 @@ mod test {
                     scope: Some(vec!["bar".to_string()]),
                     scope_arg: None,
                     docker: false,
+                    prod: false,
+                    emit_dockerfile: false,
+                    emit_metadata: false,
                     output_dir: "out".to_string(),
                 }),
                 ..Args::default()
@@ -2116,6 +2792,9 @@ mod test {
                     scope: None,
                     scope_arg: Some(vec!["foo".to_string(), "bar".to_string()]),
                     docker: false,
+                    prod: false,
+                    emit_dockerfile: false,
+                    emit_metadata: false,
                     output_dir: "out".to_string(),
                 }),
                 ..Args::default()
@@ -2129,6 +2808,59 @@ mod test {
                     scope: None,
                     scope_arg: Some(vec!["foo".into()]),
                     docker: true,
+                    prod: false,
+                    emit_dockerfile: false,
+                    emit_metadata: false,
+                    output_dir: "out".to_string(),
+                }),
+                ..Args::default()
+            }
+        );
+
+        assert_eq!(
+            Args::try_parse_from(["turbo", "prune", "--prod", "foo"]).unwrap(),
+            Args {
+                command: Some(Command::Prune {
+                    scope: None,
+                    scope_arg: Some(vec!["foo".into()]),
+                    docker: false,
+                    prod: true,
+                    emit_dockerfile: false,
+                    emit_metadata: false,
+                    output_dir: "out".to_string(),
+                }),
+                ..Args::default()
+            }
+        );
+
+        assert_eq!(
+            Args::try_parse_from(["turbo", "prune", "--docker", "--emit-dockerfile", "foo"])
+                .unwrap(),
+            Args {
+                command: Some(Command::Prune {
+                    scope: None,
+                    scope_arg: Some(vec!["foo".into()]),
+                    docker: true,
+                    prod: false,
+                    emit_dockerfile: true,
+                    emit_metadata: false,
+                    output_dir: "out".to_string(),
+                }),
+                ..Args::default()
+            }
+        );
+        assert!(Args::try_parse_from(["turbo", "prune", "--emit-dockerfile", "foo"]).is_err());
+
+        assert_eq!(
+            Args::try_parse_from(["turbo", "prune", "--emit-metadata", "foo"]).unwrap(),
+            Args {
+                command: Some(Command::Prune {
+                    scope: None,
+                    scope_arg: Some(vec!["foo".into()]),
+                    docker: false,
+                    prod: false,
+                    emit_dockerfile: false,
+                    emit_metadata: true,
                     output_dir: "out".to_string(),
                 }),
                 ..Args::default()
@@ -2142,6 +2874,9 @@ mod test {
                     scope: None,
                     scope_arg: Some(vec!["foo".into()]),
                     docker: false,
+                    prod: false,
+                    emit_dockerfile: false,
+                    emit_metadata: false,
                     output_dir: "dist".to_string(),
                 }),
                 ..Args::default()
@@ -2157,6 +2892,9 @@ mod test {
                     scope: None,
                     scope_arg: Some(vec!["foo".into()]),
                     docker: true,
+                    prod: false,
+                    emit_dockerfile: false,
+                    emit_metadata: false,
                     output_dir: "dist".to_string(),
                 }),
                 ..Args::default()
@@ -2173,6 +2911,9 @@ mod test {
                     scope: None,
                     scope_arg: Some(vec!["foo".into()]),
                     docker: true,
+                    prod: false,
+                    emit_dockerfile: false,
+                    emit_metadata: false,
                     output_dir: "dist".to_string(),
                 }),
                 cwd: Some(Utf8PathBuf::from("../examples/with-yarn")),
@@ -2194,6 +2935,9 @@ mod test {
                     scope: Some(vec!["foo".to_string()]),
                     scope_arg: None,
                     docker: true,
+                    prod: false,
+                    emit_dockerfile: false,
+                    emit_metadata: false,
                     output_dir: "dist".to_string(),
                 }),
                 ..Args::default()
@@ -2366,4 +3110,19 @@ mod test {
         assert!(Args::try_parse_from(["turbo", "build", "--cache-dir="]).is_err());
         assert!(Args::try_parse_from(["turbo", "build", "--cache-dir", ""]).is_err());
     }
+
+    #[test_case::test_case(&["turbo", "bin"], None ; "bin")]
+    #[test_case::test_case(&["turbo", "prune", "web"], None ; "prune")]
+    #[test_case::test_case(&["turbo", "daemon", "status"], None ; "daemon status")]
+    #[test_case::test_case(&["turbo", "telemetry"], None ; "telemetry")]
+    #[test_case::test_case(&["turbo", "daemon", "stop"], Some("daemon stop") ; "daemon stop")]
+    #[test_case::test_case(&["turbo", "daemon"], Some("daemon") ; "daemon server")]
+    #[test_case::test_case(&["turbo", "unlink"], Some("unlink") ; "unlink")]
+    #[test_case::test_case(&["turbo", "login"], Some("login") ; "login")]
+    #[test_case::test_case(&["turbo", "logout"], Some("logout") ; "logout")]
+    #[test_case::test_case(&["turbo", "init"], Some("init") ; "init")]
+    fn test_without_json_output(args: &[&str], expected: Option<&str>) {
+        let args = Args::try_parse_from(args).unwrap();
+        assert_eq!(args.command.unwrap().without_json_output(), expected);
+    }
 }
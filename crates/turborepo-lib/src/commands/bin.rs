@@ -1,18 +1,33 @@
-use std::{env::current_exe, io};
+use std::{env::current_exe, io, path::PathBuf};
 
+use serde::Serialize;
 use thiserror::Error;
+use turborepo_ui::UI;
+
+use super::{print_output, CommandOutput};
 
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("could not get path to turbo binary: {0}")]
     NoCurrentExe(#[from] io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+#[derive(Serialize)]
+struct Bin {
+    path: PathBuf,
+}
+
+impl CommandOutput for Bin {
+    fn print_text(&self, _: UI) {
+        println!("{}", self.path.to_string_lossy());
+    }
 }
 
-pub fn run() -> Result<(), Error> {
+pub fn run(json: bool, ui: UI) -> Result<(), Error> {
     let path = current_exe()?;
-    // NOTE: The Go version uses `base.UI.Output`, we should use the Rust equivalent
-    // eventually.
-    println!("{}", path.to_string_lossy());
+    print_output(&Bin { path }, json, ui)?;
 
     Ok(())
 }
@@ -0,0 +1,382 @@
+//! `turbo clean` removes what turbo left behind in a repository.
+//!
+//! That's the outputs of the tasks in `turbo.json`, the local cache and the
+//! `.turbo` directories with logs and run state. With `--filter`, only the
+//! outputs and `.turbo` directories of the matching packages are removed, as
+//! the cache and the state at the root are shared by all packages.
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    str::FromStr,
+};
+
+use camino::Utf8Path;
+use globwalk::{ValidatedGlob, WalkType};
+use miette::Diagnostic;
+use serde::Serialize;
+use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf, AnchoredSystemPath};
+use turborepo_cache::fs::FSCache;
+use turborepo_errors::Spanned;
+use turborepo_repository::{
+    inference::{RepoMode, RepoState},
+    package_graph::{self, PackageGraph, PackageName},
+    package_json::PackageJson,
+};
+use turborepo_scm::SCM;
+use turborepo_telemetry::events::command::CommandEventBuilder;
+use turborepo_ui::{BOLD, UI};
+
+use super::{CommandBase, CommandOutput};
+use crate::{
+    config,
+    engine::{BuilderError, EngineBuilder},
+    opts::{LegacyFilter, ScopeOpts},
+    run::{scope, task_id::TaskName},
+    turbo_json::TurboJson,
+};
+
+const TURBO_DIR: &str = ".turbo";
+// The link to a remote cache is configuration rather than state, so it's kept
+const LINK_CONFIG_FILE: &str = "config.json";
+
+#[derive(Debug, thiserror::Error, Diagnostic)]
+pub enum Error {
+    #[error("io error while cleaning: {0}")]
+    #[diagnostic(code(TURBO_CLEAN_001))]
+    Io(#[from] std::io::Error),
+    #[error("path error while cleaning: {0}")]
+    #[diagnostic(code(TURBO_CLEAN_002))]
+    Path(#[from] turbopath::PathError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    PackageJson(#[from] turborepo_repository::package_json::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    PackageGraph(#[from] package_graph::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Config(#[from] config::Error),
+    #[error(transparent)]
+    #[diagnostic(code(TURBO_CLEAN_005))]
+    Scope(#[from] scope::ResolutionError),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Builder(#[from] BuilderError),
+    #[error("invalid output glob: {0}")]
+    #[diagnostic(code(TURBO_CLEAN_006))]
+    Glob(#[from] globwalk::GlobError),
+    #[error("failed to find files to clean: {0}")]
+    #[diagnostic(code(TURBO_CLEAN_007))]
+    Walk(#[from] globwalk::WalkError),
+    #[error("failed to print the result as JSON")]
+    #[diagnostic(code(TURBO_CLEAN_008))]
+    Json(#[from] serde_json::Error),
+}
+
+pub async fn clean(
+    base: &CommandBase,
+    filter: &[String],
+    dry_run: bool,
+    cache_dir: Option<&Utf8Path>,
+    telemetry: CommandEventBuilder,
+) -> Result<(), Error> {
+    telemetry.track_arg_usage("filter", !filter.is_empty());
+    telemetry.track_arg_usage("dry-run", dry_run);
+    telemetry.track_arg_usage("cache-dir", cache_dir.is_some());
+
+    let repo_root = &base.repo_root;
+    let is_single_package = RepoState::infer(repo_root)
+        .is_ok_and(|repo_state| repo_state.mode == RepoMode::SinglePackage);
+
+    let root_package_json = PackageJson::load(&repo_root.join_component("package.json"))?;
+    let root_turbo_json = TurboJson::load(
+        repo_root,
+        AnchoredSystemPath::empty(),
+        &root_package_json,
+        is_single_package,
+    )?;
+    let package_graph = PackageGraph::builder(repo_root, root_package_json)
+        .with_single_package_mode(is_single_package)
+        .build()
+        .await?;
+
+    let scope_opts = ScopeOpts {
+        pkg_inference_root: None,
+        legacy_filter: LegacyFilter::default(),
+        global_deps: Vec::new(),
+        filter_patterns: filter.to_vec(),
+        ignore_patterns: Vec::new(),
+    };
+    let (mut packages, is_all_packages) = scope::resolve_packages(
+        &scope_opts,
+        repo_root,
+        &package_graph,
+        &SCM::new(repo_root),
+        &root_turbo_json,
+    )?;
+    if is_all_packages {
+        packages.insert(PackageName::Root);
+    }
+
+    let mut targets = Targets::default();
+    add_task_outputs(
+        &mut targets,
+        repo_root,
+        &package_graph,
+        &root_turbo_json,
+        &packages,
+        is_single_package,
+    )?;
+    for package in &packages {
+        if matches!(package, PackageName::Root) {
+            continue;
+        }
+        if let Some(info) = package_graph.package_info(package) {
+            let turbo_dir = repo_root
+                .resolve(info.package_path())
+                .join_component(TURBO_DIR);
+            targets.add_dir(turbo_dir, &[])?;
+        }
+    }
+    if is_all_packages {
+        targets.add_dir(repo_root.join_component(TURBO_DIR), &[LINK_CONFIG_FILE])?;
+        targets.add_dir(FSCache::resolve_cache_dir(repo_root, cache_dir), &[])?;
+    }
+
+    let mut cleaned = Cleaned {
+        dry_run,
+        removed: Vec::new(),
+    };
+    for (target, files) in targets.iter() {
+        if !dry_run {
+            remove(target, files)?;
+        }
+        cleaned.removed.push(Removed {
+            path: repo_root
+                .anchor(target)
+                .map_or_else(|_| target.to_string(), |target| target.to_string()),
+            files: files.len(),
+        });
+    }
+    base.print_output(&cleaned)?;
+
+    Ok(())
+}
+
+/// What `turbo clean` removed, or would remove with `--dry-run`.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Cleaned {
+    dry_run: bool,
+    removed: Vec<Removed>,
+}
+
+#[derive(Serialize)]
+struct Removed {
+    path: String,
+    files: usize,
+}
+
+impl CommandOutput for Cleaned {
+    fn print_text(&self, ui: UI) {
+        if self.removed.is_empty() {
+            println!("Nothing to clean");
+            return;
+        }
+        let verb = match self.dry_run {
+            true => "Would remove",
+            false => "Removed",
+        };
+        for removed in &self.removed {
+            let count = match removed.files {
+                1 => "1 file".to_string(),
+                count => format!("{count} files"),
+            };
+            println!(
+                "{verb} {} ({count})",
+                ui.apply(BOLD.apply_to(&removed.path))
+            );
+        }
+    }
+}
+
+// Adds the outputs of every task in `turbo.json` for `packages`
+fn add_task_outputs(
+    targets: &mut Targets,
+    repo_root: &AbsoluteSystemPath,
+    package_graph: &PackageGraph,
+    root_turbo_json: &TurboJson,
+    packages: &HashSet<PackageName>,
+    is_single_package: bool,
+) -> Result<(), Error> {
+    let mut tasks = root_turbo_json
+        .pipeline
+        .keys()
+        .map(|task| task.task().to_string())
+        .collect::<Vec<_>>();
+    tasks.sort();
+    tasks.dedup();
+
+    // The engine is built for one task at a time, as building it for all of
+    // them fails if any of them is missing from the filtered packages
+    for task in tasks {
+        let engine = match EngineBuilder::new(repo_root, package_graph, is_single_package)
+            .with_root_tasks(root_turbo_json.pipeline.keys().cloned())
+            .with_turbo_jsons(Some(
+                [(PackageName::Root, root_turbo_json.clone())]
+                    .into_iter()
+                    .collect(),
+            ))
+            .with_tasks_only(true)
+            .with_workspaces(packages.iter().cloned().collect())
+            .with_tasks([Spanned::new(TaskName::from(task.as_str()).into_owned())])
+            .build()
+        {
+            Ok(engine) => engine,
+            // Only packages that weren't selected have this task
+            Err(BuilderError::MissingTasks(_)) => continue,
+            Err(e) => return Err(e.into()),
+        };
+
+        for (task_id, definition) in engine.task_definitions() {
+            let Some(info) = package_graph.package_info(&PackageName::from(task_id.package()))
+            else {
+                continue;
+            };
+            let outputs = definition.repo_relative_hashable_outputs(task_id, info.package_path());
+            let files = globwalk::globwalk(
+                repo_root,
+                &outputs.validated_inclusions()?,
+                &outputs.validated_exclusions()?,
+                WalkType::Files,
+            )?;
+            targets.add_outputs(&repo_root.resolve(info.package_path()), files);
+        }
+    }
+
+    Ok(())
+}
+
+/// The files to remove, grouped by the file or directory they're reported as.
+#[derive(Debug, Default)]
+struct Targets(BTreeMap<AbsoluteSystemPathBuf, HashSet<AbsoluteSystemPathBuf>>);
+
+impl Targets {
+    // Outputs are reported as the top-level entry in their package, like
+    // `apps/web/.next`, instead of listing every file of a build
+    fn add_outputs(
+        &mut self,
+        package_dir: &AbsoluteSystemPath,
+        files: impl IntoIterator<Item = AbsoluteSystemPathBuf>,
+    ) {
+        for file in files {
+            let Ok(relative) = package_dir.anchor(&file) else {
+                continue;
+            };
+            let Some(entry) = relative.components().next() else {
+                continue;
+            };
+            let target = package_dir.join_component(entry.as_str());
+            self.0.entry(target).or_default().insert(file);
+        }
+    }
+
+    // Adds all files in `dir`, except for the ones matching `exclude`
+    fn add_dir(&mut self, dir: AbsoluteSystemPathBuf, exclude: &[&str]) -> Result<(), Error> {
+        if !dir.exists() {
+            return Ok(());
+        }
+        let include = [ValidatedGlob::from_str("**")?];
+        let exclude = exclude
+            .iter()
+            .map(|glob| ValidatedGlob::from_str(glob))
+            .collect::<Result<Vec<_>, _>>()?;
+        let files = globwalk::globwalk(&dir, &include, &exclude, WalkType::Files)?;
+        if !files.is_empty() {
+            self.0.entry(dir).or_default().extend(files);
+        }
+        Ok(())
+    }
+
+    fn iter(
+        &self,
+    ) -> impl Iterator<Item = (&AbsoluteSystemPathBuf, &HashSet<AbsoluteSystemPathBuf>)> {
+        self.0.iter()
+    }
+}
+
+// Removes `files` and the directories in `target` that are left empty
+fn remove(
+    target: &AbsoluteSystemPath,
+    files: &HashSet<AbsoluteSystemPathBuf>,
+) -> Result<(), Error> {
+    for file in files {
+        file.remove_file()?;
+        for dir in file
+            .ancestors()
+            .skip(1)
+            .take_while(|dir| target.contains(dir))
+        {
+            // Directories that still have files in them are removed along
+            // with their last file
+            if dir.remove_dir().is_err() {
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use tempfile::tempdir;
+    use turbopath::AbsoluteSystemPathBuf;
+
+    use super::{remove, Targets};
+
+    #[test]
+    fn test_clean_targets() {
+        let tmp = tempdir().unwrap();
+        let package_dir = AbsoluteSystemPathBuf::try_from(tmp.path()).unwrap();
+        let files = [
+            package_dir.join_components(&[".next", "server", "page.js"]),
+            package_dir.join_components(&[".next", "build-manifest.json"]),
+            package_dir.join_components(&[".turbo", "turbo-build.log"]),
+            package_dir.join_components(&[".turbo", "config.json"]),
+            package_dir.join_component("tsconfig.tsbuildinfo"),
+        ];
+        for file in &files {
+            file.ensure_dir().unwrap();
+            file.create_with_contents("").unwrap();
+        }
+
+        let mut targets = Targets::default();
+        targets.add_outputs(&package_dir, files[..2].iter().cloned());
+        targets.add_outputs(&package_dir, [files[4].clone()]);
+        targets
+            .add_dir(package_dir.join_component(".turbo"), &["config.json"])
+            .unwrap();
+
+        let reported = targets
+            .iter()
+            .map(|(target, files)| (package_dir.anchor(target).unwrap().to_string(), files.len()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            reported,
+            vec![
+                (".next".to_string(), 2),
+                (".turbo".to_string(), 1),
+                ("tsconfig.tsbuildinfo".to_string(), 1),
+            ]
+        );
+
+        for (target, files) in targets.iter() {
+            remove(target, files).unwrap();
+        }
+        assert!(!package_dir.join_component(".next").exists());
+        assert!(!files[2].exists());
+        assert!(files[3].exists());
+        assert!(!files[4].exists());
+        assert!(package_dir.exists());
+    }
+}
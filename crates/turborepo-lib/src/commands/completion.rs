@@ -0,0 +1,258 @@
+//! Shell completions for `turbo`.
+//!
+//! The scripts generated by `clap_complete` only know about the static flags
+//! and subcommands of the CLI. For bash, zsh and fish, the scripts are extended
+//! to call back into `turbo __complete` for the values that depend on the
+//! workspace: package names for `--filter` and task names for `turbo run`.
+
+use clap::CommandFactory;
+use clap_complete::{generate, Shell};
+use tracing::debug;
+use turbopath::AnchoredSystemPath;
+use turborepo_repository::{
+    package_graph::{PackageGraph, PackageName},
+    package_json::PackageJson,
+};
+
+use crate::{
+    cli::{self, CompletionKind},
+    commands::CommandBase,
+    turbo_json::TurboJson,
+    Args,
+};
+
+const BIN_NAME: &str = "turbo";
+
+/// Returns the completion script for `shell`.
+pub fn script(shell: Shell) -> String {
+    let mut script = Vec::new();
+    generate(shell, &mut Args::command(), BIN_NAME, &mut script);
+    with_dynamic_completions(shell, &String::from_utf8_lossy(&script))
+}
+
+fn with_dynamic_completions(shell: Shell, script: &str) -> String {
+    match shell {
+        Shell::Bash => format!("{script}\n{BASH_DYNAMIC}"),
+        Shell::Zsh => {
+            // The dispatch at the end of the script has to use the dynamic
+            // completion function, which must be defined before it
+            match script.find(ZSH_DISPATCH) {
+                Some(index) => format!(
+                    "{}{ZSH_DYNAMIC}\n{}",
+                    &script[..index],
+                    script[index..]
+                        .replace("    _turbo \"$@\"", "    _turbo_dynamic \"$@\"")
+                        .replace("compdef _turbo turbo", "compdef _turbo_dynamic turbo")
+                ),
+                None => format!("{script}\n{ZSH_DYNAMIC}\ncompdef _turbo_dynamic turbo\n"),
+            }
+        }
+        Shell::Fish => format!("{script}\n{FISH_DYNAMIC}"),
+        // Other shells only complete flags and subcommands
+        _ => script.to_string(),
+    }
+}
+
+/// Prints the candidates for `kind`, one per line. This runs on every
+/// keystroke that triggers a completion, so failures print nothing instead of
+/// an error.
+pub async fn run(base: &CommandBase, kind: CompletionKind) {
+    let candidates = match kind {
+        CompletionKind::Packages => packages(base).await,
+        CompletionKind::Tasks => tasks(base),
+    };
+    match candidates {
+        Ok(candidates) => {
+            for candidate in candidates {
+                println!("{candidate}");
+            }
+        }
+        Err(e) => debug!("unable to complete {kind:?}: {e}"),
+    }
+}
+
+pub(crate) async fn packages(base: &CommandBase) -> Result<Vec<String>, cli::Error> {
+    let root_package_json = PackageJson::load(&base.repo_root.join_component("package.json"))?;
+    let package_graph = PackageGraph::builder(&base.repo_root, root_package_json)
+        .build()
+        .await?;
+    let mut packages = package_graph
+        .packages()
+        .filter_map(|(name, _)| match name {
+            PackageName::Root => None,
+            PackageName::Other(name) => Some(name.clone()),
+        })
+        .collect::<Vec<_>>();
+    packages.sort();
+    Ok(packages)
+}
+
+pub(crate) fn tasks(base: &CommandBase) -> Result<Vec<String>, cli::Error> {
+    let root_package_json = PackageJson::load(&base.repo_root.join_component("package.json"))?;
+    let turbo_json = TurboJson::load(
+        &base.repo_root,
+        AnchoredSystemPath::empty(),
+        &root_package_json,
+        false,
+    )?;
+    Ok(task_names(
+        turbo_json.pipeline.keys().map(|task| task.to_string()),
+    ))
+}
+
+// Package tasks like `web#build` complete both as themselves and as the task
+// they configure
+fn task_names(pipeline: impl Iterator<Item = String>) -> Vec<String> {
+    let mut tasks = Vec::new();
+    for task in pipeline {
+        let task = task.trim_start_matches("//#").to_string();
+        if let Some((_, name)) = task.split_once('#') {
+            tasks.push(name.to_string());
+        }
+        tasks.push(task);
+    }
+    tasks.sort();
+    tasks.dedup();
+    tasks
+}
+
+const BASH_DYNAMIC: &str = r#"_turbo_is_run() {
+    local i
+    for (( i = 1; i < COMP_CWORD; i++ )); do
+        if [[ "${COMP_WORDS[i]}" == "run" ]]; then
+            return 0
+        fi
+    done
+    return 1
+}
+
+_turbo_dynamic() {
+    local cur prev
+    cur="${COMP_WORDS[COMP_CWORD]}"
+    prev="${COMP_WORDS[COMP_CWORD-1]}"
+    if [[ "${prev}" == "=" && ${COMP_CWORD} -gt 1 ]]; then
+        prev="${COMP_WORDS[COMP_CWORD-2]}"
+    fi
+
+    case "${prev}" in
+        --filter|-F)
+            COMPREPLY=( $(compgen -W "$(turbo __complete packages 2>/dev/null)" -- "${cur}") )
+            return 0
+            ;;
+    esac
+
+    # Global flags can come before the subcommand, so look for it in all
+    # the preceding words
+    if [[ "${cur}" != -* ]] && _turbo_is_run; then
+        COMPREPLY=( $(compgen -W "$(turbo __complete tasks 2>/dev/null)" -- "${cur}") )
+        return 0
+    fi
+
+    _turbo "$@"
+    if [[ "${cur}" != -* && ${COMP_CWORD} -eq 1 ]]; then
+        COMPREPLY+=( $(compgen -W "$(turbo __complete tasks 2>/dev/null)" -- "${cur}") )
+    fi
+}
+
+complete -F _turbo_dynamic -o bashdefault -o default turbo
+"#;
+
+const ZSH_DISPATCH: &str = "if [ \"$funcstack[1]\" = \"_turbo\" ]; then";
+
+const ZSH_DYNAMIC: &str = r#"_turbo_packages() {
+    local -a packages
+    packages=(${(f)"$(turbo __complete packages 2>/dev/null)"})
+    _describe 'package' packages
+}
+
+_turbo_tasks() {
+    local -a tasks
+    tasks=(${(f)"$(turbo __complete tasks 2>/dev/null)"})
+    _describe 'task' tasks
+}
+
+_turbo_dynamic() {
+    if [[ "${words[CURRENT-1]}" == (--filter|-F) ]]; then
+        _turbo_packages
+        return
+    fi
+    if [[ "${words[CURRENT]}" == --filter=* ]]; then
+        compset -P '--filter='
+        _turbo_packages
+        return
+    fi
+    if [[ "${words[CURRENT]}" != -* && ${words[(i)run]} -lt ${CURRENT} ]]; then
+        _turbo_tasks
+        return
+    fi
+    _turbo "$@"
+    if [[ "${words[CURRENT]}" != -* && ${CURRENT} -eq 2 ]]; then
+        _turbo_tasks
+    fi
+}
+"#;
+
+const FISH_DYNAMIC: &str = r#"complete -c turbo -s F -l filter -x -a '(turbo __complete packages 2>/dev/null)'
+complete -c turbo -n '__fish_use_subcommand; or __fish_seen_subcommand_from run' -f -a '(turbo __complete tasks 2>/dev/null)'
+"#;
+
+#[cfg(test)]
+mod test {
+    use clap_complete::Shell;
+    use test_case::test_case;
+
+    use super::{script, task_names, BASH_DYNAMIC};
+
+    #[test]
+    fn test_task_names() {
+        let pipeline = ["build", "//#lint", "web#build", "docs#dev", "test"];
+        assert_eq!(
+            task_names(pipeline.into_iter().map(String::from)),
+            vec!["build", "dev", "docs#dev", "lint", "test", "web#build"]
+        );
+    }
+
+    #[test]
+    fn test_scripts_call_back_into_turbo() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+            let script = script(shell);
+            assert!(script.contains("turbo __complete packages"), "{shell}");
+            assert!(script.contains("turbo __complete tasks"), "{shell}");
+        }
+    }
+
+    #[cfg(unix)]
+    #[test_case(&["turbo", "run", "bu"], "build" ; "run")]
+    #[test_case(&["turbo", "--cwd", "apps/web", "run", "bu"], "build" ; "run after global flag")]
+    #[test_case(&["turbo", "--filter", "=", "web", "run", "li"], "lint" ; "run after filter")]
+    #[test_case(&["turbo", "login", "bu"], "" ; "other subcommand")]
+    fn test_bash_completes_tasks_for_run(words: &[&str], expected: &str) {
+        // Stubs out the generated completion and the callback into turbo
+        let script = [
+            BASH_DYNAMIC,
+            "_turbo() { COMPREPLY=(); }",
+            "turbo() { printf 'build\\nlint\\n'; }",
+            format!("COMP_WORDS=({})", words.join(" ")).as_str(),
+            format!("COMP_CWORD={}", words.len() - 1).as_str(),
+            "_turbo_dynamic",
+            "echo \"${COMPREPLY[@]}\"",
+        ]
+        .join("\n");
+        let output = std::process::Command::new("bash")
+            .arg("-c")
+            .arg(script)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), expected);
+    }
+
+    #[test]
+    fn test_zsh_dispatches_to_dynamic_completion() {
+        let script = script(Shell::Zsh);
+        let definition = script.find("_turbo_dynamic() {").unwrap();
+        let dispatch = script.find("compdef _turbo_dynamic turbo").unwrap();
+        assert!(definition < dispatch);
+        assert!(!script.contains("compdef _turbo turbo"));
+    }
+}
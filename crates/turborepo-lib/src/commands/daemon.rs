@@ -8,10 +8,10 @@ use time::{format_description, OffsetDateTime};
 use tokio::signal::ctrl_c;
 use tracing::{trace, warn};
 use turbopath::AbsoluteSystemPath;
-use turborepo_ui::{color, BOLD_GREEN, BOLD_RED, GREY};
+use turborepo_ui::{color, BOLD_GREEN, BOLD_RED, GREY, UI};
 use which::which;
 
-use super::CommandBase;
+use super::{CommandBase, CommandOutput};
 use crate::{
     cli::DaemonCommand,
     daemon::{
@@ -27,7 +27,9 @@ const DAEMON_NOT_RUNNING_MESSAGE: &str =
 /// Runs the daemon command.
 pub async fn daemon_client(command: &DaemonCommand, base: &CommandBase) -> Result<(), DaemonError> {
     let (can_start_server, can_kill_server) = match command {
-        DaemonCommand::Status { .. } | DaemonCommand::Logs => (false, false),
+        DaemonCommand::Status | DaemonCommand::Logs | DaemonCommand::LogLevel { .. } => {
+            (false, false)
+        }
         DaemonCommand::Stop => (false, true),
         DaemonCommand::Restart | DaemonCommand::Start => (true, true),
         DaemonCommand::Clean { .. } => (false, true),
@@ -72,10 +74,10 @@ pub async fn daemon_client(command: &DaemonCommand, base: &CommandBase) -> Resul
             client.stop().await?;
             println!("{} stopped daemon", color!(base.ui, BOLD_GREEN, "✓"));
         }
-        DaemonCommand::Status { json } => {
+        DaemonCommand::Status => {
             let mut client = match connector.connect().await {
                 Ok(status) => status,
-                Err(DaemonConnectorError::NotRunning) if *json => {
+                Err(DaemonConnectorError::NotRunning) if base.json() => {
                     println!("{}", json!({ "error": DAEMON_NOT_RUNNING_MESSAGE }));
                     return Ok(());
                 }
@@ -101,26 +103,7 @@ pub async fn daemon_client(command: &DaemonCommand, base: &CommandBase) -> Resul
                 sock_file: paths.sock_file.to_owned(),
             };
 
-            if *json {
-                println!("{}", serde_json::to_string_pretty(&status)?);
-            } else {
-                println!("{} daemon is running", color!(base.ui, BOLD_GREEN, "✓"));
-                println!("log file: {}", color!(base.ui, GREY, "{}", status.log_file));
-                println!(
-                    "uptime: {}",
-                    color!(
-                        base.ui,
-                        GREY,
-                        "{}s",
-                        humantime::format_duration(Duration::from_millis(status.uptime_ms))
-                    )
-                );
-                println!("pid file: {}", color!(base.ui, GREY, "{}", status.pid_file));
-                println!(
-                    "socket file: {}",
-                    color!(base.ui, GREY, "{}", status.sock_file)
-                );
-            }
+            base.print_output(&status)?;
         }
         DaemonCommand::Logs => {
             let log_file = if let Ok(log_file) = get_log_file_from_daemon(connector).await {
@@ -137,6 +120,28 @@ pub async fn daemon_client(command: &DaemonCommand, base: &CommandBase) -> Resul
                 .status()
                 .expect("failed to execute tail");
         }
+        DaemonCommand::LogLevel { filter } => {
+            let mut client = match connector.connect().await {
+                Ok(client) => client,
+                Err(DaemonConnectorError::NotRunning) => {
+                    println!(
+                        "{} {}",
+                        color!(base.ui, BOLD_RED, "x"),
+                        DAEMON_NOT_RUNNING_MESSAGE
+                    );
+                    return Ok(());
+                }
+                Err(e) => {
+                    return Err(e.into());
+                }
+            };
+            client.set_log_filter(filter.clone()).await?;
+            println!(
+                "{} daemon log filter set to {}",
+                color!(base.ui, BOLD_GREEN, "✓"),
+                filter
+            );
+        }
         DaemonCommand::Clean {
             clean_logs: should_clean_logs,
         } => {
@@ -277,8 +282,19 @@ pub async fn daemon_server(
         }
         CloseReason::Interrupt
     });
+    // Metrics are optional, so an invalid configuration doesn't keep the daemon
+    // from starting
+    let metrics_port = match base.config() {
+        Ok(config) => config.daemon_metrics_port(),
+        Err(e) => {
+            warn!("unable to read configuration, not serving metrics: {e}");
+            None
+        }
+    };
     let server =
-        crate::daemon::TurboGrpcService::new(base.repo_root.clone(), paths, timeout, exit_signal);
+        crate::daemon::TurboGrpcService::new(base.repo_root.clone(), paths, timeout, exit_signal)
+            .with_metrics_port(metrics_port)
+            .with_log_filter(logging.daemon_log_filter());
 
     let reason = server.serve().await?;
 
@@ -309,3 +325,21 @@ pub struct DaemonStatus {
     pub pid_file: turbopath::AbsoluteSystemPathBuf,
     pub sock_file: turbopath::AbsoluteSystemPathBuf,
 }
+
+impl CommandOutput for DaemonStatus {
+    fn print_text(&self, ui: UI) {
+        println!("{} daemon is running", color!(ui, BOLD_GREEN, "✓"));
+        println!("log file: {}", color!(ui, GREY, "{}", self.log_file));
+        println!(
+            "uptime: {}",
+            color!(
+                ui,
+                GREY,
+                "{}s",
+                humantime::format_duration(Duration::from_millis(self.uptime_ms))
+            )
+        );
+        println!("pid file: {}", color!(ui, GREY, "{}", self.pid_file));
+        println!("socket file: {}", color!(ui, GREY, "{}", self.sock_file));
+    }
+}
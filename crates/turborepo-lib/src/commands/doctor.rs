@@ -0,0 +1,359 @@
+//! `turbo doctor` checks the environment turbo runs in.
+//!
+//! Every check prints whether it passed and, if it didn't, how to fix it, so
+//! the output can be pasted into a bug report as is. The command exits with 1
+//! if any of the checks failed.
+
+use std::{process::Command, time::Duration};
+
+use chrono::Utc;
+use serde::Serialize;
+use tracing::error;
+use turborepo_api_client::{APIClient, CacheClient, Error as ApiError};
+use turborepo_repository::{package_json::PackageJson, package_manager::PackageManager};
+use turborepo_ui::{color, BOLD_GREEN, BOLD_RED, GREY, UI, YELLOW};
+use turborepo_vercel_api::CachingStatus;
+
+use super::{CommandBase, CommandOutput};
+use crate::daemon::{DaemonConnector, DaemonConnectorError};
+
+// A clock that's off by more than this can fail TLS and token validation
+const MAX_CLOCK_SKEW: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    Passed,
+    Warning,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct Check {
+    name: &'static str,
+    status: Status,
+    message: String,
+    fix: Option<String>,
+}
+
+#[derive(Serialize)]
+struct Checks {
+    checks: Vec<Check>,
+}
+
+impl CommandOutput for Checks {
+    fn print_text(&self, ui: UI) {
+        for check in &self.checks {
+            check.print(ui);
+        }
+    }
+}
+
+impl Check {
+    fn passed(name: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Passed,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    fn warning(name: &'static str, message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Warning,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn failed(name: &'static str, message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Failed,
+            message: message.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn skipped(name: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Skipped,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    fn print(&self, ui: UI) {
+        let status = match self.status {
+            Status::Passed => color!(ui, BOLD_GREEN, "✓"),
+            Status::Warning => color!(ui, YELLOW, "!"),
+            Status::Failed => color!(ui, BOLD_RED, "x"),
+            Status::Skipped => color!(ui, GREY, "-"),
+        };
+        println!("{status} {:<16} {}", self.name, self.message);
+        if let Some(fix) = &self.fix {
+            println!("  {}", color!(ui, GREY, "→ {fix}"));
+        }
+    }
+}
+
+/// Runs all checks and returns the exit code.
+pub async fn run(base: &CommandBase, offline: bool) -> i32 {
+    let mut checks = vec![git(base), daemon(base).await];
+    checks.extend(package_manager(base));
+    if offline {
+        checks.push(Check::skipped("remote cache", "skipped with --offline"));
+        checks.push(Check::skipped("clock", "skipped with --offline"));
+    } else {
+        match base.api_client() {
+            Ok(api_client) => {
+                checks.push(remote_cache(base, &api_client).await);
+                checks.push(clock(&api_client).await);
+            }
+            Err(e) => checks.push(Check::failed(
+                "remote cache",
+                format!("invalid configuration: {e}"),
+                "fix the configuration in turbo.json or the TURBO_* environment variables",
+            )),
+        }
+    }
+
+    let failed = checks.iter().any(|check| check.status == Status::Failed);
+    if let Err(e) = base.print_output(&Checks { checks }) {
+        error!("failed to print checks: {e}");
+        return 1;
+    }
+
+    match failed {
+        true => 1,
+        false => 0,
+    }
+}
+
+fn git(base: &CommandBase) -> Check {
+    let version = match Command::new("git").arg("--version").output() {
+        Ok(output) if output.status.success() => {
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        }
+        _ => {
+            return Check::warning(
+                "git",
+                "git was not found",
+                "install git, without it files are hashed without git and `--filter` can't use \
+                 git ranges",
+            )
+        }
+    };
+
+    let in_repository = Command::new("git")
+        .args(["rev-parse", "--is-inside-work-tree"])
+        .current_dir(&base.repo_root)
+        .output()
+        .is_ok_and(|output| output.status.success());
+    match in_repository {
+        true => Check::passed("git", version),
+        false => Check::warning(
+            "git",
+            format!("{version}, but {} is not a git repository", base.repo_root),
+            "run `git init`, without it files are hashed without git and `--filter` can't use git \
+             ranges",
+        ),
+    }
+}
+
+async fn daemon(base: &CommandBase) -> Check {
+    let connector = DaemonConnector::new(false, false, &base.repo_root);
+    let status = match connector.connect().await {
+        Ok(mut client) => client.status().await.map_err(|e| e.to_string()),
+        Err(DaemonConnectorError::NotRunning) => {
+            return Check::warning(
+                "daemon",
+                "not running",
+                "run `turbo daemon start` to start it, or let the next `turbo run` start it",
+            )
+        }
+        Err(e) => Err(e.to_string()),
+    };
+
+    match status {
+        Ok(status) => Check::passed(
+            "daemon",
+            format!(
+                "running for {}",
+                humantime::format_duration(Duration::from_secs(status.uptime_msec / 1000))
+            ),
+        ),
+        Err(e) => Check::failed(
+            "daemon",
+            format!("unable to connect: {e}"),
+            "run `turbo daemon clean` to remove its state, it's restarted by the next `turbo run`",
+        ),
+    }
+}
+
+// The lockfile can only be checked once the package manager is known
+fn package_manager(base: &CommandBase) -> Vec<Check> {
+    let root_package_json = match PackageJson::load(&base.repo_root.join_component("package.json"))
+    {
+        Ok(root_package_json) => root_package_json,
+        Err(e) => {
+            return vec![Check::failed(
+                "package manager",
+                format!("unable to read package.json: {e}"),
+                "fix the package.json at the root of the repository",
+            )]
+        }
+    };
+
+    let package_manager =
+        match PackageManager::get_package_manager(&base.repo_root, Some(&root_package_json)) {
+            Ok(package_manager) => package_manager,
+            Err(e) => {
+                return vec![Check::failed(
+                    "package manager",
+                    e.to_string(),
+                    "set the `packageManager` field in package.json, e.g. \"packageManager\": \
+                     \"npm@10.2.0\"",
+                )]
+            }
+        };
+
+    let lockfile_name = package_manager.existing_lockfile_name(&base.repo_root);
+    let lockfile = match package_manager.read_lockfile(&base.repo_root, &root_package_json) {
+        Ok(_) => Check::passed("lockfile", format!("{lockfile_name} parsed")),
+        Err(e) => Check::failed(
+            "lockfile",
+            format!("unable to parse {lockfile_name}: {e}"),
+            format!(
+                "run `{package_manager} install` to regenerate it, without it turbo can't tell \
+                 which dependencies changed"
+            ),
+        ),
+    };
+
+    vec![
+        Check::passed("package manager", package_manager.to_string()),
+        lockfile,
+    ]
+}
+
+async fn remote_cache(base: &CommandBase, api_client: &APIClient) -> Check {
+    let api_auth = match base.api_auth() {
+        Ok(Some(api_auth)) => api_auth,
+        Ok(None) => {
+            return Check::skipped(
+                "remote cache",
+                "not logged in, run `turbo login` and `turbo link` to enable it",
+            )
+        }
+        Err(e) => {
+            return Check::failed(
+                "remote cache",
+                format!("invalid configuration: {e}"),
+                "fix the configuration in turbo.json or the TURBO_* environment variables",
+            )
+        }
+    };
+
+    let api_url = api_client.base_url();
+    let status = api_client
+        .get_caching_status(
+            &api_auth.token,
+            api_auth.team_id.as_deref(),
+            api_auth.team_slug.as_deref(),
+        )
+        .await;
+    match status {
+        Ok(response) => match response.status {
+            CachingStatus::Enabled => {
+                Check::passed("remote cache", format!("enabled on {api_url}"))
+            }
+            CachingStatus::Disabled => Check::warning(
+                "remote cache",
+                "disabled for this team",
+                "enable remote caching in the settings of your team",
+            ),
+            CachingStatus::OverLimit => Check::warning(
+                "remote cache",
+                "over the usage limit",
+                "upgrade your plan or wait for the next billing cycle",
+            ),
+            CachingStatus::Paused => Check::warning(
+                "remote cache",
+                "paused",
+                "resume remote caching in the settings of your team",
+            ),
+        },
+        Err(ApiError::ReqwestError(e))
+            if e.status().is_some_and(|status| {
+                status == reqwest::StatusCode::UNAUTHORIZED
+                    || status == reqwest::StatusCode::FORBIDDEN
+            }) =>
+        {
+            Check::failed(
+                "remote cache",
+                format!("the token was rejected by {api_url}"),
+                "run `turbo login` to get a new token, and `turbo link` to pick the team again",
+            )
+        }
+        Err(e) => Check::failed(
+            "remote cache",
+            format!("unable to reach {api_url}: {e}"),
+            "check your network and proxy settings, and the `--api` flag or TURBO_API",
+        ),
+    }
+}
+
+async fn clock(api_client: &APIClient) -> Check {
+    match api_client.server_time().await {
+        Ok(Some(server_time)) => clock_skew(Utc::now() - server_time, api_client.base_url()),
+        Ok(None) => Check::skipped(
+            "clock",
+            format!("{} didn't send its time", api_client.base_url()),
+        ),
+        Err(_) => Check::skipped(
+            "clock",
+            format!("unable to reach {}", api_client.base_url()),
+        ),
+    }
+}
+
+fn clock_skew(skew: chrono::Duration, api_url: &str) -> Check {
+    let seconds = skew.num_seconds();
+    if seconds.unsigned_abs() <= MAX_CLOCK_SKEW.as_secs() {
+        return Check::passed("clock", format!("in sync with {api_url}"));
+    }
+    let direction = match seconds > 0 {
+        true => "ahead of",
+        false => "behind",
+    };
+    Check::warning(
+        "clock",
+        format!("{}s {direction} {api_url}", seconds.unsigned_abs()),
+        "sync your system clock, e.g. by turning on automatic time updates",
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::{clock_skew, Status};
+
+    #[test]
+    fn test_clock_skew() {
+        let api_url = "https://vercel.com/api";
+
+        let check = clock_skew(chrono::Duration::seconds(-3), api_url);
+        assert_eq!(check.status, Status::Passed);
+
+        let check = clock_skew(chrono::Duration::seconds(90), api_url);
+        assert_eq!(check.status, Status::Warning);
+        assert_eq!(check.message, "90s ahead of https://vercel.com/api");
+
+        let check = clock_skew(chrono::Duration::minutes(-5), api_url);
+        assert_eq!(check.message, "300s behind https://vercel.com/api");
+    }
+}
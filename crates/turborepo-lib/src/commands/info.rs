@@ -10,9 +10,13 @@ use turborepo_repository::{
     package_json::PackageJson,
     package_manager::PackageManager,
 };
-use turborepo_ui::GREY;
+use turborepo_ui::{GREY, UI};
 
-use crate::{cli, commands::CommandBase, config::ConfigurationOptions};
+use crate::{
+    cli,
+    commands::{CommandBase, CommandOutput},
+    config::ConfigurationOptions,
+};
 
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -25,6 +29,8 @@ struct InfoConfig {
     signature: Option<bool>,
     preflight: Option<bool>,
     timeout: Option<u64>,
+    connect_timeout: Option<u64>,
+    upload_timeout: Option<u64>,
     enabled: Option<bool>,
     spaces_id: Option<String>,
 }
@@ -40,6 +46,8 @@ impl<'a> From<&'a ConfigurationOptions> for InfoConfig {
             signature: config.signature,
             preflight: config.preflight,
             timeout: config.timeout,
+            connect_timeout: config.connect_timeout,
+            upload_timeout: config.upload_timeout,
             enabled: config.enabled,
             spaces_id: config.spaces_id.clone(),
         }
@@ -66,11 +74,7 @@ struct WorkspaceDetails<'a> {
     dependencies: Vec<&'a str>,
 }
 
-pub async fn run(
-    base: &mut CommandBase,
-    workspace: Option<&str>,
-    json: bool,
-) -> Result<(), cli::Error> {
+pub async fn run(base: &mut CommandBase, workspace: Option<&str>) -> Result<(), cli::Error> {
     let root_package_json = PackageJson::load(&base.repo_root.join_component("package.json"))?;
 
     let package_graph = PackageGraph::builder(&base.repo_root, root_package_json)
@@ -79,20 +83,11 @@ pub async fn run(
 
     let config = base.config()?;
 
-    if let Some(workspace) = workspace {
-        let workspace_details = WorkspaceDetails::new(&package_graph, workspace);
-        if json {
-            println!("{}", serde_json::to_string_pretty(&workspace_details)?);
-        } else {
-            workspace_details.print();
-        }
-    } else {
-        let repo_details = RepositoryDetails::new(&package_graph, config);
-        if json {
-            println!("{}", serde_json::to_string_pretty(&repo_details)?);
-        } else {
-            repo_details.print()?;
+    match workspace {
+        Some(workspace) => {
+            base.print_output(&WorkspaceDetails::new(&package_graph, workspace))?;
         }
+        None => base.print_output(&RepositoryDetails::new(&package_graph, config))?,
     }
 
     Ok(())
@@ -118,7 +113,10 @@ impl<'a> RepositoryDetails<'a> {
             workspaces,
         }
     }
-    fn print(&self) -> Result<(), cli::Error> {
+}
+
+impl CommandOutput for RepositoryDetails<'_> {
+    fn print_text(&self, _: UI) {
         let is_logged_in = self.config.token.is_some();
         let is_linked = self.config.team_id.is_some();
         let team_slug = self.config.team_slug.as_deref();
@@ -142,8 +140,6 @@ impl<'a> RepositoryDetails<'a> {
             }
             println!("- {} {}", workspace_name, GREY.apply_to(entry.path));
         }
-
-        Ok(())
     }
 }
 
@@ -175,8 +171,10 @@ impl<'a> WorkspaceDetails<'a> {
             dependencies: workspace_dep_names,
         }
     }
+}
 
-    fn print(&self) {
+impl CommandOutput for WorkspaceDetails<'_> {
+    fn print_text(&self, _: UI) {
         println!("{} depends on:", self.name);
         for dep_name in &self.dependencies {
             println!("- {}", dep_name);
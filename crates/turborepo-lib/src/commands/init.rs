@@ -0,0 +1,387 @@
+//! `turbo init` generates a starter `turbo.json` for an existing repository.
+//!
+//! The scripts of the packages in the workspace become tasks of the pipeline.
+//! Well-known scripts get a configuration that fits them: builds depend on the
+//! builds of their dependencies and cache the output directory of the
+//! framework they use, and dev servers are persistent and never cached.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Serialize;
+use turbopath::AbsoluteSystemPathBuf;
+use turborepo_repository::{
+    package_graph::{self, PackageGraph, PackageName},
+    package_json::{self, PackageJson},
+};
+use turborepo_telemetry::events::command::CommandEventBuilder;
+use turborepo_ui::BOLD;
+
+use super::CommandBase;
+use crate::{gitignore::ensure_turbo_is_gitignored, turbo_json::CONFIG_FILE};
+
+const SCHEMA_URL: &str = "https://turbo.build/schema.json";
+
+// Scripts that package managers run on their own, which shouldn't be tasks
+const LIFECYCLE_SCRIPTS: &[&str] = &[
+    "preinstall",
+    "install",
+    "postinstall",
+    "prepare",
+    "prepublish",
+    "prepublishOnly",
+    "prepack",
+    "postpack",
+    "publish",
+    "preversion",
+    "version",
+    "postversion",
+];
+
+const BUILD_SCRIPTS: &[&str] = &["build", "compile"];
+const TEST_SCRIPTS: &[&str] = &["test", "test:unit", "test:e2e", "coverage"];
+const CHECK_SCRIPTS: &[&str] = &["lint", "check-types", "typecheck", "type-check"];
+const PERSISTENT_SCRIPTS: &[&str] = &["dev", "start", "watch", "serve", "preview", "storybook"];
+const UNCACHED_SCRIPTS: &[&str] = &["clean"];
+
+// The outputs of builds that use these dependencies. Anything else is assumed
+// to build to `dist`
+const FRAMEWORK_OUTPUTS: &[(&str, &[&str])] = &[
+    ("next", &[".next/**", "!.next/cache/**"]),
+    ("nuxt", &[".output/**", ".nuxt/**"]),
+    ("@sveltejs/kit", &[".svelte-kit/**"]),
+    ("@remix-run/dev", &["build/**", "public/build/**"]),
+    ("gatsby", &["public/**"]),
+];
+const DEFAULT_OUTPUTS: &[&str] = &["dist/**"];
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{path} already exists, use --force to overwrite it")]
+    TurboJsonExists { path: AbsoluteSystemPathBuf },
+    #[error("no scripts found in the packages of this repository")]
+    NoScripts,
+    #[error("io error while initializing: {0}")]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    PackageJson(#[from] package_json::Error),
+    #[error(transparent)]
+    PackageGraph(#[from] package_graph::builder::Error),
+}
+
+#[derive(Debug, Serialize)]
+struct StarterTurboJson {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    pipeline: BTreeMap<String, StarterTask>,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StarterTask {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    depends_on: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    outputs: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cache: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    persistent: Option<bool>,
+}
+
+pub async fn run(
+    base: &CommandBase,
+    force: bool,
+    no_gitignore: bool,
+    telemetry: CommandEventBuilder,
+) -> Result<(), Error> {
+    telemetry.track_arg_usage("force", force);
+    telemetry.track_arg_usage("no-gitignore", no_gitignore);
+
+    let turbo_json_path = base.repo_root.join_component(CONFIG_FILE);
+    if !force && turbo_json_path.exists() {
+        return Err(Error::TurboJsonExists {
+            path: turbo_json_path,
+        });
+    }
+
+    let root_package_json = PackageJson::load(&base.repo_root.join_component("package.json"))?;
+    let package_graph = PackageGraph::builder(&base.repo_root, root_package_json)
+        .build()
+        .await?;
+
+    // Tasks of the root package need to be configured as `//#task`, so they
+    // are only inferred from the root when it's the only package
+    let mut packages = package_graph
+        .packages()
+        .filter(|(name, _)| matches!(name, PackageName::Other(_)))
+        .collect::<Vec<_>>();
+    packages.sort_by(|a, b| a.0.cmp(b.0));
+    let mut packages = packages
+        .into_iter()
+        .map(|(_, info)| &info.package_json)
+        .collect::<Vec<_>>();
+    let single_package = packages.is_empty();
+    if single_package {
+        packages.push(package_graph.root_package_json());
+    }
+
+    let pipeline = infer_pipeline(&packages, single_package);
+    if pipeline.is_empty() {
+        return Err(Error::NoScripts);
+    }
+
+    println!(
+        "Detected {} with {} {}",
+        base.ui
+            .apply(BOLD.apply_to(package_graph.package_manager().to_string())),
+        packages.len(),
+        if packages.len() == 1 {
+            "package"
+        } else {
+            "packages"
+        }
+    );
+    for (task, definition) in &pipeline {
+        println!(" - Added {task}{}", describe(definition));
+    }
+
+    let turbo_json = StarterTurboJson {
+        schema: SCHEMA_URL,
+        pipeline,
+    };
+    let mut contents = serde_json::to_string_pretty(&turbo_json)?;
+    contents.push('\n');
+    turbo_json_path.create_with_contents(contents)?;
+
+    if !no_gitignore {
+        ensure_turbo_is_gitignored(&base.repo_root)?;
+    }
+
+    println!(
+        "\nWrote {}. Review the inferred tasks, then run them with {}",
+        base.ui.apply(BOLD.apply_to(CONFIG_FILE)),
+        base.ui.apply(BOLD.apply_to("turbo run <task>")),
+    );
+
+    Ok(())
+}
+
+fn describe(task: &StarterTask) -> String {
+    let mut details = Vec::new();
+    if !task.depends_on.is_empty() {
+        details.push(format!("depends on {}", task.depends_on.join(", ")));
+    }
+    if !task.outputs.is_empty() {
+        details.push(format!("outputs {}", task.outputs.join(", ")));
+    }
+    if task.persistent == Some(true) {
+        details.push("persistent".to_string());
+    } else if task.cache == Some(false) {
+        details.push("not cached".to_string());
+    }
+    match details.is_empty() {
+        true => String::new(),
+        false => format!(" ({})", details.join("; ")),
+    }
+}
+
+fn infer_pipeline(
+    packages: &[&PackageJson],
+    single_package: bool,
+) -> BTreeMap<String, StarterTask> {
+    let mut scripts: HashMap<&str, Vec<&PackageJson>> = HashMap::new();
+    for package in packages {
+        for script in package.scripts.keys() {
+            scripts.entry(script.as_str()).or_default().push(*package);
+        }
+    }
+
+    let build = BUILD_SCRIPTS
+        .iter()
+        .find(|script| scripts.contains_key(*script));
+    // Scripts only some package has are usually specific to that package, but
+    // in a repo with a single package they're all there is
+    let min_packages = if packages.len() > 1 { 2 } else { 1 };
+
+    // Tasks of other packages can't be depended on in a single package
+    let dependency = |task: &str| match single_package {
+        true => task.to_string(),
+        false => format!("^{task}"),
+    };
+
+    let mut pipeline = BTreeMap::new();
+    for (script, packages_with_script) in &scripts {
+        let script = *script;
+        if is_lifecycle_script(script, &scripts) {
+            continue;
+        }
+        let known = [
+            BUILD_SCRIPTS,
+            TEST_SCRIPTS,
+            CHECK_SCRIPTS,
+            PERSISTENT_SCRIPTS,
+            UNCACHED_SCRIPTS,
+        ]
+        .iter()
+        .any(|scripts| scripts.contains(&script));
+        if !known && packages_with_script.len() < min_packages {
+            continue;
+        }
+
+        let mut task = StarterTask::default();
+        if BUILD_SCRIPTS.contains(&script) {
+            if !single_package {
+                task.depends_on.push(dependency(script));
+            }
+            task.outputs = build_outputs(packages_with_script);
+        } else if TEST_SCRIPTS.contains(&script) {
+            if let Some(build) = build {
+                task.depends_on.push(dependency(build));
+            }
+            task.outputs.push("coverage/**".to_string());
+        } else if CHECK_SCRIPTS.contains(&script) {
+            if !single_package {
+                task.depends_on.push(dependency(script));
+            }
+        } else if PERSISTENT_SCRIPTS.contains(&script) {
+            task.cache = Some(false);
+            task.persistent = Some(true);
+        } else if UNCACHED_SCRIPTS.contains(&script) {
+            task.cache = Some(false);
+        }
+        pipeline.insert(script.to_string(), task);
+    }
+    pipeline
+}
+
+// `preX` and `postX` run around `X` on their own, so they only need to be
+// tasks if `X` doesn't exist
+fn is_lifecycle_script(script: &str, scripts: &HashMap<&str, Vec<&PackageJson>>) -> bool {
+    if LIFECYCLE_SCRIPTS.contains(&script) {
+        return true;
+    }
+    ["pre", "post"].iter().any(|prefix| {
+        script
+            .strip_prefix(prefix)
+            .is_some_and(|base| scripts.contains_key(base))
+    })
+}
+
+fn build_outputs(packages: &[&PackageJson]) -> Vec<String> {
+    let mut outputs = Vec::new();
+    for package in packages {
+        let framework_outputs = FRAMEWORK_OUTPUTS
+            .iter()
+            .find(|(dependency, _)| depends_on(package, dependency))
+            .map_or(DEFAULT_OUTPUTS, |(_, outputs)| outputs);
+        for output in framework_outputs {
+            if !outputs.iter().any(|existing| existing == output) {
+                outputs.push(output.to_string());
+            }
+        }
+    }
+    outputs
+}
+
+fn depends_on(package: &PackageJson, dependency: &str) -> bool {
+    [&package.dependencies, &package.dev_dependencies]
+        .into_iter()
+        .flatten()
+        .any(|dependencies| dependencies.contains_key(dependency))
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use turborepo_repository::package_json::PackageJson;
+
+    use super::{infer_pipeline, StarterTask};
+
+    fn package(scripts: &[&str], dependencies: &[&str]) -> PackageJson {
+        PackageJson {
+            scripts: scripts
+                .iter()
+                .map(|script| (script.to_string(), String::new()))
+                .collect(),
+            dependencies: Some(
+                dependencies
+                    .iter()
+                    .map(|dependency| (dependency.to_string(), "*".to_string()))
+                    .collect(),
+            ),
+            ..Default::default()
+        }
+    }
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn test_infer_workspace_pipeline() {
+        let web = package(
+            &["build", "dev", "lint", "postinstall", "prebuild", "e2e"],
+            &["next"],
+        );
+        let ui = package(&["build", "lint", "test", "clean"], &[]);
+        let docs = package(&["build", "dev", "lint"], &["next"]);
+
+        let pipeline = infer_pipeline(&[&web, &ui, &docs], false);
+
+        let expected = BTreeMap::from([
+            (
+                "build".to_string(),
+                StarterTask {
+                    depends_on: strings(&["^build"]),
+                    outputs: strings(&[".next/**", "!.next/cache/**", "dist/**"]),
+                    ..Default::default()
+                },
+            ),
+            (
+                "clean".to_string(),
+                StarterTask {
+                    cache: Some(false),
+                    ..Default::default()
+                },
+            ),
+            (
+                "dev".to_string(),
+                StarterTask {
+                    cache: Some(false),
+                    persistent: Some(true),
+                    ..Default::default()
+                },
+            ),
+            (
+                "lint".to_string(),
+                StarterTask {
+                    depends_on: strings(&["^lint"]),
+                    ..Default::default()
+                },
+            ),
+            (
+                "test".to_string(),
+                StarterTask {
+                    depends_on: strings(&["^build"]),
+                    outputs: strings(&["coverage/**"]),
+                    ..Default::default()
+                },
+            ),
+        ]);
+        assert_eq!(pipeline, expected);
+    }
+
+    #[test]
+    fn test_infer_single_package_pipeline() {
+        let root = package(&["build", "test", "format"], &[]);
+
+        let pipeline = infer_pipeline(&[&root], true);
+
+        assert_eq!(pipeline["build"].depends_on, Vec::<String>::new());
+        assert_eq!(pipeline["test"].depends_on, strings(&["build"]));
+        assert_eq!(pipeline["format"], StarterTask::default());
+    }
+}
@@ -16,16 +16,17 @@ use dialoguer::{theme::ColorfulTheme, Confirm};
 use dirs_next::home_dir;
 #[cfg(test)]
 use rand::Rng;
+use serde::Serialize;
 use thiserror::Error;
 use turborepo_api_client::{CacheClient, Client};
 #[cfg(not(test))]
 use turborepo_ui::CYAN;
-use turborepo_ui::{BOLD, GREY, UNDERLINE};
-use turborepo_vercel_api::{CachingStatus, Space, Team};
+use turborepo_ui::{BOLD, GREY, UI, UNDERLINE};
+use turborepo_vercel_api::{CachingStatus, Space, Team, User};
 
 use crate::{
     cli::LinkTarget,
-    commands::CommandBase,
+    commands::{CommandBase, CommandOutput},
     config,
     gitignore::ensure_turbo_is_gitignored,
     rewrite_json::{self, set_path, unset_path},
@@ -60,6 +61,8 @@ pub enum Error {
     TeamsRequest(#[source] turborepo_api_client::Error),
     #[error("could not get spaces information")]
     SpacesRequest(#[source] turborepo_api_client::Error),
+    #[error("found {0} spaces, run `turbo link --target spaces` without --yes to pick one")]
+    AmbiguousSpace(usize),
     #[error("could not get caching status")]
     CachingStatusNotFound(#[source] turborepo_api_client::Error),
     #[error("Failed to open browser. Please visit {0} to enable Remote Caching")]
@@ -76,6 +79,61 @@ pub enum Error {
     },
     #[error(transparent)]
     Rewrite(#[from] rewrite_json::RewriteError),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+/// What the repository was linked to.
+#[derive(Serialize)]
+#[serde(tag = "target", rename_all = "kebab-case")]
+enum Linked<'a> {
+    #[serde(rename_all = "camelCase")]
+    RemoteCache {
+        team_id: &'a str,
+        team_name: &'a str,
+    },
+    #[serde(rename_all = "camelCase")]
+    Spaces {
+        #[serde(skip)]
+        repo_root: &'a str,
+        team_id: &'a str,
+        space_id: &'a str,
+        space_name: &'a str,
+    },
+}
+
+impl CommandOutput for Linked<'_> {
+    fn print_text(&self, ui: UI) {
+        match self {
+            Linked::RemoteCache { team_name, .. } => println!(
+                "
+    {}  Turborepo CLI authorized for {}
+
+    {}
+        ",
+                ui.rainbow(">>> Success!"),
+                ui.apply(BOLD.apply_to(team_name)),
+                GREY.apply_to("To disable Remote Caching, run `npx turbo unlink`")
+            ),
+            Linked::Spaces {
+                repo_root,
+                space_name,
+                ..
+            } => println!(
+                "
+    {} {} linked to {}
+
+    {}
+        ",
+                ui.rainbow(">>> Success!"),
+                ui.apply(BOLD.apply_to(repo_root)),
+                ui.apply(BOLD.apply_to(space_name)),
+                GREY.apply_to(
+                    "To remove Spaces integration, run `npx turbo unlink --target spaces`"
+                )
+            ),
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -106,6 +164,7 @@ pub(crate) const SPACES_URL: &str = "https://vercel.com/docs/workflow-collaborat
 /// * `team_id`: ID for team selected
 /// * `token`: API token
 /// * `selected_team`: The team selected
+/// * `yes`: Enable caching without prompting
 ///
 /// returns: Result<(), Error>
 pub(crate) async fn verify_caching_enabled<'a>(
@@ -113,6 +172,7 @@ pub(crate) async fn verify_caching_enabled<'a>(
     team_id: &str,
     token: &str,
     selected_team: Option<SelectedTeam<'a>>,
+    yes: bool,
 ) -> Result<(), Error> {
     let team_slug = selected_team.as_ref().and_then(|team| match team {
         SelectedTeam::Team(team) => Some(team.slug.as_str()),
@@ -126,7 +186,7 @@ pub(crate) async fn verify_caching_enabled<'a>(
 
     match response.status {
         CachingStatus::Disabled => {
-            let should_enable = should_enable_caching()?;
+            let should_enable = yes || should_enable_caching()?;
             if should_enable {
                 match selected_team {
                     Some(SelectedTeam::Team(team)) if team.is_owner() => {
@@ -166,6 +226,8 @@ pub(crate) async fn verify_caching_enabled<'a>(
 pub async fn link(
     base: &mut CommandBase,
     modify_gitignore: bool,
+    scope: Option<&str>,
+    yes: bool,
     target: LinkTarget,
 ) -> Result<(), Error> {
     let homedir_path = home_dir().ok_or_else(|| Error::HomeDirectoryNotFound)?;
@@ -178,17 +240,19 @@ pub async fn link(
 
     match target {
         LinkTarget::RemoteCache => {
-            println!(
-                ">>> Remote Caching
+            if !base.json() {
+                println!(
+                    ">>> Remote Caching
 
     {}
       For more info, see {}
       ",
-                REMOTE_CACHING_INFO,
-                base.ui.apply(UNDERLINE.apply_to(REMOTE_CACHING_URL))
-            );
+                    REMOTE_CACHING_INFO,
+                    base.ui.apply(UNDERLINE.apply_to(REMOTE_CACHING_URL))
+                );
+            }
 
-            if !should_link_remote_cache(base, &repo_root_with_tilde)? {
+            if !yes && !should_link_remote_cache(base, &repo_root_with_tilde)? {
                 return Err(Error::NotLinking);
             }
 
@@ -208,15 +272,26 @@ pub async fn link(
                 .await
                 .map_err(Error::TeamsRequest)?;
 
-            let selected_team = select_team(base, &teams_response.teams, user_display_name)?;
+            let selected_team = match scope {
+                Some(scope) => find_scope(scope, &user_response.user, &teams_response.teams)?,
+                // The personal account is the default choice in the prompt
+                None if yes => SelectedTeam::User,
+                None => select_team(base, &teams_response.teams, user_display_name)?,
+            };
 
             let team_id = match selected_team {
                 SelectedTeam::User => user_response.user.id.as_str(),
                 SelectedTeam::Team(team) => team.id.as_str(),
             };
 
-            verify_caching_enabled(&api_client, team_id, token, Some(selected_team.clone()))
-                .await?;
+            verify_caching_enabled(
+                &api_client,
+                team_id,
+                token,
+                Some(selected_team.clone()),
+                yes,
+            )
+            .await?;
 
             let local_config_path = base.local_config_path();
             let before = local_config_path
@@ -263,28 +338,24 @@ pub async fn link(
                 })?;
             }
 
-            println!(
-                "
-    {}  Turborepo CLI authorized for {}
-
-    {}
-        ",
-                base.ui.rainbow(">>> Success!"),
-                base.ui.apply(BOLD.apply_to(chosen_team_name)),
-                GREY.apply_to("To disable Remote Caching, run `npx turbo unlink`")
-            );
+            base.print_output(&Linked::RemoteCache {
+                team_id,
+                team_name: chosen_team_name,
+            })?;
             Ok(())
         }
         LinkTarget::Spaces => {
-            println!(
-                ">>> Vercel Spaces (Beta)
+            if !base.json() {
+                println!(
+                    ">>> Vercel Spaces (Beta)
 
       For more info, see {}
       ",
-                base.ui.apply(UNDERLINE.apply_to(SPACES_URL))
-            );
+                    base.ui.apply(UNDERLINE.apply_to(SPACES_URL))
+                );
+            }
 
-            if !should_link_spaces(base, &repo_root_with_tilde)? {
+            if !yes && !should_link_spaces(base, &repo_root_with_tilde)? {
                 return Err(Error::NotLinking);
             }
 
@@ -304,7 +375,12 @@ pub async fn link(
                 .await
                 .map_err(Error::TeamsRequest)?;
 
-            let selected_team = select_team(base, &teams_response.teams, user_display_name)?;
+            let selected_team = match scope {
+                Some(scope) => find_scope(scope, &user_response.user, &teams_response.teams)?,
+                // The personal account is the default choice in the prompt
+                None if yes => SelectedTeam::User,
+                None => select_team(base, &teams_response.teams, user_display_name)?,
+            };
 
             let team_id = match selected_team {
                 SelectedTeam::User => user_response.user.id.as_str(),
@@ -316,7 +392,11 @@ pub async fn link(
                 .await
                 .map_err(Error::SpacesRequest)?;
 
-            let selected_space = select_space(base, &spaces_response.spaces)?;
+            let selected_space = if yes {
+                only_space(&spaces_response.spaces)?
+            } else {
+                select_space(base, &spaces_response.spaces)?
+            };
 
             // print result from selected_space
             let SelectedSpace::Space(space) = selected_space;
@@ -354,19 +434,12 @@ pub async fn link(
                     error,
                 })?;
 
-            println!(
-                "
-    {} {} linked to {}
-
-    {}
-        ",
-                base.ui.rainbow(">>> Success!"),
-                base.ui.apply(BOLD.apply_to(&repo_root_with_tilde)),
-                base.ui.apply(BOLD.apply_to(&space.name)),
-                GREY.apply_to(
-                    "To remove Spaces integration, run `npx turbo unlink --target spaces`"
-                )
-            );
+            base.print_output(&Linked::Spaces {
+                repo_root: &repo_root_with_tilde,
+                team_id,
+                space_id: &space.id,
+                space_name: &space.name,
+            })?;
 
             Ok(())
         }
@@ -386,6 +459,28 @@ fn should_enable_caching() -> Result<bool, Error> {
         .map_err(Error::UserCanceled)
 }
 
+/// Resolves `--scope` against the user's username or id and the slugs or ids of
+/// their teams
+fn find_scope<'a>(scope: &str, user: &User, teams: &'a [Team]) -> Result<SelectedTeam<'a>, Error> {
+    if scope == user.username || scope == user.id {
+        return Ok(SelectedTeam::User);
+    }
+
+    teams
+        .iter()
+        .find(|team| team.slug == scope || team.id == scope)
+        .map(SelectedTeam::Team)
+        .ok_or_else(|| Error::TeamNotFound(scope.to_string()))
+}
+
+// Without a prompt we can only pick a space when there's exactly one
+fn only_space(spaces: &[Space]) -> Result<SelectedSpace<'_>, Error> {
+    match spaces {
+        [space] => Ok(SelectedSpace::Space(space)),
+        _ => Err(Error::AmbiguousSpace(spaces.len())),
+    }
+}
+
 #[cfg(test)]
 fn select_team<'a>(
     _: &CommandBase,
@@ -649,7 +744,7 @@ mod test {
             )
             .unwrap();
 
-        link::link(&mut base, false, LinkTarget::RemoteCache)
+        link::link(&mut base, false, None, false, LinkTarget::RemoteCache)
             .await
             .unwrap();
 
@@ -667,6 +762,79 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_link_remote_cache_with_scope() -> Result<()> {
+        // user config
+        let user_config_file = NamedTempFile::new().unwrap();
+        fs::write(user_config_file.path(), r#"{ "token": "hello" }"#).unwrap();
+
+        // repo
+        let repo_root_tmp_dir = TempDir::new().unwrap();
+        let handle = repo_root_tmp_dir.path();
+        let repo_root = AbsoluteSystemPathBuf::try_from(handle).unwrap();
+        repo_root
+            .join_component("turbo.json")
+            .create_with_contents("{}")
+            .unwrap();
+        repo_root
+            .join_component("package.json")
+            .create_with_contents("{}")
+            .unwrap();
+
+        let port = port_scanner::request_open_port().unwrap();
+        let handle = tokio::spawn(start_test_server(port));
+        let mut base = CommandBase {
+            global_config_path: Some(
+                AbsoluteSystemPathBuf::try_from(user_config_file.path().to_path_buf()).unwrap(),
+            ),
+            repo_root: repo_root.clone(),
+            ui: UI::new(false),
+            config: OnceCell::new(),
+            args: Args::default(),
+            version: "",
+        };
+        base.config
+            .set(
+                TurborepoConfigBuilder::new(&base)
+                    .with_api_url(Some(format!("http://localhost:{}", port)))
+                    .with_login_url(Some(format!("http://localhost:{}", port)))
+                    .with_token(Some("token".to_string()))
+                    .build()
+                    .unwrap(),
+            )
+            .unwrap();
+
+        let missing = link::link(
+            &mut base,
+            false,
+            Some("not-a-team"),
+            true,
+            LinkTarget::RemoteCache,
+        )
+        .await;
+        assert!(matches!(missing, Err(link::Error::TeamNotFound(_))));
+
+        link::link(
+            &mut base,
+            false,
+            Some(turborepo_vercel_api_mock::EXPECTED_TEAM_SLUG),
+            true,
+            LinkTarget::RemoteCache,
+        )
+        .await
+        .unwrap();
+
+        handle.abort();
+
+        let updated_config = TurborepoConfigBuilder::new(&base).build().unwrap();
+        assert_eq!(
+            updated_config.team_id(),
+            Some(turborepo_vercel_api_mock::EXPECTED_TEAM_ID)
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_link_spaces() {
         // user config
@@ -724,7 +892,7 @@ mod test {
         )
         .unwrap();
 
-        link::link(&mut base, false, LinkTarget::Spaces)
+        link::link(&mut base, false, None, false, LinkTarget::Spaces)
             .await
             .unwrap();
 
@@ -1,10 +1,18 @@
-use turborepo_api_client::APIClient;
+use turborepo_api_client::{APIClient, Client};
 use turborepo_auth::{
     login as auth_login, sso_login as auth_sso_login, DefaultLoginServer, LoginOptions, Token,
 };
 use turborepo_telemetry::events::command::{CommandEventBuilder, LoginMethod};
+use turborepo_ui::{BOLD, CYAN, GREY};
+use turborepo_vercel_api::Team;
 
-use crate::{cli::Error, commands::CommandBase, config, rewrite_json::set_path};
+use crate::{
+    cli::Error,
+    commands::CommandBase,
+    config,
+    gitignore::ensure_turbo_is_gitignored,
+    rewrite_json::{set_path, unset_path},
+};
 
 pub async fn sso_login(
     base: &mut CommandBase,
@@ -25,11 +33,68 @@ pub async fn sso_login(
 
     let token = auth_sso_login(&options).await?;
 
-    // Don't write to disk if the token is already there
-    if matches!(token, Token::Existing(..)) {
-        return Ok(());
+    // Only write to disk if this is a new token
+    if matches!(token, Token::New(..)) {
+        write_token(base, &token)?;
+    }
+
+    // The SSO token is scoped to the team it was issued for, so we persist that
+    // team as well to avoid needing to run `turbo link` afterwards.
+    let teams = api_client
+        .get_teams(token.into_inner())
+        .await
+        .map_err(turborepo_auth::Error::from)?
+        .teams;
+    let team = select_sso_team(&teams, sso_team)
+        .ok_or_else(|| turborepo_auth::Error::SSOTeamNotFound(sso_team.to_owned()))?;
+
+    println!(
+        "\n{}",
+        ui.apply(BOLD.apply_to("Teams available to this token:"))
+    );
+    for available_team in &teams {
+        let line = format!("  {} ({})", available_team.name, available_team.slug);
+        if available_team.id == team.id {
+            println!("{}", ui.apply(CYAN.apply_to(format!("{line} [selected]"))));
+        } else {
+            println!("{}", ui.apply(GREY.apply_to(line)));
+        }
+    }
+
+    // Only scope the repository if we're actually in one, otherwise we'd leave a
+    // stray `.turbo/config.json` wherever `turbo login` was run.
+    if base.repo_root.join_component("turbo.json").exists() {
+        write_team_id(base, &team.id)?;
+        ensure_turbo_is_gitignored(&base.repo_root).map_err(|error| {
+            config::Error::FailedToSetConfig {
+                config_path: base.repo_root.join_component(".gitignore"),
+                error,
+            }
+        })?;
+        println!(
+            "\n{} is now linked to {}",
+            base.repo_root,
+            ui.apply(BOLD.apply_to(&team.name))
+        );
+    } else {
+        println!(
+            "\nRun {} from your Turborepo to link it to {}",
+            ui.apply(BOLD.apply_to("turbo link")),
+            ui.apply(BOLD.apply_to(&team.name))
+        );
     }
 
+    Ok(())
+}
+
+// The `--sso-team` flag accepts either the slug or the id of a team
+fn select_sso_team<'a>(teams: &'a [Team], sso_team: &str) -> Option<&'a Team> {
+    teams
+        .iter()
+        .find(|team| team.slug == sso_team || team.id == sso_team)
+}
+
+fn write_token(base: &CommandBase, token: &Token) -> Result<(), Error> {
     let global_config_path = base.global_config_path()?;
     let before = global_config_path
         .read_existing_to_string_or(Ok("{}"))
@@ -38,7 +103,7 @@ pub async fn sso_login(
             error: e,
         })?;
 
-    let after = set_path(&before, &["token"], &format!("\"{}\"", token.into_inner()))?;
+    let after = set_token(base, &before, token)?;
 
     global_config_path
         .ensure_dir()
@@ -57,6 +122,69 @@ pub async fn sso_login(
     Ok(())
 }
 
+// Logging into a named profile stores the token, along with any endpoints
+// passed on the command line, under `profiles.<name>` and leaves the default
+// token as is.
+fn set_token(base: &CommandBase, before: &str, token: &Token) -> Result<String, Error> {
+    let token = format!("\"{}\"", token.into_inner());
+    let Some(profile) = base.config()?.auth_profile() else {
+        return Ok(set_path(before, &["token"], &token)?);
+    };
+
+    let mut after = set_path(before, &["profiles", profile, "token"], &token)?;
+    if let Some(api_url) = &base.args().api {
+        after = set_path(
+            &after,
+            &["profiles", profile, "apiUrl"],
+            &format!("\"{}\"", api_url),
+        )?;
+    }
+    if let Some(login_url) = &base.args().login {
+        after = set_path(
+            &after,
+            &["profiles", profile, "loginUrl"],
+            &format!("\"{}\"", login_url),
+        )?;
+    }
+
+    Ok(after)
+}
+
+fn write_team_id(base: &CommandBase, team_id: &str) -> Result<(), Error> {
+    let local_config_path = base.local_config_path();
+    let before = local_config_path
+        .read_existing_to_string_or(Ok("{}"))
+        .map_err(|e| config::Error::FailedToReadConfig {
+            config_path: local_config_path.clone(),
+            error: e,
+        })?;
+
+    let no_preexisting_id = unset_path(&before, &["teamid"], false)?.unwrap_or(before);
+    let no_preexisting_slug =
+        unset_path(&no_preexisting_id, &["teamslug"], false)?.unwrap_or(no_preexisting_id);
+    let after = set_path(
+        &no_preexisting_slug,
+        &["teamId"],
+        &format!("\"{}\"", team_id),
+    )?;
+
+    local_config_path
+        .ensure_dir()
+        .map_err(|e| config::Error::FailedToSetConfig {
+            config_path: local_config_path.clone(),
+            error: e,
+        })?;
+
+    local_config_path.create_with_contents(after).map_err(|e| {
+        config::Error::FailedToSetConfig {
+            config_path: local_config_path.clone(),
+            error: e,
+        }
+    })?;
+
+    Ok(())
+}
+
 pub async fn login(
     base: &mut CommandBase,
     telemetry: CommandEventBuilder,
@@ -87,7 +215,7 @@ pub async fn login(
             config_path: global_config_path.clone(),
             error: e,
         })?;
-    let after = set_path(&before, &["token"], &format!("\"{}\"", token.into_inner()))?;
+    let after = set_token(base, &before, &token)?;
 
     global_config_path
         .ensure_dir()
@@ -132,3 +260,29 @@ impl<'a> Drop for LoginTelemetry<'a> {
         self.telemetry.track_login_success(self.success);
     }
 }
+
+#[cfg(test)]
+mod test {
+    use turborepo_vercel_api::{Membership, Role, Team};
+
+    use super::select_sso_team;
+
+    fn team(id: &str, slug: &str) -> Team {
+        Team {
+            id: id.to_string(),
+            slug: slug.to_string(),
+            name: slug.to_string(),
+            created_at: 0,
+            created: chrono::Utc::now(),
+            membership: Membership::new(Role::Member),
+        }
+    }
+
+    #[test]
+    fn test_select_sso_team() {
+        let teams = vec![team("team_1", "acme"), team("team_2", "globex")];
+        assert_eq!(select_sso_team(&teams, "globex").unwrap().id, "team_2");
+        assert_eq!(select_sso_team(&teams, "team_1").unwrap().slug, "acme");
+        assert!(select_sso_team(&teams, "initech").is_none());
+    }
+}
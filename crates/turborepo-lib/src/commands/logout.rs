@@ -12,6 +12,7 @@ pub async fn logout(
         ui: base.ui,
         api_client: base.api_client()?,
         invalidate,
+        profile: base.config()?.auth_profile().map(str::to_owned),
     })
     .await
     .map_err(Error::from)
@@ -1,8 +1,10 @@
-use std::cell::OnceCell;
+use std::{cell::OnceCell, path::PathBuf, time::Duration};
 
+use serde::Serialize;
+use tracing::warn;
 use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
-use turborepo_api_client::{APIAuth, APIClient};
-use turborepo_auth::{TURBO_TOKEN_DIR, TURBO_TOKEN_FILE};
+use turborepo_api_client::{APIAuth, APIClient, PreflightConfig, RetryConfig, Timeouts, TlsConfig};
+use turborepo_auth::{exchange_oidc_token, OidcProvider, TURBO_TOKEN_DIR, TURBO_TOKEN_FILE};
 use turborepo_dirs::config_dir;
 use turborepo_ui::UI;
 
@@ -12,17 +14,41 @@ use crate::{
 };
 
 pub(crate) mod bin;
+pub(crate) mod clean;
+pub(crate) mod completion;
 pub(crate) mod daemon;
+pub(crate) mod doctor;
 pub(crate) mod generate;
 pub(crate) mod info;
+pub(crate) mod init;
 pub(crate) mod link;
 pub(crate) mod login;
 pub(crate) mod logout;
+pub(crate) mod picker;
 pub(crate) mod prune;
 pub(crate) mod run;
 pub(crate) mod telemetry;
 pub(crate) mod unlink;
 
+/// The result of a command. With `--json` it's printed as JSON instead of
+/// text, so that tools wrapping turbo don't have to parse the text.
+pub trait CommandOutput: Serialize {
+    fn print_text(&self, ui: UI);
+}
+
+/// Prints `output` for commands that run without a `CommandBase`.
+pub fn print_output(
+    output: &impl CommandOutput,
+    json: bool,
+    ui: UI,
+) -> Result<(), serde_json::Error> {
+    match json {
+        true => println!("{}", serde_json::to_string_pretty(output)?),
+        false => output.print_text(ui),
+    }
+    Ok(())
+}
+
 #[derive(Debug)]
 pub struct CommandBase {
     pub repo_root: AbsoluteSystemPathBuf,
@@ -65,7 +91,11 @@ impl CommandBase {
             .with_login_url(self.args.login.clone())
             .with_team_slug(self.args.team.clone())
             .with_token(self.args.token.clone())
+            .with_preflight_requests(self.args.preflight_requests.clone())
+            .with_auth_profile(self.args.auth_profile.clone())
             .with_timeout(self.args.remote_cache_timeout)
+            .with_connect_timeout(self.args.remote_cache_connect_timeout)
+            .with_upload_timeout(self.args.remote_cache_upload_timeout)
             .build()
     }
 
@@ -110,21 +140,97 @@ impl CommandBase {
         }))
     }
 
+    /// Exchanges the CI provider's OIDC token for a short-lived remote cache
+    /// token. Failing to do so isn't fatal as the run can still continue
+    /// without remote caching.
+    pub async fn oidc_api_auth(&self, api_client: &APIClient) -> Option<APIAuth> {
+        let provider = OidcProvider::detect()?;
+        let config = self.config().ok()?;
+        let team_id = config.team_id();
+        let team_slug = config.team_slug();
+
+        match exchange_oidc_token(api_client, &provider, config.api_url(), team_id, team_slug).await
+        {
+            Ok(response) => Some(APIAuth {
+                team_id: team_id.map(|s| s.to_string()).or(response.team_id),
+                token: response.token,
+                team_slug: team_slug.map(|s| s.to_string()),
+            }),
+            Err(e) => {
+                warn!("unable to exchange {} OIDC token: {e}", provider.name());
+                None
+            }
+        }
+    }
+
     pub fn args(&self) -> &Args {
         &self.args
     }
 
+    /// Whether the output should be printed as JSON. Commands shouldn't print
+    /// anything else to stdout then.
+    pub fn json(&self) -> bool {
+        self.args.json
+    }
+
+    pub fn print_output(&self, output: &impl CommandOutput) -> Result<(), serde_json::Error> {
+        print_output(output, self.args.json, self.ui)
+    }
+
     pub fn api_client(&self) -> Result<APIClient, ConfigError> {
         let config = self.config()?;
         let args = self.args();
 
         let api_url = config.api_url();
-        let timeout = config.timeout();
+        let timeouts = Timeouts {
+            connect: config.connect_timeout(),
+            read: config.timeout(),
+            upload: config.upload_timeout(),
+        };
+
+        let retry_config = RetryConfig {
+            max_attempts: config.retry_attempts(),
+            min_backoff: Duration::from_secs(config.retry_backoff()),
+            jitter: config.retry_jitter(),
+            ..RetryConfig::default()
+        };
 
-        APIClient::new(api_url, timeout, self.version, args.preflight)
+        let tls_config = self.tls_config()?;
+
+        let preflight = if args.preflight {
+            PreflightConfig::all()
+        } else {
+            config
+                .preflight_requests()
+                .map(|requests| PreflightConfig::from_requests(requests.iter().map(String::as_str)))
+                .transpose()
+                .map_err(ConfigError::ApiClient)?
+                .unwrap_or_default()
+        };
+
+        APIClient::new_with_timeouts(api_url, timeouts, &tls_config, self.version, preflight)
+            .map(|client| client.with_retry_config(retry_config))
             .map_err(ConfigError::ApiClient)
     }
 
+    /// The TLS settings for every HTTP client of turbo, so requests to log
+    /// sinks and remote workers go through the same proxies and gateways as
+    /// the ones to the API.
+    pub fn tls_config(&self) -> Result<TlsConfig, ConfigError> {
+        let config = self.config()?;
+        let mut tls_config = TlsConfig::from_env();
+        if let Some(client_cert) = config.client_cert() {
+            // Relative paths are resolved from the repository root so they can be shared
+            // in turbo.json
+            let resolve = |path: &str| -> PathBuf {
+                AbsoluteSystemPathBuf::from_unknown(&self.repo_root, path).into()
+            };
+            tls_config = tls_config
+                .with_client_identity(resolve(client_cert), config.client_key().map(resolve));
+        }
+        Ok(tls_config)
+    }
+
     /// Current working directory for the turbo command
     pub fn cwd(&self) -> &AbsoluteSystemPath {
         // Earlier in execution
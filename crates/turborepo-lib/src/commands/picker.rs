@@ -0,0 +1,143 @@
+//! The picker for `turbo run --interactive`.
+//!
+//! Instead of passing tasks and filters as arguments, they're picked from
+//! searchable lists of the tasks in `turbo.json` and the packages of the
+//! workspace. Picking no packages runs the tasks in all of them.
+
+use std::io::IsTerminal;
+
+use console::Style;
+use dialoguer::{theme::ColorfulTheme, FuzzySelect};
+use turborepo_ui::{BOLD, CYAN};
+
+use super::{completion, CommandBase};
+use crate::cli;
+
+/// The tasks and packages that were picked.
+#[derive(Debug, PartialEq)]
+pub struct Picked {
+    pub tasks: Vec<String>,
+    pub packages: Vec<String>,
+}
+
+pub async fn pick(base: &CommandBase) -> Result<Picked, cli::Error> {
+    if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        return Err(cli::Error::InteractiveWithoutTerminal);
+    }
+
+    let tasks = completion::tasks(base)?;
+    let packages = completion::packages(base).await?;
+
+    let tasks = pick_many(base, "Which tasks do you want to run?", tasks)?;
+    // Running no tasks would fall through to `turbo run` without arguments
+    if tasks.is_empty() {
+        return Err(cli::Error::NothingPicked);
+    }
+    let packages = pick_many(
+        base,
+        "Which packages do you want to run them in? Pick none to run them in all packages",
+        packages,
+    )?;
+
+    Ok(Picked { tasks, packages })
+}
+
+// `dialoguer` can't filter the items of a multi-select, so the fuzzy select
+// is shown again after every pick until "Done" is picked
+fn pick_many(
+    base: &CommandBase,
+    prompt: &str,
+    items: Vec<String>,
+) -> Result<Vec<String>, cli::Error> {
+    let theme = ColorfulTheme {
+        active_item_style: Style::new().cyan().bold(),
+        active_item_prefix: Style::new().cyan().bold().apply_to(">".to_string()),
+        prompt_prefix: Style::new().dim().bold().apply_to("?".to_string()),
+        values_style: Style::new().cyan(),
+        ..ColorfulTheme::default()
+    };
+    let prompt = format!(
+        "{}\n  {}",
+        base.ui.apply(BOLD.apply_to(prompt)),
+        base.ui.apply(CYAN.apply_to(
+            "[Use arrows to move, type to filter, enter to toggle, pick \"Done\" to continue]"
+        ))
+    );
+
+    let mut selection = Selection::new(items);
+    let mut default = 0;
+    loop {
+        let picked = FuzzySelect::with_theme(&theme)
+            .with_prompt(&prompt)
+            .items(&selection.labels())
+            .default(default)
+            .interact()
+            .map_err(cli::Error::PickerCanceled)?;
+        if picked == 0 {
+            return Ok(selection.into_selected());
+        }
+        selection.toggle(picked - 1);
+        default = picked;
+    }
+}
+
+struct Selection {
+    items: Vec<(String, bool)>,
+}
+
+impl Selection {
+    fn new(items: Vec<String>) -> Self {
+        Self {
+            items: items.into_iter().map(|item| (item, false)).collect(),
+        }
+    }
+
+    fn toggle(&mut self, index: usize) {
+        if let Some((_, selected)) = self.items.get_mut(index) {
+            *selected = !*selected;
+        }
+    }
+
+    // The first label is the one to finish picking
+    fn labels(&self) -> Vec<String> {
+        let count = self.items.iter().filter(|(_, selected)| *selected).count();
+        std::iter::once(format!("Done ({count} selected)"))
+            .chain(self.items.iter().map(|(item, selected)| match selected {
+                true => format!("[x] {item}"),
+                false => format!("[ ] {item}"),
+            }))
+            .collect()
+    }
+
+    fn into_selected(self) -> Vec<String> {
+        self.items
+            .into_iter()
+            .filter_map(|(item, selected)| selected.then_some(item))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Selection;
+
+    #[test]
+    fn test_selection() {
+        let mut selection = Selection::new(vec!["build".to_string(), "lint".to_string()]);
+        assert_eq!(
+            selection.labels(),
+            vec!["Done (0 selected)", "[ ] build", "[ ] lint"]
+        );
+
+        selection.toggle(1);
+        selection.toggle(0);
+        selection.toggle(0);
+        selection.toggle(1);
+        selection.toggle(1);
+        assert_eq!(
+            selection.labels(),
+            vec!["Done (1 selected)", "[ ] build", "[x] lint"]
+        );
+        assert_eq!(selection.into_selected(), vec!["lint"]);
+    }
+}
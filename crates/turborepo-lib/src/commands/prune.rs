@@ -1,56 +1,99 @@
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
-use std::sync::OnceLock;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::OnceLock,
+};
 
 use lazy_static::lazy_static;
 use miette::Diagnostic;
-use tracing::trace;
+use serde::Serialize;
+use tracing::{trace, warn};
 use turbopath::{
-    AbsoluteSystemPathBuf, AnchoredSystemPath, AnchoredSystemPathBuf, RelativeUnixPath,
+    AbsoluteSystemPath, AbsoluteSystemPathBuf, AnchoredSystemPath, AnchoredSystemPathBuf,
+    RelativeUnixPath, RelativeUnixPathBuf,
 };
+use turborepo_lockfiles::{Lockfile, Package};
 use turborepo_repository::{
     package_graph::{self, PackageGraph, PackageName, PackageNode},
     package_json::PackageJson,
+    package_manager::PackageManager,
 };
+use turborepo_scm::SCM;
 use turborepo_telemetry::events::command::CommandEventBuilder;
-use turborepo_ui::BOLD;
+use turborepo_ui::{BOLD, UI};
 
-use super::CommandBase;
+use super::{CommandBase, CommandOutput};
 use crate::turbo_json::RawTurboJson;
 
 pub const DEFAULT_OUTPUT_DIR: &str = "out";
+pub const METADATA_FILE: &str = ".turbo-prune-meta.json";
 
 #[derive(Debug, thiserror::Error, Diagnostic)]
 pub enum Error {
     #[error("io error while pruning: {0}")]
+    #[diagnostic(code(TURBO_PRUNE_001))]
     Io(#[from] std::io::Error),
     #[error("fs error while pruning: {0}")]
+    #[diagnostic(code(TURBO_PRUNE_002))]
     Fs(#[from] turborepo_fs::Error),
     #[error("json error while pruning: {0}")]
+    #[diagnostic(code(TURBO_PRUNE_003))]
     Json(#[from] serde_json::Error),
     #[error("path error while pruning: {0}")]
+    #[diagnostic(code(TURBO_PRUNE_004))]
     Path(#[from] turbopath::PathError),
     #[error(transparent)]
     #[diagnostic(transparent)]
     TurboJsonParser(#[from] crate::turbo_json::parser::Error),
     #[error(transparent)]
+    #[diagnostic(transparent)]
     PackageJson(#[from] turborepo_repository::package_json::Error),
     #[error(transparent)]
+    #[diagnostic(transparent)]
     PackageGraph(#[from] package_graph::Error),
     #[error(transparent)]
+    #[diagnostic(code(TURBO_PRUNE_007))]
     Lockfile(#[from] turborepo_lockfiles::Error),
     #[error("turbo doesn't support workspaces at file system root")]
+    #[diagnostic(code(TURBO_PRUNE_008))]
     WorkspaceAtFilesystemRoot,
     #[error("at least one target must be specified")]
+    #[diagnostic(code(TURBO_PRUNE_009))]
     NoWorkspaceSpecified,
     #[error("invalid scope: package {0} not found")]
+    #[diagnostic(code(TURBO_PRUNE_010))]
     MissingWorkspace(PackageName),
     #[error("Cannot prune without parsed lockfile")]
+    #[diagnostic(code(TURBO_PRUNE_011))]
     MissingLockfile,
-    #[error("Prune is not supported for Bun")]
+    #[error("Prune is only supported for Bun when using the text bun.lock lockfile")]
+    #[diagnostic(code(TURBO_PRUNE_012))]
     BunUnsupported,
+    #[error(
+        "{workspace} depends on {dependency} which points to {path}, outside of the repository \
+         root"
+    )]
+    #[diagnostic(
+        code(TURBO_PRUNE_013),
+        help(
+            "local dependencies can only be included in the pruned output if they are located \
+             inside of the repository"
+        )
+    )]
+    LocalDependencyOutsideRepo {
+        workspace: PackageName,
+        dependency: String,
+        path: AbsoluteSystemPathBuf,
+    },
+    #[error("--prod is not supported for {0}")]
+    #[diagnostic(code(TURBO_PRUNE_014))]
+    ProdUnsupported(PackageManager),
 }
 
+// Version protocols that reference a path on disk instead of a registry
+const LOCAL_DEPENDENCY_PROTOCOLS: &[&str] = &["file:", "link:", "portal:"];
+
 // Files that should be copied from root and if they're required for install
 lazy_static! {
     static ref ADDITIONAL_FILES: Vec<(&'static RelativeUnixPath, Option<CopyDestination>)> = vec![
@@ -63,7 +106,24 @@ lazy_static! {
             RelativeUnixPath::new(".yarnrc.yml").unwrap(),
             Some(CopyDestination::Docker)
         ),
+        (
+            RelativeUnixPath::new(".yarnrc").unwrap(),
+            Some(CopyDestination::Docker)
+        ),
+        (
+            RelativeUnixPath::new(".pnpmfile.cjs").unwrap(),
+            Some(CopyDestination::Docker)
+        ),
     ];
+    // Registry and auth configuration that can live next to a workspace's
+    // package.json and needs to be present for an install of the pruned output
+    static ref WORKSPACE_INSTALL_FILES: Vec<&'static RelativeUnixPath> = vec![
+        RelativeUnixPath::new(".npmrc").unwrap(),
+        RelativeUnixPath::new(".yarnrc").unwrap(),
+        RelativeUnixPath::new(".yarnrc.yml").unwrap(),
+    ];
+    static ref YARN_CACHE_DIR: &'static RelativeUnixPath =
+        RelativeUnixPath::new(".yarn/cache").unwrap();
     static ref ADDITIONAL_DIRECTORIES: Vec<(&'static RelativeUnixPath, Option<CopyDestination>)> = vec![
         (
             RelativeUnixPath::new(".yarn/plugins").unwrap(),
@@ -86,30 +146,75 @@ fn turbo_json() -> &'static AnchoredSystemPath {
     PATH.get_or_init(|| AnchoredSystemPath::new("turbo.json").unwrap())
 }
 
+// Provenance of a pruned output
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PruneMetadata<'a> {
+    scope: &'a [String],
+    sha: Option<String>,
+    branch: Option<String>,
+    dirty: Option<bool>,
+}
+
+impl<'a> PruneMetadata<'a> {
+    // Collects the git state of the repository that is pruned. If the
+    // repository isn't a git repository the fields are left empty.
+    fn new(root: &AbsoluteSystemPath, scope: &'a [String]) -> Self {
+        let scm = SCM::new(root);
+        if scm.is_manual() {
+            return Self {
+                scope,
+                sha: None,
+                branch: None,
+                dirty: None,
+            };
+        }
+        Self {
+            scope,
+            sha: scm.get_current_sha(root).ok(),
+            branch: scm
+                .get_current_branch(root)
+                .ok()
+                .filter(|branch| !branch.is_empty()),
+            dirty: scm.is_dirty(root).ok(),
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn prune(
     base: &CommandBase,
     scope: &[String],
     docker: bool,
+    prod: bool,
+    emit_dockerfile: bool,
+    emit_metadata: bool,
     output_dir: &str,
     telemetry: CommandEventBuilder,
 ) -> Result<(), Error> {
     telemetry.track_arg_usage("docker", docker);
+    telemetry.track_arg_usage("prod", prod);
+    telemetry.track_arg_usage("emit-dockerfile", emit_dockerfile);
+    telemetry.track_arg_usage("emit-metadata", emit_metadata);
     telemetry.track_arg_usage("out-dir", output_dir != DEFAULT_OUTPUT_DIR);
 
-    let prune = Prune::new(base, scope, docker, output_dir).await?;
+    // The git state is captured before any output is written, since an output
+    // directory inside the repository would make it dirty
+    let metadata = emit_metadata.then(|| PruneMetadata::new(&base.repo_root, scope));
+    let prune = Prune::new(base, scope, docker, prod, output_dir).await?;
 
-    if matches!(
-        prune.package_graph.package_manager(),
-        turborepo_repository::package_manager::PackageManager::Bun
-    ) {
+    let package_manager = prune.package_graph.package_manager();
+    let lockfile_name = package_manager.existing_lockfile_name(&prune.root);
+    // We can only write out the text lockfile, not the binary one
+    if matches!(package_manager, PackageManager::Bun)
+        && lockfile_name == package_manager.lockfile_name()
+    {
         return Err(Error::BunUnsupported);
     }
-
-    println!(
-        "Generating pruned monorepo for {} in {}",
-        base.ui.apply(BOLD.apply_to(scope.join(", "))),
-        base.ui.apply(BOLD.apply_to(&prune.out_directory)),
-    );
+    // Berry lockfiles don't tell devDependencies of workspaces apart
+    if prod && matches!(package_manager, PackageManager::Berry) {
+        return Err(Error::ProdUnsupported(*package_manager));
+    }
 
     if let Some(workspace_config_path) = prune
         .package_graph
@@ -125,12 +230,13 @@ pub async fn prune(
     let mut workspace_paths = Vec::new();
     let mut workspace_names = Vec::new();
     let workspaces = prune.internal_dependencies();
-    let lockfile_keys: Vec<_> = prune
-        .package_graph
-        .transitive_external_dependencies(workspaces.iter())
-        .into_iter()
+    let external_dependencies = prune.external_dependencies(&workspaces)?;
+    let mut lockfile_keys = external_dependencies
+        .iter()
         .map(|pkg| pkg.key.clone())
-        .collect();
+        .collect::<Vec<_>>();
+    lockfile_keys.sort();
+    let local_dependencies = prune.copy_local_dependencies(&workspaces)?;
     for workspace in workspaces {
         let entry = prune
             .package_graph
@@ -140,6 +246,11 @@ pub async fn prune(
         // We don't want to do any copying for the root workspace
         if let PackageName::Other(workspace) = workspace {
             prune.copy_workspace(entry.package_json_path())?;
+            if prune.prod {
+                let mut prod_json = entry.package_json.clone();
+                prod_json.dev_dependencies = None;
+                prune.write_package_json(entry.package_json_path(), &prod_json)?;
+            }
             workspace_paths.push(
                 entry
                     .package_json_path()
@@ -149,21 +260,22 @@ pub async fn prune(
                     .to_string(),
             );
 
-            println!(" - Added {workspace}");
             workspace_names.push(workspace);
         }
     }
     trace!("new workspaces: {}", workspace_paths.join(", "));
     trace!("lockfile keys: {}", lockfile_keys.join(", "));
 
-    let lockfile = prune
+    let mut lockfile = prune
         .package_graph
         .lockfile()
         .expect("Lockfile presence already checked")
         .subgraph(&workspace_paths, &lockfile_keys)?;
+    if prune.prod {
+        lockfile.strip_dev_dependencies()?;
+    }
 
     let lockfile_contents = lockfile.encode()?;
-    let lockfile_name = prune.package_graph.package_manager().lockfile_name();
     let lockfile_path = prune.out_directory.join_component(lockfile_name);
     lockfile_path.create_with_contents(&lockfile_contents)?;
     if prune.docker {
@@ -173,6 +285,8 @@ pub async fn prune(
             .create_with_contents(&lockfile_contents)?;
     }
 
+    prune.copy_yarn_cache(&*lockfile)?;
+
     for (relative_path, required_for_install) in ADDITIONAL_FILES.as_slice() {
         let path = relative_path.to_anchored_system_path_buf();
         prune.copy_file(&path, *required_for_install)?;
@@ -190,37 +304,26 @@ pub async fn prune(
         .lockfile()
         .expect("lockfile presence checked earlier")
         .patches()?;
-    if !original_patches.is_empty() {
-        let pruned_patches = lockfile.patches()?;
+    let configured_patches = prune.configured_patches(&external_dependencies);
+    let mut root_json = None;
+    if !original_patches.is_empty() || !configured_patches.is_empty() {
+        let mut pruned_patches = lockfile.patches()?;
+        // Patches that are declared in the package.json but aren't present in the
+        // lockfile still need to exist on disk for the package manager to install.
+        pruned_patches.extend(configured_patches);
+        pruned_patches.sort();
+        pruned_patches.dedup();
         trace!(
             "original patches: {:?}, pruned patches: {:?}",
             original_patches,
             pruned_patches
         );
-        let pruned_json = prune
-            .package_graph
-            .package_manager()
-            .prune_patched_packages(prune.package_graph.root_package_json(), &pruned_patches);
-        let mut pruned_json_contents = serde_json::to_string_pretty(&pruned_json)?;
-        // Add trailing newline to match Go behavior
-        pruned_json_contents.push('\n');
-
-        let original = prune.root.resolve(package_json());
-        let permissions = original.symlink_metadata()?.permissions();
-        let new_package_json_path = prune.full_directory.resolve(package_json());
-        new_package_json_path.create_with_contents(&pruned_json_contents)?;
-        #[cfg(unix)]
-        new_package_json_path.set_mode(permissions.mode())?;
-        #[cfg(windows)]
-        if permissions.readonly() {
-            new_package_json_path.set_readonly()?
-        }
-        if prune.docker {
-            turborepo_fs::copy_file(
-                new_package_json_path,
-                prune.docker_directory().resolve(package_json()),
-            )?;
-        }
+        root_json = Some(
+            prune
+                .package_graph
+                .package_manager()
+                .prune_patched_packages(prune.package_graph.root_package_json(), &pruned_patches),
+        );
 
         for patch in pruned_patches {
             prune.copy_file(
@@ -228,19 +331,90 @@ pub async fn prune(
                 Some(CopyDestination::Docker),
             )?;
         }
-    } else {
-        prune.copy_file(package_json(), Some(CopyDestination::Docker))?;
+    }
+    if prune.prod {
+        let mut prod_json =
+            root_json.unwrap_or_else(|| prune.package_graph.root_package_json().clone());
+        prod_json.dev_dependencies = None;
+        root_json = Some(prod_json);
     }
 
+    match root_json {
+        Some(root_json) => prune.write_package_json(package_json(), &root_json)?,
+        None => prune.copy_file(package_json(), Some(CopyDestination::Docker))?,
+    }
+
+    let mut dockerfile_path = None;
+    if emit_dockerfile {
+        let dockerfile = prune.dockerfile()?;
+        let path = prune.out_directory.join_component("Dockerfile");
+        path.create_with_contents(dockerfile)?;
+        dockerfile_path = Some(path.to_string());
+    }
+
+    if let Some(metadata) = metadata {
+        let mut contents = serde_json::to_string_pretty(&metadata)?;
+        contents.push('\n');
+        prune
+            .out_directory
+            .join_component(METADATA_FILE)
+            .create_with_contents(contents)?;
+    }
+
+    base.print_output(&Pruned {
+        scope,
+        out_dir: prune.out_directory.to_string(),
+        local_dependencies,
+        packages: workspace_names,
+        dockerfile: dockerfile_path,
+    })?;
+
     Ok(())
 }
 
+/// What `turbo prune` added to the output directory.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Pruned<'a> {
+    scope: &'a [String],
+    out_dir: String,
+    local_dependencies: Vec<LocalDependency>,
+    packages: Vec<String>,
+    dockerfile: Option<String>,
+}
+
+#[derive(Serialize)]
+struct LocalDependency {
+    name: String,
+    path: String,
+}
+
+impl CommandOutput for Pruned<'_> {
+    fn print_text(&self, ui: UI) {
+        println!(
+            "Generating pruned monorepo for {} in {}",
+            ui.apply(BOLD.apply_to(self.scope.join(", "))),
+            ui.apply(BOLD.apply_to(&self.out_dir)),
+        );
+        for dependency in &self.local_dependencies {
+            println!(" - Added {} from {}", dependency.name, dependency.path);
+        }
+        for package in &self.packages {
+            println!(" - Added {package}");
+        }
+        if let Some(dockerfile) = &self.dockerfile {
+            println!(" - Generated {dockerfile}");
+        }
+    }
+}
+
 struct Prune<'a> {
     package_graph: PackageGraph,
     root: AbsoluteSystemPathBuf,
     out_directory: AbsoluteSystemPathBuf,
     full_directory: AbsoluteSystemPathBuf,
     docker: bool,
+    prod: bool,
     scope: &'a [String],
 }
 
@@ -258,6 +432,7 @@ impl<'a> Prune<'a> {
         base: &CommandBase,
         scope: &'a [String],
         docker: bool,
+        prod: bool,
         output_dir: &str,
     ) -> Result<Self, Error> {
         if scope.is_empty() {
@@ -280,6 +455,7 @@ impl<'a> Prune<'a> {
 
         trace!("scope: {}", scope.join(", "));
         trace!("docker: {}", docker);
+        trace!("prod: {}", prod);
         trace!("out directory: {}", &out_directory);
 
         for target in scope {
@@ -316,6 +492,7 @@ impl<'a> Prune<'a> {
             out_directory,
             full_directory,
             docker,
+            prod,
             scope,
         })
     }
@@ -396,14 +573,176 @@ impl<'a> Prune<'a> {
             let docker_workspace_dir = self.docker_directory().resolve(&relative_workspace_dir);
             docker_workspace_dir.ensure_dir()?;
             turborepo_fs::copy_file(
-                package_json_path,
+                &package_json_path,
                 docker_workspace_dir.resolve(package_json()),
             )?;
+            for install_file in WORKSPACE_INSTALL_FILES.iter() {
+                let install_file = install_file.to_anchored_system_path_buf();
+                let from_path = original_dir.resolve(&install_file);
+                if !from_path.try_exists()? {
+                    continue;
+                }
+                turborepo_fs::copy_file(&from_path, docker_workspace_dir.resolve(&install_file))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Patch files listed in the root package.json's `pnpm.patchedDependencies`
+    // that apply to one of the pruned external dependencies
+    fn configured_patches(
+        &self,
+        external_dependencies: &HashSet<Package>,
+    ) -> Vec<RelativeUnixPathBuf> {
+        if !matches!(
+            self.package_graph.package_manager(),
+            PackageManager::Pnpm | PackageManager::Pnpm6
+        ) {
+            return Vec::new();
+        }
+        let Some(patched_dependencies) = self
+            .package_graph
+            .root_package_json()
+            .pnpm
+            .as_ref()
+            .and_then(|config| config.patched_dependencies.as_ref())
+        else {
+            return Vec::new();
+        };
+        self.package_graph
+            .lockfile()
+            .expect("lockfile presence checked earlier")
+            .configured_patches_for(external_dependencies, patched_dependencies)
+    }
+
+    // Copies any `file:`, `link:`, or `portal:` dependencies of the pruned
+    // workspaces that aren't workspaces themselves. These targets aren't
+    // picked up by the workspace globs, but need to be present for an install.
+    fn copy_local_dependencies(
+        &self,
+        workspaces: &[PackageName],
+    ) -> Result<Vec<LocalDependency>, Error> {
+        let workspace_dirs = workspaces
+            .iter()
+            .filter(|workspace| matches!(workspace, PackageName::Other(_)))
+            .filter_map(|workspace| self.package_graph.package_dir(workspace))
+            .map(|dir| self.root.resolve(dir))
+            .collect::<Vec<_>>();
+        let real_root = self.root.to_realpath()?;
+        let mut copied = Vec::new();
+
+        for workspace in workspaces {
+            let Some(info) = self.package_graph.package_info(workspace) else {
+                continue;
+            };
+            let workspace_dir = self.root.resolve(info.package_path());
+            let dependencies: Vec<_> = match self.prod {
+                true => info.package_json.prod_dependencies().collect(),
+                false => info.package_json.all_dependencies().collect(),
+            };
+            for (dependency, version) in dependencies {
+                let Some(target) = LOCAL_DEPENDENCY_PROTOCOLS
+                    .iter()
+                    .find_map(|protocol| version.strip_prefix(protocol))
+                else {
+                    continue;
+                };
+                let path = AbsoluteSystemPathBuf::from_unknown(&workspace_dir, target).clean()?;
+                if workspace_dirs.iter().any(|dir| dir.contains(&path)) {
+                    continue;
+                }
+                if !check_local_dependency(&self.root, &real_root, workspace, &dependency, &path)? {
+                    warn!(
+                        "{workspace} depends on {dependency} which points to {path}, which \
+                         doesn't exist. It is not included in the pruned output."
+                    );
+                    continue;
+                }
+
+                let anchored_path = self.root.anchor(&path)?;
+                if path.stat()?.is_dir() {
+                    self.copy_directory(&anchored_path, Some(CopyDestination::Docker))?;
+                } else {
+                    self.copy_file(&anchored_path, Some(CopyDestination::Docker))?;
+                }
+                copied.push(LocalDependency {
+                    name: dependency.clone(),
+                    path: anchored_path.to_string(),
+                });
+            }
+        }
+
+        Ok(copied)
+    }
+
+    // With Yarn's zero-installs the cache is checked into the repository, only
+    // the entries for packages in the pruned lockfile are copied so the output
+    // can still be installed offline.
+    fn copy_yarn_cache(&self, lockfile: &dyn Lockfile) -> Result<(), Error> {
+        if !matches!(self.package_graph.package_manager(), PackageManager::Berry) {
+            return Ok(());
+        }
+        let cache_dir = YARN_CACHE_DIR.to_anchored_system_path_buf();
+        let entries = match std::fs::read_dir(self.root.resolve(&cache_dir).as_std_path()) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                trace!("no yarn cache found, skipping copying");
+                return Ok(());
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let mut cache_files = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            if let Some(file_name) = entry.file_name().to_str() {
+                cache_files.push(file_name.to_string());
+            }
+        }
+
+        let pruned_cache_files = lockfile.cache_entries(&cache_files);
+        trace!(
+            "copying {} of {} yarn cache entries",
+            pruned_cache_files.len(),
+            cache_files.len()
+        );
+        for file_name in pruned_cache_files {
+            self.copy_file(
+                &cache_dir.join_component(&file_name),
+                Some(CopyDestination::Docker),
+            )?;
         }
 
         Ok(())
     }
 
+    // Writes a modified package.json to the output directories while keeping the
+    // permissions of the original
+    fn write_package_json(
+        &self,
+        path: &AnchoredSystemPath,
+        package_json: &PackageJson,
+    ) -> Result<(), Error> {
+        let mut contents = serde_json::to_string_pretty(package_json)?;
+        // Add trailing newline to match Go behavior
+        contents.push('\n');
+
+        let original = self.root.resolve(path);
+        let permissions = original.symlink_metadata()?.permissions();
+        let new_package_json_path = self.full_directory.resolve(path);
+        new_package_json_path.create_with_contents(&contents)?;
+        #[cfg(unix)]
+        new_package_json_path.set_mode(permissions.mode())?;
+        #[cfg(windows)]
+        if permissions.readonly() {
+            new_package_json_path.set_readonly()?
+        }
+        if self.docker {
+            turborepo_fs::copy_file(new_package_json_path, self.docker_directory().resolve(path))?;
+        }
+        Ok(())
+    }
+
     fn internal_dependencies(&self) -> Vec<PackageName> {
         let workspaces = std::iter::once(PackageNode::Workspace(PackageName::Root))
             .chain(
@@ -412,6 +751,9 @@ impl<'a> Prune<'a> {
                     .map(|workspace| PackageNode::Workspace(PackageName::Other(workspace.clone()))),
             )
             .collect::<Vec<_>>();
+        if self.prod {
+            return self.production_dependencies(workspaces);
+        }
         let nodes = self.package_graph.transitive_closure(workspaces.iter());
 
         let mut names: Vec<_> = nodes
@@ -425,6 +767,141 @@ impl<'a> Prune<'a> {
         names
     }
 
+    // Walks the package graph only following edges for dependencies that are
+    // installed in a production install
+    fn production_dependencies(&self, workspaces: Vec<PackageNode>) -> Vec<PackageName> {
+        let mut visited = HashSet::new();
+        let mut stack = workspaces;
+        while let Some(node) = stack.pop() {
+            let PackageNode::Workspace(name) = &node else {
+                continue;
+            };
+            if visited.contains(name) {
+                continue;
+            }
+            let Some(info) = self.package_graph.package_info(name) else {
+                continue;
+            };
+            let prod_dependencies = info
+                .package_json
+                .prod_dependencies()
+                .map(|(name, _)| name.as_str())
+                .collect::<HashSet<_>>();
+            for dependency in self
+                .package_graph
+                .immediate_dependencies(&node)
+                .into_iter()
+                .flatten()
+            {
+                if let PackageNode::Workspace(PackageName::Other(dependency_name)) = dependency {
+                    if prod_dependencies.contains(dependency_name.as_str()) {
+                        stack.push(dependency.clone());
+                    }
+                }
+            }
+            visited.insert(name.clone());
+        }
+
+        let mut names: Vec<_> = visited.into_iter().collect();
+        names.sort();
+        names
+    }
+
+    fn external_dependencies(&self, workspaces: &[PackageName]) -> Result<HashSet<Package>, Error> {
+        if !self.prod {
+            return Ok(self
+                .package_graph
+                .transitive_external_dependencies(workspaces.iter())
+                .into_iter()
+                .cloned()
+                .collect());
+        }
+
+        // The transitive closures calculated by the package graph include
+        // devDependencies so we need to recalculate them for production only
+        let unresolved_dependencies = workspaces
+            .iter()
+            .filter_map(|workspace| self.package_graph.package_info(workspace))
+            .map(|info| {
+                let prod_dependencies = info
+                    .package_json
+                    .prod_dependencies()
+                    .map(|(name, _)| name)
+                    .collect::<HashSet<_>>();
+                let external_dependencies = info
+                    .unresolved_external_dependencies
+                    .iter()
+                    .flatten()
+                    .filter(|(name, _)| prod_dependencies.contains(name))
+                    .map(|(name, version)| (name.clone(), version.clone()))
+                    .collect::<HashMap<_, _>>();
+                (
+                    info.package_path().to_unix().to_string(),
+                    external_dependencies,
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        let closures = turborepo_lockfiles::all_transitive_closures(
+            self.package_graph
+                .lockfile()
+                .expect("lockfile presence checked earlier"),
+            unresolved_dependencies,
+        )?;
+
+        Ok(closures.into_values().flatten().collect())
+    }
+
+    // Generates a multi-stage Dockerfile that is meant to be built with the
+    // docker output directory as the build context.
+    fn dockerfile(&self) -> Result<String, Error> {
+        let package_manager = self.package_graph.package_manager();
+        let target = PackageName::Other(self.scope[0].clone());
+        let info = self
+            .package_graph
+            .package_info(&target)
+            .ok_or_else(|| Error::MissingWorkspace(target.clone()))?;
+
+        // An exact version from .nvmrc wins over the engines ranges
+        let nvmrc = match self.root.join_component(".nvmrc").read_to_string() {
+            Ok(contents) => Some(contents),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+        let node_version = nvmrc
+            .as_deref()
+            .and_then(nvmrc_node_version)
+            .or_else(|| engines_node_version(&info.package_json))
+            .or_else(|| engines_node_version(self.package_graph.root_package_json()));
+
+        let command = if info.package_json.scripts.contains_key("start") {
+            Some(
+                match package_manager {
+                    PackageManager::Npm => "npm run start",
+                    PackageManager::Yarn | PackageManager::Berry => "yarn start",
+                    PackageManager::Pnpm | PackageManager::Pnpm6 => "pnpm start",
+                    PackageManager::Bun => "bun run start",
+                }
+                .to_string(),
+            )
+        } else {
+            info.package_json
+                .other
+                .get("main")
+                .and_then(|main| main.as_str())
+                .map(|main| format!("node {main}"))
+        };
+
+        Ok(Dockerfile {
+            package_manager,
+            lockfile_name: package_manager.existing_lockfile_name(&self.root),
+            node_version: node_version.as_deref().unwrap_or(DEFAULT_NODE_VERSION),
+            scope: self.scope,
+            target_dir: info.package_path().to_unix().to_string(),
+            command: command.as_deref(),
+        }
+        .render())
+    }
+
     fn copy_turbo_json(&self, workspaces: &[String]) -> Result<(), Error> {
         let anchored_turbo_path = turbo_json();
         let original_turbo_path = self.root.resolve(anchored_turbo_path);
@@ -447,3 +924,313 @@ impl<'a> Prune<'a> {
         Ok(())
     }
 }
+
+// Checks that the target of a `file:`, `link:`, or `portal:` dependency can be
+// copied into the pruned output, returns whether it exists
+fn check_local_dependency(
+    root: &AbsoluteSystemPath,
+    real_root: &AbsoluteSystemPath,
+    workspace: &PackageName,
+    dependency: &str,
+    path: &AbsoluteSystemPath,
+) -> Result<bool, Error> {
+    let outside_repo_error = || Error::LocalDependencyOutsideRepo {
+        workspace: workspace.clone(),
+        dependency: dependency.to_string(),
+        path: path.to_owned(),
+    };
+    if !root.contains(path) {
+        return Err(outside_repo_error());
+    }
+    if !path.try_exists()? {
+        return Ok(false);
+    }
+    // A target inside the repo might be a symlink that escapes it, copying it would
+    // result in a dangling link in the output.
+    if !real_root.contains(&path.to_realpath()?) {
+        return Err(outside_repo_error());
+    }
+    Ok(true)
+}
+
+// Used when neither .nvmrc nor the engines field pick a Node version
+const DEFAULT_NODE_VERSION: &str = "18";
+
+// The version in an .nvmrc, unless it's an alias like `lts/*` that doesn't map
+// to an image tag
+fn nvmrc_node_version(contents: &str) -> Option<String> {
+    let version = contents.trim();
+    let version = version.strip_prefix('v').unwrap_or(version);
+    (!version.is_empty() && version.chars().all(|c| c.is_ascii_digit() || c == '.'))
+        .then(|| version.to_string())
+}
+
+// The major version of the lowest Node version allowed by `engines.node`
+fn engines_node_version(package_json: &PackageJson) -> Option<String> {
+    let range = package_json.other.get("engines")?.get("node")?.as_str()?;
+    range
+        .split(|c: char| c.is_whitespace() || c == '|')
+        // Upper bounds don't tell which version to use
+        .filter(|comparator| !comparator.starts_with('<'))
+        .map(|comparator| {
+            comparator
+                .trim_start_matches(|c: char| matches!(c, '>' | '=' | '^' | '~' | 'v'))
+                .chars()
+                .take_while(|c| c.is_ascii_digit())
+                .collect::<String>()
+        })
+        .find(|major| !major.is_empty())
+}
+
+struct Dockerfile<'a> {
+    package_manager: &'a PackageManager,
+    lockfile_name: &'a str,
+    node_version: &'a str,
+    scope: &'a [String],
+    target_dir: String,
+    // How the first target is started, if it can be told from its package.json
+    command: Option<&'a str>,
+}
+
+impl Dockerfile<'_> {
+    // Dependencies are installed from the `json` directory first so the install
+    // layer is only invalidated when a package.json or the lockfile changes.
+    fn render(&self) -> String {
+        let Self {
+            package_manager,
+            lockfile_name,
+            node_version,
+            scope,
+            target_dir,
+            command,
+        } = self;
+        let (setup, install, turbo) = match package_manager {
+            PackageManager::Npm => ("", "npm ci", "npx turbo"),
+            PackageManager::Yarn => ("", "yarn install --frozen-lockfile", "yarn turbo"),
+            PackageManager::Berry => (
+                "RUN corepack enable\n",
+                "yarn install --immutable",
+                "yarn turbo",
+            ),
+            PackageManager::Pnpm | PackageManager::Pnpm6 => (
+                "RUN corepack enable\n",
+                "pnpm install --frozen-lockfile",
+                "pnpm turbo",
+            ),
+            // The Node images don't ship with Bun
+            PackageManager::Bun => (
+                "RUN npm install --global bun\n",
+                "bun install --frozen-lockfile",
+                "bunx turbo",
+            ),
+        };
+        let filters = scope
+            .iter()
+            .map(|target| format!("--filter={target}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let cmd = match command {
+            Some(command) => format!("CMD {command}"),
+            None => format!(
+                "# {} has no start script or main entry, add a CMD that runs it",
+                scope[0]
+            ),
+        };
+
+        format!(
+            r#"FROM node:{node_version}-alpine AS base
+RUN apk add --no-cache libc6-compat
+{setup}
+# Install dependencies using only the package.json files and pruned lockfile
+FROM base AS installer
+WORKDIR /app
+COPY json/ .
+COPY {lockfile_name} ./{lockfile_name}
+RUN {install}
+
+# Build the project
+FROM installer AS builder
+COPY full/ .
+RUN {turbo} run build {filters}
+
+FROM base AS runner
+WORKDIR /app
+COPY --from=builder /app .
+WORKDIR /app/{target_dir}
+{cmd}
+"#
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::assert_matches::assert_matches;
+
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use tempfile::tempdir;
+    use test_case::test_case;
+    use turbopath::AbsoluteSystemPathBuf;
+    use turborepo_repository::{
+        package_graph::PackageName, package_json::PackageJson, package_manager::PackageManager,
+    };
+
+    use super::{
+        check_local_dependency, engines_node_version, nvmrc_node_version, Dockerfile, Error,
+    };
+
+    #[test_case("20.11.1\n", Some("20.11.1") ; "exact")]
+    #[test_case("v18\n", Some("18") ; "prefixed")]
+    #[test_case("lts/iron", None ; "alias")]
+    #[test_case("", None ; "empty")]
+    fn test_nvmrc_node_version(contents: &str, expected: Option<&str>) {
+        assert_eq!(nvmrc_node_version(contents).as_deref(), expected);
+    }
+
+    #[test_case(">=18", Some("18") ; "lower bound")]
+    #[test_case("^20.1.0", Some("20") ; "caret")]
+    #[test_case("18.x || 20.x", Some("18") ; "alternatives")]
+    #[test_case("<21 >= 19", Some("19") ; "upper bound first")]
+    #[test_case("*", None ; "any")]
+    fn test_engines_node_version(range: &str, expected: Option<&str>) {
+        let package_json =
+            PackageJson::from_value(json!({ "engines": { "node": range } })).unwrap();
+        assert_eq!(engines_node_version(&package_json).as_deref(), expected);
+    }
+
+    #[test]
+    fn test_engines_node_version_missing() {
+        assert_eq!(engines_node_version(&PackageJson::default()), None);
+    }
+
+    #[test]
+    fn test_check_local_dependency() {
+        let tmp = tempdir().unwrap();
+        let tmp = AbsoluteSystemPathBuf::try_from(tmp.path()).unwrap();
+        let root = tmp.join_component("repo");
+        let vendored = root.join_components(&["vendor", "lib"]);
+        vendored.create_dir_all().unwrap();
+        let outside = tmp.join_component("outside");
+        outside.create_dir_all().unwrap();
+        let real_root = root.to_realpath().unwrap();
+
+        let workspace = PackageName::from("web");
+        let check = |path: &AbsoluteSystemPathBuf| {
+            check_local_dependency(&root, &real_root, &workspace, "lib", path)
+        };
+        assert!(check(&vendored).unwrap());
+        assert!(!check(&root.join_components(&["vendor", "missing"])).unwrap());
+        assert_matches!(
+            check(&outside),
+            Err(Error::LocalDependencyOutsideRepo { path, .. }) if path == outside
+        );
+
+        #[cfg(unix)]
+        {
+            let link = root.join_components(&["vendor", "link"]);
+            link.symlink_to_dir(outside.as_str()).unwrap();
+            assert_matches!(
+                check(&link),
+                Err(Error::LocalDependencyOutsideRepo { path, .. }) if path == link
+            );
+        }
+    }
+
+    #[test]
+    fn test_dockerfile() {
+        let scope = ["web".to_string(), "docs".to_string()];
+        let dockerfile = Dockerfile {
+            package_manager: &PackageManager::Pnpm,
+            lockfile_name: "pnpm-lock.yaml",
+            node_version: "20",
+            scope: &scope,
+            target_dir: "apps/web".to_string(),
+            command: Some("pnpm start"),
+        };
+        assert_eq!(
+            dockerfile.render(),
+            r#"FROM node:20-alpine AS base
+RUN apk add --no-cache libc6-compat
+RUN corepack enable
+
+# Install dependencies using only the package.json files and pruned lockfile
+FROM base AS installer
+WORKDIR /app
+COPY json/ .
+COPY pnpm-lock.yaml ./pnpm-lock.yaml
+RUN pnpm install --frozen-lockfile
+
+# Build the project
+FROM installer AS builder
+COPY full/ .
+RUN pnpm turbo run build --filter=web --filter=docs
+
+FROM base AS runner
+WORKDIR /app
+COPY --from=builder /app .
+WORKDIR /app/apps/web
+CMD pnpm start
+"#
+        );
+    }
+
+    #[test]
+    fn test_dockerfile_bun() {
+        let scope = ["web".to_string()];
+        let dockerfile = Dockerfile {
+            package_manager: &PackageManager::Bun,
+            lockfile_name: "bun.lock",
+            node_version: "20",
+            scope: &scope,
+            target_dir: "apps/web".to_string(),
+            command: Some("bun run start"),
+        };
+        assert_eq!(
+            dockerfile.render(),
+            r#"FROM node:20-alpine AS base
+RUN apk add --no-cache libc6-compat
+RUN npm install --global bun
+
+# Install dependencies using only the package.json files and pruned lockfile
+FROM base AS installer
+WORKDIR /app
+COPY json/ .
+COPY bun.lock ./bun.lock
+RUN bun install --frozen-lockfile
+
+# Build the project
+FROM installer AS builder
+COPY full/ .
+RUN bunx turbo run build --filter=web
+
+FROM base AS runner
+WORKDIR /app
+COPY --from=builder /app .
+WORKDIR /app/apps/web
+CMD bun run start
+"#
+        );
+    }
+
+    #[test]
+    fn test_dockerfile_without_command() {
+        let scope = ["api".to_string()];
+        let dockerfile = Dockerfile {
+            package_manager: &PackageManager::Npm,
+            lockfile_name: "package-lock.json",
+            node_version: "18",
+            scope: &scope,
+            target_dir: "apps/api".to_string(),
+            command: None,
+        };
+        let rendered = dockerfile.render();
+        assert!(rendered.starts_with("FROM node:18-alpine AS base\n"));
+        assert!(rendered.contains("RUN npm ci\n"));
+        assert!(rendered.contains("RUN npx turbo run build --filter=api\n"));
+        assert!(rendered.ends_with(
+            "WORKDIR /app/apps/api\n# api has no start script or main entry, add a CMD that runs \
+             it\n"
+        ));
+    }
+}
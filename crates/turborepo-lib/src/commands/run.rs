@@ -1,48 +1,120 @@
+use std::{
+    pin::pin,
+    sync::{
+        atomic::{AtomicI32, Ordering},
+        Arc,
+    },
+};
+
 use turborepo_telemetry::events::command::CommandEventBuilder;
 
-use crate::{commands::CommandBase, run, run::Run, signal::SignalHandler};
+use crate::{
+    commands::CommandBase,
+    run,
+    run::Run,
+    signal::{SignalHandler, GRACEFUL_SHUTDOWN_BUDGET},
+};
 
 pub async fn run(base: CommandBase, telemetry: CommandEventBuilder) -> Result<i32, run::Error> {
+    // The number of the first signal we received, Windows doesn't have signal
+    // numbers so it stays 0 there
+    let received_signal = Arc::new(AtomicI32::new(0));
     #[cfg(windows)]
-    let signal = {
-        let mut ctrl_c = tokio::signal::windows::ctrl_c().map_err(run::Error::SignalHandler)?;
-        async move { ctrl_c.recv().await }
+    let signals = {
+        use futures::{stream, StreamExt};
+        use tokio::signal::windows;
+        let ctrl_c = windows::ctrl_c().map_err(run::Error::SignalHandler)?;
+        let ctrl_break = windows::ctrl_break().map_err(run::Error::SignalHandler)?;
+        let ctrl_close = windows::ctrl_close().map_err(run::Error::SignalHandler)?;
+        let ctrl_logoff = windows::ctrl_logoff().map_err(run::Error::SignalHandler)?;
+        let ctrl_shutdown = windows::ctrl_shutdown().map_err(run::Error::SignalHandler)?;
+        stream::unfold(
+            (ctrl_c, ctrl_break, ctrl_close, ctrl_logoff, ctrl_shutdown),
+            |mut events| async move {
+                let (ctrl_c, ctrl_break, ctrl_close, ctrl_logoff, ctrl_shutdown) = &mut events;
+                // Windows terminates the process a few seconds after the console window is
+                // closed, the user logs off or the system shuts down. There's no time for a
+                // graceful shutdown, so these count as two signals which forces it right away
+                // and kills the child tasks before we are terminated.
+                let signals = tokio::select! {
+                    res = ctrl_c.recv() => res.map(|_| 1),
+                    res = ctrl_break.recv() => res.map(|_| 1),
+                    res = ctrl_close.recv() => res.map(|_| 2),
+                    res = ctrl_logoff.recv() => res.map(|_| 2),
+                    res = ctrl_shutdown.recv() => res.map(|_| 2),
+                };
+                signals.map(|signals| (stream::iter(std::iter::repeat(()).take(signals)), events))
+            },
+        )
+        .flatten()
     };
     #[cfg(not(windows))]
-    let signal = {
+    let signals = {
         use tokio::signal::unix;
-        let mut sigint =
+        let sigint =
             unix::signal(unix::SignalKind::interrupt()).map_err(run::Error::SignalHandler)?;
-        let mut sigterm =
+        let sigterm =
             unix::signal(unix::SignalKind::terminate()).map_err(run::Error::SignalHandler)?;
-        async move {
-            tokio::select! {
-                res = sigint.recv() => {
-                    res
-                }
-                res = sigterm.recv() => {
-                    res
-                }
-            }
-        }
+        let received_signal = received_signal.clone();
+        futures::stream::unfold(
+            (sigint, sigterm, received_signal),
+            |(mut sigint, mut sigterm, received_signal)| async move {
+                let signal = tokio::select! {
+                    res = sigint.recv() => {
+                        res.map(|_| libc::SIGINT)
+                    }
+                    res = sigterm.recv() => {
+                        res.map(|_| libc::SIGTERM)
+                    }
+                };
+                signal.map(|signal| {
+                    received_signal
+                        .compare_exchange(0, signal, Ordering::SeqCst, Ordering::SeqCst)
+                        .ok();
+                    ((), (sigint, sigterm, received_signal))
+                })
+            },
+        )
     };
 
-    let handler = SignalHandler::new(signal);
+    // The first signal shuts down gracefully, a second one forces the shutdown
+    let handler = SignalHandler::from_signals(signals);
 
-    let api_auth = base.api_auth()?;
     let api_client = base.api_client()?;
+    let api_auth = if base.args().offline {
+        // Without auth we never talk to the remote cache, analytics, or spaces
+        None
+    } else {
+        match base.api_auth()? {
+            Some(api_auth) => Some(api_auth),
+            None => base.oidc_api_auth(&api_client).await,
+        }
+    };
     let run = Run::new(base, api_auth)?;
-    let run_fut = run.run(&handler, telemetry, api_client);
+    let mut run_fut = pin!(run.run(&handler, telemetry, api_client));
     let handler_fut = handler.done();
     tokio::select! {
         biased;
         // If we get a handler exit at the same time as a run finishes we choose that
         // future to display that we're respecting user input
         _ = handler_fut => {
-            // We caught a signal, which already notified the subscribers
-            Ok(1)
+            // We caught a signal, which already notified the subscribers. Give the run a
+            // chance to wrap up, e.g. writing its summary, unless the shutdown is forced.
+            let result = tokio::select! {
+                result = &mut run_fut => Some(result),
+                _ = handler.force_shutdown() => None,
+                _ = tokio::time::sleep(GRACEFUL_SHUTDOWN_BUDGET) => None,
+            };
+            match (received_signal.load(Ordering::SeqCst), result) {
+                (_, Some(Err(e))) => Err(e),
+                // Like a shell, a run that was interrupted by a signal exits with 128 plus
+                // the signal number
+                (0, Some(Ok(exit_code))) => Ok(exit_code),
+                (0, None) => Ok(1),
+                (signal, _) => Ok(128 + signal),
+            }
         }
-        result = run_fut => {
+        result = &mut run_fut => {
             // Run finished so close the signal handler
             handler.close().await;
             result
@@ -1,77 +1,153 @@
-use turborepo_telemetry::{config::TelemetryConfig, events::command::CommandEventBuilder};
-use turborepo_ui::{color, BOLD, BOLD_GREEN, BOLD_RED};
+use std::collections::BTreeMap;
 
-use super::CommandBase;
-use crate::cli::TelemetryCommand;
+use miette::Diagnostic;
+use serde::Serialize;
+use thiserror::Error;
+use turborepo_telemetry::{
+    config::{self, ConfigError, TelemetryConfig},
+    events::command::CommandEventBuilder,
+};
+use turborepo_ui::{color, BOLD, BOLD_GREEN, BOLD_RED, UI};
 
-fn log_status(config: TelemetryConfig, base: &CommandBase) {
-    let status = config.is_enabled();
-    match status {
-        true => {
-            println!(
-                "\nStatus: {}",
-                base.ui.apply(BOLD_GREEN.apply_to("Enabled"))
-            );
-            println!("\nTurborepo telemetry is completely anonymous. Thank you for participating!");
+use super::{CommandBase, CommandOutput};
+use crate::cli::{TelemetryCategory, TelemetryCommand};
+
+#[derive(Debug, Error, Diagnostic)]
+pub enum Error {
+    #[error("Failed to load telemetry config")]
+    #[diagnostic(code(TURBO_TELEMETRY_001))]
+    Load(#[source] ConfigError),
+    #[error("Failed to enable telemetry")]
+    #[diagnostic(code(TURBO_TELEMETRY_002))]
+    Enable(#[source] ConfigError),
+    #[error("Failed to disable telemetry")]
+    #[diagnostic(code(TURBO_TELEMETRY_003))]
+    Disable(#[source] ConfigError),
+    #[error("Failed to print telemetry status")]
+    #[diagnostic(code(TURBO_TELEMETRY_004))]
+    Print(#[from] serde_json::Error),
+}
+
+impl From<TelemetryCategory> for config::TelemetryCategory {
+    fn from(category: TelemetryCategory) -> Self {
+        match category {
+            TelemetryCategory::Usage => config::TelemetryCategory::Usage,
+            TelemetryCategory::Errors => config::TelemetryCategory::Errors,
+            TelemetryCategory::Performance => config::TelemetryCategory::Performance,
+        }
+    }
+}
+
+/// The telemetry settings after the command ran.
+#[derive(Serialize)]
+struct TelemetryStatus {
+    // Whether the command changed the settings
+    #[serde(skip)]
+    changed: bool,
+    enabled: bool,
+    categories: BTreeMap<String, bool>,
+}
+
+impl TelemetryStatus {
+    fn new(config: &TelemetryConfig, changed: bool) -> Self {
+        let categories = config.categories();
+        Self {
+            changed,
+            enabled: config.is_enabled(),
+            categories: config::TelemetryCategory::ALL
+                .into_iter()
+                .map(|category| (category.to_string(), categories.is_enabled(category)))
+                .collect(),
+        }
+    }
+}
+
+impl CommandOutput for TelemetryStatus {
+    fn print_text(&self, ui: UI) {
+        if self.changed {
+            println!("{}", color!(ui, BOLD, "{}", "Success!"));
         }
-        false => {
-            println!("\nStatus: {}", base.ui.apply(BOLD_RED.apply_to("Disabled")));
-            println!(
-                "\nYou have opted-out of Turborepo anonymous telemetry. No data will be collected \
-                 from your machine."
-            );
+        match self.enabled {
+            true => {
+                println!("\nStatus: {}", ui.apply(BOLD_GREEN.apply_to("Enabled")));
+                println!();
+                for category in config::TelemetryCategory::ALL {
+                    let category = category.to_string();
+                    let status = match self.categories[&category] {
+                        true => ui.apply(BOLD_GREEN.apply_to("Enabled")),
+                        false => ui.apply(BOLD_RED.apply_to("Disabled")),
+                    };
+                    println!("  {:<13}{}", category, status);
+                }
+                println!(
+                    "\nTurborepo telemetry is completely anonymous. Thank you for participating!"
+                );
+            }
+            false => {
+                println!("\nStatus: {}", ui.apply(BOLD_RED.apply_to("Disabled")));
+                println!(
+                    "\nYou have opted-out of Turborepo anonymous telemetry. No data will be \
+                     collected from your machine."
+                );
+            }
         }
+        println!("Learn more: https://turbo.build/repo/docs/telemetry");
     }
-    println!("Learn more: https://turbo.build/repo/docs/telemetry");
 }
 
-fn log_error(message: &str, error: &str, base: &CommandBase) {
+/// Prints `error` the way `turbo telemetry` always reported failures, for
+/// text output.
+pub fn log_error(error: &Error, base: &CommandBase) {
+    let cause = std::error::Error::source(error)
+        .map(|source| source.to_string())
+        .unwrap_or_default();
     println!(
         "{}: {}",
-        color!(base.ui, BOLD_RED, "{}", message),
-        color!(base.ui, BOLD_RED, "{}", error)
+        color!(base.ui, BOLD_RED, "{}", error),
+        color!(base.ui, BOLD_RED, "{}", cause)
     );
 }
 
+fn log_status(config: &TelemetryConfig, changed: bool, base: &CommandBase) -> Result<(), Error> {
+    Ok(base.print_output(&TelemetryStatus::new(config, changed))?)
+}
+
 pub fn configure(
     command: &Option<TelemetryCommand>,
     base: &mut CommandBase,
     telemetry: CommandEventBuilder,
-) {
-    let config = TelemetryConfig::with_default_config_path();
-    let mut config = match config {
-        Ok(config) => config,
-        Err(e) => {
-            log_error("Failed to load telemetry config", &e.to_string(), base);
-            return;
-        }
-    };
+) -> Result<(), Error> {
+    let mut config = TelemetryConfig::with_default_config_path().map_err(Error::Load)?;
 
     match command {
-        Some(TelemetryCommand::Enable) => {
-            let result = config.enable();
-            match result {
-                Ok(_) => {
-                    println!("{}", color!(base.ui, BOLD, "{}", "Success!"));
-                    log_status(config, base);
-                    telemetry.track_telemetry_config(true);
-                }
-                Err(e) => log_error("Failed to enable telemetry", &e.to_string(), base),
-            }
+        Some(TelemetryCommand::Enable {
+            category: Some(category),
+        }) => {
+            config
+                .enable_category((*category).into())
+                .map_err(Error::Enable)?;
+            log_status(&config, true, base)
         }
-        Some(TelemetryCommand::Disable) => {
-            let result = config.disable();
-            match result {
-                Ok(_) => {
-                    println!("{}", color!(base.ui, BOLD, "{}", "Success!"));
-                    log_status(config, base);
-                    telemetry.track_telemetry_config(false);
-                }
-                Err(e) => log_error("Failed to disable telemetry", &e.to_string(), base),
-            }
+        Some(TelemetryCommand::Disable {
+            category: Some(category),
+        }) => {
+            config
+                .disable_category((*category).into())
+                .map_err(Error::Disable)?;
+            log_status(&config, true, base)
+        }
+        Some(TelemetryCommand::Enable { category: None }) => {
+            config.enable().map_err(Error::Enable)?;
+            log_status(&config, true, base)?;
+            telemetry.track_telemetry_config(true);
+            Ok(())
         }
-        _ => {
-            log_status(config, base);
+        Some(TelemetryCommand::Disable { category: None }) => {
+            config.disable().map_err(Error::Disable)?;
+            log_status(&config, true, base)?;
+            telemetry.track_telemetry_config(false);
+            Ok(())
         }
+        _ => log_status(&config, false, base),
     }
 }
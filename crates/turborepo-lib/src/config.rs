@@ -1,6 +1,5 @@
 use std::{collections::HashMap, ffi::OsString, io};
 
-use convert_case::{Case, Casing};
 use miette::{Diagnostic, NamedSource, SourceSpan};
 use serde::Deserialize;
 use struct_iterable::Iterable;
@@ -17,8 +16,8 @@ use crate::{commands::CommandBase, turbo_json};
 #[derive(Debug, Error, Diagnostic)]
 #[error("Environment variables should not be prefixed with \"{env_pipeline_delimiter}\"")]
 #[diagnostic(
-    code(invalid_env_prefix),
-    url("{}/messages/{}", TURBO_SITE, self.code().unwrap().to_string().to_case(Case::Kebab))
+    code(TURBO_CONFIG_031),
+    url("{}/messages/invalid-env-prefix", TURBO_SITE)
 )]
 pub struct InvalidEnvPrefixError {
     pub value: String,
@@ -34,34 +33,46 @@ pub struct InvalidEnvPrefixError {
 #[derive(Debug, Error, Diagnostic)]
 pub enum Error {
     #[error("Authentication error: {0}")]
+    #[diagnostic(code(TURBO_CONFIG_001))]
     Auth(#[from] turborepo_auth::Error),
     #[error("Global config path not found")]
+    #[diagnostic(code(TURBO_CONFIG_002))]
     NoGlobalConfigPath,
     #[error("Global auth file path not found")]
+    #[diagnostic(code(TURBO_CONFIG_003))]
     NoGlobalAuthFilePath,
     #[error("Global config directory not found")]
+    #[diagnostic(code(TURBO_CONFIG_004))]
     NoGlobalConfigDir,
     #[error(transparent)]
+    #[diagnostic(transparent)]
     PackageJson(#[from] turborepo_repository::package_json::Error),
     #[error(
         "Could not find turbo.json.\nFollow directions at https://turbo.build/repo/docs to create \
          one"
     )]
+    #[diagnostic(code(TURBO_CONFIG_006))]
     NoTurboJSON,
     #[error(transparent)]
+    #[diagnostic(code(TURBO_CONFIG_007))]
     SerdeJson(#[from] serde_json::Error),
     #[error(transparent)]
+    #[diagnostic(code(TURBO_CONFIG_008))]
     Io(#[from] io::Error),
     #[error(transparent)]
+    #[diagnostic(code(TURBO_CONFIG_009))]
     Camino(#[from] camino::FromPathBufError),
     #[error(transparent)]
+    #[diagnostic(code(TURBO_CONFIG_010))]
     Reqwest(#[from] reqwest::Error),
     #[error("Encountered an IO error while attempting to read {config_path}: {error}")]
+    #[diagnostic(code(TURBO_CONFIG_011))]
     FailedToReadConfig {
         config_path: AbsoluteSystemPathBuf,
         error: io::Error,
     },
     #[error("Encountered an IO error while attempting to set {config_path}: {error}")]
+    #[diagnostic(code(TURBO_CONFIG_012))]
     FailedToSetConfig {
         config_path: AbsoluteSystemPathBuf,
         error: io::Error,
@@ -70,7 +81,10 @@ pub enum Error {
         "Package tasks (<package>#<task>) are not allowed in single-package repositories: found \
          {task_id}"
     )]
-    #[diagnostic(code(package_task_in_single_package_mode), url("{}/messages/{}", TURBO_SITE, self.code().unwrap().to_string().to_case(Case::Kebab)))]
+    #[diagnostic(
+        code(TURBO_CONFIG_013),
+        url("{}/messages/package-task-in-single-package-mode", TURBO_SITE)
+    )]
     PackageTaskInSinglePackageMode {
         task_id: String,
         #[source_code]
@@ -82,10 +96,11 @@ pub enum Error {
     #[diagnostic(transparent)]
     InvalidEnvPrefix(Box<InvalidEnvPrefixError>),
     #[error(transparent)]
+    #[diagnostic(code(TURBO_CONFIG_014))]
     PathError(#[from] turbopath::PathError),
     #[diagnostic(
-        code(unnecessary_package_task_syntax),
-        url("{}/messages/{}", TURBO_SITE, self.code().unwrap().to_string().to_case(Case::Kebab))
+        code(TURBO_CONFIG_015),
+        url("{}/messages/unnecessary-package-task-syntax", TURBO_SITE)
     )]
     #[error("\"{actual}\". Use \"{wanted}\" instead")]
     UnnecessaryPackageTaskSyntax {
@@ -97,6 +112,7 @@ pub enum Error {
         text: NamedSource,
     },
     #[error("You can only extend from the root workspace")]
+    #[diagnostic(code(TURBO_CONFIG_016))]
     ExtendFromNonRoot {
         #[label("non-root workspace found here")]
         span: Option<SourceSpan>,
@@ -104,6 +120,7 @@ pub enum Error {
         text: NamedSource,
     },
     #[error("`{field}` cannot contain an absolute path")]
+    #[diagnostic(code(TURBO_CONFIG_017))]
     AbsolutePathInConfig {
         field: &'static str,
         #[label("absolute path found here")]
@@ -111,7 +128,18 @@ pub enum Error {
         #[source_code]
         text: NamedSource,
     },
+    #[error("`resources.{field}` must be {expected}")]
+    #[diagnostic(code(TURBO_CONFIG_018))]
+    InvalidResourceLimit {
+        field: &'static str,
+        expected: &'static str,
+        #[label("invalid limit found here")]
+        span: Option<SourceSpan>,
+        #[source_code]
+        text: NamedSource,
+    },
     #[error("No \"extends\" key found")]
+    #[diagnostic(code(TURBO_CONFIG_019))]
     NoExtends {
         #[label("add extends key here")]
         span: Option<SourceSpan>,
@@ -119,17 +147,46 @@ pub enum Error {
         text: NamedSource,
     },
     #[error("Failed to create APIClient: {0}")]
+    #[diagnostic(code(TURBO_CONFIG_020))]
     ApiClient(#[source] turborepo_api_client::Error),
     #[error("{0} is not UTF8.")]
+    #[diagnostic(code(TURBO_CONFIG_021))]
     Encoding(String),
     #[error("TURBO_SIGNATURE should be either 1 or 0.")]
+    #[diagnostic(code(TURBO_CONFIG_022))]
     InvalidSignature,
     #[error("TURBO_REMOTE_CACHE_ENABLED should be either 1 or 0.")]
+    #[diagnostic(code(TURBO_CONFIG_023))]
     InvalidRemoteCacheEnabled,
     #[error("TURBO_REMOTE_CACHE_TIMEOUT: error parsing timeout.")]
+    #[diagnostic(code(TURBO_CONFIG_024))]
     InvalidRemoteCacheTimeout(#[source] std::num::ParseIntError),
+    #[error("TURBO_REMOTE_CACHE_CONNECT_TIMEOUT: error parsing timeout.")]
+    #[diagnostic(code(TURBO_CONFIG_025))]
+    InvalidRemoteCacheConnectTimeout(#[source] std::num::ParseIntError),
+    #[error("TURBO_REMOTE_CACHE_UPLOAD_TIMEOUT: error parsing timeout.")]
+    #[diagnostic(code(TURBO_CONFIG_026))]
+    InvalidRemoteCacheUploadTimeout(#[source] std::num::ParseIntError),
+    #[error("TURBO_REMOTE_CACHE_RETRY_ATTEMPTS: error parsing retry attempts.")]
+    #[diagnostic(code(TURBO_CONFIG_027))]
+    InvalidRemoteCacheRetryAttempts(#[source] std::num::ParseIntError),
+    #[error("TURBO_DAEMON_METRICS_PORT: error parsing port.")]
+    #[diagnostic(code(TURBO_CONFIG_028))]
+    InvalidDaemonMetricsPort(#[source] std::num::ParseIntError),
     #[error("TURBO_PREFLIGHT should be either 1 or 0.")]
+    #[diagnostic(code(TURBO_CONFIG_029))]
     InvalidPreflight,
+    #[error("TURBO_DEBUG_LOGS should be either 1 or 0.")]
+    #[diagnostic(code(TURBO_CONFIG_030))]
+    InvalidDebugLogs,
+    #[error("The `docker` executor requires an `image`")]
+    #[diagnostic(code(TURBO_CONFIG_033))]
+    MissingDockerImage {
+        #[label("add an image here")]
+        span: Option<SourceSpan>,
+        #[source_code]
+        text: NamedSource,
+    },
     #[error(transparent)]
     #[diagnostic(transparent)]
     TurboJsonParseError(#[from] turbo_json::parser::Error),
@@ -146,7 +203,10 @@ macro_rules! create_builder {
 
 const DEFAULT_API_URL: &str = "https://vercel.com/api";
 const DEFAULT_LOGIN_URL: &str = "https://vercel.com";
+const DEFAULT_TELEMETRY_ENDPOINT: &str = "https://telemetry.vercel.com";
 const DEFAULT_TIMEOUT: u64 = 30;
+const DEFAULT_RETRY_ATTEMPTS: u32 = 2;
+const DEFAULT_RETRY_BACKOFF: u64 = 2;
 
 // We intentionally don't derive Serialize so that different parts
 // of the code that want to display the config can tune how they
@@ -173,7 +233,22 @@ pub struct ConfigurationOptions {
     pub(crate) token: Option<String>,
     pub(crate) signature: Option<bool>,
     pub(crate) preflight: Option<bool>,
+    pub(crate) preflight_requests: Option<Vec<String>>,
     pub(crate) timeout: Option<u64>,
+    pub(crate) connect_timeout: Option<u64>,
+    pub(crate) upload_timeout: Option<u64>,
+    pub(crate) retry_attempts: Option<u32>,
+    pub(crate) retry_backoff: Option<u64>,
+    pub(crate) retry_jitter: Option<bool>,
+    pub(crate) fallback_on_rate_limit: Option<bool>,
+    pub(crate) client_cert: Option<String>,
+    pub(crate) client_key: Option<String>,
+    pub(crate) audit_log: Option<String>,
+    pub(crate) auth_profile: Option<String>,
+    pub(crate) daemon_metrics_port: Option<u16>,
+    pub(crate) statsd_address: Option<String>,
+    pub(crate) debug_logs: Option<bool>,
+    pub(crate) telemetry_endpoint: Option<String>,
     pub(crate) enabled: Option<bool>,
     pub(crate) spaces_id: Option<String>,
 }
@@ -225,10 +300,87 @@ impl ConfigurationOptions {
         self.preflight.unwrap_or_default()
     }
 
+    /// Kinds of requests to send a preflight request before, when preflight
+    /// isn't enabled for every request
+    pub fn preflight_requests(&self) -> Option<&[String]> {
+        self.preflight_requests.as_deref()
+    }
+
     pub fn timeout(&self) -> u64 {
         self.timeout.unwrap_or(DEFAULT_TIMEOUT)
     }
 
+    /// A value of 0 means connecting is only limited by `timeout`
+    pub fn connect_timeout(&self) -> u64 {
+        self.connect_timeout.unwrap_or_default()
+    }
+
+    /// Uploads use `timeout` unless an upload timeout is configured
+    pub fn upload_timeout(&self) -> u64 {
+        self.upload_timeout.unwrap_or_else(|| self.timeout())
+    }
+
+    pub fn retry_attempts(&self) -> u32 {
+        self.retry_attempts.unwrap_or(DEFAULT_RETRY_ATTEMPTS)
+    }
+
+    pub fn retry_backoff(&self) -> u64 {
+        self.retry_backoff.unwrap_or(DEFAULT_RETRY_BACKOFF)
+    }
+
+    pub fn retry_jitter(&self) -> bool {
+        self.retry_jitter.unwrap_or(true)
+    }
+
+    /// Stop using the remote cache for the rest of the run if it starts rate
+    /// limiting requests
+    pub fn fallback_on_rate_limit(&self) -> bool {
+        self.fallback_on_rate_limit.unwrap_or_default()
+    }
+
+    /// Path to a PEM encoded certificate to present to the remote cache
+    pub fn client_cert(&self) -> Option<&str> {
+        non_empty_str(self.client_cert.as_deref())
+    }
+
+    /// Path to the PEM encoded private key for `client_cert`. May be omitted
+    /// if the key is included in the certificate file.
+    pub fn client_key(&self) -> Option<&str> {
+        non_empty_str(self.client_key.as_deref())
+    }
+
+    /// File to record remote cache uploads and downloads in
+    pub fn audit_log(&self) -> Option<&str> {
+        non_empty_str(self.audit_log.as_deref())
+    }
+
+    /// Name of the stored credentials to use from the global config's
+    /// `profiles`
+    pub fn auth_profile(&self) -> Option<&str> {
+        non_empty_str(self.auth_profile.as_deref())
+    }
+
+    /// Local port that the daemon serves Prometheus metrics on
+    pub fn daemon_metrics_port(&self) -> Option<u16> {
+        self.daemon_metrics_port
+    }
+
+    /// Address of the StatsD server to send run metrics to
+    pub fn statsd_address(&self) -> Option<&str> {
+        non_empty_str(self.statsd_address.as_deref())
+    }
+
+    /// Write debug logs of every invocation to `.turbo/logs`, regardless of
+    /// the verbosity
+    pub fn debug_logs(&self) -> bool {
+        self.debug_logs.unwrap_or_default()
+    }
+
+    /// Base URL of the collector that telemetry events are sent to
+    pub fn telemetry_endpoint(&self) -> &str {
+        non_empty_str(self.telemetry_endpoint.as_deref()).unwrap_or(DEFAULT_TELEMETRY_ENDPOINT)
+    }
+
     pub fn spaces_id(&self) -> Option<&str> {
         self.spaces_id.as_deref()
     }
@@ -298,6 +450,42 @@ fn get_env_var_config(
     turbo_mapping.insert(OsString::from("turbo_teamid"), "team_id");
     turbo_mapping.insert(OsString::from("turbo_token"), "token");
     turbo_mapping.insert(OsString::from("turbo_remote_cache_timeout"), "timeout");
+    turbo_mapping.insert(
+        OsString::from("turbo_remote_cache_connect_timeout"),
+        "connect_timeout",
+    );
+    turbo_mapping.insert(
+        OsString::from("turbo_remote_cache_upload_timeout"),
+        "upload_timeout",
+    );
+    turbo_mapping.insert(
+        OsString::from("turbo_remote_cache_retry_attempts"),
+        "retry_attempts",
+    );
+    turbo_mapping.insert(
+        OsString::from("turbo_remote_cache_client_cert"),
+        "client_cert",
+    );
+    turbo_mapping.insert(
+        OsString::from("turbo_remote_cache_client_key"),
+        "client_key",
+    );
+    turbo_mapping.insert(OsString::from("turbo_remote_cache_audit_log"), "audit_log");
+    turbo_mapping.insert(
+        OsString::from("turbo_preflight_requests"),
+        "preflight_requests",
+    );
+    turbo_mapping.insert(OsString::from("turbo_auth_profile"), "auth_profile");
+    turbo_mapping.insert(
+        OsString::from("turbo_daemon_metrics_port"),
+        "daemon_metrics_port",
+    );
+    turbo_mapping.insert(OsString::from("turbo_statsd_address"), "statsd_address");
+    turbo_mapping.insert(OsString::from("turbo_debug_logs"), "debug_logs");
+    turbo_mapping.insert(
+        OsString::from("turbo_telemetry_endpoint"),
+        "telemetry_endpoint",
+    );
 
     // We do not enable new config sources:
     // turbo_mapping.insert(String::from("turbo_signature"), "signature"); // new
@@ -345,6 +533,25 @@ fn get_env_var_config(
         None
     };
 
+    // Process debug logs
+    let debug_logs = if let Some(debug_logs) = output_map.get("debug_logs") {
+        match debug_logs.as_str() {
+            "0" => Some(false),
+            "1" => Some(true),
+            _ => return Err(Error::InvalidDebugLogs),
+        }
+    } else {
+        None
+    };
+
+    let preflight_requests = output_map.get("preflight_requests").map(|requests| {
+        requests
+            .split(',')
+            .map(|request| request.trim().to_string())
+            .filter(|request| !request.is_empty())
+            .collect()
+    });
+
     // Process enabled
     let enabled = if let Some(enabled) = output_map.get("enabled") {
         match enabled.as_str() {
@@ -367,6 +574,43 @@ fn get_env_var_config(
         None
     };
 
+    let connect_timeout = if let Some(connect_timeout) = output_map.get("connect_timeout") {
+        Some(
+            connect_timeout
+                .parse::<u64>()
+                .map_err(Error::InvalidRemoteCacheConnectTimeout)?,
+        )
+    } else {
+        None
+    };
+
+    let upload_timeout = if let Some(upload_timeout) = output_map.get("upload_timeout") {
+        Some(
+            upload_timeout
+                .parse::<u64>()
+                .map_err(Error::InvalidRemoteCacheUploadTimeout)?,
+        )
+    } else {
+        None
+    };
+
+    // Process retry attempts
+    let retry_attempts = if let Some(retry_attempts) = output_map.get("retry_attempts") {
+        Some(
+            retry_attempts
+                .parse::<u32>()
+                .map_err(Error::InvalidRemoteCacheRetryAttempts)?,
+        )
+    } else {
+        None
+    };
+
+    let daemon_metrics_port = output_map
+        .get("daemon_metrics_port")
+        .map(|port| port.parse::<u16>())
+        .transpose()
+        .map_err(Error::InvalidDaemonMetricsPort)?;
+
     // We currently don't pick up a Spaces ID via env var, we likely won't
     // continue using the Spaces name, we can add an env var when we have the
     // name we want to stick with.
@@ -378,14 +622,29 @@ fn get_env_var_config(
         team_slug: output_map.get("team_slug").cloned(),
         team_id: output_map.get("team_id").cloned(),
         token: output_map.get("token").cloned(),
+        client_cert: output_map.get("client_cert").cloned(),
+        client_key: output_map.get("client_key").cloned(),
+        audit_log: output_map.get("audit_log").cloned(),
+        auth_profile: output_map.get("auth_profile").cloned(),
+        statsd_address: output_map.get("statsd_address").cloned(),
+        telemetry_endpoint: output_map.get("telemetry_endpoint").cloned(),
 
         // Processed booleans
         signature,
         preflight,
+        preflight_requests,
         enabled,
+        debug_logs,
 
         // Processed numbers
         timeout,
+        connect_timeout,
+        upload_timeout,
+        retry_attempts,
+        daemon_metrics_port,
+        retry_backoff: None,
+        retry_jitter: None,
+        fallback_on_rate_limit: None,
         spaces_id,
     };
 
@@ -428,8 +687,23 @@ fn get_override_env_var_config(
 
         signature: None,
         preflight: None,
+        preflight_requests: None,
         enabled: None,
         timeout: None,
+        connect_timeout: None,
+        upload_timeout: None,
+        retry_attempts: None,
+        retry_backoff: None,
+        retry_jitter: None,
+        fallback_on_rate_limit: None,
+        client_cert: None,
+        client_key: None,
+        audit_log: None,
+        auth_profile: None,
+        daemon_metrics_port: None,
+        statsd_address: None,
+        debug_logs: None,
+        telemetry_endpoint: None,
         spaces_id: None,
     };
 
@@ -514,6 +788,43 @@ impl TurborepoConfigBuilder {
         Ok(global_config)
     }
 
+    fn get_auth_profile(&self, name: &str) -> Result<ConfigurationOptions, Error> {
+        #[derive(Deserialize, Default)]
+        struct GlobalProfiles {
+            #[serde(default)]
+            profiles: HashMap<String, ConfigurationOptions>,
+        }
+
+        let global_config_path = self.global_config_path()?;
+        let contents = global_config_path
+            .read_existing_to_string_or(Ok("{}"))
+            .map_err(|error| Error::FailedToReadConfig {
+                config_path: global_config_path.clone(),
+                error,
+            })?;
+        let GlobalProfiles { mut profiles } = if contents.is_empty() {
+            GlobalProfiles::default()
+        } else {
+            serde_json::from_str(&contents)?
+        };
+
+        // A profile that hasn't been logged into yet is treated the same as
+        // missing global auth so that `turbo login` can create it.
+        let Some(profile) = profiles.remove(name) else {
+            return Ok(ConfigurationOptions::default());
+        };
+
+        // Profiles only hold credentials and where to use them
+        Ok(ConfigurationOptions {
+            api_url: profile.api_url,
+            login_url: profile.login_url,
+            team_slug: profile.team_slug,
+            team_id: profile.team_id,
+            token: profile.token,
+            ..Default::default()
+        })
+    }
+
     fn get_local_config(&self) -> Result<ConfigurationOptions, Error> {
         let local_config_path = self.local_config_path();
         let mut contents = local_config_path
@@ -564,7 +875,22 @@ impl TurborepoConfigBuilder {
     create_builder!(with_signature, signature, Option<bool>);
     create_builder!(with_enabled, enabled, Option<bool>);
     create_builder!(with_preflight, preflight, Option<bool>);
+    create_builder!(
+        with_preflight_requests,
+        preflight_requests,
+        Option<Vec<String>>
+    );
     create_builder!(with_timeout, timeout, Option<u64>);
+    create_builder!(with_connect_timeout, connect_timeout, Option<u64>);
+    create_builder!(with_upload_timeout, upload_timeout, Option<u64>);
+    create_builder!(with_client_cert, client_cert, Option<String>);
+    create_builder!(with_client_key, client_key, Option<String>);
+    create_builder!(with_audit_log, audit_log, Option<String>);
+    create_builder!(with_auth_profile, auth_profile, Option<String>);
+    create_builder!(with_daemon_metrics_port, daemon_metrics_port, Option<u16>);
+    create_builder!(with_statsd_address, statsd_address, Option<String>);
+    create_builder!(with_debug_logs, debug_logs, Option<bool>);
+    create_builder!(with_telemetry_endpoint, telemetry_endpoint, Option<String>);
 
     pub fn build(&self) -> Result<ConfigurationOptions, Error> {
         // Priority, from least significant to most significant:
@@ -600,12 +926,34 @@ impl TurborepoConfigBuilder {
             Err(e)
         })?;
         let global_config = self.get_global_config()?;
-        let global_auth = self.get_global_auth()?;
         let local_config = self.get_local_config()?;
         let env_vars = self.get_environment();
         let env_var_config = get_env_var_config(&env_vars)?;
         let override_env_var_config = get_override_env_var_config(&env_vars)?;
 
+        // The auth profile decides which credentials get layered in, so it needs
+        // to be resolved before everything else.
+        let auth_profile = [
+            &self.override_config,
+            &env_var_config,
+            &local_config,
+            &global_config,
+        ]
+        .into_iter()
+        .find_map(|config| config.auth_profile())
+        .map(str::to_owned);
+        let (global_config, global_auth) = match auth_profile.as_deref() {
+            Some(auth_profile) => (
+                // Don't let the default token leak into a profile without one
+                ConfigurationOptions {
+                    token: None,
+                    ..global_config
+                },
+                self.get_auth_profile(auth_profile)?,
+            ),
+            None => (global_config, self.get_global_auth()?),
+        };
+
         let sources = [
             root_package_json.get_configuration_options(),
             turbo_json.get_configuration_options(),
@@ -645,9 +993,60 @@ impl TurborepoConfigBuilder {
                     if let Some(preflight) = current_source_config.preflight {
                         acc.preflight = Some(preflight);
                     }
+                    if let Some(preflight_requests) =
+                        current_source_config.preflight_requests.clone()
+                    {
+                        acc.preflight_requests = Some(preflight_requests);
+                    }
                     if let Some(timeout) = current_source_config.timeout {
                         acc.timeout = Some(timeout);
                     }
+                    if let Some(connect_timeout) = current_source_config.connect_timeout {
+                        acc.connect_timeout = Some(connect_timeout);
+                    }
+                    if let Some(upload_timeout) = current_source_config.upload_timeout {
+                        acc.upload_timeout = Some(upload_timeout);
+                    }
+                    if let Some(retry_attempts) = current_source_config.retry_attempts {
+                        acc.retry_attempts = Some(retry_attempts);
+                    }
+                    if let Some(retry_backoff) = current_source_config.retry_backoff {
+                        acc.retry_backoff = Some(retry_backoff);
+                    }
+                    if let Some(retry_jitter) = current_source_config.retry_jitter {
+                        acc.retry_jitter = Some(retry_jitter);
+                    }
+                    if let Some(fallback_on_rate_limit) =
+                        current_source_config.fallback_on_rate_limit
+                    {
+                        acc.fallback_on_rate_limit = Some(fallback_on_rate_limit);
+                    }
+                    if let Some(client_cert) = current_source_config.client_cert.clone() {
+                        acc.client_cert = Some(client_cert);
+                    }
+                    if let Some(client_key) = current_source_config.client_key.clone() {
+                        acc.client_key = Some(client_key);
+                    }
+                    if let Some(audit_log) = current_source_config.audit_log.clone() {
+                        acc.audit_log = Some(audit_log);
+                    }
+                    if let Some(auth_profile) = current_source_config.auth_profile.clone() {
+                        acc.auth_profile = Some(auth_profile);
+                    }
+                    if let Some(daemon_metrics_port) = current_source_config.daemon_metrics_port {
+                        acc.daemon_metrics_port = Some(daemon_metrics_port);
+                    }
+                    if let Some(statsd_address) = current_source_config.statsd_address.clone() {
+                        acc.statsd_address = Some(statsd_address);
+                    }
+                    if let Some(debug_logs) = current_source_config.debug_logs {
+                        acc.debug_logs = Some(debug_logs);
+                    }
+                    if let Some(telemetry_endpoint) =
+                        current_source_config.telemetry_endpoint.clone()
+                    {
+                        acc.telemetry_endpoint = Some(telemetry_endpoint);
+                    }
                     if let Some(spaces_id) = current_source_config.spaces_id {
                         acc.spaces_id = Some(spaces_id);
                     }
@@ -668,7 +1067,8 @@ mod test {
 
     use crate::config::{
         get_env_var_config, get_override_env_var_config, ConfigurationOptions,
-        TurborepoConfigBuilder, DEFAULT_API_URL, DEFAULT_LOGIN_URL, DEFAULT_TIMEOUT,
+        TurborepoConfigBuilder, DEFAULT_API_URL, DEFAULT_LOGIN_URL, DEFAULT_RETRY_ATTEMPTS,
+        DEFAULT_RETRY_BACKOFF, DEFAULT_TELEMETRY_ENDPOINT, DEFAULT_TIMEOUT,
     };
 
     #[test]
@@ -683,6 +1083,15 @@ mod test {
         assert!(defaults.enabled());
         assert!(!defaults.preflight());
         assert_eq!(defaults.timeout(), DEFAULT_TIMEOUT);
+        assert_eq!(defaults.connect_timeout(), 0);
+        assert_eq!(defaults.upload_timeout(), DEFAULT_TIMEOUT);
+        assert_eq!(defaults.retry_attempts(), DEFAULT_RETRY_ATTEMPTS);
+        assert_eq!(defaults.retry_backoff(), DEFAULT_RETRY_BACKOFF);
+        assert!(defaults.retry_jitter());
+        assert_eq!(defaults.daemon_metrics_port(), None);
+        assert_eq!(defaults.statsd_address(), None);
+        assert!(!defaults.debug_logs());
+        assert_eq!(defaults.telemetry_endpoint(), DEFAULT_TELEMETRY_ENDPOINT);
         assert_eq!(defaults.spaces_id(), None);
     }
 
@@ -696,6 +1105,9 @@ mod test {
         let turbo_teamid = "team_nLlpyC6REAqxydlFKbrMDlud";
         let turbo_token = "abcdef1234567890abcdef";
         let turbo_remote_cache_timeout = 200;
+        let turbo_remote_cache_connect_timeout = 5;
+        let turbo_remote_cache_upload_timeout = 600;
+        let turbo_remote_cache_retry_attempts = 5;
 
         env.insert("turbo_api".into(), turbo_api.into());
         env.insert("turbo_login".into(), turbo_login.into());
@@ -706,6 +1118,18 @@ mod test {
             "turbo_remote_cache_timeout".into(),
             turbo_remote_cache_timeout.to_string().into(),
         );
+        env.insert(
+            "turbo_remote_cache_connect_timeout".into(),
+            turbo_remote_cache_connect_timeout.to_string().into(),
+        );
+        env.insert(
+            "turbo_remote_cache_upload_timeout".into(),
+            turbo_remote_cache_upload_timeout.to_string().into(),
+        );
+        env.insert(
+            "turbo_remote_cache_retry_attempts".into(),
+            turbo_remote_cache_retry_attempts.to_string().into(),
+        );
 
         let config = get_env_var_config(&env).unwrap();
         assert_eq!(turbo_api, config.api_url.unwrap());
@@ -714,6 +1138,18 @@ mod test {
         assert_eq!(turbo_teamid, config.team_id.unwrap());
         assert_eq!(turbo_token, config.token.unwrap());
         assert_eq!(turbo_remote_cache_timeout, config.timeout.unwrap());
+        assert_eq!(
+            turbo_remote_cache_connect_timeout,
+            config.connect_timeout.unwrap()
+        );
+        assert_eq!(
+            turbo_remote_cache_upload_timeout,
+            config.upload_timeout.unwrap()
+        );
+        assert_eq!(
+            turbo_remote_cache_retry_attempts,
+            config.retry_attempts.unwrap()
+        );
     }
 
     #[test]
@@ -803,4 +1239,200 @@ mod test {
         assert_eq!(config.token().unwrap(), vercel_artifacts_token);
         assert_eq!(config.spaces_id().unwrap(), "my-spaces-id");
     }
+
+    #[test]
+    fn test_turbo_json_remote_cache() {
+        let tmp_dir = TempDir::new().unwrap();
+        let repo_root = AbsoluteSystemPathBuf::try_from(tmp_dir.path()).unwrap();
+        let global_config_path = AbsoluteSystemPathBuf::try_from(
+            TempDir::new().unwrap().path().join("nonexistent.json"),
+        )
+        .unwrap();
+
+        repo_root
+            .join_component("turbo.json")
+            .create_with_contents(
+                r#"{"remoteCache": {
+                    "apiUrl": "https://cache.example.com/api",
+                    "loginUrl": "https://cache.example.com",
+                    "teamId": "team_shared"
+                }}"#,
+            )
+            .unwrap();
+
+        let builder = TurborepoConfigBuilder {
+            repo_root: repo_root.clone(),
+            override_config: ConfigurationOptions::default(),
+            global_config_path: Some(global_config_path.clone()),
+            environment: HashMap::new(),
+        };
+        let config = builder.build().unwrap();
+        assert_eq!(config.api_url(), "https://cache.example.com/api");
+        assert_eq!(config.login_url(), "https://cache.example.com");
+        assert_eq!(config.team_id(), Some("team_shared"));
+
+        // Environment variables take precedence over the shared configuration
+        let mut env: HashMap<OsString, OsString> = HashMap::new();
+        env.insert(
+            "turbo_api".into(),
+            "https://ci-cache.example.com/api".into(),
+        );
+        env.insert("turbo_teamid".into(), "team_ci".into());
+        let builder = TurborepoConfigBuilder {
+            repo_root,
+            override_config: ConfigurationOptions::default(),
+            global_config_path: Some(global_config_path),
+            environment: env,
+        };
+        let config = builder.build().unwrap();
+        assert_eq!(config.api_url(), "https://ci-cache.example.com/api");
+        assert_eq!(config.login_url(), "https://cache.example.com");
+        assert_eq!(config.team_id(), Some("team_ci"));
+    }
+
+    #[test]
+    fn test_client_certificate() {
+        let tmp_dir = TempDir::new().unwrap();
+        let repo_root = AbsoluteSystemPathBuf::try_from(tmp_dir.path()).unwrap();
+        let global_config_path = AbsoluteSystemPathBuf::try_from(
+            TempDir::new().unwrap().path().join("nonexistent.json"),
+        )
+        .unwrap();
+
+        repo_root
+            .join_component("turbo.json")
+            .create_with_contents(r#"{"remoteCache": {"clientCert": "certs/client.pem"}}"#)
+            .unwrap();
+
+        let mut env: HashMap<OsString, OsString> = HashMap::new();
+        env.insert(
+            "turbo_remote_cache_client_key".into(),
+            "/secrets/client.key".into(),
+        );
+        let builder = TurborepoConfigBuilder {
+            repo_root,
+            override_config: ConfigurationOptions::default(),
+            global_config_path: Some(global_config_path),
+            environment: env,
+        };
+        let config = builder.build().unwrap();
+        assert_eq!(config.client_cert(), Some("certs/client.pem"));
+        assert_eq!(config.client_key(), Some("/secrets/client.key"));
+    }
+
+    #[test]
+    fn test_preflight_requests() {
+        let mut env: HashMap<OsString, OsString> = HashMap::new();
+        env.insert("turbo_preflight_requests".into(), "uploads, api,".into());
+
+        let config = get_env_var_config(&env).unwrap();
+        assert_eq!(
+            config.preflight_requests(),
+            Some(["uploads".to_string(), "api".to_string()].as_slice())
+        );
+    }
+
+    #[test]
+    fn test_daemon_metrics_port() {
+        let mut env: HashMap<OsString, OsString> = HashMap::new();
+        env.insert("turbo_daemon_metrics_port".into(), "9464".into());
+        let config = get_env_var_config(&env).unwrap();
+        assert_eq!(config.daemon_metrics_port(), Some(9464));
+
+        env.insert("turbo_daemon_metrics_port".into(), "not-a-port".into());
+        assert!(get_env_var_config(&env).is_err());
+    }
+
+    #[test]
+    fn test_upload_timeout() {
+        let mut env: HashMap<OsString, OsString> = HashMap::new();
+        env.insert("turbo_remote_cache_timeout".into(), "0".into());
+        let config = get_env_var_config(&env).unwrap();
+        assert_eq!(config.upload_timeout(), 0);
+
+        env.insert("turbo_remote_cache_upload_timeout".into(), "600".into());
+        let config = get_env_var_config(&env).unwrap();
+        assert_eq!(config.upload_timeout(), 600);
+    }
+
+    #[test]
+    fn test_debug_logs() {
+        let mut env: HashMap<OsString, OsString> = HashMap::new();
+        env.insert("turbo_debug_logs".into(), "1".into());
+        let config = get_env_var_config(&env).unwrap();
+        assert!(config.debug_logs());
+
+        env.insert("turbo_debug_logs".into(), "true".into());
+        assert!(get_env_var_config(&env).is_err());
+    }
+
+    #[test]
+    fn test_auth_profile() {
+        let tmp_dir = TempDir::new().unwrap();
+        let repo_root = AbsoluteSystemPathBuf::try_from(tmp_dir.path()).unwrap();
+        let global_config_dir = TempDir::new().unwrap();
+        let global_config_path =
+            AbsoluteSystemPathBuf::try_from(global_config_dir.path().join("config.json")).unwrap();
+        global_config_path
+            .create_with_contents(
+                r#"{
+                    "token": "default-token",
+                    "profiles": {
+                        "work": {
+                            "token": "work-token",
+                            "apiUrl": "https://cache.work.example.com/api",
+                            "teamId": "team_work"
+                        },
+                        "oss": { "teamSlug": "oss" }
+                    }
+                }"#,
+            )
+            .unwrap();
+
+        let build = |override_config: ConfigurationOptions, env: &[(&str, &str)]| {
+            TurborepoConfigBuilder {
+                repo_root: repo_root.clone(),
+                override_config,
+                global_config_path: Some(global_config_path.clone()),
+                environment: env
+                    .iter()
+                    .map(|(k, v)| (OsString::from(k), OsString::from(v)))
+                    .collect(),
+            }
+            .build()
+            .unwrap()
+        };
+
+        let config = build(ConfigurationOptions::default(), &[]);
+        assert_eq!(config.token(), Some("default-token"));
+        assert_eq!(config.api_url(), DEFAULT_API_URL);
+
+        let config = build(
+            ConfigurationOptions::default(),
+            &[("turbo_auth_profile", "work")],
+        );
+        assert_eq!(config.auth_profile(), Some("work"));
+        assert_eq!(config.token(), Some("work-token"));
+        assert_eq!(config.api_url(), "https://cache.work.example.com/api");
+        assert_eq!(config.team_id(), Some("team_work"));
+
+        // The flag wins over the environment, and the default token isn't used
+        // for a profile that doesn't have one
+        let config = build(
+            ConfigurationOptions {
+                auth_profile: Some("oss".into()),
+                ..Default::default()
+            },
+            &[("turbo_auth_profile", "work")],
+        );
+        assert_eq!(config.token(), None);
+        assert_eq!(config.team_slug(), Some("oss"));
+        assert_eq!(config.team_id(), None);
+
+        let config = build(
+            ConfigurationOptions::default(),
+            &[("turbo_auth_profile", "new")],
+        );
+        assert_eq!(config.token(), None);
+    }
 }
@@ -114,6 +114,32 @@ impl<T> DaemonClient<T> {
         Ok(())
     }
 
+    /// Report the results of a finished run for the daemon's metrics.
+    pub async fn notify_run_finished(
+        &mut self,
+        exit_code: i32,
+        tasks: Vec<proto::TaskResult>,
+    ) -> Result<(), DaemonError> {
+        self.client
+            .notify_run_finished(proto::NotifyRunFinishedRequest { exit_code, tasks })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Replace the filter of the daemon's log file.
+    pub async fn set_log_filter(&mut self, filter: String) -> Result<(), DaemonError> {
+        self.client
+            .set_log_filter(proto::SetLogFilterRequest { filter })
+            .await
+            .map_err(|status| match status.code() {
+                Code::InvalidArgument => DaemonError::InvalidLogFilter(status.message().to_owned()),
+                _ => status.into(),
+            })?;
+
+        Ok(())
+    }
+
     /// Get the status of the daemon.
     pub async fn status(&mut self) -> Result<proto::DaemonStatus, DaemonError> {
         self.client
@@ -206,6 +232,9 @@ pub enum DaemonError {
     #[error("unable to construct log file name: {0}")]
     InvalidLogFile(#[from] time::Error),
 
+    #[error("{0}")]
+    InvalidLogFilter(String),
+
     #[error("unable to complete daemon clean")]
     CleanFailed,
 
@@ -639,6 +639,20 @@ mod test {
         ) -> Result<tonic::Response<proto::DiscoverPackagesResponse>, tonic::Status> {
             unimplemented!()
         }
+
+        async fn notify_run_finished(
+            &self,
+            _req: tonic::Request<proto::NotifyRunFinishedRequest>,
+        ) -> Result<tonic::Response<proto::NotifyRunFinishedResponse>, tonic::Status> {
+            unimplemented!()
+        }
+
+        async fn set_log_filter(
+            &self,
+            _req: tonic::Request<proto::SetLogFilterRequest>,
+        ) -> Result<tonic::Response<proto::SetLogFilterResponse>, tonic::Status> {
+            unimplemented!()
+        }
     }
 
     #[tokio::test]
@@ -0,0 +1,282 @@
+//! Prometheus metrics of the daemon.
+//!
+//! When `daemonMetricsPort` is configured, the daemon serves its metrics in
+//! the Prometheus text format at `http://127.0.0.1:<port>/metrics`. Runs that
+//! use the daemon report their results to it once they finish, which together
+//! with the daemon's own file watching make up the metrics. Counters start
+//! from zero whenever the daemon starts.
+
+use std::{
+    fmt::Write,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Instant,
+};
+
+use axum::{http::header::CONTENT_TYPE, routing::get, Router};
+use tracing::{debug, warn};
+
+use super::proto;
+
+/// The upper bounds of the buckets of the task duration histograms, in
+/// seconds.
+const DURATION_BUCKETS: &[f64] = &[0.1, 0.5, 1.0, 5.0, 10.0, 30.0, 60.0, 300.0, 900.0];
+
+const TEXT_FORMAT: &str = "text/plain; version=0.0.4";
+
+#[derive(Debug)]
+struct Histogram {
+    // Counts per bucket, not cumulative
+    buckets: [u64; DURATION_BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self {
+            buckets: [0; DURATION_BUCKETS.len()],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+}
+
+impl Histogram {
+    fn observe(&mut self, value: f64) {
+        if let Some(bucket) = DURATION_BUCKETS.iter().position(|bound| value <= *bound) {
+            self.buckets[bucket] += 1;
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+
+    fn render(&self, out: &mut String, name: &str, labels: &str) {
+        let mut cumulative = 0;
+        for (bound, count) in DURATION_BUCKETS.iter().zip(self.buckets) {
+            cumulative += count;
+            writeln!(out, "{name}_bucket{{{labels},le=\"{bound}\"}} {cumulative}").ok();
+        }
+        writeln!(out, "{name}_bucket{{{labels},le=\"+Inf\"}} {}", self.count).ok();
+        writeln!(out, "{name}_sum{{{labels}}} {}", self.sum).ok();
+        writeln!(out, "{name}_count{{{labels}}} {}", self.count).ok();
+    }
+}
+
+#[derive(Debug, Default)]
+struct RunStats {
+    successful_runs: u64,
+    failed_runs: u64,
+    failed_tasks: u64,
+    hit_durations: Histogram,
+    miss_durations: Histogram,
+}
+
+#[derive(Debug)]
+pub struct Metrics {
+    start_time: Instant,
+    runs: Mutex<RunStats>,
+    file_events: AtomicU64,
+    watched_outputs: AtomicUsize,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self {
+            start_time: Instant::now(),
+            runs: Mutex::default(),
+            file_events: AtomicU64::default(),
+            watched_outputs: AtomicUsize::default(),
+        }
+    }
+}
+
+impl Metrics {
+    pub fn record_run(&self, run: &proto::NotifyRunFinishedRequest) {
+        let mut stats = self.runs.lock().expect("metrics lock poisoned");
+        match run.exit_code {
+            0 => stats.successful_runs += 1,
+            _ => stats.failed_runs += 1,
+        }
+        for task in &run.tasks {
+            if task.failed {
+                stats.failed_tasks += 1;
+            }
+            let duration = task.duration_msec as f64 / 1000.0;
+            match task.cache_hit {
+                true => stats.hit_durations.observe(duration),
+                false => stats.miss_durations.observe(duration),
+            }
+        }
+    }
+
+    pub fn record_file_event(&self) {
+        self.file_events.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn set_watched_outputs(&self, count: usize) {
+        self.watched_outputs.store(count, Ordering::Relaxed);
+    }
+
+    /// Renders the metrics in the Prometheus text format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let stats = self.runs.lock().expect("metrics lock poisoned");
+
+        out.push_str("# HELP turbo_daemon_uptime_seconds Time since the daemon started.\n");
+        out.push_str("# TYPE turbo_daemon_uptime_seconds gauge\n");
+        writeln!(
+            out,
+            "turbo_daemon_uptime_seconds {}",
+            self.start_time.elapsed().as_secs_f64()
+        )
+        .ok();
+
+        out.push_str("# HELP turbo_runs_total Runs that finished, by outcome.\n");
+        out.push_str("# TYPE turbo_runs_total counter\n");
+        writeln!(
+            out,
+            "turbo_runs_total{{outcome=\"success\"}} {}",
+            stats.successful_runs
+        )
+        .ok();
+        writeln!(
+            out,
+            "turbo_runs_total{{outcome=\"failure\"}} {}",
+            stats.failed_runs
+        )
+        .ok();
+
+        out.push_str("# HELP turbo_tasks_total Tasks of finished runs, by cache status.\n");
+        out.push_str("# TYPE turbo_tasks_total counter\n");
+        writeln!(
+            out,
+            "turbo_tasks_total{{cache=\"hit\"}} {}",
+            stats.hit_durations.count
+        )
+        .ok();
+        writeln!(
+            out,
+            "turbo_tasks_total{{cache=\"miss\"}} {}",
+            stats.miss_durations.count
+        )
+        .ok();
+
+        out.push_str("# HELP turbo_task_failures_total Tasks of finished runs that failed.\n");
+        out.push_str("# TYPE turbo_task_failures_total counter\n");
+        writeln!(out, "turbo_task_failures_total {}", stats.failed_tasks).ok();
+
+        out.push_str(
+            "# HELP turbo_task_duration_seconds Duration of the tasks of finished runs, by cache \
+             status.\n",
+        );
+        out.push_str("# TYPE turbo_task_duration_seconds histogram\n");
+        stats
+            .hit_durations
+            .render(&mut out, "turbo_task_duration_seconds", "cache=\"hit\"");
+        stats
+            .miss_durations
+            .render(&mut out, "turbo_task_duration_seconds", "cache=\"miss\"");
+
+        out.push_str(
+            "# HELP turbo_daemon_file_events_total File system events received by the file \
+             watcher.\n",
+        );
+        out.push_str("# TYPE turbo_daemon_file_events_total counter\n");
+        writeln!(
+            out,
+            "turbo_daemon_file_events_total {}",
+            self.file_events.load(Ordering::Relaxed)
+        )
+        .ok();
+
+        out.push_str(
+            "# HELP turbo_daemon_watched_outputs Task outputs that are watched for changes.\n",
+        );
+        out.push_str("# TYPE turbo_daemon_watched_outputs gauge\n");
+        writeln!(
+            out,
+            "turbo_daemon_watched_outputs {}",
+            self.watched_outputs.load(Ordering::Relaxed)
+        )
+        .ok();
+
+        out
+    }
+}
+
+/// Serves the metrics on `port` of the loopback interface. The server runs
+/// until the future is dropped.
+pub async fn serve(metrics: Arc<Metrics>, port: u16) {
+    let app = Router::new().route(
+        "/metrics",
+        get(move || {
+            let metrics = metrics.clone();
+            async move { ([(CONTENT_TYPE, TEXT_FORMAT)], metrics.render()) }
+        }),
+    );
+    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+    let server = match axum::Server::try_bind(&addr) {
+        Ok(server) => server,
+        Err(e) => {
+            warn!("unable to serve metrics on {addr}: {e}");
+            return;
+        }
+    };
+    debug!("serving metrics on {addr}");
+    if let Err(e) = server.serve(app.into_make_service()).await {
+        warn!("metrics server failed: {e}");
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Metrics;
+    use crate::daemon::proto;
+
+    #[test]
+    fn test_render() {
+        let metrics = Metrics::default();
+        metrics.record_run(&proto::NotifyRunFinishedRequest {
+            exit_code: 1,
+            tasks: vec![
+                proto::TaskResult {
+                    cache_hit: true,
+                    failed: false,
+                    duration_msec: 50,
+                },
+                proto::TaskResult {
+                    cache_hit: false,
+                    failed: true,
+                    duration_msec: 12_000,
+                },
+            ],
+        });
+        metrics.record_file_event();
+        metrics.set_watched_outputs(3);
+
+        let rendered = metrics.render();
+        for line in [
+            "turbo_runs_total{outcome=\"success\"} 0",
+            "turbo_runs_total{outcome=\"failure\"} 1",
+            "turbo_tasks_total{cache=\"hit\"} 1",
+            "turbo_tasks_total{cache=\"miss\"} 1",
+            "turbo_task_failures_total 1",
+            "turbo_task_duration_seconds_bucket{cache=\"hit\",le=\"0.1\"} 1",
+            "turbo_task_duration_seconds_bucket{cache=\"miss\",le=\"10\"} 0",
+            "turbo_task_duration_seconds_bucket{cache=\"miss\",le=\"30\"} 1",
+            "turbo_task_duration_seconds_bucket{cache=\"miss\",le=\"+Inf\"} 1",
+            "turbo_task_duration_seconds_sum{cache=\"miss\"} 12",
+            "turbo_daemon_file_events_total 1",
+            "turbo_daemon_watched_outputs 3",
+        ] {
+            assert!(
+                rendered.lines().any(|rendered| rendered == line),
+                "missing {line} in:\n{rendered}"
+            );
+        }
+    }
+}
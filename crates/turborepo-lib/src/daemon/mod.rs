@@ -26,6 +26,7 @@ mod client;
 mod connector;
 mod default_timeout_layer;
 pub(crate) mod endpoint;
+mod metrics;
 mod server;
 
 pub use client::{DaemonClient, DaemonError};
@@ -100,7 +101,7 @@ pub(crate) mod proto {
     /// - Bump the minor version if adding new features, such that clients can
     ///   mandate at least some set of features on the target server.
     /// - Bump the patch version if making backwards compatible bug fixes.
-    pub const VERSION: &str = "1.11.0";
+    pub const VERSION: &str = "1.13.0";
 
     impl From<PackageManager> for turborepo_repository::package_manager::PackageManager {
         fn from(pm: PackageManager) -> Self {
@@ -36,10 +36,13 @@ use turborepo_repository::{
     package_manager,
 };
 
-use super::{bump_timeout::BumpTimeout, endpoint::SocketOpenError, proto};
-use crate::daemon::{
-    bump_timeout_layer::BumpTimeoutLayer, default_timeout_layer::DefaultTimeoutLayer,
-    endpoint::listen_socket, Paths,
+use super::{bump_timeout::BumpTimeout, endpoint::SocketOpenError, metrics::Metrics, proto};
+use crate::{
+    daemon::{
+        bump_timeout_layer::BumpTimeoutLayer, default_timeout_layer::DefaultTimeoutLayer,
+        endpoint::listen_socket, Paths,
+    },
+    tracing::{DaemonLogFilter, LogFilterError},
 };
 
 #[derive(Debug)]
@@ -133,6 +136,8 @@ pub struct TurboGrpcService<S> {
     paths: Paths,
     timeout: Duration,
     external_shutdown: S,
+    metrics_port: Option<u16>,
+    log_filter: Option<DaemonLogFilter>,
 
     package_discovery_backup: LocalPackageDiscoveryBuilder,
 }
@@ -164,9 +169,23 @@ where
             paths,
             timeout,
             external_shutdown,
+            metrics_port: None,
+            log_filter: None,
             package_discovery_backup,
         }
     }
+
+    /// Serve Prometheus metrics on the given local port
+    pub fn with_metrics_port(mut self, metrics_port: Option<u16>) -> Self {
+        self.metrics_port = metrics_port;
+        self
+    }
+
+    /// Allow clients to change the filter of the daemon's log file
+    pub fn with_log_filter(mut self, log_filter: DaemonLogFilter) -> Self {
+        self.log_filter = Some(log_filter);
+        self
+    }
 }
 
 impl<S> TurboGrpcService<S>
@@ -179,6 +198,8 @@ where
             paths,
             repo_root,
             timeout,
+            metrics_port,
+            log_filter,
             package_discovery_backup,
         } = self;
 
@@ -188,12 +209,17 @@ where
         let (trigger_shutdown, mut shutdown_signal) = mpsc::channel::<()>(1);
 
         let package_discovery_backup = package_discovery_backup.build()?;
+        let metrics = Arc::new(Metrics::default());
         let (service, exit_root_watch, watch_root_handle) = TurboGrpcServiceInner::new(
             package_discovery_backup,
             repo_root.clone(),
             trigger_shutdown,
             paths.log_file,
+            metrics.clone(),
+            log_filter,
         );
+        let metrics_handle =
+            metrics_port.map(|port| tokio::spawn(super::metrics::serve(metrics, port)));
 
         let running = Arc::new(AtomicBool::new(true));
         let (_pid_lock, stream) =
@@ -239,6 +265,9 @@ where
         tracing::debug!("server started");
         let _ = server_fut.await;
         tracing::debug!("server exited");
+        if let Some(metrics_handle) = metrics_handle {
+            metrics_handle.abort();
+        }
         // Ensure our timer will exit
         running.store(false, Ordering::SeqCst);
         // We expect to have a signal from the grpc server on what triggered the exit
@@ -261,6 +290,8 @@ struct TurboGrpcServiceInner {
     start_time: Instant,
     log_file: AbsoluteSystemPathBuf,
     package_discovery: Arc<WatchingPackageDiscovery>,
+    metrics: Arc<Metrics>,
+    log_filter: Option<DaemonLogFilter>,
 }
 
 // we have a grpc service that uses watching package discovery, and where the
@@ -272,6 +303,8 @@ impl TurboGrpcServiceInner {
         repo_root: AbsoluteSystemPathBuf,
         trigger_shutdown: mpsc::Sender<()>,
         log_file: AbsoluteSystemPathBuf,
+        metrics: Arc<Metrics>,
+        log_filter: Option<DaemonLogFilter>,
     ) -> (
         Self,
         oneshot::Sender<()>,
@@ -293,6 +326,7 @@ impl TurboGrpcServiceInner {
             repo_root.clone(),
             trigger_shutdown.clone(),
             root_watch_exit_signal,
+            metrics.clone(),
         ));
 
         (
@@ -303,6 +337,8 @@ impl TurboGrpcServiceInner {
                 times_saved: Arc::new(Mutex::new(HashMap::new())),
                 start_time: Instant::now(),
                 log_file,
+                metrics,
+                log_filter,
             },
             exit_root_watch,
             watch_root_handle,
@@ -329,6 +365,7 @@ impl TurboGrpcServiceInner {
         {
             let mut times_saved = self.times_saved.lock().expect("times saved lock poisoned");
             times_saved.insert(hash, time_saved);
+            self.metrics.set_watched_outputs(times_saved.len());
         }
         Ok(())
     }
@@ -356,6 +393,7 @@ async fn watch_root(
     root: AbsoluteSystemPathBuf,
     trigger_shutdown: mpsc::Sender<()>,
     mut exit_signal: oneshot::Receiver<()>,
+    metrics: Arc<Metrics>,
 ) -> Result<(), WatchError> {
     let mut recv_events = filewatching_access
         .watcher
@@ -376,6 +414,7 @@ async fn watch_root(
                     break;
                 };
                 tracing::debug!("root watcher received event: {:?}", event);
+                metrics.record_file_event();
                 let should_trigger_shutdown = match event {
                     // filewatching can throw some weird events, so check that the root is actually gone
                     // before triggering a shutdown
@@ -543,6 +582,32 @@ impl proto::turbod_server::Turbod for TurboGrpcServiceInner {
                 }
             })
     }
+
+    async fn notify_run_finished(
+        &self,
+        request: tonic::Request<proto::NotifyRunFinishedRequest>,
+    ) -> Result<tonic::Response<proto::NotifyRunFinishedResponse>, tonic::Status> {
+        self.metrics.record_run(&request.into_inner());
+        Ok(tonic::Response::new(proto::NotifyRunFinishedResponse {}))
+    }
+
+    async fn set_log_filter(
+        &self,
+        request: tonic::Request<proto::SetLogFilterRequest>,
+    ) -> Result<tonic::Response<proto::SetLogFilterResponse>, tonic::Status> {
+        let Some(log_filter) = &self.log_filter else {
+            return Err(tonic::Status::unimplemented(
+                "daemon does not support changing its log filter",
+            ));
+        };
+        let filter = request.into_inner().filter;
+        log_filter.set(&filter).map_err(|e| match e {
+            LogFilterError::Parse(_) => tonic::Status::invalid_argument(e.to_string()),
+            LogFilterError::Reload(_) => tonic::Status::internal(e.to_string()),
+        })?;
+        info!("log filter changed to {filter}");
+        Ok(tonic::Response::new(proto::SetLogFilterResponse {}))
+    }
 }
 
 /// Determine whether a server can serve a client's request based on its
@@ -1,6 +1,5 @@
 use std::collections::{HashMap, HashSet, VecDeque};
 
-use convert_case::{Case, Casing};
 use itertools::Itertools;
 use miette::{Diagnostic, NamedSource, SourceSpan};
 use turbopath::AbsoluteSystemPath;
@@ -12,36 +11,39 @@ use super::Engine;
 use crate::{
     config,
     run::task_id::{TaskId, TaskName},
+    suggestions,
     task_graph::TaskDefinition,
     turbo_json::{validate_extends, validate_no_package_task_syntax, RawTaskDefinition, TurboJson},
 };
 
 #[derive(Debug, thiserror::Error, Diagnostic)]
 #[error("could not find task `{name}` in project")]
+#[diagnostic(code(TURBO_TASK_001))]
 pub struct MissingTaskError {
     name: String,
     #[label]
     span: Option<SourceSpan>,
     #[source_code]
     text: NamedSource,
+    #[help]
+    help: Option<String>,
 }
 
 #[derive(Debug, thiserror::Error, Diagnostic)]
 pub enum Error {
     #[error("missing tasks in project")]
+    #[diagnostic(code(TURBO_TASK_002))]
     MissingTasks(#[related] Vec<MissingTaskError>),
     #[error("No package.json for {workspace}")]
+    #[diagnostic(code(TURBO_TASK_003))]
     MissingPackageJson { workspace: PackageName },
     #[error(
         "{task_id} needs an entry in turbo.json before it can be depended on because it is a task \
          declared in the root package.json"
     )]
     #[diagnostic(
-        code(missing_root_task_in_turbo_json),
-        url(
-            "{}/messages/{}",
-            TURBO_SITE, self.code().unwrap().to_string().to_case(Case::Kebab)
-        )
+        code(TURBO_TASK_004),
+        url("{}/messages/missing-root-task-in-turbo-json", TURBO_SITE)
     )]
     MissingRootTaskInTurboJson {
         task_id: String,
@@ -51,6 +53,7 @@ pub enum Error {
         text: NamedSource,
     },
     #[error("Could not find package \"{package}\" from task \"{task_id}\" in project")]
+    #[diagnostic(code(TURBO_TASK_005))]
     MissingPackageFromTask {
         #[label]
         span: Option<SourceSpan>,
@@ -60,6 +63,7 @@ pub enum Error {
         task_id: String,
     },
     #[error("Could not find \"{task_id}\" in root turbo.json or \"{task_name}\" in package")]
+    #[diagnostic(code(TURBO_TASK_006))]
     MissingPackageTask {
         #[label]
         span: Option<SourceSpan>,
@@ -72,13 +76,16 @@ pub enum Error {
     #[diagnostic(transparent)]
     Config(#[from] crate::config::Error),
     #[error("invalid turbo json")]
+    #[diagnostic(code(TURBO_TASK_007))]
     Validation {
         #[related]
         errors: Vec<config::Error>,
     },
     #[error(transparent)]
+    #[diagnostic(code(TURBO_TASK_008))]
     Graph(#[from] graph::Error),
     #[error("invalid task name: {reason}")]
+    #[diagnostic(code(TURBO_TASK_009))]
     InvalidTaskName {
         #[label]
         span: Option<SourceSpan>,
@@ -190,15 +197,32 @@ impl<'a> EngineBuilder<'a> {
         if !missing_tasks.is_empty() {
             let mut missing_tasks = missing_tasks
                 .into_iter()
-                .map(|(task_name, span)| (task_name.to_string(), span))
+                .map(|(task_name, span)| (task_name.to_string(), task_name.task(), span))
                 .collect::<Vec<_>>();
             // We sort the tasks mostly to keep it deterministic for our tests
             missing_tasks.sort_by(|a, b| a.0.cmp(&b.0));
+            let known_tasks = self.known_tasks(&mut turbo_jsons);
             let errors = missing_tasks
                 .into_iter()
-                .map(|(name, span)| {
+                .map(|(name, task, span)| {
                     let (span, text) = span.span_and_text("turbo.json");
-                    MissingTaskError { name, span, text }
+                    // A script that isn't in turbo.json is missing too, so it's no
+                    // suggestion for itself
+                    let candidates = known_tasks
+                        .keys()
+                        .map(String::as_str)
+                        .filter(|candidate| *candidate != task);
+                    let help = suggestions::did_you_mean(
+                        suggestions::closest(task, candidates)
+                            .into_iter()
+                            .map(|suggestion| (suggestion, known_tasks[suggestion].clone())),
+                    );
+                    MissingTaskError {
+                        name,
+                        span,
+                        text,
+                        help,
+                    }
                 })
                 .collect();
 
@@ -438,6 +462,29 @@ impl<'a> EngineBuilder<'a> {
         Ok(task_definitions)
     }
 
+    // The tasks in the root turbo.json and the scripts of the packages, along
+    // with the packages that have them
+    fn known_tasks(
+        &self,
+        turbo_jsons: &mut HashMap<PackageName, TurboJson>,
+    ) -> HashMap<String, Vec<&'a str>> {
+        let mut tasks: HashMap<String, Vec<&str>> = HashMap::new();
+        if let Ok(Some(root_turbo_json)) = self.turbo_json(turbo_jsons, &PackageName::Root) {
+            for task_name in root_turbo_json.pipeline.keys() {
+                tasks.entry(task_name.task().to_string()).or_default();
+            }
+        }
+        for (package, info) in self.package_graph.packages() {
+            for script in info.package_json.scripts.keys() {
+                tasks
+                    .entry(script.clone())
+                    .or_default()
+                    .push(package.as_ref());
+            }
+        }
+        tasks
+    }
+
     fn turbo_json<'b>(
         &self,
         turbo_jsons: &'b mut HashMap<PackageName, TurboJson>,
@@ -550,6 +597,10 @@ mod test {
         fn global_change(&self, _other: &dyn Lockfile) -> bool {
             unreachable!()
         }
+
+        fn strip_dev_dependencies(&mut self) -> Result<(), turborepo_lockfiles::Error> {
+            unreachable!()
+        }
     }
 
     struct MockDiscovery;
@@ -1005,6 +1056,56 @@ mod test {
         assert_matches!(engine, Err(Error::MissingRootTaskInTurboJson { .. }));
     }
 
+    #[test]
+    fn test_missing_task_suggestions() {
+        let repo_root_dir = TempDir::new("repo").unwrap();
+        let repo_root = AbsoluteSystemPathBuf::new(repo_root_dir.path().to_str().unwrap()).unwrap();
+        let package_graph = mock_package_graph(
+            &repo_root,
+            package_jsons! {
+                repo_root,
+                "app1" => [],
+                "libA" => []
+            },
+        );
+        let turbo_jsons = vec![(
+            PackageName::Root,
+            turbo_json(json!({
+                "pipeline": {
+                    "build": {},
+                    "libA#lint": {},
+                }
+            })),
+        )]
+        .into_iter()
+        .collect();
+        let engine = EngineBuilder::new(&repo_root, &package_graph, false)
+            .with_turbo_jsons(Some(turbo_jsons))
+            .with_tasks(vec![
+                Spanned::new(TaskName::from("biuld")),
+                Spanned::new(TaskName::from("lnit")),
+                Spanned::new(TaskName::from("deploy")),
+            ])
+            .with_workspaces(vec![PackageName::from("app1")])
+            .build();
+
+        let Err(Error::MissingTasks(errors)) = engine else {
+            panic!("expected missing tasks");
+        };
+        let help = errors
+            .iter()
+            .map(|error| error.help.as_deref())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            help,
+            vec![
+                Some("did you mean `build`?"),
+                None,
+                Some("did you mean `lint`?")
+            ]
+        );
+    }
+
     #[test]
     fn test_depend_on_multiple_package_tasks() {
         let repo_root_dir = TempDir::new("repo").unwrap();
@@ -48,6 +48,9 @@ pub struct TaskHashable<'a> {
     pub(crate) task_dependency_hashes: Vec<String>,
     pub(crate) hash_of_files: &'a str,
     pub(crate) external_deps_hash: Option<String>,
+    // Only set for packages with patched or overridden external dependencies, to
+    // keep the hashes of all other packages stable
+    pub(crate) dependency_patches_hash: Option<&'a str>,
 
     // task
     pub(crate) package_dir: Option<turbopath::RelativeUnixPathBuf>,
@@ -208,6 +211,9 @@ impl From<TaskHashable<'_>> for Builder<HeapAllocator> {
         if let Some(external_deps_hash) = task_hashable.external_deps_hash {
             builder.set_external_deps_hash(&external_deps_hash);
         }
+        if let Some(dependency_patches_hash) = task_hashable.dependency_patches_hash {
+            builder.set_dependency_patches_hash(dependency_patches_hash);
+        }
 
         builder.set_task(task_hashable.task);
         builder.set_env_mode(task_hashable.env_mode.into());
@@ -397,6 +403,7 @@ mod test {
             package_dir: Some(turbopath::RelativeUnixPathBuf::new("package_dir").unwrap()),
             hash_of_files: "hash_of_files",
             external_deps_hash: Some("external_deps_hash".to_string()),
+            dependency_patches_hash: None,
             task: "task",
             outputs: TaskOutputs {
                 inclusions: vec!["inclusions".to_string()],
@@ -413,6 +420,36 @@ mod test {
         assert_eq!(task_hashable.hash(), "ff765ee2f83bc034");
     }
 
+    #[test]
+    fn task_hashable_dependency_patches() {
+        let task_hashable = |dependency_patches_hash| TaskHashable {
+            global_hash: "global_hash",
+            task_dependency_hashes: vec![],
+            package_dir: None,
+            hash_of_files: "hash_of_files",
+            external_deps_hash: Some("external_deps_hash".to_string()),
+            dependency_patches_hash,
+            task: "task",
+            outputs: TaskOutputs {
+                inclusions: vec![],
+                exclusions: vec![],
+            },
+            pass_through_args: &[],
+            env: &[],
+            resolved_env_vars: vec![],
+            pass_through_env: &[],
+            env_mode: ResolvedEnvMode::Loose,
+            dot_env: &[],
+        };
+
+        let unpatched = task_hashable(None).hash();
+        let patched = task_hashable(Some("patch_hash\0foo=1.0.0")).hash();
+        let repatched = task_hashable(Some("other_patch_hash\0foo=1.0.0")).hash();
+        assert_ne!(unpatched, patched);
+        assert_ne!(patched, repatched);
+        assert_eq!(task_hashable(None).hash(), unpatched);
+    }
+
     #[test]
     fn global_hashable() {
         let global_file_hash_map = vec![(
@@ -30,6 +30,7 @@ mod rewrite_json;
 mod run;
 mod shim;
 mod signal;
+mod suggestions;
 mod task_graph;
 mod task_hash;
 mod tracing;
@@ -1,12 +1,21 @@
-use std::backtrace;
+use std::{backtrace, time::Duration};
 
 use thiserror::Error;
 use turbopath::AnchoredSystemPathBuf;
 use turborepo_cache::CacheOpts;
+use turborepo_ci::Vendor;
 
 use crate::{
-    cli::{Command, DryRunMode, EnvMode, LogOrder, LogPrefix, OutputLogsMode, RunArgs},
-    run::task_id::TaskId,
+    cli::{
+        CiVendor, Command, DebugSignal, DryRunMode, EnvMode, LogOrder, LogPrefix,
+        OutputLogsOverrides, RunArgs, UndeclaredOutputsMode,
+    },
+    run::{
+        log_sink::{self, LogSinkConfig},
+        remote_exec::{self, RemoteWorker},
+        task_id::TaskId,
+    },
+    signal::GRACEFUL_SHUTDOWN_BUDGET,
     Args,
 };
 
@@ -28,6 +37,10 @@ pub enum Error {
     ConcurrencyOutOfBounds(#[backtrace] backtrace::Backtrace, String),
     #[error(transparent)]
     Path(#[from] turbopath::PathError),
+    #[error(transparent)]
+    LogSink(#[from] log_sink::Error),
+    #[error(transparent)]
+    RemoteExec(#[from] remote_exec::Error),
 }
 
 #[derive(Debug)]
@@ -104,7 +117,7 @@ impl<'a> TryFrom<&'a Args> for Opts {
 pub struct RunCacheOpts {
     pub(crate) skip_reads: bool,
     pub(crate) skip_writes: bool,
-    pub(crate) task_output_mode_override: Option<OutputLogsMode>,
+    pub(crate) task_output_mode_override: Option<OutputLogsOverrides>,
 }
 
 impl<'a> From<&'a RunArgs> for RunCacheOpts {
@@ -112,7 +125,7 @@ impl<'a> From<&'a RunArgs> for RunCacheOpts {
         RunCacheOpts {
             skip_reads: args.force.flatten().is_some_and(|f| f),
             skip_writes: args.no_cache,
-            task_output_mode_override: args.output_logs,
+            task_output_mode_override: args.output_logs.clone(),
         }
     }
 }
@@ -135,8 +148,25 @@ pub struct RunOpts {
     pub(crate) single_package: bool,
     pub log_prefix: ResolvedLogPrefix,
     pub log_order: ResolvedLogOrder,
+    pub(crate) log_sinks: Vec<LogSinkConfig>,
+    // The task to forward stdin to, empty if none was selected yet
+    pub(crate) attach: Option<String>,
+    pub(crate) undeclared_outputs: Option<UndeclaredOutputsMode>,
     pub summarize: Option<Option<bool>>,
+    pub(crate) report: Option<String>,
+    pub(crate) junit: Option<String>,
+    pub(crate) heartbeat: Option<Duration>,
+    pub(crate) status_bar: bool,
+    pub(crate) ci: Option<CiVendor>,
+    pub(crate) ignore_lockfile: bool,
+    pub(crate) debug_signal: DebugSignal,
+    pub(crate) cache_shutdown_timeout: Duration,
+    pub(crate) resume: bool,
     pub(crate) experimental_space_id: Option<String>,
+    pub(crate) experimental_remote_workers: Vec<RemoteWorker>,
+    // Where to send run metrics to, only configurable through the environment and
+    // the config files
+    pub(crate) statsd_address: Option<String>,
     pub is_github_actions: bool,
 }
 
@@ -191,8 +221,11 @@ impl<'a> TryFrom<&'a RunArgs> for RunOpts {
             f => GraphOpts::File(f.to_string()),
         });
 
+        let vendor = ci_vendor(args.ci);
+        let is_github_actions = vendor.is_some_and(|vendor| vendor.constant == "GITHUB_ACTIONS");
+        let folds_logs = folds_logs(args.ci, vendor);
         let (is_github_actions, log_order, log_prefix) = match args.log_order {
-            LogOrder::Auto if turborepo_ci::Vendor::get_constant() == Some("GITHUB_ACTIONS") => (
+            LogOrder::Auto if is_github_actions => (
                 true,
                 ResolvedLogOrder::Grouped,
                 match args.log_prefix {
@@ -201,19 +234,52 @@ impl<'a> TryFrom<&'a RunArgs> for RunOpts {
                 },
             ),
 
-            // Streaming is the default behavior except when running on GitHub Actions
+            // CI systems that fold the logs of each task need them grouped
+            LogOrder::Auto if folds_logs => {
+                (false, ResolvedLogOrder::Grouped, args.log_prefix.into())
+            }
+
+            // Streaming is the default behavior except when running on CI systems that
+            // fold logs
             LogOrder::Auto | LogOrder::Stream => {
                 (false, ResolvedLogOrder::Stream, args.log_prefix.into())
             }
             LogOrder::Grouped => (false, ResolvedLogOrder::Grouped, args.log_prefix.into()),
         };
 
+        let log_sinks = args
+            .log_sink
+            .iter()
+            .map(|sink| sink.parse())
+            .collect::<Result<_, _>>()?;
+        let experimental_remote_workers = args
+            .experimental_remote_worker
+            .iter()
+            .map(|worker| worker.parse())
+            .collect::<Result<_, _>>()?;
+
         Ok(Self {
             tasks: args.tasks.clone(),
             log_prefix,
             log_order,
+            log_sinks,
+            attach: args.attach.clone(),
+            undeclared_outputs: args.undeclared_outputs,
             summarize: args.summarize,
+            report: args.report.clone(),
+            junit: args.junit.clone(),
+            heartbeat: args.heartbeat.map(Duration::from_secs),
+            status_bar: args.status_bar,
+            ci: args.ci,
+            ignore_lockfile: args.ignore_lockfile,
+            debug_signal: args.debug_signal,
+            cache_shutdown_timeout: args
+                .cache_shutdown_timeout
+                .map_or(GRACEFUL_SHUTDOWN_BUDGET, Duration::from_secs),
+            resume: args.resume,
             experimental_space_id: args.experimental_space_id.clone(),
+            experimental_remote_workers,
+            statsd_address: None,
             framework_inference: args.framework_inference,
             env_mode: args.env_mode,
             concurrency,
@@ -353,7 +419,26 @@ impl<'a> From<&'a RunArgs> for CacheOpts {
     }
 }
 
+// The CI system that `--ci` selects, or the detected one
+fn ci_vendor(ci: Option<CiVendor>) -> Option<&'static Vendor> {
+    match ci {
+        Some(ci) => Vendor::from_constant(ci.constant()),
+        None => Vendor::infer(),
+    }
+}
+
+// Whether the logs of each task are grouped so the CI system can fold them.
+// Detected vendors other than TeamCity and Buildkite keep streaming, as they
+// did before those sections were added, unless `--ci` asks for it explicitly
+fn folds_logs(ci: Option<CiVendor>, vendor: Option<&Vendor>) -> bool {
+    ci.is_some() || vendor.is_some_and(|vendor| matches!(vendor.constant, "TEAMCITY" | "BUILDKITE"))
+}
+
 impl RunOpts {
+    pub fn ci_vendor(&self) -> Option<&'static Vendor> {
+        ci_vendor(self.ci)
+    }
+
     pub fn should_redirect_stderr_to_stdout(&self) -> bool {
         // If we're running on Github Actions, force everything to stdout
         // so as not to have out-of-order log lines
@@ -375,10 +460,11 @@ impl ScopeOpts {
 mod test {
     use test_case::test_case;
     use turborepo_cache::CacheOpts;
+    use turborepo_ci::Vendor;
 
-    use super::{LegacyFilter, RunOpts};
+    use super::{folds_logs, LegacyFilter, RunOpts};
     use crate::{
-        cli::DryRunMode,
+        cli::{CiVendor, DryRunMode},
         opts::{Opts, RunCacheOpts, ScopeOpts},
     };
 
@@ -406,6 +492,17 @@ mod test {
             expected.iter().map(|s| s.to_string()).collect::<Vec<_>>()
         )
     }
+
+    #[test_case(None, "TEAMCITY", true ; "teamcity")]
+    #[test_case(None, "BUILDKITE", true ; "buildkite")]
+    #[test_case(None, "AZURE_PIPELINES", false ; "azure")]
+    #[test_case(None, "TRAVIS", false ; "travis")]
+    #[test_case(Some(CiVendor::Teamcity), "TEAMCITY", true ; "explicit teamcity")]
+    fn test_folds_logs(ci: Option<CiVendor>, constant: &str, expected: bool) {
+        let vendor = Vendor::from_constant(constant);
+        assert!(vendor.is_some());
+        assert_eq!(folds_logs(ci, vendor), expected);
+    }
     #[derive(Default)]
     struct TestCaseOpts {
         filter_patterns: Vec<String>,
@@ -529,8 +626,22 @@ mod test {
             single_package: false,
             log_prefix: crate::opts::ResolvedLogPrefix::Task,
             log_order: crate::opts::ResolvedLogOrder::Stream,
+            log_sinks: Vec::new(),
+            attach: None,
+            undeclared_outputs: None,
             summarize: None,
+            report: None,
+            junit: None,
+            heartbeat: None,
+            status_bar: false,
+            ci: None,
+            ignore_lockfile: false,
+            debug_signal: DebugSignal::Quit,
+            cache_shutdown_timeout: GRACEFUL_SHUTDOWN_BUDGET,
+            resume: false,
             experimental_space_id: None,
+            experimental_remote_workers: Vec::new(),
+            statsd_address: None,
             is_github_actions: false,
         };
         let cache_opts = CacheOpts::default();
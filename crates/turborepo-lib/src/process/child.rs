@@ -19,21 +19,26 @@ const CHILD_POLL_INTERVAL: Duration = Duration::from_micros(50);
 
 use std::{
     fmt,
+    future::Future,
     io::{self, BufRead, Read, Write},
     sync::{Arc, Mutex},
     time::Duration,
 };
 
-use portable_pty::{native_pty_system, Child as PtyChild, MasterPty as PtyController};
+use portable_pty::{native_pty_system, Child as PtyChild, MasterPty as PtyController, PtySize};
 use tokio::{
-    io::{AsyncBufRead, AsyncBufReadExt, BufReader},
+    io::{AsyncBufRead, AsyncBufReadExt, AsyncWriteExt as _, BufReader},
     join,
     process::Command as TokioCommand,
     sync::{mpsc, watch, RwLock},
 };
 use tracing::debug;
 
-use super::Command;
+#[cfg(windows)]
+use super::job_object::JobObject;
+#[cfg(unix)]
+use super::limits::set_rlimit;
+use super::{limits::ResourceLimiter, Command, ResourceLimits};
 
 #[derive(Debug)]
 pub enum ChildState {
@@ -54,13 +59,47 @@ impl ChildState {
 pub enum ChildExit {
     Finished(Option<i32>),
     Killed,
-    /// The child process was killed by someone else. Note that on
-    /// windows, it is not possible to distinguish between whether
-    /// the process exited normally or was killed
-    KilledExternal,
+    /// The child process was killed by someone else, with the signal that
+    /// killed it if it's known. Note that on windows, it is not possible to
+    /// distinguish between whether the process exited normally or was killed
+    KilledExternal(Option<i32>),
     Failed,
 }
 
+/// The name of a signal, e.g. `SIGKILL`
+pub fn signal_name(signal: i32) -> Option<&'static str> {
+    #[cfg(unix)]
+    {
+        nix::sys::signal::Signal::try_from(signal)
+            .ok()
+            .map(|signal| signal.as_str())
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = signal;
+        None
+    }
+}
+
+// portable_pty only keeps the description of the signal that killed the child,
+// e.g. "Killed", so we look for the signal with the same description
+#[cfg(unix)]
+fn pty_signal(status: &portable_pty::ExitStatus) -> Option<i32> {
+    let status = status.to_string();
+    let description = status.strip_prefix("Terminated by ")?;
+    if let Some(signal) = description.strip_prefix("Signal ") {
+        return signal.parse().ok();
+    }
+    nix::sys::signal::Signal::iterator()
+        .map(|signal| signal as i32)
+        .find(|signal| {
+            // SAFETY: strsignal returns a pointer to a nul terminated string or null
+            let name = unsafe { libc::strsignal(*signal) };
+            !name.is_null()
+                && unsafe { std::ffi::CStr::from_ptr(name) }.to_string_lossy() == description
+        })
+}
+
 #[derive(Debug, Clone)]
 pub enum ShutdownStyle {
     /// On windows this will immediately kill, and on posix systems it
@@ -84,6 +123,11 @@ impl From<std::io::Error> for ShutdownFailed {
 struct ChildHandle {
     pid: Option<u32>,
     imp: ChildHandleImpl,
+    // Holds the process tree of the child so it can be killed as a whole
+    #[cfg(windows)]
+    job: Option<JobObject>,
+    // Keeps the resource limits of the child in place until it exited
+    _limiter: Option<ResourceLimiter>,
 }
 
 enum ChildHandleImpl {
@@ -94,15 +138,21 @@ enum ChildHandleImpl {
 impl ChildHandle {
     #[tracing::instrument(skip(command))]
     pub fn spawn_normal(command: Command) -> io::Result<SpawnResult> {
+        let limits = command.resource_limits();
+        let priority = command.get_priority();
         let mut command = TokioCommand::from(command);
 
         // Create a process group for the child on unix like systems
         #[cfg(unix)]
         {
             use nix::unistd::setsid;
+            let rlimit = limits.and_then(ResourceLimiter::rlimit);
             unsafe {
-                command.pre_exec(|| {
+                command.pre_exec(move || {
                     setsid()?;
+                    if let Some(memory) = rlimit {
+                        set_rlimit(memory)?;
+                    }
                     Ok(())
                 });
             }
@@ -110,6 +160,9 @@ impl ChildHandle {
 
         let mut child = command.spawn()?;
         let pid = child.id();
+        if let Some(pid) = pid {
+            priority.apply(pid);
+        }
 
         let stdin = child.stdin.take().map(ChildInput::Std);
         let stdout = child
@@ -125,6 +178,9 @@ impl ChildHandle {
             handle: Self {
                 pid,
                 imp: ChildHandleImpl::Tokio(child),
+                #[cfg(windows)]
+                job: Self::job_object(pid),
+                _limiter: Self::limiter(limits, pid),
             },
             io: ChildIO {
                 stdin,
@@ -135,10 +191,15 @@ impl ChildHandle {
     }
 
     #[tracing::instrument(skip(command))]
-    pub fn spawn_pty(command: Command) -> io::Result<SpawnResult> {
-        use portable_pty::PtySize;
-
+    pub fn spawn_pty(mut command: Command) -> io::Result<SpawnResult> {
         let keep_stdin_open = command.will_open_stdin();
+        let limits = command.resource_limits();
+        let priority = command.get_priority();
+        // portable_pty can't run code in the child before it executes its command
+        #[cfg(unix)]
+        if let Some(memory) = limits.and_then(ResourceLimiter::rlimit) {
+            command = command.with_data_rlimit(memory);
+        }
 
         let command = portable_pty::CommandBuilder::from(command);
         let pty_system = native_pty_system();
@@ -189,6 +250,9 @@ impl ChildHandle {
             })?;
 
         let pid = child.process_id();
+        if let Some(pid) = pid {
+            priority.apply(pid);
+        }
 
         let mut stdin = controller.take_writer().ok();
         let output = controller.try_clone_reader().ok().map(ChildOutput::Pty);
@@ -203,6 +267,9 @@ impl ChildHandle {
             handle: Self {
                 pid,
                 imp: ChildHandleImpl::Pty(child),
+                #[cfg(windows)]
+                job: Self::job_object(pid),
+                _limiter: Self::limiter(limits, pid),
             },
             io: ChildIO {
                 stdin: stdin.map(ChildInput::Pty),
@@ -212,13 +279,38 @@ impl ChildHandle {
         })
     }
 
+    #[cfg(windows)]
+    fn job_object(pid: Option<u32>) -> Option<JobObject> {
+        let pid = pid?;
+        JobObject::for_process(pid)
+            .map_err(|e| debug!("unable to assign child {pid} to a job object: {e}"))
+            .ok()
+    }
+
+    fn limiter(limits: Option<ResourceLimits>, pid: Option<u32>) -> Option<ResourceLimiter> {
+        let (limits, pid) = limits.zip(pid)?;
+        ResourceLimiter::apply(limits, pid)
+    }
+
     pub fn pid(&self) -> Option<u32> {
         self.pid
     }
 
-    pub async fn wait(&mut self) -> io::Result<Option<i32>> {
+    /// Waits for the child to exit, which is either `ChildExit::Finished` or
+    /// `ChildExit::KilledExternal` if a signal killed it.
+    pub async fn wait(&mut self) -> io::Result<ChildExit> {
         match &mut self.imp {
-            ChildHandleImpl::Tokio(child) => child.wait().await.map(|status| status.code()),
+            ChildHandleImpl::Tokio(child) => {
+                let status = child.wait().await?;
+                #[cfg(unix)]
+                if let Some(signal) = std::os::unix::process::ExitStatusExt::signal(&status) {
+                    return Ok(ChildExit::KilledExternal(Some(signal)));
+                }
+                Ok(match status.code() {
+                    Some(code) => ChildExit::Finished(Some(code)),
+                    None => ChildExit::KilledExternal(None),
+                })
+            }
             ChildHandleImpl::Pty(child) => {
                 // TODO: we currently poll the child to see if it has finished yet which is less
                 // than ideal
@@ -230,16 +322,20 @@ impl ChildHandle {
                             // exited normally with exit code 1 or got killed by a signal is to
                             // display it as the signal will be included
                             // in the message.
-                            let exit_code = if status.exit_code() == 1
+                            let exit = if status.exit_code() == 1
                                 && status.to_string().contains("Terminated by")
                             {
-                                None
+                                #[cfg(unix)]
+                                let signal = pty_signal(&status);
+                                #[cfg(not(unix))]
+                                let signal = None;
+                                ChildExit::KilledExternal(signal)
                             } else {
                                 // This is safe as the portable_pty::ExitStatus's exit code is just
                                 // converted from a i32 to an u32 before we get it
-                                Some(status.exit_code() as i32)
+                                ChildExit::Finished(Some(status.exit_code() as i32))
                             };
-                            return Ok(exit_code);
+                            return Ok(exit);
                         }
                         Ok(None) => {
                             // child hasn't finished, we sleep for a short time
@@ -253,6 +349,13 @@ impl ChildHandle {
     }
 
     pub async fn kill(&mut self) -> io::Result<()> {
+        // Killing the child alone would leave its descendants running
+        #[cfg(windows)]
+        if let Some(job) = &self.job {
+            job.terminate()?;
+            return self.wait().await.map(|_| ());
+        }
+
         match &mut self.imp {
             ChildHandleImpl::Tokio(child) => child.kill().await,
             ChildHandleImpl::Pty(child) => {
@@ -322,9 +425,13 @@ impl fmt::Debug for ChildOutput {
 impl ShutdownStyle {
     /// Process the shutdown style for the given child process.
     ///
-    /// If an exit channel is provided, the exit code will be sent to the
-    /// channel when the child process exits.
-    async fn process(&self, child: &mut ChildHandle) -> ChildState {
+    /// If `escalate` completes while waiting on a graceful shutdown, the child
+    /// process is killed right away.
+    async fn process(
+        &self,
+        child: &mut ChildHandle,
+        escalate: impl Future<Output = ()>,
+    ) -> ChildState {
         match self {
             // Windows doesn't give the ability to send a signal to a process so we
             // can't make use of the graceful shutdown timeout.
@@ -345,21 +452,24 @@ impl ShutdownStyle {
                             child.wait().await
                         } else {
                             // if there is no pid, then just report successful with no exit code
-                            Ok(None)
+                            Ok(ChildExit::Finished(None))
                         }
                     };
 
                     debug!("starting shutdown");
 
-                    let result = tokio::time::timeout(*timeout, fut).await;
+                    let result = tokio::select! {
+                        result = tokio::time::timeout(*timeout, fut) => result.ok(),
+                        _ = escalate => None,
+                    };
                     match result {
                         // We ignore the exit code and mark it as killed since we sent a SIGINT
                         // This avoids reliance on an underlying process exiting with
                         // no exit code or a non-zero in order for turbo to operate correctly.
-                        Ok(Ok(_exit_code)) => ChildState::Exited(ChildExit::Killed),
-                        Ok(Err(_)) => ChildState::Exited(ChildExit::Failed),
-                        Err(_) => {
-                            debug!("graceful shutdown timed out, killing child");
+                        Some(Ok(_exit_code)) => ChildState::Exited(ChildExit::Killed),
+                        Some(Err(_)) => ChildState::Exited(ChildExit::Failed),
+                        None => {
+                            debug!("graceful shutdown timed out or was escalated, killing child");
                             match child.kill().await {
                                 Ok(_) => ChildState::Exited(ChildExit::Killed),
                                 Err(_) => ChildState::Exited(ChildExit::Failed),
@@ -370,6 +480,7 @@ impl ShutdownStyle {
 
                 #[cfg(windows)]
                 {
+                    let _ = escalate;
                     debug!("timeout not supported on windows, killing");
                     match child.kill().await {
                         Ok(_) => ChildState::Exited(ChildExit::Killed),
@@ -405,6 +516,8 @@ pub struct Child {
     exit_channel: watch::Receiver<Option<ChildExit>>,
     stdin: Arc<Mutex<Option<ChildInput>>>,
     output: Arc<Mutex<Option<ChildOutput>>>,
+    pty_size: Arc<watch::Sender<Option<PtySize>>>,
+    sanitize_output: bool,
     label: String,
 }
 
@@ -441,6 +554,7 @@ impl Child {
         use_pty: bool,
     ) -> io::Result<Self> {
         let label = command.label();
+        let sanitize_output = command.will_sanitize_output();
         let SpawnResult {
             handle: mut child,
             io: ChildIO { stdin, output },
@@ -465,6 +579,9 @@ impl Child {
         let state = Arc::new(RwLock::new(ChildState::Running(command_tx)));
         let task_state = state.clone();
 
+        // only the latest size matters, so resizes are sent over a watch channel
+        let (pty_size_tx, mut pty_size_rx) = watch::channel(None);
+
         let _task = tokio::spawn(async move {
             // On Windows it is important that this gets dropped once the child process
             // exits
@@ -475,13 +592,27 @@ impl Child {
                 task_state,
                 exit_tx,
             };
-            tokio::select! {
-                command = command_rx.recv() => {
-                    manager.handle_child_command(command, &mut child, controller).await;
-                }
-                status = child.wait() => {
-                    drop(controller);
-                    manager.handle_child_exit(status).await;
+            loop {
+                tokio::select! {
+                    command = command_rx.recv() => {
+                        manager
+                            .handle_child_command(command, &mut command_rx, &mut child, controller)
+                            .await;
+                        break;
+                    }
+                    status = child.wait() => {
+                        drop(controller);
+                        manager.handle_child_exit(status).await;
+                        break;
+                    }
+                    Ok(()) = pty_size_rx.changed(), if controller.is_some() => {
+                        let size = *pty_size_rx.borrow_and_update();
+                        if let (Some(controller), Some(size)) = (&controller, size) {
+                            if let Err(e) = controller.resize(size) {
+                                debug!("unable to resize pty: {e}");
+                            }
+                        }
+                    }
                 }
             }
 
@@ -494,10 +625,23 @@ impl Child {
             exit_channel: exit_rx,
             stdin: Arc::new(Mutex::new(stdin)),
             output: Arc::new(Mutex::new(output)),
+            pty_size: Arc::new(pty_size_tx),
+            sanitize_output,
             label,
         })
     }
 
+    /// Resize the PTY the child is hooked up to. Does nothing for children
+    /// that aren't hooked up to a PTY.
+    pub fn resize(&self, rows: u16, cols: u16) {
+        self.pty_size.send_replace(Some(PtySize {
+            rows,
+            cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        }));
+    }
+
     /// Wait for the `Child` to exit, returning the exit code.
     pub async fn wait(&mut self) -> Option<ChildExit> {
         // If sending end of exit channel closed, then return last value in the channel
@@ -563,7 +707,7 @@ impl Child {
         code
     }
 
-    fn pid(&self) -> Option<u32> {
+    pub fn pid(&self) -> Option<u32> {
         self.pid
     }
 
@@ -571,6 +715,29 @@ impl Child {
         self.stdin.lock().unwrap().take()
     }
 
+    /// Write `bytes` to the stdin of the child. Fails if the child was spawned
+    /// without an open stdin.
+    pub async fn write_stdin(&self, bytes: &[u8]) -> io::Result<()> {
+        // The input is taken out while writing to not hold the lock across an
+        // await point
+        let input = self.stdin.lock().unwrap().take();
+        let Some(mut input) = input else {
+            return Err(io::Error::new(
+                io::ErrorKind::NotConnected,
+                "child has no open stdin",
+            ));
+        };
+        let result = match &mut input {
+            ChildInput::Std(stdin) => match stdin.write_all(bytes).await {
+                Ok(()) => stdin.flush().await,
+                Err(e) => Err(e),
+            },
+            ChildInput::Pty(stdin) => stdin.write_all(bytes).and_then(|()| stdin.flush()),
+        };
+        *self.stdin.lock().unwrap() = Some(input);
+        result
+    }
+
     fn outputs(&mut self) -> Option<ChildOutput> {
         self.output.lock().unwrap().take()
     }
@@ -630,9 +797,15 @@ impl Child {
             Ok(())
         });
 
+        let sanitize_output = self.sanitize_output;
         let writer_fut = async {
             let mut result = Ok(());
             while let Some(mut bytes) = byte_rx.recv().await {
+                // Tools hooked up to a PTY assume they own the terminal, so we remove
+                // anything that would break once the line is interleaved with others
+                if sanitize_output {
+                    bytes = sanitize_control_sequences(&bytes);
+                }
                 add_trailing_newline(&mut bytes);
                 if let Err(err) = stdout_pipe.write_all(&bytes) {
                     result = Err(err);
@@ -735,13 +908,97 @@ fn add_trailing_newline(buffer: &mut Vec<u8>) {
     }
 }
 
+/// Removes the control sequences of a line of output that don't survive being
+/// prefixed and interleaved with the output of other tasks. Colors and
+/// hyperlinks are kept, while e.g. cursor movements, erasing and window titles
+/// are removed. A carriage return that isn't part of a line break rewrites the
+/// line, like progress bars do, so only the output after it is kept.
+fn sanitize_control_sequences(line: &[u8]) -> Vec<u8> {
+    const ESC: u8 = 0x1b;
+    const BEL: u8 = 0x07;
+
+    let mut output = Vec::with_capacity(line.len());
+    let mut i = 0;
+    while i < line.len() {
+        match line[i] {
+            b'\r' if line.get(i + 1).is_some_and(|&next| next != b'\n') => {
+                output.clear();
+                i += 1;
+            }
+            ESC => match line.get(i + 1) {
+                // Control Sequence Introducer, ends with a byte in 0x40..=0x7e
+                Some(b'[') => {
+                    let end = line[i + 2..]
+                        .iter()
+                        .position(|b| (0x40..=0x7e).contains(b))
+                        .map(|offset| i + 2 + offset);
+                    match end {
+                        // Select Graphic Rendition sets colors and styles
+                        Some(end) if line[end] == b'm' => output.extend_from_slice(&line[i..=end]),
+                        _ => (),
+                    }
+                    i = end.map_or(line.len(), |end| end + 1);
+                }
+                // Operating System Command, ends with BEL or ESC \
+                Some(b']') => {
+                    let mut end = None;
+                    let mut j = i + 2;
+                    while j < line.len() {
+                        if line[j] == BEL {
+                            end = Some(j + 1);
+                            break;
+                        }
+                        if line[j] == ESC && line.get(j + 1) == Some(&b'\\') {
+                            end = Some(j + 2);
+                            break;
+                        }
+                        j += 1;
+                    }
+                    let end = end.unwrap_or(line.len());
+                    // OSC 8 is a hyperlink
+                    if line[i + 2..end].starts_with(b"8;") {
+                        output.extend_from_slice(&line[i..end]);
+                    }
+                    i = end;
+                }
+                // Other escape sequences, e.g. saving the cursor or selecting a
+                // character set, are an escape, optional intermediate bytes and a
+                // final byte
+                Some(_) => {
+                    let end = line[i + 1..]
+                        .iter()
+                        .position(|b| !(0x20..=0x2f).contains(b))
+                        .map(|offset| i + 1 + offset);
+                    i = end.map_or(line.len(), |end| end + 1);
+                }
+                None => i += 1,
+            },
+            byte => {
+                output.push(byte);
+                i += 1;
+            }
+        }
+    }
+    output
+}
+
 impl ChildStateManager {
     async fn handle_child_command(
         &self,
         command: Option<ChildCommand>,
+        command_rx: &mut mpsc::Receiver<ChildCommand>,
         child: &mut ChildHandle,
         controller: Option<Box<dyn PtyController + Send>>,
     ) {
+        // A kill command while we wait on a graceful shutdown cuts it short
+        let escalate = async {
+            while let Some(command) = command_rx.recv().await {
+                if matches!(command, ChildCommand::Kill) {
+                    return;
+                }
+            }
+            std::future::pending().await
+        };
         let state = match command {
             // we received a command to stop the child process, or the channel was closed.
             // in theory this happens when the last child is dropped, however in practice
@@ -749,12 +1006,12 @@ impl ChildStateManager {
             // dropped, and the channel is not closed while there are still permits
             Some(ChildCommand::Stop) | None => {
                 debug!("stopping child process");
-                self.shutdown_style.process(child).await
+                self.shutdown_style.process(child, escalate).await
             }
             // we received a command to kill the child process
             Some(ChildCommand::Kill) => {
                 debug!("killing child process");
-                ShutdownStyle::Kill.process(child).await
+                ShutdownStyle::Kill.process(child, escalate).await
             }
         };
         match state {
@@ -774,16 +1031,11 @@ impl ChildStateManager {
         }
     }
 
-    async fn handle_child_exit(&self, status: io::Result<Option<i32>>) {
+    async fn handle_child_exit(&self, status: io::Result<ChildExit>) {
         debug!("child process exited normally");
-        // the child process exited
-        let child_exit = match status {
-            Ok(Some(c)) => ChildExit::Finished(Some(c)),
-            // if we hit this case, it means that the child process was killed
-            // by someone else, and we should report that it was killed
-            Ok(None) => ChildExit::KilledExternal,
-            Err(_e) => ChildExit::Failed,
-        };
+        // the child process exited, if it was killed by a signal it was killed by
+        // someone else, and we report that it was killed
+        let child_exit = status.unwrap_or(ChildExit::Failed);
         {
             let mut task_state = self.task_state.write().await;
             *task_state = ChildState::Exited(child_exit);
@@ -804,7 +1056,7 @@ mod test {
     use tracing_test::traced_test;
     use turbopath::AbsoluteSystemPathBuf;
 
-    use super::{Child, ChildInput, ChildOutput, ChildState, Command};
+    use super::{sanitize_control_sequences, Child, ChildInput, ChildOutput, ChildState, Command};
     use crate::process::child::{ChildExit, ShutdownStyle};
 
     const STARTUP_DELAY: Duration = Duration::from_millis(500);
@@ -962,6 +1214,36 @@ mod test {
         assert_matches!(&*state, ChildState::Exited(ChildExit::Finished(Some(0))));
     }
 
+    #[test_case(false)]
+    #[test_case(TEST_PTY)]
+    #[tokio::test]
+    async fn test_write_stdin(use_pty: bool) {
+        let script = find_script_dir().join_component("stdin_stdout.js");
+        let mut cmd = Command::new("node");
+        cmd.args([script.as_std_path()]);
+        cmd.open_stdin();
+        let mut child = Child::spawn(cmd, ShutdownStyle::Kill, use_pty).unwrap();
+
+        tokio::time::sleep(STARTUP_DELAY).await;
+
+        child.write_stdin(b"hello ").await.unwrap();
+        child.write_stdin(b"world").await.unwrap();
+        // drop stdin to close the pipe
+        child.stdin();
+        assert!(child.write_stdin(b"!").await.is_err());
+
+        let mut output = Vec::new();
+        match child.outputs().unwrap() {
+            ChildOutput::Std { mut stdout, .. } => stdout.read_to_end(&mut output).await.unwrap(),
+            ChildOutput::Pty(mut stdout) => stdout.read_to_end(&mut output).unwrap(),
+        };
+
+        let output_str = String::from_utf8(output).expect("Failed to parse stdout");
+        assert!(output_str.contains("hello world"), "got: {}", output_str);
+
+        child.wait().await;
+    }
+
     #[test_case(false)]
     #[test_case(TEST_PTY)]
     #[tokio::test]
@@ -1080,7 +1362,7 @@ mod test {
         };
 
         #[cfg(unix)]
-        assert_matches!(state, ChildExit::KilledExternal);
+        assert_matches!(state, ChildExit::KilledExternal(Some(libc::SIGINT)));
         #[cfg(not(unix))]
         assert_matches!(state, ChildExit::Finished(Some(3)));
     }
@@ -1211,7 +1493,7 @@ mod test {
         }
 
         let exit = child.wait().await;
-        assert_matches!(exit, Some(ChildExit::KilledExternal));
+        assert_matches!(exit, Some(ChildExit::KilledExternal(Some(libc::SIGKILL))));
 
         let mut output = Vec::new();
         match tokio::time::timeout(
@@ -1221,7 +1503,10 @@ mod test {
         .await
         {
             Ok(exit_status) => {
-                assert_matches!(exit_status, Ok(Some(ChildExit::KilledExternal)));
+                assert_matches!(
+                    exit_status,
+                    Ok(Some(ChildExit::KilledExternal(Some(libc::SIGKILL))))
+                );
             }
             Err(_) => panic!("expected wait_with_piped_outputs to exit after it was killed"),
         }
@@ -1250,4 +1535,90 @@ mod test {
             .is_some()
         {}
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_resize_pty() {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "sleep 0.5; stty size"]);
+        let mut child = Child::spawn(cmd, ShutdownStyle::Kill, true).unwrap();
+        child.resize(20, 40);
+
+        let mut out = Vec::new();
+        let exit = child.wait_with_piped_outputs(&mut out).await.unwrap();
+
+        let output = String::from_utf8(out).unwrap();
+        assert!(output.contains("20 40"), "got: {}", output);
+        assert_matches!(exit, Some(ChildExit::Finished(Some(0))));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_pty_data_rlimit() {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "ulimit -d"]);
+        let cmd = cmd.with_data_rlimit(64 * 1024 * 1024);
+        let mut child = Child::spawn(cmd, ShutdownStyle::Kill, true).unwrap();
+
+        let mut out = Vec::new();
+        let exit = child.wait_with_piped_outputs(&mut out).await.unwrap();
+
+        assert_eq!(String::from_utf8(out).unwrap().trim(), "65536");
+        assert_matches!(exit, Some(ChildExit::Finished(Some(0))));
+    }
+
+    #[cfg(unix)]
+    #[test_case(false)]
+    #[test_case(true)]
+    #[tokio::test]
+    async fn test_killed_by_signal(use_pty: bool) {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "kill -TERM $$"]);
+        let mut child = Child::spawn(cmd, ShutdownStyle::Kill, use_pty).unwrap();
+
+        let exit = child
+            .wait_with_piped_outputs(&mut std::io::sink())
+            .await
+            .unwrap();
+
+        assert_matches!(exit, Some(ChildExit::KilledExternal(Some(libc::SIGTERM))));
+        assert_eq!(super::signal_name(libc::SIGTERM), Some("SIGTERM"));
+    }
+
+    #[cfg(unix)]
+    #[test_case(false ; "kept")]
+    #[test_case(true ; "sanitized")]
+    #[tokio::test]
+    async fn test_sanitize_pty_output(sanitize: bool) {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "printf '\\033[2Kdone\\n'"]);
+        if sanitize {
+            cmd.sanitize_output();
+        }
+        let mut child = Child::spawn(cmd, ShutdownStyle::Kill, true).unwrap();
+
+        let mut out = Vec::new();
+        let exit = child.wait_with_piped_outputs(&mut out).await.unwrap();
+
+        let output = String::from_utf8(out).unwrap();
+        assert!(output.contains("done"), "got: {}", output);
+        assert_eq!(output.contains("\x1b[2K"), !sanitize, "got: {:?}", output);
+        assert_matches!(exit, Some(ChildExit::Finished(Some(0))));
+    }
+
+    #[test_case(b"hello\r\n", b"hello\r\n" ; "line break")]
+    #[test_case(b"\x1b[31mred\x1b[0m\n", b"\x1b[31mred\x1b[0m\n" ; "colors")]
+    #[test_case(b"\x1b[2K\x1b[1Gdone\n", b"done\n" ; "erase and move cursor")]
+    #[test_case(b"10%\r50%\r100%\n", b"100%\n" ; "progress")]
+    #[test_case(b"\x1b]0;title\x07text\n", b"text\n" ; "window title")]
+    #[test_case(
+        b"\x1b]8;;https://turbo.build\x1b\\link\x1b]8;;\x1b\\\n",
+        b"\x1b]8;;https://turbo.build\x1b\\link\x1b]8;;\x1b\\\n" ;
+        "hyperlink"
+    )]
+    #[test_case(b"\x1b7saved\x1b8\n", b"saved\n" ; "save cursor")]
+    #[test_case(b"\x1b[", b"" ; "incomplete")]
+    fn test_sanitize_control_sequences(input: &[u8], expected: &[u8]) {
+        assert_eq!(sanitize_control_sequences(input), expected);
+    }
 }
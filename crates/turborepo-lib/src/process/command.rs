@@ -7,15 +7,25 @@ use std::{
 use itertools::Itertools;
 use turbopath::AbsoluteSystemPathBuf;
 
+use super::{Priority, ResourceLimits};
+
 /// A command builder that can be used to build both regular
 /// child processes and ones spawned hooked up to a PTY
 pub struct Command {
     program: OsString,
     args: Vec<OsString>,
+    // The args are added to the command line as they are, instead of being
+    // quoted. Only supported on Windows, for programs like cmd.exe that don't
+    // parse their command line like the C runtime does.
+    verbatim_args: bool,
     cwd: Option<AbsoluteSystemPathBuf>,
     env: BTreeMap<OsString, OsString>,
     open_stdin: bool,
     env_clear: bool,
+    pty: Option<bool>,
+    sanitize_output: bool,
+    resource_limits: Option<ResourceLimits>,
+    priority: Priority,
 }
 
 impl Command {
@@ -24,10 +34,15 @@ impl Command {
         Self {
             program,
             args: Vec::new(),
+            verbatim_args: false,
             cwd: None,
             env: BTreeMap::new(),
             open_stdin: false,
             env_clear: false,
+            pty: None,
+            sanitize_output: false,
+            resource_limits: None,
+            priority: Priority::Normal,
         }
     }
 
@@ -43,6 +58,20 @@ impl Command {
         self
     }
 
+    /// Like `args`, but the arguments are added to the command line of the
+    /// process as they are, without any quoting. The PTY doesn't support this,
+    /// so the process is never hooked up to one.
+    #[cfg(windows)]
+    pub fn raw_args<I, S>(&mut self, args: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        self.args(args);
+        self.verbatim_args = true;
+        self
+    }
+
     pub fn current_dir(&mut self, dir: AbsoluteSystemPathBuf) -> &mut Self {
         self.cwd = Some(dir);
         self
@@ -100,6 +129,97 @@ impl Command {
     pub fn will_open_stdin(&self) -> bool {
         self.open_stdin
     }
+
+    /// Overrides whether the child process is spawned hooked up to a PTY.
+    /// By default the process manager decides.
+    pub fn pty(&mut self, use_pty: bool) -> &mut Self {
+        self.pty = Some(use_pty);
+        self
+    }
+
+    /// If the child process should be spawned hooked up to a PTY, if this was
+    /// configured for the command
+    pub fn will_use_pty(&self) -> Option<bool> {
+        match self.verbatim_args {
+            true => Some(false),
+            false => self.pty,
+        }
+    }
+
+    /// Removes control sequences, like cursor movements, from the output of a
+    /// process hooked up to a PTY, as they garble the output once it is
+    /// interleaved with the output of other processes. Colors and hyperlinks
+    /// are kept.
+    pub fn sanitize_output(&mut self) -> &mut Self {
+        self.sanitize_output = true;
+        self
+    }
+
+    /// If control sequences are removed from the output of the child process
+    pub fn will_sanitize_output(&self) -> bool {
+        self.sanitize_output
+    }
+
+    /// Limit the resources that the process and its descendants may use
+    pub fn limit_resources(&mut self, limits: ResourceLimits) -> &mut Self {
+        self.resource_limits = Some(limits);
+        self
+    }
+
+    pub fn resource_limits(&self) -> Option<ResourceLimits> {
+        self.resource_limits
+    }
+
+    /// Set the scheduling priority of the process and its descendants
+    pub fn priority(&mut self, priority: Priority) -> &mut Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn get_priority(&self) -> Priority {
+        self.priority
+    }
+
+    pub fn get_program(&self) -> &OsStr {
+        &self.program
+    }
+
+    pub fn get_args(&self) -> &[OsString] {
+        &self.args
+    }
+
+    pub fn get_current_dir(&self) -> Option<&AbsoluteSystemPathBuf> {
+        self.cwd.as_ref()
+    }
+
+    /// The environment variables that are set for the child process, in
+    /// addition to the inherited ones unless the environment was cleared
+    pub fn get_envs(&self) -> impl Iterator<Item = (&OsStr, &OsStr)> {
+        self.env.iter().map(|(k, v)| (k.as_os_str(), v.as_os_str()))
+    }
+
+    /// If the child process won't inherit the environment of turbo
+    pub fn will_clear_env(&self) -> bool {
+        self.env_clear
+    }
+
+    /// Runs the command through `sh`, which sets the `RLIMIT_DATA` of the
+    /// process to `memory` bytes before it executes the command. This is for
+    /// children that can't run code of turbo before their command.
+    #[cfg(unix)]
+    pub(crate) fn with_data_rlimit(mut self, memory: u64) -> Self {
+        let program = std::mem::replace(&mut self.program, "/bin/sh".into());
+        let mut args = vec![
+            OsString::from("-c"),
+            // ulimit takes the limit in KiB
+            format!("ulimit -d {} && exec \"$@\"", memory / 1024).into(),
+            OsString::from("sh"),
+            program,
+        ];
+        args.append(&mut self.args);
+        self.args = args;
+        self
+    }
 }
 
 impl From<Command> for tokio::process::Command {
@@ -107,18 +227,27 @@ impl From<Command> for tokio::process::Command {
         let Command {
             program,
             args,
+            verbatim_args,
             cwd,
             env,
             open_stdin,
             env_clear,
+            ..
         } = value;
 
         let mut cmd = tokio::process::Command::new(program);
         if env_clear {
             cmd.env_clear();
         }
-        cmd.args(args)
-            .envs(env)
+        if verbatim_args {
+            #[cfg(windows)]
+            for arg in args {
+                cmd.raw_arg(arg);
+            }
+        } else {
+            cmd.args(args);
+        }
+        cmd.envs(env)
             // We always pipe stdout/stderr to allow us to capture task output
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
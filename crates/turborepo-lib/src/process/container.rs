@@ -0,0 +1,197 @@
+//! `container`
+//!
+//! Tasks can be executed inside of a container instead of on the host, which
+//! gives them a hermetic toolchain. Hashing, caching and output handling stay
+//! on the host, only the command itself runs in the container. The repository
+//! is mounted into the container, so the task sees the same files and writes
+//! its outputs to the host.
+
+use std::{ffi::OsString, fmt};
+
+use serde::Deserialize;
+use turbopath::AbsoluteSystemPath;
+
+use super::{Command, Priority};
+
+/// Environment variables that describe the host rather than the task. They
+/// aren't forwarded into the container, as they would break its toolchain.
+const HOST_ONLY_ENV: &[&str] = &["PATH", "HOME", "TMPDIR", "SHELL"];
+
+/// Where the repository is mounted in containers on Windows, where host
+/// paths can't be used in Linux containers.
+#[cfg(windows)]
+const CONTAINER_REPO_ROOT: &str = "/repo";
+
+/// Executes tasks somewhere other than directly on the host.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub enum Executor {
+    /// Runs the task in a Docker container of `image`
+    Docker { image: String },
+}
+
+impl fmt::Display for Executor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Executor::Docker { image } => write!(f, "docker:{image}"),
+        }
+    }
+}
+
+impl Executor {
+    /// Wraps `command` to be executed by this executor.
+    pub fn wrap(&self, command: Command, repo_root: &AbsoluteSystemPath) -> Command {
+        match self {
+            Executor::Docker { image } => docker_command(image, command, repo_root),
+        }
+    }
+}
+
+fn docker_command(image: &str, command: Command, repo_root: &AbsoluteSystemPath) -> Command {
+    let mut args: Vec<OsString> = vec!["run".into(), "--rm".into(), "--init".into()];
+    if command.will_open_stdin() {
+        args.push("--interactive".into());
+    }
+
+    args.push("--volume".into());
+    args.push(format!("{}:{}", repo_root, container_path(repo_root, repo_root)).into());
+    if let Some(cwd) = command.get_current_dir() {
+        args.push("--workdir".into());
+        args.push(container_path(repo_root, cwd).into());
+    }
+    // Files written to the mounted repository should belong to the user and not
+    // to root
+    #[cfg(unix)]
+    {
+        args.push("--user".into());
+        args.push(format!("{}:{}", nix::unistd::getuid(), nix::unistd::getgid()).into());
+    }
+
+    // Containers have their own limits which cover the whole container, the
+    // docker client itself doesn't need to be limited
+    if let Some(limits) = command.resource_limits() {
+        if let Some(cpus) = limits.cpus {
+            args.push(format!("--cpus={cpus}").into());
+        }
+        if let Some(memory) = limits.memory {
+            args.push(format!("--memory={memory}b").into());
+        }
+    }
+    // The container isn't a descendant of the docker client, so instead of the
+    // client's priority, the container gets a smaller share of the CPU than the
+    // default of 1024
+    if command.get_priority() == Priority::Low {
+        args.push("--cpu-shares=256".into());
+    }
+
+    // Variables are passed by name, so docker takes their values from its own
+    // environment and they don't show up in the arguments of the process
+    for (key, _) in command.get_envs() {
+        if !HOST_ONLY_ENV.iter().any(|host_only| key == *host_only) {
+            args.push("--env".into());
+            args.push(key.to_os_string());
+        }
+    }
+
+    args.push(image.into());
+    args.push(command.get_program().to_os_string());
+    args.extend(command.get_args().iter().cloned());
+
+    let mut docker = Command::new("docker");
+    docker.args(args);
+    if command.will_clear_env() {
+        docker.env_clear();
+    }
+    docker.envs(command.get_envs());
+    // The docker client needs its own configuration, even when the task doesn't
+    // get it
+    docker.envs(std::env::vars_os().filter(|(key, _)| {
+        key.to_str()
+            .is_some_and(|key| key.starts_with("DOCKER_") || key == "HOME")
+    }));
+    if let Some(cwd) = command.get_current_dir() {
+        docker.current_dir(cwd.clone());
+    }
+    if command.will_open_stdin() {
+        docker.open_stdin();
+    }
+    if let Some(pty) = command.will_use_pty() {
+        docker.pty(pty);
+    }
+    docker
+}
+
+#[cfg(unix)]
+fn container_path(_repo_root: &AbsoluteSystemPath, path: &AbsoluteSystemPath) -> String {
+    path.to_string()
+}
+
+#[cfg(windows)]
+fn container_path(repo_root: &AbsoluteSystemPath, path: &AbsoluteSystemPath) -> String {
+    match repo_root.anchor(path) {
+        Ok(relative) if relative.as_str().is_empty() => CONTAINER_REPO_ROOT.to_string(),
+        Ok(relative) => format!("{CONTAINER_REPO_ROOT}/{}", relative.to_unix()),
+        Err(_) => path.to_string(),
+    }
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use turbopath::AbsoluteSystemPathBuf;
+
+    use super::Executor;
+    use crate::process::{Command, ResourceLimits};
+
+    #[test]
+    fn test_docker_command() {
+        let repo_root = AbsoluteSystemPathBuf::new("/repo").unwrap();
+        let mut cmd = Command::new("npm");
+        cmd.args(["run", "build"])
+            .current_dir(repo_root.join_components(&["apps", "web"]))
+            .env_clear()
+            .env("PATH", "/usr/bin")
+            .env("NODE_ENV", "production")
+            .limit_resources(ResourceLimits {
+                cpus: Some(2),
+                memory: None,
+            });
+
+        let executor = Executor::Docker {
+            image: "node:20".to_string(),
+        };
+        let docker = executor.wrap(cmd, &repo_root);
+
+        assert_eq!(docker.get_program(), "docker");
+        let args = docker
+            .get_args()
+            .iter()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .filter(|arg| !arg.starts_with(|c: char| c.is_ascii_digit()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            args,
+            [
+                "run",
+                "--rm",
+                "--init",
+                "--volume",
+                "/repo:/repo",
+                "--workdir",
+                "/repo/apps/web",
+                "--user",
+                "--cpus=2",
+                "--env",
+                "NODE_ENV",
+                "node:20",
+                "npm",
+                "run",
+                "build"
+            ]
+        );
+        // The values of the variables are passed through the environment
+        assert!(docker
+            .get_envs()
+            .any(|(key, value)| key == "NODE_ENV" && value == "production"));
+        assert!(docker.will_clear_env());
+        assert_eq!(docker.resource_limits(), None);
+    }
+}
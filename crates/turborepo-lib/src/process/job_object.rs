@@ -0,0 +1,176 @@
+//! `job_object`
+//!
+//! Windows has no process groups that can be signalled as a whole, so killing
+//! a child only stops the child itself. Its descendants, like the workers of a
+//! bundler, keep running and hold on to ports and files. To be able to stop the
+//! entire process tree, every child is assigned to a job object, which the
+//! descendants it spawns inherit. The job is killed when its handle is closed,
+//! so the tree doesn't outlive `turbo` either, even if `turbo` itself crashes.
+
+use std::{io, mem, ptr};
+
+use winapi::{
+    shared::minwindef::{DWORD, FALSE},
+    um::{
+        handleapi::CloseHandle,
+        jobapi2::{
+            AssignProcessToJobObject, CreateJobObjectW, SetInformationJobObject, TerminateJobObject,
+        },
+        processthreadsapi::OpenProcess,
+        winnt::{
+            JobObjectExtendedLimitInformation, HANDLE, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+            JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE, PROCESS_SET_QUOTA, PROCESS_TERMINATE,
+        },
+    },
+};
+
+/// The exit code of processes that are terminated through their job object.
+const TERMINATED_EXIT_CODE: u32 = 1;
+
+/// A job object that holds the process tree of a single child.
+#[derive(Debug)]
+pub(crate) struct JobObject {
+    handle: HANDLE,
+}
+
+// The handle is only used through thread safe system calls
+unsafe impl Send for JobObject {}
+unsafe impl Sync for JobObject {}
+
+impl JobObject {
+    /// Creates a job object and assigns the process with `pid` to it.
+    ///
+    /// Processes that the child spawns before it is assigned are not part of
+    /// the job, so this should be called right after the child is spawned.
+    /// Closing that gap would require creating the child suspended and
+    /// resuming it after the assignment, but neither `std` nor `portable_pty`
+    /// give access to the primary thread that has to be resumed. In practice
+    /// the child hasn't gotten to spawning anything by the time this is called.
+    pub(crate) fn for_process(pid: u32) -> io::Result<Self> {
+        let handle = unsafe { CreateJobObjectW(ptr::null_mut(), ptr::null()) };
+        if handle.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        let job = Self { handle };
+        job.kill_on_close()?;
+
+        let process = unsafe { OpenProcess(PROCESS_SET_QUOTA | PROCESS_TERMINATE, FALSE, pid) };
+        if process.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        let assigned = unsafe { AssignProcessToJobObject(job.handle, process) };
+        let result = match assigned {
+            FALSE => Err(io::Error::last_os_error()),
+            _ => Ok(job),
+        };
+        unsafe { CloseHandle(process) };
+        result
+    }
+
+    // Terminates the processes of the job once the last handle to it is
+    // closed, which the system does for us if `turbo` exits without cleaning up
+    fn kill_on_close(&self) -> io::Result<()> {
+        let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = unsafe { mem::zeroed() };
+        info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+        let set = unsafe {
+            SetInformationJobObject(
+                self.handle,
+                JobObjectExtendedLimitInformation,
+                &mut info as *mut _ as *mut _,
+                mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as DWORD,
+            )
+        };
+        match set {
+            FALSE => Err(io::Error::last_os_error()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Terminates every process of the job.
+    pub(crate) fn terminate(&self) -> io::Result<()> {
+        match unsafe { TerminateJobObject(self.handle, TERMINATED_EXIT_CODE) } {
+            FALSE => Err(io::Error::last_os_error()),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Drop for JobObject {
+    fn drop(&mut self) {
+        // Descendants that are still running when the child is dropped are
+        // killed along with the job
+        unsafe { CloseHandle(self.handle) };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        process::{Command, Stdio},
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use winapi::um::{
+        jobapi2::QueryInformationJobObject,
+        winnt::{JobObjectBasicAccountingInformation, JOBOBJECT_BASIC_ACCOUNTING_INFORMATION},
+    };
+
+    use super::*;
+
+    fn active_processes(job: &JobObject) -> u32 {
+        let mut info: JOBOBJECT_BASIC_ACCOUNTING_INFORMATION = unsafe { mem::zeroed() };
+        let queried = unsafe {
+            QueryInformationJobObject(
+                job.handle,
+                JobObjectBasicAccountingInformation,
+                &mut info as *mut _ as *mut _,
+                mem::size_of::<JOBOBJECT_BASIC_ACCOUNTING_INFORMATION>() as DWORD,
+                ptr::null_mut(),
+            )
+        };
+        assert_ne!(queried, FALSE, "{}", io::Error::last_os_error());
+        info.ActiveProcesses
+    }
+
+    fn wait_for(mut condition: impl FnMut() -> bool) {
+        let start = Instant::now();
+        while !condition() {
+            assert!(start.elapsed() < Duration::from_secs(10), "timed out");
+            thread::sleep(Duration::from_millis(50));
+        }
+    }
+
+    // `cmd` runs `ping` as a child of its own, which is what killing just the
+    // child would leave running
+    fn spawn_tree() -> std::process::Child {
+        Command::new("cmd")
+            .args(["/C", "ping -n 30 127.0.0.1 > nul"])
+            .stdout(Stdio::null())
+            .spawn()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_terminate_kills_descendants() {
+        let mut child = spawn_tree();
+        let job = JobObject::for_process(child.id()).unwrap();
+        wait_for(|| active_processes(&job) >= 2);
+
+        job.terminate().unwrap();
+        child.wait().unwrap();
+        wait_for(|| active_processes(&job) == 0);
+    }
+
+    #[test]
+    fn test_close_kills_descendants() {
+        let mut child = spawn_tree();
+        let job = JobObject::for_process(child.id()).unwrap();
+        wait_for(|| active_processes(&job) >= 2);
+
+        drop(job);
+        let start = Instant::now();
+        child.wait().unwrap();
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+}
@@ -0,0 +1,322 @@
+//! `limits`
+//!
+//! Resource limits of child processes. On Linux the limits are enforced with
+//! a cgroup v2 per child, which covers the entire process tree of the child.
+//! If turbo can't manage cgroups, e.g. because the cgroup hierarchy isn't
+//! delegated to it, the memory limit falls back to a `RLIMIT_DATA` of the
+//! child itself, which is set before the child executes its command. Other
+//! platforms don't enforce limits.
+
+use serde::Deserialize;
+
+/// Limits on the resources a child process and its descendants may use.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct ResourceLimits {
+    /// The number of CPUs worth of CPU time the child may use.
+    pub cpus: Option<u32>,
+    /// The amount of memory in bytes the child may use.
+    pub memory: Option<u64>,
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) use linux::{set_rlimit, ResourceLimiter};
+
+/// Applies resource limits on platforms that don't support them.
+#[cfg(not(target_os = "linux"))]
+#[derive(Debug)]
+pub(crate) struct ResourceLimiter;
+
+#[cfg(all(unix, not(target_os = "linux")))]
+pub(crate) fn set_rlimit(_memory: u64) -> std::io::Result<()> {
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+impl ResourceLimiter {
+    pub(crate) fn rlimit(_limits: ResourceLimits) -> Option<u64> {
+        None
+    }
+
+    pub(crate) fn apply(_limits: ResourceLimits, _pid: u32) -> Option<Self> {
+        tracing::debug!("resource limits are only enforced on Linux");
+        None
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::{
+        fs, io,
+        path::{Path, PathBuf},
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            OnceLock,
+        },
+    };
+
+    use tracing::debug;
+
+    use super::ResourceLimits;
+
+    const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+    /// The period that `cpu.max` quotas are relative to, in microseconds.
+    const CPU_PERIOD: u64 = 100_000;
+
+    /// Keeps the cgroup of a child alive. The cgroup is removed once the
+    /// child exited.
+    #[derive(Debug)]
+    pub(crate) struct ResourceLimiter {
+        cgroup: PathBuf,
+    }
+
+    impl ResourceLimiter {
+        /// The `RLIMIT_DATA` that limits the memory of a child if cgroups
+        /// can't be used. It has to be set with `set_rlimit` before the child
+        /// executes its command.
+        pub(crate) fn rlimit(limits: ResourceLimits) -> Option<u64> {
+            match parent_cgroup() {
+                Some(_) => None,
+                None => limits.memory,
+            }
+        }
+
+        /// Applies `limits` to the process with `pid`. Processes that the
+        /// child spawns before its limits are applied aren't limited, so this
+        /// should be called right after the child is spawned.
+        pub(crate) fn apply(limits: ResourceLimits, pid: u32) -> Option<Self> {
+            if parent_cgroup().is_none() {
+                if limits.cpus.is_some() {
+                    debug!("cpu limits require cgroups, not limiting cpus of child {pid}");
+                }
+                return None;
+            }
+            match create_cgroup(limits, pid) {
+                Ok(cgroup) => Some(Self { cgroup }),
+                Err(e) => {
+                    debug!("unable to limit child {pid} with a cgroup: {e}");
+                    None
+                }
+            }
+        }
+    }
+
+    /// Sets the `RLIMIT_DATA` of the calling process. Only calls async signal
+    /// safe functions, so it can be used in `pre_exec`.
+    pub(crate) fn set_rlimit(memory: u64) -> io::Result<()> {
+        let limit = libc::rlimit {
+            rlim_cur: memory,
+            rlim_max: memory,
+        };
+        match unsafe { libc::setrlimit(libc::RLIMIT_DATA, &limit) } {
+            0 => Ok(()),
+            _ => Err(io::Error::last_os_error()),
+        }
+    }
+
+    impl Drop for ResourceLimiter {
+        fn drop(&mut self) {
+            // Fails if processes of the child are still running, the cgroup
+            // is then removed by the system once they exited
+            if let Err(e) = fs::remove_dir(&self.cgroup) {
+                debug!("unable to remove cgroup {}: {e}", self.cgroup.display());
+            }
+        }
+    }
+
+    fn create_cgroup(limits: ResourceLimits, pid: u32) -> io::Result<PathBuf> {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+        let parent = parent_cgroup().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::Unsupported, "cgroups v2 are not available")
+        })?;
+        let cgroup = parent.join(format!(
+            "turbo-{}-task-{}",
+            std::process::id(),
+            NEXT_ID.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir(&cgroup)?;
+
+        let configure = || -> io::Result<()> {
+            if let Some(cpus) = limits.cpus {
+                let quota = u64::from(cpus) * CPU_PERIOD;
+                fs::write(cgroup.join("cpu.max"), format!("{quota} {CPU_PERIOD}"))?;
+            }
+            if let Some(memory) = limits.memory {
+                fs::write(cgroup.join("memory.max"), memory.to_string())?;
+            }
+            fs::write(cgroup.join("cgroup.procs"), pid.to_string())
+        };
+        if let Err(e) = configure() {
+            fs::remove_dir(&cgroup).ok();
+            return Err(e);
+        }
+        Ok(cgroup)
+    }
+
+    /// Returns the cgroup that the cgroups of children are created in.
+    ///
+    /// Processes can only live in leaf cgroups once the cgroup distributes
+    /// controllers to its children, so turbo moves itself into a leaf of its
+    /// own cgroup.
+    fn parent_cgroup() -> Option<&'static Path> {
+        static PARENT: OnceLock<Option<PathBuf>> = OnceLock::new();
+        PARENT
+            .get_or_init(|| match setup_parent_cgroup() {
+                Ok(parent) => Some(parent),
+                Err(e) => {
+                    debug!("unable to set up cgroups for resource limits: {e}");
+                    None
+                }
+            })
+            .as_deref()
+    }
+
+    fn setup_parent_cgroup() -> io::Result<PathBuf> {
+        let cgroups = fs::read_to_string("/proc/self/cgroup")?;
+        // cgroups v2 only have a single hierarchy with the id 0
+        let own = cgroups
+            .lines()
+            .find_map(|line| line.strip_prefix("0::"))
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::Unsupported, "cgroups v2 are not mounted")
+            })?;
+        let parent = Path::new(CGROUP_ROOT).join(own.trim_start_matches('/'));
+        delegate(&parent, std::process::id())?;
+        Ok(parent)
+    }
+
+    /// Moves the process `pid` into a leaf of its cgroup `parent`, and enables
+    /// the cpu and memory controllers for the children of `parent`. The cgroup
+    /// is left as it was unless all of that succeeds.
+    fn delegate(parent: &Path, pid: u32) -> io::Result<()> {
+        check_delegated(parent, pid)?;
+
+        let pid = pid.to_string();
+        // Named after the process, so concurrent runs don't share a leaf
+        let leaf = parent.join(format!("turbo-{pid}"));
+        fs::create_dir(&leaf)?;
+        if let Err(e) = fs::write(leaf.join("cgroup.procs"), &pid) {
+            fs::remove_dir(&leaf).ok();
+            return Err(e);
+        }
+        if let Err(e) = fs::write(parent.join("cgroup.subtree_control"), "+cpu +memory") {
+            if let Err(e) = fs::write(parent.join("cgroup.procs"), &pid) {
+                debug!("unable to move turbo back to {}: {e}", parent.display());
+            }
+            fs::remove_dir(&leaf).ok();
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    // Checks that turbo may enable the controllers for the children of its
+    // cgroup before anything is changed. That's the case if they're available
+    // to the cgroup, turbo can write to it, and no other processes live in it.
+    fn check_delegated(parent: &Path, pid: u32) -> io::Result<()> {
+        let unsupported = |msg: String| io::Error::new(io::ErrorKind::Unsupported, msg);
+
+        let controllers = fs::read_to_string(parent.join("cgroup.controllers"))?;
+        for controller in ["cpu", "memory"] {
+            if !controllers.split_whitespace().any(|c| c == controller) {
+                return Err(unsupported(format!(
+                    "the {controller} controller isn't available to {}",
+                    parent.display()
+                )));
+            }
+        }
+
+        let procs = fs::read_to_string(parent.join("cgroup.procs"))?;
+        let pid = pid.to_string();
+        if procs.lines().any(|proc| proc != pid) {
+            return Err(unsupported(format!(
+                "other processes share the cgroup {}",
+                parent.display()
+            )));
+        }
+
+        for file in ["cgroup.procs", "cgroup.subtree_control"] {
+            nix::unistd::access(&parent.join(file), nix::unistd::AccessFlags::W_OK)?;
+        }
+        Ok(())
+    }
+
+    #[cfg(test)]
+    mod test {
+        use std::{fs, path::Path, process::Command};
+
+        use tempfile::tempdir;
+
+        use super::{delegate, set_rlimit};
+
+        fn fake_cgroup(dir: &Path, controllers: &str, procs: &str) {
+            fs::write(dir.join("cgroup.controllers"), controllers).unwrap();
+            fs::write(dir.join("cgroup.procs"), procs).unwrap();
+            fs::write(dir.join("cgroup.subtree_control"), "").unwrap();
+        }
+
+        #[test]
+        fn test_delegate() {
+            let dir = tempdir().unwrap();
+            fake_cgroup(dir.path(), "cpuset cpu io memory pids", "42\n");
+
+            delegate(dir.path(), 42).unwrap();
+
+            let leaf = dir.path().join("turbo-42");
+            assert_eq!(fs::read_to_string(leaf.join("cgroup.procs")).unwrap(), "42");
+            assert_eq!(
+                fs::read_to_string(dir.path().join("cgroup.subtree_control")).unwrap(),
+                "+cpu +memory"
+            );
+        }
+
+        #[test]
+        fn test_delegate_unsupported() {
+            let missing_controller = tempdir().unwrap();
+            fake_cgroup(missing_controller.path(), "cpu io pids", "42\n");
+            let shared = tempdir().unwrap();
+            fake_cgroup(shared.path(), "cpu memory", "42\n43\n");
+
+            for dir in [missing_controller, shared] {
+                assert!(delegate(dir.path(), 42).is_err());
+                assert!(
+                    !dir.path().join("turbo-42").exists(),
+                    "turbo isn't moved unless the cgroup is delegated"
+                );
+                assert_eq!(
+                    fs::read_to_string(dir.path().join("cgroup.subtree_control")).unwrap(),
+                    ""
+                );
+            }
+        }
+
+        #[test]
+        fn test_delegate_undo() {
+            let dir = tempdir().unwrap();
+            fake_cgroup(dir.path(), "cpu memory", "42\n");
+            // Fails to enable the controllers once turbo was moved
+            fs::remove_file(dir.path().join("cgroup.subtree_control")).unwrap();
+            fs::create_dir(dir.path().join("cgroup.subtree_control")).unwrap();
+
+            assert!(delegate(dir.path(), 42).is_err());
+            assert_eq!(
+                fs::read_to_string(dir.path().join("cgroup.procs")).unwrap(),
+                "42",
+                "turbo is moved back into its cgroup"
+            );
+        }
+
+        #[test]
+        fn test_set_rlimit() {
+            let mut cmd = Command::new("sh");
+            cmd.args(["-c", "ulimit -d"]);
+            unsafe {
+                std::os::unix::process::CommandExt::pre_exec(&mut cmd, || {
+                    set_rlimit(64 * 1024 * 1024)
+                });
+            }
+            let output = cmd.output().unwrap();
+            // ulimit reports the limit in KiB
+            assert_eq!(String::from_utf8(output.stdout).unwrap().trim(), "65536");
+        }
+    }
+}
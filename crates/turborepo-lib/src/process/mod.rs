@@ -11,6 +11,12 @@
 
 mod child;
 mod command;
+mod container;
+#[cfg(windows)]
+mod job_object;
+mod limits;
+mod orphans;
+mod priority;
 
 use std::{
     io,
@@ -22,8 +28,16 @@ pub use command::Command;
 use futures::Future;
 use tokio::task::JoinSet;
 use tracing::{debug, trace};
-
-pub use self::child::{Child, ChildExit};
+use turbopath::AbsoluteSystemPath;
+
+use self::orphans::PidFile;
+pub use self::{
+    child::{signal_name, Child, ChildExit},
+    container::Executor,
+    limits::ResourceLimits,
+    orphans::{Orphan, Orphans},
+    priority::Priority,
+};
 
 /// A process manager that is responsible for spawning and managing child
 /// processes. When the manager is Open, new child processes can be spawned
@@ -39,6 +53,7 @@ pub struct ProcessManager {
 struct ProcessManagerInner {
     is_closing: bool,
     children: Vec<child::Child>,
+    pid_file: Option<PidFile>,
 }
 
 impl ProcessManager {
@@ -47,6 +62,7 @@ impl ProcessManager {
             state: Arc::new(Mutex::new(ProcessManagerInner {
                 is_closing: false,
                 children: Vec::new(),
+                pid_file: None,
             })),
             use_pty,
         }
@@ -79,15 +95,68 @@ impl ProcessManager {
         if lock.is_closing {
             return None;
         }
+        Some(self.spawn_locked(&mut lock, command, stop_timeout))
+    }
+
+    /// Spawn a child process that cleans up after another one, e.g. the after
+    /// hook of a task. Unlike `spawn` this also works while the manager is
+    /// closing, so the cleanup isn't skipped when turbo is interrupted. A
+    /// stop that is already in progress doesn't affect the child, but it is
+    /// still killed if the shutdown is forced.
+    pub fn spawn_teardown(
+        &self,
+        command: Command,
+        stop_timeout: Duration,
+    ) -> io::Result<child::Child> {
+        let mut lock = self.state.lock().unwrap();
+        self.spawn_locked(&mut lock, command, stop_timeout)
+    }
+
+    fn spawn_locked(
+        &self,
+        lock: &mut ProcessManagerInner,
+        command: Command,
+        stop_timeout: Duration,
+    ) -> io::Result<child::Child> {
+        let use_pty = command.will_use_pty().unwrap_or(self.use_pty);
         let child = child::Child::spawn(
             command,
             child::ShutdownStyle::Graceful(stop_timeout),
-            self.use_pty,
+            use_pty,
         );
         if let Ok(child) = &child {
+            if let (Some(pid_file), Some(pid)) = (&mut lock.pid_file, child.pid()) {
+                if let Err(e) = pid_file.add(pid, child.label()) {
+                    debug!("unable to record pid of {}: {e}", child.label());
+                }
+            }
             lock.children.push(child.clone());
         }
-        Some(child)
+        child
+    }
+
+    /// Record the pids of spawned children in a pid file in `dir`, so they can
+    /// be cleaned up if turbo crashes before they exited.
+    pub fn record_pids(&self, dir: &AbsoluteSystemPath) {
+        let mut lock = self.state.lock().expect("not poisoned");
+        lock.pid_file = Some(PidFile::new(dir));
+    }
+
+    /// Remove the pid file once all children exited.
+    pub fn remove_pid_file(&self) {
+        let pid_file = self.state.lock().expect("not poisoned").pid_file.take();
+        if let Some(pid_file) = pid_file {
+            pid_file.remove();
+        }
+    }
+
+    /// Resize the PTYs of all running child processes that are hooked up to
+    /// one, e.g. after the terminal turbo is running in was resized.
+    pub fn resize(&self, rows: u16, cols: u16) {
+        let lock = self.state.lock().expect("not poisoned");
+        for child in lock.children.iter() {
+            child.resize(rows, cols);
+        }
     }
 
     /// Stop the process manager, closing all child processes. On posix
@@ -97,6 +166,12 @@ impl ProcessManager {
         self.close(|mut c| async move { c.stop().await }).await
     }
 
+    /// Stop the process manager, killing all child processes immediately. This
+    /// also cuts short any graceful shutdown that is already in progress.
+    pub async fn kill(&self) {
+        self.close(|mut c| async move { c.kill().await }).await
+    }
+
     /// Stop the process manager, waiting for all child processes to exit.
     ///
     /// If you want to set a timeout, use `tokio::time::timeout` and
@@ -127,17 +202,25 @@ impl ProcessManager {
             }
         }
 
-        debug!("waiting for {} processes to exit", set.len());
+        let closed = set.len();
+        debug!("waiting for {} processes to exit", closed);
 
         while let Some(out) = set.join_next().await {
             trace!("process exited: {:?}", out);
         }
 
-        {
+        let teardown_running = {
             let mut lock = self.state.lock().expect("not poisoned");
 
-            // just allocate a new vec rather than clearing the old one
-            lock.children = vec![];
+            // Children spawned by `spawn_teardown` while closing come after the
+            // ones we just closed and are kept until they are closed as well
+            let closed = closed.min(lock.children.len());
+            lock.children.drain(..closed);
+            !lock.children.is_empty()
+        };
+
+        if !teardown_running {
+            self.remove_pid_file();
         }
     }
 }
@@ -227,6 +310,31 @@ mod test {
         manager.stop().await;
     }
 
+    #[tokio::test]
+    async fn test_teardown_while_closing() {
+        let manager = ProcessManager::new(false);
+        let mut child = manager
+            .spawn(get_command(), Duration::from_secs(2))
+            .unwrap()
+            .unwrap();
+        let (exit, _) = join! {
+            child.wait(),
+            manager.stop(),
+        };
+        assert_eq!(exit, STOPPED_EXIT);
+
+        // Cleanup still runs once the manager is closed
+        let mut teardown = manager
+            .spawn_teardown(get_script_command("hello_world.js"), Duration::from_secs(2))
+            .unwrap();
+        let mut out = Vec::new();
+        let exit = teardown.wait_with_piped_outputs(&mut out).await.unwrap();
+        assert_eq!(exit, Some(ChildExit::Finished(Some(0))));
+        assert_eq!(out, b"hello world\n");
+
+        manager.stop().await;
+    }
+
     #[tokio::test]
     async fn test_exit_code() {
         let manager = ProcessManager::new(false);
@@ -357,4 +465,36 @@ mod test {
 
         assert!((finish_time - start_time).lt(&Duration::from_secs(2)));
     }
+
+    #[tokio::test]
+    async fn test_kill_during_stop() {
+        let manager = ProcessManager::new(false);
+
+        let mut child = manager
+            .spawn(
+                get_script_command("sleep_5_ignore.js"),
+                Duration::from_secs(10),
+            )
+            .unwrap()
+            .unwrap();
+
+        // let the task start
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let start_time = Instant::now();
+
+        // the child ignores the SIGINT from stop, so it's only killed once
+        // kill cuts the graceful shutdown short
+        let stopping_manager = manager.clone();
+        let stop = tokio::spawn(async move { stopping_manager.stop().await });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let (exit, _) = join! {
+            child.wait(),
+            manager.kill(),
+        };
+        stop.await.unwrap();
+
+        assert_eq!(exit, STOPPED_EXIT);
+        assert!((Instant::now() - start_time).lt(&Duration::from_secs(2)));
+    }
 }
@@ -0,0 +1,294 @@
+//! `orphans`
+//!
+//! If turbo crashes, the children it spawned keep running, as every child is
+//! started in a process group of its own. Dev servers left behind this way
+//! keep their ports bound, which breaks the next run. To be able to find them
+//! again, every run records the children it spawns in a pid file, which is
+//! removed once the run is over. Pid files of runs that are no longer alive
+//! point to orphaned processes.
+
+use std::{io, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use sysinfo::{Pid, ProcessExt, ProcessRefreshKind, RefreshKind, System, SystemExt};
+use tracing::debug;
+use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
+
+/// How long orphans get to shut down gracefully before they are killed.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RunRecord {
+    pid: u32,
+    start_time: u64,
+    children: Vec<ChildRecord>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChildRecord {
+    pid: u32,
+    // Used to tell the child apart from unrelated processes that reused its pid
+    start_time: u64,
+    label: String,
+}
+
+/// The pid file of the current run.
+#[derive(Debug)]
+pub(crate) struct PidFile {
+    path: AbsoluteSystemPathBuf,
+    record: RunRecord,
+    system: System,
+}
+
+impl PidFile {
+    pub(crate) fn new(dir: &AbsoluteSystemPath) -> Self {
+        let mut system = System::new();
+        let pid = std::process::id();
+        Self {
+            path: dir.join_component(&format!("{pid}.json")),
+            record: RunRecord {
+                pid,
+                start_time: start_time(&mut system, pid).unwrap_or_default(),
+                children: Vec::new(),
+            },
+            system,
+        }
+    }
+
+    /// Records a spawned child.
+    pub(crate) fn add(&mut self, pid: u32, label: &str) -> io::Result<()> {
+        let Some(start_time) = start_time(&mut self.system, pid) else {
+            // The child already exited
+            return Ok(());
+        };
+        self.record.children.push(ChildRecord {
+            pid,
+            start_time,
+            label: label.to_string(),
+        });
+        self.path.ensure_dir()?;
+        self.path
+            .create_with_contents(serde_json::to_string(&self.record)?)
+    }
+
+    pub(crate) fn remove(self) {
+        if let Err(e) = self.path.remove_file() {
+            if e.kind() != io::ErrorKind::NotFound {
+                debug!("unable to remove pid file {}: {e}", self.path);
+            }
+        }
+    }
+}
+
+/// A process that was left behind by a previous run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Orphan {
+    pub pid: u32,
+    pub label: String,
+}
+
+/// The processes left behind by previous runs that are still running.
+#[derive(Debug, Default)]
+pub struct Orphans {
+    pid_files: Vec<AbsoluteSystemPathBuf>,
+    processes: Vec<Orphan>,
+}
+
+impl Orphans {
+    /// Finds the orphans recorded in the pid files in `dir`. Pid files of
+    /// crashed runs without any running processes left are removed.
+    pub fn find(dir: &AbsoluteSystemPath) -> Self {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Self::default();
+        };
+        let system = System::new_with_specifics(
+            RefreshKind::new().with_processes(ProcessRefreshKind::new()),
+        );
+        let is_running = |pid: u32, recorded_start_time: u64| {
+            system
+                .process(Pid::from(pid as usize))
+                .is_some_and(|process| process.start_time() == recorded_start_time)
+        };
+
+        let mut orphans = Self::default();
+        for entry in entries.flatten() {
+            let Ok(path) = AbsoluteSystemPathBuf::try_from(entry.path()) else {
+                continue;
+            };
+            let record =
+                match path
+                    .read_to_string()
+                    .map_err(|e| e.to_string())
+                    .and_then(|contents| {
+                        serde_json::from_str::<RunRecord>(&contents).map_err(|e| e.to_string())
+                    }) {
+                    Ok(record) => record,
+                    Err(e) => {
+                        debug!("ignoring invalid pid file {path}: {e}");
+                        continue;
+                    }
+                };
+            // The run is still going
+            if is_running(record.pid, record.start_time) {
+                continue;
+            }
+
+            let processes = record
+                .children
+                .into_iter()
+                .filter(|child| is_group_running(&system, child))
+                .map(|child| Orphan {
+                    pid: child.pid,
+                    label: child.label,
+                })
+                .collect::<Vec<_>>();
+            if processes.is_empty() {
+                path.remove_file().ok();
+            } else {
+                orphans.pid_files.push(path);
+                orphans.processes.extend(processes);
+            }
+        }
+        orphans
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.processes.is_empty()
+    }
+
+    pub fn processes(&self) -> &[Orphan] {
+        &self.processes
+    }
+
+    /// Stops the orphans, first gracefully and then forcefully once
+    /// `SHUTDOWN_TIMEOUT` passed.
+    pub async fn stop(self) {
+        for orphan in &self.processes {
+            signal(orphan.pid, false);
+        }
+        let deadline = tokio::time::Instant::now() + SHUTDOWN_TIMEOUT;
+        while self.processes.iter().any(|orphan| is_alive(orphan.pid))
+            && tokio::time::Instant::now() < deadline
+        {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        for orphan in self.processes.iter().filter(|orphan| is_alive(orphan.pid)) {
+            debug!("killing orphan {} that didn't shut down", orphan.pid);
+            signal(orphan.pid, true);
+        }
+        for pid_file in self.pid_files {
+            pid_file.remove_file().ok();
+        }
+    }
+}
+
+fn start_time(system: &mut System, pid: u32) -> Option<u64> {
+    let pid = Pid::from(pid as usize);
+    system.refresh_process_specifics(pid, ProcessRefreshKind::new());
+    system.process(pid).map(|process| process.start_time())
+}
+
+// Children are the leaders of their process groups, so on unix the group
+// outlives the child when its descendants keep running. Pids aren't reused
+// while a group with the same id exists, so a running group always belongs to
+// the child, unless a new process with the same pid became a group leader.
+#[cfg(unix)]
+fn is_group_running(system: &System, child: &ChildRecord) -> bool {
+    let leader = system.process(Pid::from(child.pid as usize));
+    is_alive(child.pid) && leader.map_or(true, |leader| leader.start_time() == child.start_time)
+}
+
+#[cfg(windows)]
+fn is_group_running(system: &System, child: &ChildRecord) -> bool {
+    system
+        .process(Pid::from(child.pid as usize))
+        .is_some_and(|process| process.start_time() == child.start_time)
+}
+
+#[cfg(unix)]
+fn is_alive(pid: u32) -> bool {
+    use nix::{sys::signal::killpg, unistd::Pid};
+
+    killpg(Pid::from_raw(pid as i32), None).is_ok()
+}
+
+#[cfg(unix)]
+fn signal(pid: u32, kill: bool) {
+    use nix::{
+        sys::signal::{killpg, Signal},
+        unistd::Pid,
+    };
+
+    let signal = if kill {
+        Signal::SIGKILL
+    } else {
+        Signal::SIGTERM
+    };
+    if let Err(e) = killpg(Pid::from_raw(pid as i32), signal) {
+        debug!("unable to send {signal} to orphan {pid}: {e}");
+    }
+}
+
+#[cfg(windows)]
+fn is_alive(pid: u32) -> bool {
+    let mut system = System::new();
+    system.refresh_process_specifics(Pid::from(pid as usize), ProcessRefreshKind::new())
+}
+
+// Windows has no graceful shutdown for processes without a console, so
+// orphans are killed right away
+#[cfg(windows)]
+fn signal(pid: u32, _kill: bool) {
+    let mut system = System::new();
+    let pid = Pid::from(pid as usize);
+    system.refresh_process_specifics(pid, ProcessRefreshKind::new());
+    if let Some(process) = system.process(pid) {
+        process.kill();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tempfile::tempdir;
+    use turbopath::AbsoluteSystemPathBuf;
+
+    use super::{ChildRecord, Orphans, PidFile, RunRecord};
+
+    #[test]
+    fn test_pid_file_of_running_process() {
+        let tmp = tempdir().unwrap();
+        let dir = AbsoluteSystemPathBuf::try_from(tmp.path()).unwrap();
+
+        let mut pid_file = PidFile::new(&dir);
+        pid_file.add(std::process::id(), "self").unwrap();
+        // The run that recorded the pid file is still running
+        assert!(Orphans::find(&dir).is_empty());
+
+        pid_file.remove();
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_stale_pid_file() {
+        let tmp = tempdir().unwrap();
+        let dir = AbsoluteSystemPathBuf::try_from(tmp.path()).unwrap();
+        let record = RunRecord {
+            pid: i32::MAX as u32,
+            start_time: 0,
+            children: vec![ChildRecord {
+                pid: i32::MAX as u32 - 1,
+                start_time: 0,
+                label: "(web) node server.js".to_string(),
+            }],
+        };
+        let path = dir.join_component("1.json");
+        path.create_with_contents(serde_json::to_string(&record).unwrap())
+            .unwrap();
+
+        // Neither the run nor its child are running anymore
+        assert!(Orphans::find(&dir).is_empty());
+        assert!(!path.exists());
+    }
+}
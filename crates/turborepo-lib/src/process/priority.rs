@@ -0,0 +1,117 @@
+//! `priority`
+//!
+//! Scheduling priority of child processes. Children with a low priority only
+//! get CPU time and disk bandwidth that processes with a normal priority, like
+//! an interactive dev server, don't need. On unix the niceness of the child is
+//! raised, and on Linux it is also moved to the lowest best-effort I/O
+//! priority. On Windows the child gets a below normal priority class. The
+//! processes that the child spawns inherit its priority.
+
+use std::io;
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// The niceness of children with a low priority.
+#[cfg(unix)]
+const LOW_NICENESS: libc::c_int = 10;
+
+/// The scheduling priority of a child process.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    #[default]
+    Normal,
+    Low,
+}
+
+impl Priority {
+    /// Applies the priority to the process with `pid`. Processes that the
+    /// child spawns before its priority is applied keep the normal priority,
+    /// so this should be called right after the child is spawned.
+    pub(crate) fn apply(self, pid: u32) {
+        if self == Priority::Normal {
+            return;
+        }
+        if let Err(e) = lower_priority(pid) {
+            debug!("unable to lower priority of child {pid}: {e}");
+        }
+    }
+}
+
+#[cfg(unix)]
+fn lower_priority(pid: u32) -> io::Result<()> {
+    let result = unsafe { libc::setpriority(libc::PRIO_PROCESS, pid as libc::id_t, LOW_NICENESS) };
+    if result != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // The idle I/O class could starve the child entirely while other processes
+    // use the disk, so it gets the lowest priority of the best-effort class
+    #[cfg(target_os = "linux")]
+    {
+        const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+        const IOPRIO_CLASS_BE: libc::c_int = 2;
+        const IOPRIO_CLASS_SHIFT: libc::c_int = 13;
+        const IOPRIO_LOWEST_LEVEL: libc::c_int = 7;
+
+        let result = unsafe {
+            libc::syscall(
+                libc::SYS_ioprio_set,
+                IOPRIO_WHO_PROCESS,
+                pid as libc::c_int,
+                (IOPRIO_CLASS_BE << IOPRIO_CLASS_SHIFT) | IOPRIO_LOWEST_LEVEL,
+            )
+        };
+        if result != 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn lower_priority(pid: u32) -> io::Result<()> {
+    use winapi::{
+        shared::minwindef::FALSE,
+        um::{
+            handleapi::CloseHandle,
+            processthreadsapi::{OpenProcess, SetPriorityClass},
+            winbase::BELOW_NORMAL_PRIORITY_CLASS,
+            winnt::PROCESS_SET_INFORMATION,
+        },
+    };
+
+    let handle = unsafe { OpenProcess(PROCESS_SET_INFORMATION, FALSE, pid) };
+    if handle.is_null() {
+        return Err(io::Error::last_os_error());
+    }
+    let result = unsafe { SetPriorityClass(handle, BELOW_NORMAL_PRIORITY_CLASS) };
+    let error = io::Error::last_os_error();
+    unsafe { CloseHandle(handle) };
+    match result {
+        0 => Err(error),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use super::{Priority, LOW_NICENESS};
+
+    #[test]
+    fn test_low_priority() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("5")
+            .spawn()
+            .unwrap();
+        Priority::Low.apply(child.id());
+
+        let niceness = unsafe { libc::getpriority(libc::PRIO_PROCESS, child.id() as libc::id_t) };
+        child.kill().unwrap();
+        child.wait().unwrap();
+        // The niceness can only be raised, so it's higher if turbo itself already
+        // runs with a low priority
+        assert!(niceness >= LOW_NICENESS);
+    }
+}
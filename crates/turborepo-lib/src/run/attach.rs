@@ -0,0 +1,224 @@
+//! Forwards the stdin of turbo to a single persistent task, so interactive dev
+//! tools keep working while several of them run at once.
+//!
+//! Input is forwarded line by line to the attached task. A line starting with
+//! Ctrl-] (`^]`) switches the task that stdin is attached to, e.g. `^]web#dev`
+//! or just `^]web`, while a `^]` on its own lists the tasks that can be
+//! attached to.
+
+use std::{
+    collections::BTreeMap,
+    io::BufRead,
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::mpsc;
+use tracing::debug;
+use turborepo_ui::{color, GREY, UI};
+
+use crate::process::Child;
+
+/// The byte that Ctrl-] sends, which terminals don't treat specially.
+const ATTACH_ESCAPE: u8 = 0x1d;
+
+#[derive(Clone)]
+pub struct StdinRouter {
+    state: Arc<Mutex<State>>,
+    ui: UI,
+}
+
+#[derive(Default)]
+struct State {
+    tasks: BTreeMap<String, Child>,
+    attached: Option<String>,
+    // The task passed to --attach, which gets attached once it is started
+    requested: Option<String>,
+    warned_detached: bool,
+}
+
+impl StdinRouter {
+    pub fn new(requested: Option<String>, ui: UI) -> Self {
+        let state = State {
+            requested: requested.filter(|task| !task.is_empty()),
+            ..Default::default()
+        };
+        Self {
+            state: Arc::new(Mutex::new(state)),
+            ui,
+        }
+    }
+
+    /// Makes `child` available to attach to as `task`.
+    pub fn register(&self, task: String, child: Child) {
+        let mut state = self.state.lock().expect("lock poisoned");
+        state.tasks.insert(task.clone(), child);
+        if state.attached.is_none()
+            && state
+                .requested
+                .as_deref()
+                .is_some_and(|requested| matches_task(&task, requested))
+        {
+            state.requested = None;
+            state.attached = Some(task.clone());
+            self.notify(&format!("stdin attached to {task}"));
+        }
+    }
+
+    /// Removes `task` once it exited.
+    pub fn unregister(&self, task: &str) {
+        let mut state = self.state.lock().expect("lock poisoned");
+        state.tasks.remove(task);
+        if state.attached.as_deref() == Some(task) {
+            state.attached = None;
+            state.warned_detached = false;
+            self.notify(&format!("{task} exited, stdin detached"));
+        }
+    }
+
+    /// Starts reading the stdin of turbo. Reading stops once stdin is closed.
+    ///
+    /// Stdin is read on a dedicated thread rather than with `tokio::io::stdin`,
+    /// whose blocking read can't be cancelled and would keep the runtime from
+    /// shutting down until another line is entered.
+    pub fn forward_stdin(&self) {
+        let (lines_tx, mut lines_rx) = mpsc::unbounded_channel();
+        let spawned = std::thread::Builder::new()
+            .name("turbo-stdin".to_string())
+            .spawn(move || read_lines(std::io::stdin().lock(), lines_tx));
+        if let Err(e) = spawned {
+            debug!("unable to start reading stdin: {e}");
+            return;
+        }
+
+        let router = self.clone();
+        tokio::spawn(async move {
+            while let Some(line) = lines_rx.recv().await {
+                router.handle_line(&line).await;
+            }
+        });
+    }
+
+    async fn handle_line(&self, line: &[u8]) {
+        if let Some(command) = line.strip_prefix(&[ATTACH_ESCAPE]) {
+            let query = String::from_utf8_lossy(command);
+            self.attach(query.trim());
+            return;
+        }
+
+        let attached = {
+            let mut guard = self.state.lock().expect("lock poisoned");
+            let state = &mut *guard;
+            match &state.attached {
+                Some(task) => Some((task.clone(), state.tasks[task].clone())),
+                None => {
+                    if !state.warned_detached {
+                        state.warned_detached = true;
+                        self.notify("stdin isn't attached to a task, use ^]<task> to attach");
+                    }
+                    None
+                }
+            }
+        };
+        if let Some((task, child)) = attached {
+            if let Err(e) = child.write_stdin(line).await {
+                debug!("unable to write to stdin of {task}: {e}");
+            }
+        }
+    }
+
+    fn attach(&self, query: &str) {
+        let mut state = self.state.lock().expect("lock poisoned");
+        if query.is_empty() {
+            let tasks = state.tasks.keys().cloned().collect::<Vec<_>>().join(", ");
+            match tasks.is_empty() {
+                true => self.notify("no running tasks to attach stdin to"),
+                false => self.notify(&format!("tasks to attach stdin to: {tasks}")),
+            }
+            return;
+        }
+
+        let candidates = state
+            .tasks
+            .keys()
+            .filter(|task| matches_task(task, query))
+            .cloned()
+            .collect::<Vec<_>>();
+        match candidates.as_slice() {
+            [task] => {
+                self.notify(&format!("stdin attached to {task}"));
+                state.attached = Some(task.clone());
+                state.warned_detached = false;
+            }
+            [] => self.notify(&format!("no running task matches {query}")),
+            _ => self.notify(&format!(
+                "{query} matches multiple tasks: {}",
+                candidates.join(", ")
+            )),
+        }
+    }
+
+    fn notify(&self, message: &str) {
+        turborepo_ui::json::eprint_line(color!(self.ui, GREY, "• {}", message));
+    }
+}
+
+/// Whether `query` refers to `task`, either by its id, `package:task` or just
+/// the package name.
+fn matches_task(task: &str, query: &str) -> bool {
+    let package = task.split_once('#').map(|(package, _)| package);
+    task == query || task.replacen('#', ":", 1) == query || package == Some(query)
+}
+
+/// Sends every line of `reader` until it is closed, or nothing is listening
+/// anymore.
+fn read_lines(mut reader: impl BufRead, lines: mpsc::UnboundedSender<Vec<u8>>) {
+    loop {
+        let mut line = Vec::new();
+        match reader.read_until(b'\n', &mut line) {
+            Ok(0) => break,
+            Ok(_) => {
+                if lines.send(line).is_err() {
+                    break;
+                }
+            }
+            Err(e) => {
+                debug!("unable to read stdin: {e}");
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use test_case::test_case;
+    use tokio::sync::mpsc;
+
+    use super::{matches_task, read_lines};
+
+    #[test_case("web#dev", "web#dev", true ; "task id")]
+    #[test_case("web#dev", "web:dev", true ; "prefix")]
+    #[test_case("web#dev", "web", true ; "package")]
+    #[test_case("web#dev", "dev", false ; "task name")]
+    #[test_case("@repo/web#dev", "@repo/web", true ; "scoped package")]
+    #[test_case("dev", "dev", true ; "single package")]
+    #[test_case("web#dev", "docs", false ; "other package")]
+    fn test_matches_task(task: &str, query: &str, expected: bool) {
+        assert_eq!(matches_task(task, query), expected);
+    }
+
+    #[test]
+    fn test_read_lines() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        read_lines(&b"\x1dweb\nhello\nno newline"[..], tx);
+
+        let mut lines = Vec::new();
+        while let Ok(line) = rx.try_recv() {
+            lines.push(line);
+        }
+        assert_eq!(
+            lines,
+            [&b"\x1dweb\n"[..], &b"hello\n"[..], &b"no newline"[..]]
+        );
+    }
+}
@@ -1,11 +1,11 @@
-use std::{io::Write, sync::Arc, time::Duration};
+use std::{future::Future, io::Write, sync::Arc, time::Duration};
 
 use console::StyledObject;
 use tracing::debug;
 use turbopath::{
     AbsoluteSystemPath, AbsoluteSystemPathBuf, AnchoredSystemPath, AnchoredSystemPathBuf,
 };
-use turborepo_cache::{AsyncCache, CacheError, CacheHitMetadata, CacheSource};
+use turborepo_cache::{AsyncCache, CacheError, CacheHitMetadata, CacheSource, ShutdownSummary};
 use turborepo_repository::package_graph::PackageInfo;
 use turborepo_scm::SCM;
 use turborepo_telemetry::events::{task::PackageTaskEventBuilder, TrackedErrors};
@@ -14,7 +14,7 @@ use turborepo_ui::{
 };
 
 use crate::{
-    cli::OutputLogsMode,
+    cli::{OutputLogsMode, OutputLogsOverrides},
     daemon::{DaemonClient, DaemonConnector},
     hash::{FileHashes, TurboHash},
     opts::RunCacheOpts,
@@ -43,7 +43,7 @@ pub enum Error {
 }
 
 pub struct RunCache {
-    task_output_mode: Option<OutputLogsMode>,
+    task_output_mode: Option<OutputLogsOverrides>,
     cache: AsyncCache,
     reads_disabled: bool,
     writes_disabled: bool,
@@ -64,9 +64,9 @@ impl RunCache {
         is_dry_run: bool,
     ) -> Self {
         let task_output_mode = if is_dry_run {
-            Some(OutputLogsMode::None)
+            Some(OutputLogsMode::None.into())
         } else {
-            opts.task_output_mode_override
+            opts.task_output_mode_override.clone()
         };
         RunCache {
             task_output_mode,
@@ -95,10 +95,11 @@ impl RunCache {
         let repo_relative_globs =
             task_definition.repo_relative_hashable_outputs(&task_id, workspace_info.package_path());
 
-        let mut task_output_mode = task_definition.output_mode;
-        if let Some(task_output_mode_override) = self.task_output_mode {
-            task_output_mode = task_output_mode_override;
-        }
+        let task_output_mode = self
+            .task_output_mode
+            .as_ref()
+            .and_then(|overrides| overrides.mode_for(task_id.package(), task_id.task()))
+            .unwrap_or(task_definition.output_mode);
 
         let caching_disabled = !task_definition.cache;
 
@@ -116,9 +117,21 @@ impl RunCache {
         }
     }
 
-    pub async fn shutdown_cache(&self) {
+    /// The number of task outputs that are still being written to the cache
+    pub fn pending_cache_writes(&self) -> usize {
+        self.cache.pending_writes()
+    }
+
+    /// Shuts down the cache, cancelling the writes that don't finish within
+    /// `timeout` or once `cancel` resolves. Returns `None` if the cache was
+    /// already shut down.
+    pub async fn shutdown_cache(
+        &self,
+        timeout: Duration,
+        cancel: impl Future<Output = ()>,
+    ) -> Option<ShutdownSummary> {
         // Ignore errors coming from cache already shutting down
-        self.cache.shutdown().await.ok();
+        self.cache.shutdown_within(timeout, cancel).await.ok()
     }
 }
 
@@ -162,7 +175,8 @@ impl TaskCache {
         writer: W,
     ) -> Result<LogWriter<W>, Error> {
         let mut log_writer = LogWriter::default();
-        let prefixed_writer = PrefixedWriter::new(self.run_cache.ui, prefix, writer);
+        let prefixed_writer =
+            PrefixedWriter::new(self.run_cache.ui, prefix, writer).with_task(&self.task_id);
 
         if self.caching_disabled || self.run_cache.writes_disabled {
             log_writer.with_prefixed_writer(prefixed_writer);
@@ -181,6 +195,11 @@ impl TaskCache {
         Ok(log_writer)
     }
 
+    /// Whether the outputs of the task can be restored from the cache.
+    pub fn is_restorable(&self) -> bool {
+        !self.caching_disabled && !self.run_cache.reads_disabled
+    }
+
     pub async fn exists(&self) -> Result<Option<CacheHitMetadata>, CacheError> {
         self.run_cache.cache.exists(&self.hash).await
     }
@@ -374,6 +393,28 @@ impl TaskCache {
         Ok(())
     }
 
+    /// Filters `written_files` down to the files that aren't part of the
+    /// task's outputs, and so won't be cached.
+    pub fn undeclared_outputs(
+        &self,
+        written_files: Vec<AbsoluteSystemPathBuf>,
+    ) -> Result<Vec<AnchoredSystemPathBuf>, Error> {
+        let outputs = globwalk::globwalk(
+            &self.run_cache.repo_root,
+            &self.repo_relative_globs.validated_inclusions()?,
+            &self.repo_relative_globs.validated_exclusions()?,
+            globwalk::WalkType::Files,
+        )?;
+
+        Ok(written_files
+            .into_iter()
+            .filter(|path| !outputs.contains(path))
+            .map(|path| {
+                AnchoredSystemPathBuf::relative_path_between(&self.run_cache.repo_root, &path)
+            })
+            .collect())
+    }
+
     pub fn expanded_outputs(&self) -> &[AnchoredSystemPathBuf] {
         &self.expanded_outputs
     }
@@ -0,0 +1,97 @@
+//! Debug logs of runs, written to `.turbo/logs` in the repository.
+//!
+//! When `debugLogs` is enabled, every run logs at the debug level to a file
+//! that rotates daily, regardless of the verbosity that is used for the
+//! console. When a run misbehaves, the file already contains the details that
+//! would otherwise require reproducing the run with `-vv`. Only the most recent
+//! files are kept.
+
+use std::io;
+
+use tracing::{debug, warn};
+use turbopath::AbsoluteSystemPath;
+
+use crate::tracing::TurboSubscriber;
+
+const LOG_FILE_PREFIX: &str = "turbo.log";
+
+/// The number of daily log files to keep.
+const MAX_LOG_FILES: usize = 7;
+
+/// Starts logging to `.turbo/logs`. Failing to do so doesn't fail the run.
+pub fn enable(logger: &TurboSubscriber, repo_root: &AbsoluteSystemPath) {
+    let log_dir = repo_root.join_components(&[".turbo", "logs"]);
+    if let Err(e) = log_dir.create_dir_all() {
+        warn!("unable to create {log_dir} for debug logs: {e}");
+        return;
+    }
+    if let Err(e) = remove_old_logs(&log_dir) {
+        debug!("unable to remove old debug logs: {e}");
+    }
+    if let Err(e) =
+        logger.set_debug_logger(tracing_appender::rolling::daily(&log_dir, LOG_FILE_PREFIX))
+    {
+        warn!("unable to enable debug logs: {e}");
+    }
+}
+
+// The daily appender suffixes files with the date, so sorting them by name
+// sorts them from oldest to newest
+fn remove_old_logs(log_dir: &AbsoluteSystemPath) -> io::Result<()> {
+    let mut log_files = Vec::new();
+    for entry in log_dir.as_std_path().read_dir()? {
+        let entry = entry?;
+        if entry
+            .file_name()
+            .to_str()
+            .is_some_and(|name| name.starts_with(LOG_FILE_PREFIX))
+        {
+            log_files.push(entry.path());
+        }
+    }
+    log_files.sort();
+
+    // Today's file is created or reused once logging starts
+    let keep = MAX_LOG_FILES - 1;
+    let remove = log_files.len().saturating_sub(keep);
+    for log_file in &log_files[..remove] {
+        std::fs::remove_file(log_file)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use tempfile::tempdir;
+    use turbopath::AbsoluteSystemPathBuf;
+
+    use super::{remove_old_logs, MAX_LOG_FILES};
+
+    #[test]
+    fn test_remove_old_logs() {
+        let tmp = tempdir().unwrap();
+        let dir = AbsoluteSystemPathBuf::try_from(tmp.path()).unwrap();
+        for day in 1..=9 {
+            dir.join_component(&format!("turbo.log.2024-01-0{day}"))
+                .create_with_contents("")
+                .unwrap();
+        }
+        let unrelated = dir.join_component("notes.txt");
+        unrelated.create_with_contents("").unwrap();
+
+        remove_old_logs(&dir).unwrap();
+
+        let mut remaining = dir
+            .as_std_path()
+            .read_dir()
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        remaining.sort();
+        let mut expected = (10 - MAX_LOG_FILES + 1..=9)
+            .map(|day| format!("turbo.log.2024-01-0{day}"))
+            .collect::<Vec<_>>();
+        expected.push("notes.txt".to_string());
+        assert_eq!(remaining, expected);
+    }
+}
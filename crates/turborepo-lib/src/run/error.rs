@@ -7,46 +7,67 @@ use crate::{
     config, daemon, engine,
     engine::ValidateError,
     opts,
-    run::{global_hash, scope},
+    run::{global_hash, log_sink, remote_exec, scope},
     task_graph, task_hash,
 };
 
 #[derive(Debug, Error, Diagnostic)]
 pub enum Error {
     #[error("invalid persistent task configuration")]
+    #[diagnostic(code(TURBO_RUN_001))]
     EngineValidation(#[related] Vec<ValidateError>),
     #[error(transparent)]
+    #[diagnostic(code(TURBO_RUN_002))]
     Graph(#[from] graph_visualizer::Error),
     #[error(transparent)]
     #[diagnostic(transparent)]
     Builder(#[from] engine::BuilderError),
     #[error(transparent)]
+    #[diagnostic(code(TURBO_RUN_003))]
     Env(#[from] turborepo_env::Error),
     #[error(transparent)]
+    #[diagnostic(code(TURBO_RUN_004))]
     Opts(#[from] opts::Error),
     #[error(transparent)]
+    #[diagnostic(transparent)]
     PackageJson(#[from] turborepo_repository::package_json::Error),
     #[error(transparent)]
+    #[diagnostic(transparent)]
     PackageManager(#[from] turborepo_repository::package_manager::Error),
     #[error(transparent)]
     #[diagnostic(transparent)]
     Config(#[from] config::Error),
     #[error(transparent)]
+    #[diagnostic(transparent)]
     PackageGraphBuilder(#[from] package_graph::builder::Error),
     #[error(transparent)]
+    #[diagnostic(code(TURBO_RUN_008))]
     DaemonConnector(#[from] daemon::DaemonConnectorError),
     #[error(transparent)]
+    #[diagnostic(code(TURBO_RUN_009))]
     Cache(#[from] turborepo_cache::CacheError),
     #[error(transparent)]
+    #[diagnostic(code(TURBO_RUN_010))]
     Path(#[from] turbopath::PathError),
     #[error(transparent)]
+    #[diagnostic(code(TURBO_RUN_011))]
     Scope(#[from] scope::ResolutionError),
     #[error(transparent)]
+    #[diagnostic(code(TURBO_RUN_012))]
     GlobalHash(#[from] global_hash::Error),
     #[error(transparent)]
+    #[diagnostic(code(TURBO_RUN_013))]
     TaskHash(#[from] task_hash::Error),
     #[error(transparent)]
+    #[diagnostic(code(TURBO_RUN_014))]
     Visitor(#[from] task_graph::VisitorError),
+    #[error(transparent)]
+    #[diagnostic(code(TURBO_RUN_015))]
+    LogSink(#[from] log_sink::Error),
+    #[error(transparent)]
+    #[diagnostic(code(TURBO_RUN_016))]
+    RemoteExec(#[from] remote_exec::Error),
     #[error("error registering signal handler: {0}")]
+    #[diagnostic(code(TURBO_RUN_017))]
     SignalHandler(std::io::Error),
 }
@@ -57,6 +57,7 @@ pub fn get_global_hash_inputs<'a, L: ?Sized + Lockfile>(
     root_path: &AbsoluteSystemPath,
     package_manager: &PackageManager,
     lockfile: Option<&L>,
+    ignore_lockfile: bool,
     global_file_dependencies: &'a [String],
     env_at_execution_start: &'a EnvironmentVariableMap,
     global_env: &'a [String],
@@ -64,6 +65,7 @@ pub fn get_global_hash_inputs<'a, L: ?Sized + Lockfile>(
     env_mode: EnvMode,
     framework_inference: bool,
     dot_env: Option<&'a [RelativeUnixPathBuf]>,
+    tool_version_files: &[RelativeUnixPathBuf],
     hasher: &SCM,
 ) -> Result<GlobalHashableInputs<'a>, Error> {
     let global_hashable_env_vars =
@@ -80,7 +82,8 @@ pub fn get_global_hash_inputs<'a, L: ?Sized + Lockfile>(
     if lockfile.is_none() {
         global_deps.insert(root_path.join_component("package.json"));
         let lockfile_path = package_manager.lockfile_path(root_path);
-        if lockfile_path.exists() {
+        // An ignored lockfile shouldn't cause cache misses when it changes
+        if !ignore_lockfile && lockfile_path.exists() {
             global_deps.insert(lockfile_path);
         }
     }
@@ -104,6 +107,16 @@ pub fn get_global_hash_inputs<'a, L: ?Sized + Lockfile>(
         global_file_hash_map.extend(dot_env_object);
     }
 
+    // Missing tool version files are skipped so repositories without them keep
+    // the same global hash.
+    let tool_version_object = hasher.hash_existing_of(
+        root_path,
+        tool_version_files
+            .iter()
+            .map(|p| p.to_anchored_system_path_buf()),
+    )?;
+    global_file_hash_map.extend(tool_version_object);
+
     debug!(
         "external deps hash: {}",
         root_external_dependencies_hash.unwrap_or("no hash (single package)")
@@ -217,7 +230,7 @@ impl<'a> GlobalHashableInputs<'a> {
 
 #[cfg(test)]
 mod tests {
-    use turbopath::AbsoluteSystemPathBuf;
+    use turbopath::{AbsoluteSystemPathBuf, RelativeUnixPathBuf};
     use turborepo_env::EnvironmentVariableMap;
     use turborepo_lockfiles::Lockfile;
     use turborepo_repository::package_manager::PackageManager;
@@ -252,6 +265,7 @@ mod tests {
             &root,
             &PackageManager::Pnpm,
             lockfile,
+            false,
             &file_deps,
             &env_var_map,
             &[],
@@ -259,11 +273,101 @@ mod tests {
             EnvMode::Infer,
             false,
             None,
+            &[],
             &SCM::new(&root),
         );
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_tool_version_files() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let root = AbsoluteSystemPathBuf::try_from(tempdir.path())
+            .unwrap()
+            .to_realpath()
+            .unwrap();
+        root.join_component("package.json")
+            .create_with_contents("{}")
+            .unwrap();
+        root.join_component(".nvmrc")
+            .create_with_contents("20")
+            .unwrap();
+
+        let env_var_map = EnvironmentVariableMap::default();
+        let lockfile: Option<&dyn Lockfile> = None;
+        let tool_version_files = [
+            RelativeUnixPathBuf::new(".nvmrc").unwrap(),
+            RelativeUnixPathBuf::new(".node-version").unwrap(),
+        ];
+        let inputs = get_global_hash_inputs(
+            None,
+            &root,
+            &PackageManager::Npm,
+            lockfile,
+            false,
+            &[],
+            &env_var_map,
+            &[],
+            None,
+            EnvMode::Infer,
+            false,
+            None,
+            &tool_version_files,
+            &SCM::new(&root),
+        )
+        .unwrap();
+
+        // Only the tool version files that exist are hashed
+        assert!(inputs
+            .global_file_hash_map
+            .contains_key(&RelativeUnixPathBuf::new(".nvmrc").unwrap()));
+        assert!(!inputs
+            .global_file_hash_map
+            .contains_key(&RelativeUnixPathBuf::new(".node-version").unwrap()));
+    }
+
+    #[test]
+    fn test_ignored_lockfile() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let root = AbsoluteSystemPathBuf::try_from(tempdir.path())
+            .unwrap()
+            .to_realpath()
+            .unwrap();
+        root.join_component("package.json")
+            .create_with_contents("{}")
+            .unwrap();
+        root.join_component("package-lock.json")
+            .create_with_contents("{}")
+            .unwrap();
+
+        let env_var_map = EnvironmentVariableMap::default();
+        let lockfile: Option<&dyn Lockfile> = None;
+        let inputs = get_global_hash_inputs(
+            None,
+            &root,
+            &PackageManager::Npm,
+            lockfile,
+            true,
+            &[],
+            &env_var_map,
+            &[],
+            None,
+            EnvMode::Infer,
+            false,
+            None,
+            &[],
+            &SCM::new(&root),
+        )
+        .unwrap();
+
+        assert!(inputs
+            .global_file_hash_map
+            .contains_key(&RelativeUnixPathBuf::new("package.json").unwrap()));
+        assert!(!inputs
+            .global_file_hash_map
+            .contains_key(&RelativeUnixPathBuf::new("package-lock.json").unwrap()));
+    }
+
     /// get_global_hash_inputs should not yield any folders when walking since
     /// turbo does not consider changes to folders when evaluating hashes,
     /// only to files
@@ -6,7 +6,7 @@ use std::{
 
 use thiserror::Error;
 use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
-use turborepo_ui::{cprintln, cwrite, cwriteln, BOLD, BOLD_YELLOW_REVERSE, UI, YELLOW};
+use turborepo_ui::{color, cwrite, cwriteln, BOLD, BOLD_YELLOW_REVERSE, UI, YELLOW};
 use which::which;
 
 use crate::{engine::Engine, opts::GraphOpts, spawn_child};
@@ -52,8 +52,10 @@ pub(crate) fn write_graph(
                 write_graphviz_warning(ui).map_err(Error::GraphOutput)?;
                 render_dot_graph(std::io::stdout(), engine, single_package)?;
             }
-            print!("\n✔ Generated task graph in ");
-            cprintln!(ui, BOLD, "{filename}");
+            turborepo_ui::json::print_line(format!(
+                "\n✔ Generated task graph in {}",
+                color!(ui, BOLD, "{filename}")
+            ));
         }
     }
     Ok(())
@@ -0,0 +1,422 @@
+//! Log sinks receive the raw output of every task that runs, independent of
+//! how the output is displayed on the console. They make it possible to
+//! archive the logs of each task even when the console output is grouped or
+//! hidden.
+
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufWriter, Write},
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use serde::Serialize;
+use thiserror::Error;
+use tokio::task::JoinHandle;
+use tracing::warn;
+use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
+use turborepo_api_client::TlsConfig;
+use url::Url;
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(
+        "invalid log sink \"{0}\": expected \"file:<directory>\", \"unix:<socket path>\" or an \
+         http(s) URL"
+    )]
+    InvalidConfig(String),
+    #[error("unable to open log sink \"{sink}\": {source}")]
+    Open { sink: String, source: io::Error },
+    #[error("unable to create the client for log sink \"{sink}\"")]
+    Client {
+        sink: String,
+        #[source]
+        source: turborepo_api_client::Error,
+    },
+}
+
+/// Where the output of tasks should be sent to, as passed to `--log-sink`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LogSinkConfig {
+    /// A log file per task in a directory
+    Files(String),
+    /// JSON lines streamed to a unix socket
+    Socket(String),
+    /// The log of every task uploaded once the task finished
+    Http(Url),
+}
+
+impl FromStr for LogSinkConfig {
+    type Err = Error;
+
+    fn from_str(config: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::InvalidConfig(config.to_string());
+        if let Some(dir) = config.strip_prefix("file:") {
+            return (!dir.is_empty())
+                .then(|| LogSinkConfig::Files(dir.to_string()))
+                .ok_or_else(invalid);
+        }
+        if let Some(path) = config.strip_prefix("unix:") {
+            return (!path.is_empty())
+                .then(|| LogSinkConfig::Socket(path.to_string()))
+                .ok_or_else(invalid);
+        }
+        match Url::parse(config) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => Ok(LogSinkConfig::Http(url)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// A destination for the output of tasks.
+///
+/// Sinks are shared by all tasks of a run, so they receive the output of
+/// tasks that run in parallel interleaved and need to tell the tasks apart by
+/// their id.
+pub trait LogSink: Send + Sync {
+    /// Receives the next chunk of the output of `task`.
+    fn write(&self, task: &str, bytes: &[u8]) -> io::Result<()>;
+
+    /// Called once `task` finished. Tasks that were restored from the cache
+    /// finish without writing any output.
+    fn finish_task(&self, _task: &str, _success: bool) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The log sinks of a run. Cloning is cheap, clones share the sinks.
+#[derive(Clone, Default)]
+pub struct LogSinks {
+    sinks: Arc<Vec<Box<dyn LogSink>>>,
+    uploads: Arc<Mutex<Vec<JoinHandle<()>>>>,
+}
+
+impl LogSinks {
+    pub fn new(
+        configs: &[LogSinkConfig],
+        repo_root: &AbsoluteSystemPath,
+        tls_config: &TlsConfig,
+    ) -> Result<Self, Error> {
+        let uploads = Arc::new(Mutex::new(Vec::new()));
+        let mut sinks: Vec<Box<dyn LogSink>> = Vec::with_capacity(configs.len());
+        for config in configs {
+            match config {
+                LogSinkConfig::Files(dir) => {
+                    let dir = AbsoluteSystemPathBuf::from_unknown(repo_root, dir.as_str());
+                    sinks.push(Box::new(FileLogSink::new(dir)?));
+                }
+                LogSinkConfig::Socket(path) => {
+                    let path = AbsoluteSystemPathBuf::from_unknown(repo_root, path.as_str());
+                    sinks.push(Box::new(SocketLogSink::connect(&path)?));
+                }
+                LogSinkConfig::Http(url) => {
+                    let sink = HttpLogSink::new(url.clone(), tls_config, uploads.clone()).map_err(
+                        |source| Error::Client {
+                            sink: url.to_string(),
+                            source,
+                        },
+                    )?;
+                    sinks.push(Box::new(sink));
+                }
+            }
+        }
+        Ok(Self {
+            sinks: Arc::new(sinks),
+            uploads,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sinks.is_empty()
+    }
+
+    /// A writer that sends everything written to it to all sinks as output of
+    /// `task`.
+    pub fn writer(&self, task: String) -> LogSinkWriter {
+        LogSinkWriter {
+            sinks: self.clone(),
+            task,
+            warned: false,
+        }
+    }
+
+    pub fn finish_task(&self, task: &str, success: bool) {
+        for sink in self.sinks.iter() {
+            if let Err(e) = sink.finish_task(task, success) {
+                warn!("unable to finish the log of {task}: {e}");
+            }
+        }
+    }
+
+    /// Waits for the logs that are still being uploaded.
+    pub async fn close(&self) {
+        let uploads = std::mem::take(&mut *self.uploads.lock().expect("lock poisoned"));
+        for upload in uploads {
+            upload.await.ok();
+        }
+    }
+}
+
+/// Writes the output of a single task to all sinks. Failing sinks don't fail
+/// the task.
+pub struct LogSinkWriter {
+    sinks: LogSinks,
+    task: String,
+    warned: bool,
+}
+
+impl Write for LogSinkWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for sink in self.sinks.sinks.iter() {
+            if let Err(e) = sink.write(&self.task, buf) {
+                if !self.warned {
+                    warn!(
+                        "unable to write the log of {} to a log sink: {e}",
+                        self.task
+                    );
+                    self.warned = true;
+                }
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Writes the output of every task to `<dir>/<task id>.log`.
+struct FileLogSink {
+    dir: AbsoluteSystemPathBuf,
+    files: Mutex<HashMap<String, BufWriter<File>>>,
+}
+
+impl FileLogSink {
+    fn new(dir: AbsoluteSystemPathBuf) -> Result<Self, Error> {
+        dir.create_dir_all().map_err(|source| Error::Open {
+            sink: dir.to_string(),
+            source,
+        })?;
+        Ok(Self {
+            dir,
+            files: Mutex::default(),
+        })
+    }
+
+    fn file_name(task: &str) -> String {
+        // Package names can contain slashes, e.g. @scope/package#build
+        format!("{}.log", task.replace(['/', '\\', ':'], "_"))
+    }
+}
+
+impl LogSink for FileLogSink {
+    fn write(&self, task: &str, bytes: &[u8]) -> io::Result<()> {
+        let mut files = self.files.lock().expect("lock poisoned");
+        let file = match files.get_mut(task) {
+            Some(file) => file,
+            None => {
+                let file = self.dir.join_component(&Self::file_name(task)).create()?;
+                files
+                    .entry(task.to_string())
+                    .or_insert(BufWriter::new(file))
+            }
+        };
+        file.write_all(bytes)
+    }
+
+    fn finish_task(&self, task: &str, _success: bool) -> io::Result<()> {
+        let file = self.files.lock().expect("lock poisoned").remove(task);
+        match file {
+            Some(mut file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+enum SocketMessage<'a> {
+    Output { task: &'a str, output: &'a str },
+    Finished { task: &'a str, success: bool },
+}
+
+/// Streams the output of every task to a unix socket as JSON lines.
+struct SocketLogSink {
+    #[cfg(unix)]
+    stream: Mutex<std::os::unix::net::UnixStream>,
+}
+
+impl SocketLogSink {
+    #[cfg(unix)]
+    fn connect(path: &AbsoluteSystemPath) -> Result<Self, Error> {
+        let stream =
+            std::os::unix::net::UnixStream::connect(path).map_err(|source| Error::Open {
+                sink: path.to_string(),
+                source,
+            })?;
+        Ok(Self {
+            stream: Mutex::new(stream),
+        })
+    }
+
+    #[cfg(not(unix))]
+    fn connect(path: &AbsoluteSystemPath) -> Result<Self, Error> {
+        Err(Error::Open {
+            sink: path.to_string(),
+            source: io::Error::new(
+                io::ErrorKind::Unsupported,
+                "unix sockets are not supported on this platform",
+            ),
+        })
+    }
+
+    fn send(&self, message: &SocketMessage) -> io::Result<()> {
+        let mut line = serde_json::to_vec(message)?;
+        line.push(b'\n');
+        #[cfg(unix)]
+        self.stream
+            .lock()
+            .expect("lock poisoned")
+            .write_all(&line)?;
+        Ok(())
+    }
+}
+
+impl LogSink for SocketLogSink {
+    fn write(&self, task: &str, bytes: &[u8]) -> io::Result<()> {
+        self.send(&SocketMessage::Output {
+            task,
+            output: &String::from_utf8_lossy(bytes),
+        })
+    }
+
+    fn finish_task(&self, task: &str, success: bool) -> io::Result<()> {
+        self.send(&SocketMessage::Finished { task, success })
+    }
+}
+
+/// Collects the output of every task and uploads it with a `POST` request
+/// once the task finished.
+/// Uploads that don't finish in time are dropped, so an unresponsive endpoint
+/// can't hold up the end of the run.
+const UPLOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+struct HttpLogSink {
+    url: Url,
+    client: reqwest::Client,
+    logs: Mutex<HashMap<String, Vec<u8>>>,
+    uploads: Arc<Mutex<Vec<JoinHandle<()>>>>,
+}
+
+impl HttpLogSink {
+    fn new(
+        url: Url,
+        tls_config: &TlsConfig,
+        uploads: Arc<Mutex<Vec<JoinHandle<()>>>>,
+    ) -> Result<Self, turborepo_api_client::Error> {
+        let client = tls_config
+            .client_builder()?
+            .build()
+            .map_err(turborepo_api_client::Error::TlsError)?;
+        Ok(Self {
+            url,
+            client,
+            logs: Mutex::default(),
+            uploads,
+        })
+    }
+}
+
+impl LogSink for HttpLogSink {
+    fn write(&self, task: &str, bytes: &[u8]) -> io::Result<()> {
+        self.logs
+            .lock()
+            .expect("lock poisoned")
+            .entry(task.to_string())
+            .or_default()
+            .extend_from_slice(bytes);
+        Ok(())
+    }
+
+    fn finish_task(&self, task: &str, success: bool) -> io::Result<()> {
+        let Some(log) = self.logs.lock().expect("lock poisoned").remove(task) else {
+            return Ok(());
+        };
+        let request = self
+            .client
+            .post(self.url.clone())
+            .header("x-turbo-task", task)
+            .header("x-turbo-task-success", success.to_string())
+            .header(reqwest::header::CONTENT_TYPE, "text/plain")
+            .timeout(UPLOAD_TIMEOUT)
+            .body(log);
+        let task = task.to_string();
+        let upload = tokio::spawn(async move {
+            match request.send().await.and_then(|r| r.error_for_status()) {
+                Ok(_) => (),
+                Err(e) => warn!("unable to upload the log of {task}: {e}"),
+            }
+        });
+        self.uploads.lock().expect("lock poisoned").push(upload);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Write;
+
+    use tempfile::tempdir;
+    use test_case::test_case;
+    use turbopath::AbsoluteSystemPathBuf;
+    use turborepo_api_client::TlsConfig;
+
+    use super::{LogSinkConfig, LogSinks};
+
+    #[test_case("file:logs", Some(LogSinkConfig::Files("logs".into())) ; "files")]
+    #[test_case("unix:/tmp/turbo.sock", Some(LogSinkConfig::Socket("/tmp/turbo.sock".into())) ; "socket")]
+    #[test_case("https://example.com/logs", Some(LogSinkConfig::Http("https://example.com/logs".parse().unwrap())) ; "http")]
+    #[test_case("file:", None ; "empty directory")]
+    #[test_case("ftp://example.com", None ; "unsupported scheme")]
+    #[test_case("logs", None ; "missing kind")]
+    fn test_parse_config(config: &str, expected: Option<LogSinkConfig>) {
+        assert_eq!(config.parse::<LogSinkConfig>().ok(), expected);
+    }
+
+    #[test]
+    fn test_file_sink() {
+        let dir = tempdir().unwrap();
+        let repo_root = AbsoluteSystemPathBuf::try_from(dir.path()).unwrap();
+        let sinks = LogSinks::new(
+            &[LogSinkConfig::Files("logs".into())],
+            &repo_root,
+            &TlsConfig::default(),
+        )
+        .unwrap();
+
+        let mut web = sinks.writer("web#build".into());
+        let mut ui = sinks.writer("@repo/ui#build".into());
+        writeln!(web, "building web").unwrap();
+        writeln!(ui, "building ui").unwrap();
+        writeln!(web, "done").unwrap();
+        sinks.finish_task("web#build", true);
+        sinks.finish_task("@repo/ui#build", false);
+
+        let logs = repo_root.join_component("logs");
+        assert_eq!(
+            logs.join_component("web#build.log")
+                .read_to_string()
+                .unwrap(),
+            "building web\ndone\n"
+        );
+        assert_eq!(
+            logs.join_component("@repo_ui#build.log")
+                .read_to_string()
+                .unwrap(),
+            "building ui\n"
+        );
+    }
+}
@@ -1,29 +1,36 @@
 #![allow(dead_code)]
 
+pub mod attach;
 mod cache;
+pub mod debug_log;
 mod error;
 pub(crate) mod global_hash;
 mod graph_visualizer;
+pub mod log_sink;
 pub(crate) mod package_discovery;
-mod scope;
+pub mod remote_exec;
+pub mod resume;
+pub(crate) mod scope;
 pub(crate) mod summary;
 pub mod task_access;
 pub mod task_id;
+pub mod undeclared_outputs;
 
 use std::{
     collections::HashSet,
-    io::{ErrorKind, IsTerminal, Write},
+    io::{ErrorKind, IsTerminal},
     sync::Arc,
     time::SystemTime,
 };
 
 pub use cache::{ConfigCache, RunCache, TaskCache};
 use chrono::{DateTime, Local};
+use dialoguer::Confirm;
 use rayon::iter::ParallelBridge;
-use tracing::debug;
-use turbopath::{AbsoluteSystemPathBuf, AnchoredSystemPath};
+use tracing::{debug, warn};
+use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf, AnchoredSystemPath};
 use turborepo_analytics::{start_analytics, AnalyticsHandle, AnalyticsSender};
-use turborepo_api_client::{APIAuth, APIClient};
+use turborepo_api_client::{APIAuth, APIClient, TlsConfig};
 use turborepo_cache::{AsyncCache, RemoteCacheOpts};
 use turborepo_ci::Vendor;
 use turborepo_env::EnvironmentVariableMap;
@@ -39,7 +46,11 @@ use turborepo_telemetry::events::{
     repo::{RepoEventBuilder, RepoType},
     EventBuilder, TrackedErrors,
 };
-use turborepo_ui::{cprint, cprintln, ColorSelector, BOLD_GREY, GREY, UI};
+use turborepo_ui::{
+    color, cprintln,
+    json::{eprint_line, print_line},
+    ColorSelector, BOLD_GREY, BOLD_RED, GREY, UI,
+};
 #[cfg(feature = "daemon-package-discovery")]
 use {
     crate::run::package_discovery::DaemonPackageDiscovery,
@@ -50,7 +61,13 @@ use {
     },
 };
 
-use self::task_id::TaskName;
+use self::{
+    attach::StdinRouter,
+    log_sink::{LogSinkConfig, LogSinks},
+    remote_exec::RemoteExecutor,
+    resume::ResumeState,
+    task_id::TaskName,
+};
 pub use crate::run::error::Error;
 use crate::{
     cli::{DryRunMode, EnvMode},
@@ -58,12 +75,12 @@ use crate::{
     daemon::DaemonConnector,
     engine::{Engine, EngineBuilder},
     opts::Opts,
-    process::ProcessManager,
+    process::{Orphans, ProcessManager},
     run::{global_hash::get_global_hash_inputs, summary::RunTracker, task_access::TaskAccess},
     shim::TurboState,
     signal::{SignalHandler, SignalSubscriber},
     task_graph::Visitor,
-    task_hash::{get_external_deps_hash, PackageInputsHashes},
+    task_hash::{get_external_deps_hash, load_dot_env, PackageInputsHashes},
     turbo_json::TurboJson,
 };
 
@@ -71,6 +88,7 @@ pub struct Run {
     processes: ProcessManager,
     opts: Opts,
     api_auth: Option<APIAuth>,
+    tls_config: TlsConfig,
     repo_root: AbsoluteSystemPathBuf,
     ui: UI,
     version: &'static str,
@@ -94,33 +112,88 @@ impl Run {
         // configured team_id matches the final resolved team_id.
         let unused_remote_cache_opts_team_id = config.team_id().map(|team_id| team_id.to_string());
         let signature = config.signature();
+        opts.cache_opts.fallback_on_rate_limit = config.fallback_on_rate_limit();
+        opts.cache_opts.audit_log = config
+            .audit_log()
+            .map(|path| AbsoluteSystemPathBuf::from_unknown(&base.repo_root, path));
         opts.cache_opts.remote_cache_opts = Some(RemoteCacheOpts::new(
             unused_remote_cache_opts_team_id,
             signature,
         ));
-        if opts.run_opts.experimental_space_id.is_none() {
+        if base.args().offline {
+            opts.run_opts.experimental_space_id = None;
+            let log_sinks = opts.run_opts.log_sinks.len();
+            opts.run_opts
+                .log_sinks
+                .retain(|sink| !matches!(sink, LogSinkConfig::Http(_)));
+            if opts.run_opts.log_sinks.len() < log_sinks {
+                warn!("task logs aren't uploaded to http log sinks in offline mode");
+            }
+        } else if opts.run_opts.experimental_space_id.is_none() {
             opts.run_opts.experimental_space_id = config.spaces_id().map(|s| s.to_owned());
         }
+        // Metrics leave the machine, so they aren't sent in offline mode
+        if !base.args().offline {
+            opts.run_opts.statsd_address = config.statsd_address().map(|s| s.to_owned());
+        }
+        if !opts.run_opts.experimental_remote_workers.is_empty() && opts.cache_opts.skip_remote {
+            return Err(remote_exec::Error::NoRemoteCache.into());
+        }
+        let tls_config = base.tls_config()?;
         let version = base.version();
         let CommandBase { repo_root, ui, .. } = base;
         Ok(Self {
             processes,
             opts,
             api_auth,
+            tls_config,
             repo_root,
             ui,
             version,
         })
     }
 
-    fn connect_process_manager(&self, signal_subscriber: SignalSubscriber) {
+    fn connect_process_manager(
+        &self,
+        signal_handler: &SignalHandler,
+        signal_subscriber: SignalSubscriber,
+    ) {
         let manager = self.processes.clone();
+        let signal_handler = signal_handler.clone();
+        let ui = self.ui;
         tokio::spawn(async move {
             let _guard = signal_subscriber.listen().await;
-            manager.stop().await;
+            tokio::select! {
+                _ = manager.stop() => {}
+                _ = signal_handler.force_shutdown() => {
+                    cprintln!(ui, BOLD_RED, "Forcing shutdown, killing all tasks immediately");
+                    manager.kill().await;
+                }
+            }
         });
     }
 
+    // Keeps the pseudo-terminals of tasks the same size as the terminal turbo is
+    // running in
+    fn forward_terminal_resizes(&self) {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+
+            let Ok(mut resizes) = signal(SignalKind::window_change()) else {
+                return;
+            };
+            let manager = self.processes.clone();
+            tokio::spawn(async move {
+                while resizes.recv().await.is_some() {
+                    if let Some((rows, cols)) = console::Term::stdout().size_checked() {
+                        manager.resize(rows, cols);
+                    }
+                }
+            });
+        }
+    }
+
     fn initialize_analytics(
         api_auth: Option<APIAuth>,
         api_client: APIClient,
@@ -135,8 +208,11 @@ impl Run {
     fn print_run_prelude(&self, filtered_pkgs: &HashSet<PackageName>) {
         let targets_list = self.opts.run_opts.tasks.join(", ");
         if self.opts.run_opts.single_package {
-            cprint!(self.ui, GREY, "{}", "• Running");
-            cprint!(self.ui, BOLD_GREY, " {}\n", targets_list);
+            print_line(format!(
+                "{} {}",
+                color!(self.ui, GREY, "• Running"),
+                color!(self.ui, BOLD_GREY, "{}", targets_list)
+            ));
         } else {
             let mut packages = filtered_pkgs
                 .iter()
@@ -149,9 +225,12 @@ impl Run {
                 "• Packages in scope: {}",
                 packages.join(", ")
             );
-            cprint!(self.ui, GREY, "{} ", "• Running");
-            cprint!(self.ui, BOLD_GREY, "{}", targets_list);
-            cprint!(self.ui, GREY, " in {} packages\n", filtered_pkgs.len());
+            print_line(format!(
+                "{} {}{}",
+                color!(self.ui, GREY, "• Running"),
+                color!(self.ui, BOLD_GREY, "{}", targets_list),
+                color!(self.ui, GREY, " in {} packages", filtered_pkgs.len())
+            ));
         }
 
         let use_http_cache = !self.opts.cache_opts.skip_remote;
@@ -179,7 +258,14 @@ impl Run {
         );
         let start_at = Local::now();
         if let Some(subscriber) = signal_handler.subscribe() {
-            self.connect_process_manager(subscriber);
+            self.connect_process_manager(signal_handler, subscriber);
+        }
+        self.forward_terminal_resizes();
+
+        let pids_dir = self.repo_root.join_components(&[".turbo", "processes"]);
+        if self.opts.run_opts.dry_run.is_none() {
+            self.clean_up_orphans(&pids_dir).await;
+            self.processes.record_pids(&pids_dir);
         }
 
         let (analytics_sender, analytics_handle) =
@@ -198,10 +284,52 @@ impl Run {
         if let Some(analytics_handle) = analytics_handle {
             analytics_handle.close_with_timeout().await;
         }
+        self.processes.remove_pid_file();
 
         result
     }
 
+    // Offers to stop the processes that crashed runs left behind, as they would
+    // keep ports bound that the tasks of this run need
+    async fn clean_up_orphans(&self, pids_dir: &AbsoluteSystemPath) {
+        let orphans = Orphans::find(pids_dir);
+        if orphans.is_empty() {
+            return;
+        }
+        let processes = orphans
+            .processes()
+            .iter()
+            .map(|orphan| format!("  {} {}", orphan.pid, orphan.label))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let is_interactive = !turborepo_ci::is_ci()
+            && std::io::stdin().is_terminal()
+            && std::io::stdout().is_terminal();
+        if !is_interactive {
+            warn!(
+                "found processes left behind by a previous run that crashed, run turbo \
+                 interactively to stop them:\n{processes}"
+            );
+            return;
+        }
+
+        cprintln!(
+            self.ui,
+            GREY,
+            "• Found processes left behind by a previous run that crashed:\n{}",
+            processes
+        );
+        let stop = Confirm::new()
+            .with_prompt("Stop them?")
+            .default(true)
+            .interact()
+            .unwrap_or(false);
+        if stop {
+            orphans.stop().await;
+        }
+    }
+
     // We split this into a separate function because we need
     // to close the AnalyticsHandle regardless of whether the run succeeds or not
     async fn run_with_analytics(
@@ -284,7 +412,8 @@ impl Run {
 
         let mut pkg_dep_graph = {
             let builder = PackageGraph::builder(&self.repo_root, root_package_json.clone())
-                .with_single_package_mode(self.opts.run_opts.single_package);
+                .with_single_package_mode(self.opts.run_opts.single_package)
+                .with_ignore_lockfile(self.opts.run_opts.ignore_lockfile);
 
             #[cfg(feature = "daemon-package-discovery")]
             let graph = match (&daemon, self.opts.run_opts.daemon) {
@@ -424,6 +553,7 @@ impl Run {
             &self.repo_root,
             pkg_dep_graph.package_manager(),
             pkg_dep_graph.lockfile(),
+            self.opts.run_opts.ignore_lockfile,
             &root_turbo_json.global_deps,
             &env_at_execution_start,
             &root_turbo_json.global_env,
@@ -431,6 +561,7 @@ impl Run {
             self.opts.run_opts.env_mode,
             self.opts.run_opts.framework_inference,
             root_turbo_json.global_dot_env.as_deref(),
+            &root_turbo_json.tool_version_files(),
             &scm,
         )?;
 
@@ -445,17 +576,39 @@ impl Run {
             &self.repo_root,
             &self.opts.runcache_opts,
             color_selector,
-            daemon,
+            daemon.clone(),
             self.ui,
             self.opts.run_opts.dry_run.is_some(),
         ));
         if let Some(subscriber) = signal_handler.subscribe() {
             let runcache = runcache.clone();
+            let signal_handler = signal_handler.clone();
+            let timeout = self.opts.run_opts.cache_shutdown_timeout;
             tokio::spawn(async move {
                 let _guard = subscriber.listen().await;
                 let spinner = turborepo_ui::start_spinner("...Finishing writing to cache...");
-                runcache.shutdown_cache().await;
+                // A forced shutdown doesn't wait for any cache writes
+                let summary = runcache
+                    .shutdown_cache(timeout, signal_handler.force_shutdown())
+                    .await;
                 spinner.finish_and_clear();
+                if let Some(summary) = summary.filter(|summary| !summary.all_persisted()) {
+                    let total =
+                        summary.persisted.len() + summary.failed.len() + summary.cancelled.len();
+                    warn!(
+                        "persisted {} of {total} pending cache writes before shutting down",
+                        summary.persisted.len()
+                    );
+                    if !summary.cancelled.is_empty() {
+                        warn!(
+                            "cancelled cache writes that didn't finish in time: {}",
+                            summary.cancelled.join(", ")
+                        );
+                    }
+                    if !summary.failed.is_empty() {
+                        warn!("failed cache writes: {}", summary.failed.join(", "));
+                    }
+                }
             });
         }
 
@@ -471,6 +624,7 @@ impl Run {
             &scm,
             engine.tasks().par_bridge(),
             workspaces,
+            pkg_dep_graph.lockfile(),
             engine.task_definitions(),
             &self.repo_root,
             &run_telemetry,
@@ -507,6 +661,14 @@ impl Run {
             env
         };
 
+        let global_dot_env = load_dot_env(
+            &self.repo_root,
+            root_turbo_json
+                .global_dot_env
+                .as_deref()
+                .unwrap_or_default(),
+        )?;
+
         let run_tracker = RunTracker::new(
             start_at,
             self.opts.synthesize_command(),
@@ -519,8 +681,38 @@ impl Run {
             self.api_auth.clone(),
             Vendor::get_user(),
             &scm,
+            daemon,
         );
 
+        // Sinks aren't opened for dry runs, as no tasks are executed
+        let log_sinks = match self.opts.run_opts.dry_run {
+            Some(_) => LogSinks::default(),
+            None => LogSinks::new(
+                &self.opts.run_opts.log_sinks,
+                &self.repo_root,
+                &self.tls_config,
+            )?,
+        };
+
+        let stdin_router = match (&self.opts.run_opts.attach, &self.opts.run_opts.dry_run) {
+            (Some(task), None) => {
+                let router = StdinRouter::new(Some(task.clone()), self.ui);
+                router.forward_stdin();
+                Some(router)
+            }
+            _ => None,
+        };
+
+        let remote_executor = match self.opts.run_opts.dry_run {
+            Some(_) => None,
+            None => RemoteExecutor::new(
+                self.opts.run_opts.experimental_remote_workers.clone(),
+                &self.tls_config,
+            )?,
+        };
+
+        let resume = ResumeState::load(&self.repo_root, self.opts.run_opts.resume);
+
         let mut visitor = Visitor::new(
             pkg_dep_graph.clone(),
             runcache,
@@ -535,6 +727,11 @@ impl Run {
             self.processes.clone(),
             &self.repo_root,
             global_env,
+            global_dot_env,
+            log_sinks.clone(),
+            stdin_router,
+            remote_executor,
+            resume.clone(),
         );
 
         if self.opts.run_opts.dry_run.is_some() {
@@ -546,13 +743,25 @@ impl Run {
         debug!("running visitor");
 
         let errors = visitor.visit(engine.clone(), &run_telemetry).await?;
-
+        log_sinks.close().await;
+
+        // The summary is still written when the run was interrupted, with the tasks
+        // that didn't get to finish marked as interrupted
+        let interrupted_at = signal_handler.interrupted_at();
+        // Dry runs don't complete any tasks, so there's nothing to record for them
+        if self.opts.run_opts.dry_run.is_none() {
+            resume.save(errors.is_empty() && interrupted_at.is_none());
+        }
         let exit_code = errors
             .iter()
             .filter_map(|err| err.exit_code())
             .max()
             // We hit some error, it shouldn't be exit code 0
-            .unwrap_or(if errors.is_empty() { 0 } else { 1 });
+            .unwrap_or(if errors.is_empty() && interrupted_at.is_none() {
+                0
+            } else {
+                1
+            });
 
         let error_prefix = if self.opts.run_opts.is_github_actions {
             "::error::"
@@ -560,12 +769,13 @@ impl Run {
             ""
         };
         for err in &errors {
-            writeln!(std::io::stderr(), "{error_prefix}{err}").ok();
+            eprint_line(format!("{error_prefix}{err}"));
         }
 
         visitor
             .finish(
                 exit_code,
+                interrupted_at,
                 filtered_pkgs,
                 global_hash_inputs,
                 &engine,
@@ -0,0 +1,368 @@
+//! Experimental remote execution of tasks.
+//!
+//! Instead of spawning a task locally, turbo can dispatch it to a remote
+//! worker agent. Workers are expected to have the repository checked out at
+//! the same commit and access to the same remote cache. A task is sent to a
+//! worker as a `POST` request to `<worker>/v0/tasks` with a [`RemoteTask`] as
+//! its JSON body, which includes the environment variables the task hash
+//! depends on. Workers are therefore reached over `https`, plain `http` is
+//! only accepted for workers on the loopback interface. The worker:
+//!
+//! 1. verifies that its checkout matches the task's inputs and restores the
+//!    outputs of the task's dependencies from the remote cache,
+//! 2. runs the command,
+//! 3. uploads the outputs and logs of a successful task to the remote cache
+//!    under the task's hash,
+//!
+//! and responds with a [`RemoteTaskResult`] once the task finished. The
+//! outputs then come back through the cache like any other cache hit.
+
+use std::{
+    collections::BTreeMap,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::debug;
+use turbopath::RelativeUnixPathBuf;
+use turborepo_api_client::TlsConfig;
+use url::{Host, Url};
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("invalid remote worker \"{0}\": expected an http(s) URL")]
+    InvalidWorker(String),
+    #[error(
+        "remote worker \"{0}\" must use https, tasks are sent to it along with their environment \
+         variables. http is only allowed for workers on this machine."
+    )]
+    InsecureWorker(String),
+    #[error(
+        "remote execution requires remote caching to be enabled, as the outputs of remotely \
+         executed tasks are returned through the remote cache"
+    )]
+    NoRemoteCache,
+    #[error("unable to create the client for remote workers")]
+    Client(#[source] turborepo_api_client::Error),
+    #[error("unable to dispatch {task} to a remote worker: {source}")]
+    Dispatch {
+        task: String,
+        #[source]
+        source: reqwest::Error,
+    },
+    #[error("remote worker {worker} rejected {task}: {message}")]
+    Rejected {
+        worker: Url,
+        task: String,
+        message: String,
+    },
+}
+
+/// A remote worker agent, as passed to `--experimental-remote-worker`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RemoteWorker(Url);
+
+impl FromStr for RemoteWorker {
+    type Err = Error;
+
+    fn from_str(worker: &str) -> Result<Self, Self::Err> {
+        match Url::parse(worker) {
+            Ok(url) if url.scheme() == "https" => Ok(RemoteWorker(url)),
+            Ok(url) if url.scheme() == "http" && is_loopback(&url) => Ok(RemoteWorker(url)),
+            Ok(url) if url.scheme() == "http" => Err(Error::InsecureWorker(worker.to_string())),
+            _ => Err(Error::InvalidWorker(worker.to_string())),
+        }
+    }
+}
+
+fn is_loopback(url: &Url) -> bool {
+    match url.host() {
+        Some(Host::Domain(domain)) => domain == "localhost",
+        Some(Host::Ipv4(ip)) => ip.is_loopback(),
+        Some(Host::Ipv6(ip)) => ip.is_loopback(),
+        None => false,
+    }
+}
+
+impl RemoteWorker {
+    fn tasks_url(&self) -> Url {
+        let mut url = self.0.clone();
+        url.path_segments_mut()
+            .expect("http(s) URLs can be a base")
+            .pop_if_empty()
+            .extend(["v0", "tasks"]);
+        url
+    }
+}
+
+/// A task to be executed by a remote worker.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteTask {
+    pub task_id: String,
+    /// The hash that the outputs of the task are cached under
+    pub hash: String,
+    pub command: RemoteCommand,
+    /// The hashes of the files the task depends on, relative to its package
+    pub inputs: BTreeMap<RelativeUnixPathBuf, String>,
+    /// The hashes of the tasks this task depends on, whose outputs need to be
+    /// restored before it runs
+    pub dependencies: BTreeMap<String, String>,
+    pub outputs: RemoteOutputs,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteCommand {
+    pub program: String,
+    pub args: Vec<String>,
+    /// The directory to run the command in, relative to the repository root
+    pub cwd: RelativeUnixPathBuf,
+    /// The variables the task hash depends on and `TURBO_HASH`. The rest of
+    /// the environment is up to the worker.
+    pub env: BTreeMap<String, String>,
+}
+
+/// The globs of the outputs to cache, relative to the repository root.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteOutputs {
+    pub inclusions: Vec<String>,
+    pub exclusions: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteTaskResult {
+    pub exit_code: i32,
+    /// The output of the task. Only needed for failed tasks, as the logs of
+    /// successful tasks are restored from the cache.
+    #[serde(default)]
+    pub logs: String,
+}
+
+/// Dispatches tasks to a pool of remote workers.
+///
+/// Tasks are spread over the workers round-robin. A task is dispatched to the
+/// next worker when a worker can't be reached.
+#[derive(Debug, Clone)]
+pub struct RemoteExecutor {
+    workers: Arc<[RemoteWorker]>,
+    next: Arc<AtomicUsize>,
+    client: reqwest::Client,
+}
+
+impl RemoteExecutor {
+    pub fn new(workers: Vec<RemoteWorker>, tls_config: &TlsConfig) -> Result<Option<Self>, Error> {
+        if workers.is_empty() {
+            return Ok(None);
+        }
+        let client = tls_config
+            .client_builder()
+            .and_then(|builder| {
+                builder
+                    .build()
+                    .map_err(turborepo_api_client::Error::TlsError)
+            })
+            .map_err(Error::Client)?;
+        Ok(Some(Self {
+            workers: workers.into(),
+            next: Arc::default(),
+            client,
+        }))
+    }
+
+    /// Executes `task` on one of the workers and waits for it to finish.
+    pub async fn execute(&self, task: &RemoteTask) -> Result<RemoteTaskResult, Error> {
+        let first = self.next.fetch_add(1, Ordering::Relaxed);
+        let mut last_error = None;
+        for i in 0..self.workers.len() {
+            let worker = &self.workers[(first + i) % self.workers.len()];
+            let response = match self.client.post(worker.tasks_url()).json(task).send().await {
+                Ok(response) => response,
+                Err(e) if e.is_connect() => {
+                    debug!("remote worker {} is unreachable: {e}", worker.0);
+                    last_error = Some(e);
+                    continue;
+                }
+                Err(source) => {
+                    return Err(Error::Dispatch {
+                        task: task.task_id.clone(),
+                        source,
+                    })
+                }
+            };
+
+            let status = response.status();
+            if !status.is_success() {
+                let body = response.text().await.unwrap_or_default();
+                return Err(Error::Rejected {
+                    worker: worker.0.clone(),
+                    task: task.task_id.clone(),
+                    message: match body.trim() {
+                        "" => status.to_string(),
+                        body => format!("{status}: {body}"),
+                    },
+                });
+            }
+            return response.json().await.map_err(|source| Error::Dispatch {
+                task: task.task_id.clone(),
+                source,
+            });
+        }
+
+        Err(Error::Dispatch {
+            task: task.task_id.clone(),
+            source: last_error.expect("there is at least one worker"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        collections::BTreeMap,
+        net::{SocketAddr, TcpListener},
+    };
+
+    use axum::{http::StatusCode, routing::post, Json, Router};
+    use serde_json::json;
+    use test_case::test_case;
+    use turbopath::RelativeUnixPathBuf;
+    use turborepo_api_client::TlsConfig;
+
+    use super::{
+        Error, RemoteCommand, RemoteExecutor, RemoteOutputs, RemoteTask, RemoteTaskResult,
+        RemoteWorker,
+    };
+
+    fn task() -> RemoteTask {
+        RemoteTask {
+            task_id: "web#build".to_string(),
+            hash: "0123456789abcdef".to_string(),
+            command: RemoteCommand {
+                program: "npm".to_string(),
+                args: vec!["run".to_string(), "build".to_string()],
+                cwd: RelativeUnixPathBuf::new("apps/web").unwrap(),
+                env: BTreeMap::from([("TURBO_HASH".to_string(), "0123456789abcdef".to_string())]),
+            },
+            inputs: BTreeMap::from([(
+                RelativeUnixPathBuf::new("package.json").unwrap(),
+                "fedcba".to_string(),
+            )]),
+            dependencies: BTreeMap::from([("ui#build".to_string(), "abcdef".to_string())]),
+            outputs: RemoteOutputs {
+                inclusions: vec!["apps/web/.turbo/turbo-build.log".to_string()],
+                exclusions: vec![],
+            },
+        }
+    }
+
+    fn serve(app: Router) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(
+            axum::Server::from_tcp(listener)
+                .unwrap()
+                .serve(app.into_make_service()),
+        );
+        format!("http://{addr}")
+    }
+
+    // An address that nothing listens on
+    fn unreachable() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr: SocketAddr = listener.local_addr().unwrap();
+        format!("http://{addr}")
+    }
+
+    #[test_case("https://worker.example.com", true ; "https")]
+    #[test_case("http://127.0.0.1:8080/", true ; "http on loopback")]
+    #[test_case("http://localhost:8080/", true ; "http on localhost")]
+    #[test_case("http://[::1]:8080/", true ; "http on ipv6 loopback")]
+    #[test_case("http://10.0.0.2:8080/", false ; "http")]
+    #[test_case("worker.example.com", false ; "missing scheme")]
+    #[test_case("ftp://worker.example.com", false ; "unsupported scheme")]
+    fn test_parse_worker(worker: &str, valid: bool) {
+        assert_eq!(worker.parse::<RemoteWorker>().is_ok(), valid);
+    }
+
+    #[test]
+    fn test_tasks_url() {
+        let worker: RemoteWorker = "https://example.com/workers/".parse().unwrap();
+        assert_eq!(
+            worker.tasks_url().as_str(),
+            "https://example.com/workers/v0/tasks"
+        );
+    }
+
+    #[test]
+    fn test_serialize_task() {
+        assert_eq!(
+            serde_json::to_value(task()).unwrap(),
+            json!({
+                "taskId": "web#build",
+                "hash": "0123456789abcdef",
+                "command": {
+                    "program": "npm",
+                    "args": ["run", "build"],
+                    "cwd": "apps/web",
+                    "env": { "TURBO_HASH": "0123456789abcdef" },
+                },
+                "inputs": { "package.json": "fedcba" },
+                "dependencies": { "ui#build": "abcdef" },
+                "outputs": {
+                    "inclusions": ["apps/web/.turbo/turbo-build.log"],
+                    "exclusions": [],
+                },
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_skips_unreachable_workers() {
+        let app = Router::new().route(
+            "/v0/tasks",
+            post(|Json(task): Json<serde_json::Value>| async move {
+                assert_eq!(task["taskId"], "web#build");
+                Json(json!({ "exitCode": 0 }))
+            }),
+        );
+        let workers = vec![unreachable().parse().unwrap(), serve(app).parse().unwrap()];
+        let executor = RemoteExecutor::new(workers, &TlsConfig::default())
+            .unwrap()
+            .unwrap();
+
+        let result = executor.execute(&task()).await.unwrap();
+        assert_eq!(
+            result,
+            RemoteTaskResult {
+                exit_code: 0,
+                logs: String::new()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_rejected() {
+        let app = Router::new().route(
+            "/v0/tasks",
+            post(|| async { (StatusCode::CONFLICT, "inputs don't match") }),
+        );
+        let executor =
+            RemoteExecutor::new(vec![serve(app).parse().unwrap()], &TlsConfig::default())
+                .unwrap()
+                .unwrap();
+
+        let result = executor.execute(&task()).await;
+        assert!(matches!(
+            result,
+            Err(Error::Rejected { message, .. }) if message == "409 Conflict: inputs don't match"
+        ));
+    }
+}
@@ -0,0 +1,202 @@
+//! Resuming interrupted runs.
+//!
+//! When a run is interrupted or fails, the hashes of the tasks that completed
+//! successfully are recorded in `.turbo/resume.json`. `turbo run --resume`
+//! skips the tasks whose hash is unchanged since then, even if they aren't
+//! cacheable, as their outputs were already produced by the earlier attempt.
+//!
+//! The record is shared by all runs, but each run only updates the entries of
+//! its own tasks: a failed run adds the tasks that completed, and a successful
+//! run removes its tasks, as there's nothing left to resume for them.
+
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
+
+use crate::run::task_id::TaskId;
+
+pub const RESUME_STATE_PATH: [&str; 2] = [".turbo", "resume.json"];
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CompletedTasks {
+    // Hashes of the tasks that completed, by task id
+    tasks: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ResumeState {
+    path: AbsoluteSystemPathBuf,
+    // Tasks that completed in earlier runs that didn't succeed
+    resumable: Arc<CompletedTasks>,
+    completed: Arc<Mutex<CompletedTasks>>,
+}
+
+impl ResumeState {
+    /// Loads the tasks that completed in earlier interrupted or failed runs if
+    /// `resume` is set.
+    pub fn load(repo_root: &AbsoluteSystemPath, resume: bool) -> Self {
+        let path = repo_root.join_components(&RESUME_STATE_PATH);
+        let resumable = match resume {
+            true => Self::read(&path).unwrap_or_else(|| {
+                warn!("there is no interrupted run to resume, running all tasks");
+                CompletedTasks::default()
+            }),
+            false => CompletedTasks::default(),
+        };
+        Self {
+            path,
+            resumable: Arc::new(resumable),
+            completed: Default::default(),
+        }
+    }
+
+    fn read(path: &AbsoluteSystemPath) -> Option<CompletedTasks> {
+        let contents = path.read_to_string().ok()?;
+        match serde_json::from_str(&contents) {
+            Ok(completed) => Some(completed),
+            Err(e) => {
+                debug!("unable to parse {path}: {e}");
+                None
+            }
+        }
+    }
+
+    /// Whether the task already completed in an earlier run with the same hash,
+    /// so it doesn't need to run again.
+    pub fn is_completed(&self, task_id: &TaskId, hash: &str) -> bool {
+        self.resumable
+            .tasks
+            .get(&task_id.to_string())
+            .is_some_and(|completed| completed == hash)
+    }
+
+    pub fn task_completed(&self, task_id: &TaskId, hash: &str) {
+        self.completed
+            .lock()
+            .expect("lock poisoned")
+            .tasks
+            .insert(task_id.to_string(), hash.to_string());
+    }
+
+    /// Records the tasks that completed unless the run succeeded, so they can
+    /// be skipped by `--resume`. Once a run succeeds there's nothing left to
+    /// resume for its tasks, so they are removed instead. The entries of tasks
+    /// that this run didn't complete are kept.
+    pub fn save(&self, succeeded: bool) {
+        let completed = self.completed.lock().expect("lock poisoned");
+        let mut record = Self::read(&self.path).unwrap_or_default();
+        for (task_id, hash) in &completed.tasks {
+            match succeeded {
+                true => record.tasks.remove(task_id),
+                false => record.tasks.insert(task_id.clone(), hash.clone()),
+            };
+        }
+
+        if record.tasks.is_empty() {
+            if self.path.exists() {
+                if let Err(e) = self.path.remove_file() {
+                    warn!("unable to remove {}: {e}", self.path);
+                }
+            }
+            return;
+        }
+
+        let result = serde_json::to_string_pretty(&record)
+            .map_err(std::io::Error::from)
+            .and_then(|contents| {
+                self.path.ensure_dir()?;
+                self.path.create_with_contents(contents)
+            });
+        if let Err(e) = result {
+            warn!("unable to record the completed tasks of the run: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tempfile::tempdir;
+    use turbopath::AbsoluteSystemPath;
+
+    use super::{ResumeState, RESUME_STATE_PATH};
+    use crate::run::task_id::TaskId;
+
+    #[test]
+    fn test_resume() {
+        let tmp = tempdir().unwrap();
+        let repo_root = AbsoluteSystemPath::from_std_path(tmp.path()).unwrap();
+        let build = TaskId::new("web", "build");
+        let lint = TaskId::new("web", "lint");
+
+        let interrupted = ResumeState::load(repo_root, false);
+        interrupted.task_completed(&build, "abc");
+        interrupted.save(false);
+
+        let resumed = ResumeState::load(repo_root, true);
+        assert!(resumed.is_completed(&build, "abc"));
+        assert!(
+            !resumed.is_completed(&build, "def"),
+            "tasks with changed inputs run again"
+        );
+        assert!(!resumed.is_completed(&lint, "abc"));
+
+        // A resumed run that fails again keeps the tasks that completed, including
+        // the ones that were skipped
+        resumed.task_completed(&build, "abc");
+        resumed.save(false);
+        let resumed = ResumeState::load(repo_root, true);
+        assert!(resumed.is_completed(&build, "abc"));
+
+        resumed.task_completed(&build, "abc");
+        resumed.task_completed(&lint, "def");
+        resumed.save(true);
+        assert!(!repo_root.join_components(&RESUME_STATE_PATH).exists());
+        assert!(!ResumeState::load(repo_root, true).is_completed(&build, "abc"));
+    }
+
+    #[test]
+    fn test_unrelated_runs() {
+        let tmp = tempdir().unwrap();
+        let repo_root = AbsoluteSystemPath::from_std_path(tmp.path()).unwrap();
+        let build = TaskId::new("web", "build");
+        let lint = TaskId::new("web", "lint");
+        let test = TaskId::new("web", "test");
+
+        let interrupted = ResumeState::load(repo_root, false);
+        interrupted.task_completed(&build, "abc");
+        interrupted.save(false);
+
+        // A successful run of other tasks doesn't remove the record of `build`
+        let other = ResumeState::load(repo_root, false);
+        other.task_completed(&lint, "def");
+        other.save(true);
+        assert!(ResumeState::load(repo_root, true).is_completed(&build, "abc"));
+
+        // A failed run of other tasks adds to the record instead of replacing it
+        let other = ResumeState::load(repo_root, false);
+        other.task_completed(&test, "ghi");
+        other.save(false);
+        let resumed = ResumeState::load(repo_root, true);
+        assert!(resumed.is_completed(&build, "abc"));
+        assert!(resumed.is_completed(&test, "ghi"));
+        assert!(!resumed.is_completed(&lint, "def"));
+    }
+
+    #[test]
+    fn test_not_resuming() {
+        let tmp = tempdir().unwrap();
+        let repo_root = AbsoluteSystemPath::from_std_path(tmp.path()).unwrap();
+        let build = TaskId::new("web", "build");
+
+        let interrupted = ResumeState::load(repo_root, false);
+        interrupted.task_completed(&build, "abc");
+        interrupted.save(false);
+
+        assert!(!ResumeState::load(repo_root, false).is_completed(&build, "abc"));
+    }
+}
@@ -76,7 +76,13 @@ impl<'a> ScopeChangeDetector<'a> {
             return Some(LockfileChange::Empty);
         };
 
-        Some(LockfileChange::WithContent(content))
+        Some(LockfileChange::WithContent {
+            path: self
+                .turbo_root
+                .anchor(&lockfile_path)
+                .expect("lockfile should be in repo"),
+            content,
+        })
     }
 }
 
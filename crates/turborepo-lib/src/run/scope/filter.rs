@@ -4,7 +4,7 @@ use std::{
     str::FromStr,
 };
 
-use tracing::debug;
+use tracing::{debug, warn};
 use turbopath::{AbsoluteSystemPath, AnchoredSystemPathBuf};
 use turborepo_repository::{
     change_mapper::ChangeMapError,
@@ -20,7 +20,7 @@ use super::{
 };
 use crate::{
     global_deps_package_change_mapper, run::scope::change_detector::ScopeChangeDetector,
-    turbo_json::TurboJson,
+    suggestions, turbo_json::TurboJson,
 };
 
 pub struct PackageInference {
@@ -330,6 +330,12 @@ impl<'a, T: GitChangeDetector> FilterResolver<'a, T> {
             }
         }
 
+        for selector in &unmatched_selectors {
+            if let Some(help) = self.suggest_packages(selector) {
+                warn!("No package matched the filter \"{}\", {help}", selector.raw);
+            }
+        }
+
         let mut all_packages = HashSet::new();
         all_packages.extend(walked_dependencies);
         all_packages.extend(walked_dependents);
@@ -339,6 +345,26 @@ impl<'a, T: GitChangeDetector> FilterResolver<'a, T> {
         Ok(all_packages)
     }
 
+    // Only plain package names are likely to be typos, a glob or a directory
+    // that doesn't match anything is probably intentional
+    fn suggest_packages(&self, selector: &TargetSelector) -> Option<String> {
+        let name = selector.name_pattern.as_str();
+        if name.is_empty() || name.contains('*') {
+            return None;
+        }
+        let packages = self
+            .pkg_graph
+            .packages()
+            .filter(|(package, _)| !PackageName::Root.eq(package))
+            .map(|(package, info)| (package.as_ref(), info.package_path().as_str()))
+            .collect::<HashMap<_, _>>();
+        suggestions::did_you_mean(
+            suggestions::closest(name, packages.keys().copied())
+                .into_iter()
+                .map(|package| (package, vec![packages[package]])),
+        )
+    }
+
     fn filter_graph_with_selector(
         &self,
         selector: &TargetSelector,
@@ -1,10 +1,12 @@
-use std::fmt;
+use std::{collections::HashMap, fmt};
 
 use chrono::{DateTime, Local};
 use serde::Serialize;
 use tokio::sync::mpsc;
 use turbopath::{AbsoluteSystemPathBuf, AnchoredSystemPath};
-use turborepo_ui::{color, cprintln, BOLD, BOLD_GREEN, BOLD_RED, MAGENTA, UI, YELLOW};
+use turborepo_ui::{
+    color, cprintln, json::print_line, BOLD, BOLD_GREEN, BOLD_RED, MAGENTA, UI, YELLOW,
+};
 
 use super::TurboDuration;
 use crate::run::{summary::task::TaskSummary, task_id::TaskId};
@@ -26,25 +28,32 @@ pub struct ExecutionTracker {
 #[serde(rename_all = "camelCase")]
 pub struct ExecutionSummary<'a> {
     // a synthesized turbo command to produce this invocation
-    command: String,
+    pub(crate) command: String,
     // the (possibly empty) path from the turborepo root to where the command was run
     #[serde(rename = "repoPath")]
     repo_path: &'a AnchoredSystemPath,
     // number of tasks that exited successfully (does not include cache hits)
-    success: usize,
+    pub(crate) success: usize,
     // number of tasks that exited with failure
-    failed: usize,
+    pub(crate) failed: usize,
     // number of tasks that had a cache hit
-    cached: usize,
+    pub(crate) cached: usize,
+    // number of tasks that were still running when the run was interrupted
+    #[serde(skip_serializing_if = "is_zero")]
+    pub(crate) interrupted: usize,
     // number of tasks that started
-    attempted: usize,
+    pub(crate) attempted: usize,
     pub(crate) start_time: i64,
     pub(crate) end_time: i64,
     #[serde(skip)]
-    duration: TurboDuration,
+    pub(crate) duration: TurboDuration,
     pub(crate) exit_code: i32,
 }
 
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
 impl<'a> ExecutionSummary<'a> {
     pub fn new(
         command: String,
@@ -60,6 +69,7 @@ impl<'a> ExecutionSummary<'a> {
             success: state.success,
             failed: state.failed,
             cached: state.cached,
+            interrupted: state.interrupted,
             attempted: state.attempted,
             // We're either at some path in the repo, or at the root, which is an empty path
             repo_path: package_inference_root.unwrap_or_else(|| AnchoredSystemPath::empty()),
@@ -114,6 +124,13 @@ impl<'a> ExecutionSummary<'a> {
             line_data.push(("Summary", path.to_string()));
         }
 
+        if self.interrupted > 0 {
+            line_data.push((
+                "Interrupted",
+                color!(ui, YELLOW, "{} interrupted", self.interrupted).to_string(),
+            ));
+        }
+
         if !failed_tasks.is_empty() {
             let mut formatted: Vec<_> = failed_tasks
                 .iter()
@@ -144,16 +161,16 @@ impl<'a> ExecutionSummary<'a> {
             .collect();
 
         if self.attempted == 0 {
-            println!();
+            print_line("");
             cprintln!(ui, YELLOW, "No tasks were executed as part of this run.");
         }
 
-        println!();
+        print_line("");
         for line in lines {
-            println!("{}", line);
+            print_line(line);
         }
 
-        println!();
+        print_line("");
     }
 
     fn successful(&self) -> usize {
@@ -168,7 +185,10 @@ pub struct SummaryState {
     pub failed: usize,
     pub cached: usize,
     pub success: usize,
+    pub interrupted: usize,
     pub tasks: Vec<TaskState>,
+    // Tasks that started but haven't finished
+    running: HashMap<TaskId<'static>, TaskExecutionSummary>,
 }
 
 #[derive(Debug, Clone)]
@@ -187,6 +207,35 @@ impl SummaryState {
             Event::Canceled => (),
         }
     }
+
+    // Tasks that were still running when the run was interrupted, or that failed
+    // after that, count as interrupted instead of failed
+    fn interrupt(&mut self, interrupted_at: DateTime<Local>, ended_at: DateTime<Local>) {
+        let interrupted_at = interrupted_at.timestamp_millis();
+        for execution in self
+            .tasks
+            .iter_mut()
+            .filter_map(|task| task.execution.as_mut())
+        {
+            if execution.is_failure() && execution.end_time >= interrupted_at {
+                execution.interrupted = true;
+                self.failed -= 1;
+                self.interrupted += 1;
+            }
+        }
+
+        let mut running = self.running.drain().collect::<Vec<_>>();
+        running.sort_by(|(a, _), (b, _)| a.cmp(b));
+        for (task_id, mut execution) in running {
+            execution.end_time = ended_at.timestamp_millis();
+            execution.interrupted = true;
+            self.interrupted += 1;
+            self.tasks.push(TaskState {
+                task_id,
+                execution: Some(execution),
+            });
+        }
+    }
 }
 
 /// A tracker constructed for each task and used to communicate task events back
@@ -222,6 +271,8 @@ pub struct TaskExecutionSummary {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
     pub exit_code: Option<i32>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub interrupted: bool,
 }
 
 impl TaskExecutionSummary {
@@ -245,8 +296,17 @@ impl ExecutionTracker {
             }) = receiver.recv().await
             {
                 state.handle_event(event);
-                if let Some(task_state) = task_state {
-                    state.tasks.push(task_state);
+                match (event, task_state) {
+                    (Event::Building, Some(TaskState { task_id, execution })) => {
+                        state
+                            .running
+                            .extend(execution.map(|execution| (task_id, execution)));
+                    }
+                    (_, Some(task_state)) => {
+                        state.running.remove(&task_state.task_id);
+                        state.tasks.push(task_state);
+                    }
+                    (_, None) => (),
                 }
             }
             state
@@ -267,7 +327,12 @@ impl ExecutionTracker {
         }
     }
 
-    pub async fn finish(self) -> Result<SummaryState, tokio::task::JoinError> {
+    /// Waits for all trackers to finish. If the run was interrupted, the tasks
+    /// that were running at `interrupted_at` are marked as interrupted.
+    pub async fn finish(
+        self,
+        interrupted_at: Option<DateTime<Local>>,
+    ) -> Result<SummaryState, tokio::task::JoinError> {
         let Self {
             state_thread,
             sender,
@@ -278,7 +343,10 @@ impl ExecutionTracker {
         // to send their execution summary.
         drop(sender);
 
-        let summary_state = state_thread.await?;
+        let mut summary_state = state_thread.await?;
+        if let Some(interrupted_at) = interrupted_at {
+            summary_state.interrupt(interrupted_at, Local::now());
+        }
 
         Ok(summary_state)
     }
@@ -294,7 +362,16 @@ impl TaskTracker<()> {
         sender
             .send(TrackerMessage {
                 event: Event::Building,
-                state: None,
+                state: Some(TaskState {
+                    task_id: task_id.clone(),
+                    execution: Some(TaskExecutionSummary {
+                        start_time: started_at.timestamp_millis(),
+                        end_time: started_at.timestamp_millis(),
+                        error: None,
+                        exit_code: None,
+                        interrupted: false,
+                    }),
+                }),
             })
             .await
             .expect("execution summary state thread finished");
@@ -343,6 +420,7 @@ impl TaskTracker<chrono::DateTime<Local>> {
             // Go synthesizes a zero exit code on cache hits
             exit_code: Some(0),
             error: None,
+            interrupted: false,
         };
 
         let state = TaskState {
@@ -372,6 +450,7 @@ impl TaskTracker<chrono::DateTime<Local>> {
             end_time: ended_at.timestamp_millis(),
             exit_code: Some(exit_code),
             error: None,
+            interrupted: false,
         };
 
         let state = TaskState {
@@ -405,6 +484,7 @@ impl TaskTracker<chrono::DateTime<Local>> {
             end_time: ended_at.timestamp_millis(),
             exit_code,
             error: Some(error.to_string()),
+            interrupted: false,
         };
 
         let state = TaskState {
@@ -470,7 +550,7 @@ mod test {
             task.await.unwrap();
         }
 
-        let state = summary.finish().await.unwrap();
+        let state = summary.finish(None).await.unwrap();
         assert_eq!(state.attempted, 4);
         assert_eq!(state.cached, 1);
         assert_eq!(state.failed, 1);
@@ -488,6 +568,62 @@ mod test {
         );
     }
 
+    #[tokio::test]
+    async fn test_interrupted() {
+        let summary = ExecutionTracker::new();
+        let done = TaskId::new("done", "build");
+        let killed = TaskId::new("killed", "build");
+        let running = TaskId::new("running", "build");
+
+        let tracker = summary.task_tracker(done.clone()).start().await;
+        tracker.build_succeeded(0).await;
+        let killed_tracker = summary.task_tracker(killed.clone()).start().await;
+        let running_tracker = summary.task_tracker(running.clone()).start().await;
+
+        let interrupted_at = Local::now();
+        killed_tracker.build_failed(Some(130), "interrupted").await;
+        running_tracker.cancel();
+
+        let state = summary.finish(Some(interrupted_at)).await.unwrap();
+        assert_eq!(state.attempted, 3);
+        assert_eq!(state.success, 1);
+        assert_eq!(state.failed, 0);
+        assert_eq!(state.interrupted, 2);
+        let execution = |task_id: &TaskId| {
+            state
+                .tasks
+                .iter()
+                .find(|task| &task.task_id == task_id)
+                .and_then(|task| task.execution.clone())
+                .unwrap()
+        };
+        assert!(!execution(&done).interrupted);
+        assert!(execution(&killed).interrupted);
+        assert_eq!(execution(&killed).exit_code, Some(130));
+        assert!(execution(&running).interrupted);
+        assert_eq!(execution(&running).exit_code, None);
+
+        let now = Local::now();
+        let summary = |state| {
+            serde_json::to_value(ExecutionSummary::new(
+                "turbo run build".to_string(),
+                state,
+                None,
+                1,
+                now,
+                now,
+            ))
+            .unwrap()
+        };
+        assert_eq!(summary(state)["interrupted"], json!(2));
+        assert!(
+            summary(SummaryState::default())
+                .get("interrupted")
+                .is_none(),
+            "runs that weren't interrupted don't have the field"
+        );
+    }
+
     #[tokio::test]
     async fn test_timing() {
         let summary = ExecutionTracker::new();
@@ -500,7 +636,7 @@ mod test {
 
         tokio::time::sleep(sleep_duration.to_std().unwrap()).await;
         tracker.build_succeeded(0).await;
-        let mut state = summary.finish().await.unwrap();
+        let mut state = summary.finish(None).await.unwrap();
         assert_eq!(state.tasks.len(), 1);
         let summary = state.tasks.pop().unwrap().execution.unwrap();
         assert!(
@@ -518,7 +654,8 @@ mod test {
             start_time: 123,
             end_time: 234,
             exit_code: Some(0),
-            error: None
+            error: None,
+            interrupted: false,
         },
         json!({ "startTime": 123, "endTime": 234, "exitCode": 0 })
         ; "success"
@@ -529,10 +666,22 @@ mod test {
             end_time: 234,
             exit_code: Some(1),
             error: Some("cannot find anything".into()),
+            interrupted: false,
         },
         json!({ "startTime": 123, "endTime": 234, "exitCode": 1, "error": "cannot find anything" })
         ; "failure"
     )]
+    #[test_case(
+        TaskExecutionSummary {
+            start_time: 123,
+            end_time: 234,
+            exit_code: None,
+            error: None,
+            interrupted: true,
+        },
+        json!({ "startTime": 123, "endTime": 234, "exitCode": null, "interrupted": true })
+        ; "interrupted"
+    )]
     fn test_serialization(value: impl serde::Serialize, expected: serde_json::Value) {
         assert_eq!(serde_json::to_value(value).unwrap(), expected);
     }
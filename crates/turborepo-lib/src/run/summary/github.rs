@@ -0,0 +1,287 @@
+//! Output for GitHub Actions, enabled with `--ci=github`.
+//!
+//! Errors in the logs of failed tasks are turned into annotations, which
+//! GitHub shows on the workflow run and next to the offending lines of pull
+//! requests. Locations are found heuristically, by looking for `file:line:col`
+//! and `file(line,col)` on lines that mention an error. A table of the tasks
+//! of the run is appended to the job summary.
+
+use std::{fs::OpenOptions, io::Write as _, sync::OnceLock};
+
+use regex::Regex;
+use tracing::debug;
+use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
+
+use super::{Error, RunSummary, TaskSummary};
+
+const STEP_SUMMARY_ENV_VAR: &str = "GITHUB_STEP_SUMMARY";
+
+/// GitHub only shows the first few annotations of a step, so there's no point
+/// in emitting many for a single task.
+const MAX_ANNOTATIONS_PER_TASK: usize = 10;
+
+#[derive(Debug, PartialEq)]
+struct Annotation {
+    file: String,
+    line: u32,
+    col: Option<u32>,
+    title: String,
+    message: String,
+}
+
+impl Annotation {
+    fn command(&self) -> String {
+        let mut properties = format!("file={},line={}", escape_property(&self.file), self.line);
+        if let Some(col) = self.col {
+            properties.push_str(&format!(",col={col}"));
+        }
+        properties.push_str(&format!(",title={}", escape_property(&self.title)));
+        format!("::error {properties}::{}", escape_data(&self.message))
+    }
+}
+
+/// Prints annotations for the failed tasks of the run and appends the run to
+/// the job summary.
+pub(super) fn write(run_summary: &RunSummary) -> Result<(), Error> {
+    // Workflow commands would break up the JSON lines
+    let failed_tasks = match turborepo_ui::json::is_enabled() {
+        true => Vec::new(),
+        false => run_summary.get_failed_tasks(),
+    };
+    for task in failed_tasks {
+        for annotation in annotations(task, run_summary.repo_root) {
+            println!("{}", annotation.command());
+        }
+    }
+
+    let Ok(path) = std::env::var(STEP_SUMMARY_ENV_VAR) else {
+        debug!("{STEP_SUMMARY_ENV_VAR} isn't set, not writing a job summary");
+        return Ok(());
+    };
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(step_summary(run_summary).as_bytes())?;
+    Ok(())
+}
+
+fn location_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(
+            r"(?P<file>[^\s:()'`]+\.[A-Za-z0-9]+)(?::(?P<line>\d+)(?::(?P<col>\d+))?|\((?P<paren_line>\d+),(?P<paren_col>\d+)\))",
+        )
+        .unwrap()
+    })
+}
+
+fn annotations(task: &TaskSummary, repo_root: &AbsoluteSystemPath) -> Vec<Annotation> {
+    let log_file = AbsoluteSystemPathBuf::from_unknown(repo_root, &task.shared.log_file);
+    let Ok(logs) = log_file.read_to_string() else {
+        return Vec::new();
+    };
+    let package_dir = task.shared.directory.as_deref().unwrap_or_default();
+    parse_annotations(
+        &console::strip_ansi_codes(&logs),
+        package_dir,
+        repo_root,
+        &task.task_id.to_string(),
+    )
+}
+
+fn parse_annotations(
+    logs: &str,
+    package_dir: &str,
+    repo_root: &AbsoluteSystemPath,
+    title: &str,
+) -> Vec<Annotation> {
+    let mut annotations = Vec::new();
+    for line in logs.lines() {
+        if !line.to_lowercase().contains("error") {
+            continue;
+        }
+        let Some(captures) = location_regex().captures(line) else {
+            continue;
+        };
+        let file = &captures["file"];
+        if file.contains("node_modules") || file.starts_with("http") {
+            continue;
+        }
+        let Some(file) = repo_relative(file, package_dir, repo_root) else {
+            continue;
+        };
+        let Some(line_number) = captures
+            .name("line")
+            .or(captures.name("paren_line"))
+            .and_then(|line| line.as_str().parse().ok())
+        else {
+            continue;
+        };
+        let col = captures
+            .name("col")
+            .or(captures.name("paren_col"))
+            .and_then(|col| col.as_str().parse().ok());
+
+        let location = captures.get(0).expect("regex matched");
+        let message = line[location.end()..]
+            .trim_start_matches(|c: char| c == ':' || c == '-' || c.is_whitespace())
+            .trim_end();
+        let message = match message.is_empty() {
+            true => line.trim(),
+            false => message,
+        };
+
+        annotations.push(Annotation {
+            file,
+            line: line_number,
+            col,
+            title: title.to_string(),
+            message: message.to_string(),
+        });
+        if annotations.len() == MAX_ANNOTATIONS_PER_TASK {
+            break;
+        }
+    }
+    annotations
+}
+
+// Annotations need paths relative to the repository, while tools print them
+// relative to the package they run in
+fn repo_relative(file: &str, package_dir: &str, repo_root: &AbsoluteSystemPath) -> Option<String> {
+    let file = file.replace('\\', "/");
+    let repo_root = repo_root.as_str().replace('\\', "/");
+    if let Some(relative) = file.strip_prefix(&repo_root) {
+        return Some(relative.trim_start_matches('/').to_string());
+    }
+    if file.starts_with('/') {
+        // An absolute path outside of the repository can't be annotated
+        return None;
+    }
+    let file = file.trim_start_matches("./");
+    let package_dir = package_dir.replace('\\', "/");
+    match package_dir.is_empty() {
+        true => Some(file.to_string()),
+        false => Some(format!("{}/{file}", package_dir.trim_end_matches('/'))),
+    }
+}
+
+fn step_summary(run_summary: &RunSummary) -> String {
+    let mut out = String::new();
+    let command = run_summary
+        .execution
+        .as_ref()
+        .map_or("turbo run", |execution| execution.command.as_str());
+    out.push_str(&format!("### `{command}`\n\n"));
+
+    out.push_str("| Task | Cache | Result | Duration |\n");
+    out.push_str("| :--- | :--- | :--- | ---: |\n");
+    for task in &run_summary.tasks {
+        let Some(execution) = &task.shared.execution else {
+            continue;
+        };
+        let cache = match task.shared.cache.is_hit() {
+            true => "hit",
+            false => "miss",
+        };
+        let result = match execution.is_failure() {
+            true => "❌ failed",
+            false => "✅ succeeded",
+        };
+        let duration = (execution.end_time - execution.start_time).max(0) as f64 / 1000.0;
+        out.push_str(&format!(
+            "| `{}` | {cache} | {result} | {duration:.1}s |\n",
+            task.task_id
+        ));
+    }
+
+    if let Some(execution) = &run_summary.execution {
+        out.push_str(&format!(
+            "\n{} successful, {} cached, {} failed, {} total in {}\n",
+            execution.success,
+            execution.cached,
+            execution.failed,
+            execution.attempted,
+            execution.duration,
+        ));
+    }
+    out.push('\n');
+    out
+}
+
+fn escape_data(data: &str) -> String {
+    data.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_property(property: &str) -> String {
+    escape_data(property)
+        .replace(':', "%3A")
+        .replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod test {
+    use test_case::test_case;
+    use turbopath::AbsoluteSystemPathBuf;
+
+    use super::{parse_annotations, Annotation};
+
+    fn repo_root() -> AbsoluteSystemPathBuf {
+        AbsoluteSystemPathBuf::new(if cfg!(windows) { "C:\\repo" } else { "/repo" }).unwrap()
+    }
+
+    #[test_case(
+        "src/index.ts(12,5): error TS2322: Type 'string' is not assignable to type 'number'.",
+        Some(("apps/web/src/index.ts", 12, Some(5), "error TS2322: Type 'string' is not assignable to type 'number'."))
+        ; "tsc"
+    )]
+    #[test_case(
+        "src/index.ts:12:5 - error TS2322: Type 'string' is not assignable.",
+        Some(("apps/web/src/index.ts", 12, Some(5), "error TS2322: Type 'string' is not assignable."))
+        ; "tsc pretty"
+    )]
+    #[test_case(
+        "Error: ./src/page.tsx:3:1 Module not found",
+        Some(("apps/web/src/page.tsx", 3, Some(1), "Module not found"))
+        ; "relative to the package"
+    )]
+    #[test_case(
+        "warning: src/index.ts:1:1 unused import",
+        None
+        ; "not an error"
+    )]
+    #[test_case(
+        "error in node_modules/foo/index.js:1:1",
+        None
+        ; "dependency"
+    )]
+    fn test_parse_annotations(line: &str, expected: Option<(&str, u32, Option<u32>, &str)>) {
+        let annotations = parse_annotations(line, "apps/web", &repo_root(), "web#build");
+        let expected = expected
+            .map(|(file, line, col, message)| Annotation {
+                file: file.to_string(),
+                line,
+                col,
+                title: "web#build".to_string(),
+                message: message.to_string(),
+            })
+            .into_iter()
+            .collect::<Vec<_>>();
+        assert_eq!(annotations, expected);
+    }
+
+    #[test]
+    fn test_command() {
+        let annotation = Annotation {
+            file: "apps/web/src/index.ts".to_string(),
+            line: 12,
+            col: Some(5),
+            title: "web#build".to_string(),
+            message: "100% broken\nreally".to_string(),
+        };
+        assert_eq!(
+            annotation.command(),
+            "::error file=apps/web/src/index.ts,line=12,col=5,title=web#build::100%25 \
+             broken%0Areally"
+        );
+    }
+}
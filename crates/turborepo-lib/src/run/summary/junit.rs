@@ -0,0 +1,237 @@
+//! Results of a run in the JUnit XML format.
+//!
+//! Many CI systems can only display test results as JUnit XML. Every package
+//! of the run becomes a test suite and every task that ran becomes a test case,
+//! so these systems can show which tasks passed and failed and how their
+//! durations change over time. The test cases of failed tasks contain their
+//! logs.
+
+use std::{collections::BTreeMap, fmt::Write};
+
+use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
+
+use super::{execution::ExecutionSummary, report::escape, Error, RunSummary, TaskSummary};
+
+/// Writes the results of `run_summary` to `path`, relative to the repo root if
+/// it isn't absolute.
+pub(super) fn write(run_summary: &RunSummary, path: &str) -> Result<(), Error> {
+    let path = AbsoluteSystemPathBuf::from_unknown(run_summary.repo_root, path);
+    path.ensure_dir()?;
+    path.create_with_contents(render(
+        run_summary.execution.as_ref(),
+        &run_summary.tasks,
+        run_summary.repo_root,
+    ))?;
+    Ok(())
+}
+
+fn render(
+    execution: Option<&ExecutionSummary>,
+    tasks: &[TaskSummary],
+    repo_root: &AbsoluteSystemPath,
+) -> String {
+    let mut suites: BTreeMap<&str, Vec<&TaskSummary>> = BTreeMap::new();
+    for task in tasks {
+        if task.shared.execution.is_some() {
+            suites.entry(task.package.as_str()).or_default().push(task);
+        }
+    }
+
+    let name = execution.map_or("turbo run", |execution| execution.command.as_str());
+    let time = execution.map_or(0, |execution| execution.end_time - execution.start_time);
+    let tests = suites.values().map(Vec::len).sum::<usize>();
+    let failures = suites
+        .values()
+        .flatten()
+        .filter(|task| is_failure(task))
+        .count();
+
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    writeln!(
+        out,
+        "<testsuites name=\"{}\" tests=\"{tests}\" failures=\"{failures}\" time=\"{}\">",
+        xml_escape(name),
+        seconds(time),
+    )
+    .ok();
+    for (package, tasks) in suites {
+        let failures = tasks.iter().filter(|task| is_failure(task)).count();
+        let time = tasks.iter().map(|task| duration(task)).sum();
+        writeln!(
+            out,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{failures}\" time=\"{}\">",
+            xml_escape(package),
+            tasks.len(),
+            seconds(time),
+        )
+        .ok();
+        for task in tasks {
+            render_test_case(&mut out, task, repo_root);
+        }
+        out.push_str("  </testsuite>\n");
+    }
+    out.push_str("</testsuites>\n");
+    out
+}
+
+fn render_test_case(out: &mut String, task: &TaskSummary, repo_root: &AbsoluteSystemPath) {
+    write!(
+        out,
+        "    <testcase classname=\"{}\" name=\"{}\" time=\"{}\"",
+        xml_escape(&task.package),
+        xml_escape(&task.task),
+        seconds(duration(task)),
+    )
+    .ok();
+    let Some(execution) = task.shared.execution.as_ref().filter(|e| e.is_failure()) else {
+        out.push_str("/>\n");
+        return;
+    };
+
+    out.push_str(">\n");
+    let message = match (&execution.error, execution.exit_code) {
+        (Some(error), _) => error.clone(),
+        (None, Some(exit_code)) => format!("exited with code {exit_code}"),
+        (None, None) => "failed".to_string(),
+    };
+    let log_file = AbsoluteSystemPathBuf::from_unknown(repo_root, &task.shared.log_file);
+    let logs = log_file
+        .read_to_string()
+        .map(|logs| console::strip_ansi_codes(&logs).into_owned())
+        .unwrap_or_default();
+    writeln!(
+        out,
+        "      <failure message=\"{}\">{}</failure>",
+        xml_escape(&message),
+        xml_escape(&logs),
+    )
+    .ok();
+    out.push_str("    </testcase>\n");
+}
+
+fn is_failure(task: &TaskSummary) -> bool {
+    task.shared
+        .execution
+        .as_ref()
+        .map_or(false, |execution| execution.is_failure())
+}
+
+fn duration(task: &TaskSummary) -> i64 {
+    task.shared.execution.as_ref().map_or(0, |execution| {
+        (execution.end_time - execution.start_time).max(0)
+    })
+}
+
+fn seconds(millis: i64) -> String {
+    format!("{:.3}", millis as f64 / 1000.0)
+}
+
+// XML 1.0 doesn't allow most control characters, even when escaped, and task
+// logs can contain anything
+fn xml_escape(text: &str) -> String {
+    let text = text
+        .chars()
+        .filter(|c| !c.is_control() || matches!(c, '\t' | '\n' | '\r'))
+        .collect::<String>();
+    escape(&text)
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{Duration, Local};
+    use tempfile::tempdir;
+    use turbopath::AbsoluteSystemPath;
+    use turborepo_cache::{CacheHitMetadata, CacheSource};
+
+    use super::{render, seconds, xml_escape};
+    use crate::run::summary::{
+        execution::{ExecutionSummary, SummaryState},
+        task::{TaskCacheSummary, TaskSummary},
+        TaskExecutionSummary,
+    };
+
+    fn execution(start_time: i64, end_time: i64, exit_code: i32) -> Option<TaskExecutionSummary> {
+        Some(TaskExecutionSummary {
+            start_time,
+            end_time,
+            error: None,
+            exit_code: Some(exit_code),
+            interrupted: false,
+        })
+    }
+
+    #[test]
+    fn test_render() {
+        let tmp = tempdir().unwrap();
+        let repo_root = AbsoluteSystemPath::from_std_path(tmp.path()).unwrap();
+        let log_file = repo_root.join_components(&["web", ".turbo", "turbo-test.log"]);
+        log_file.ensure_dir().unwrap();
+        log_file
+            .create_with_contents("\u{1b}[31mexpected 1 to be <2>\u{1b}[0m\n")
+            .unwrap();
+
+        let cached = TaskCacheSummary::from(Some(CacheHitMetadata {
+            source: CacheSource::Local,
+            time_saved: 1500,
+        }));
+        let tasks = [
+            TaskSummary::for_test(
+                "web",
+                "build",
+                TaskCacheSummary::cache_miss(),
+                execution(1000, 2500, 0),
+            ),
+            TaskSummary::for_test(
+                "web",
+                "test",
+                TaskCacheSummary::cache_miss(),
+                execution(2500, 3000, 1),
+            ),
+            TaskSummary::for_test("docs", "build", cached, execution(1000, 1010, 0)),
+            // Tasks that didn't run aren't test cases
+            TaskSummary::for_test("docs", "lint", TaskCacheSummary::cache_miss(), None),
+        ];
+        let start = Local::now();
+        let run_execution = ExecutionSummary::new(
+            "turbo run build test".to_string(),
+            SummaryState::default(),
+            None,
+            1,
+            start,
+            start + Duration::milliseconds(2500),
+        );
+
+        assert_eq!(
+            render(Some(&run_execution), &tasks, repo_root),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="turbo run build test" tests="3" failures="1" time="2.500">
+  <testsuite name="docs" tests="1" failures="0" time="0.010">
+    <testcase classname="docs" name="build" time="0.010"/>
+  </testsuite>
+  <testsuite name="web" tests="2" failures="1" time="2.000">
+    <testcase classname="web" name="build" time="1.500"/>
+    <testcase classname="web" name="test" time="0.500">
+      <failure message="exited with code 1">expected 1 to be &lt;2&gt;
+</failure>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#
+        );
+    }
+
+    #[test]
+    fn test_xml_escape() {
+        assert_eq!(
+            xml_escape("expected <div> & got \u{1b}\u{7}\"nothing\"\n"),
+            "expected &lt;div&gt; &amp; got &quot;nothing&quot;\n"
+        );
+    }
+
+    #[test]
+    fn test_seconds() {
+        assert_eq!(seconds(1234), "1.234");
+        assert_eq!(seconds(0), "0.000");
+    }
+}
@@ -6,9 +6,13 @@
 #[allow(dead_code)]
 mod duration;
 mod execution;
+mod github;
 mod global_hash;
+mod junit;
+mod report;
 mod scm;
 mod spaces;
+mod statsd;
 mod task;
 mod task_factory;
 use std::{collections::HashSet, io, io::Write};
@@ -23,13 +27,13 @@ pub use spaces::{SpacesTaskClient, SpacesTaskInformation};
 use svix_ksuid::{Ksuid, KsuidLike};
 use tabwriter::TabWriter;
 use thiserror::Error;
-use tracing::{error, log::warn};
+use tracing::{debug, error, log::warn};
 use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf, AnchoredSystemPath};
 use turborepo_api_client::{spaces::CreateSpaceRunPayload, APIAuth, APIClient};
 use turborepo_env::EnvironmentVariableMap;
 use turborepo_repository::package_graph::{PackageGraph, PackageName};
 use turborepo_scm::SCM;
-use turborepo_ui::{color, cprintln, cwriteln, BOLD, BOLD_CYAN, GREY, UI};
+use turborepo_ui::{color, cprintln, cwriteln, json::print_line, BOLD, BOLD_CYAN, GREY, UI};
 
 use self::{
     execution::TaskState, task::SinglePackageTaskSummary, task_factory::TaskSummaryFactory,
@@ -37,7 +41,8 @@ use self::{
 use super::task_id::TaskId;
 use crate::{
     cli,
-    cli::DryRunMode,
+    cli::{CiVendor, DryRunMode},
+    daemon::{proto, DaemonClient, DaemonConnector},
     engine::Engine,
     opts::RunOpts,
     run::summary::{
@@ -128,6 +133,16 @@ pub struct RunSummary<'a> {
     run_type: RunType,
     #[serde(skip)]
     spaces_client_handle: Option<SpacesClientHandle>,
+    #[serde(skip)]
+    daemon_client: Option<DaemonClient<DaemonConnector>>,
+    #[serde(skip)]
+    statsd_address: Option<&'a str>,
+    #[serde(skip)]
+    report_path: Option<&'a str>,
+    #[serde(skip)]
+    junit_path: Option<&'a str>,
+    #[serde(skip)]
+    github_actions: bool,
 }
 
 /// We use this to track the run, so it's constructed before the run.
@@ -138,6 +153,7 @@ pub struct RunTracker {
     started_at: DateTime<Local>,
     execution_tracker: ExecutionTracker,
     spaces_client_handle: Option<SpacesClientHandle>,
+    daemon_client: Option<DaemonClient<DaemonConnector>>,
     user: String,
     synthesized_command: String,
 }
@@ -156,6 +172,7 @@ impl RunTracker {
         api_auth: Option<APIAuth>,
         user: String,
         scm: &SCM,
+        daemon_client: Option<DaemonClient<DaemonConnector>>,
     ) -> Self {
         let scm = SCMState::get(env_at_execution_start, scm, repo_root);
 
@@ -183,6 +200,7 @@ impl RunTracker {
             user,
             synthesized_command,
             spaces_client_handle,
+            daemon_client,
         }
     }
 
@@ -201,6 +219,7 @@ impl RunTracker {
         package_inference_root: Option<&'a AnchoredSystemPath>,
         exit_code: i32,
         end_time: DateTime<Local>,
+        interrupted_at: Option<DateTime<Local>>,
         run_opts: &'a RunOpts,
         packages: HashSet<PackageName>,
         global_hash_summary: GlobalHashSummary<'a>,
@@ -216,7 +235,7 @@ impl RunTracker {
             Some(DryRunMode::Text) => RunType::DryText,
         };
 
-        let summary_state = self.execution_tracker.finish().await?;
+        let summary_state = self.execution_tracker.finish(interrupted_at).await?;
 
         let tasks = summary_state
             .tasks
@@ -250,6 +269,11 @@ impl RunTracker {
             should_save,
             run_type,
             spaces_client_handle: self.spaces_client_handle,
+            daemon_client: self.daemon_client,
+            statsd_address: run_opts.statsd_address.as_deref(),
+            report_path: run_opts.report.as_deref(),
+            junit_path: run_opts.junit.as_deref(),
+            github_actions: run_opts.ci == Some(CiVendor::Github),
         })
     }
 
@@ -267,6 +291,7 @@ impl RunTracker {
     pub async fn finish<'a>(
         self,
         exit_code: i32,
+        interrupted_at: Option<DateTime<Local>>,
         pkg_dep_graph: &PackageGraph,
         ui: UI,
         repo_root: &'a AbsoluteSystemPath,
@@ -296,6 +321,7 @@ impl RunTracker {
                 package_inference_root,
                 exit_code,
                 end_time,
+                interrupted_at,
                 run_opts,
                 packages,
                 global_hash_summary,
@@ -391,20 +417,74 @@ impl<'a> RunSummary<'a> {
             }
         }
 
+        if let Some(report_path) = self.report_path {
+            if let Err(err) = report::write(&self, report_path) {
+                warn!("Error writing run report: {}", err)
+            }
+        }
+
+        if let Some(junit_path) = self.junit_path {
+            if let Err(err) = junit::write(&self, junit_path) {
+                warn!("Error writing JUnit results: {}", err)
+            }
+        }
+
         if let Some(execution) = &self.execution {
             let path = self.get_path();
             let failed_tasks = self.get_failed_tasks();
             execution.print(ui, path, failed_tasks);
         }
 
+        if self.github_actions {
+            if let Err(err) = github::write(&self) {
+                warn!("Error writing GitHub Actions output: {}", err)
+            }
+        }
+
         if let Some(spaces_client_handle) = self.spaces_client_handle.take() {
             self.send_to_space(spaces_client_handle, end_time, exit_code)
                 .await;
         }
 
+        if let Some(daemon_client) = self.daemon_client.take() {
+            self.send_to_daemon(daemon_client, exit_code).await;
+        }
+
+        if let Some(address) = self.statsd_address {
+            let metrics = statsd::run_metrics(&self, exit_code);
+            if let Err(err) = statsd::send(address, &metrics).await {
+                warn!("Error sending metrics to {}: {}", address, err);
+            }
+        }
+
         Ok(())
     }
 
+    // Reports the results of the run to the daemon, which aggregates them into its
+    // metrics
+    async fn send_to_daemon(
+        &self,
+        mut daemon_client: DaemonClient<DaemonConnector>,
+        exit_code: i32,
+    ) {
+        let tasks = self
+            .tasks
+            .iter()
+            .filter_map(|task| {
+                let execution = task.shared.execution.as_ref()?;
+                Some(proto::TaskResult {
+                    cache_hit: task.shared.cache.is_hit(),
+                    failed: execution.is_failure(),
+                    duration_msec: (execution.end_time - execution.start_time).max(0) as u64,
+                })
+            })
+            .collect();
+        // Older daemons don't collect metrics, which isn't worth failing the run for
+        if let Err(err) = daemon_client.notify_run_finished(exit_code, tasks).await {
+            debug!("unable to report run to daemon: {}", err);
+        }
+    }
+
     #[tracing::instrument(skip_all)]
     async fn send_to_space(
         &self,
@@ -430,7 +510,7 @@ impl<'a> RunSummary<'a> {
         Self::print_errors(&result.errors);
 
         if let Some(run) = result.run {
-            println!("Run: {}\n", run.url);
+            print_line(format!("Run: {}\n", run.url));
         }
     }
 
@@ -0,0 +1,287 @@
+//! Self-contained HTML report of a run.
+//!
+//! The report is a single file without external resources, so it can be
+//! uploaded as a CI artifact and opened from anywhere. It shows a timeline of
+//! the tasks of the run, their cache statuses and the logs of the tasks that
+//! failed.
+
+use std::fmt::Write;
+
+use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
+
+use super::{execution::ExecutionSummary, Error, RunSummary, TaskSummary};
+
+const STYLE: &str = r#"
+body { font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Helvetica, Arial, sans-serif; margin: 2rem; color: #111; }
+h1 { font-size: 1.5rem; }
+h2 { font-size: 1.2rem; margin-top: 2rem; }
+table { border-collapse: collapse; width: 100%; }
+th, td { text-align: left; padding: 0.25rem 0.5rem; border-bottom: 1px solid #eee; font-size: 0.875rem; white-space: nowrap; }
+td.timeline { width: 60%; }
+.track { position: relative; height: 1rem; background: #f6f6f6; }
+.bar { position: absolute; top: 0; bottom: 0; min-width: 2px; }
+.hit { background: #3b82f6; }
+.miss { background: #22c55e; }
+.failure { background: #ef4444; }
+pre { background: #111; color: #eee; padding: 1rem; overflow-x: auto; font-size: 0.8rem; }
+"#;
+
+/// Writes the report of `run_summary` to `path`, relative to the repo root if
+/// it isn't absolute.
+pub(super) fn write(run_summary: &RunSummary, path: &str) -> Result<(), Error> {
+    let path = AbsoluteSystemPathBuf::from_unknown(run_summary.repo_root, path);
+    path.ensure_dir()?;
+    path.create_with_contents(render(
+        run_summary.execution.as_ref(),
+        &run_summary.tasks,
+        run_summary.repo_root,
+    ))?;
+    Ok(())
+}
+
+fn render(
+    execution: Option<&ExecutionSummary>,
+    tasks: &[TaskSummary],
+    repo_root: &AbsoluteSystemPath,
+) -> String {
+    let mut out = String::new();
+    let title = execution.map_or("turbo run", |execution| execution.command.as_str());
+
+    out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    writeln!(out, "<title>{}</title>", escape(title)).ok();
+    writeln!(out, "<style>{STYLE}</style>").ok();
+    out.push_str("</head>\n<body>\n");
+    writeln!(out, "<h1>{}</h1>", escape(title)).ok();
+
+    if let Some(execution) = execution {
+        writeln!(
+            out,
+            "<p>{} successful, {} cached, {} failed, {} total in {}</p>",
+            execution.success,
+            execution.cached,
+            execution.failed,
+            execution.attempted,
+            execution.duration,
+        )
+        .ok();
+    }
+
+    let tasks = tasks
+        .iter()
+        .filter(|task| task.shared.execution.is_some())
+        .collect::<Vec<_>>();
+    render_timeline(&mut out, &tasks);
+
+    let failed_tasks = tasks
+        .iter()
+        .filter(|task| {
+            task.shared
+                .execution
+                .as_ref()
+                .map_or(false, |e| e.is_failure())
+        })
+        .collect::<Vec<_>>();
+    if !failed_tasks.is_empty() {
+        out.push_str("<h2>Failures</h2>\n");
+        for task in failed_tasks {
+            render_failure(&mut out, task, repo_root);
+        }
+    }
+
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+fn render_timeline(out: &mut String, tasks: &[&TaskSummary]) {
+    let executions = tasks
+        .iter()
+        .filter_map(|task| task.shared.execution.as_ref());
+    let Some(start) = executions.clone().map(|e| e.start_time).min() else {
+        return;
+    };
+    let end = executions.map(|e| e.end_time).max().unwrap_or(start);
+    // Avoid dividing by zero when every task finished instantly
+    let span = (end - start).max(1) as f64;
+
+    out.push_str("<h2>Timeline</h2>\n<table>\n");
+    out.push_str("<tr><th>Task</th><th>Cache</th><th>Status</th><th>Duration</th><th></th></tr>\n");
+    let mut tasks = tasks.to_vec();
+    tasks.sort_by_key(|task| {
+        task.shared
+            .execution
+            .as_ref()
+            .map(|execution| execution.start_time)
+    });
+    for task in tasks {
+        let Some(execution) = &task.shared.execution else {
+            continue;
+        };
+        let cache = match task.shared.cache.is_hit() {
+            true => "hit",
+            false => "miss",
+        };
+        let (status, class) = match execution.is_failure() {
+            true => ("failed", "failure"),
+            false => ("succeeded", cache),
+        };
+        let left = (execution.start_time - start) as f64 / span * 100.0;
+        let width = (execution.end_time - execution.start_time) as f64 / span * 100.0;
+        writeln!(
+            out,
+            "<tr><td>{}</td><td>{cache}</td><td>{status}</td><td>{}ms</td><td \
+             class=\"timeline\"><div class=\"track\"><div class=\"bar {class}\" style=\"left: \
+             {left:.2}%; width: {width:.2}%\"></div></div></td></tr>",
+            escape(&task.task_id.to_string()),
+            execution.end_time - execution.start_time,
+        )
+        .ok();
+    }
+    out.push_str("</table>\n");
+}
+
+fn render_failure(out: &mut String, task: &TaskSummary, repo_root: &AbsoluteSystemPath) {
+    writeln!(out, "<h3>{}</h3>", escape(&task.task_id.to_string())).ok();
+    if let Some(error) = task
+        .shared
+        .execution
+        .as_ref()
+        .and_then(|execution| execution.error.as_ref())
+    {
+        writeln!(out, "<p>{}</p>", escape(error)).ok();
+    }
+    let log_file = AbsoluteSystemPathBuf::from_unknown(repo_root, &task.shared.log_file);
+    match log_file.read_to_string() {
+        Ok(logs) => {
+            let logs = console::strip_ansi_codes(&logs);
+            writeln!(out, "<pre>{}</pre>", escape(&logs)).ok();
+        }
+        Err(_) => {
+            writeln!(out, "<p>No logs found at {}</p>", escape(log_file.as_str())).ok();
+        }
+    }
+}
+
+pub(super) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{Duration, Local};
+    use tempfile::tempdir;
+    use turbopath::AbsoluteSystemPath;
+    use turborepo_cache::{CacheHitMetadata, CacheSource};
+
+    use super::{escape, render, STYLE};
+    use crate::run::summary::{
+        execution::{ExecutionSummary, SummaryState},
+        task::{TaskCacheSummary, TaskSummary},
+        TaskExecutionSummary,
+    };
+
+    fn execution(start_time: i64, end_time: i64, exit_code: i32) -> Option<TaskExecutionSummary> {
+        Some(TaskExecutionSummary {
+            start_time,
+            end_time,
+            error: None,
+            exit_code: Some(exit_code),
+            interrupted: false,
+        })
+    }
+
+    #[test]
+    fn test_render() {
+        let tmp = tempdir().unwrap();
+        let repo_root = AbsoluteSystemPath::from_std_path(tmp.path()).unwrap();
+        let log_file = repo_root.join_components(&["web", ".turbo", "turbo-test.log"]);
+        log_file.ensure_dir().unwrap();
+        log_file
+            .create_with_contents("\u{1b}[31mexpected 1 to be <2>\u{1b}[0m\n")
+            .unwrap();
+
+        let cached = TaskCacheSummary::from(Some(CacheHitMetadata {
+            source: CacheSource::Local,
+            time_saved: 1500,
+        }));
+        let tasks = [
+            TaskSummary::for_test(
+                "web",
+                "build",
+                TaskCacheSummary::cache_miss(),
+                execution(1000, 2500, 0),
+            ),
+            TaskSummary::for_test(
+                "web",
+                "test",
+                TaskCacheSummary::cache_miss(),
+                execution(2500, 3000, 1),
+            ),
+            TaskSummary::for_test("docs", "build", cached, execution(1000, 1010, 0)),
+            // Tasks that didn't run aren't on the timeline
+            TaskSummary::for_test("docs", "lint", TaskCacheSummary::cache_miss(), None),
+        ];
+        let mut state = SummaryState::default();
+        state.attempted = 3;
+        state.success = 2;
+        state.cached = 1;
+        state.failed = 1;
+        let start = Local::now();
+        let run_execution = ExecutionSummary::new(
+            "turbo run build test".to_string(),
+            state,
+            None,
+            1,
+            start,
+            start + Duration::milliseconds(2500),
+        );
+
+        assert_eq!(
+            render(Some(&run_execution), &tasks, repo_root),
+            format!(
+                r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>turbo run build test</title>
+<style>{STYLE}</style>
+</head>
+<body>
+<h1>turbo run build test</h1>
+<p>2 successful, 1 cached, 1 failed, 3 total in 2.5s</p>
+<h2>Timeline</h2>
+<table>
+<tr><th>Task</th><th>Cache</th><th>Status</th><th>Duration</th><th></th></tr>
+<tr><td>web#build</td><td>miss</td><td>succeeded</td><td>1500ms</td><td class="timeline"><div class="track"><div class="bar miss" style="left: 0.00%; width: 75.00%"></div></div></td></tr>
+<tr><td>docs#build</td><td>hit</td><td>succeeded</td><td>10ms</td><td class="timeline"><div class="track"><div class="bar hit" style="left: 0.00%; width: 0.50%"></div></div></td></tr>
+<tr><td>web#test</td><td>miss</td><td>failed</td><td>500ms</td><td class="timeline"><div class="track"><div class="bar failure" style="left: 75.00%; width: 25.00%"></div></div></td></tr>
+</table>
+<h2>Failures</h2>
+<h3>web#test</h3>
+<pre>expected 1 to be &lt;2&gt;
+</pre>
+</body>
+</html>
+"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            escape("<script>alert('a & b')</script>"),
+            "&lt;script&gt;alert(&#39;a &amp; b&#39;)&lt;/script&gt;"
+        );
+    }
+}
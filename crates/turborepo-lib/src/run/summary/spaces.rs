@@ -148,9 +148,9 @@ impl SpacesClient {
         let space_id = space_id?;
         let is_linked = api_auth.as_ref().map_or(false, |auth| auth.is_linked());
         if !is_linked {
-            eprintln!(
+            turborepo_ui::json::eprint_line(
                 "Error: experimentalSpaceId is enabled, but repo is not linked to API. Run `turbo \
-                 link` or `turbo login` first"
+                 link` or `turbo login` first",
             );
             return None;
         }
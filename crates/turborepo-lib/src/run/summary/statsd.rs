@@ -0,0 +1,201 @@
+//! Emission of run metrics to StatsD.
+//!
+//! When `TURBO_STATSD_ADDRESS` is set, every run sends its duration and
+//! outcome, and the duration and cache status of each of its tasks, to the
+//! StatsD server at that address over UDP. Metrics are tagged in the DogStatsD
+//! format, which the Datadog agent understands. Metrics are sent on a best
+//! effort basis, so failing to send them doesn't fail the run.
+
+use std::{
+    io,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr},
+};
+
+use tokio::net::{lookup_host, UdpSocket};
+
+use super::RunSummary;
+
+/// Larger datagrams might be fragmented or dropped on common networks.
+const MAX_PACKET_SIZE: usize = 1432;
+
+#[derive(Debug, Clone, Copy)]
+enum MetricType {
+    Count,
+    Timing,
+}
+
+fn metric(name: &str, value: i64, metric_type: MetricType, tags: &[(&str, &str)]) -> String {
+    let metric_type = match metric_type {
+        MetricType::Count => "c",
+        MetricType::Timing => "ms",
+    };
+    let tags = tags
+        .iter()
+        .map(|(key, value)| format!("{key}:{}", sanitize_tag(value)))
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("turbo.{name}:{value}|{metric_type}|#{tags}")
+}
+
+// Commas separate tags and pipes separate the fields of a metric
+fn sanitize_tag(value: &str) -> String {
+    value.replace([',', '|', '#', '\n'], "_")
+}
+
+fn outcome(failed: bool) -> &'static str {
+    match failed {
+        true => "failure",
+        false => "success",
+    }
+}
+
+/// The metrics of a finished run, one per line.
+pub(super) fn run_metrics(run_summary: &RunSummary, exit_code: i32) -> Vec<String> {
+    let mut metrics = Vec::new();
+
+    let run_tags = [("outcome", outcome(exit_code != 0))];
+    metrics.push(metric("run.count", 1, MetricType::Count, &run_tags));
+    if let Some(execution) = &run_summary.execution {
+        let duration = execution.end_time - execution.start_time;
+        metrics.push(metric(
+            "run.duration",
+            duration,
+            MetricType::Timing,
+            &run_tags,
+        ));
+    }
+
+    for task in &run_summary.tasks {
+        let Some(execution) = &task.shared.execution else {
+            continue;
+        };
+        let cache = match task.shared.cache.is_hit() {
+            true => "hit",
+            false => "miss",
+        };
+        let tags = [
+            ("package", task.package.as_str()),
+            ("task", task.task.as_str()),
+            ("cache", cache),
+            ("outcome", outcome(execution.is_failure())),
+        ];
+        metrics.push(metric("task.count", 1, MetricType::Count, &tags));
+        metrics.push(metric(
+            "task.duration",
+            execution.end_time - execution.start_time,
+            MetricType::Timing,
+            &tags,
+        ));
+    }
+
+    metrics
+}
+
+// Packs metrics into as few datagrams as possible, separated by newlines
+fn packets(metrics: &[String]) -> Vec<String> {
+    let mut packets = Vec::new();
+    let mut packet = String::new();
+    for metric in metrics {
+        if !packet.is_empty() && packet.len() + 1 + metric.len() > MAX_PACKET_SIZE {
+            packets.push(std::mem::take(&mut packet));
+        }
+        if !packet.is_empty() {
+            packet.push('\n');
+        }
+        packet.push_str(metric);
+    }
+    if !packet.is_empty() {
+        packets.push(packet);
+    }
+    packets
+}
+
+/// Sends `metrics` to the StatsD server at `address`.
+pub(super) async fn send(address: &str, metrics: &[String]) -> io::Result<()> {
+    let target = lookup_host(address).await?.next().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{address} didn't resolve to any address"),
+        )
+    })?;
+    // The socket has to be of the same family as the server
+    let local: IpAddr = match target {
+        SocketAddr::V4(_) => Ipv4Addr::UNSPECIFIED.into(),
+        SocketAddr::V6(_) => Ipv6Addr::UNSPECIFIED.into(),
+    };
+    let socket = UdpSocket::bind((local, 0)).await?;
+    socket.connect(target).await?;
+    for packet in packets(metrics) {
+        socket.send(packet.as_bytes()).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use tokio::net::UdpSocket;
+
+    use super::{metric, packets, send, MetricType, MAX_PACKET_SIZE};
+
+    #[test]
+    fn test_metric() {
+        assert_eq!(
+            metric(
+                "task.duration",
+                1250,
+                MetricType::Timing,
+                &[("package", "@repo/web"), ("task", "build,lint")]
+            ),
+            "turbo.task.duration:1250|ms|#package:@repo/web,task:build_lint"
+        );
+    }
+
+    #[test]
+    fn test_packets() {
+        let metric = "a".repeat(MAX_PACKET_SIZE / 2);
+        let metrics = vec![
+            metric.clone(),
+            metric.clone(),
+            "turbo.run.count:1|c".to_string(),
+        ];
+        assert_eq!(
+            packets(&metrics),
+            vec![metric.clone(), format!("{metric}\nturbo.run.count:1|c")]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send() {
+        let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let address = server.local_addr().unwrap().to_string();
+
+        let metrics = vec![
+            "turbo.run.count:1|c|#outcome:success".to_string(),
+            "turbo.run.duration:20|ms|#outcome:success".to_string(),
+        ];
+        send(&address, &metrics).await.unwrap();
+
+        let mut buf = [0; MAX_PACKET_SIZE];
+        let len = server.recv(&mut buf).await.unwrap();
+        assert_eq!(
+            std::str::from_utf8(&buf[..len]).unwrap(),
+            metrics.join("\n")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_ipv6() {
+        // Not every environment has IPv6 enabled
+        let Ok(server) = UdpSocket::bind("[::1]:0").await else {
+            return;
+        };
+        let address = server.local_addr().unwrap().to_string();
+
+        let metrics = vec!["turbo.run.count:1|c|#outcome:success".to_string()];
+        send(&address, &metrics).await.unwrap();
+
+        let mut buf = [0; MAX_PACKET_SIZE];
+        let len = server.recv(&mut buf).await.unwrap();
+        assert_eq!(std::str::from_utf8(&buf[..len]).unwrap(), metrics[0]);
+    }
+}
@@ -127,6 +127,10 @@ impl TaskCacheSummary {
             source: None,
         }
     }
+
+    pub fn is_hit(&self) -> bool {
+        matches!(self.status, CacheStatus::Hit)
+    }
 }
 
 impl From<Option<CacheHitMetadata>> for TaskCacheSummary {
@@ -199,6 +203,54 @@ impl TaskEnvVarSummary {
     }
 }
 
+#[cfg(test)]
+impl TaskSummary {
+    /// A summary of `package#task` with only the fields that the renderers of
+    /// a run look at. The logs of the task are at
+    /// `{package}/.turbo/turbo-{task}.log`.
+    pub(crate) fn for_test(
+        package: &str,
+        task: &str,
+        cache: TaskCacheSummary,
+        execution: Option<TaskExecutionSummary>,
+    ) -> Self {
+        Self {
+            task_id: TaskId::new(package, task).into_owned(),
+            task: task.to_string(),
+            package: package.to_string(),
+            shared: SharedTaskSummary {
+                hash: String::new(),
+                inputs: BTreeMap::new(),
+                hash_of_external_dependencies: String::new(),
+                cache,
+                command: String::new(),
+                cli_arguments: Vec::new(),
+                outputs: None,
+                excluded_outputs: None,
+                log_file: format!("{package}/.turbo/turbo-{task}.log"),
+                directory: Some(package.to_string()),
+                dependencies: Vec::new(),
+                dependents: Vec::new(),
+                resolved_task_definition: TaskSummaryTaskDefinition::default(),
+                expanded_outputs: Vec::new(),
+                framework: String::new(),
+                env_mode: EnvMode::Strict,
+                environment_variables: TaskEnvVarSummary {
+                    specified: TaskEnvConfiguration {
+                        env: Vec::new(),
+                        pass_through_env: None,
+                    },
+                    configured: Vec::new(),
+                    inferred: Vec::new(),
+                    pass_through: None,
+                },
+                dot_env: None,
+                execution,
+            },
+        }
+    }
+}
+
 impl From<TaskSummary> for SinglePackageTaskSummary {
     fn from(value: TaskSummary) -> Self {
         let TaskSummary {
@@ -282,6 +334,7 @@ impl From<TaskDefinition> for TaskSummaryTaskDefinition {
             mut inputs,
             output_mode,
             persistent,
+            ..
         } = value;
 
         let mut outputs = inclusions;
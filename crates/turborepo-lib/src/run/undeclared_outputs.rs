@@ -0,0 +1,116 @@
+//! Detection of files that a task writes outside of its declared `outputs`.
+//!
+//! These files silently don't get cached, so restoring the task from the
+//! cache produces a different result than running it. To find them, the
+//! package directory of a task is scanned once the task finished, and the
+//! files that were modified after it started are compared against the task's
+//! outputs.
+//!
+//! Only the package directory of the task is scanned. Tasks of the same
+//! package that run concurrently can show up as writing each other's files.
+//! Files that keep their previous modification time, e.g. when extracted from
+//! an archive, aren't detected.
+
+use std::{str::FromStr, time::SystemTime};
+
+use globwalk::{ValidatedGlob, WalkType};
+use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
+
+/// Directories that tools write to as a side effect, which aren't expected to
+/// be declared as outputs.
+const IGNORED_DIRECTORIES: &[&str] = &["node_modules/**", ".turbo/**", ".git/**"];
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("unable to scan {dir} for undeclared outputs: {source}")]
+    Walk {
+        dir: AbsoluteSystemPathBuf,
+        #[source]
+        source: globwalk::WalkError,
+    },
+}
+
+/// The point in time from which on writes to a directory are detected.
+#[derive(Debug)]
+pub struct Snapshot {
+    dir: AbsoluteSystemPathBuf,
+    taken: SystemTime,
+}
+
+impl Snapshot {
+    /// Doesn't touch the file system, the directory is only scanned by
+    /// [Snapshot::written_files].
+    pub fn take(dir: &AbsoluteSystemPath) -> Self {
+        Self {
+            dir: dir.to_owned(),
+            taken: SystemTime::now(),
+        }
+    }
+
+    /// Scans the directory and returns the files that were created or
+    /// modified since the snapshot was taken.
+    ///
+    /// File systems with coarse timestamps round modification times down, so
+    /// files written right before the snapshot are never reported. Files
+    /// written right after it can be missed on such file systems.
+    pub fn written_files(&self) -> Result<Vec<AbsoluteSystemPathBuf>, Error> {
+        let include = [ValidatedGlob::from_str("**").expect("glob is valid")];
+        let exclude = IGNORED_DIRECTORIES
+            .iter()
+            .map(|glob| ValidatedGlob::from_str(glob).expect("glob is valid"))
+            .collect::<Vec<_>>();
+        let paths = globwalk::globwalk(&self.dir, &include, &exclude, WalkType::Files).map_err(
+            |source| Error::Walk {
+                dir: self.dir.clone(),
+                source,
+            },
+        )?;
+
+        let mut written = paths
+            .into_iter()
+            .filter(|path| {
+                // Files can disappear while the directory is scanned
+                path.symlink_metadata()
+                    .and_then(|metadata| metadata.modified())
+                    .map_or(false, |modified| modified >= self.taken)
+            })
+            .collect::<Vec<_>>();
+        written.sort();
+        Ok(written)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tempfile::tempdir;
+    use turbopath::AbsoluteSystemPathBuf;
+
+    use super::Snapshot;
+
+    #[test]
+    fn test_written_files() {
+        let tmp = tempdir().unwrap();
+        let dir = AbsoluteSystemPathBuf::try_from(tmp.path()).unwrap();
+        let unchanged = dir.join_component("package.json");
+        unchanged.create_with_contents("{}").unwrap();
+        let modified = dir.join_components(&["src", "index.ts"]);
+        modified.ensure_dir().unwrap();
+        modified.create_with_contents("export {};").unwrap();
+
+        let snapshot = Snapshot::take(&dir);
+        // Makes the writes visible on file systems with coarse timestamps
+        std::thread::sleep(std::time::Duration::from_millis(1100));
+
+        modified
+            .create_with_contents("export const a = 1;")
+            .unwrap();
+        let created = dir.join_components(&["dist", "index.js"]);
+        created.ensure_dir().unwrap();
+        created.create_with_contents("exports.a = 1;").unwrap();
+        let ignored = dir.join_components(&["node_modules", ".cache", "babel.json"]);
+        ignored.ensure_dir().unwrap();
+        ignored.create_with_contents("{}").unwrap();
+
+        assert_eq!(snapshot.written_files().unwrap(), vec![created, modified]);
+    }
+}
@@ -9,6 +9,7 @@ use std::{
 };
 
 use camino::Utf8PathBuf;
+use clap::ValueEnum;
 use const_format::formatcp;
 use dunce::canonicalize as fs_canonicalize;
 use itertools::Itertools;
@@ -17,7 +18,7 @@ use semver::Version;
 use serde::Deserialize;
 use thiserror::Error;
 use tiny_gradient::{GradientStr, RGB};
-use tracing::debug;
+use tracing::{debug, error};
 use turbo_updater::check_for_updates;
 use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
 use turborepo_repository::{
@@ -26,11 +27,15 @@ use turborepo_repository::{
 };
 use turborepo_ui::UI;
 
-use crate::{cli, get_version, spawn_child, tracing::TurboSubscriber};
+use crate::{
+    cli::{self, LogFormat},
+    get_version, spawn_child,
+    tracing::TurboSubscriber,
+};
 
 #[derive(Debug, Error, Diagnostic)]
 #[error("cannot have multiple `--cwd` flags in command")]
-#[diagnostic(code(turbo::shim::multiple_cwd))]
+#[diagnostic(code(TURBO_CLI_001))]
 pub struct MultipleCwd {
     #[backtrace]
     backtrace: Backtrace,
@@ -53,7 +58,7 @@ pub enum Error {
     #[diagnostic(transparent)]
     MultipleCwd(Box<MultipleCwd>),
     #[error("No value assigned to `--cwd` flag")]
-    #[diagnostic(code(turbo::shim::empty_cwd))]
+    #[diagnostic(code(TURBO_CLI_002))]
     EmptyCwd {
         #[backtrace]
         backtrace: Backtrace,
@@ -66,14 +71,19 @@ pub enum Error {
     #[diagnostic(transparent)]
     Cli(#[from] cli::Error),
     #[error(transparent)]
+    #[diagnostic(code(TURBO_CLI_003))]
     Inference(#[from] turborepo_repository::inference::Error),
     #[error("failed to execute local turbo process")]
+    #[diagnostic(code(TURBO_CLI_004))]
     LocalTurboProcess(#[source] std::io::Error),
     #[error("failed to resolve local turbo path: {0}")]
+    #[diagnostic(code(TURBO_CLI_005))]
     LocalTurboPath(String),
     #[error("failed to resolve repository root: {0}")]
+    #[diagnostic(code(TURBO_CLI_006))]
     RepoRootPath(AbsoluteSystemPathBuf),
     #[error(transparent)]
+    #[diagnostic(code(TURBO_CLI_007))]
     Path(#[from] turbopath::PathError),
 }
 
@@ -89,8 +99,14 @@ static TURBO_PURE_OUTPUT_ARGS: [&str; 6] = [
     "--dry-run=json",
 ];
 
-static TURBO_SKIP_NOTIFIER_ARGS: [&str; 5] =
-    ["--help", "--h", "--version", "--v", "--no-update-notifier"];
+static TURBO_SKIP_NOTIFIER_ARGS: [&str; 6] = [
+    "--help",
+    "--h",
+    "--version",
+    "--v",
+    "--no-update-notifier",
+    "--offline",
+];
 
 fn turbo_version_has_shim(version: &str) -> bool {
     let version = Version::parse(version).unwrap();
@@ -108,6 +124,8 @@ struct ShimArgs {
     invocation_dir: AbsoluteSystemPathBuf,
     skip_infer: bool,
     verbosity: usize,
+    log_format: LogFormat,
+    json: bool,
     force_update_check: bool,
     remaining_turbo_args: Vec<String>,
     forwarded_args: Vec<String>,
@@ -122,6 +140,9 @@ impl ShimArgs {
         let mut skip_infer = false;
         let mut found_verbosity_flag = false;
         let mut verbosity = 0;
+        let mut found_log_format_flag = false;
+        let mut log_format = LogFormat::default();
+        let mut json = false;
         let mut force_update_check = false;
         let mut remaining_turbo_args = Vec::new();
         let mut forwarded_args = Vec::new();
@@ -155,6 +176,24 @@ impl ShimArgs {
 
                 verbosity = verbosity_count.parse::<usize>().unwrap_or(0);
                 remaining_turbo_args.push(arg);
+            } else if arg == "--log-format" {
+                // If we see `--log-format` we expect the next arg to be the format.
+                remaining_turbo_args.push(arg);
+                found_log_format_flag = true
+            } else if arg.starts_with("--log-format=") || found_log_format_flag {
+                let format = if found_log_format_flag {
+                    found_log_format_flag = false;
+                    &arg
+                } else {
+                    arg.strip_prefix("--log-format=").unwrap_or_default()
+                };
+
+                // Invalid formats are reported when the arguments are parsed by clap
+                log_format = LogFormat::from_str(format, true).unwrap_or_default();
+                remaining_turbo_args.push(arg);
+            } else if arg == "--json" {
+                json = true;
+                remaining_turbo_args.push(arg);
             } else if arg == "-v" || arg.starts_with("-vv") {
                 verbosity = arg[1..].len();
                 remaining_turbo_args.push(arg);
@@ -218,6 +257,8 @@ impl ShimArgs {
             invocation_dir,
             skip_infer,
             verbosity,
+            log_format,
+            json,
             force_update_check,
             remaining_turbo_args,
             forwarded_args,
@@ -742,15 +783,48 @@ pub fn run() -> Result<i32, Error> {
             )
         }));
     }
-    let subscriber = TurboSubscriber::new_with_verbosity(args.verbosity, &ui);
+    let log_format = args.log_format;
+    let json = args.json;
+    turborepo_ui::json::set_enabled(log_format == LogFormat::Json);
+    let subscriber = TurboSubscriber::new_with_verbosity(args.verbosity, &ui, log_format);
+
+    match run_with_subscriber(args, &subscriber, ui) {
+        // With --json, stdout holds a single JSON document, which is the error
+        // if the command failed
+        Err(err) if json => {
+            println!("{}", json_error(&err));
+            Ok(1)
+        }
+        // Errors are logged as JSON as well, so that automation can key off
+        // their code instead of their message
+        Err(err) if log_format == LogFormat::Json => {
+            match err.code() {
+                Some(code) => error!(code = %code, "{err}"),
+                None => error!("{err}"),
+            }
+            Ok(1)
+        }
+        result => result,
+    }
+}
+
+fn json_error(err: &Error) -> serde_json::Value {
+    serde_json::json!({
+        "error": {
+            "code": err.code().map(|code| code.to_string()),
+            "message": err.to_string(),
+        }
+    })
+}
 
+fn run_with_subscriber(args: ShimArgs, subscriber: &TurboSubscriber, ui: UI) -> Result<i32, Error> {
     debug!("Global turbo version: {}", get_version());
 
     // If skip_infer is passed, we're probably running local turbo with
     // global turbo having handled the inference. We can run without any
     // concerns.
     if args.skip_infer {
-        return Ok(cli::run(None, &subscriber, ui)?);
+        return Ok(cli::run(None, subscriber, ui)?);
     }
 
     // If the TURBO_BINARY_PATH is set, we do inference but we do not use
@@ -759,31 +833,32 @@ pub fn run() -> Result<i32, Error> {
     if is_turbo_binary_path_set() {
         let repo_state = RepoState::infer(&args.cwd)?;
         debug!("Repository Root: {}", repo_state.root);
-        return Ok(cli::run(Some(repo_state), &subscriber, ui)?);
+        return Ok(cli::run(Some(repo_state), subscriber, ui)?);
     }
 
     match RepoState::infer(&args.cwd) {
         Ok(repo_state) => {
             debug!("Repository Root: {}", repo_state.root);
-            run_correct_turbo(repo_state, args, &subscriber, ui)
+            run_correct_turbo(repo_state, args, subscriber, ui)
         }
         Err(err) => {
             // If we cannot infer, we still run global turbo. This allows for global
             // commands like login/logout/link/unlink to still work
             debug!("Repository inference failed: {}", err);
             debug!("Running command as global turbo");
-            Ok(cli::run(None, &subscriber, ui)?)
+            Ok(cli::run(None, subscriber, ui)?)
         }
     }
 }
 
 #[cfg(test)]
 mod test {
-    use miette::SourceSpan;
+    use miette::{Diagnostic, SourceSpan};
     use test_case::test_case;
+    use turborepo_repository::{package_graph, package_json};
 
-    use super::turbo_version_has_shim;
-    use crate::shim::ShimArgs;
+    use super::{json_error, turbo_version_has_shim, Error};
+    use crate::{cli, config, run, shim::ShimArgs};
 
     #[test]
     fn test_skip_infer_version_constraint() {
@@ -819,4 +894,55 @@ mod test {
             ShimArgs::get_spans_in_args_string(arg_indices, args.into_iter());
         assert_eq!(indices_in_args_string, expected_indices_in_arg_string);
     }
+
+    #[test]
+    fn test_error_codes() {
+        let code = |err: Error| err.code().map(|code| code.to_string());
+
+        assert_eq!(
+            code(Error::LocalTurboPath("turbo".to_string())).as_deref(),
+            Some("TURBO_CLI_005")
+        );
+        // Wrapped errors keep the code of the error they wrap
+        assert_eq!(
+            code(Error::Cli(cli::Error::Config(config::Error::NoTurboJSON))).as_deref(),
+            Some("TURBO_CONFIG_006")
+        );
+        // The same error has the same code, no matter which command failed
+        let package_json_error =
+            || package_json::Error::from(std::io::Error::from(std::io::ErrorKind::NotFound));
+        assert_eq!(
+            code(Error::Cli(cli::Error::PackageJson(package_json_error()))).as_deref(),
+            Some("TURBO_REPO_001")
+        );
+        assert_eq!(
+            code(Error::Cli(cli::Error::Run(run::Error::PackageJson(
+                package_json_error()
+            ))))
+            .as_deref(),
+            Some("TURBO_REPO_001")
+        );
+        assert_eq!(
+            code(Error::Cli(cli::Error::Run(
+                run::Error::PackageGraphBuilder(package_graph::builder::Error::PackageJson(
+                    package_json_error()
+                ))
+            )))
+            .as_deref(),
+            Some("TURBO_REPO_001")
+        );
+    }
+
+    #[test]
+    fn test_json_error() {
+        assert_eq!(
+            json_error(&Error::Cli(cli::Error::Config(config::Error::NoTurboJSON))),
+            serde_json::json!({
+                "error": {
+                    "code": "TURBO_CONFIG_006",
+                    "message": config::Error::NoTurboJSON.to_string(),
+                }
+            })
+        );
+    }
 }
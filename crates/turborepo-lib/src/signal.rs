@@ -1,11 +1,18 @@
 use std::{
     fmt::Debug,
     future::Future,
-    sync::{Arc, Mutex},
+    pin::pin,
+    sync::{Arc, Mutex, OnceLock},
+    time::Duration,
 };
 
-use futures::{stream::FuturesUnordered, StreamExt};
-use tokio::sync::{mpsc, oneshot};
+use chrono::{DateTime, Local};
+use futures::{stream::FuturesUnordered, Stream, StreamExt};
+use tokio::sync::{mpsc, oneshot, watch};
+
+/// How long subscribers should spend wrapping up their work, e.g. finishing
+/// cache uploads, before giving up on a graceful shutdown.
+pub const GRACEFUL_SHUTDOWN_BUDGET: Duration = Duration::from_secs(30);
 
 /// SignalHandler provides a mechanism to subscribe to a future and get alerted
 /// whenever the future completes or the handler gets a close message.
@@ -13,6 +20,8 @@ use tokio::sync::{mpsc, oneshot};
 pub struct SignalHandler {
     state: Arc<Mutex<HandlerState>>,
     close: mpsc::Sender<()>,
+    force: watch::Receiver<bool>,
+    interrupted_at: Arc<OnceLock<DateTime<Local>>>,
 }
 
 #[derive(Debug, Default)]
@@ -31,15 +40,33 @@ impl SignalHandler {
     /// Construct a new SignalHandler that will alert any subscribers when
     /// `signal_source` completes or `close` is called on it.
     pub fn new(signal_source: impl Future<Output = Option<()>> + Send + 'static) -> Self {
+        Self::from_signals(futures::stream::once(async move {
+            signal_source.await;
+        }))
+    }
+
+    /// Construct a new SignalHandler that will alert any subscribers when the
+    /// first signal is received from `signals` or `close` is called on it.
+    /// Any signal received after that forces the shutdown, see
+    /// `force_shutdown`.
+    pub fn from_signals(signals: impl Stream<Item = ()> + Send + 'static) -> Self {
         // think about channel size
         let state = Arc::new(Mutex::new(HandlerState::default()));
         let worker_state = state.clone();
         let (close, mut rx) = mpsc::channel::<()>(1);
+        let (force_tx, force) = watch::channel(false);
+        let interrupted_at = Arc::new(OnceLock::new());
+        let worker_interrupted_at = interrupted_at.clone();
         tokio::spawn(async move {
+            let mut signals = pin!(signals.fuse());
             tokio::select! {
                 // We don't care if we get a signal or if we are unable to receive signals
                 // Either way we start the shutdown.
-                _ = signal_source => {},
+                signal = signals.next() => {
+                    if signal.is_some() {
+                        worker_interrupted_at.set(Local::now()).ok();
+                    }
+                },
                 // We don't care if a close message was sent or if all handlers are dropped.
                 // Either way start the shutdown process.
                 _ = rx.recv() => {}
@@ -63,10 +90,38 @@ impl SignalHandler {
             };
 
             // We don't care if callback gets dropped or if the done signal is sent.
-            while let Some(_fut) = callbacks.next().await {}
+            // Another signal while we wait means the user doesn't want to wait, subscribers
+            // are expected to cut their work short once the shutdown is forced.
+            let mut forced = false;
+            loop {
+                tokio::select! {
+                    callback = callbacks.next() => {
+                        if callback.is_none() {
+                            break;
+                        }
+                    }
+                    Some(()) = signals.next(), if !forced => {
+                        forced = true;
+                        force_tx.send_replace(true);
+                    }
+                }
+            }
+            // Dropping the receiver marks the handler as done
+            drop(rx);
+
+            // Keep listening after we're done, otherwise signals would be swallowed while
+            // the process winds down
+            if !forced && signals.next().await.is_some() {
+                force_tx.send_replace(true);
+            }
         });
 
-        Self { state, close }
+        Self {
+            state,
+            close,
+            force,
+            interrupted_at,
+        }
     }
 
     /// Register a new subscriber
@@ -93,10 +148,28 @@ impl SignalHandler {
     /// Wait until handler is finished and all subscribers finish their cleanup
     /// work
     pub async fn done(&self) {
-        // Receiver is dropped once the worker task completes
+        // Receiver is dropped once the worker finished alerting subscribers
         self.close.closed().await;
     }
 
+    /// Wait until a second signal is received after the shutdown started,
+    /// meaning that cleanup work should be abandoned and any remaining
+    /// processes killed.
+    pub async fn force_shutdown(&self) {
+        let mut force = self.force.clone();
+        let forced = force.wait_for(|forced| *forced).await.is_ok();
+        if !forced {
+            // The worker exited without forcing the shutdown
+            std::future::pending::<()>().await;
+        }
+    }
+
+    /// When a signal started the shutdown, `None` if there wasn't one or the
+    /// shutdown was started by `close`.
+    pub fn interrupted_at(&self) -> Option<DateTime<Local>> {
+        self.interrupted_at.get().copied()
+    }
+
     // Check if the worker thread is done, only meant to be used for assertions in
     // testing
     #[cfg(test)]
@@ -156,6 +229,7 @@ mod test {
         drop(_guard);
         tokio::time::sleep(Duration::from_millis(5)).await;
         handler.done().await;
+        assert!(handler.interrupted_at().is_some());
     }
 
     #[tokio::test]
@@ -179,6 +253,34 @@ mod test {
         );
         drop(_guard);
         handler.done().await;
+        assert!(
+            handler.interrupted_at().is_none(),
+            "closing isn't an interruption"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_second_signal_forces_shutdown() {
+        let (tx, rx) = mpsc::channel(2);
+        let handler =
+            SignalHandler::from_signals(futures::stream::unfold(rx, |mut rx| async move {
+                rx.recv().await.map(|signal| (signal, rx))
+            }));
+        let subscriber = handler.subscribe().unwrap();
+
+        tx.send(()).await.unwrap();
+        let guard = subscriber.listen().await;
+        assert!(
+            tokio::time::timeout(Duration::from_millis(5), handler.force_shutdown())
+                .await
+                .is_err(),
+            "first signal shouldn't force the shutdown"
+        );
+
+        tx.send(()).await.unwrap();
+        handler.force_shutdown().await;
+        drop(guard);
+        handler.done().await;
     }
 
     #[tokio::test]
@@ -0,0 +1,134 @@
+//! "Did you mean" suggestions for task and package names that don't exist.
+
+/// The number of suggestions to show at most.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// The number of owners of a suggestion to list before summarizing the rest.
+const MAX_OWNERS: usize = 3;
+
+/// Returns the candidates that are close enough to `name` to be a likely typo
+/// of it, closest first.
+pub fn closest<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Vec<&'a str> {
+    let name = name.to_lowercase();
+    // Short names are too similar to everything to allow more than one edit
+    let max_distance = (name.chars().count() / 3).max(1);
+
+    let mut matches = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let distance = distance(&name, candidate);
+            (distance <= max_distance).then_some((distance, candidate))
+        })
+        .collect::<Vec<_>>();
+    matches.sort();
+    matches.dedup_by(|a, b| a.1 == b.1);
+    matches
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+// Scoped package names are usually referred to without their scope, so
+// `ui` is as close to `@acme/ui` as it is to `ui`
+fn distance(name: &str, candidate: &str) -> usize {
+    let candidate = candidate.to_lowercase();
+    let unscoped = candidate
+        .strip_prefix('@')
+        .and_then(|scoped| scoped.split_once('/'))
+        .map(|(_, unscoped)| unscoped);
+    let distance = edit_distance(name, &candidate);
+    match unscoped {
+        Some(unscoped) if !name.starts_with('@') => distance.min(edit_distance(name, unscoped)),
+        _ => distance,
+    }
+}
+
+// The edit distance between `a` and `b`, where swapping two adjacent
+// characters counts as a single edit since it's such a common typo
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[a.len()][b.len()]
+}
+
+/// Formats `suggestions` with the names of their owners, e.g. the packages
+/// that have a task, as a help message.
+pub fn did_you_mean<'a>(
+    suggestions: impl IntoIterator<Item = (&'a str, Vec<&'a str>)>,
+) -> Option<String> {
+    let suggestions = suggestions
+        .into_iter()
+        .map(|(suggestion, owners)| match owners.is_empty() {
+            true => format!("`{suggestion}`"),
+            false => format!("`{suggestion}` ({})", format_owners(owners)),
+        })
+        .collect::<Vec<_>>();
+    match suggestions.is_empty() {
+        true => None,
+        false => Some(format!("did you mean {}?", suggestions.join(" or "))),
+    }
+}
+
+fn format_owners(mut owners: Vec<&str>) -> String {
+    owners.sort();
+    owners.dedup();
+    if owners.len() <= MAX_OWNERS {
+        return owners.join(", ");
+    }
+    format!(
+        "{} and {} more",
+        owners[..MAX_OWNERS].join(", "),
+        owners.len() - MAX_OWNERS
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use test_case::test_case;
+
+    use super::{closest, did_you_mean};
+
+    #[test_case("buidl", &["build", "lint", "dev"], &["build"] ; "transposition")]
+    #[test_case("Build", &["build"], &["build"] ; "case")]
+    #[test_case("tst", &["test", "lint"], &["test"] ; "short")]
+    #[test_case("lnt", &["test", "dev"], &[] ; "too far")]
+    #[test_case("ui", &["@acme/ui", "@acme/utils"], &["@acme/ui"] ; "scoped")]
+    #[test_case("buiid", &["build", "bundle"], &["build"] ; "substitution")]
+    #[test_case("docz", &["docs", "doc", "dogs"], &["doc", "docs"] ; "closest first")]
+    fn test_closest(name: &str, candidates: &[&str], expected: &[&str]) {
+        assert_eq!(closest(name, candidates.iter().copied()), expected);
+    }
+
+    #[test]
+    fn test_did_you_mean() {
+        assert_eq!(did_you_mean([]), None);
+        assert_eq!(
+            did_you_mean([("build", vec!["web", "docs"]), ("lint", vec![])]),
+            Some("did you mean `build` (docs, web) or `lint`?".to_string())
+        );
+        assert_eq!(
+            did_you_mean([("build", vec!["a", "b", "c", "d", "e"])]),
+            Some("did you mean `build` (a, b, c and 2 more)?".to_string())
+        );
+    }
+}
@@ -0,0 +1,157 @@
+//! A dump of the state of a run for debugging runs that hang.
+//!
+//! When turbo receives the debug signal (`SIGQUIT` unless another one was
+//! picked with `--debug-signal`), it prints the running tasks, the queued tasks
+//! along with the dependencies they are waiting on and the pending cache writes
+//! to stderr. The run keeps going afterwards. Not supported on Windows, which
+//! doesn't have these signals.
+
+use std::{fmt::Write as _, io::Write, sync::Arc, time::Duration};
+
+use tokio::task::JoinHandle;
+use turborepo_ui::OutputSink;
+
+use super::progress::Progress;
+use crate::{
+    cli::DebugSignal,
+    engine::{Engine, TaskNode},
+    run::RunCache,
+};
+
+/// Stops listening for the debug signal when dropped.
+pub struct DebugDump(JoinHandle<()>);
+
+impl DebugDump {
+    /// Prints the state of the run to `sink` whenever `signal` is received,
+    /// until the dump is dropped.
+    #[cfg(unix)]
+    pub fn start<W: Write + Send + 'static>(
+        signal: DebugSignal,
+        engine: Arc<Engine>,
+        progress: Arc<Progress>,
+        run_cache: Arc<RunCache>,
+        sink: OutputSink<W>,
+    ) -> Option<Self> {
+        use tokio::signal::unix::{signal as listen, SignalKind};
+        use turborepo_ui::OutputClientBehavior;
+
+        let kind = match signal {
+            DebugSignal::Quit => SignalKind::quit(),
+            DebugSignal::Usr1 => SignalKind::user_defined1(),
+            DebugSignal::Usr2 => SignalKind::user_defined2(),
+        };
+        let mut signals = match listen(kind) {
+            Ok(signals) => signals,
+            Err(e) => {
+                tracing::debug!("unable to listen for {signal}: {e}");
+                return None;
+            }
+        };
+        Some(Self(tokio::spawn(async move {
+            while signals.recv().await.is_some() {
+                let state = report(&engine, &progress, run_cache.pending_cache_writes());
+                let logger = sink.logger(OutputClientBehavior::Passthrough);
+                // Failing to print the state is no reason to fail the run
+                let _ = logger
+                    .stderr()
+                    .write_all(turborepo_ui::json::format_output(&state).as_bytes());
+            }
+        })))
+    }
+
+    #[cfg(not(unix))]
+    pub fn start<W: Write + Send + 'static>(
+        _signal: DebugSignal,
+        _engine: Arc<Engine>,
+        _progress: Arc<Progress>,
+        _run_cache: Arc<RunCache>,
+        _sink: OutputSink<W>,
+    ) -> Option<Self> {
+        None
+    }
+}
+
+impl Drop for DebugDump {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+fn report(engine: &Engine, progress: &Progress, pending_cache_writes: usize) -> String {
+    let mut report = format!("turbo: state of the run ({} elapsed)\n", progress.elapsed());
+
+    let running = progress.running();
+    let _ = writeln!(report, "running tasks ({}):", running.len());
+    for (task, elapsed) in running {
+        let elapsed = humantime::format_duration(Duration::from_secs(elapsed.as_secs()));
+        let _ = writeln!(report, "  {task} ({elapsed})");
+    }
+
+    let mut queued = engine
+        .tasks()
+        .filter_map(|node| match node {
+            TaskNode::Task(task) if !progress.is_started(task) => Some(task),
+            _ => None,
+        })
+        .map(|task| {
+            let mut blocked_by = engine
+                .dependencies(task)
+                .into_iter()
+                .flatten()
+                .filter_map(|dependency| match dependency {
+                    TaskNode::Task(dependency) if !progress.is_finished(dependency) => {
+                        Some(dependency.to_string())
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            blocked_by.sort();
+            (task.to_string(), blocked_by)
+        })
+        .collect::<Vec<_>>();
+    queued.sort();
+    let _ = writeln!(report, "queued tasks ({}):", queued.len());
+    for (task, blocked_by) in queued {
+        match blocked_by.is_empty() {
+            true => {
+                let _ = writeln!(report, "  {task} (waiting for a free slot)");
+            }
+            false => {
+                let _ = writeln!(report, "  {task} (waiting on {})", blocked_by.join(", "));
+            }
+        }
+    }
+
+    let _ = writeln!(report, "pending cache writes: {pending_cache_writes}");
+    report
+}
+
+#[cfg(test)]
+mod test {
+    use super::report;
+    use crate::{
+        engine::Engine,
+        run::task_id::TaskId,
+        task_graph::progress::{Progress, TaskOutcome},
+    };
+
+    #[test]
+    fn test_report() {
+        let mut engine = Engine::new();
+        for package in ["a", "b", "c"] {
+            engine.get_index(&TaskId::new(package, "build"));
+        }
+        let engine = engine.seal();
+
+        let progress = Progress::new(3);
+        progress.task_started(&TaskId::new("a", "build"));
+        progress.task_finished(&TaskId::new("a", "build"), TaskOutcome::Succeeded);
+        progress.task_started(&TaskId::new("b", "build"));
+
+        assert_eq!(
+            report(&engine, &progress, 2),
+            "turbo: state of the run (0s elapsed)\nrunning tasks (1):\n  b#build (0s)\nqueued \
+             tasks (1):\n  c#build (waiting for a free slot)\npending cache writes: 2\n"
+        );
+    }
+}
@@ -0,0 +1,184 @@
+//! Running task scripts without a shell.
+//!
+//! Tasks with `"shell": false` have their script split into arguments and
+//! executed directly, instead of through `<package manager> run`, which spawns
+//! the package manager and a shell for every task. Scripts that rely on shell
+//! features, like pipes, variables or globs, still run through the package
+//! manager.
+
+use std::{
+    collections::BTreeMap,
+    ffi::{OsStr, OsString},
+    path::PathBuf,
+};
+
+use turbopath::AbsoluteSystemPath;
+
+/// Characters that have a special meaning for `sh` or `cmd` outside of
+/// quotes.
+const SHELL_METACHARACTERS: &[char] = &[
+    '|', '&', ';', '<', '>', '(', ')', '$', '`', '*', '?', '[', ']', '{', '}', '~', '#', '%', '!',
+    '^', '\n', '\r',
+];
+
+/// Returns the arguments to directly execute `task` with. Returns `None` when
+/// the task needs to run through the package manager, because the script
+/// needs a shell or the package manager would run `pre` or `post` scripts
+/// around it.
+pub(crate) fn direct_args(task: &str, scripts: &BTreeMap<String, String>) -> Option<Vec<String>> {
+    if scripts.contains_key(&format!("pre{task}")) || scripts.contains_key(&format!("post{task}")) {
+        return None;
+    }
+    split_script(scripts.get(task)?)
+}
+
+/// Splits a script into its arguments like `sh` would. Returns `None` when the
+/// script relies on anything but quoting and escaping.
+pub(crate) fn split_script(script: &str) -> Option<Vec<String>> {
+    let mut args = Vec::new();
+    let mut current: Option<String> = None;
+    let mut chars = script.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' | '\t' => {
+                args.extend(current.take());
+            }
+            '\'' => {
+                let arg = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next()? {
+                        '\'' => break,
+                        c => arg.push(c),
+                    }
+                }
+            }
+            '"' => {
+                let arg = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next()? {
+                        '"' => break,
+                        '$' | '`' => return None,
+                        '\\' => match chars.next()? {
+                            c @ ('"' | '\\' | '$' | '`') => arg.push(c),
+                            c => {
+                                arg.push('\\');
+                                arg.push(c);
+                            }
+                        },
+                        c => arg.push(c),
+                    }
+                }
+            }
+            '\\' => match chars.next()? {
+                '\n' => return None,
+                c => current.get_or_insert_with(String::new).push(c),
+            },
+            // Leading variable assignments, like `NODE_ENV=production next build`
+            '=' if args.is_empty() => return None,
+            c if SHELL_METACHARACTERS.contains(&c) => return None,
+            c => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+    args.extend(current);
+    (!args.is_empty()).then_some(args)
+}
+
+/// Returns the `PATH` that the package manager would run scripts with: the
+/// `node_modules/.bin` directories of the workspace and its ancestors up to
+/// the repository root, followed by `path`.
+pub(crate) fn script_path(
+    workspace_directory: &AbsoluteSystemPath,
+    repo_root: &AbsoluteSystemPath,
+    path: Option<&OsStr>,
+) -> Option<OsString> {
+    let bin_dirs = workspace_directory
+        .ancestors()
+        .take_while(|dir| dir.as_std_path().starts_with(repo_root.as_std_path()))
+        .map(|dir| PathBuf::from(dir.join_components(&["node_modules", ".bin"])));
+    let path_dirs = path.map(std::env::split_paths).into_iter().flatten();
+    std::env::join_paths(bin_dirs.chain(path_dirs)).ok()
+}
+
+/// Resolves `program` like a shell would, in the directories of
+/// `search_path`. Programs containing a path separator are resolved relative
+/// to `workspace_directory`.
+pub(crate) fn resolve_program(
+    program: &str,
+    workspace_directory: &AbsoluteSystemPath,
+    search_path: &OsStr,
+) -> Option<PathBuf> {
+    which::which_in(
+        program,
+        Some(search_path),
+        workspace_directory.as_std_path(),
+    )
+    .ok()
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::BTreeMap, path::PathBuf};
+
+    use tempfile::tempdir;
+    use test_case::test_case;
+    use turbopath::AbsoluteSystemPathBuf;
+
+    use super::{direct_args, script_path, split_script};
+
+    #[test_case("tsc", Some(&["tsc"]) ; "single word")]
+    #[test_case("  eslint  . --fix ", Some(&["eslint", ".", "--fix"]) ; "extra whitespace")]
+    #[test_case("echo 'a  b' \"c d\"", Some(&["echo", "a  b", "c d"]) ; "quotes")]
+    #[test_case("echo a' 'b\"c\"", Some(&["echo", "a b", "bc"]) ; "adjacent quotes")]
+    #[test_case("echo '' \"\"", Some(&["echo", "", ""]) ; "empty quotes")]
+    #[test_case(r#"echo a\ b "\"c\\" "\n""#, Some(&["echo", "a b", "\"c\\", "\\n"]) ; "escapes")]
+    #[test_case("jest --env=node", Some(&["jest", "--env=node"]) ; "assignment in argument")]
+    #[test_case("NODE_ENV=production next build", None ; "variable assignment")]
+    #[test_case("tsc && vite build", None ; "and")]
+    #[test_case("cat a | wc -l", None ; "pipe")]
+    #[test_case("echo $HOME", None ; "variable")]
+    #[test_case("echo \"$HOME\"", None ; "variable in double quotes")]
+    #[test_case("echo '$HOME'", Some(&["echo", "$HOME"]) ; "variable in single quotes")]
+    #[test_case("rm -rf dist/*", None ; "glob")]
+    #[test_case("echo %PATH%", None ; "windows variable")]
+    #[test_case("echo 'unterminated", None ; "unterminated quote")]
+    #[test_case("echo a\\", None ; "trailing backslash")]
+    #[test_case("", None ; "empty")]
+    #[test_case("   ", None ; "blank")]
+    fn test_split_script(script: &str, expected: Option<&[&str]>) {
+        let expected = expected.map(|args| args.iter().map(|arg| arg.to_string()).collect());
+        assert_eq!(split_script(script), expected);
+    }
+
+    #[test]
+    fn test_direct_args_with_lifecycle_scripts() {
+        let mut scripts = BTreeMap::new();
+        scripts.insert("build".to_string(), "tsc".to_string());
+        assert_eq!(
+            direct_args("build", &scripts),
+            Some(vec!["tsc".to_string()])
+        );
+        assert_eq!(direct_args("lint", &scripts), None);
+
+        scripts.insert("prebuild".to_string(), "rimraf dist".to_string());
+        assert_eq!(direct_args("build", &scripts), None);
+    }
+
+    #[test]
+    fn test_script_path() {
+        let tmp = tempdir().unwrap();
+        let repo_root = AbsoluteSystemPathBuf::try_from(tmp.path()).unwrap();
+        let workspace = repo_root.join_components(&["packages", "ui"]);
+
+        let path = script_path(&workspace, &repo_root, Some("/usr/bin".as_ref())).unwrap();
+        let dirs = std::env::split_paths(&path).collect::<Vec<_>>();
+        assert_eq!(
+            dirs,
+            vec![
+                PathBuf::from(workspace.join_components(&["node_modules", ".bin"])),
+                PathBuf::from(repo_root.join_components(&["packages", "node_modules", ".bin"])),
+                PathBuf::from(repo_root.join_components(&["node_modules", ".bin"])),
+                "/usr/bin".into(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,54 @@
+//! Progress lines for runs that are quiet for a long time.
+//!
+//! Some CI systems kill jobs that don't produce any output for a while, even
+//! if their tasks are still busy. When no output was written for the
+//! configured interval, a line with the progress of the run is printed
+//! instead.
+
+use std::{io::Write, sync::Arc, time::Duration};
+
+use tokio::task::JoinHandle;
+use turborepo_ui::{OutputClientBehavior, OutputSink};
+
+use super::progress::Progress;
+
+/// Stops printing progress lines when dropped.
+pub struct Heartbeat(JoinHandle<()>);
+
+impl Heartbeat {
+    /// Prints the progress to `sink` whenever it was idle for `interval`,
+    /// until the heartbeat is dropped.
+    pub fn start<W: Write + Send + 'static>(
+        progress: Arc<Progress>,
+        sink: OutputSink<W>,
+        interval: Duration,
+    ) -> Self {
+        Self(tokio::spawn(async move {
+            print_when_idle(&progress, sink, interval).await
+        }))
+    }
+}
+
+impl Drop for Heartbeat {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+async fn print_when_idle<W: Write>(progress: &Progress, sink: OutputSink<W>, interval: Duration) {
+    loop {
+        let idle_time = sink.idle_time();
+        if idle_time < interval {
+            tokio::time::sleep(interval - idle_time).await;
+            continue;
+        }
+        let logger = sink.logger(OutputClientBehavior::Passthrough);
+        // Failing to print the progress is no reason to fail the run
+        let _ = logger
+            .stdout()
+            .write_all(turborepo_ui::json::format_output(&progress.line()).as_bytes());
+        // The progress line counts as output, so the sink is idle again for a
+        // whole interval
+        tokio::time::sleep(interval).await;
+    }
+}
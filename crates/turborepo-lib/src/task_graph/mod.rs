@@ -1,3 +1,8 @@
+mod debug_dump;
+mod direct_exec;
+mod heartbeat;
+mod progress;
+mod status_bar;
 mod visitor;
 
 use std::str::FromStr;
@@ -10,6 +15,7 @@ pub use visitor::{Error as VisitorError, Visitor};
 
 use crate::{
     cli::OutputLogsMode,
+    process::{Executor, Priority, ResourceLimits},
     run::task_id::{TaskId, TaskName},
     turbo_json::RawTaskDefinition,
 };
@@ -73,6 +79,48 @@ pub struct TaskDefinition {
     // Persistent indicates whether the Task is expected to exit or not
     // Tasks marked Persistent do not exit (e.g. --watch mode or dev servers)
     pub persistent: bool,
+
+    // Pty overrides whether the Task runs hooked up to a pseudo-terminal, so tools
+    // detect a TTY and emit colors and progress. By default a pseudo-terminal is
+    // used when turbo itself runs in one.
+    pub(crate) pty: Option<bool>,
+
+    // Shell indicates whether the Task's script runs through the package manager
+    // and a shell. Without it, scripts that only consist of a command and its
+    // arguments are executed directly.
+    pub(crate) shell: bool,
+
+    // ResourceLimits caps the CPU and memory that the Task's processes may use
+    pub(crate) resource_limits: Option<ResourceLimits>,
+
+    // Priority is the scheduling priority of the Task's processes
+    pub(crate) priority: Priority,
+
+    // Executor runs the Task's command somewhere other than on the host, e.g. in a
+    // container. Hashing and caching still happen on the host.
+    pub(crate) executor: Option<Executor>,
+
+    // Hooks are commands that run before and after the Task's command
+    pub(crate) hooks: Option<TaskHooks>,
+
+    // Command replaces the Task's script on the current platform. It runs through
+    // the shell in the package directory.
+    pub(crate) command: Option<String>,
+
+    // PassThroughArgs are passed to the Task's script on the current platform,
+    // ahead of the arguments passed after `--`
+    pub(crate) pass_through_args: Vec<String>,
+}
+
+// TaskHooks are shell commands that run in the package directory before and
+// after the Task's command, e.g. to start and stop a database it needs. They
+// only run when the Task is executed, not when it is restored from the cache.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TaskHooks {
+    pub before: Option<String>,
+    pub after: Option<String>,
+    // Whether the hooks are part of the Task's hash
+    pub hash: bool,
 }
 
 impl Default for TaskDefinition {
@@ -88,6 +136,14 @@ impl Default for TaskDefinition {
             output_mode: Default::default(),
             persistent: Default::default(),
             dot_env: Default::default(),
+            pty: Default::default(),
+            shell: true,
+            resource_limits: Default::default(),
+            priority: Default::default(),
+            executor: Default::default(),
+            hooks: Default::default(),
+            command: Default::default(),
+            pass_through_args: Default::default(),
         }
     }
 }
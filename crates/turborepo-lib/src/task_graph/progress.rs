@@ -0,0 +1,185 @@
+//! Progress of the tasks of a run, as reported by the heartbeat, the status
+//! bar and the debug dump.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::run::task_id::TaskId;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TaskOutcome {
+    Succeeded,
+    Cached,
+    Failed,
+}
+
+#[derive(Debug)]
+pub struct Progress {
+    start: Instant,
+    total: usize,
+    started: AtomicUsize,
+    finished: AtomicUsize,
+    failed: AtomicUsize,
+    cached: AtomicUsize,
+    running: Mutex<HashMap<TaskId<'static>, Instant>>,
+    done: Mutex<HashSet<TaskId<'static>>>,
+}
+
+impl Progress {
+    pub fn new(total: usize) -> Self {
+        Self {
+            start: Instant::now(),
+            total,
+            started: AtomicUsize::new(0),
+            finished: AtomicUsize::new(0),
+            failed: AtomicUsize::new(0),
+            cached: AtomicUsize::new(0),
+            running: Mutex::default(),
+            done: Mutex::default(),
+        }
+    }
+
+    pub fn task_started(&self, task: &TaskId<'static>) {
+        self.running
+            .lock()
+            .expect("lock poisoned")
+            .insert(task.clone(), Instant::now());
+        self.started.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn task_finished(&self, task: &TaskId<'static>, outcome: TaskOutcome) {
+        self.running.lock().expect("lock poisoned").remove(task);
+        self.done
+            .lock()
+            .expect("lock poisoned")
+            .insert(task.clone());
+        match outcome {
+            TaskOutcome::Succeeded => (),
+            TaskOutcome::Cached => {
+                self.cached.fetch_add(1, Ordering::Relaxed);
+            }
+            TaskOutcome::Failed => {
+                self.failed.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        self.finished.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// The tasks that are running along with how long they have been running
+    /// for, longest running first.
+    pub fn running(&self) -> Vec<(TaskId<'static>, Duration)> {
+        let mut running = self
+            .running
+            .lock()
+            .expect("lock poisoned")
+            .iter()
+            .map(|(task, start)| (task.clone(), start.elapsed()))
+            .collect::<Vec<_>>();
+        running.sort_by(|(a_task, a), (b_task, b)| b.cmp(a).then_with(|| a_task.cmp(b_task)));
+        running
+    }
+
+    pub fn is_started(&self, task: &TaskId<'static>) -> bool {
+        self.is_finished(task)
+            || self
+                .running
+                .lock()
+                .expect("lock poisoned")
+                .contains_key(task)
+    }
+
+    pub fn is_finished(&self, task: &TaskId<'static>) -> bool {
+        self.done.lock().expect("lock poisoned").contains(task)
+    }
+
+    pub(crate) fn elapsed(&self) -> String {
+        humantime::format_duration(Duration::from_secs(self.start.elapsed().as_secs())).to_string()
+    }
+
+    /// A line for the heartbeat, e.g.
+    /// `turbo: 2 running, 1 completed, 2 remaining (5s elapsed)`
+    pub fn line(&self) -> String {
+        let started = self.started.load(Ordering::Relaxed);
+        let finished = self.finished.load(Ordering::Relaxed);
+        format!(
+            "turbo: {} running, {} completed, {} remaining ({} elapsed)\n",
+            started.saturating_sub(finished),
+            finished,
+            self.total.saturating_sub(started),
+            self.elapsed(),
+        )
+    }
+
+    /// A status for the status bar, e.g.
+    /// `turbo: 2 running, 3 queued, 4 passed, 1 failed, 50% cached (1m 5s)`.
+    /// It doesn't end in a newline.
+    pub fn status(&self) -> String {
+        let started = self.started.load(Ordering::Relaxed);
+        let finished = self.finished.load(Ordering::Relaxed);
+        let failed = self.failed.load(Ordering::Relaxed);
+        let cached = self.cached.load(Ordering::Relaxed);
+        let mut status = format!(
+            "turbo: {} running, {} queued, {} passed, {} failed",
+            started.saturating_sub(finished),
+            self.total.saturating_sub(started),
+            finished.saturating_sub(failed),
+            failed,
+        );
+        if finished > 0 {
+            status.push_str(&format!(", {}% cached", cached * 100 / finished));
+        }
+        status.push_str(&format!(" ({})", self.elapsed()));
+        status
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Progress, TaskOutcome};
+    use crate::run::task_id::TaskId;
+
+    fn task(package: &str) -> TaskId<'static> {
+        TaskId::new(package, "build").into_owned()
+    }
+
+    #[test]
+    fn test_line() {
+        let progress = Progress::new(5);
+        progress.task_started(&task("a"));
+        progress.task_started(&task("b"));
+        progress.task_started(&task("c"));
+        progress.task_finished(&task("a"), TaskOutcome::Succeeded);
+
+        assert_eq!(
+            progress.line(),
+            "turbo: 2 running, 1 completed, 2 remaining (0s elapsed)\n"
+        );
+    }
+
+    #[test]
+    fn test_status() {
+        let progress = Progress::new(6);
+        assert_eq!(
+            progress.status(),
+            "turbo: 0 running, 6 queued, 0 passed, 0 failed (0s)"
+        );
+
+        for package in ["a", "b", "c", "d", "e"] {
+            progress.task_started(&task(package));
+        }
+        progress.task_finished(&task("a"), TaskOutcome::Cached);
+        progress.task_finished(&task("b"), TaskOutcome::Succeeded);
+        progress.task_finished(&task("c"), TaskOutcome::Failed);
+
+        assert_eq!(
+            progress.status(),
+            "turbo: 2 running, 1 queued, 2 passed, 1 failed, 33% cached (0s)"
+        );
+    }
+}
@@ -0,0 +1,73 @@
+//! A status line below the task logs, enabled with `--status-bar`.
+//!
+//! On big runs it's hard to tell from the logs alone how far along the run
+//! is. The status bar shows the progress of the run on the last line of the
+//! terminal and is redrawn below any output of the tasks. It's only shown when
+//! the output is a terminal, as the control sequences that redraw it would end
+//! up in logs otherwise.
+
+use std::{io::Write, sync::Arc, time::Duration};
+
+use tokio::task::JoinHandle;
+use turborepo_ui::OutputSink;
+
+use super::progress::Progress;
+
+const REFRESH_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Removes the status bar when dropped.
+pub struct StatusBar<W: Write> {
+    task: JoinHandle<()>,
+    sink: OutputSink<W>,
+}
+
+impl<W: Write + Send + 'static> StatusBar<W> {
+    pub fn start(progress: Arc<Progress>, sink: OutputSink<W>) -> Self {
+        let task = {
+            let sink = sink.clone();
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(REFRESH_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    let status = fit_to_terminal(progress.status());
+                    // Failing to draw the status is no reason to fail the run
+                    let _ = sink.set_status(Some(status));
+                }
+            })
+        };
+        Self { task, sink }
+    }
+}
+
+impl<W: Write> Drop for StatusBar<W> {
+    fn drop(&mut self) {
+        self.task.abort();
+        let _ = self.sink.set_status(None);
+    }
+}
+
+// A status that wraps would leave its first lines behind when it's redrawn
+fn fit_to_terminal(status: String) -> String {
+    match console::Term::stdout().size_checked() {
+        Some((_, cols)) => truncate(status, usize::from(cols).saturating_sub(1)),
+        None => status,
+    }
+}
+
+fn truncate(status: String, width: usize) -> String {
+    match status.char_indices().nth(width) {
+        Some((index, _)) => status[..index].to_string(),
+        None => status,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::truncate;
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("turbo: 1 running".to_string(), 8), "turbo: 1");
+        assert_eq!(truncate("turbo".to_string(), 8), "turbo");
+    }
+}
@@ -1,44 +1,64 @@
 use std::{
     borrow::Cow,
-    collections::HashSet,
-    io::Write,
+    collections::{BTreeMap, HashSet},
+    ffi::OsString,
+    io::{IsTerminal, Write},
     sync::{Arc, Mutex, OnceLock},
     time::{Duration, Instant},
 };
 
+use chrono::{DateTime, Local};
 use console::{Style, StyledObject};
 use futures::{stream::FuturesUnordered, StreamExt};
 use regex::Regex;
 use tokio::sync::{mpsc, oneshot};
 use tracing::{debug, error, Instrument, Span};
 use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf, AnchoredSystemPath};
-use turborepo_ci::{Vendor, VendorBehavior};
+use turborepo_ci::VendorBehavior;
 use turborepo_env::{EnvironmentVariableMap, ResolvedEnvMode};
 use turborepo_repository::{
-    package_graph::{PackageGraph, PackageName, ROOT_PKG_NAME},
+    package_graph::{PackageGraph, PackageInfo, PackageName, ROOT_PKG_NAME},
     package_manager::PackageManager,
 };
 use turborepo_telemetry::events::{
     generic::GenericEventBuilder, task::PackageTaskEventBuilder, EventBuilder, TrackedErrors,
 };
-use turborepo_ui::{ColorSelector, OutputClient, OutputSink, OutputWriter, PrefixedUI, UI};
+use turborepo_ui::{
+    color, ColorSelector, OutputClient, OutputSink, OutputWriter, PrefixedUI, GREY, UI,
+};
 use which::which;
 
 use crate::{
-    cli::EnvMode,
-    engine::{Engine, ExecutionOptions, StopExecution},
+    cli::{EnvMode, UndeclaredOutputsMode},
+    engine::{Engine, ExecutionOptions, StopExecution, TaskNode},
+    hash::FileHashes,
     opts::RunOpts,
-    process::{ChildExit, Command, ProcessManager},
+    process::{
+        signal_name, ChildExit, Command, Executor, Priority, ProcessManager, ResourceLimits,
+    },
     run::{
+        attach::StdinRouter,
         global_hash::GlobalHashableInputs,
+        log_sink::LogSinks,
+        remote_exec::{RemoteCommand, RemoteExecutor, RemoteOutputs, RemoteTask},
+        resume::ResumeState,
         summary::{
             self, GlobalHashSummary, RunTracker, SpacesTaskClient, SpacesTaskInformation,
             TaskExecutionSummary, TaskTracker,
         },
         task_access::TaskAccess,
         task_id::TaskId,
+        undeclared_outputs::Snapshot,
         RunCache, TaskCache,
     },
+    task_graph::{
+        debug_dump::DebugDump,
+        direct_exec,
+        heartbeat::Heartbeat,
+        progress::{Progress, TaskOutcome},
+        status_bar::StatusBar,
+        TaskDefinition, TaskHooks,
+    },
     task_hash::{self, PackageInputsHashes, TaskHashTracker, TaskHashTrackerState, TaskHasher},
 };
 
@@ -47,8 +67,13 @@ pub struct Visitor<'a> {
     color_cache: ColorSelector,
     dry: bool,
     global_env: EnvironmentVariableMap,
+    global_dot_env: EnvironmentVariableMap,
     global_env_mode: EnvMode,
+    log_sinks: LogSinks,
     manager: ProcessManager,
+    stdin_router: Option<StdinRouter>,
+    remote_executor: Option<RemoteExecutor>,
+    resume: ResumeState,
     run_opts: &'a RunOpts,
     package_graph: Arc<PackageGraph>,
     repo_root: &'a AbsoluteSystemPath,
@@ -100,6 +125,11 @@ impl<'a> Visitor<'a> {
         manager: ProcessManager,
         repo_root: &'a AbsoluteSystemPath,
         global_env: EnvironmentVariableMap,
+        global_dot_env: EnvironmentVariableMap,
+        log_sinks: LogSinks,
+        stdin_router: Option<StdinRouter>,
+        remote_executor: Option<RemoteExecutor>,
+        resume: ResumeState,
     ) -> Self {
         let task_hasher = TaskHasher::new(
             package_inputs_hashes,
@@ -114,7 +144,11 @@ impl<'a> Visitor<'a> {
             color_cache,
             dry: false,
             global_env_mode,
+            log_sinks,
             manager,
+            stdin_router,
+            remote_executor,
+            resume,
             run_opts,
             package_graph,
             repo_root,
@@ -125,6 +159,7 @@ impl<'a> Visitor<'a> {
             task_hasher,
             ui,
             global_env,
+            global_dot_env,
         }
     }
 
@@ -146,6 +181,35 @@ impl<'a> Visitor<'a> {
 
         let factory = ExecContextFactory::new(self, errors.clone(), self.manager.clone(), &engine);
 
+        let progress = Arc::new(Progress::new(
+            engine
+                .tasks()
+                .filter(|task| matches!(task, TaskNode::Task(_)))
+                .count(),
+        ));
+        let heartbeat = self
+            .run_opts
+            .heartbeat
+            .filter(|_| !self.dry)
+            .map(|interval| Heartbeat::start(progress.clone(), self.sink.clone(), interval));
+        // The status bar redraws in place, which would break up JSON lines
+        let status_bar = (self.run_opts.status_bar
+            && !self.dry
+            && !turborepo_ui::json::is_enabled()
+            && std::io::stdout().is_terminal())
+        .then(|| StatusBar::start(progress.clone(), self.sink.clone()));
+        let debug_dump = (!self.dry)
+            .then(|| {
+                DebugDump::start(
+                    self.run_opts.debug_signal,
+                    engine.clone(),
+                    progress.clone(),
+                    self.run_cache.clone(),
+                    self.sink.clone(),
+                )
+            })
+            .flatten();
+
         while let Some(message) = node_stream.recv().await {
             let span = tracing::debug_span!(parent: &span, "queue_task", task = %message.info);
             let _enter = span.enter();
@@ -213,9 +277,17 @@ impl<'a> Visitor<'a> {
             // We do this calculation earlier than we do in Go due to the `task_hasher`
             // being !Send. In the future we can look at doing this right before
             // task execution instead.
-            let execution_env =
+            let mut execution_env =
                 self.task_hasher
                     .env(&info, task_env_mode, task_definition, &self.global_env)?;
+            // Variables from .env files never override the ones from the environment
+            let workspace_directory = self.repo_root.resolve(workspace_info.package_path());
+            let task_dot_env = task_hash::load_dot_env(
+                &workspace_directory,
+                task_definition.dot_env.as_deref().unwrap_or_default(),
+            )?;
+            execution_env.union_missing(&task_dot_env);
+            execution_env.union_missing(&self.global_dot_env);
 
             let task_cache = self.run_cache.task_cache(
                 task_definition,
@@ -243,13 +315,56 @@ impl<'a> Visitor<'a> {
                     // hashing so that downstream tasks can count on the hash existing
                     //
                     // bail if the script doesn't exist or is empty
-                    if command.map_or(true, |s| s.is_empty()) {
+                    if command.map_or(true, |s| s.is_empty()) && task_definition.command.is_none() {
+                        // There's nothing to run, so the task is already completed
+                        progress.task_started(&info);
+                        progress.task_finished(&info, TaskOutcome::Succeeded);
                         continue;
                     }
 
-                    let workspace_directory = self.repo_root.resolve(workspace_info.package_path());
-
                     let persistent = task_definition.persistent;
+                    let pty = task_definition.pty;
+                    let resource_limits = task_definition.resource_limits;
+                    let priority = task_definition.priority;
+                    let executor = task_definition.executor.clone();
+                    let hooks = task_definition.hooks.clone();
+                    let command = task_definition.command.clone();
+                    let pass_through_args = self.pass_through_args(&info, task_definition);
+                    let direct_args = match task_definition.shell {
+                        true => None,
+                        false => direct_exec::direct_args(
+                            info.task(),
+                            &workspace_info.package_json.scripts,
+                        ),
+                    };
+                    // Persistent tasks never finish, so they can't be executed remotely. The
+                    // outputs of remotely executed tasks are restored from the cache, which
+                    // requires the task to be cached. Hooks set up the local machine and
+                    // commands are specific to its platform, so tasks with either always run
+                    // locally.
+                    let remote = self
+                        .remote_executor
+                        .as_ref()
+                        .filter(|_| {
+                            !persistent
+                                && task_cache.is_restorable()
+                                && task_definition.hooks.is_none()
+                                && task_definition.command.is_none()
+                        })
+                        .map(|remote_executor| {
+                            let remote_task = self.remote_task(
+                                &info,
+                                &task_hash,
+                                task_definition,
+                                workspace_info,
+                                &engine,
+                            );
+                            (remote_executor.clone(), remote_task)
+                        });
+                    let undeclared_outputs = self
+                        .run_opts
+                        .undeclared_outputs
+                        .filter(|_| task_definition.cache && !persistent);
                     let mut exec_context = factory.exec_context(
                         info.clone(),
                         task_hash,
@@ -257,20 +372,37 @@ impl<'a> Visitor<'a> {
                         workspace_directory,
                         execution_env,
                         persistent,
+                        pty,
+                        direct_args,
+                        resource_limits,
+                        priority,
+                        executor,
+                        hooks,
+                        command,
+                        pass_through_args,
+                        remote,
+                        undeclared_outputs,
                         self.task_access.clone(),
                     );
 
-                    let vendor_behavior =
-                        Vendor::infer().and_then(|vendor| vendor.behavior.as_ref());
+                    // Group markers aren't JSON, so they're left out with the JSON log format
+                    let vendor_behavior = self
+                        .run_opts
+                        .ci_vendor()
+                        .filter(|_| !turborepo_ui::json::is_enabled())
+                        .and_then(|vendor| vendor.behavior.as_ref());
 
                     let output_client = self.output_client(&info, vendor_behavior);
                     let tracker = self.run_tracker.track_task(info.clone().into_owned());
                     let spaces_client = self.run_tracker.spaces_task_client();
                     let parent_span = Span::current();
                     let execution_telemetry = package_task_event.child();
+                    let progress = progress.clone();
+                    let task_id = info.clone();
 
+                    progress.task_started(&task_id);
                     tasks.push(tokio::spawn(async move {
-                        exec_context
+                        let outcome = exec_context
                             .execute(
                                 parent_span.id(),
                                 tracker,
@@ -280,6 +412,7 @@ impl<'a> Visitor<'a> {
                                 &execution_telemetry,
                             )
                             .await;
+                        progress.task_finished(&task_id, outcome);
                     }));
                 }
             }
@@ -291,6 +424,9 @@ impl<'a> Visitor<'a> {
         while let Some(result) = tasks.next().await {
             result.expect("task executor panicked");
         }
+        drop(heartbeat);
+        drop(status_bar);
+        drop(debug_dump);
         drop(factory);
 
         // Write out the traced-config.json file if we have one
@@ -316,6 +452,7 @@ impl<'a> Visitor<'a> {
     pub(crate) async fn finish(
         self,
         exit_code: i32,
+        interrupted_at: Option<DateTime<Local>>,
         packages: HashSet<PackageName>,
         global_hash_inputs: GlobalHashableInputs<'_>,
         engine: &Engine,
@@ -338,6 +475,7 @@ impl<'a> Visitor<'a> {
             .run_tracker
             .finish(
                 exit_code,
+                interrupted_at,
                 &package_graph,
                 ui,
                 repo_root,
@@ -411,6 +549,97 @@ impl<'a> Visitor<'a> {
         }
     }
 
+    // Describes the task for a remote worker. Workers have their own toolchain,
+    // so the script always runs through the package manager.
+    fn remote_task(
+        &self,
+        task_id: &TaskId<'static>,
+        task_hash: &str,
+        task_definition: &TaskDefinition,
+        workspace_info: &PackageInfo,
+        engine: &Engine,
+    ) -> RemoteTask {
+        let package_manager = self.package_graph.package_manager();
+        let mut args = vec!["run".to_string(), task_id.task().to_string()];
+        if let Some(pass_through_args) = self.pass_through_args(task_id, task_definition) {
+            args.extend(
+                package_manager
+                    .arg_separator(&pass_through_args)
+                    .map(|s| s.to_string()),
+            );
+            args.extend(pass_through_args);
+        }
+
+        let hash_tracker = self.task_hasher.task_hash_tracker();
+        // Only the variables that the task hash depends on are sent. The rest of the
+        // environment, especially in loose mode, can hold credentials of this machine.
+        let mut hashed_env = self.global_env.clone();
+        if let Some(task_env) = hash_tracker.env_vars(task_id) {
+            hashed_env.union(&task_env.all);
+        }
+        let mut env = hashed_env
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect::<BTreeMap<_, _>>();
+        env.insert("TURBO_HASH".to_string(), task_hash.to_string());
+
+        let inputs = hash_tracker
+            .get_expanded_inputs(task_id)
+            .map(|FileHashes(inputs)| inputs.into_iter().collect())
+            .unwrap_or_default();
+        let dependencies = engine
+            .dependencies(task_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|node| match node {
+                TaskNode::Task(dependency) => {
+                    Some((dependency.to_string(), hash_tracker.hash(dependency)?))
+                }
+                TaskNode::Root => None,
+            })
+            .collect();
+        let outputs =
+            task_definition.repo_relative_hashable_outputs(task_id, workspace_info.package_path());
+
+        RemoteTask {
+            task_id: task_id.to_string(),
+            hash: task_hash.to_string(),
+            command: RemoteCommand {
+                program: package_manager.command().to_string(),
+                args,
+                cwd: workspace_info.package_path().to_unix(),
+                env,
+            },
+            inputs,
+            dependencies,
+            outputs: RemoteOutputs {
+                inclusions: outputs.inclusions,
+                exclusions: outputs.exclusions,
+            },
+        }
+    }
+
+    // The arguments passed to the script of a task, the ones configured for the
+    // current platform followed by the ones passed after `--`
+    fn pass_through_args(
+        &self,
+        task_id: &TaskId,
+        task_definition: &TaskDefinition,
+    ) -> Option<Vec<String>> {
+        let args = self.run_opts.args_for_task(task_id);
+        if task_definition.pass_through_args.is_empty() {
+            return args;
+        }
+        Some(
+            task_definition
+                .pass_through_args
+                .iter()
+                .cloned()
+                .chain(args.into_iter().flatten())
+                .collect(),
+        )
+    }
+
     // Task ID as displayed in error messages
     fn display_task_id(&self, task_id: &TaskId) -> String {
         match self.run_opts.single_package {
@@ -517,14 +746,22 @@ enum TaskErrorCause {
     Spawn { msg: String },
     #[error("command {command} exited ({exit_code})")]
     Exit { command: String, exit_code: i32 },
+    // Reported like a shell would, with an exit code of 128 + the signal number
+    #[error("command {command} was killed by {signal} ({exit_code})")]
+    Signal {
+        command: String,
+        signal: String,
+        exit_code: i32,
+    },
+    #[error("wrote files outside of its outputs: {}", files.join(", "))]
+    UndeclaredOutputs { files: Vec<String> },
+    #[error("{msg}")]
+    Hook { msg: String },
 }
 
 impl TaskError {
     pub fn exit_code(&self) -> Option<i32> {
-        match self.cause {
-            TaskErrorCause::Exit { exit_code, .. } => Some(exit_code),
-            _ => None,
-        }
+        self.cause.exit_code()
     }
 
     fn from_spawn(task_id: String, err: std::io::Error) -> Self {
@@ -554,6 +791,31 @@ impl TaskErrorCause {
     fn from_execution(command: String, exit_code: i32) -> Self {
         TaskErrorCause::Exit { command, exit_code }
     }
+
+    // The error for a command that failed, or None if it succeeded or it's
+    // unknown how it exited
+    fn from_exit(command: String, exit: ChildExit) -> Option<Self> {
+        match exit {
+            ChildExit::Finished(Some(0)) | ChildExit::Finished(None) => None,
+            ChildExit::Finished(Some(exit_code)) => Some(Self::from_execution(command, exit_code)),
+            ChildExit::KilledExternal(Some(signal)) => Some(TaskErrorCause::Signal {
+                command,
+                signal: signal_name(signal)
+                    .map_or_else(|| format!("signal {signal}"), str::to_string),
+                exit_code: 128 + signal,
+            }),
+            ChildExit::Killed | ChildExit::KilledExternal(None) | ChildExit::Failed => None,
+        }
+    }
+
+    fn exit_code(&self) -> Option<i32> {
+        match self {
+            TaskErrorCause::Exit { exit_code, .. } | TaskErrorCause::Signal { exit_code, .. } => {
+                Some(*exit_code)
+            }
+            _ => None,
+        }
+    }
 }
 
 struct ExecContextFactory<'a> {
@@ -587,10 +849,19 @@ impl<'a> ExecContextFactory<'a> {
         workspace_directory: AbsoluteSystemPathBuf,
         execution_env: EnvironmentVariableMap,
         persistent: bool,
+        pty: Option<bool>,
+        direct_args: Option<Vec<String>>,
+        resource_limits: Option<ResourceLimits>,
+        priority: Priority,
+        executor: Option<Executor>,
+        hooks: Option<TaskHooks>,
+        command: Option<String>,
+        pass_through_args: Option<Vec<String>>,
+        remote: Option<(RemoteExecutor, RemoteTask)>,
+        undeclared_outputs: Option<UndeclaredOutputsMode>,
         task_access: TaskAccess,
     ) -> ExecContext {
         let task_id_for_display = self.visitor.display_task_id(&task_id);
-        let pass_through_args = self.visitor.run_opts.args_for_task(&task_id);
         ExecContext {
             engine: self.engine.clone(),
             ui: self.visitor.ui,
@@ -604,6 +875,7 @@ impl<'a> ExecContextFactory<'a> {
             task_cache,
             hash_tracker: self.visitor.task_hasher.task_hash_tracker(),
             package_manager: *self.visitor.package_graph.package_manager(),
+            repo_root: self.visitor.repo_root.to_owned(),
             workspace_directory,
             manager: self.manager.clone(),
             task_hash,
@@ -612,7 +884,23 @@ impl<'a> ExecContextFactory<'a> {
             pass_through_args,
             errors: self.errors.clone(),
             persistent,
+            pty,
+            interleaved_output: matches!(
+                self.visitor.run_opts.log_order,
+                crate::opts::ResolvedLogOrder::Stream
+            ),
+            direct_args,
+            resource_limits,
+            priority,
+            executor,
+            hooks,
+            command,
+            remote,
+            undeclared_outputs,
+            log_sinks: self.visitor.log_sinks.clone(),
+            stdin_router: self.visitor.stdin_router.clone(),
             task_access,
+            resume: self.visitor.resume.clone(),
         }
     }
 
@@ -639,6 +927,7 @@ struct ExecContext {
     task_cache: TaskCache,
     hash_tracker: TaskHashTracker,
     package_manager: PackageManager,
+    repo_root: AbsoluteSystemPathBuf,
     workspace_directory: AbsoluteSystemPathBuf,
     manager: ProcessManager,
     task_hash: String,
@@ -647,7 +936,26 @@ struct ExecContext {
     pass_through_args: Option<Vec<String>>,
     errors: Arc<Mutex<Vec<TaskError>>>,
     persistent: bool,
+    pty: Option<bool>,
+    // The output of tasks is streamed as it is produced, so it is interleaved
+    // with the output of other tasks
+    interleaved_output: bool,
+    // The arguments to execute the script with instead of running it through the
+    // package manager
+    direct_args: Option<Vec<String>>,
+    resource_limits: Option<ResourceLimits>,
+    priority: Priority,
+    executor: Option<Executor>,
+    hooks: Option<TaskHooks>,
+    // The command to run through the shell instead of the script
+    command: Option<String>,
+    // Where to dispatch the task to instead of running it locally
+    remote: Option<(RemoteExecutor, RemoteTask)>,
+    undeclared_outputs: Option<UndeclaredOutputsMode>,
+    log_sinks: LogSinks,
+    stdin_router: Option<StdinRouter>,
     task_access: TaskAccess,
+    resume: ResumeState,
 }
 
 enum ExecOutcome {
@@ -665,9 +973,38 @@ enum ExecOutcome {
 enum SuccessOutcome {
     CacheHit,
     Run,
+    // Completed in the interrupted or failed run that is being resumed
+    Resumed,
 }
 
 impl ExecContext {
+    // Builds the command to execute the script directly, together with the PATH
+    // to run it with. Returns None if the script needs to run through the package
+    // manager.
+    fn direct_command(&self) -> Option<(Command, Option<OsString>)> {
+        let (program, args) = self.direct_args.as_ref()?.split_first()?;
+        let path = self
+            .execution_env
+            .get("PATH")
+            .map(OsString::from)
+            .or_else(|| std::env::var_os("PATH"));
+        let script_path =
+            direct_exec::script_path(&self.workspace_directory, &self.repo_root, path.as_deref())?;
+        let Some(program) =
+            direct_exec::resolve_program(program, &self.workspace_directory, &script_path)
+        else {
+            debug!(
+                "unable to resolve {program} for {}, running it through the package manager",
+                self.task_id
+            );
+            return None;
+        };
+
+        let mut cmd = Command::new(program);
+        cmd.args(args.iter().chain(self.pass_through_args.iter().flatten()));
+        Some((cmd, Some(script_path)))
+    }
+
     pub async fn execute_dry_run(&mut self, tracker: TaskTracker<()>) {
         if let Ok(Some(status)) = self.task_cache.exists().await {
             self.hash_tracker
@@ -684,9 +1021,9 @@ impl ExecContext {
         callback: oneshot::Sender<Result<(), StopExecution>>,
         spaces_client: Option<SpacesTaskClient>,
         telemetry: &PackageTaskEventBuilder,
-    ) {
+    ) -> TaskOutcome {
         let tracker = tracker.start().await;
-        let span = tracing::debug_span!("execute_task", task = %self.task_id.task());
+        let span = tracing::debug_span!("execute_task", task = %self.task_id);
         span.follows_from(parent_span_id);
         let mut result = self
             .execute_inner(&output_client, telemetry)
@@ -705,12 +1042,19 @@ impl ExecContext {
                 None
             }
         };
+        self.log_sinks.finish_task(
+            &self.task_id.to_string(),
+            matches!(result, ExecOutcome::Success(_)),
+        );
 
         match result {
             ExecOutcome::Success(outcome) => {
-                let task_summary = match outcome {
+                self.resume.task_completed(&self.task_id, &self.task_hash);
+                let task_summary = match &outcome {
                     SuccessOutcome::CacheHit => tracker.cached().await,
-                    SuccessOutcome::Run => tracker.build_succeeded(0).await,
+                    SuccessOutcome::Run | SuccessOutcome::Resumed => {
+                        tracker.build_succeeded(0).await
+                    }
                 };
                 callback.send(Ok(())).ok();
                 if let Some(client) = spaces_client {
@@ -718,11 +1062,16 @@ impl ExecContext {
                     let info = self.spaces_task_info(self.task_id.clone(), task_summary, logs);
                     client.finish_task(info).await.ok();
                 }
+                match outcome {
+                    SuccessOutcome::CacheHit => TaskOutcome::Cached,
+                    SuccessOutcome::Run | SuccessOutcome::Resumed => TaskOutcome::Succeeded,
+                }
             }
             ExecOutcome::Internal => {
                 tracker.cancel();
                 callback.send(Err(StopExecution)).ok();
                 self.manager.stop().await;
+                TaskOutcome::Failed
             }
             ExecOutcome::Task { exit_code, message } => {
                 let task_summary = tracker.build_failed(exit_code, message).await;
@@ -754,6 +1103,7 @@ impl ExecContext {
                             tokio::join!(client.finish_task(info), self.manager.stop());
                     }
                 }
+                TaskOutcome::Failed
             }
         }
     }
@@ -769,7 +1119,16 @@ impl ExecContext {
             self.is_github_actions,
             output_client,
             self.pretty_prefix.clone(),
-        );
+        )
+        .with_task(&self.task_id);
+
+        if self.resume.is_completed(&self.task_id, &self.task_hash) {
+            prefixed_ui.output(format!(
+                "completed in the resumed run, skipping {}",
+                color!(self.ui, GREY, "{}", self.task_hash)
+            ));
+            return ExecOutcome::Success(SuccessOutcome::Resumed);
+        }
 
         match self
             .task_cache
@@ -793,26 +1152,68 @@ impl ExecContext {
             }
         }
 
-        let Ok(package_manager_binary) = which(self.package_manager.command()) else {
-            return ExecOutcome::Internal;
+        if let Some((remote_executor, remote_task)) = self.remote.take() {
+            if let Some(outcome) = self
+                .execute_remote(
+                    &remote_executor,
+                    &remote_task,
+                    output_client,
+                    &mut prefixed_ui,
+                    telemetry,
+                )
+                .await
+            {
+                return outcome;
+            }
+        }
+
+        let direct = match &self.command {
+            Some(command) => {
+                let args = self.pass_through_args.as_deref().unwrap_or_default();
+                match shell_command(command, args) {
+                    Ok(cmd) => Some((cmd, None)),
+                    Err(e) => {
+                        let e = std::io::Error::new(
+                            std::io::ErrorKind::NotFound,
+                            format!("unable to find a shell to run the command: {e}"),
+                        );
+                        return self.spawn_failed(&mut prefixed_ui, e);
+                    }
+                }
+            }
+            None => self.direct_command(),
         };
+        let (mut cmd, script_path) = match direct {
+            Some(direct) => direct,
+            None => {
+                let Ok(package_manager_binary) = which(self.package_manager.command()) else {
+                    return ExecOutcome::Internal;
+                };
 
-        let mut cmd = Command::new(package_manager_binary);
-        let mut args = vec!["run".to_string(), self.task_id.task().to_string()];
-        if let Some(pass_through_args) = &self.pass_through_args {
-            args.extend(
-                self.package_manager
-                    .arg_separator(pass_through_args.as_slice())
-                    .map(|s| s.to_string()),
-            );
-            args.extend(pass_through_args.iter().cloned());
-        }
-        cmd.args(args);
+                let mut cmd = Command::new(package_manager_binary);
+                let mut args = vec!["run".to_string(), self.task_id.task().to_string()];
+                if let Some(pass_through_args) = &self.pass_through_args {
+                    args.extend(
+                        self.package_manager
+                            .arg_separator(pass_through_args.as_slice())
+                            .map(|s| s.to_string()),
+                    );
+                    args.extend(pass_through_args.iter().cloned());
+                }
+                cmd.args(args);
+                (cmd, None)
+            }
+        };
         cmd.current_dir(self.workspace_directory.clone());
 
         // We clear the env before populating it with variables we expect
         cmd.env_clear();
         cmd.envs(self.execution_env.iter());
+        // Directly executed scripts don't get the package manager's PATH, so we
+        // set it up ourselves.
+        if let Some(script_path) = script_path {
+            cmd.env("PATH", script_path);
+        }
         // Always last to make sure it overwrites any user configured env var.
         cmd.env("TURBO_HASH", &self.task_hash);
         // enable task access tracing
@@ -830,6 +1231,19 @@ impl ExecContext {
         if self.persistent {
             cmd.open_stdin();
         }
+        if let Some(pty) = self.pty {
+            cmd.pty(pty);
+        }
+        if self.interleaved_output {
+            cmd.sanitize_output();
+        }
+        if let Some(resource_limits) = self.resource_limits {
+            cmd.limit_resources(resource_limits);
+        }
+        cmd.priority(self.priority);
+        if let Some(executor) = &self.executor {
+            cmd = executor.wrap(cmd, &self.repo_root);
+        }
 
         let mut stdout_writer = match self
             .task_cache
@@ -842,30 +1256,70 @@ impl ExecContext {
                 return ExecOutcome::Internal;
             }
         };
+        if !self.log_sinks.is_empty() {
+            stdout_writer.with_sink(self.log_sinks.writer(self.task_id.to_string()));
+        }
+
+        if let Some(before) = self.hooks.as_ref().and_then(|hooks| hooks.before.clone()) {
+            match self.run_hook("before", &before, &mut stdout_writer).await {
+                Some(Ok(())) => (),
+                Some(Err(msg)) => {
+                    if let Err(e) = stdout_writer.flush() {
+                        error!("error flushing logs: {e}");
+                    }
+                    if let Err(e) = self.task_cache.on_error(&mut prefixed_ui) {
+                        error!("error reading logs: {e}");
+                    }
+                    prefixed_ui.error(&msg);
+                    self.errors.lock().expect("lock poisoned").push(TaskError {
+                        task_id: self.task_id_for_display.clone(),
+                        cause: TaskErrorCause::Hook { msg: msg.clone() },
+                    });
+                    return ExecOutcome::Task {
+                        exit_code: None,
+                        message: msg,
+                    };
+                }
+                // Turbo is shutting down
+                None => return ExecOutcome::Internal,
+            }
+        }
+
+        // Taken before the task starts, to find the files that it writes
+        let snapshot = self
+            .undeclared_outputs
+            .map(|_| Snapshot::take(&self.workspace_directory));
 
         let mut process = match self.manager.spawn(cmd, Duration::from_millis(500)) {
             Some(Ok(child)) => child,
             // Turbo was unable to spawn a process
             Some(Err(e)) => {
-                // Note: we actually failed to spawn, but this matches the Go output
-                prefixed_ui.error(format!("command finished with error: {e}"));
-                let error_string = e.to_string();
-                self.errors
-                    .lock()
-                    .expect("lock poisoned")
-                    .push(TaskError::from_spawn(self.task_id_for_display.clone(), e));
-                return ExecOutcome::Task {
-                    exit_code: None,
-                    message: error_string,
-                };
+                self.run_after_hook(&mut stdout_writer, &mut prefixed_ui)
+                    .await;
+                return self.spawn_failed(&mut prefixed_ui, e);
             }
             // Turbo is shutting down
             None => {
+                self.run_after_hook(&mut stdout_writer, &mut prefixed_ui)
+                    .await;
                 return ExecOutcome::Internal;
             }
         };
 
-        let exit_status = match process.wait_with_piped_outputs(&mut stdout_writer).await {
+        // Only persistent tasks keep their stdin open
+        let stdin_router = self.stdin_router.as_ref().filter(|_| self.persistent);
+        if let Some(router) = stdin_router {
+            router.register(self.task_id_for_display.clone(), process.clone());
+        }
+        let wait_result = process.wait_with_piped_outputs(&mut stdout_writer).await;
+        if let Some(router) = stdin_router {
+            router.unregister(&self.task_id_for_display);
+        }
+
+        self.run_after_hook(&mut stdout_writer, &mut prefixed_ui)
+            .await;
+
+        let exit_status = match wait_result {
             Ok(Some(exit_status)) => exit_status,
             Err(e) => {
                 telemetry.track_error(TrackedErrors::FailedToPipeOutputs);
@@ -885,6 +1339,12 @@ impl ExecContext {
 
         match exit_status {
             ChildExit::Finished(Some(0)) => {
+                if let Some(outcome) =
+                    self.check_undeclared_outputs(snapshot, &mut stdout_writer, &mut prefixed_ui)
+                {
+                    return outcome;
+                }
+
                 // Attempt to flush stdout_writer and log any errors encountered
                 if let Err(e) = stdout_writer.flush() {
                     error!("{e}");
@@ -907,7 +1367,13 @@ impl ExecContext {
                 // Return success outcome
                 ExecOutcome::Success(SuccessOutcome::Run)
             }
-            ChildExit::Finished(Some(code)) => {
+            exit_status => {
+                let Some(error) =
+                    TaskErrorCause::from_exit(process.label().to_string(), exit_status)
+                else {
+                    // All of these indicate a failure where we don't know how to recover
+                    return ExecOutcome::Internal;
+                };
                 // If there was an error, flush the buffered output
                 if let Err(e) = stdout_writer.flush() {
                     error!("error flushing logs: {e}");
@@ -915,7 +1381,7 @@ impl ExecContext {
                 if let Err(e) = self.task_cache.on_error(&mut prefixed_ui) {
                     error!("error reading logs: {e}");
                 }
-                let error = TaskErrorCause::from_execution(process.label().to_string(), code);
+                let exit_code = error.exit_code();
                 let message = error.to_string();
                 if self.continue_on_error {
                     prefixed_ui.warn("command finished with error, but continuing...");
@@ -926,16 +1392,234 @@ impl ExecContext {
                     task_id: self.task_id_for_display.clone(),
                     cause: error,
                 });
-                ExecOutcome::Task {
-                    exit_code: Some(code),
+                ExecOutcome::Task { exit_code, message }
+            }
+        }
+    }
+
+    // Runs one of the task's hooks through the shell in the package directory,
+    // with the environment of the task. The output of the hook becomes part of
+    // the task's logs. Returns None if turbo is shutting down, except for the
+    // after hook, which also runs when the task was interrupted.
+    async fn run_hook(
+        &self,
+        name: &str,
+        hook: &str,
+        output: &mut impl Write,
+    ) -> Option<Result<(), String>> {
+        let mut cmd = match shell_command(hook, &[]) {
+            Ok(cmd) => cmd,
+            Err(e) => {
+                return Some(Err(format!(
+                    "unable to find a shell to run {name} hook: {e}"
+                )))
+            }
+        };
+        cmd.current_dir(self.workspace_directory.clone());
+        cmd.env_clear();
+        cmd.envs(self.execution_env.iter());
+        cmd.env("TURBO_HASH", &self.task_hash);
+
+        let process = if name == "after" {
+            self.manager.spawn_teardown(cmd, Duration::from_millis(500))
+        } else {
+            self.manager.spawn(cmd, Duration::from_millis(500))?
+        };
+        let mut process = match process {
+            Ok(child) => child,
+            Err(e) => return Some(Err(format!("unable to spawn {name} hook: {e}"))),
+        };
+        Some(match process.wait_with_piped_outputs(output).await {
+            Ok(Some(ChildExit::Finished(Some(0)))) => Ok(()),
+            Ok(Some(ChildExit::Finished(Some(code)))) => {
+                Err(format!("{name} hook `{hook}` exited ({code})"))
+            }
+            Ok(_) => Err(format!("{name} hook `{hook}` didn't finish")),
+            Err(e) => Err(format!("unable to pipe outputs of {name} hook: {e}")),
+        })
+    }
+
+    // Reports that turbo was unable to spawn the process of the task
+    fn spawn_failed(
+        &self,
+        prefixed_ui: &mut PrefixedUI<impl Write>,
+        e: std::io::Error,
+    ) -> ExecOutcome {
+        // Note: we actually failed to spawn, but this matches the Go output
+        prefixed_ui.error(format!("command finished with error: {e}"));
+        let error_string = e.to_string();
+        self.errors
+            .lock()
+            .expect("lock poisoned")
+            .push(TaskError::from_spawn(self.task_id_for_display.clone(), e));
+        ExecOutcome::Task {
+            exit_code: None,
+            message: error_string,
+        }
+    }
+
+    // The after hook tears down what the before hook set up, so it runs even if
+    // the task failed, couldn't be spawned or was interrupted
+    async fn run_after_hook(
+        &self,
+        output: &mut impl Write,
+        prefixed_ui: &mut PrefixedUI<impl Write>,
+    ) {
+        if let Some(after) = self.hooks.as_ref().and_then(|hooks| hooks.after.clone()) {
+            if let Some(Err(msg)) = self.run_hook("after", &after, output).await {
+                prefixed_ui.warn(msg);
+            }
+        }
+    }
+
+    // Compares the files that the task wrote against its outputs. Returns the
+    // outcome of the task if it failed because of undeclared outputs.
+    fn check_undeclared_outputs(
+        &self,
+        snapshot: Option<Snapshot>,
+        stdout_writer: &mut impl Write,
+        prefixed_ui: &mut PrefixedUI<impl Write>,
+    ) -> Option<ExecOutcome> {
+        let mode = self.undeclared_outputs?;
+        let undeclared = snapshot?
+            .written_files()
+            .map_err(|e| e.to_string())
+            .and_then(|written| {
+                self.task_cache
+                    .undeclared_outputs(written)
+                    .map_err(|e| e.to_string())
+            });
+        let files = match undeclared {
+            Ok(undeclared) if undeclared.is_empty() => return None,
+            Ok(undeclared) => undeclared
+                .iter()
+                .map(|path| path.to_string())
+                .collect::<Vec<_>>(),
+            Err(e) => {
+                debug!(
+                    "unable to check {} for undeclared outputs: {e}",
+                    self.task_id
+                );
+                return None;
+            }
+        };
+
+        match mode {
+            UndeclaredOutputsMode::Warn => {
+                prefixed_ui.warn(format!(
+                    "wrote files outside of its outputs, which won't be cached: {}",
+                    files.join(", ")
+                ));
+                None
+            }
+            UndeclaredOutputsMode::Error => {
+                // Like any other failed task, show the logs before the error
+                if let Err(e) = stdout_writer.flush() {
+                    error!("error flushing logs: {e}");
+                }
+                if let Err(e) = self.task_cache.on_error(prefixed_ui) {
+                    error!("error reading logs: {e}");
+                }
+                let error = TaskErrorCause::UndeclaredOutputs { files };
+                let message = error.to_string();
+                prefixed_ui.error(&message);
+                self.errors.lock().expect("lock poisoned").push(TaskError {
+                    task_id: self.task_id_for_display.clone(),
+                    cause: error,
+                });
+                Some(ExecOutcome::Task {
+                    exit_code: None,
                     message,
+                })
+            }
+        }
+    }
+
+    // Dispatches the task to a remote worker and restores its outputs from the
+    // cache once it finished. Returns None if the task needs to be executed
+    // locally instead.
+    async fn execute_remote(
+        &mut self,
+        remote_executor: &RemoteExecutor,
+        remote_task: &RemoteTask,
+        output_client: &OutputClient<impl std::io::Write>,
+        prefixed_ui: &mut PrefixedUI<impl Write>,
+        telemetry: &PackageTaskEventBuilder,
+    ) -> Option<ExecOutcome> {
+        let result = match remote_executor.execute(remote_task).await {
+            Ok(result) => result,
+            Err(e) => {
+                prefixed_ui.warn(format!("{e}, executing locally"));
+                return None;
+            }
+        };
+
+        if result.exit_code != 0 {
+            // The logs of failed tasks aren't cached, so they're written like the logs
+            // of a task that failed locally
+            match self
+                .task_cache
+                .output_writer(self.pretty_prefix.clone(), output_client.stdout())
+            {
+                Ok(mut writer) => {
+                    if let Err(e) = writer
+                        .write_all(result.logs.as_bytes())
+                        .and_then(|_| writer.flush())
+                    {
+                        error!("error writing logs: {e}");
+                    }
                 }
+                Err(e) => error!("failed to capture outputs for \"{}\": {e}", self.task_id),
+            }
+            if let Err(e) = self.task_cache.on_error(prefixed_ui) {
+                error!("error reading logs: {e}");
+            }
+
+            let command = std::iter::once(&remote_task.command.program)
+                .chain(&remote_task.command.args)
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(" ");
+            let error = TaskErrorCause::from_execution(command, result.exit_code);
+            let message = error.to_string();
+            if self.continue_on_error {
+                prefixed_ui.warn("command finished with error, but continuing...");
+            } else {
+                prefixed_ui.error(format!("command finished with error: {error}"));
+            }
+            self.errors.lock().expect("lock poisoned").push(TaskError {
+                task_id: self.task_id_for_display.clone(),
+                cause: error,
+            });
+            return Some(ExecOutcome::Task {
+                exit_code: Some(result.exit_code),
+                message,
+            });
+        }
+
+        match self
+            .task_cache
+            .restore_outputs(prefixed_ui, telemetry)
+            .await
+        {
+            Ok(Some(_)) => {
+                self.hash_tracker.insert_expanded_outputs(
+                    self.task_id.clone(),
+                    self.task_cache.expanded_outputs().to_vec(),
+                );
+                Some(ExecOutcome::Success(SuccessOutcome::Run))
+            }
+            Ok(None) => {
+                prefixed_ui.warn(
+                    "remote worker didn't upload the outputs to the cache, executing locally",
+                );
+                None
+            }
+            Err(e) => {
+                telemetry.track_error(TrackedErrors::ErrorFetchingFromCache);
+                prefixed_ui.error(format!("error fetching from cache: {e}"));
+                None
             }
-            // All of these indicate a failure where we don't know how to recover
-            ChildExit::Finished(None)
-            | ChildExit::Killed
-            | ChildExit::KilledExternal
-            | ChildExit::Failed => ExecOutcome::Internal,
         }
     }
 
@@ -960,6 +1644,62 @@ impl ExecContext {
     }
 }
 
+// Builds the command to run `script` through the system shell, like package
+// managers run scripts, with `args` passed on to the script.
+fn shell_command(script: &str, args: &[String]) -> Result<Command, which::Error> {
+    let mut cmd;
+    #[cfg(windows)]
+    {
+        let mut script = script.to_string();
+        for arg in args {
+            script.push(' ');
+            script.push_str(&escape_cmd_arg(arg));
+        }
+        cmd = Command::new(which("cmd")?);
+        // The command line is passed as it is, since cmd.exe doesn't understand the
+        // quoting of the C runtime. /s strips the quotes around the script.
+        cmd.raw_args(["/d", "/s", "/c", &format!("\"{script}\"")]);
+    }
+    #[cfg(not(windows))]
+    {
+        // Arguments are passed as positional parameters, so they don't need to be
+        // quoted
+        cmd = Command::new(which("sh")?);
+        cmd.args(["-c", &format!("{script} \"$@\""), "sh"]);
+        cmd.args(args);
+    }
+    Ok(cmd)
+}
+
+// Escapes `arg` so that cmd.exe passes it on to the program of the script as a
+// single argument. It's quoted for the C runtime of the program, and then the
+// characters that cmd.exe would interpret are escaped with a caret.
+#[cfg(any(windows, test))]
+fn escape_cmd_arg(arg: &str) -> String {
+    let mut quoted = String::from("\"");
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        // Backslashes before a quote are escaped, as well as the quote itself
+        if c == '"' {
+            quoted.extend(std::iter::repeat('\\').take(backslashes + 1));
+        }
+        backslashes = if c == '\\' { backslashes + 1 } else { 0 };
+        quoted.push(c);
+    }
+    // As are the backslashes before the closing quote
+    quoted.extend(std::iter::repeat('\\').take(backslashes));
+    quoted.push('"');
+
+    let mut escaped = String::with_capacity(quoted.len());
+    for c in quoted.chars() {
+        if "()[]%!^\"`<>&|;, *?".contains(c) {
+            escaped.push('^');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
 struct DryRunExecContext {
     task_id: TaskId<'static>,
     task_cache: TaskCache,
@@ -976,3 +1716,20 @@ impl DryRunExecContext {
         tracker.dry_run().await;
     }
 }
+
+#[cfg(test)]
+mod test {
+    use test_case::test_case;
+
+    use super::escape_cmd_arg;
+
+    #[test_case("build", r#"^"build^""# ; "plain")]
+    #[test_case("hello world", r#"^"hello^ world^""# ; "whitespace")]
+    #[test_case(r#"say "hi""#, r#"^"say^ \^"hi\^"^""# ; "quotes")]
+    #[test_case(r#"dir\"#, r#"^"dir\\^""# ; "trailing backslash")]
+    #[test_case(r#"a\"b"#, r#"^"a\\\^"b^""# ; "backslash before quote")]
+    #[test_case("50% & more", r#"^"50^%^ ^&^ more^""# ; "cmd metacharacters")]
+    fn test_escape_cmd_arg(arg: &str, expected: &str) {
+        assert_eq!(escape_cmd_arg(arg), expected);
+    }
+}
@@ -1,5 +1,6 @@
 use std::{
     collections::{HashMap, HashSet},
+    hash::Hasher,
     sync::{Arc, Mutex},
 };
 
@@ -7,9 +8,13 @@ use rayon::prelude::*;
 use serde::Serialize;
 use thiserror::Error;
 use tracing::{debug, Span};
-use turbopath::{AbsoluteSystemPath, AnchoredSystemPath, AnchoredSystemPathBuf};
+use turbopath::{
+    AbsoluteSystemPath, AbsoluteSystemPathBuf, AnchoredSystemPath, AnchoredSystemPathBuf,
+    RelativeUnixPathBuf,
+};
 use turborepo_cache::CacheHitMetadata;
 use turborepo_env::{BySource, DetailedMap, EnvironmentVariableMap, ResolvedEnvMode};
+use turborepo_lockfiles::Lockfile;
 use turborepo_repository::package_graph::{PackageInfo, PackageName};
 use turborepo_scm::SCM;
 use turborepo_telemetry::events::{
@@ -47,6 +52,18 @@ pub enum Error {
     Regex(#[from] regex::Error),
     #[error(transparent)]
     Path(#[from] turbopath::PathError),
+    #[error(transparent)]
+    Lockfile(#[from] turborepo_lockfiles::Error),
+    #[error("failed to read {path}: {source}")]
+    DotEnvRead {
+        path: AbsoluteSystemPathBuf,
+        source: std::io::Error,
+    },
+    #[error("failed to load {path}: {source}")]
+    DotEnvParse {
+        path: AbsoluteSystemPathBuf,
+        source: turborepo_env::Error,
+    },
 }
 
 impl TaskHashable<'_> {
@@ -63,14 +80,17 @@ impl TaskHashable<'_> {
 pub struct PackageInputsHashes {
     hashes: HashMap<TaskId<'static>, String>,
     expanded_hashes: HashMap<TaskId<'static>, FileHashes>,
+    // Only packages with patched or overridden external dependencies have an entry
+    dependency_patches_hashes: HashMap<PackageName, String>,
 }
 
 impl PackageInputsHashes {
-    #[tracing::instrument(skip(all_tasks, workspaces, task_definitions, repo_root, scm))]
+    #[tracing::instrument(skip(all_tasks, workspaces, lockfile, task_definitions, repo_root, scm))]
     pub fn calculate_file_hashes<'a>(
         scm: &SCM,
         all_tasks: impl ParallelIterator<Item = &'a TaskNode>,
         workspaces: HashMap<&PackageName, &PackageInfo>,
+        lockfile: Option<&dyn Lockfile>,
         task_definitions: &HashMap<TaskId<'static>, TaskDefinition>,
         repo_root: &AbsoluteSystemPath,
         telemetry: &GenericEventBuilder,
@@ -79,6 +99,19 @@ impl PackageInputsHashes {
 
         let span = Span::current();
 
+        let dependency_patches_hashes = match lockfile {
+            Some(lockfile) => workspaces
+                .iter()
+                .filter_map(|(name, info)| {
+                    let transitive_dependencies = info.transitive_dependencies.as_ref()?;
+                    dependency_patches_hash(scm, repo_root, lockfile, transitive_dependencies)
+                        .transpose()
+                        .map(|hash| hash.map(|hash| ((*name).clone(), hash)))
+                })
+                .collect::<Result<_, _>>()?,
+            None => HashMap::new(),
+        };
+
         let (hashes, expanded_hashes): (HashMap<_, _>, HashMap<_, _>) = all_tasks
             .filter_map(|task| {
                 let span = tracing::info_span!(parent: &span, "calculate_file_hash", ?task);
@@ -154,10 +187,44 @@ impl PackageInputsHashes {
         Ok(PackageInputsHashes {
             hashes,
             expanded_hashes,
+            dependency_patches_hashes,
         })
     }
 }
 
+// Editing a patch file or an override doesn't necessarily change the resolved
+// versions of the patched packages, so we hash them for every package that has
+// them in its closure.
+fn dependency_patches_hash(
+    scm: &SCM,
+    repo_root: &AbsoluteSystemPath,
+    lockfile: &dyn Lockfile,
+    transitive_dependencies: &HashSet<turborepo_lockfiles::Package>,
+) -> Result<Option<String>, Error> {
+    let patches = lockfile.patches_for(transitive_dependencies)?;
+    let overrides = lockfile.overrides_for(transitive_dependencies);
+    if patches.is_empty() && overrides.is_empty() {
+        return Ok(None);
+    }
+
+    let patch_hashes = scm.hash_existing_of(
+        repo_root,
+        patches
+            .iter()
+            .map(|patch| patch.to_anchored_system_path_buf()),
+    )?;
+    let overrides = overrides
+        .iter()
+        .map(|(selector, replacement)| format!("{selector}={replacement}"))
+        .collect::<Vec<_>>();
+
+    Ok(Some(format!(
+        "{}\0{}",
+        FileHashes(patch_hashes).hash(),
+        overrides.join("\0")
+    )))
+}
+
 #[derive(Default, Debug, Clone)]
 pub struct TaskHashTracker {
     state: Arc<Mutex<TaskHashTrackerState>>,
@@ -181,6 +248,7 @@ pub struct TaskHashTrackerState {
 /// Caches package-inputs hashes, and package-task hashes.
 pub struct TaskHasher<'a> {
     hashes: HashMap<TaskId<'static>, String>,
+    dependency_patches_hashes: HashMap<PackageName, String>,
     run_opts: &'a RunOpts,
     env_at_execution_start: &'a EnvironmentVariableMap,
     global_hash: &'a str,
@@ -197,9 +265,11 @@ impl<'a> TaskHasher<'a> {
         let PackageInputsHashes {
             hashes,
             expanded_hashes,
+            dependency_patches_hashes,
         } = package_inputs_hashes;
         Self {
             hashes,
+            dependency_patches_hashes,
             run_opts,
             env_at_execution_start,
             global_hash,
@@ -324,6 +394,11 @@ impl<'a> TaskHasher<'a> {
             package_dir: optional_package_dir,
             hash_of_files,
             external_deps_hash,
+            dependency_patches_hash: self
+                .dependency_patches_hashes
+                .get(&task_id.to_workspace_name())
+                .filter(|_| is_monorepo)
+                .map(|hash| hash.as_str()),
             task: task_id.task(),
             outputs,
 
@@ -339,6 +414,42 @@ impl<'a> TaskHasher<'a> {
         };
 
         let task_hash = task_hashable.calculate_task_hash();
+        // The image of a container determines the toolchain the task runs with. It's
+        // only hashed in when set, to keep the hashes of tasks running on the host
+        // stable.
+        let task_hash = match &task_definition.executor {
+            Some(executor) => extend_hash(&task_hash, &executor.to_string()),
+            None => task_hash,
+        };
+        // Hooks are opted into the hash, as they usually only set up the environment
+        // of the task rather than affect its outputs.
+        let task_hash = match &task_definition.hooks {
+            Some(hooks) if hooks.hash => extend_hash(
+                &task_hash,
+                &format!(
+                    "hooks:{}\0{}",
+                    hooks.before.as_deref().unwrap_or_default(),
+                    hooks.after.as_deref().unwrap_or_default()
+                ),
+            ),
+            _ => task_hash,
+        };
+        // The command and arguments of the current platform change what the task runs,
+        // so tasks on different platforms don't share their outputs.
+        let task_hash = match (
+            &task_definition.command,
+            task_definition.pass_through_args.as_slice(),
+        ) {
+            (None, []) => task_hash,
+            (command, args) => extend_hash(
+                &task_hash,
+                &format!(
+                    "command:{}\0{}",
+                    command.as_deref().unwrap_or_default(),
+                    args.join("\0")
+                ),
+            ),
+        };
 
         self.task_hash_tracker.insert_hash(
             task_id.clone(),
@@ -437,6 +548,37 @@ impl<'a> TaskHasher<'a> {
     }
 }
 
+// Loads the variables defined in the .env files that exist in `dir`. Variables
+// from earlier files take precedence over the ones from later files.
+pub fn load_dot_env(
+    dir: &AbsoluteSystemPath,
+    files: &[RelativeUnixPathBuf],
+) -> Result<EnvironmentVariableMap, Error> {
+    let mut env = EnvironmentVariableMap::default();
+    for file in files {
+        let path = dir.resolve(&file.to_anchored_system_path_buf());
+        let contents = match path.read_to_string() {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(source) => return Err(Error::DotEnvRead { path, source }),
+        };
+        let file_env = EnvironmentVariableMap::from_dot_env(&contents)
+            .map_err(|source| Error::DotEnvParse { path, source })?;
+        env.union_missing(&file_env);
+    }
+    Ok(env)
+}
+
+// Combines the hash of a task with configuration that isn't part of the task
+// hashable, e.g. the executor, so outputs built in a container are never
+// restored for the host or another image.
+fn extend_hash(task_hash: &str, extra: &str) -> String {
+    let mut hasher = twox_hash::XxHash64::with_seed(0);
+    hasher.write(task_hash.as_bytes());
+    hasher.write(extra.as_bytes());
+    hex::encode(hasher.finish().to_be_bytes())
+}
+
 pub fn get_external_deps_hash(
     transitive_dependencies: &Option<HashSet<turborepo_lockfiles::Package>>,
 ) -> String {
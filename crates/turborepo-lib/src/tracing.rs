@@ -5,16 +5,21 @@ use owo_colors::{
     colors::{Black, Default, Red, Yellow},
     Color, OwoColorize,
 };
-use tracing::{field::Visit, metadata::LevelFilter, trace, Event, Level, Subscriber};
+use serde_json::{Map, Value};
+use tracing::{
+    field::{Field, Visit},
+    metadata::LevelFilter,
+    trace, Event, Level, Subscriber,
+};
 use tracing_appender::{non_blocking::NonBlocking, rolling::RollingFileAppender};
 use tracing_chrome::ChromeLayer;
 pub use tracing_subscriber::reload::Error;
 use tracing_subscriber::{
-    filter::Filtered,
+    filter::{Filtered, ParseError},
     fmt::{
         self,
-        format::{DefaultFields, Writer},
-        FmtContext, FormatEvent, FormatFields, MakeWriter,
+        format::{DefaultFields, JsonFields, Writer},
+        FmtContext, FormatEvent, FormatFields, FormattedFields, MakeWriter,
     },
     layer,
     prelude::*,
@@ -24,6 +29,8 @@ use tracing_subscriber::{
 };
 use turborepo_ui::UI;
 
+use crate::cli::LogFormat;
+
 // a lot of types to make sure we record the right relationships
 
 /// Note that we cannot express the type of `std::io::stderr` directly, so
@@ -40,8 +47,9 @@ impl<'a> MakeWriter<'a> for StdErrWrapper {
 
 /// A basic logger that logs to stderr using the TurboFormatter.
 /// The first generic parameter refers to the previous layer, which
-/// is in this case the default layer (`Registry`).
-type StdErrLog = fmt::Layer<Registry, DefaultFields, TurboFormatter, StdErrWrapper>;
+/// is in this case the default layer (`Registry`). The fields of spans are
+/// recorded as JSON, so that the JSON log format can include them.
+type StdErrLog = fmt::Layer<Registry, JsonFields, TurboFormatter, StdErrWrapper>;
 /// We filter this using an EnvFilter.
 type StdErrLogFiltered = Filtered<StdErrLog, EnvFilter, Registry>;
 /// When the `StdErrLogFiltered` is applied to the `Registry`, we get a
@@ -53,10 +61,13 @@ type StdErrLogLayered = layer::Layered<StdErrLogFiltered, Registry>;
 type DaemonLog = fmt::Layer<StdErrLogLayered, DefaultFields, fmt::format::Format, NonBlocking>;
 /// This layer can be reloaded. `None` means the layer is disabled.
 type DaemonReload = reload::Layer<Option<DaemonLog>, StdErrLogLayered>;
+/// The filter can be reloaded as well, so that the filter of a running daemon
+/// can be changed.
+type DaemonFilter = reload::Layer<EnvFilter, StdErrLogLayered>;
 /// We filter this using a custom filter that only logs events
 /// - with evel `TRACE` or higher for the `turborepo` target
 /// - with level `INFO` or higher for all other targets
-type DaemonLogFiltered = Filtered<DaemonReload, EnvFilter, StdErrLogLayered>;
+type DaemonLogFiltered = Filtered<DaemonReload, DaemonFilter, StdErrLogLayered>;
 /// When the `DaemonLogFiltered` is applied to the `StdErrLogLayered`, we get a
 /// `DaemonLogLayered`, which forms the base for the next layer.
 type DaemonLogLayered = layer::Layered<DaemonLogFiltered, StdErrLogLayered>;
@@ -70,8 +81,48 @@ type ChromeReload = reload::Layer<Option<ChromeLog>, DaemonLogLayered>;
 /// `ChromeLogLayered`, which forms the base for the next layer.
 type ChromeLogLayered = layer::Layered<ChromeReload, DaemonLogLayered>;
 
+/// A logger that spits lines into a file in the repository, using the standard
+/// formatter. It is applied on top of the `ChromeLogLayered` layer.
+type DebugLog = fmt::Layer<ChromeLogLayered, DefaultFields, fmt::format::Format, NonBlocking>;
+/// This layer can be reloaded. `None` means the layer is disabled.
+type DebugReload = reload::Layer<Option<DebugLog>, ChromeLogLayered>;
+/// The filter can be reloaded as well, so that debug events aren't recorded
+/// while the layer is disabled.
+type DebugFilter = reload::Layer<EnvFilter, ChromeLogLayered>;
+/// We filter this using a filter that logs events with level `DEBUG` or
+/// higher, regardless of the verbosity.
+type DebugLogFiltered = Filtered<DebugReload, DebugFilter, ChromeLogLayered>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum LogFilterError {
+    #[error("invalid log filter: {0}")]
+    Parse(#[from] ParseError),
+    #[error("unable to update log filter: {0}")]
+    Reload(#[from] Error),
+}
+
+/// Changes the filter of the daemon's log file while the daemon is running.
+#[derive(Clone)]
+pub struct DaemonLogFilter {
+    update: Handle<EnvFilter, StdErrLogLayered>,
+}
+
+impl DaemonLogFilter {
+    /// Replaces the filter with `directives`, in the syntax of
+    /// `TURBO_LOG_VERBOSITY`. Targets without a directive are logged at the
+    /// `INFO` level.
+    pub fn set(&self, directives: &str) -> Result<(), LogFilterError> {
+        let filter = EnvFilter::builder()
+            .with_default_directive(LevelFilter::INFO.into())
+            .parse(directives)?;
+        self.update.reload(filter)?;
+        Ok(())
+    }
+}
+
 pub struct TurboSubscriber {
     daemon_update: Handle<Option<DaemonLog>, StdErrLogLayered>,
+    daemon_filter_update: Handle<EnvFilter, StdErrLogLayered>,
 
     /// The non-blocking file logger only continues to log while this guard is
     /// held. We keep it here so that it doesn't get dropped.
@@ -80,6 +131,10 @@ pub struct TurboSubscriber {
     chrome_update: Handle<Option<ChromeLog>, DaemonLogLayered>,
     chrome_guard: Mutex<Option<tracing_chrome::FlushGuard>>,
 
+    debug_update: Handle<Option<DebugLog>, ChromeLogLayered>,
+    debug_filter_update: Handle<EnvFilter, ChromeLogLayered>,
+    debug_guard: Mutex<Option<tracing_appender::non_blocking::WorkerGuard>>,
+
     #[cfg(feature = "pprof")]
     pprof_guard: pprof::ProfilerGuard<'static>,
 }
@@ -96,13 +151,17 @@ impl TurboSubscriber {
     /// - If the verbosity argument (usually detemined by a flag) is provided,
     ///   it overrides the default global log level. This means it overrides the
     ///   `TURBO_LOG_VERBOSITY` global setting, but not per-module settings.
+    /// - If the log format is JSON, every event is logged as a JSON object on a
+    ///   single line.
     ///
     /// `TurboSubscriber` has optional loggers that can be enabled later:
     /// - `set_daemon_logger` enables logging to a file, using the standard
     ///  formatter.
     /// - `enable_chrome_tracing` enables logging to a file, using the chrome
     ///  tracing formatter.
-    pub fn new_with_verbosity(verbosity: usize, ui: &UI) -> Self {
+    /// - `set_debug_logger` enables logging to a file at the debug level,
+    ///  regardless of the verbosity.
+    pub fn new_with_verbosity(verbosity: usize, ui: &UI, log_format: LogFormat) -> Self {
         let level_override = match verbosity {
             0 => None,
             1 => Some(LevelFilter::INFO),
@@ -127,20 +186,29 @@ impl TurboSubscriber {
         };
 
         let stderr = fmt::layer()
+            .fmt_fields(JsonFields::new())
             .with_writer(StdErrWrapper {})
-            .event_format(TurboFormatter::new_with_ansi(!ui.should_strip_ansi))
+            .event_format(TurboFormatter::new(!ui.should_strip_ansi, log_format))
             .with_filter(env_filter(LevelFilter::WARN));
 
         // we set this layer to None to start with, effectively disabling it
         let (logrotate, daemon_update) = reload::Layer::new(Option::<DaemonLog>::None);
-        let logrotate: DaemonLogFiltered = logrotate.with_filter(env_filter(LevelFilter::INFO));
+        let (daemon_filter, daemon_filter_update) =
+            reload::Layer::new(env_filter(LevelFilter::INFO));
+        let logrotate: DaemonLogFiltered = logrotate.with_filter(daemon_filter);
 
         let (chrome, chrome_update) = reload::Layer::new(Option::<ChromeLog>::None);
 
+        let (debug, debug_update) = reload::Layer::new(Option::<DebugLog>::None);
+        let (debug_filter, debug_filter_update) =
+            reload::Layer::new(EnvFilter::new(LevelFilter::OFF.to_string()));
+        let debug: DebugLogFiltered = debug.with_filter(debug_filter);
+
         let registry = Registry::default()
             .with(stderr)
             .with(logrotate)
-            .with(chrome);
+            .with(chrome)
+            .with(debug);
 
         #[cfg(feature = "pprof")]
         let pprof_guard = pprof::ProfilerGuardBuilder::default()
@@ -153,9 +221,13 @@ impl TurboSubscriber {
 
         Self {
             daemon_update,
+            daemon_filter_update,
             daemon_guard: Mutex::new(None),
             chrome_update,
             chrome_guard: Mutex::new(None),
+            debug_update,
+            debug_filter_update,
+            debug_guard: Mutex::new(None),
             #[cfg(feature = "pprof")]
             pprof_guard,
         }
@@ -182,6 +254,40 @@ impl TurboSubscriber {
         Ok(())
     }
 
+    /// Returns a handle to change the filter of the daemon logger.
+    pub fn daemon_log_filter(&self) -> DaemonLogFilter {
+        DaemonLogFilter {
+            update: self.daemon_filter_update.clone(),
+        }
+    }
+
+    /// Enables debug logging with the specified rotation settings.
+    ///
+    /// Debug logging uses the standard tracing formatter and isn't affected by
+    /// the verbosity.
+    #[tracing::instrument(skip(self, appender))]
+    pub fn set_debug_logger(&self, appender: RollingFileAppender) -> Result<(), Error> {
+        let (file_writer, guard) = tracing_appender::non_blocking(appender);
+
+        let layer: DebugLog = tracing_subscriber::fmt::layer()
+            .with_writer(file_writer)
+            .with_ansi(false);
+
+        self.debug_update.reload(Some(layer))?;
+        self.debug_filter_update.reload(
+            EnvFilter::new(LevelFilter::DEBUG.to_string())
+                .add_directive("reqwest=error".parse().unwrap())
+                .add_directive("hyper=warn".parse().unwrap())
+                .add_directive("h2=warn".parse().unwrap()),
+        )?;
+        self.debug_guard
+            .lock()
+            .expect("not poisoned")
+            .replace(guard);
+
+        Ok(())
+    }
+
     /// Enables chrome tracing.
     #[tracing::instrument(skip(self, to_file))]
     pub fn enable_chrome_tracing<P: AsRef<Path>>(
@@ -245,13 +351,21 @@ impl Drop for TurboSubscriber {
 /// This formatter does not print any information about spans, and does
 /// not print any event metadata other than the message set when you
 /// call `debug!(...)` or `info!(...)` etc.
+///
+/// With the JSON log format, events are instead written as JSON objects with
+/// their timestamp, level, target and fields, along with the task they were
+/// logged for, if any.
 pub struct TurboFormatter {
     is_ansi: bool,
+    log_format: LogFormat,
 }
 
 impl TurboFormatter {
-    pub fn new_with_ansi(is_ansi: bool) -> Self {
-        Self { is_ansi }
+    pub fn new(is_ansi: bool, log_format: LogFormat) -> Self {
+        Self {
+            is_ansi,
+            log_format,
+        }
     }
 }
 
@@ -262,10 +376,14 @@ where
 {
     fn format_event(
         &self,
-        _ctx: &FmtContext<'_, S, N>,
+        ctx: &FmtContext<'_, S, N>,
         mut writer: Writer<'_>,
         event: &Event<'_>,
     ) -> std::fmt::Result {
+        if self.log_format == LogFormat::Json {
+            return write_json(ctx, writer, event);
+        }
+
         let level = event.metadata().level();
         let target = event.metadata().target();
 
@@ -351,3 +469,77 @@ fn write_message<FG: Color, BG: Color>(
     event.record(&mut visitor);
     writeln!(writer)
 }
+
+/// A visitor that collects the fields of an event into a JSON object.
+#[derive(Default)]
+struct JsonVisitor {
+    fields: Map<String, Value>,
+}
+
+impl Visit for JsonVisitor {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.fields.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.fields.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.fields.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.fields.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.fields.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.fields
+            .insert(field.name().to_string(), format!("{:?}", value).into());
+    }
+}
+
+/// Writes an event as a JSON object on a single line.
+fn write_json<S, N>(
+    ctx: &FmtContext<'_, S, N>,
+    mut writer: Writer<'_>,
+    event: &Event,
+) -> Result<(), std::fmt::Error>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    let metadata = event.metadata();
+    let mut visitor = JsonVisitor::default();
+    event.record(&mut visitor);
+
+    // Uses the same format as the lines that turbo prints
+    let line = turborepo_ui::json::format_line(
+        metadata.level().as_str(),
+        metadata.target(),
+        current_task(ctx),
+        visitor.fields,
+    );
+    writeln!(writer, "{}", line)
+}
+
+/// Finds the task of the innermost span of the event that records one. The
+/// fields of spans are recorded as JSON objects by the stderr logger.
+fn current_task<S, N>(ctx: &FmtContext<'_, S, N>) -> Option<Value>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    ctx.event_scope()?.find_map(|span| {
+        let extensions = span.extensions();
+        let fields = extensions.get::<FormattedFields<N>>()?;
+        match serde_json::from_str(fields) {
+            Ok(Value::Object(mut fields)) => fields.remove("task"),
+            _ => None,
+        }
+    })
+}
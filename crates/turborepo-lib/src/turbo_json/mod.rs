@@ -16,11 +16,12 @@ use turborepo_repository::{package_graph::ROOT_PKG_NAME, package_json::PackageJs
 use crate::{
     cli::OutputLogsMode,
     config::{ConfigurationOptions, Error, InvalidEnvPrefixError},
+    process::{Executor, Priority, ResourceLimits},
     run::{
         task_access::{TaskAccessTraceFile, TASK_ACCESS_CONFIG_PATH},
         task_id::{TaskId, TaskName},
     },
-    task_graph::{TaskDefinition, TaskOutputs},
+    task_graph::{TaskDefinition, TaskHooks, TaskOutputs},
     unescape::UnescapedString,
 };
 
@@ -53,9 +54,20 @@ pub struct TurboJson {
     pub(crate) global_dot_env: Option<Vec<RelativeUnixPathBuf>>,
     pub(crate) global_env: Vec<String>,
     pub(crate) global_pass_through_env: Option<Vec<String>>,
+    pub(crate) global_tool_version_files: Option<Vec<RelativeUnixPathBuf>>,
     pub(crate) pipeline: Pipeline,
 }
 
+// Files that pin the version of a tool that tasks are commonly run with.
+// Switching versions can produce incompatible outputs, so these are part of the
+// global hash unless `globalToolVersionFiles` is configured.
+const DEFAULT_TOOL_VERSION_FILES: [&str; 4] = [
+    ".nvmrc",
+    ".node-version",
+    ".tool-versions",
+    "rust-toolchain.toml",
+];
+
 // Iterable is required to enumerate allowed keys
 #[derive(Clone, Debug, Default, Iterable, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -73,8 +85,28 @@ pub(crate) struct RawRemoteCacheOptions {
     #[serde(skip_serializing_if = "Option::is_none")]
     preflight: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    preflight_requests: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     timeout: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    connect_timeout: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    upload_timeout: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    retry_attempts: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    retry_backoff: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    retry_jitter: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fallback_on_rate_limit: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_cert: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    audit_log: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     enabled: Option<bool>,
 }
 
@@ -87,7 +119,17 @@ impl From<&RawRemoteCacheOptions> for ConfigurationOptions {
             team_id: remote_cache_opts.team_id.clone(),
             signature: remote_cache_opts.signature,
             preflight: remote_cache_opts.preflight,
+            preflight_requests: remote_cache_opts.preflight_requests.clone(),
             timeout: remote_cache_opts.timeout,
+            connect_timeout: remote_cache_opts.connect_timeout,
+            upload_timeout: remote_cache_opts.upload_timeout,
+            retry_attempts: remote_cache_opts.retry_attempts,
+            retry_backoff: remote_cache_opts.retry_backoff,
+            retry_jitter: remote_cache_opts.retry_jitter,
+            fallback_on_rate_limit: remote_cache_opts.fallback_on_rate_limit,
+            client_cert: remote_cache_opts.client_cert.clone(),
+            client_key: remote_cache_opts.client_key.clone(),
+            audit_log: remote_cache_opts.audit_log.clone(),
             enabled: remote_cache_opts.enabled,
             ..Self::default()
         }
@@ -121,6 +163,9 @@ pub struct RawTurboJson {
     // .env files to consider, in order.
     #[serde(skip_serializing_if = "Option::is_none")]
     global_dot_env: Option<Vec<UnescapedString>>,
+    // Files pinning tool versions to include in the global hash
+    #[serde(skip_serializing_if = "Option::is_none")]
+    global_tool_version_files: Option<Vec<Spanned<UnescapedString>>>,
     // Pipeline is a map of Turbo pipeline entries which define the task graph
     // and cache behavior on a per task or per package-task basis.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -179,6 +224,90 @@ pub struct RawTaskDefinition {
     outputs: Option<Vec<Spanned<UnescapedString>>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     output_mode: Option<Spanned<OutputLogsMode>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pty: Option<Spanned<bool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shell: Option<Spanned<bool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    resources: Option<RawResourceLimits>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    priority: Option<Spanned<Priority>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    executor: Option<RawExecutor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hooks: Option<RawTaskHooks>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    platforms: Option<RawPlatforms>,
+}
+
+// Iterable is required to enumerate allowed keys
+#[derive(Serialize, Default, Debug, PartialEq, Clone, Iterable)]
+#[serde(rename_all = "camelCase")]
+pub struct RawResourceLimits {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cpus: Option<Spanned<u32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    memory: Option<Spanned<UnescapedString>>,
+}
+
+// An empty executor runs the task on the host, which allows workspaces to opt
+// out of an executor configured in the root turbo.json
+#[derive(Serialize, Default, Debug, PartialEq, Clone, Iterable)]
+#[serde(rename_all = "camelCase")]
+pub struct RawExecutor {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    docker: Option<Spanned<RawDockerExecutor>>,
+}
+
+#[derive(Serialize, Default, Debug, PartialEq, Clone, Iterable)]
+#[serde(rename_all = "camelCase")]
+pub struct RawDockerExecutor {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    image: Option<Spanned<UnescapedString>>,
+}
+
+#[derive(Serialize, Default, Debug, PartialEq, Clone, Iterable)]
+#[serde(rename_all = "camelCase")]
+pub struct RawTaskHooks {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    before: Option<Spanned<UnescapedString>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    after: Option<Spanned<UnescapedString>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hash: Option<Spanned<bool>>,
+}
+
+// Overrides of a task for the platform turbo runs on, keyed by the values of
+// `std::env::consts::OS`
+#[derive(Serialize, Default, Debug, PartialEq, Clone, Iterable)]
+#[serde(rename_all = "camelCase")]
+pub struct RawPlatforms {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    windows: Option<RawPlatformOverrides>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    macos: Option<RawPlatformOverrides>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    linux: Option<RawPlatformOverrides>,
+}
+
+impl RawPlatforms {
+    fn current(self) -> Option<RawPlatformOverrides> {
+        match std::env::consts::OS {
+            "windows" => self.windows,
+            "macos" => self.macos,
+            "linux" => self.linux,
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Default, Debug, PartialEq, Clone, Iterable)]
+#[serde(rename_all = "camelCase")]
+pub struct RawPlatformOverrides {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    command: Option<Spanned<UnescapedString>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pass_through_args: Option<Vec<Spanned<UnescapedString>>>,
 }
 
 macro_rules! set_field {
@@ -205,10 +334,17 @@ impl RawTaskDefinition {
         set_field!(self, other, env);
         set_field!(self, other, pass_through_env);
         set_field!(self, other, dot_env);
+        set_field!(self, other, pty);
+        set_field!(self, other, shell);
+        set_field!(self, other, resources);
+        set_field!(self, other, priority);
+        set_field!(self, other, executor);
+        set_field!(self, other, hooks);
+        set_field!(self, other, platforms);
     }
 }
 
-const CONFIG_FILE: &str = "turbo.json";
+pub(crate) const CONFIG_FILE: &str = "turbo.json";
 const ENV_PIPELINE_DELIMITER: &str = "$";
 const TOPOLOGICAL_PIPELINE_DELIMITER: &str = "^";
 
@@ -346,6 +482,20 @@ impl TryFrom<RawTaskDefinition> for TaskDefinition {
             })
             .transpose()?;
 
+        let platform = raw_task.platforms.and_then(RawPlatforms::current);
+        let command = platform
+            .as_ref()
+            .and_then(|platform| platform.command.clone())
+            .map(|command| command.into_inner().into());
+        let pass_through_args = platform
+            .and_then(|platform| platform.pass_through_args)
+            .map(|args| {
+                args.into_iter()
+                    .map(|arg| arg.into_inner().into())
+                    .collect()
+            })
+            .unwrap_or_default();
+
         Ok(TaskDefinition {
             outputs,
             cache: cache.into_inner().unwrap_or(true),
@@ -357,10 +507,115 @@ impl TryFrom<RawTaskDefinition> for TaskDefinition {
             dot_env,
             output_mode: *raw_task.output_mode.unwrap_or_default(),
             persistent: *raw_task.persistent.unwrap_or_default(),
+            pty: raw_task.pty.map(Spanned::into_inner),
+            shell: raw_task.shell.map_or(true, Spanned::into_inner),
+            resource_limits: raw_task
+                .resources
+                .map(ResourceLimits::try_from)
+                .transpose()?,
+            priority: raw_task
+                .priority
+                .map(Spanned::into_inner)
+                .unwrap_or_default(),
+            executor: raw_task
+                .executor
+                .and_then(|executor| executor.docker)
+                .map(Executor::try_from)
+                .transpose()?,
+            hooks: raw_task
+                .hooks
+                .map(TaskHooks::from)
+                .filter(|hooks| hooks.before.is_some() || hooks.after.is_some()),
+            command,
+            pass_through_args,
         })
     }
 }
 
+impl From<RawTaskHooks> for TaskHooks {
+    fn from(raw_hooks: RawTaskHooks) -> Self {
+        TaskHooks {
+            before: raw_hooks.before.map(|before| before.into_inner().into()),
+            after: raw_hooks.after.map(|after| after.into_inner().into()),
+            hash: raw_hooks.hash.map_or(false, Spanned::into_inner),
+        }
+    }
+}
+
+impl TryFrom<Spanned<RawDockerExecutor>> for Executor {
+    type Error = Error;
+
+    fn try_from(raw_docker: Spanned<RawDockerExecutor>) -> Result<Self, Error> {
+        let (raw_docker, docker_span) = raw_docker.split();
+        match raw_docker.image {
+            Some(image) => Ok(Executor::Docker {
+                image: image.into_inner().into(),
+            }),
+            None => {
+                let (span, text) = docker_span.span_and_text("turbo.json");
+                Err(Error::MissingDockerImage { span, text })
+            }
+        }
+    }
+}
+
+impl TryFrom<RawResourceLimits> for ResourceLimits {
+    type Error = Error;
+
+    fn try_from(raw_limits: RawResourceLimits) -> Result<Self, Error> {
+        let cpus = raw_limits
+            .cpus
+            .map(|cpus| match *cpus {
+                0 => {
+                    let (span, text) = cpus.span_and_text("turbo.json");
+                    Err(Error::InvalidResourceLimit {
+                        field: "cpus",
+                        expected: "a positive number of CPUs",
+                        span,
+                        text,
+                    })
+                }
+                count => Ok(count),
+            })
+            .transpose()?;
+        let memory = raw_limits
+            .memory
+            .map(|memory| {
+                parse_memory_limit(&memory).ok_or_else(|| {
+                    let (span, text) = memory.span_and_text("turbo.json");
+                    Error::InvalidResourceLimit {
+                        field: "memory",
+                        expected: "a positive amount of memory, like \"512MB\"",
+                        span,
+                        text,
+                    }
+                })
+            })
+            .transpose()?;
+        Ok(ResourceLimits { cpus, memory })
+    }
+}
+
+// Parses an amount of memory like "512MB" into bytes. Units are binary, so
+// "1KB" and "1KiB" are both 1024 bytes.
+fn parse_memory_limit(limit: &str) -> Option<u64> {
+    let limit = limit.trim();
+    let unit_start = limit
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(limit.len());
+    let (amount, unit) = limit.split_at(unit_start);
+    let amount: u64 = amount.parse().ok()?;
+    let shift = match unit.trim_start().to_ascii_uppercase().as_str() {
+        "" | "B" => 0,
+        "K" | "KB" | "KIB" => 10,
+        "M" | "MB" | "MIB" => 20,
+        "G" | "GB" | "GIB" => 30,
+        "T" | "TB" | "TIB" => 40,
+        _ => return None,
+    };
+    amount.checked_mul(1 << shift).filter(|bytes| *bytes > 0)
+}
+
 impl RawTurboJson {
     pub(crate) fn read(
         repo_root: &AbsoluteSystemPath,
@@ -438,6 +693,26 @@ impl TryFrom<RawTurboJson> for TurboJson {
             gather_env_vars(global_env_from_turbo, "globalEnv", &mut global_env)?;
         }
 
+        let global_tool_version_files = raw_turbo
+            .global_tool_version_files
+            .map(|files| -> Result<Vec<RelativeUnixPathBuf>, Error> {
+                files
+                    .into_iter()
+                    .map(|file| {
+                        if Utf8Path::new(&file.value).is_absolute() {
+                            let (span, text) = file.span_and_text("turbo.json");
+                            return Err(Error::AbsolutePathInConfig {
+                                field: "globalToolVersionFiles",
+                                span,
+                                text,
+                            });
+                        }
+                        Ok(RelativeUnixPathBuf::new(file.into_inner())?)
+                    })
+                    .collect()
+            })
+            .transpose()?;
+
         for global_dep in raw_turbo.global_dependencies.into_iter().flatten() {
             if let Some(env_var) = global_dep.strip_prefix(ENV_PIPELINE_DELIMITER) {
                 println!(
@@ -500,6 +775,7 @@ impl TryFrom<RawTurboJson> for TurboJson {
                     Ok(global_dot_env)
                 })
                 .transpose()?,
+            global_tool_version_files,
             pipeline: raw_turbo.pipeline.unwrap_or_default(),
             // copy these over, we don't need any changes here.
             extends: raw_turbo
@@ -512,6 +788,17 @@ impl TryFrom<RawTurboJson> for TurboJson {
 }
 
 impl TurboJson {
+    /// The files pinning tool versions that are included in the global hash
+    pub(crate) fn tool_version_files(&self) -> Vec<RelativeUnixPathBuf> {
+        match &self.global_tool_version_files {
+            Some(files) => files.clone(),
+            None => DEFAULT_TOOL_VERSION_FILES
+                .iter()
+                .map(|file| RelativeUnixPathBuf::new(*file).expect("valid relative path"))
+                .collect(),
+        }
+    }
+
     /// Loads turbo.json by reading the file at `dir` and optionally combining
     /// with synthesized information from the provided package.json
     pub fn load(
@@ -719,7 +1006,7 @@ fn gather_env_vars(
 
 #[cfg(test)]
 mod tests {
-    use std::fs;
+    use std::{assert_matches::assert_matches, fs};
 
     use anyhow::Result;
     use biome_deserialize::json::deserialize_from_json_str;
@@ -731,11 +1018,15 @@ mod tests {
     use turbopath::{AbsoluteSystemPath, AnchoredSystemPath, RelativeUnixPathBuf};
     use turborepo_repository::package_json::PackageJson;
 
-    use super::{Pipeline, RawTurboJson, Spanned};
+    use super::{
+        parse_memory_limit, Error, Pipeline, RawDockerExecutor, RawExecutor, RawResourceLimits,
+        RawTaskHooks, RawTurboJson, Spanned,
+    };
     use crate::{
         cli::OutputLogsMode,
+        process::{Executor, Priority, ResourceLimits},
         run::task_id::TaskName,
-        task_graph::{TaskDefinition, TaskOutputs},
+        task_graph::{TaskDefinition, TaskHooks, TaskOutputs},
         turbo_json::{RawTaskDefinition, TurboJson},
         unescape::UnescapedString,
     };
@@ -753,6 +1044,12 @@ mod tests {
             ..TurboJson::default()
         }
     ; "global dot env (unsorted)")]
+    #[test_case(r#"{ "globalToolVersionFiles": [".python-version"] }"#,
+        TurboJson {
+            global_tool_version_files: Some(vec![RelativeUnixPathBuf::new(".python-version").unwrap()]),
+            ..TurboJson::default()
+        }
+    ; "global tool version files")]
     #[test_case(r#"{ "globalPassThroughEnv": ["GITHUB_TOKEN", "AWS_SECRET_KEY"] }"#,
         TurboJson {
             global_pass_through_env: Some(vec!["AWS_SECRET_KEY".to_string(), "GITHUB_TOKEN".to_string()]),
@@ -881,6 +1178,116 @@ mod tests {
         TaskDefinition::default()
     ; "just persistent"
     )]
+    #[test_case(
+        r#"{ "pty": true }"#,
+        RawTaskDefinition {
+            pty: Some(Spanned::new(true).with_range(9..13)),
+            ..RawTaskDefinition::default()
+        },
+        TaskDefinition {
+            pty: Some(true),
+            ..Default::default()
+        }
+    ; "just pty"
+    )]
+    #[test_case(
+        r#"{ "shell": false }"#,
+        RawTaskDefinition {
+            shell: Some(Spanned::new(false).with_range(11..16)),
+            ..RawTaskDefinition::default()
+        },
+        TaskDefinition {
+            shell: false,
+            ..Default::default()
+        }
+    ; "just shell"
+    )]
+    #[test_case(
+        r#"{ "resources": { "cpus": 2, "memory": "512MB" } }"#,
+        RawTaskDefinition {
+            resources: Some(RawResourceLimits {
+                cpus: Some(Spanned::new(2).with_range(25..26)),
+                memory: Some(Spanned::<UnescapedString>::new("512MB".into()).with_range(38..45)),
+            }),
+            ..RawTaskDefinition::default()
+        },
+        TaskDefinition {
+            resource_limits: Some(ResourceLimits {
+                cpus: Some(2),
+                memory: Some(512 * 1024 * 1024),
+            }),
+            ..Default::default()
+        }
+    ; "resources"
+    )]
+    #[test_case(
+        r#"{ "executor": { "docker": { "image": "node:20" } } }"#,
+        RawTaskDefinition {
+            executor: Some(RawExecutor {
+                docker: Some(Spanned::new(RawDockerExecutor {
+                    image: Some(Spanned::<UnescapedString>::new("node:20".into()).with_range(37..46)),
+                }).with_range(26..48)),
+            }),
+            ..RawTaskDefinition::default()
+        },
+        TaskDefinition {
+            executor: Some(Executor::Docker {
+                image: "node:20".to_string(),
+            }),
+            ..Default::default()
+        }
+    ; "docker executor"
+    )]
+    #[test_case(
+        r#"{ "executor": {} }"#,
+        RawTaskDefinition {
+            executor: Some(RawExecutor::default()),
+            ..RawTaskDefinition::default()
+        },
+        TaskDefinition::default()
+    ; "host executor"
+    )]
+    #[test_case(
+        r#"{ "priority": "low" }"#,
+        RawTaskDefinition {
+            priority: Some(Spanned::new(Priority::Low).with_range(14..19)),
+            ..RawTaskDefinition::default()
+        },
+        TaskDefinition {
+            priority: Priority::Low,
+            ..Default::default()
+        }
+    ; "low priority"
+    )]
+    #[test_case(
+        r#"{ "hooks": { "before": "docker compose up -d", "after": "docker compose down", "hash": true } }"#,
+        RawTaskDefinition {
+            hooks: Some(RawTaskHooks {
+                before: Some(Spanned::<UnescapedString>::new("docker compose up -d".into()).with_range(23..45)),
+                after: Some(Spanned::<UnescapedString>::new("docker compose down".into()).with_range(56..77)),
+                hash: Some(Spanned::new(true).with_range(87..91)),
+            }),
+            ..RawTaskDefinition::default()
+        },
+        TaskDefinition {
+            hooks: Some(TaskHooks {
+                before: Some("docker compose up -d".to_string()),
+                after: Some("docker compose down".to_string()),
+                hash: true,
+            }),
+            ..Default::default()
+        }
+    ; "hooks"
+    )]
+    #[test_case(
+        r#"{ "hooks": {} }"#,
+        RawTaskDefinition {
+            hooks: Some(RawTaskHooks::default()),
+            ..RawTaskDefinition::default()
+        },
+        TaskDefinition::default()
+    ; "no hooks"
+    )]
     #[test_case(
         r#"{ "dotEnv": [] }"#,
         RawTaskDefinition {
@@ -920,6 +1327,13 @@ mod tests {
             inputs: Some(vec![Spanned::<UnescapedString>::new("package/a/src/**".into()).with_range(241..259)]),
             output_mode: Some(Spanned::new(OutputLogsMode::Full).with_range(286..292)),
             persistent: Some(Spanned::new(true).with_range(318..322)),
+            pty: None,
+            shell: None,
+            resources: None,
+            priority: None,
+            executor: None,
+            hooks: None,
+            platforms: None,
         },
         TaskDefinition {
           dot_env: Some(vec![RelativeUnixPathBuf::new("package/a/.env").unwrap()]),
@@ -935,6 +1349,14 @@ mod tests {
           task_dependencies: vec![Spanned::<TaskName<'_>>::new("cli#build".into()).with_range(26..37)],
           topological_dependencies: vec![],
           persistent: true,
+          pty: None,
+          shell: true,
+          resource_limits: None,
+          priority: Priority::Normal,
+          executor: None,
+          hooks: None,
+          command: None,
+          pass_through_args: vec![],
         }
       ; "full"
     )]
@@ -960,6 +1382,13 @@ mod tests {
             inputs: Some(vec![Spanned::<UnescapedString>::new("package\\a\\src\\**".into()).with_range(273..294)]),
             output_mode: Some(Spanned::new(OutputLogsMode::Full).with_range(325..331)),
             persistent: Some(Spanned::new(true).with_range(361..365)),
+            pty: None,
+            shell: None,
+            resources: None,
+            priority: None,
+            executor: None,
+            hooks: None,
+            platforms: None,
         },
         TaskDefinition {
             dot_env: Some(vec![RelativeUnixPathBuf::new("package\\a\\.env").unwrap()]),
@@ -975,6 +1404,14 @@ mod tests {
             task_dependencies: vec![Spanned::<TaskName<'_>>::new("cli#build".into()).with_range(30..41)],
             topological_dependencies: vec![],
             persistent: true,
+            pty: None,
+            shell: true,
+            resource_limits: None,
+            priority: Priority::Normal,
+            executor: None,
+            hooks: None,
+            command: None,
+            pass_through_args: vec![],
         }
       ; "full (windows)"
     )]
@@ -997,6 +1434,46 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_docker_executor_without_image() {
+        let deserialized_result = deserialize_from_json_str(
+            r#"{ "executor": { "docker": {} } }"#,
+            JsonParserOptions::default().with_allow_comments(),
+        );
+        let raw_task_definition: RawTaskDefinition =
+            deserialized_result.into_deserialized().unwrap();
+        let error = TaskDefinition::try_from(raw_task_definition).unwrap_err();
+        assert_matches!(error, Error::MissingDockerImage { .. });
+    }
+
+    #[test]
+    fn test_platform_overrides() -> Result<()> {
+        let deserialized_result = deserialize_from_json_str(
+            r#"{
+              "platforms": {
+                "windows": { "command": "build.cmd", "passThroughArgs": ["/quiet"] },
+                "macos": { "command": "./build-macos.sh" },
+                "linux": { "passThroughArgs": ["--static"] }
+              }
+            }"#,
+            JsonParserOptions::default().with_allow_comments(),
+        );
+        let raw_task_definition: RawTaskDefinition =
+            deserialized_result.into_deserialized().unwrap();
+        let task_definition: TaskDefinition = raw_task_definition.try_into()?;
+
+        let (command, pass_through_args) = match std::env::consts::OS {
+            "windows" => (Some("build.cmd"), vec!["/quiet"]),
+            "macos" => (Some("./build-macos.sh"), vec![]),
+            "linux" => (None, vec!["--static"]),
+            _ => (None, vec![]),
+        };
+        assert_eq!(task_definition.command.as_deref(), command);
+        assert_eq!(task_definition.pass_through_args, pass_through_args);
+
+        Ok(())
+    }
+
     #[test_case("[]", TaskOutputs::default() ; "empty")]
     #[test_case(r#"["target/**"]"#, TaskOutputs { inclusions: vec!["target/**".to_string()], exclusions: vec![] })]
     #[test_case(
@@ -1090,4 +1567,16 @@ mod tests {
             .map(|mode| mode.into_inner());
         assert_eq!(actual, expected);
     }
+
+    #[test_case("1024", Some(1024) ; "bytes")]
+    #[test_case("512MB", Some(512 * 1024 * 1024) ; "megabytes")]
+    #[test_case("2 GiB", Some(2 * 1024 * 1024 * 1024) ; "gibibytes with space")]
+    #[test_case("1k", Some(1024) ; "lowercase unit")]
+    #[test_case("0MB", None ; "zero")]
+    #[test_case("1.5GB", None ; "fraction")]
+    #[test_case("512PB", None ; "unknown unit")]
+    #[test_case("MB", None ; "missing amount")]
+    fn test_parse_memory_limit(limit: &str, expected: Option<u64>) {
+        assert_eq!(parse_memory_limit(limit), expected);
+    }
 }
@@ -24,14 +24,18 @@ use super::RawRemoteCacheOptions;
 use crate::{
     cli::OutputLogsMode,
     config::ConfigurationOptions,
+    process::Priority,
     run::task_id::TaskName,
-    turbo_json::{Pipeline, RawTaskDefinition, RawTurboJson, SpacesJson, Spanned},
+    turbo_json::{
+        Pipeline, RawDockerExecutor, RawExecutor, RawPlatformOverrides, RawPlatforms,
+        RawResourceLimits, RawTaskDefinition, RawTaskHooks, RawTurboJson, SpacesJson, Spanned,
+    },
     unescape::UnescapedString,
 };
 
 #[derive(Debug, Error, Diagnostic)]
 #[error("failed to parse turbo json")]
-#[diagnostic(code(turbo_json_parse_error))]
+#[diagnostic(code(TURBO_CONFIG_032))]
 pub struct Error {
     #[related]
     diagnostics: Vec<ParseDiagnostic>,
@@ -68,7 +72,7 @@ impl From<biome_diagnostics::Error> for ParseDiagnostic {
 
 #[derive(Debug, Error, Diagnostic)]
 #[error("{message}")]
-#[diagnostic(code(turbo_json_parse_error))]
+#[diagnostic(code(TURBO_CONFIG_032))]
 struct ParseDiagnostic {
     message: String,
     #[source_code]
@@ -122,6 +126,28 @@ impl Deserializable for OutputLogsMode {
     }
 }
 
+impl Deserializable for Priority {
+    fn deserialize(
+        value: &impl DeserializableValue,
+        name: &str,
+        diagnostics: &mut Vec<DeserializationDiagnostic>,
+    ) -> Option<Self> {
+        let priority = String::deserialize(value, name, diagnostics)?;
+        match priority.as_str() {
+            "normal" => Some(Priority::Normal),
+            "low" => Some(Priority::Low),
+            _ => {
+                diagnostics.push(DeserializationDiagnostic::new_unknown_value(
+                    &priority,
+                    value.range(),
+                    &["normal", "low"],
+                ));
+                None
+            }
+        }
+    }
+}
+
 impl Deserializable for TaskName<'static> {
     fn deserialize(
         value: &impl DeserializableValue,
@@ -256,6 +282,361 @@ impl DeserializationVisitor for RawTaskDefinitionVisitor {
                         result.output_mode = Some(Spanned::new(output_mode).with_range(range));
                     }
                 }
+                "pty" => {
+                    if let Some(pty) = bool::deserialize(&value, &key_text, diagnostics) {
+                        result.pty = Some(Spanned::new(pty).with_range(range));
+                    }
+                }
+                "shell" => {
+                    if let Some(shell) = bool::deserialize(&value, &key_text, diagnostics) {
+                        result.shell = Some(Spanned::new(shell).with_range(range));
+                    }
+                }
+                "resources" => {
+                    if let Some(resources) =
+                        RawResourceLimits::deserialize(&value, &key_text, diagnostics)
+                    {
+                        result.resources = Some(resources);
+                    }
+                }
+                "priority" => {
+                    if let Some(priority) = Priority::deserialize(&value, &key_text, diagnostics) {
+                        result.priority = Some(Spanned::new(priority).with_range(range));
+                    }
+                }
+                "executor" => {
+                    if let Some(executor) = RawExecutor::deserialize(&value, &key_text, diagnostics)
+                    {
+                        result.executor = Some(executor);
+                    }
+                }
+                "hooks" => {
+                    if let Some(hooks) = RawTaskHooks::deserialize(&value, &key_text, diagnostics) {
+                        result.hooks = Some(hooks);
+                    }
+                }
+                "platforms" => {
+                    if let Some(platforms) =
+                        RawPlatforms::deserialize(&value, &key_text, diagnostics)
+                    {
+                        result.platforms = Some(platforms);
+                    }
+                }
+                unknown_key => {
+                    diagnostics.push(create_unknown_key_diagnostic_from_struct(
+                        &result,
+                        unknown_key,
+                        key.range(),
+                    ));
+                }
+            }
+        }
+
+        Some(result)
+    }
+}
+
+impl Deserializable for RawResourceLimits {
+    fn deserialize(
+        value: &impl DeserializableValue,
+        name: &str,
+        diagnostics: &mut Vec<DeserializationDiagnostic>,
+    ) -> Option<Self> {
+        value.deserialize(RawResourceLimitsVisitor, name, diagnostics)
+    }
+}
+
+struct RawResourceLimitsVisitor;
+
+impl DeserializationVisitor for RawResourceLimitsVisitor {
+    type Output = RawResourceLimits;
+
+    const EXPECTED_TYPE: VisitableType = VisitableType::MAP;
+
+    fn visit_map(
+        self,
+        // Iterator of key-value pairs.
+        members: impl Iterator<Item = Option<(impl DeserializableValue, impl DeserializableValue)>>,
+        // range of the map in the source text.
+        _: TextRange,
+        _: &str,
+        diagnostics: &mut Vec<DeserializationDiagnostic>,
+    ) -> Option<Self::Output> {
+        let mut result = RawResourceLimits::default();
+        for (key, value) in members.flatten() {
+            let Some(key_text) = Text::deserialize(&key, "", diagnostics) else {
+                continue;
+            };
+            match key_text.text() {
+                "cpus" => {
+                    result.cpus = Spanned::<u32>::deserialize(&value, &key_text, diagnostics);
+                }
+                "memory" => {
+                    result.memory =
+                        Spanned::<UnescapedString>::deserialize(&value, &key_text, diagnostics);
+                }
+                unknown_key => {
+                    diagnostics.push(create_unknown_key_diagnostic_from_struct(
+                        &result,
+                        unknown_key,
+                        key.range(),
+                    ));
+                }
+            }
+        }
+
+        Some(result)
+    }
+}
+
+impl Deserializable for RawExecutor {
+    fn deserialize(
+        value: &impl DeserializableValue,
+        name: &str,
+        diagnostics: &mut Vec<DeserializationDiagnostic>,
+    ) -> Option<Self> {
+        value.deserialize(RawExecutorVisitor, name, diagnostics)
+    }
+}
+
+struct RawExecutorVisitor;
+
+impl DeserializationVisitor for RawExecutorVisitor {
+    type Output = RawExecutor;
+
+    const EXPECTED_TYPE: VisitableType = VisitableType::MAP;
+
+    fn visit_map(
+        self,
+        members: impl Iterator<Item = Option<(impl DeserializableValue, impl DeserializableValue)>>,
+        _: TextRange,
+        _: &str,
+        diagnostics: &mut Vec<DeserializationDiagnostic>,
+    ) -> Option<Self::Output> {
+        let mut result = RawExecutor::default();
+        for (key, value) in members.flatten() {
+            let Some(key_text) = Text::deserialize(&key, "", diagnostics) else {
+                continue;
+            };
+            match key_text.text() {
+                "docker" => {
+                    result.docker =
+                        Spanned::<RawDockerExecutor>::deserialize(&value, &key_text, diagnostics);
+                }
+                unknown_key => {
+                    diagnostics.push(create_unknown_key_diagnostic_from_struct(
+                        &result,
+                        unknown_key,
+                        key.range(),
+                    ));
+                }
+            }
+        }
+
+        Some(result)
+    }
+}
+
+impl Deserializable for RawDockerExecutor {
+    fn deserialize(
+        value: &impl DeserializableValue,
+        name: &str,
+        diagnostics: &mut Vec<DeserializationDiagnostic>,
+    ) -> Option<Self> {
+        value.deserialize(RawDockerExecutorVisitor, name, diagnostics)
+    }
+}
+
+struct RawDockerExecutorVisitor;
+
+impl DeserializationVisitor for RawDockerExecutorVisitor {
+    type Output = RawDockerExecutor;
+
+    const EXPECTED_TYPE: VisitableType = VisitableType::MAP;
+
+    fn visit_map(
+        self,
+        members: impl Iterator<Item = Option<(impl DeserializableValue, impl DeserializableValue)>>,
+        _: TextRange,
+        _: &str,
+        diagnostics: &mut Vec<DeserializationDiagnostic>,
+    ) -> Option<Self::Output> {
+        let mut result = RawDockerExecutor::default();
+        for (key, value) in members.flatten() {
+            let Some(key_text) = Text::deserialize(&key, "", diagnostics) else {
+                continue;
+            };
+            match key_text.text() {
+                "image" => {
+                    result.image =
+                        Spanned::<UnescapedString>::deserialize(&value, &key_text, diagnostics);
+                }
+                unknown_key => {
+                    diagnostics.push(create_unknown_key_diagnostic_from_struct(
+                        &result,
+                        unknown_key,
+                        key.range(),
+                    ));
+                }
+            }
+        }
+
+        Some(result)
+    }
+}
+
+impl Deserializable for RawTaskHooks {
+    fn deserialize(
+        value: &impl DeserializableValue,
+        name: &str,
+        diagnostics: &mut Vec<DeserializationDiagnostic>,
+    ) -> Option<Self> {
+        value.deserialize(RawTaskHooksVisitor, name, diagnostics)
+    }
+}
+
+struct RawTaskHooksVisitor;
+
+impl DeserializationVisitor for RawTaskHooksVisitor {
+    type Output = RawTaskHooks;
+
+    const EXPECTED_TYPE: VisitableType = VisitableType::MAP;
+
+    fn visit_map(
+        self,
+        members: impl Iterator<Item = Option<(impl DeserializableValue, impl DeserializableValue)>>,
+        _: TextRange,
+        _: &str,
+        diagnostics: &mut Vec<DeserializationDiagnostic>,
+    ) -> Option<Self::Output> {
+        let mut result = RawTaskHooks::default();
+        for (key, value) in members.flatten() {
+            let Some(key_text) = Text::deserialize(&key, "", diagnostics) else {
+                continue;
+            };
+            match key_text.text() {
+                "before" => {
+                    result.before =
+                        Spanned::<UnescapedString>::deserialize(&value, &key_text, diagnostics);
+                }
+                "after" => {
+                    result.after =
+                        Spanned::<UnescapedString>::deserialize(&value, &key_text, diagnostics);
+                }
+                "hash" => {
+                    result.hash = Spanned::<bool>::deserialize(&value, &key_text, diagnostics);
+                }
+                unknown_key => {
+                    diagnostics.push(create_unknown_key_diagnostic_from_struct(
+                        &result,
+                        unknown_key,
+                        key.range(),
+                    ));
+                }
+            }
+        }
+
+        Some(result)
+    }
+}
+
+impl Deserializable for RawPlatforms {
+    fn deserialize(
+        value: &impl DeserializableValue,
+        name: &str,
+        diagnostics: &mut Vec<DeserializationDiagnostic>,
+    ) -> Option<Self> {
+        value.deserialize(RawPlatformsVisitor, name, diagnostics)
+    }
+}
+
+struct RawPlatformsVisitor;
+
+impl DeserializationVisitor for RawPlatformsVisitor {
+    type Output = RawPlatforms;
+
+    const EXPECTED_TYPE: VisitableType = VisitableType::MAP;
+
+    fn visit_map(
+        self,
+        members: impl Iterator<Item = Option<(impl DeserializableValue, impl DeserializableValue)>>,
+        _: TextRange,
+        _: &str,
+        diagnostics: &mut Vec<DeserializationDiagnostic>,
+    ) -> Option<Self::Output> {
+        let mut result = RawPlatforms::default();
+        for (key, value) in members.flatten() {
+            let Some(key_text) = Text::deserialize(&key, "", diagnostics) else {
+                continue;
+            };
+            match key_text.text() {
+                "windows" => {
+                    result.windows =
+                        RawPlatformOverrides::deserialize(&value, &key_text, diagnostics);
+                }
+                "macos" => {
+                    result.macos =
+                        RawPlatformOverrides::deserialize(&value, &key_text, diagnostics);
+                }
+                "linux" => {
+                    result.linux =
+                        RawPlatformOverrides::deserialize(&value, &key_text, diagnostics);
+                }
+                unknown_key => {
+                    diagnostics.push(create_unknown_key_diagnostic_from_struct(
+                        &result,
+                        unknown_key,
+                        key.range(),
+                    ));
+                }
+            }
+        }
+
+        Some(result)
+    }
+}
+
+impl Deserializable for RawPlatformOverrides {
+    fn deserialize(
+        value: &impl DeserializableValue,
+        name: &str,
+        diagnostics: &mut Vec<DeserializationDiagnostic>,
+    ) -> Option<Self> {
+        value.deserialize(RawPlatformOverridesVisitor, name, diagnostics)
+    }
+}
+
+struct RawPlatformOverridesVisitor;
+
+impl DeserializationVisitor for RawPlatformOverridesVisitor {
+    type Output = RawPlatformOverrides;
+
+    const EXPECTED_TYPE: VisitableType = VisitableType::MAP;
+
+    fn visit_map(
+        self,
+        members: impl Iterator<Item = Option<(impl DeserializableValue, impl DeserializableValue)>>,
+        _: TextRange,
+        _: &str,
+        diagnostics: &mut Vec<DeserializationDiagnostic>,
+    ) -> Option<Self::Output> {
+        let mut result = RawPlatformOverrides::default();
+        for (key, value) in members.flatten() {
+            let Some(key_text) = Text::deserialize(&key, "", diagnostics) else {
+                continue;
+            };
+            match key_text.text() {
+                "command" => {
+                    result.command =
+                        Spanned::<UnescapedString>::deserialize(&value, &key_text, diagnostics);
+                }
+                "passThroughArgs" => {
+                    result.pass_through_args = Vec::<Spanned<UnescapedString>>::deserialize(
+                        &value,
+                        &key_text,
+                        diagnostics,
+                    );
+                }
                 unknown_key => {
                     diagnostics.push(create_unknown_key_diagnostic_from_struct(
                         &result,
@@ -394,11 +775,73 @@ impl DeserializationVisitor for RawRemoteCacheOptionsVisitor {
                         result.preflight = Some(preflight);
                     }
                 }
+                "preflightRequests" => {
+                    if let Some(preflight_requests) =
+                        Vec::<UnescapedString>::deserialize(&value, &key_text, diagnostics)
+                    {
+                        result.preflight_requests =
+                            Some(preflight_requests.into_iter().map(String::from).collect());
+                    }
+                }
                 "timeout" => {
                     if let Some(timeout) = u64::deserialize(&value, &key_text, diagnostics) {
                         result.timeout = Some(timeout);
                     }
                 }
+                "connectTimeout" => {
+                    if let Some(connect_timeout) = u64::deserialize(&value, &key_text, diagnostics)
+                    {
+                        result.connect_timeout = Some(connect_timeout);
+                    }
+                }
+                "uploadTimeout" => {
+                    if let Some(upload_timeout) = u64::deserialize(&value, &key_text, diagnostics) {
+                        result.upload_timeout = Some(upload_timeout);
+                    }
+                }
+                "retryAttempts" => {
+                    if let Some(retry_attempts) = u32::deserialize(&value, &key_text, diagnostics) {
+                        result.retry_attempts = Some(retry_attempts);
+                    }
+                }
+                "retryBackoff" => {
+                    if let Some(retry_backoff) = u64::deserialize(&value, &key_text, diagnostics) {
+                        result.retry_backoff = Some(retry_backoff);
+                    }
+                }
+                "retryJitter" => {
+                    if let Some(retry_jitter) = bool::deserialize(&value, &key_text, diagnostics) {
+                        result.retry_jitter = Some(retry_jitter);
+                    }
+                }
+                "fallbackOnRateLimit" => {
+                    if let Some(fallback_on_rate_limit) =
+                        bool::deserialize(&value, &key_text, diagnostics)
+                    {
+                        result.fallback_on_rate_limit = Some(fallback_on_rate_limit);
+                    }
+                }
+                "clientCert" => {
+                    if let Some(client_cert) =
+                        UnescapedString::deserialize(&value, &key_text, diagnostics)
+                    {
+                        result.client_cert = Some(client_cert.into());
+                    }
+                }
+                "clientKey" => {
+                    if let Some(client_key) =
+                        UnescapedString::deserialize(&value, &key_text, diagnostics)
+                    {
+                        result.client_key = Some(client_key.into());
+                    }
+                }
+                "auditLog" => {
+                    if let Some(audit_log) =
+                        UnescapedString::deserialize(&value, &key_text, diagnostics)
+                    {
+                        result.audit_log = Some(audit_log.into());
+                    }
+                }
                 "enabled" => {
                     if let Some(enabled) = bool::deserialize(&value, &key_text, diagnostics) {
                         result.enabled = Some(enabled);
@@ -485,11 +928,97 @@ impl DeserializationVisitor for ConfigurationOptionsVisitor {
                         result.preflight = Some(preflight);
                     }
                 }
+                "preflightRequests" => {
+                    if let Some(preflight_requests) =
+                        Vec::<UnescapedString>::deserialize(&value, &key_text, diagnostics)
+                    {
+                        result.preflight_requests =
+                            Some(preflight_requests.into_iter().map(String::from).collect());
+                    }
+                }
                 "timeout" => {
                     if let Some(timeout) = u64::deserialize(&value, &key_text, diagnostics) {
                         result.timeout = Some(timeout);
                     }
                 }
+                "connectTimeout" => {
+                    if let Some(connect_timeout) = u64::deserialize(&value, &key_text, diagnostics)
+                    {
+                        result.connect_timeout = Some(connect_timeout);
+                    }
+                }
+                "uploadTimeout" => {
+                    if let Some(upload_timeout) = u64::deserialize(&value, &key_text, diagnostics) {
+                        result.upload_timeout = Some(upload_timeout);
+                    }
+                }
+                "retryAttempts" => {
+                    if let Some(retry_attempts) = u32::deserialize(&value, &key_text, diagnostics) {
+                        result.retry_attempts = Some(retry_attempts);
+                    }
+                }
+                "retryBackoff" => {
+                    if let Some(retry_backoff) = u64::deserialize(&value, &key_text, diagnostics) {
+                        result.retry_backoff = Some(retry_backoff);
+                    }
+                }
+                "retryJitter" => {
+                    if let Some(retry_jitter) = bool::deserialize(&value, &key_text, diagnostics) {
+                        result.retry_jitter = Some(retry_jitter);
+                    }
+                }
+                "fallbackOnRateLimit" => {
+                    if let Some(fallback_on_rate_limit) =
+                        bool::deserialize(&value, &key_text, diagnostics)
+                    {
+                        result.fallback_on_rate_limit = Some(fallback_on_rate_limit);
+                    }
+                }
+                "clientCert" => {
+                    if let Some(client_cert) =
+                        UnescapedString::deserialize(&value, &key_text, diagnostics)
+                    {
+                        result.client_cert = Some(client_cert.into());
+                    }
+                }
+                "clientKey" => {
+                    if let Some(client_key) =
+                        UnescapedString::deserialize(&value, &key_text, diagnostics)
+                    {
+                        result.client_key = Some(client_key.into());
+                    }
+                }
+                "auditLog" => {
+                    if let Some(audit_log) =
+                        UnescapedString::deserialize(&value, &key_text, diagnostics)
+                    {
+                        result.audit_log = Some(audit_log.into());
+                    }
+                }
+                "statsdAddress" => {
+                    if let Some(statsd_address) =
+                        UnescapedString::deserialize(&value, &key_text, diagnostics)
+                    {
+                        result.statsd_address = Some(statsd_address.into());
+                    }
+                }
+                "telemetryEndpoint" => {
+                    if let Some(telemetry_endpoint) =
+                        UnescapedString::deserialize(&value, &key_text, diagnostics)
+                    {
+                        result.telemetry_endpoint = Some(telemetry_endpoint.into());
+                    }
+                }
+                "debugLogs" => {
+                    if let Some(debug_logs) = bool::deserialize(&value, &key_text, diagnostics) {
+                        result.debug_logs = Some(debug_logs);
+                    }
+                }
+                "daemonMetricsPort" => {
+                    if let Some(port) = u16::deserialize(&value, &key_text, diagnostics) {
+                        result.daemon_metrics_port = Some(port);
+                    }
+                }
                 "enabled" => {
                     if let Some(enabled) = bool::deserialize(&value, &key_text, diagnostics) {
                         result.enabled = Some(enabled);
@@ -578,6 +1107,13 @@ impl DeserializationVisitor for RawTurboJsonVisitor {
                         result.global_dot_env = Some(global_dot_env);
                     }
                 }
+                "globalToolVersionFiles" => {
+                    if let Some(global_tool_version_files) =
+                        Vec::deserialize(&value, &key_text, diagnostics)
+                    {
+                        result.global_tool_version_files = Some(global_tool_version_files);
+                    }
+                }
                 "experimentalSpaces" => {
                     if let Some(spaces) = SpacesJson::deserialize(&value, &key_text, diagnostics) {
                         result.experimental_spaces = Some(spaces);
@@ -617,6 +1153,7 @@ impl WithMetadata for RawTurboJson {
         self.global_dependencies.add_text(text.clone());
         self.global_env.add_text(text.clone());
         self.global_pass_through_env.add_text(text.clone());
+        self.global_tool_version_files.add_text(text.clone());
         self.pipeline.add_text(text);
     }
 
@@ -626,6 +1163,7 @@ impl WithMetadata for RawTurboJson {
         self.global_dependencies.add_path(path.clone());
         self.global_env.add_path(path.clone());
         self.global_pass_through_env.add_path(path.clone());
+        self.global_tool_version_files.add_path(path.clone());
         self.pipeline.add_path(path);
     }
 }
@@ -646,6 +1184,74 @@ impl WithMetadata for Pipeline {
     }
 }
 
+impl WithMetadata for RawResourceLimits {
+    fn add_text(&mut self, text: Arc<str>) {
+        self.cpus.add_text(text.clone());
+        self.memory.add_text(text);
+    }
+
+    fn add_path(&mut self, path: Arc<str>) {
+        self.cpus.add_path(path.clone());
+        self.memory.add_path(path);
+    }
+}
+
+impl WithMetadata for RawExecutor {
+    fn add_text(&mut self, text: Arc<str>) {
+        if let Some(docker) = &mut self.docker {
+            docker.value.image.add_text(text.clone());
+            docker.add_text(text);
+        }
+    }
+
+    fn add_path(&mut self, path: Arc<str>) {
+        if let Some(docker) = &mut self.docker {
+            docker.value.image.add_path(path.clone());
+            docker.add_path(path);
+        }
+    }
+}
+
+impl WithMetadata for RawTaskHooks {
+    fn add_text(&mut self, text: Arc<str>) {
+        self.before.add_text(text.clone());
+        self.after.add_text(text.clone());
+        self.hash.add_text(text);
+    }
+
+    fn add_path(&mut self, path: Arc<str>) {
+        self.before.add_path(path.clone());
+        self.after.add_path(path.clone());
+        self.hash.add_path(path);
+    }
+}
+
+impl WithMetadata for RawPlatformOverrides {
+    fn add_text(&mut self, text: Arc<str>) {
+        self.command.add_text(text.clone());
+        self.pass_through_args.add_text(text);
+    }
+
+    fn add_path(&mut self, path: Arc<str>) {
+        self.command.add_path(path.clone());
+        self.pass_through_args.add_path(path);
+    }
+}
+
+impl WithMetadata for RawPlatforms {
+    fn add_text(&mut self, text: Arc<str>) {
+        self.windows.add_text(text.clone());
+        self.macos.add_text(text.clone());
+        self.linux.add_text(text);
+    }
+
+    fn add_path(&mut self, path: Arc<str>) {
+        self.windows.add_path(path.clone());
+        self.macos.add_path(path.clone());
+        self.linux.add_path(path);
+    }
+}
+
 impl WithMetadata for RawTaskDefinition {
     fn add_text(&mut self, text: Arc<str>) {
         self.depends_on.add_text(text.clone());
@@ -658,7 +1264,14 @@ impl WithMetadata for RawTaskDefinition {
         self.pass_through_env.add_text(text.clone());
         self.persistent.add_text(text.clone());
         self.outputs.add_text(text.clone());
-        self.output_mode.add_text(text);
+        self.output_mode.add_text(text.clone());
+        self.pty.add_text(text.clone());
+        self.shell.add_text(text.clone());
+        self.resources.add_text(text.clone());
+        self.priority.add_text(text.clone());
+        self.executor.add_text(text.clone());
+        self.hooks.add_text(text.clone());
+        self.platforms.add_text(text);
     }
 
     fn add_path(&mut self, path: Arc<str>) {
@@ -672,7 +1285,14 @@ impl WithMetadata for RawTaskDefinition {
         self.pass_through_env.add_path(path.clone());
         self.persistent.add_path(path.clone());
         self.outputs.add_path(path.clone());
-        self.output_mode.add_path(path);
+        self.output_mode.add_path(path.clone());
+        self.pty.add_path(path.clone());
+        self.shell.add_path(path.clone());
+        self.resources.add_path(path.clone());
+        self.priority.add_path(path.clone());
+        self.executor.add_path(path.clone());
+        self.hooks.add_path(path.clone());
+        self.platforms.add_path(path);
     }
 }
 
@@ -254,6 +254,26 @@ impl<'a> Locator<'a> {
         // THis has an issue of cutting off the last char
         Locator::from_patch_reference(&self.reference)
     }
+
+    /// The start of the file name Yarn uses for this locator in its cache.
+    /// Yarn follows this with a hash of the locator and the package checksum.
+    pub fn cache_file_prefix(&self) -> String {
+        let ident = match &self.ident.scope {
+            Some(scope) => format!("@{scope}-{}", self.ident.name),
+            None => self.ident.name.to_string(),
+        };
+        let (protocol, selector) = match self.reference.split_once(':') {
+            Some((protocol, selector)) if !protocol.contains('#') => (protocol, selector),
+            _ => ("exotic", self.reference.as_ref()),
+        };
+        let selector = selector
+            .split_once('#')
+            .map_or(selector, |(selector, _)| selector);
+        match semver::Version::parse(selector) {
+            Ok(_) => format!("{ident}-{protocol}-{selector}-"),
+            Err(_) => format!("{ident}-{protocol}-"),
+        }
+    }
 }
 
 impl<'a> From<Locator<'a>> for Descriptor<'a> {
@@ -269,6 +289,7 @@ impl<'a> From<Locator<'a>> for Descriptor<'a> {
 #[cfg(test)]
 mod test {
     use pretty_assertions::assert_eq;
+    use test_case::test_case;
 
     use super::*;
 
@@ -349,6 +370,18 @@ mod test {
         }
     }
 
+    #[test_case("react@npm:18.2.0", "react-npm-18.2.0-" ; "npm")]
+    #[test_case("@babel/core@npm:7.20.12", "@babel-core-npm-7.20.12-" ; "scoped")]
+    #[test_case(
+        "is-odd@patch:is-odd@npm%3A3.0.1#./.yarn/patches/is-odd-npm-3.0.1.patch::version=3.0.1&hash=0b8ff4",
+        "is-odd-patch-" ;
+        "patch"
+    )]
+    fn test_locator_cache_file_prefix(locator: &str, expected: &str) {
+        let locator = Locator::try_from(locator).unwrap();
+        assert_eq!(locator.cache_file_prefix(), expected);
+    }
+
     #[test]
     fn test_locator_patch_file() {
         struct TestCase {
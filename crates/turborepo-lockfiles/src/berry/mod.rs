@@ -511,6 +511,43 @@ impl Lockfile for BerryLockfile {
         Ok(patches)
     }
 
+    fn patches_for(
+        &self,
+        packages: &HashSet<crate::Package>,
+    ) -> Result<Vec<RelativeUnixPathBuf>, crate::Error> {
+        let mut patches = Vec::new();
+        for package in packages {
+            let locator = Locator::try_from(package.key.as_str()).map_err(Error::from)?;
+            // Patched packages usually resolve to the patch locator, but fall back to
+            // looking up any patch applied to the original locator.
+            let patch_file = locator
+                .patch_file()
+                .or_else(|| self.patches.get(&locator).and_then(|p| p.patch_file()));
+            if let Some(patch_file) = patch_file.filter(|path| !Locator::is_patch_builtin(path)) {
+                patches.push(RelativeUnixPathBuf::new(patch_file)?);
+            }
+        }
+        patches.sort();
+        patches.dedup();
+        Ok(patches)
+    }
+
+    fn overrides_for(&self, packages: &HashSet<crate::Package>) -> Vec<(String, String)> {
+        let idents = packages
+            .iter()
+            .filter_map(|package| Locator::try_from(package.key.as_str()).ok())
+            .map(|locator| locator.ident)
+            .collect::<HashSet<_>>();
+        let mut overrides = self
+            .overrides
+            .iter()
+            .filter(|(resolution, _)| idents.contains(resolution.ident()))
+            .map(|(resolution, reference)| (resolution.to_string(), reference.clone()))
+            .collect::<Vec<_>>();
+        overrides.sort();
+        overrides
+    }
+
     fn global_change(&self, other: &dyn Lockfile) -> bool {
         let any_other = other as &dyn Any;
         if let Some(other) = any_other.downcast_ref::<Self>() {
@@ -520,6 +557,48 @@ impl Lockfile for BerryLockfile {
             true
         }
     }
+
+    fn strip_dev_dependencies(&mut self) -> Result<(), crate::Error> {
+        // Workspace entries list devDependencies together with the other
+        // dependencies, so they can't be told apart
+        Err(crate::Error::DevDependenciesUnsupported("yarn berry"))
+    }
+
+    fn cache_entries(&self, cache_files: &[String]) -> Vec<String> {
+        // Cache files are named `<prefix><locator hash>-<checksum>.zip`, we can't
+        // compute the locator hash so we match on the prefix and checksum.
+        let mut prefixes: HashMap<&str, Vec<String>> = HashMap::new();
+        for (locator, package) in &self.locator_package {
+            let Some(checksum) = package.checksum.as_deref() else {
+                continue;
+            };
+            // Yarn 4 prefixes checksums with the cache key e.g. `10c0/<hash>`
+            let checksum = checksum.rsplit_once('/').map_or(checksum, |(_, hash)| hash);
+            let Some(checksum) = checksum.get(..10) else {
+                continue;
+            };
+            prefixes
+                .entry(checksum)
+                .or_default()
+                .push(locator.cache_file_prefix());
+        }
+
+        cache_files
+            .iter()
+            .filter(|file| {
+                let Some((slug, checksum)) = file
+                    .strip_suffix(".zip")
+                    .and_then(|file| file.rsplit_once('-'))
+                else {
+                    return false;
+                };
+                prefixes.get(checksum).map_or(false, |prefixes| {
+                    prefixes.iter().any(|p| slug.starts_with(p))
+                })
+            })
+            .cloned()
+            .collect()
+    }
 }
 
 impl LockfileData {
@@ -619,6 +698,16 @@ mod test {
         assert_eq!(contents, new_contents);
     }
 
+    #[test]
+    fn test_strip_dev_dependencies_unsupported() {
+        let data = LockfileData::from_bytes(include_bytes!("../../fixtures/berry.lock")).unwrap();
+        let mut lockfile = BerryLockfile::new(data, None).unwrap();
+        assert!(matches!(
+            lockfile.strip_dev_dependencies(),
+            Err(crate::Error::DevDependenciesUnsupported(_))
+        ));
+    }
+
     #[test]
     fn test_resolve_package() {
         let data: LockfileData =
@@ -762,6 +851,25 @@ mod test {
         assert_eq!(lodash_desc.unwrap().reference, "npm:4.17.21");
     }
 
+    #[test]
+    fn test_cache_entries() {
+        let data: LockfileData =
+            serde_yaml::from_str(include_str!("../../fixtures/minimal-berry.lock")).unwrap();
+        let lockfile = BerryLockfile::new(data, None).unwrap();
+        let cache_files = vec![
+            "lodash-npm-4.17.21-6382451519-eb835a2e51.zip".to_string(),
+            "lodash-npm-4.17.20-6382451519-eb835a2e51.zip".to_string(),
+            "react-npm-18.2.0-1fa3d5ebee-88e38092da.zip".to_string(),
+        ];
+        assert_eq!(
+            lockfile.cache_entries(&cache_files),
+            vec!["lodash-npm-4.17.21-6382451519-eb835a2e51.zip".to_string()]
+        );
+
+        let pruned_lockfile = lockfile.subgraph(&["packages/c".into()], &[]).unwrap();
+        assert!(pruned_lockfile.cache_entries(&cache_files).is_empty());
+    }
+
     #[test]
     fn test_closure_with_patch() {
         let data = LockfileData::from_bytes(include_bytes!("../../fixtures/berry.lock")).unwrap();
@@ -832,6 +940,15 @@ mod test {
         };
         let lockfile = BerryLockfile::new(data, Some(manifest)).unwrap();
 
+        assert_eq!(
+            lockfile.overrides_for(
+                &[Package::new("ms@npm:0.6.0", "0.6.0")]
+                    .into_iter()
+                    .collect()
+            ),
+            vec![("debug/ms".to_string(), "0.6.0".to_string())]
+        );
+
         let deps = lockfile
             .all_dependencies("debug@npm:1.0.0")
             .unwrap()
@@ -1104,6 +1221,7 @@ mod test {
             ];
         assert_eq!(lockfile.patches().unwrap(), patches);
         assert_eq!(subgraph.patches().unwrap(), patches);
+        assert_eq!(lockfile.patches_for(&deps).unwrap(), patches);
     }
 
     #[test]
@@ -1146,4 +1264,52 @@ mod test {
             ]
         );
     }
+
+    #[test]
+    fn test_yarn4_workspace_ranges() {
+        let data =
+            LockfileData::from_bytes(include_bytes!("../../fixtures/yarn4-workspace-ranges.lock"))
+                .unwrap();
+        let lockfile = BerryLockfile::new(data, None).unwrap();
+
+        for (name, range, key) in [
+            ("b", "workspace:^", "b@workspace:pkgs/b"),
+            ("c", "workspace:~", "c@workspace:pkgs/c"),
+            ("c", "workspace:pkgs/c", "c@workspace:pkgs/c"),
+        ] {
+            let package = lockfile
+                .resolve_package("pkgs/a", name, range)
+                .unwrap()
+                .unwrap();
+            assert_eq!(package.key, key);
+        }
+
+        let closure = crate::transitive_closure(
+            &lockfile,
+            "pkgs/a",
+            HashMap::from([("is-odd".to_string(), "^3.0.1".to_string())]),
+        )
+        .unwrap();
+        let mut keys = closure
+            .iter()
+            .map(|package| package.key.as_str())
+            .collect::<Vec<_>>();
+        keys.sort();
+        assert_eq!(keys, vec!["is-number@npm:6.0.0", "is-odd@npm:3.0.1"]);
+
+        // Pruning keeps the workspace ranges so the lockfile stays valid
+        let subgraph = lockfile
+            .subgraph(
+                &["pkgs/a".into(), "pkgs/b".into(), "pkgs/c".into()],
+                &["is-odd@npm:3.0.1".into(), "is-number@npm:6.0.0".into()],
+            )
+            .unwrap();
+        let sublockfile = subgraph.lockfile().unwrap();
+        assert!(sublockfile
+            .packages
+            .contains_key("b@workspace:^, b@workspace:pkgs/b"));
+        assert!(sublockfile
+            .packages
+            .contains_key("c@workspace:pkgs/c, c@workspace:~"));
+    }
 }
@@ -94,6 +94,11 @@ fn parse_specifier(specifier: Pair<'_, Rule>) -> Result<Option<Specifier>, Error
 }
 
 impl Resolution {
+    /// The package that this resolution overrides
+    pub fn ident(&self) -> &Ident<'static> {
+        self.descriptor.ident()
+    }
+
     /// Returns a new descriptor if an override is applicable
     // reference: version that this resolution resolves to
     // locator: package that depends on the dependency
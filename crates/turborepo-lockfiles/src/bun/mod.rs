@@ -2,9 +2,11 @@ use std::{any::Any, str::FromStr};
 
 use serde::Deserialize;
 
+pub use self::text::BunTextLockfile;
 use crate::Lockfile;
 
 mod de;
+mod text;
 
 type Map<K, V> = std::collections::BTreeMap<K, V>;
 
@@ -118,6 +120,11 @@ impl Lockfile for BunLockfile {
         // if the types don't match then we changed package managers
         any_other.downcast_ref::<Self>().is_none()
     }
+
+    fn strip_dev_dependencies(&mut self) -> Result<(), crate::Error> {
+        // Like yarn 1, workspaces don't have entries in the lockfile
+        Ok(())
+    }
 }
 
 impl Entry {
@@ -144,6 +151,14 @@ mod test {
     use super::*;
     const FULL: &str = include_str!("../../fixtures/yarn1full.lock");
 
+    #[test]
+    fn test_strip_dev_dependencies() {
+        let mut lockfile = BunLockfile::from_str(FULL).unwrap();
+        let contents = lockfile.encode().unwrap();
+        lockfile.strip_dev_dependencies().unwrap();
+        assert_eq!(contents, lockfile.encode().unwrap());
+    }
+
     #[test]
     fn test_key_splitting() {
         let lockfile = BunLockfile::from_str(FULL).unwrap();
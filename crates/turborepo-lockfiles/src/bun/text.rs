@@ -0,0 +1,426 @@
+use std::{
+    any::Any,
+    collections::{HashMap, HashSet},
+    iter,
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::Error;
+use crate::{Lockfile, Package};
+
+type Map<K, V> = std::collections::BTreeMap<K, V>;
+
+/// The text based `bun.lock` lockfile.
+/// It's JSON with trailing commas allowed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BunTextLockfile {
+    lockfile_version: i32,
+    workspaces: Map<String, WorkspaceEntry>,
+    #[serde(default)]
+    packages: Map<String, PackageEntry>,
+    // We want to reserialize any additional fields, but we don't use them
+    #[serde(flatten)]
+    other: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WorkspaceEntry {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(flatten)]
+    other: Map<String, Value>,
+}
+
+// Packages are encoded as a tuple of [ident, resolution, info, integrity] where
+// the exact layout depends on the type of resolution, so we keep the raw values
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PackageEntry(Vec<Value>);
+
+const DEPENDENCY_FIELDS: &[&str] = &["dependencies", "optionalDependencies", "peerDependencies"];
+
+impl BunTextLockfile {
+    pub fn from_bytes(input: &[u8]) -> Result<Self, crate::Error> {
+        let input = std::str::from_utf8(input).map_err(Error::from)?;
+        let json = strip_trailing_commas(input);
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    /// Checks if the contents look like a text lockfile as opposed to the
+    /// output of `bun bun.lockb`
+    pub fn is_text_lockfile(input: &[u8]) -> bool {
+        input
+            .iter()
+            .find(|b| !b.is_ascii_whitespace())
+            .map_or(false, |b| *b == b'{')
+    }
+
+    fn workspace_name(&self, workspace_path: &str) -> Result<Option<&str>, crate::Error> {
+        let workspace = self
+            .workspaces
+            .get(workspace_path)
+            .ok_or_else(|| crate::Error::MissingWorkspace(workspace_path.to_string()))?;
+        Ok(workspace.name.as_deref())
+    }
+}
+
+impl PackageEntry {
+    fn ident(&self) -> Option<&str> {
+        self.0.first()?.as_str()
+    }
+
+    fn version(&self) -> &str {
+        self.ident()
+            // Skip the first character so we don't split on the @ of a scope
+            .and_then(|ident| ident.get(1..)?.split_once('@'))
+            .map_or("", |(_, version)| version)
+    }
+
+    fn info(&self) -> Option<&serde_json::Map<String, Value>> {
+        self.0.iter().find_map(Value::as_object)
+    }
+
+    fn dependencies(&self) -> impl Iterator<Item = (&str, &str)> + '_ {
+        let info = self.info();
+        DEPENDENCY_FIELDS
+            .iter()
+            .filter_map(move |field| info?.get(*field)?.as_object())
+            .flatten()
+            .filter_map(|(name, version)| Some((name.as_str(), version.as_str()?)))
+    }
+}
+
+impl Lockfile for BunTextLockfile {
+    #[tracing::instrument(skip(self))]
+    fn resolve_package(
+        &self,
+        workspace_path: &str,
+        name: &str,
+        version: &str,
+    ) -> Result<Option<Package>, crate::Error> {
+        // Keys returned from `all_dependencies` are already resolved and come with
+        // the exact version of the entry
+        if let Some(entry) = self.packages.get(name) {
+            if entry.version() == version {
+                return Ok(Some(Package::new(name, version)));
+            }
+        }
+
+        let workspace_name = self.workspace_name(workspace_path)?;
+        let possible_keys = workspace_name
+            .map(|workspace| format!("{workspace}/{name}"))
+            .into_iter()
+            .chain(iter::once(name.to_string()));
+        for key in possible_keys {
+            if let Some(entry) = self.packages.get(&key) {
+                let version = entry.version().to_string();
+                return Ok(Some(Package { key, version }));
+            }
+        }
+
+        Ok(None)
+    }
+
+    #[tracing::instrument(skip(self))]
+    fn all_dependencies(&self, key: &str) -> Result<Option<HashMap<String, String>>, crate::Error> {
+        let Some(entry) = self.packages.get(key) else {
+            return Ok(None);
+        };
+
+        let mut deps = HashMap::new();
+        for (name, _) in entry.dependencies() {
+            // Nested packages are keyed by the path of packages that depend on them
+            // e.g. `a/b/c` is the version of `c` used by `a/b`
+            let parents = iter::successors(Some(key), |prefix| parent_key(prefix));
+            let resolved = parents
+                .map(|parent| format!("{parent}/{name}"))
+                .chain(iter::once(name.to_string()))
+                .find_map(|possible_key| {
+                    let entry = self.packages.get(&possible_key)?;
+                    Some((possible_key, entry.version().to_string()))
+                });
+            if let Some((resolved_key, version)) = resolved {
+                deps.insert(resolved_key, version);
+            }
+        }
+
+        Ok(Some(deps))
+    }
+
+    fn subgraph(
+        &self,
+        workspace_packages: &[String],
+        packages: &[String],
+    ) -> Result<Box<dyn Lockfile>, crate::Error> {
+        let workspaces = self
+            .workspaces
+            .iter()
+            .filter(|(path, _)| path.is_empty() || workspace_packages.contains(path))
+            .map(|(path, entry)| (path.clone(), entry.clone()))
+            .collect::<Map<_, _>>();
+
+        let workspace_idents = workspaces
+            .iter()
+            .filter_map(|(path, entry)| {
+                let name = entry.name.as_deref()?;
+                Some(format!("{name}@workspace:{path}"))
+            })
+            .collect::<HashSet<_>>();
+
+        let mut pruned_packages = Map::new();
+        for key in packages {
+            let entry = self
+                .packages
+                .get(key)
+                .ok_or_else(|| crate::Error::MissingPackage(key.clone()))?;
+            pruned_packages.insert(key.clone(), entry.clone());
+        }
+        for (key, entry) in &self.packages {
+            if entry
+                .ident()
+                .map_or(false, |ident| workspace_idents.contains(ident))
+            {
+                pruned_packages.insert(key.clone(), entry.clone());
+            }
+        }
+
+        Ok(Box::new(Self {
+            lockfile_version: self.lockfile_version,
+            workspaces,
+            packages: pruned_packages,
+            other: self.other.clone(),
+        }))
+    }
+
+    fn encode(&self) -> Result<Vec<u8>, crate::Error> {
+        let mut contents = serde_json::to_vec_pretty(&self)?;
+        contents.push(b'\n');
+        Ok(contents)
+    }
+
+    fn global_change(&self, other: &dyn Lockfile) -> bool {
+        let any_other = other as &dyn Any;
+        if let Some(other) = any_other.downcast_ref::<Self>() {
+            self.lockfile_version != other.lockfile_version
+        } else {
+            true
+        }
+    }
+
+    fn strip_dev_dependencies(&mut self) -> Result<(), crate::Error> {
+        for workspace in self.workspaces.values_mut() {
+            workspace.other.remove("devDependencies");
+        }
+        Ok(())
+    }
+}
+
+// Strips the last package name from a key, e.g. `a/@scope/b` becomes `a`.
+// Scoped names contain a `/` themselves, so keys can't be split on every `/`.
+fn parent_key(key: &str) -> Option<&str> {
+    let (parent, _) = key.rsplit_once('/')?;
+    match parent.rsplit_once('/') {
+        Some((grandparent, scope)) if scope.starts_with('@') => Some(grandparent),
+        None if parent.starts_with('@') => None,
+        _ => Some(parent),
+    }
+}
+
+// Removes any commas that are directly followed by the closing of an object or
+// array so the contents can be parsed as JSON
+fn strip_trailing_commas(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    for (idx, c) in input.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => (),
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if c == ',' {
+            let next = input[idx + 1..].trim_start().chars().next();
+            if matches!(next, Some('}') | Some(']')) {
+                continue;
+            }
+        }
+        output.push(c);
+    }
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const LOCKFILE: &str = r#"{
+  "lockfileVersion": 0,
+  "workspaces": {
+    "": {
+      "name": "monorepo",
+      "devDependencies": {
+        "turbo": "^1.12.0",
+      },
+    },
+    "apps/docs": {
+      "name": "docs",
+      "dependencies": {
+        "is-odd": "^3.0.1",
+        "ui": "workspace:*",
+      },
+    },
+    "apps/web": {
+      "name": "web",
+      "dependencies": {
+        "is-odd": "^2.0.0",
+      },
+    },
+    "packages/ui": {
+      "name": "ui",
+    },
+  },
+  "packages": {
+    "docs": ["docs@workspace:apps/docs"],
+    "is-number": ["is-number@6.0.0", "", {}, "sha512-a"],
+    "is-odd": ["is-odd@3.0.1", "", { "dependencies": { "is-number": "^6.0.0" } }, "sha512-b"],
+    "turbo": ["turbo@1.12.0", "", {}, "sha512-c"],
+    "ui": ["ui@workspace:packages/ui"],
+    "web": ["web@workspace:apps/web"],
+    "web/is-odd": ["is-odd@2.0.0", "", { "dependencies": { "is-number": "^4.0.0" } }, "sha512-d"],
+    "web/is-odd/is-number": ["is-number@4.0.0", "", {}, "sha512-e"],
+  }
+}
+"#;
+
+    #[test]
+    fn test_strip_trailing_commas() {
+        assert_eq!(
+            strip_trailing_commas(r#"{"a": [1, 2,], "b": ",}", }"#),
+            r#"{"a": [1, 2], "b": ",}" }"#
+        );
+    }
+
+    #[test]
+    fn test_nested_resolution() {
+        let lockfile = BunTextLockfile::from_bytes(LOCKFILE.as_bytes()).unwrap();
+        let closure = crate::transitive_closure(
+            &lockfile,
+            "apps/web",
+            HashMap::from([("is-odd".to_string(), "^2.0.0".to_string())]),
+        )
+        .unwrap();
+        assert_eq!(
+            closure,
+            HashSet::from([
+                Package::new("web/is-odd", "2.0.0"),
+                Package::new("web/is-odd/is-number", "4.0.0"),
+            ])
+        );
+
+        let closure = crate::transitive_closure(
+            &lockfile,
+            "apps/docs",
+            HashMap::from([("is-odd".to_string(), "^3.0.1".to_string())]),
+        )
+        .unwrap();
+        assert_eq!(
+            closure,
+            HashSet::from([
+                Package::new("is-odd", "3.0.1"),
+                Package::new("is-number", "6.0.0"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parent_key() {
+        assert_eq!(parent_key("a"), None);
+        assert_eq!(parent_key("@scope/a"), None);
+        assert_eq!(parent_key("a/b"), Some("a"));
+        assert_eq!(parent_key("a/@scope/b"), Some("a"));
+        assert_eq!(parent_key("@scope/a/b"), Some("@scope/a"));
+        assert_eq!(parent_key("@scope/a/@other/b"), Some("@scope/a"));
+    }
+
+    #[test]
+    fn test_scoped_nested_resolution() {
+        let lockfile = BunTextLockfile::from_bytes(
+            br#"{
+  "lockfileVersion": 0,
+  "workspaces": {
+    "": {
+      "name": "monorepo",
+      "dependencies": {
+        "@scope/a": "^1.0.0",
+      },
+    },
+  },
+  "packages": {
+    "@scope/a": ["@scope/a@1.0.0", "", { "dependencies": { "@scope/b": "^1.0.0" } }, "sha512-a"],
+    "@scope/a/@scope/b": ["@scope/b@1.0.0", "", { "dependencies": { "c": "^1.0.0" } }, "sha512-b"],
+    "@scope/b": ["@scope/b@2.0.0", "", {}, "sha512-c"],
+    "@scope/a/@scope/c": ["@scope/c@1.0.0", "", {}, "sha512-f"],
+    "@scope/a/c": ["c@1.0.0", "", {}, "sha512-d"],
+    "c": ["c@2.0.0", "", {}, "sha512-e"],
+  }
+}
+"#,
+        )
+        .unwrap();
+        let closure = crate::transitive_closure(
+            &lockfile,
+            "",
+            HashMap::from([("@scope/a".to_string(), "^1.0.0".to_string())]),
+        )
+        .unwrap();
+        // `c` of the nested `@scope/b` comes from `@scope/a`, splitting the key on
+        // every `/` would look up `@scope/a/@scope/c` instead
+        assert_eq!(
+            closure,
+            HashSet::from([
+                Package::new("@scope/a", "1.0.0"),
+                Package::new("@scope/a/@scope/b", "1.0.0"),
+                Package::new("@scope/a/c", "1.0.0"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_subgraph() {
+        let lockfile = BunTextLockfile::from_bytes(LOCKFILE.as_bytes()).unwrap();
+        let pruned = lockfile
+            .subgraph(
+                &["apps/docs".into(), "packages/ui".into()],
+                &["is-odd".into(), "is-number".into()],
+            )
+            .unwrap();
+        let pruned = BunTextLockfile::from_bytes(&pruned.encode().unwrap()).unwrap();
+        assert_eq!(
+            pruned.workspaces.keys().collect::<Vec<_>>(),
+            vec!["", "apps/docs", "packages/ui"]
+        );
+        assert_eq!(
+            pruned.packages.keys().collect::<Vec<_>>(),
+            vec!["docs", "is-number", "is-odd", "ui"]
+        );
+    }
+
+    #[test]
+    fn test_strip_dev_dependencies() {
+        let mut lockfile = BunTextLockfile::from_bytes(LOCKFILE.as_bytes()).unwrap();
+        lockfile.strip_dev_dependencies().unwrap();
+        let root = lockfile.workspaces.get("").unwrap();
+        assert!(!root.other.contains_key("devDependencies"));
+        let docs = lockfile.workspaces.get("apps/docs").unwrap();
+        assert!(docs.other.contains_key("dependencies"));
+    }
+}
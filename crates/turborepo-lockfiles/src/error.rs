@@ -22,6 +22,8 @@ pub enum Error {
     Bun(#[from] crate::bun::Error),
     #[error(transparent)]
     Berry(#[from] crate::berry::Error),
+    #[error("Removing devDependencies from {0} lockfiles is not supported")]
+    DevDependenciesUnsupported(&'static str),
     #[error("lockfile contains invalid path: {0}")]
     Path(#[from] turbopath::PathError),
 }
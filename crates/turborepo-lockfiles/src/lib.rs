@@ -10,11 +10,11 @@ mod yarn1;
 
 use std::{
     any::Any,
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
 };
 
 pub use berry::{Error as BerryError, *};
-pub use bun::BunLockfile;
+pub use bun::{BunLockfile, BunTextLockfile};
 pub use error::Error;
 pub use npm::*;
 pub use pnpm::{pnpm_global_change, pnpm_subgraph, PnpmLockfile};
@@ -58,8 +58,41 @@ pub trait Lockfile: Send + Sync + Any + std::fmt::Debug {
         Ok(Vec::new())
     }
 
+    /// Patch files that are applied to any of the given packages
+    fn patches_for(&self, _packages: &HashSet<Package>) -> Result<Vec<RelativeUnixPathBuf>, Error> {
+        Ok(Vec::new())
+    }
+
+    /// Patch files out of `patched_dependencies`, which maps either a package
+    /// name or `name@version` to a patch file, that are applied to any of the
+    /// given packages
+    fn configured_patches_for(
+        &self,
+        _packages: &HashSet<Package>,
+        _patched_dependencies: &BTreeMap<String, RelativeUnixPathBuf>,
+    ) -> Vec<RelativeUnixPathBuf> {
+        Vec::new()
+    }
+
+    /// Overrides, as pairs of selector and replacement, that target any of the
+    /// given packages
+    fn overrides_for(&self, _packages: &HashSet<Package>) -> Vec<(String, String)> {
+        Vec::new()
+    }
+
     /// Determine if there's a global change between two lockfiles
     fn global_change(&self, other: &dyn Lockfile) -> bool;
+
+    /// Remove devDependencies from every workspace entry in the lockfile.
+    /// Errors if the lockfile can't be pruned to production dependencies.
+    fn strip_dev_dependencies(&mut self) -> Result<(), Error>;
+
+    /// Filters the file names of a package manager's in-repository cache down
+    /// to the entries for packages present in this lockfile. Used for Yarn's
+    /// zero-installs where the cache is checked into the repository.
+    fn cache_entries(&self, _cache_files: &[String]) -> Vec<String> {
+        Vec::new()
+    }
 }
 
 /// Takes a lockfile, and a map of workspace directory paths -> (package name,
@@ -124,6 +157,19 @@ fn transitive_closure_helper<L: Lockfile + ?Sized>(
     Ok(())
 }
 
+// Overrides can be scoped to a parent package e.g. `foo@1>bar@2`, the package
+// being overridden is always the last one in the selector.
+fn override_target(selector: &str) -> &str {
+    let target = selector
+        .rsplit_once('>')
+        .map_or(selector, |(_, target)| target);
+    // Skip the leading `@` of scoped packages when looking for a version range
+    match target.get(1..).and_then(|rest| rest.find('@')) {
+        Some(idx) => &target[..idx + 1],
+        None => target,
+    }
+}
+
 impl Package {
     pub fn new(key: impl Into<String>, version: impl Into<String>) -> Self {
         let key = key.into();
@@ -1,9 +1,12 @@
-use std::{any::Any, collections::HashMap};
+use std::{
+    any::Any,
+    collections::{HashMap, HashSet},
+};
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use super::{Error, Lockfile, Package};
+use super::{override_target, Error, Lockfile, Package};
 
 type Map<K, V> = std::collections::BTreeMap<K, V>;
 
@@ -23,6 +26,10 @@ pub struct NpmLockfile {
     // we keep them as raw values to avoid describing the correct schema.
     #[serde(flatten)]
     other: Map<String, Value>,
+    // npm doesn't record the overrides of the root package.json in the lockfile,
+    // so they're added separately as pairs of selector and replacement
+    #[serde(skip)]
+    overrides: Vec<(String, String)>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -131,6 +138,7 @@ impl Lockfile for NpmLockfile {
             packages: pruned_packages,
             dependencies: Map::default(),
             other: self.other.clone(),
+            overrides: self.overrides.clone(),
         }))
     }
 
@@ -138,6 +146,25 @@ impl Lockfile for NpmLockfile {
         Ok(serde_json::to_vec_pretty(&self)?)
     }
 
+    fn overrides_for(&self, packages: &HashSet<Package>) -> Vec<(String, String)> {
+        let names = packages
+            .iter()
+            .filter_map(|package| package.key.rsplit_once("node_modules/"))
+            .map(|(_, name)| name)
+            .collect::<HashSet<_>>();
+        self.overrides
+            .iter()
+            .filter(|(selector, replacement)| {
+                names.contains(override_target(selector))
+                    // Aliased overrides resolve to a package with a different name
+                    || replacement
+                        .strip_prefix("npm:")
+                        .is_some_and(|alias| names.contains(override_target(alias)))
+            })
+            .cloned()
+            .collect()
+    }
+
     fn global_change(&self, other: &dyn Lockfile) -> bool {
         let any_other = other as &dyn Any;
         if let Some(other) = any_other.downcast_ref::<Self>() {
@@ -147,6 +174,17 @@ impl Lockfile for NpmLockfile {
             true
         }
     }
+
+    fn strip_dev_dependencies(&mut self) -> Result<(), crate::Error> {
+        // Workspace entries are the only ones that aren't installed into a
+        // node_modules directory
+        for (key, package) in self.packages.iter_mut() {
+            if !key.contains("node_modules/") {
+                package.dev_dependencies.clear();
+            }
+        }
+        Ok(())
+    }
 }
 
 impl NpmLockfile {
@@ -166,6 +204,13 @@ impl NpmLockfile {
         }
     }
 
+    /// Adds the `overrides` of the root package.json
+    pub fn with_overrides(mut self, overrides: &Value) -> Self {
+        flatten_overrides(None, overrides, &mut self.overrides);
+        self.overrides.sort();
+        self
+    }
+
     fn get_package(&self, package: impl AsRef<str>) -> Result<&NpmPackage, Error> {
         let pkg_str = package.as_ref();
         self.packages
@@ -209,6 +254,36 @@ impl NpmPackage {
     }
 }
 
+// Overrides can be nested to only apply to the dependencies of a package, in
+// which case the `.` key overrides the package itself. They're flattened into
+// selectors like `foo>bar`, the format pnpm uses.
+fn flatten_overrides(
+    parent: Option<&str>,
+    overrides: &Value,
+    flattened: &mut Vec<(String, String)>,
+) {
+    let Value::Object(overrides) = overrides else {
+        return;
+    };
+    for (key, value) in overrides {
+        if key == "." {
+            if let (Some(parent), Some(replacement)) = (parent, value.as_str()) {
+                flattened.push((parent.to_string(), replacement.to_string()));
+            }
+            continue;
+        }
+        let selector = match parent {
+            Some(parent) => format!("{parent}>{key}"),
+            None => key.clone(),
+        };
+        match value {
+            Value::String(replacement) => flattened.push((selector, replacement.clone())),
+            Value::Object(_) => flatten_overrides(Some(&selector), value, flattened),
+            _ => (),
+        }
+    }
+}
+
 pub fn npm_subgraph(
     contents: &[u8],
     workspace_packages: &[String],
@@ -296,6 +371,17 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_strip_dev_dependencies() -> Result<(), Error> {
+        let mut lockfile = NpmLockfile::load(include_bytes!("../fixtures/npm-lock.json"))?;
+        lockfile.strip_dev_dependencies()?;
+        for key in ["", "apps/docs", "apps/web", "packages/ui"] {
+            assert!(lockfile.packages[key].dev_dependencies.is_empty());
+        }
+        assert!(!lockfile.packages["apps/web"].dependencies.is_empty());
+        Ok(())
+    }
+
     #[test]
     fn test_resolve_package() -> Result<(), Error> {
         let lockfile = NpmLockfile::load(include_bytes!("../fixtures/npm-lock.json"))?;
@@ -449,4 +535,34 @@ mod test {
         assert!(closures.get("packages/c").unwrap().is_empty());
         Ok(())
     }
+
+    #[test]
+    fn test_overrides_for() {
+        let lockfile = NpmLockfile::load(include_bytes!("../fixtures/npm-lock.json"))
+            .unwrap()
+            .with_overrides(&serde_json::json!({
+                "foo": "1.0.0",
+                "bar": {
+                    ".": "2.0.0",
+                    "@scope/baz@<3": "3.0.0"
+                },
+                "qux": "npm:@scope/quux@1.0.0"
+            }));
+        let packages = [
+            Package::new("node_modules/bar", "2.0.0"),
+            Package::new("node_modules/bar/node_modules/@scope/baz", "3.0.0"),
+            Package::new("node_modules/@scope/quux", "1.0.0"),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            lockfile.overrides_for(&packages),
+            vec![
+                ("bar".to_string(), "2.0.0".to_string()),
+                ("bar>@scope/baz@<3".to_string(), "3.0.0".to_string()),
+                ("qux".to_string(), "npm:@scope/quux@1.0.0".to_string()),
+            ]
+        );
+        assert!(lockfile.overrides_for(&HashSet::new()).is_empty());
+    }
 }
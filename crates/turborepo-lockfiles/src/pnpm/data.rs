@@ -1,9 +1,14 @@
-use std::{any::Any, borrow::Cow, collections::BTreeMap};
+use std::{
+    any::Any,
+    borrow::Cow,
+    collections::{BTreeMap, HashSet},
+};
 
 use serde::{Deserialize, Serialize};
 use turbopath::RelativeUnixPathBuf;
 
 use super::{dep_path::DepPath, Error, LockfileVersion};
+use crate::override_target;
 
 type Map<K, V> = std::collections::BTreeMap<K, V>;
 
@@ -14,10 +19,14 @@ pub struct PnpmLockfile {
     #[serde(skip_serializing_if = "Option::is_none")]
     settings: Option<LockfileSettings>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    catalogs: Option<Map<String, serde_yaml::Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     never_built_dependencies: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     only_built_dependencies: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    ignored_optional_dependencies: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     overrides: Option<Map<String, String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     package_extensions_checksum: Option<String>,
@@ -26,6 +35,10 @@ pub struct PnpmLockfile {
     importers: Map<String, ProjectSnapshot>,
     #[serde(skip_serializing_if = "Option::is_none")]
     packages: Option<Map<String, PackageSnapshot>>,
+    // With v9 the dependencies of packages are stored separately from their
+    // metadata, keyed by the dependency path including the peer suffix
+    #[serde(skip_serializing_if = "Option::is_none")]
+    snapshots: Option<Map<String, PackageSnapshot>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     time: Option<Map<String, String>>,
 }
@@ -83,7 +96,9 @@ pub struct Dependency {
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct PackageSnapshot {
-    resolution: PackageResolution,
+    // Snapshots of v9 lockfiles don't have a resolution
+    #[serde(skip_serializing_if = "Option::is_none")]
+    resolution: Option<PackageResolution>,
     #[serde(skip_serializing_if = "Option::is_none")]
     id: Option<String>,
 
@@ -141,18 +156,87 @@ pub struct PackageResolution {
 struct LockfileSettings {
     auto_install_peers: Option<bool>,
     exclude_links_from_lockfile: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    inject_workspace_packages: Option<bool>,
 }
 
 impl PnpmLockfile {
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, crate::Error> {
-        let this = serde_yaml::from_slice(bytes)?;
-        Ok(this)
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Version {
+            lockfile_version: LockfileVersion,
+        }
+
+        match serde_yaml::from_slice::<Self>(bytes) {
+            Ok(this) if this.lockfile_version.is_supported() => Ok(this),
+            Ok(this) => Err(Error::UnsupportedVersion(this.lockfile_version.version).into()),
+            // A future version might not parse at all, that's still reported as
+            // an unsupported version so callers can fall back to the whole lockfile
+            Err(e) => match serde_yaml::from_slice::<Version>(bytes) {
+                Ok(Version { lockfile_version }) if !lockfile_version.is_supported() => {
+                    Err(Error::UnsupportedVersion(lockfile_version.version).into())
+                }
+                _ => Err(e.into()),
+            },
+        }
     }
 
+    // Returns the entry with the dependencies of a package
     fn get_packages(&self, key: &str) -> Option<&PackageSnapshot> {
-        self.packages
+        match self.is_v9() {
+            true => self.snapshots.as_ref(),
+            false => self.packages.as_ref(),
+        }
+        .and_then(|packages| packages.get(key))
+    }
+
+    // Returns the key of the entry with the metadata of a package, for v9
+    // that's the key without the peer suffix
+    fn metadata_key<'b>(&self, key: &'b str) -> Result<Cow<'b, str>, Error> {
+        match self.is_v9() {
+            true => {
+                let dp = DepPath::parse_v9(key)?;
+                Ok(format!("{}@{}", dp.name, dp.version).into())
+            }
+            false => Ok(key.into()),
+        }
+    }
+
+    // The version recorded for a package, only present for packages that
+    // aren't resolved from a registry
+    fn package_version(&self, key: &str) -> Result<Option<&str>, Error> {
+        let metadata_key = self.metadata_key(key)?;
+        Ok(self
+            .packages
             .as_ref()
-            .and_then(|packages| packages.get(key))
+            .and_then(|packages| packages.get(metadata_key.as_ref()))
+            .and_then(|pkg| pkg.version.as_deref()))
+    }
+
+    // Entries of `patched_dependencies` that apply to any of the packages
+    fn matching_patches<'p, T>(
+        &self,
+        packages: &HashSet<crate::Package>,
+        patched_dependencies: &'p Map<String, T>,
+    ) -> Vec<&'p T> {
+        packages
+            .iter()
+            .filter_map(|package| self.dep_path(&package.key).ok())
+            .filter_map(|dp| {
+                // Patches can either target an exact version or all versions of a package
+                patched_dependencies
+                    .get(&format!("{}@{}", dp.name, dp.version))
+                    .or_else(|| patched_dependencies.get(dp.name))
+            })
+            .collect()
+    }
+
+    fn dep_path<'b>(&self, key: &'b str) -> Result<DepPath<'b>, Error> {
+        Ok(match self.is_v9() {
+            true => DepPath::parse_v9(key)?,
+            false => DepPath::try_from(key)?,
+        })
     }
 
     fn get_workspace(&self, workspace_path: &str) -> Result<&ProjectSnapshot, crate::Error> {
@@ -171,16 +255,23 @@ impl PnpmLockfile {
         matches!(self.lockfile_version.format, super::VersionFormat::String)
     }
 
+    fn is_v9(&self) -> bool {
+        self.lockfile_version
+            .major()
+            .is_some_and(|major| major >= 9)
+    }
+
     fn format_key(&self, name: &str, version: &str) -> String {
-        match self.is_v6() {
-            true => format!("/{name}@{version}"),
-            false => format!("/{name}/{version}"),
+        match (self.is_v9(), self.is_v6()) {
+            (true, _) => format!("{name}@{version}"),
+            (false, true) => format!("/{name}@{version}"),
+            (false, false) => format!("/{name}/{version}"),
         }
     }
 
     // Extracts the version from a dependency path
     fn extract_version<'a>(&self, key: &'a str) -> Result<Cow<'a, str>, Error> {
-        let dp = DepPath::try_from(key)?;
+        let dp = self.dep_path(key)?;
         // If there's a suffix, the suffix gets included as part of the version
         // so we can track patch file changes
         if let Some(suffix) = dp.peer_suffix {
@@ -235,12 +326,13 @@ impl PnpmLockfile {
     }
 
     fn prune_patches(
+        &self,
         patches: &Map<String, PatchFile>,
         pruned_packages: &Map<String, PackageSnapshot>,
     ) -> Result<Map<String, PatchFile>, Error> {
         let mut pruned_patches = Map::new();
         for dependency in pruned_packages.keys() {
-            let dp = DepPath::try_from(dependency.as_str())?;
+            let dp = self.dep_path(dependency)?;
             let patch_key = format!("{}@{}", dp.name, dp.version);
             if let Some(patch) = patches
                 .get(&patch_key)
@@ -261,6 +353,7 @@ impl PnpmLockfile {
             overrides: self.overrides.as_ref(),
             patched_dependencies: self.patched_dependencies.as_ref(),
             settings: self.settings.as_ref(),
+            ignored_optional_dependencies: self.ignored_optional_dependencies.as_deref(),
         }
     }
 }
@@ -272,6 +365,7 @@ struct GlobalFields<'a> {
     overrides: Option<&'a BTreeMap<String, String>>,
     patched_dependencies: Option<&'a BTreeMap<String, PatchFile>>,
     settings: Option<&'a LockfileSettings>,
+    ignored_optional_dependencies: Option<&'a [String]>,
 }
 
 impl crate::Lockfile for PnpmLockfile {
@@ -297,22 +391,17 @@ impl crate::Lockfile for PnpmLockfile {
 
         let key = self.format_key(name, resolved_version);
 
-        if let Some(pkg) = self.get_packages(&key) {
-            Ok(Some(crate::Package {
-                key,
-                version: pkg
-                    .version
-                    .clone()
-                    .unwrap_or_else(|| resolved_version.to_string()),
-            }))
-        } else if let Some(pkg) = self.get_packages(resolved_version) {
-            let version = pkg.version.clone().map_or_else(
-                || {
-                    self.extract_version(resolved_version)
-                        .map(|s| s.to_string())
-                },
-                Ok,
-            )?;
+        if self.get_packages(&key).is_some() {
+            let version = self
+                .package_version(&key)?
+                .unwrap_or(resolved_version)
+                .to_string();
+            Ok(Some(crate::Package { key, version }))
+        } else if self.get_packages(resolved_version).is_some() {
+            let version = match self.package_version(resolved_version)? {
+                Some(version) => version.to_string(),
+                None => self.extract_version(resolved_version)?.to_string(),
+            };
             Ok(Some(crate::Package {
                 key: resolved_version.to_string(),
                 version,
@@ -327,7 +416,7 @@ impl crate::Lockfile for PnpmLockfile {
         &self,
         key: &str,
     ) -> Result<Option<std::collections::HashMap<String, String>>, crate::Error> {
-        let Some(entry) = self.packages.as_ref().and_then(|pkgs| pkgs.get(key)) else {
+        let Some(entry) = self.get_packages(key) else {
             return Ok(None);
         };
         Ok(Some(
@@ -378,28 +467,53 @@ impl crate::Lockfile for PnpmLockfile {
                     .find_resolution(dependency)
                     .ok_or_else(|| Error::MissingInjectedPackage(dependency.clone()))?;
 
+                // Before v9 the version of an injected package is its key
+                let key = match self.is_v9() {
+                    true => self.format_key(dependency, version),
+                    false => version.to_string(),
+                };
                 let entry = self
-                    .get_packages(version)
-                    .ok_or_else(|| crate::Error::MissingPackage(version.into()))?;
-                pruned_packages.insert(version.to_string(), entry.clone());
+                    .get_packages(&key)
+                    .ok_or_else(|| crate::Error::MissingPackage(key.clone()))?;
+                pruned_packages.insert(key, entry.clone());
             }
         }
 
         let patches = self
             .patched_dependencies
             .as_ref()
-            .map(|patches| Self::prune_patches(patches, &pruned_packages))
+            .map(|patches| self.prune_patches(patches, &pruned_packages))
             .transpose()?;
 
+        let (packages, snapshots) = match self.is_v9() {
+            true => {
+                let mut pruned_metadata = Map::new();
+                for key in pruned_packages.keys() {
+                    let metadata_key = self.metadata_key(key)?.into_owned();
+                    let entry = self
+                        .packages
+                        .as_ref()
+                        .and_then(|packages| packages.get(&metadata_key))
+                        .ok_or_else(|| crate::Error::MissingPackage(metadata_key.clone()))?;
+                    pruned_metadata.insert(metadata_key, entry.clone());
+                }
+                (pruned_metadata, Some(pruned_packages))
+            }
+            false => (pruned_packages, None),
+        };
+
         Ok(Box::new(Self {
             importers,
-            packages: match pruned_packages.is_empty() {
-                false => Some(pruned_packages),
+            packages: match packages.is_empty() {
+                false => Some(packages),
                 true => None,
             },
+            snapshots: snapshots.filter(|snapshots| !snapshots.is_empty()),
             lockfile_version: self.lockfile_version.clone(),
+            catalogs: self.catalogs.clone(),
             never_built_dependencies: self.never_built_dependencies.clone(),
             only_built_dependencies: self.only_built_dependencies.clone(),
+            ignored_optional_dependencies: self.ignored_optional_dependencies.clone(),
             overrides: self.overrides.clone(),
             package_extensions_checksum: self.package_extensions_checksum.clone(),
             patched_dependencies: patches,
@@ -423,6 +537,60 @@ impl crate::Lockfile for PnpmLockfile {
         Ok(patches)
     }
 
+    fn patches_for(
+        &self,
+        packages: &HashSet<crate::Package>,
+    ) -> Result<Vec<RelativeUnixPathBuf>, crate::Error> {
+        let Some(patched_dependencies) = &self.patched_dependencies else {
+            return Ok(Vec::new());
+        };
+        let mut patches = self
+            .matching_patches(packages, patched_dependencies)
+            .into_iter()
+            .map(|patch| RelativeUnixPathBuf::new(&patch.path))
+            .collect::<Result<Vec<_>, turbopath::PathError>>()?;
+        patches.sort();
+        patches.dedup();
+        Ok(patches)
+    }
+
+    fn configured_patches_for(
+        &self,
+        packages: &HashSet<crate::Package>,
+        patched_dependencies: &BTreeMap<String, RelativeUnixPathBuf>,
+    ) -> Vec<RelativeUnixPathBuf> {
+        let mut patches = self
+            .matching_patches(packages, patched_dependencies)
+            .into_iter()
+            .cloned()
+            .collect::<Vec<_>>();
+        patches.sort();
+        patches.dedup();
+        patches
+    }
+
+    fn overrides_for(&self, packages: &HashSet<crate::Package>) -> Vec<(String, String)> {
+        let Some(overrides) = &self.overrides else {
+            return Vec::new();
+        };
+        let names = packages
+            .iter()
+            .filter_map(|package| self.dep_path(&package.key).ok())
+            .map(|dp| dp.name)
+            .collect::<HashSet<_>>();
+        overrides
+            .iter()
+            .filter(|(selector, replacement)| {
+                names.contains(override_target(selector))
+                    // Aliased overrides resolve to a package with a different name
+                    || replacement
+                        .strip_prefix("npm:")
+                        .is_some_and(|alias| names.contains(override_target(alias)))
+            })
+            .map(|(selector, replacement)| (selector.clone(), replacement.clone()))
+            .collect()
+    }
+
     fn global_change(&self, other: &dyn crate::Lockfile) -> bool {
         let any_other = other as &dyn Any;
         if let Some(other) = any_other.downcast_ref::<Self>() {
@@ -431,6 +599,13 @@ impl crate::Lockfile for PnpmLockfile {
             true
         }
     }
+
+    fn strip_dev_dependencies(&mut self) -> Result<(), crate::Error> {
+        for importer in self.importers.values_mut() {
+            importer.dependencies.strip_dev_dependencies();
+        }
+        Ok(())
+    }
 }
 
 impl DependencyInfo {
@@ -461,6 +636,33 @@ impl DependencyInfo {
         }
     }
 
+    fn strip_dev_dependencies(&mut self) {
+        match self {
+            DependencyInfo::PreV6 {
+                specifiers,
+                dependencies,
+                optional_dependencies,
+                dev_dependencies,
+            } => {
+                if let (Some(specifiers), Some(dev_dependencies)) =
+                    (specifiers.as_mut(), dev_dependencies.take())
+                {
+                    // Only remove specifiers that aren't also used by a prod dependency
+                    specifiers.retain(|name, _| {
+                        !dev_dependencies.contains_key(name)
+                            || Self::get_resolution(dependencies, name).is_some()
+                            || Self::get_resolution(optional_dependencies, name).is_some()
+                    });
+                }
+            }
+            DependencyInfo::V6 {
+                dev_dependencies, ..
+            } => {
+                *dev_dependencies = None;
+            }
+        }
+    }
+
     fn get_resolution<'a, V>(maybe_map: &'a Option<Map<String, V>>, key: &str) -> Option<&'a V> {
         maybe_map.as_ref().and_then(|maybe_map| maybe_map.get(key))
     }
@@ -477,13 +679,22 @@ pub fn pnpm_global_change(
     prev_contents: &[u8],
     curr_contents: &[u8],
 ) -> Result<bool, crate::Error> {
-    let prev_data = PnpmLockfile::from_bytes(prev_contents)?;
-    let curr_data = PnpmLockfile::from_bytes(curr_contents)?;
+    let (prev_data, curr_data) = match (
+        PnpmLockfile::from_bytes(prev_contents),
+        PnpmLockfile::from_bytes(curr_contents),
+    ) {
+        (Ok(prev_data), Ok(curr_data)) => (prev_data, curr_data),
+        // Any change to a lockfile we can't read could affect every package
+        (Err(crate::Error::Pnpm(Error::UnsupportedVersion(_))), _)
+        | (_, Err(crate::Error::Pnpm(Error::UnsupportedVersion(_)))) => return Ok(true),
+        (Err(e), _) | (_, Err(e)) => return Err(e),
+    };
     Ok(prev_data.lockfile_version != curr_data.lockfile_version
         || prev_data.package_extensions_checksum != curr_data.package_extensions_checksum
         || prev_data.overrides != curr_data.overrides
         || prev_data.patched_dependencies != curr_data.patched_dependencies
-        || prev_data.settings != curr_data.settings)
+        || prev_data.settings != curr_data.settings
+        || prev_data.ignored_optional_dependencies != curr_data.ignored_optional_dependencies)
 }
 
 #[cfg(test)]
@@ -504,13 +715,14 @@ mod tests {
     const PNPM_OVERRIDE: &[u8] = include_bytes!("../../fixtures/pnpm-override.yaml").as_slice();
     const PNPM_PATCH: &[u8] = include_bytes!("../../fixtures/pnpm-patch.yaml").as_slice();
     const PNPM_PATCH_V6: &[u8] = include_bytes!("../../fixtures/pnpm-patch-v6.yaml").as_slice();
+    const PNPM9: &[u8] = include_bytes!("../../fixtures/pnpm-v9.yaml").as_slice();
 
     use super::*;
     use crate::{Lockfile, Package};
 
     #[test]
     fn test_roundtrip() {
-        for fixture in &[PNPM6, PNPM7, PNPM8, PNPM8_6] {
+        for fixture in &[PNPM6, PNPM7, PNPM8, PNPM8_6, PNPM9] {
             let lockfile = PnpmLockfile::from_bytes(fixture).unwrap();
             let serialized_lockfile = serde_yaml::to_string(&lockfile).unwrap();
             let lockfile_from_serialized =
@@ -629,6 +841,22 @@ mod tests {
         Err("Workspace 'apps/bad_workspace' not found in lockfile")
         ; "v6 missing workspace"
     )]
+    #[test_case(
+        PNPM9,
+        "packages/a",
+        "is-odd",
+        "^3.0.1",
+        Ok(Some("3.0.1"))
+        ; "v9 external package"
+    )]
+    #[test_case(
+        PNPM9,
+        "packages/b",
+        "is-even",
+        "^1.0.0",
+        Ok(Some("1.0.0(patch_hash=trwuddosrpxsvtoqztvint6pca)"))
+        ; "v9 patched package"
+    )]
     fn test_specifier_resolution(
         lockfile: &[u8],
         workspace_path: &str,
@@ -719,6 +947,28 @@ mod tests {
         }))
         ; "pnpm override"
     )]
+    #[test_case(
+        PNPM9,
+        "packages/b",
+        "is-even",
+        "^1.0.0",
+        Ok(Some(crate::Package {
+            key: "is-even@1.0.0(patch_hash=trwuddosrpxsvtoqztvint6pca)".into(),
+            version: "1.0.0(patch_hash=trwuddosrpxsvtoqztvint6pca)".into(),
+        }))
+        ; "v9 package with suffix"
+    )]
+    #[test_case(
+        PNPM9,
+        "packages/a",
+        "is-number",
+        "is-number@6.0.0",
+        Ok(Some(crate::Package {
+            key: "is-number@6.0.0".into(),
+            version: "6.0.0".into(),
+        }))
+        ; "v9 key as version"
+    )]
     fn test_resolve_package(
         lockfile: &[u8],
         workspace_path: &str,
@@ -742,6 +992,79 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_patches_for() {
+        let lockfile = PnpmLockfile::from_bytes(PNPM_PATCH).unwrap();
+        let packages = [
+            Package::new("/is-odd/3.0.1_nrrwwz7lemethtlvvm75r5bmhq", "3.0.1"),
+            Package::new("/is-number/6.0.0", "6.0.0"),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            lockfile.patches_for(&packages).unwrap(),
+            vec![RelativeUnixPathBuf::new("patches/is-odd@3.0.1.patch").unwrap()]
+        );
+        let unpatched = [Package::new("/is-number/6.0.0", "6.0.0")]
+            .into_iter()
+            .collect();
+        assert!(lockfile.patches_for(&unpatched).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_configured_patches_for() {
+        let lockfile = PnpmLockfile::from_bytes(PNPM_PATCH).unwrap();
+        let packages = [
+            Package::new("/is-odd/3.0.1_nrrwwz7lemethtlvvm75r5bmhq", "3.0.1"),
+            Package::new("/is-number/6.0.0", "6.0.0"),
+        ]
+        .into_iter()
+        .collect();
+        let patched_dependencies = [
+            ("is-odd", "patches/is-odd.patch"),
+            ("is-number@6.0.0", "patches/is-number@6.0.0.patch"),
+            ("is-number@7.0.0", "patches/is-number@7.0.0.patch"),
+            ("left-pad", "patches/left-pad.patch"),
+        ]
+        .into_iter()
+        .map(|(name, path)| (name.to_string(), RelativeUnixPathBuf::new(path).unwrap()))
+        .collect();
+        assert_eq!(
+            lockfile.configured_patches_for(&packages, &patched_dependencies),
+            vec![
+                RelativeUnixPathBuf::new("patches/is-number@6.0.0.patch").unwrap(),
+                RelativeUnixPathBuf::new("patches/is-odd.patch").unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_overrides_for() {
+        let lockfile = PnpmLockfile::from_bytes(PNPM_OVERRIDE).unwrap();
+        let packages = [Package::new(
+            "/hardhat-deploy-ethers/0.3.0-beta.13_yab2ug5tvye2kp6e24l5x3z7uy",
+            "0.3.0-beta.13",
+        )]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            lockfile.overrides_for(&packages),
+            vec![(
+                "@nomiclabs/hardhat-ethers".to_string(),
+                "npm:hardhat-deploy-ethers@^0.3.0-beta.13".to_string()
+            )]
+        );
+        assert!(lockfile.overrides_for(&HashSet::new()).is_empty());
+    }
+
+    #[test_case("foo", "foo" ; "name")]
+    #[test_case("foo@<2", "foo" ; "version range")]
+    #[test_case("@scope/foo@1", "@scope/foo" ; "scoped")]
+    #[test_case("bar@1>@scope/foo", "@scope/foo" ; "parent")]
+    fn test_override_target(selector: &str, expected: &str) {
+        assert_eq!(override_target(selector), expected);
+    }
+
     #[test]
     fn test_prune_patches() {
         let lockfile = PnpmLockfile::from_bytes(PNPM_PATCH).unwrap();
@@ -799,6 +1122,90 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_v9_transitive_closure() {
+        let lockfile = PnpmLockfile::from_bytes(PNPM9).unwrap();
+        let closures = crate::all_transitive_closures(
+            &lockfile,
+            vec![(
+                "packages/b".to_string(),
+                vec![("is-even".to_string(), "^1.0.0".to_string())]
+                    .into_iter()
+                    .collect(),
+            )]
+            .into_iter()
+            .collect(),
+        )
+        .unwrap();
+
+        let mut closure = closures
+            .get("packages/b")
+            .unwrap()
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>();
+        closure.sort();
+        assert_eq!(
+            closure,
+            vec![
+                Package::new("is-buffer@1.1.6", "1.1.6"),
+                Package::new(
+                    "is-even@1.0.0(patch_hash=trwuddosrpxsvtoqztvint6pca)",
+                    "1.0.0(patch_hash=trwuddosrpxsvtoqztvint6pca)"
+                ),
+                Package::new("is-number@3.0.0", "3.0.0"),
+                Package::new("is-odd@0.1.2", "0.1.2"),
+                Package::new("kind-of@3.2.2", "3.2.2"),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_v9_subgraph() {
+        let lockfile = PnpmLockfile::from_bytes(PNPM9).unwrap();
+        let pruned = lockfile
+            .subgraph(
+                &["packages/b".into()],
+                &[
+                    "is-even@1.0.0(patch_hash=trwuddosrpxsvtoqztvint6pca)".into(),
+                    "is-odd@0.1.2".into(),
+                ],
+            )
+            .unwrap();
+        assert_eq!(
+            pruned.patches().unwrap(),
+            vec![RelativeUnixPathBuf::new("patches/is-even@1.0.0.patch").unwrap()]
+        );
+
+        let pruned = PnpmLockfile::from_bytes(&pruned.encode().unwrap()).unwrap();
+        assert_eq!(
+            pruned.packages.unwrap().keys().collect::<Vec<_>>(),
+            vec!["is-even@1.0.0", "is-odd@0.1.2"]
+        );
+        assert_eq!(
+            pruned.snapshots.unwrap().keys().collect::<Vec<_>>(),
+            vec![
+                "is-even@1.0.0(patch_hash=trwuddosrpxsvtoqztvint6pca)",
+                "is-odd@0.1.2"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unsupported_version() {
+        let contents = "lockfileVersion: '10.0'\nimporters:\n  .:\n    - future\n";
+        let err = PnpmLockfile::from_bytes(contents.as_bytes()).unwrap_err();
+        assert!(
+            matches!(err, crate::Error::Pnpm(Error::UnsupportedVersion(ref v)) if v == "10.0"),
+            "unexpected error: {err}"
+        );
+
+        let contents = String::from_utf8(PNPM9.to_vec())
+            .unwrap()
+            .replace("lockfileVersion: '9.0'", "lockfileVersion: '10.0'");
+        assert!(pnpm_global_change(PNPM9, contents.as_bytes()).unwrap());
+    }
+
     #[test]
     fn test_pnpm_alias_overlap() {
         let lockfile = PnpmLockfile::from_bytes(PNPM_ABSOLUTE).unwrap();
@@ -914,4 +1321,18 @@ c:
         assert_eq!(settings.auto_install_peers, Some(true));
         assert_eq!(settings.exclude_links_from_lockfile, Some(false));
     }
+
+    #[test]
+    fn test_strip_dev_dependencies() {
+        let mut lockfile = PnpmLockfile::from_bytes(PNPM7).unwrap();
+        lockfile.strip_dev_dependencies().unwrap();
+        let root = lockfile.importers.get(".").unwrap();
+        assert_eq!(root.dependencies.find_resolution("turbo"), None);
+        let docs = lockfile.importers.get("apps/docs").unwrap();
+        assert_eq!(docs.dependencies.find_resolution("@babel/core"), None);
+        assert_eq!(
+            docs.dependencies.find_resolution("next"),
+            Some(("12.2.5", "12.2.5_ir3quccc6i62x6qn6jjhyjjiey"))
+        );
+    }
 }
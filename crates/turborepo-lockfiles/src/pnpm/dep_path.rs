@@ -35,6 +35,15 @@ impl<'a> DepPath<'a> {
         self
     }
 
+    // v9 dependency paths drop the leading `/` and always use `@` as the
+    // separator, which makes them ambiguous with a host in the older format
+    pub fn parse_v9(value: &'a str) -> Result<Self, nom::error::Error<String>> {
+        let (_, dep_path) = parse_dep_path_v9(value)
+            .map_err(|e| e.to_owned())
+            .finish()?;
+        Ok(dep_path)
+    }
+
     pub fn patch_hash(&self) -> Option<&str> {
         self.peer_suffix.and_then(|s| {
             if s.starts_with('(') {
@@ -82,6 +91,20 @@ fn parse_dep_path(i: &str) -> IResult<&str, DepPath> {
     ))
 }
 
+// See https://github.com/pnpm/pnpm/blob/v9.0.0/packages/dependency-path/src/index.ts
+fn parse_dep_path_v9(i: &str) -> IResult<&str, DepPath> {
+    let (i, name) = parse_name(i)?;
+    let (i, _) = nom::character::complete::char('@')(i)?;
+    // Versions can contain `_` and `/`, e.g. `file:` and tarball URLs
+    let (i, version) = is_not("(")(i)?;
+    let (i, peer_suffix) = opt(parse_new_peer_suffix)(i)?;
+    let (_, _) = nom::combinator::eof(i)?;
+    Ok((
+        "",
+        DepPath::new(name, version).with_peer_suffix(peer_suffix),
+    ))
+}
+
 fn parse_host(i: &str) -> IResult<&str, Option<&str>> {
     let (i, host) = opt(is_not("/"))(i)?;
     Ok((i, host))
@@ -149,6 +172,15 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test_case("foo@1.0.0", DepPath::new("foo", "1.0.0"); "basic v9 dep path")]
+    #[test_case("@foo/bar@1.0.0_beta", DepPath::new("@foo/bar", "1.0.0_beta"); "v9 scoped dep path")]
+    #[test_case("foo@1.0.0(bar@1.0.0)(baz@1.0.0)", DepPath::new("foo", "1.0.0").with_peer_suffix(Some("(bar@1.0.0)(baz@1.0.0)")); "v9 with multiple peers")]
+    #[test_case("foo@file:packages/foo", DepPath::new("foo", "file:packages/foo"); "v9 local dependency")]
+    #[test_case("foo@https://example.org/foo/-/foo-1.0.0.tgz", DepPath::new("foo", "https://example.org/foo/-/foo-1.0.0.tgz"); "v9 tarball")]
+    fn dep_path_parse_v9_tests(s: &str, expected: DepPath) {
+        assert_eq!(DepPath::parse_v9(s).unwrap(), expected);
+    }
+
     #[test_case("/@babel/helper-string-parser/7.19.4(patch_hash=wjhgmpzh47qmycrzgpeyoyh3ce)(@babel/core@7.21.0)", Some("wjhgmpzh47qmycrzgpeyoyh3ce"); "v6 patch")]
     #[test_case("/foo/1.0.0_patchHash_peerHash", Some("patchHash"); "pre v6 patch")]
     #[test_case("/foo/1.0.0", None; "no suffix")]
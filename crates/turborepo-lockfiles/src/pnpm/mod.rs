@@ -13,14 +13,34 @@ pub enum Error {
     DependencyPath(#[from] nom::error::Error<String>),
     #[error("Unable to find '{0}' other than reference in dependenciesMeta")]
     MissingInjectedPackage(String),
+    #[error(
+        "pnpm lockfile version {0} isn't supported yet, changes to the lockfile will affect all \
+         packages"
+    )]
+    UnsupportedVersion(String),
 }
 
+// Lockfiles newer than this might change the meaning of fields we rely on, so
+// they're rejected rather than read incorrectly
+const MAX_SUPPORTED_MAJOR_VERSION: u32 = 9;
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 struct LockfileVersion {
     version: String,
     format: VersionFormat,
 }
 
+impl LockfileVersion {
+    fn major(&self) -> Option<u32> {
+        self.version.split('.').next()?.parse().ok()
+    }
+
+    fn is_supported(&self) -> bool {
+        self.major()
+            .is_some_and(|major| major <= MAX_SUPPORTED_MAJOR_VERSION)
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 enum VersionFormat {
     String,
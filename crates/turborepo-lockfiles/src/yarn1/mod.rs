@@ -117,6 +117,12 @@ impl Lockfile for Yarn1Lockfile {
         // if the types don't match then we changed package managers
         any_other.downcast_ref::<Self>().is_none()
     }
+
+    fn strip_dev_dependencies(&mut self) -> Result<(), crate::Error> {
+        // Workspaces don't have entries in the lockfile, so the subgraph of the
+        // production dependencies doesn't contain any devDependencies
+        Ok(())
+    }
 }
 
 pub fn yarn_subgraph(contents: &[u8], packages: &[String]) -> Result<Vec<u8>, crate::Error> {
@@ -161,6 +167,13 @@ mod test {
         assert_eq!(input, lockfile.to_string());
     }
 
+    #[test]
+    fn test_strip_dev_dependencies() {
+        let mut lockfile = Yarn1Lockfile::from_str(FULL).unwrap();
+        lockfile.strip_dev_dependencies().unwrap();
+        assert_eq!(FULL, lockfile.to_string());
+    }
+
     #[test]
     fn test_key_splitting() {
         let lockfile = Yarn1Lockfile::from_str(FULL).unwrap();
@@ -19,7 +19,12 @@ const DEFAULT_GLOBAL_DEPS: [&str; 2] = ["package.json", "turbo.json"];
 // still want to be able to express a generic change.
 pub enum LockfileChange {
     Empty,
-    WithContent(Vec<u8>),
+    WithContent {
+        // The lockfile's path relative to the repository root
+        path: AnchoredSystemPathBuf,
+        // The contents of the lockfile before the change
+        content: Vec<u8>,
+    },
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -64,7 +69,19 @@ impl<'a, PD: PackageChangeMapper> ChangeMapper<'a, PD> {
         }
 
         // get filtered files and add the packages that contain them
-        let filtered_changed_files = self.filter_ignored_files(changed_files.iter())?;
+        let mut filtered_changed_files = self.filter_ignored_files(changed_files.iter())?;
+        // If we can compare the lockfile contents, the packages it affects are found by
+        // diffing their dependency closures below. We only treat the lockfile as a
+        // regular changed file if it invalidates everything e.g. it's a global
+        // dependency.
+        if let Some(LockfileChange::WithContent { path, .. }) = &lockfile_change {
+            if !matches!(
+                self.package_detector.detect_package(path),
+                PackageMapping::All
+            ) {
+                filtered_changed_files.remove(path);
+            }
+        }
         let PackageChanges::Some(mut changed_pkgs) =
             self.get_changed_packages(filtered_changed_files.into_iter())?
         else {
@@ -72,7 +89,7 @@ impl<'a, PD: PackageChangeMapper> ChangeMapper<'a, PD> {
         };
 
         match lockfile_change {
-            Some(LockfileChange::WithContent(content)) => {
+            Some(LockfileChange::WithContent { content, .. }) => {
                 // if we run into issues, don't error, just assume all packages have changed
                 let Ok(lockfile_changes) = self.get_changed_packages_from_lockfile(content) else {
                     return Ok(PackageChanges::All);
@@ -173,10 +190,168 @@ impl From<ChangedPackagesError> for ChangeMapError {
 
 #[cfg(test)]
 mod test {
+    use std::collections::HashMap;
+
+    use serde_json::json;
     use test_case::test_case;
+    use turbopath::{AbsoluteSystemPathBuf, AnchoredSystemPathBuf};
+
+    use super::{ChangeMapper, LockfileChange, PackageChanges};
+    use crate::{
+        change_mapper::package::{DefaultPackageChangeMapper, GlobalDepsPackageChangeMapper},
+        discovery::{self, PackageDiscovery},
+        package_graph::{PackageGraph, PackageName, WorkspacePackage},
+        package_json::PackageJson,
+    };
+
+    struct MockDiscovery;
+
+    impl PackageDiscovery for MockDiscovery {
+        async fn discover_packages(
+            &self,
+        ) -> Result<discovery::DiscoveryResponse, discovery::Error> {
+            Ok(discovery::DiscoveryResponse {
+                package_manager: crate::package_manager::PackageManager::Npm,
+                workspaces: vec![],
+            })
+        }
+
+        async fn discover_packages_blocking(
+            &self,
+        ) -> Result<discovery::DiscoveryResponse, discovery::Error> {
+            self.discover_packages().await
+        }
+    }
+
+    fn npm_lockfile(loose_envify_version: &str) -> Vec<u8> {
+        serde_json::to_vec(&json!({
+            "name": "root",
+            "lockfileVersion": 3,
+            "requires": true,
+            "packages": {
+                "": {
+                    "name": "root",
+                    "workspaces": ["packages/*"]
+                },
+                "packages/a": {
+                    "name": "a",
+                    "dependencies": { "lodash": "^4.17.0" }
+                },
+                "packages/b": {
+                    "name": "b",
+                    "dependencies": { "react": "^18.0.0" }
+                },
+                "node_modules/a": { "resolved": "packages/a", "link": true },
+                "node_modules/b": { "resolved": "packages/b", "link": true },
+                "node_modules/lodash": { "version": "4.17.21" },
+                "node_modules/react": {
+                    "version": "18.2.0",
+                    "dependencies": { "loose-envify": "^1.1.0" }
+                },
+                "node_modules/loose-envify": { "version": loose_envify_version }
+            }
+        }))
+        .unwrap()
+    }
+
+    async fn npm_package_graph(root: &AbsoluteSystemPathBuf) -> PackageGraph {
+        let lockfile = turborepo_lockfiles::NpmLockfile::load(&npm_lockfile("1.4.0")).unwrap();
+        PackageGraph::builder(
+            root,
+            PackageJson::from_value(json!({ "name": "root" })).unwrap(),
+        )
+        .with_package_discovery(MockDiscovery)
+        .with_package_jsons(Some({
+            let mut map = HashMap::new();
+            map.insert(
+                root.join_components(&["packages", "a", "package.json"]),
+                PackageJson::from_value(json!({
+                    "name": "a",
+                    "dependencies": { "lodash": "^4.17.0" }
+                }))
+                .unwrap(),
+            );
+            map.insert(
+                root.join_components(&["packages", "b", "package.json"]),
+                PackageJson::from_value(json!({
+                    "name": "b",
+                    "dependencies": { "react": "^18.0.0" }
+                }))
+                .unwrap(),
+            );
+            map
+        }))
+        .with_lockfile(Some(Box::new(lockfile)))
+        .build()
+        .await
+        .unwrap()
+    }
 
-    use super::ChangeMapper;
-    use crate::change_mapper::package::DefaultPackageChangeMapper;
+    #[tokio::test]
+    async fn test_lockfile_change_only_affects_dependents() {
+        let root =
+            AbsoluteSystemPathBuf::new(if cfg!(windows) { r"C:\repo" } else { "/repo" }).unwrap();
+        let pkg_graph = npm_package_graph(&root).await;
+        let lockfile_path = AnchoredSystemPathBuf::from_raw("package-lock.json").unwrap();
+
+        let change_mapper = ChangeMapper::new(
+            &pkg_graph,
+            vec![],
+            GlobalDepsPackageChangeMapper::new(&pkg_graph, std::iter::empty::<&str>()).unwrap(),
+        );
+        let changes = change_mapper
+            .changed_packages(
+                [lockfile_path.clone()].into_iter().collect(),
+                Some(LockfileChange::WithContent {
+                    path: lockfile_path.clone(),
+                    content: npm_lockfile("1.3.0"),
+                }),
+            )
+            .unwrap();
+
+        // Only `b` depends on `loose-envify` through `react`, and the lockfile
+        // itself shouldn't mark the root package as changed
+        assert_eq!(
+            changes,
+            PackageChanges::Some(
+                [WorkspacePackage {
+                    name: PackageName::from("b"),
+                    path: AnchoredSystemPathBuf::from_raw(
+                        ["packages", "b"].join(std::path::MAIN_SEPARATOR_STR)
+                    )
+                    .unwrap(),
+                }]
+                .into_iter()
+                .collect()
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_lockfile_change_as_global_dependency() {
+        let root =
+            AbsoluteSystemPathBuf::new(if cfg!(windows) { r"C:\repo" } else { "/repo" }).unwrap();
+        let pkg_graph = npm_package_graph(&root).await;
+        let lockfile_path = AnchoredSystemPathBuf::from_raw("package-lock.json").unwrap();
+
+        let change_mapper = ChangeMapper::new(
+            &pkg_graph,
+            vec![],
+            GlobalDepsPackageChangeMapper::new(&pkg_graph, ["package-lock.json"].into_iter())
+                .unwrap(),
+        );
+        let changes = change_mapper
+            .changed_packages(
+                [lockfile_path.clone()].into_iter().collect(),
+                Some(LockfileChange::WithContent {
+                    path: lockfile_path,
+                    content: npm_lockfile("1.3.0"),
+                }),
+            )
+            .unwrap();
+
+        assert_eq!(changes, PackageChanges::All);
+    }
 
     #[cfg(unix)]
     #[test_case("/a/b/c", &["package.lock"], "/a/b/c/package.lock", true ; "simple")]
@@ -1,10 +1,8 @@
-use std::{
-    backtrace::Backtrace,
-    collections::{BTreeMap, HashMap, HashSet},
-};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
+use miette::Diagnostic;
 use petgraph::graph::{Graph, NodeIndex};
-use tracing::{warn, Instrument};
+use tracing::{debug, warn, Instrument};
 use turbopath::{
     AbsoluteSystemPath, AbsoluteSystemPathBuf, AnchoredSystemPath, AnchoredSystemPathBuf,
 };
@@ -12,7 +10,8 @@ use turborepo_graph_utils as graph;
 use turborepo_lockfiles::Lockfile;
 
 use super::{
-    dep_splitter::DependencySplitter, PackageGraph, PackageInfo, PackageName, PackageNode,
+    dep_splitter::DependencySplitter, node_modules, PackageGraph, PackageInfo, PackageName,
+    PackageNode,
 };
 use crate::{
     discovery::{
@@ -20,6 +19,7 @@ use crate::{
         PackageDiscoveryBuilder,
     },
     package_json::PackageJson,
+    package_manager::PackageManager,
 };
 
 pub struct PackageGraphBuilder<'a, T> {
@@ -28,36 +28,42 @@ pub struct PackageGraphBuilder<'a, T> {
     is_single_package: bool,
     package_jsons: Option<HashMap<AbsoluteSystemPathBuf, PackageJson>>,
     lockfile: Option<Box<dyn Lockfile>>,
+    ignore_lockfile: bool,
     package_discovery: T,
 }
 
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, thiserror::Error, Diagnostic)]
 pub enum Error {
     #[error("could not resolve workspaces: {0}")]
-    PackageManager(
-        #[from] crate::package_manager::Error,
-        #[backtrace] Backtrace,
-    ),
+    #[diagnostic(transparent)]
+    PackageManager(#[from] crate::package_manager::Error),
     #[error(
         "Failed to add workspace \"{name}\" from \"{path}\", it already exists at \
          \"{existing_path}\""
     )]
+    #[diagnostic(code(TURBO_REPO_005))]
     DuplicateWorkspace {
         name: String,
         path: String,
         existing_path: String,
     },
     #[error("path error: {0}")]
+    #[diagnostic(code(TURBO_REPO_006))]
     Path(#[from] turbopath::PathError),
     #[error("unable to parse workspace package.json: {0}")]
+    #[diagnostic(transparent)]
     PackageJson(#[from] crate::package_json::Error),
     #[error("package.json must have a name field:\n{0}")]
+    #[diagnostic(code(TURBO_REPO_007))]
     PackageJsonMissingName(AbsoluteSystemPathBuf),
     #[error("Invalid package dependency graph: {0}")]
+    #[diagnostic(code(TURBO_REPO_008))]
     InvalidPackageGraph(#[source] graph::Error),
     #[error(transparent)]
+    #[diagnostic(code(TURBO_REPO_009))]
     Lockfile(#[from] turborepo_lockfiles::Error),
     #[error(transparent)]
+    #[diagnostic(code(TURBO_REPO_010))]
     Discovery(#[from] crate::discovery::Error),
 }
 
@@ -74,6 +80,7 @@ impl<'a> PackageGraphBuilder<'a, LocalPackageDiscoveryBuilder> {
             is_single_package: false,
             package_jsons: None,
             lockfile: None,
+            ignore_lockfile: false,
         }
     }
 }
@@ -97,6 +104,13 @@ impl<'a, P> PackageGraphBuilder<'a, P> {
         self
     }
 
+    /// Don't read the package manager's lockfile. External dependencies will
+    /// instead be resolved from the packages installed in `node_modules`.
+    pub fn with_ignore_lockfile(mut self, ignore_lockfile: bool) -> Self {
+        self.ignore_lockfile = ignore_lockfile;
+        self
+    }
+
     /// Set the package discovery strategy to use. Note that whatever strategy
     /// selected here will be wrapped in a `CachingPackageDiscovery` to
     /// prevent unnecessary work during building.
@@ -110,6 +124,7 @@ impl<'a, P> PackageGraphBuilder<'a, P> {
             is_single_package: self.is_single_package,
             package_jsons: self.package_jsons,
             lockfile: self.lockfile,
+            ignore_lockfile: self.ignore_lockfile,
             package_discovery: discovery,
         }
     }
@@ -145,6 +160,7 @@ struct BuildState<'a, S, T> {
     workspace_graph: Graph<PackageNode, ()>,
     node_lookup: HashMap<PackageNode, NodeIndex>,
     lockfile: Option<Box<dyn Lockfile>>,
+    ignore_lockfile: bool,
     package_jsons: Option<HashMap<AbsoluteSystemPathBuf, PackageJson>>,
     state: std::marker::PhantomData<S>,
     package_discovery: T,
@@ -193,6 +209,7 @@ where
 
             package_jsons,
             lockfile,
+            ignore_lockfile,
             package_discovery,
         } = builder;
         let mut workspaces = HashMap::new();
@@ -211,6 +228,7 @@ where
 
             workspaces,
             lockfile,
+            ignore_lockfile,
             package_jsons,
             workspace_graph: Graph::new(),
             node_lookup: HashMap::new(),
@@ -298,6 +316,7 @@ impl<'a, T: PackageDiscovery> BuildState<'a, ResolvedPackageManager, T> {
             workspace_graph,
             node_lookup,
             lockfile,
+            ignore_lockfile,
             package_discovery,
             ..
         } = self;
@@ -308,6 +327,7 @@ impl<'a, T: PackageDiscovery> BuildState<'a, ResolvedPackageManager, T> {
             workspace_graph,
             node_lookup,
             lockfile,
+            ignore_lockfile,
             package_discovery,
             package_jsons: None,
             state: std::marker::PhantomData,
@@ -341,7 +361,12 @@ impl<'a, T: PackageDiscovery> BuildState<'a, ResolvedPackageManager, T> {
 
 impl<'a, T: PackageDiscovery> BuildState<'a, ResolvedWorkspaces, T> {
     #[tracing::instrument(skip(self))]
-    fn connect_internal_dependencies(&mut self) -> Result<(), Error> {
+    async fn connect_internal_dependencies(&mut self) -> Result<(), Error> {
+        let package_manager = self
+            .package_discovery
+            .discover_packages()
+            .await?
+            .package_manager;
         let split_deps = self
             .workspaces
             .iter()
@@ -353,6 +378,7 @@ impl<'a, T: PackageDiscovery> BuildState<'a, ResolvedWorkspaces, T> {
                         self.repo_root,
                         &entry.package_json_path,
                         &self.workspaces,
+                        &package_manager,
                         entry.package_json.all_dependencies(),
                     ),
                 )
@@ -415,18 +441,24 @@ impl<'a, T: PackageDiscovery> BuildState<'a, ResolvedWorkspaces, T> {
 
     #[tracing::instrument(skip(self))]
     async fn resolve_lockfile(mut self) -> Result<BuildState<'a, ResolvedLockfile, T>, Error> {
-        self.connect_internal_dependencies()?;
-
-        let lockfile = match self.populate_lockfile().await {
-            Ok(lockfile) => Some(lockfile),
-            Err(e) => {
-                warn!(
-                    "Issues occurred when constructing package graph. Turbo will function, but \
-                     some features may not be available: {}",
-                    e
-                );
+        self.connect_internal_dependencies().await?;
+
+        let lockfile = match self.ignore_lockfile {
+            true => {
+                debug!("ignoring lockfile, resolving external dependencies from node_modules");
                 None
             }
+            false => match self.populate_lockfile().await {
+                Ok(lockfile) => Some(lockfile),
+                Err(e) => {
+                    warn!(
+                        "Issues occurred when constructing package graph. Turbo will function, \
+                         but some features may not be available: {}",
+                        e
+                    );
+                    None
+                }
+            },
         };
 
         let Self {
@@ -435,6 +467,7 @@ impl<'a, T: PackageDiscovery> BuildState<'a, ResolvedWorkspaces, T> {
             workspaces,
             workspace_graph,
             node_lookup,
+            ignore_lockfile,
             package_discovery,
             ..
         } = self;
@@ -445,6 +478,7 @@ impl<'a, T: PackageDiscovery> BuildState<'a, ResolvedWorkspaces, T> {
             workspace_graph,
             node_lookup,
             lockfile,
+            ignore_lockfile,
             package_jsons: None,
             state: std::marker::PhantomData,
             package_discovery,
@@ -479,8 +513,29 @@ impl<'a, T: PackageDiscovery> BuildState<'a, ResolvedLockfile, T> {
 
     #[tracing::instrument(skip_all)]
     fn populate_transitive_dependencies(&mut self) -> Result<(), Error> {
-        let Some(lockfile) = self.lockfile.as_deref() else {
-            return Ok(());
+        let installed;
+        let lockfile: &dyn Lockfile = match self.lockfile.as_deref() {
+            Some(lockfile) => lockfile,
+            // When the lockfile is ignored we fall back to what is installed in
+            // node_modules so changes to external dependencies still get picked up.
+            None if self.ignore_lockfile => {
+                let workspace_paths = self
+                    .workspaces
+                    .values()
+                    .map(|entry| Ok((entry.unix_dir_str()?, &entry.package_json)))
+                    .collect::<Result<Vec<_>, Error>>()?;
+                let Some(lockfile) = node_modules::installed_packages(
+                    self.repo_root,
+                    workspace_paths
+                        .iter()
+                        .map(|(path, package_json)| (path.as_str(), *package_json)),
+                ) else {
+                    return Ok(());
+                };
+                installed = lockfile;
+                &installed
+            }
+            None => return Ok(()),
         };
 
         let mut closures = turborepo_lockfiles::all_transitive_closures(
@@ -531,6 +586,7 @@ impl Dependencies {
         repo_root: &AbsoluteSystemPath,
         workspace_json_path: &AnchoredSystemPathBuf,
         workspaces: &HashMap<PackageName, PackageInfo>,
+        package_manager: &PackageManager,
         dependencies: I,
     ) -> Self {
         let resolved_workspace_json_path = repo_root.resolve(workspace_json_path);
@@ -539,7 +595,8 @@ impl Dependencies {
             .expect("package.json path should have parent");
         let mut internal = HashSet::new();
         let mut external = BTreeMap::new();
-        let splitter = DependencySplitter::new(repo_root, workspace_dir, workspaces);
+        let splitter =
+            DependencySplitter::new(repo_root, workspace_dir, workspaces, package_manager);
         for (name, version) in dependencies.into_iter() {
             if let Some(workspace) = splitter.is_internal(name, version) {
                 internal.insert(workspace);
@@ -3,11 +3,13 @@ use std::{collections::HashMap, fmt};
 use turbopath::{AbsoluteSystemPath, AnchoredSystemPathBuf, RelativeUnixPath, RelativeUnixPathBuf};
 
 use super::{PackageInfo, PackageName};
+use crate::package_manager::PackageManager;
 
 pub struct DependencySplitter<'a> {
     repo_root: &'a AbsoluteSystemPath,
     workspace_dir: &'a AbsoluteSystemPath,
     workspaces: &'a HashMap<PackageName, PackageInfo>,
+    package_manager: &'a PackageManager,
 }
 
 impl<'a> DependencySplitter<'a> {
@@ -15,15 +17,28 @@ impl<'a> DependencySplitter<'a> {
         repo_root: &'a AbsoluteSystemPath,
         workspace_dir: &'a AbsoluteSystemPath,
         workspaces: &'a HashMap<PackageName, PackageInfo>,
+        package_manager: &'a PackageManager,
     ) -> Self {
         Self {
             repo_root,
             workspace_dir,
             workspaces,
+            package_manager,
         }
     }
 
     pub fn is_internal(&self, name: &str, version: &str) -> Option<PackageName> {
+        // An npm alias refers to the package it aliases and not the name it is
+        // installed under e.g. `"foo": "npm:bar@^1.0.0"` should only match a `bar`
+        // workspace.
+        let alias_version;
+        let (name, version) = match npm_alias(version) {
+            Some((alias, range)) => {
+                alias_version = format!("npm:{range}");
+                (alias, alias_version.as_str())
+            }
+            None => (name, version),
+        };
         let workspace_specifier = WorkspacePackageSpecifier::new(version)
             .unwrap_or(WorkspacePackageSpecifier::Alias(name));
         let (workspace_name, info) = self.find_package(workspace_specifier)?;
@@ -54,7 +69,7 @@ impl<'a> DependencySplitter<'a> {
                 Some((package_name, info))
             }
             WorkspacePackageSpecifier::Path(path) => {
-                let path = self.workspace_dir.join_unix_path(path);
+                let path = self.workspace_path_base().join_unix_path(path);
                 // There's a chance that the user provided path could escape the root, in which
                 // case we don't support packages outside of the workspace.
                 // Pnpm also doesn't support this so we defer to them to provide the error
@@ -68,6 +83,22 @@ impl<'a> DependencySplitter<'a> {
             }
         }
     }
+
+    // The directory a `workspace:` path is relative to. Yarn resolves these from
+    // the project root, regardless of how `nmHoistingLimits` lays out
+    // `node_modules`, while pnpm resolves them from the depending package.
+    fn workspace_path_base(&self) -> &AbsoluteSystemPath {
+        match self.package_manager {
+            PackageManager::Berry | PackageManager::Yarn => self.repo_root,
+            _ => self.workspace_dir,
+        }
+    }
+}
+
+// Splits an npm alias e.g. `npm:foo@^1.0.0` into the aliased package and range
+fn npm_alias(version: &str) -> Option<(&str, &str)> {
+    let (name, range) = version.strip_prefix("npm:")?.rsplit_once('@')?;
+    (!name.is_empty()).then_some((name, range))
 }
 
 // A parsed variant of a package dependency that uses the workspace protocol
@@ -187,6 +218,66 @@ mod test {
     use super::*;
     use crate::package_json::PackageJson;
 
+    fn repo_root() -> AbsoluteSystemPathBuf {
+        AbsoluteSystemPathBuf::new(if cfg!(windows) {
+            "C:\\some\\repo"
+        } else {
+            "/some/repo"
+        })
+        .unwrap()
+    }
+
+    fn workspaces(package_version: &str) -> HashMap<PackageName, PackageInfo> {
+        let mut map = HashMap::new();
+        map.insert(
+            PackageName::Other("@scope/foo".to_string()),
+            PackageInfo {
+                package_json: PackageJson {
+                    version: Some(package_version.to_string()),
+                    ..Default::default()
+                },
+                package_json_path: AnchoredSystemPathBuf::from_raw(
+                    ["packages", "@scope", "foo", "package.json"]
+                        .join(std::path::MAIN_SEPARATOR_STR),
+                )
+                .unwrap(),
+                unresolved_external_dependencies: None,
+                transitive_dependencies: None,
+            },
+        );
+        map.insert(
+            PackageName::Other("bar".to_string()),
+            PackageInfo {
+                package_json: PackageJson {
+                    version: Some("1.0.0".to_string()),
+                    ..Default::default()
+                },
+                package_json_path: AnchoredSystemPathBuf::from_raw(
+                    ["packages", "bar", "package.json"].join(std::path::MAIN_SEPARATOR_STR),
+                )
+                .unwrap(),
+                unresolved_external_dependencies: None,
+                transitive_dependencies: None,
+            },
+        );
+        map.insert(
+            PackageName::Other("baz".to_string()),
+            PackageInfo {
+                package_json: PackageJson {
+                    version: Some("1.0.0".to_string()),
+                    ..Default::default()
+                },
+                package_json_path: AnchoredSystemPathBuf::from_raw(
+                    ["packages", "baz", "package.json"].join(std::path::MAIN_SEPARATOR_STR),
+                )
+                .unwrap(),
+                unresolved_external_dependencies: None,
+                transitive_dependencies: None,
+            },
+        );
+        map
+    }
+
     #[test_case("1.2.3", None, "1.2.3", Some("@scope/foo") ; "handles exact match")]
     #[test_case("1.2.3", None, "^1.0.0", Some("@scope/foo") ; "handles semver range satisfied")]
     #[test_case("2.3.4", None, "^1.0.0", None ; "handles semver range not satisfied")]
@@ -209,74 +300,26 @@ mod test {
     #[test_case("1.2.3", Some("foo"), "workspace:@scope/foo@*", Some("@scope/foo") ; "handles pnpm alias star")]
     #[test_case("1.2.3", Some("foo"), "workspace:@scope/foo@~", Some("@scope/foo") ; "handles pnpm alias tilda")]
     #[test_case("1.2.3", Some("foo"), "workspace:@scope/foo@^", Some("@scope/foo") ; "handles pnpm alias caret")]
+    #[test_case("1.2.3", None, "workspace:^", Some("@scope/foo") ; "handles workspace protocol caret")]
+    #[test_case("1.2.3", None, "workspace:~", Some("@scope/foo") ; "handles workspace protocol tilde")]
+    #[test_case("1.2.3", Some("foo"), "npm:@scope/foo@^1.0.0", Some("@scope/foo") ; "handles npm alias to workspace")]
+    #[test_case("1.2.3", Some("bar"), "npm:lodash@^4.17.21", None ; "handles npm alias to external package")]
+    #[test_case("1.2.3", None, "npm:@scope/foo@^2.0.0", None ; "handles npm alias with not satisfied semver range")]
     fn test_matches_workspace_package(
         package_version: &str,
         dependency_name: Option<&str>,
         range: &str,
         expected: Option<&str>,
     ) {
-        let root = AbsoluteSystemPathBuf::new(if cfg!(windows) {
-            "C:\\some\\repo"
-        } else {
-            "/some/repo"
-        })
-        .unwrap();
+        let root = repo_root();
         let pkg_dir = root.join_components(&["packages", "libA"]);
-        let workspaces = {
-            let mut map = HashMap::new();
-            map.insert(
-                PackageName::Other("@scope/foo".to_string()),
-                PackageInfo {
-                    package_json: PackageJson {
-                        version: Some(package_version.to_string()),
-                        ..Default::default()
-                    },
-                    package_json_path: AnchoredSystemPathBuf::from_raw(
-                        ["packages", "@scope", "foo", "package.json"]
-                            .join(std::path::MAIN_SEPARATOR_STR),
-                    )
-                    .unwrap(),
-                    unresolved_external_dependencies: None,
-                    transitive_dependencies: None,
-                },
-            );
-            map.insert(
-                PackageName::Other("bar".to_string()),
-                PackageInfo {
-                    package_json: PackageJson {
-                        version: Some("1.0.0".to_string()),
-                        ..Default::default()
-                    },
-                    package_json_path: AnchoredSystemPathBuf::from_raw(
-                        ["packages", "bar", "package.json"].join(std::path::MAIN_SEPARATOR_STR),
-                    )
-                    .unwrap(),
-                    unresolved_external_dependencies: None,
-                    transitive_dependencies: None,
-                },
-            );
-            map.insert(
-                PackageName::Other("baz".to_string()),
-                PackageInfo {
-                    package_json: PackageJson {
-                        version: Some("1.0.0".to_string()),
-                        ..Default::default()
-                    },
-                    package_json_path: AnchoredSystemPathBuf::from_raw(
-                        ["packages", "baz", "package.json"].join(std::path::MAIN_SEPARATOR_STR),
-                    )
-                    .unwrap(),
-                    unresolved_external_dependencies: None,
-                    transitive_dependencies: None,
-                },
-            );
-            map
-        };
+        let workspaces = workspaces(package_version);
 
         let splitter = DependencySplitter {
             repo_root: &root,
             workspace_dir: &pkg_dir,
             workspaces: &workspaces,
+            package_manager: &PackageManager::Pnpm,
         };
 
         assert_eq!(
@@ -285,6 +328,33 @@ mod test {
         );
     }
 
+    #[test_case(PackageManager::Pnpm, "workspace:../bar", Some("bar") ; "pnpm path relative to package")]
+    #[test_case(PackageManager::Pnpm, "workspace:packages/bar", None ; "pnpm path relative to root")]
+    #[test_case(PackageManager::Berry, "workspace:packages/bar", Some("bar") ; "berry path relative to root")]
+    #[test_case(PackageManager::Berry, "workspace:../bar", None ; "berry path relative to package")]
+    #[test_case(PackageManager::Berry, "workspace:packages/qux", None ; "berry path to missing package")]
+    fn test_workspace_path_base(
+        package_manager: PackageManager,
+        range: &str,
+        expected: Option<&str>,
+    ) {
+        let root = repo_root();
+        let pkg_dir = root.join_components(&["packages", "libA"]);
+        let workspaces = workspaces("1.2.3");
+
+        let splitter = DependencySplitter {
+            repo_root: &root,
+            workspace_dir: &pkg_dir,
+            workspaces: &workspaces,
+            package_manager: &package_manager,
+        };
+
+        assert_eq!(
+            splitter.is_internal("bar", range),
+            expected.map(PackageName::from)
+        );
+    }
+
     #[test_case("1.2.3", None ; "non-workspace")]
     #[test_case("workspace:1.2.3", None ; "workspace version")]
     #[test_case("workspace:*", None ; "workspace any")]
@@ -16,6 +16,7 @@ use crate::{
 
 pub mod builder;
 mod dep_splitter;
+mod node_modules;
 
 pub use builder::{Error, PackageGraphBuilder};
 
@@ -605,6 +606,10 @@ mod test {
         fn global_change(&self, _other: &dyn Lockfile) -> bool {
             unreachable!("global change detection not necessary for package graph construction")
         }
+
+        fn strip_dev_dependencies(&mut self) -> Result<(), turborepo_lockfiles::Error> {
+            unreachable!("pruning not necessary for package graph construction")
+        }
     }
 
     #[tokio::test]
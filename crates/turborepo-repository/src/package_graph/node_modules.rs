@@ -0,0 +1,446 @@
+//! Reconstructs the external dependencies of a repository from the packages
+//! installed in its `node_modules` directories. This is used to hash
+//! external dependencies when the lockfile is ignored.
+
+use std::{
+    any::Any,
+    collections::{BTreeMap, HashMap},
+};
+
+use serde::Serialize;
+use tracing::debug;
+use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
+use turborepo_lockfiles::{Lockfile, Package};
+
+use crate::package_json::PackageJson;
+
+const NODE_MODULES: &str = "node_modules";
+
+/// The packages installed for a repository, resolved the way Node resolves
+/// them. Packages are keyed by their real path in the repository so that the
+/// symlinks of pnpm's `node_modules/.pnpm` layout resolve to the same package
+/// as the nested and hoisted directories of npm and yarn.
+#[derive(Debug, Default, Serialize)]
+pub struct InstalledPackages {
+    packages: BTreeMap<String, InstalledPackage>,
+    workspaces: BTreeMap<String, WorkspaceDependencies>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct InstalledPackage {
+    version: String,
+    // The key each dependency resolved to, by name
+    dependencies: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+struct WorkspaceDependencies {
+    dependencies: BTreeMap<String, String>,
+    dev_dependencies: BTreeMap<String, String>,
+}
+
+/// Resolves the dependencies of the given workspaces, and all of their
+/// dependencies in turn, from the packages installed in `node_modules`.
+///
+/// Returns `None` if no packages are installed.
+pub fn installed_packages<'a>(
+    repo_root: &AbsoluteSystemPath,
+    workspaces: impl Iterator<Item = (&'a str, &'a PackageJson)>,
+) -> Option<InstalledPackages> {
+    // Resolved packages are compared against the real path of the repository
+    let repo_root = repo_root.to_realpath().ok()?;
+    let mut installed = InstalledPackages::default();
+    let mut pending = Vec::new();
+
+    for (workspace_path, package_json) in workspaces {
+        let dir = resolve_key(&repo_root, workspace_path);
+        let dependencies = WorkspaceDependencies {
+            dependencies: resolve_all(&repo_root, &dir, production_dependencies(package_json)),
+            dev_dependencies: resolve_all(
+                &repo_root,
+                &dir,
+                package_json.dev_dependencies.iter().flatten(),
+            ),
+        };
+        pending.extend(
+            dependencies
+                .dependencies
+                .values()
+                .chain(dependencies.dev_dependencies.values())
+                .cloned(),
+        );
+        installed
+            .workspaces
+            .insert(workspace_path.to_string(), dependencies);
+    }
+
+    while let Some(key) = pending.pop() {
+        if installed.packages.contains_key(&key) {
+            continue;
+        }
+        let dir = resolve_key(&repo_root, &key);
+        let Ok(package_json) = PackageJson::load(&dir.join_component("package.json")) else {
+            continue;
+        };
+        let Some(version) = package_json.version.clone() else {
+            continue;
+        };
+        let dependencies = resolve_all(&repo_root, &dir, production_dependencies(&package_json));
+        pending.extend(dependencies.values().cloned());
+        installed.packages.insert(
+            key,
+            InstalledPackage {
+                version,
+                dependencies,
+            },
+        );
+    }
+
+    if installed.packages.is_empty() {
+        return None;
+    }
+    debug!("found {} installed packages", installed.packages.len());
+    Some(installed)
+}
+
+fn production_dependencies(package_json: &PackageJson) -> impl Iterator<Item = (&String, &String)> {
+    package_json
+        .dependencies
+        .iter()
+        .flatten()
+        .chain(package_json.optional_dependencies.iter().flatten())
+        .chain(package_json.peer_dependencies.iter().flatten())
+}
+
+fn resolve_all<'a>(
+    repo_root: &AbsoluteSystemPath,
+    dir: &AbsoluteSystemPath,
+    dependencies: impl Iterator<Item = (&'a String, &'a String)>,
+) -> BTreeMap<String, String> {
+    dependencies
+        .filter_map(|(name, _)| Some((name.clone(), resolve(repo_root, dir, name)?)))
+        .collect()
+}
+
+fn resolve_key(repo_root: &AbsoluteSystemPath, key: &str) -> AbsoluteSystemPathBuf {
+    match key {
+        "" => repo_root.to_owned(),
+        key => repo_root.join_components(&key.split('/').collect::<Vec<_>>()),
+    }
+}
+
+// Looks up `name` in the `node_modules` of `dir` and each of its parents up to
+// the repository root, and returns the key of the real package directory.
+fn resolve(repo_root: &AbsoluteSystemPath, dir: &AbsoluteSystemPath, name: &str) -> Option<String> {
+    let name_components = name.split('/').collect::<Vec<_>>();
+    let mut current = Some(dir);
+    while let Some(dir) = current {
+        // Node doesn't look for `node_modules/node_modules`
+        if dir.file_name() != Some(NODE_MODULES) {
+            let candidate = dir
+                .join_component(NODE_MODULES)
+                .join_components(&name_components);
+            if candidate.join_component("package.json").exists() {
+                let real_path = candidate.to_realpath().ok()?;
+                let key = repo_root.anchor(&real_path).ok()?.to_unix().to_string();
+                // Workspaces get linked into `node_modules`, but they aren't
+                // external dependencies. Installed packages always live in a
+                // `node_modules` directory.
+                return key
+                    .split('/')
+                    .any(|component| component == NODE_MODULES)
+                    .then_some(key);
+            }
+        }
+        if dir == repo_root {
+            break;
+        }
+        current = dir.parent();
+    }
+    None
+}
+
+impl Lockfile for InstalledPackages {
+    fn resolve_package(
+        &self,
+        workspace_path: &str,
+        name: &str,
+        _version: &str,
+    ) -> Result<Option<Package>, turborepo_lockfiles::Error> {
+        // Dependencies of installed packages are already resolved to keys
+        let key = match self.packages.contains_key(name) {
+            true => Some(name),
+            false => self.workspaces.get(workspace_path).and_then(|workspace| {
+                workspace
+                    .dependencies
+                    .get(name)
+                    .or_else(|| workspace.dev_dependencies.get(name))
+                    .map(String::as_str)
+            }),
+        };
+        Ok(key.and_then(|key| {
+            let package = self.packages.get(key)?;
+            Some(Package::new(key, package.version.clone()))
+        }))
+    }
+
+    fn all_dependencies(
+        &self,
+        key: &str,
+    ) -> Result<Option<HashMap<String, String>>, turborepo_lockfiles::Error> {
+        Ok(self.packages.get(key).map(|package| {
+            package
+                .dependencies
+                .values()
+                .filter_map(|key| {
+                    let dependency = self.packages.get(key)?;
+                    Some((key.clone(), dependency.version.clone()))
+                })
+                .collect()
+        }))
+    }
+
+    fn subgraph(
+        &self,
+        workspace_packages: &[String],
+        packages: &[String],
+    ) -> Result<Box<dyn Lockfile>, turborepo_lockfiles::Error> {
+        Ok(Box::new(Self {
+            packages: packages
+                .iter()
+                .filter_map(|key| Some((key.clone(), self.packages.get(key)?.clone())))
+                .collect(),
+            workspaces: workspace_packages
+                .iter()
+                .filter_map(|path| Some((path.clone(), self.workspaces.get(path)?.clone())))
+                .collect(),
+        }))
+    }
+
+    fn encode(&self) -> Result<Vec<u8>, turborepo_lockfiles::Error> {
+        Ok(serde_json::to_vec_pretty(self)?)
+    }
+
+    fn global_change(&self, other: &dyn Lockfile) -> bool {
+        // Installed packages have no global settings, only a change of package
+        // manager counts
+        (other as &dyn Any).downcast_ref::<Self>().is_none()
+    }
+
+    fn strip_dev_dependencies(&mut self) -> Result<(), turborepo_lockfiles::Error> {
+        for workspace in self.workspaces.values_mut() {
+            workspace.dev_dependencies.clear();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use tempfile::tempdir;
+    use turbopath::{AbsoluteSystemPath, AbsoluteSystemPathBuf};
+    use turborepo_lockfiles::{transitive_closure, Package};
+
+    use super::installed_packages;
+    use crate::package_json::PackageJson;
+
+    fn install(dir: &AbsoluteSystemPath, path: &[&str], contents: &str) {
+        let package_json = dir.join_components(path).join_component("package.json");
+        package_json.ensure_dir().unwrap();
+        package_json.create_with_contents(contents).unwrap();
+    }
+
+    fn link(dir: &AbsoluteSystemPath, path: &[&str], to: &[&str]) {
+        let link = dir.join_components(path);
+        link.ensure_dir().unwrap();
+        link.symlink_to_dir(dir.join_components(to).as_str())
+            .unwrap();
+    }
+
+    fn repo_root(tmp: &tempfile::TempDir) -> AbsoluteSystemPathBuf {
+        AbsoluteSystemPath::from_std_path(tmp.path())
+            .unwrap()
+            .to_realpath()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_installed_packages() {
+        let tmp = tempdir().unwrap();
+        let repo_root = repo_root(&tmp);
+        install(
+            &repo_root,
+            &["node_modules", "react"],
+            r#"{"name": "react", "version": "18.2.0", "dependencies": {"loose-envify": "^1.1.0"}}"#,
+        );
+        install(
+            &repo_root,
+            &["node_modules", "loose-envify"],
+            r#"{"name": "loose-envify", "version": "1.4.0"}"#,
+        );
+        install(
+            &repo_root,
+            &["node_modules", "@types", "react"],
+            r#"{"name": "@types/react", "version": "18.2.1"}"#,
+        );
+        install(
+            &repo_root,
+            &["packages", "a", "node_modules", "loose-envify"],
+            r#"{"name": "loose-envify", "version": "1.3.0"}"#,
+        );
+
+        let root = PackageJson::from_value(serde_json::json!({
+            "devDependencies": {"@types/react": "^18.0.0"}
+        }))
+        .unwrap();
+        let a = PackageJson::from_value(serde_json::json!({
+            "name": "a",
+            "dependencies": {"react": "^18.0.0", "loose-envify": "^1.3.0"}
+        }))
+        .unwrap();
+        let lockfile =
+            installed_packages(&repo_root, [("", &root), ("packages/a", &a)].into_iter()).unwrap();
+
+        let closure = transitive_closure(
+            &lockfile,
+            "packages/a",
+            HashMap::from([
+                ("react".to_string(), "^18.0.0".to_string()),
+                ("loose-envify".to_string(), "^1.3.0".to_string()),
+            ]),
+        )
+        .unwrap();
+        assert_eq!(
+            closure,
+            [
+                Package::new("node_modules/react", "18.2.0"),
+                Package::new("node_modules/loose-envify", "1.4.0"),
+                Package::new("packages/a/node_modules/loose-envify", "1.3.0"),
+            ]
+            .into_iter()
+            .collect()
+        );
+
+        let types = transitive_closure(
+            &lockfile,
+            "",
+            HashMap::from([("@types/react".to_string(), "^18.0.0".to_string())]),
+        )
+        .unwrap();
+        assert_eq!(
+            types,
+            [Package::new("node_modules/@types/react", "18.2.1")]
+                .into_iter()
+                .collect()
+        );
+    }
+
+    #[test]
+    fn test_pnpm_layout() {
+        let tmp = tempdir().unwrap();
+        let repo_root = repo_root(&tmp);
+        // pnpm links direct dependencies to `.pnpm`, where the dependencies of
+        // each package are linked next to it
+        install(
+            &repo_root,
+            &[
+                "node_modules",
+                ".pnpm",
+                "react@18.2.0",
+                "node_modules",
+                "react",
+            ],
+            r#"{"name": "react", "version": "18.2.0", "dependencies": {"loose-envify": "^1.1.0"}}"#,
+        );
+        install(
+            &repo_root,
+            &[
+                "node_modules",
+                ".pnpm",
+                "loose-envify@1.4.0",
+                "node_modules",
+                "loose-envify",
+            ],
+            r#"{"name": "loose-envify", "version": "1.4.0"}"#,
+        );
+        link(
+            &repo_root,
+            &[
+                "node_modules",
+                ".pnpm",
+                "react@18.2.0",
+                "node_modules",
+                "loose-envify",
+            ],
+            &[
+                "node_modules",
+                ".pnpm",
+                "loose-envify@1.4.0",
+                "node_modules",
+                "loose-envify",
+            ],
+        );
+        link(
+            &repo_root,
+            &["packages", "a", "node_modules", "react"],
+            &[
+                "node_modules",
+                ".pnpm",
+                "react@18.2.0",
+                "node_modules",
+                "react",
+            ],
+        );
+        // Workspaces are linked too, but aren't external dependencies
+        install(&repo_root, &["packages", "b"], r#"{"name": "b"}"#);
+        link(
+            &repo_root,
+            &["packages", "a", "node_modules", "b"],
+            &["packages", "b"],
+        );
+
+        let root = PackageJson::default();
+        let a = PackageJson::from_value(serde_json::json!({
+            "name": "a",
+            "dependencies": {"react": "^18.0.0", "b": "workspace:*"}
+        }))
+        .unwrap();
+        let lockfile =
+            installed_packages(&repo_root, [("", &root), ("packages/a", &a)].into_iter()).unwrap();
+
+        let closure = transitive_closure(
+            &lockfile,
+            "packages/a",
+            HashMap::from([
+                ("react".to_string(), "^18.0.0".to_string()),
+                ("b".to_string(), "workspace:*".to_string()),
+            ]),
+        )
+        .unwrap();
+        assert_eq!(
+            closure,
+            [
+                Package::new(
+                    "node_modules/.pnpm/react@18.2.0/node_modules/react",
+                    "18.2.0"
+                ),
+                Package::new(
+                    "node_modules/.pnpm/loose-envify@1.4.0/node_modules/loose-envify",
+                    "1.4.0"
+                ),
+            ]
+            .into_iter()
+            .collect()
+        );
+    }
+
+    #[test]
+    fn test_nothing_installed() {
+        let tmp = tempdir().unwrap();
+        let repo_root = repo_root(&tmp);
+        let root = PackageJson::default();
+        assert!(installed_packages(&repo_root, [("", &root)].into_iter()).is_none());
+    }
+}
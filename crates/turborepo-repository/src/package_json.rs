@@ -1,6 +1,7 @@
 use std::{collections::BTreeMap, str::FromStr};
 
 use anyhow::Result;
+use miette::Diagnostic;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use turbopath::{AbsoluteSystemPath, RelativeUnixPathBuf};
@@ -45,11 +46,13 @@ pub struct PnpmConfig {
     pub other: BTreeMap<String, Value>,
 }
 
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, thiserror::Error, Diagnostic)]
 pub enum Error {
     #[error("unable to read package.json: {0}")]
+    #[diagnostic(code(TURBO_REPO_001))]
     Io(#[from] std::io::Error),
     #[error("unable to parse package.json: {0}")]
+    #[diagnostic(code(TURBO_REPO_002))]
     Json(#[from] serde_json::Error),
 }
 
@@ -73,6 +76,15 @@ impl PackageJson {
             .chain(self.optional_dependencies.iter().flatten())
             .chain(self.dependencies.iter().flatten())
     }
+
+    /// Dependencies that get installed in a production install, i.e. all
+    /// dependencies except for devDependencies
+    pub fn prod_dependencies(&self) -> impl Iterator<Item = (&String, &String)> + '_ {
+        self.optional_dependencies
+            .iter()
+            .flatten()
+            .chain(self.dependencies.iter().flatten())
+    }
 }
 
 impl FromStr for PackageJson {
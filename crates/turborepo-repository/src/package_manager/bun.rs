@@ -3,6 +3,7 @@ use turbopath::AbsoluteSystemPath;
 use crate::package_manager::{Error, PackageManager};
 
 pub const LOCKFILE: &str = "bun.lockb";
+pub const TEXT_LOCKFILE: &str = "bun.lock";
 
 pub struct BunDetector<'a> {
     repo_root: &'a AbsoluteSystemPath,
@@ -27,9 +28,10 @@ impl<'a> Iterator for BunDetector<'a> {
         }
 
         self.found = true;
-        let package_json = self.repo_root.join_component(LOCKFILE);
+        let lockfile = self.repo_root.join_component(LOCKFILE);
+        let text_lockfile = self.repo_root.join_component(TEXT_LOCKFILE);
 
-        if package_json.exists() {
+        if lockfile.exists() || text_lockfile.exists() {
             Some(Ok(PackageManager::Bun))
         } else {
             None
@@ -45,7 +47,7 @@ mod tests {
     use tempfile::tempdir;
     use turbopath::AbsoluteSystemPathBuf;
 
-    use super::LOCKFILE;
+    use super::{LOCKFILE, TEXT_LOCKFILE};
     use crate::package_manager::PackageManager;
 
     #[test]
@@ -60,4 +62,21 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_detect_bun_text_lockfile() -> Result<()> {
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPathBuf::try_from(repo_root.path())?;
+
+        let lockfile_path = repo_root.path().join(TEXT_LOCKFILE);
+        File::create(lockfile_path)?;
+        let package_manager = PackageManager::detect_package_manager(&repo_root_path)?;
+        assert_eq!(package_manager, PackageManager::Bun);
+        assert_eq!(
+            package_manager.lockfile_path(&repo_root_path),
+            repo_root_path.join_component(TEXT_LOCKFILE)
+        );
+
+        Ok(())
+    }
 }
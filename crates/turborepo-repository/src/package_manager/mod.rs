@@ -14,6 +14,7 @@ use std::{
 use globwalk::{fix_glob_pattern, ValidatedGlob};
 use itertools::{Either, Itertools};
 use lazy_regex::{lazy_regex, Lazy};
+use miette::Diagnostic;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -251,47 +252,65 @@ impl From<wax::BuildError> for Error {
     }
 }
 
-#[derive(Debug, Error)]
+#[derive(Debug, Error, Diagnostic)]
 pub enum Error {
     #[error("io error: {0}")]
+    #[diagnostic(code(TURBO_REPO_011))]
     Io(#[from] std::io::Error, #[backtrace] backtrace::Backtrace),
     #[error(transparent)]
+    #[diagnostic(code(TURBO_REPO_004))]
     Workspace(#[from] MissingWorkspaceError),
     #[error("yaml parsing error: {0}")]
+    #[diagnostic(code(TURBO_REPO_012))]
     ParsingYaml(#[from] serde_yaml::Error, #[backtrace] backtrace::Backtrace),
     #[error("json parsing error: {0}")]
+    #[diagnostic(code(TURBO_REPO_013))]
     ParsingJson(#[from] serde_json::Error, #[backtrace] backtrace::Backtrace),
     #[error("globbing error: {0}")]
+    #[diagnostic(code(TURBO_REPO_014))]
     Wax(Box<wax::BuildError>, #[backtrace] backtrace::Backtrace),
     #[error(transparent)]
+    #[diagnostic(code(TURBO_REPO_015))]
     Other(#[from] anyhow::Error),
     #[error(transparent)]
+    #[diagnostic(code(TURBO_REPO_003))]
     NoPackageManager(#[from] NoPackageManager),
     #[error("We detected multiple package managers in your repository: {}. Please remove one \
     of them.", managers.join(", "))]
+    #[diagnostic(code(TURBO_REPO_016))]
     MultiplePackageManagers { managers: Vec<String> },
     #[error(transparent)]
+    #[diagnostic(code(TURBO_REPO_017))]
     Semver(#[from] node_semver::SemverError),
     #[error(transparent)]
+    #[diagnostic(code(TURBO_REPO_018))]
     Which(#[from] which::Error),
     #[error("invalid utf8: {0}")]
+    #[diagnostic(code(TURBO_REPO_019))]
     Utf8Error(#[from] std::string::FromUtf8Error),
     #[error(transparent)]
+    #[diagnostic(code(TURBO_REPO_020))]
     Path(#[from] turbopath::PathError),
     #[error(
         "We could not parse the packageManager field in package.json, expected: {0}, received: {1}"
     )]
+    #[diagnostic(code(TURBO_REPO_021))]
     InvalidPackageManager(String, String),
     #[error(transparent)]
+    #[diagnostic(code(TURBO_REPO_022))]
     WalkError(#[from] globwalk::WalkError),
     #[error("invalid workspace glob {0}: {1}")]
+    #[diagnostic(code(TURBO_REPO_023))]
     Glob(String, #[source] Box<wax::BuildError>),
     #[error("invalid globwalk pattern {0}")]
+    #[diagnostic(code(TURBO_REPO_024))]
     Globwalk(#[from] globwalk::GlobError),
     #[error(transparent)]
+    #[diagnostic(code(TURBO_REPO_009))]
     Lockfile(#[from] turborepo_lockfiles::Error),
 
     #[error("discovering workspace: {0}")]
+    #[diagnostic(code(TURBO_REPO_010))]
     WorkspaceDiscovery(#[from] discovery::Error),
 }
 
@@ -513,7 +532,7 @@ impl PackageManager {
     ) -> Result<Box<dyn Lockfile>, Error> {
         let lockfile_path = self.lockfile_path(root_path);
         let contents = match self {
-            PackageManager::Bun => {
+            PackageManager::Bun if lockfile_path.file_name() == Some(bun::LOCKFILE) => {
                 Command::new(which("bun")?)
                     .arg(lockfile_path.to_string())
                     .current_dir(root_path.to_string())
@@ -532,13 +551,24 @@ impl PackageManager {
         contents: &[u8],
     ) -> Result<Box<dyn Lockfile>, Error> {
         Ok(match self {
-            PackageManager::Npm => Box::new(turborepo_lockfiles::NpmLockfile::load(contents)?),
+            PackageManager::Npm => {
+                let lockfile = turborepo_lockfiles::NpmLockfile::load(contents)?;
+                Box::new(match root_package_json.other.get("overrides") {
+                    Some(overrides) => lockfile.with_overrides(overrides),
+                    None => lockfile,
+                })
+            }
             PackageManager::Pnpm | PackageManager::Pnpm6 => {
                 Box::new(turborepo_lockfiles::PnpmLockfile::from_bytes(contents)?)
             }
             PackageManager::Yarn => {
                 Box::new(turborepo_lockfiles::Yarn1Lockfile::from_bytes(contents)?)
             }
+            PackageManager::Bun
+                if turborepo_lockfiles::BunTextLockfile::is_text_lockfile(contents) =>
+            {
+                Box::new(turborepo_lockfiles::BunTextLockfile::from_bytes(contents)?)
+            }
             PackageManager::Bun => {
                 Box::new(turborepo_lockfiles::BunLockfile::from_bytes(contents)?)
             }
@@ -572,7 +602,23 @@ impl PackageManager {
     }
 
     pub fn lockfile_path(&self, turbo_root: &AbsoluteSystemPath) -> AbsoluteSystemPathBuf {
-        turbo_root.join_component(self.lockfile_name())
+        turbo_root.join_component(self.existing_lockfile_name(turbo_root))
+    }
+
+    /// The name of the lockfile in the repository. This only differs from
+    /// `lockfile_name` for Bun which will use the text `bun.lock` over the
+    /// binary `bun.lockb` if it is present, and for npm which will use
+    /// `npm-shrinkwrap.json` over `package-lock.json`.
+    pub fn existing_lockfile_name(&self, turbo_root: &AbsoluteSystemPath) -> &'static str {
+        match self {
+            PackageManager::Bun if turbo_root.join_component(bun::TEXT_LOCKFILE).exists() => {
+                bun::TEXT_LOCKFILE
+            }
+            PackageManager::Npm if turbo_root.join_component(npm::SHRINKWRAP).exists() => {
+                npm::SHRINKWRAP
+            }
+            _ => self.lockfile_name(),
+        }
     }
 
     pub fn arg_separator(&self, user_args: &[String]) -> Option<&str> {
@@ -3,6 +3,9 @@ use turbopath::AbsoluteSystemPath;
 use crate::package_manager::{Error, PackageManager};
 
 pub const LOCKFILE: &str = "package-lock.json";
+// Has the same format as `package-lock.json` and takes precedence over it, but
+// is published along with a package
+pub const SHRINKWRAP: &str = "npm-shrinkwrap.json";
 
 pub struct NpmDetector<'a> {
     repo_root: &'a AbsoluteSystemPath,
@@ -27,9 +30,10 @@ impl<'a> Iterator for NpmDetector<'a> {
         }
 
         self.found = true;
-        let package_json = self.repo_root.join_component(LOCKFILE);
+        let lockfile = self.repo_root.join_component(LOCKFILE);
+        let shrinkwrap = self.repo_root.join_component(SHRINKWRAP);
 
-        if package_json.exists() {
+        if lockfile.exists() || shrinkwrap.exists() {
             Some(Ok(PackageManager::Npm))
         } else {
             None
@@ -45,7 +49,7 @@ mod tests {
     use tempfile::tempdir;
     use turbopath::AbsoluteSystemPathBuf;
 
-    use super::LOCKFILE;
+    use super::{LOCKFILE, SHRINKWRAP};
     use crate::package_manager::PackageManager;
 
     #[test]
@@ -60,4 +64,27 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_detect_npm_shrinkwrap() -> Result<()> {
+        let repo_root = tempdir()?;
+        let repo_root_path = AbsoluteSystemPathBuf::try_from(repo_root.path())?;
+
+        File::create(repo_root.path().join(SHRINKWRAP))?;
+        let package_manager = PackageManager::detect_package_manager(&repo_root_path)?;
+        assert_eq!(package_manager, PackageManager::Npm);
+        assert_eq!(
+            package_manager.lockfile_path(&repo_root_path),
+            repo_root_path.join_component(SHRINKWRAP)
+        );
+
+        // npm uses the shrinkwrap if both are present
+        File::create(repo_root.path().join(LOCKFILE))?;
+        assert_eq!(
+            package_manager.lockfile_path(&repo_root_path),
+            repo_root_path.join_component(SHRINKWRAP)
+        );
+
+        Ok(())
+    }
 }
@@ -21,6 +21,15 @@ impl SCM {
         }
     }
 
+    /// Returns true if there are any uncommitted changes, including untracked
+    /// files, in the repository
+    pub fn is_dirty(&self, path: &AbsoluteSystemPath) -> Result<bool, Error> {
+        match self {
+            Self::Git(git) => git.is_dirty(),
+            Self::Manual => Err(Error::GitRequired(path.to_owned())),
+        }
+    }
+
     pub fn changed_files(
         &self,
         turbo_root: &AbsoluteSystemPath,
@@ -92,6 +101,11 @@ impl Git {
         Ok(output.trim().to_owned())
     }
 
+    fn is_dirty(&self) -> Result<bool, Error> {
+        let output = self.execute_git_command(&["status", "--porcelain"], "")?;
+        Ok(!output.is_empty())
+    }
+
     fn changed_files(
         &self,
         turbo_root: &AbsoluteSystemPath,
@@ -236,7 +250,7 @@ mod tests {
     use which::which;
 
     use super::previous_content;
-    use crate::{git::changed_files, Error};
+    use crate::{git::changed_files, Error, SCM};
 
     fn setup_repository() -> Result<(TempDir, Repository), Error> {
         let repo_root = tempfile::tempdir()?;
@@ -589,6 +603,23 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_is_dirty() -> Result<(), Error> {
+        let (repo_root, repo) = setup_repository()?;
+        let root = AbsoluteSystemPathBuf::try_from(repo_root.path()).unwrap();
+        let file = root.join_component("foo.js");
+        file.create_with_contents("let z = 0;")?;
+        commit_file(&repo, Path::new("foo.js"), None);
+
+        let scm = SCM::new(&root);
+        assert!(!scm.is_dirty(&root)?);
+
+        fs::write(&file, "let z = 1;")?;
+        assert!(scm.is_dirty(&root)?);
+
+        Ok(())
+    }
+
     #[test]
     fn test_revparse() -> Result<(), Error> {
         let (repo_root, repo) = setup_repository()?;
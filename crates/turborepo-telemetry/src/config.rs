@@ -1,4 +1,4 @@
-use std::env;
+use std::{env, fmt};
 
 use chrono::{DateTime, Utc};
 pub use config::{Config, ConfigError, File, FileFormat};
@@ -30,6 +30,76 @@ pub struct TelemetryConfigContents {
     // when the alert was shown
     #[serde(skip_serializing_if = "Option::is_none")]
     telemetry_alerted: Option<DateTime<Utc>>,
+
+    // which categories of events are collected while telemetry is enabled
+    #[serde(default)]
+    telemetry_categories: TelemetryCategories,
+}
+
+/// The categories that telemetry events are grouped in, which can be
+/// disabled individually.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TelemetryCategory {
+    /// Commands, arguments and the shape of the repository
+    Usage,
+    /// Errors that turbo recovered from
+    Errors,
+    /// Timing, hardware and hashing strategies
+    Performance,
+}
+
+impl TelemetryCategory {
+    pub const ALL: [TelemetryCategory; 3] = [
+        TelemetryCategory::Usage,
+        TelemetryCategory::Errors,
+        TelemetryCategory::Performance,
+    ];
+}
+
+impl fmt::Display for TelemetryCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TelemetryCategory::Usage => write!(f, "usage"),
+            TelemetryCategory::Errors => write!(f, "errors"),
+            TelemetryCategory::Performance => write!(f, "performance"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TelemetryCategories {
+    usage: bool,
+    errors: bool,
+    performance: bool,
+}
+
+impl Default for TelemetryCategories {
+    fn default() -> Self {
+        Self {
+            usage: true,
+            errors: true,
+            performance: true,
+        }
+    }
+}
+
+impl TelemetryCategories {
+    pub fn is_enabled(&self, category: TelemetryCategory) -> bool {
+        match category {
+            TelemetryCategory::Usage => self.usage,
+            TelemetryCategory::Errors => self.errors,
+            TelemetryCategory::Performance => self.performance,
+        }
+    }
+
+    fn set(&mut self, category: TelemetryCategory, enabled: bool) {
+        match category {
+            TelemetryCategory::Usage => self.usage = enabled,
+            TelemetryCategory::Errors => self.errors = enabled,
+            TelemetryCategory::Performance => self.performance = enabled,
+        }
+    }
 }
 
 impl Default for TelemetryConfigContents {
@@ -41,6 +111,7 @@ impl Default for TelemetryConfigContents {
         TelemetryConfigContents {
             telemetry_enabled: true,
             telemetry_alerted: None,
+            telemetry_categories: TelemetryCategories::default(),
             telemetry_salt,
             telemetry_id,
         }
@@ -202,6 +273,10 @@ impl TelemetryConfig {
         &self.config.telemetry_id
     }
 
+    pub fn categories(&self) -> TelemetryCategories {
+        self.config.telemetry_categories
+    }
+
     // setters
     pub fn enable(&mut self) -> Result<&TelemetryConfigContents, ConfigError> {
         self.config.telemetry_enabled = true;
@@ -215,6 +290,24 @@ impl TelemetryConfig {
         Ok(&self.config)
     }
 
+    pub fn enable_category(
+        &mut self,
+        category: TelemetryCategory,
+    ) -> Result<&TelemetryConfigContents, ConfigError> {
+        self.config.telemetry_categories.set(category, true);
+        self.write()?;
+        Ok(&self.config)
+    }
+
+    pub fn disable_category(
+        &mut self,
+        category: TelemetryCategory,
+    ) -> Result<&TelemetryConfigContents, ConfigError> {
+        self.config.telemetry_categories.set(category, false);
+        self.write()?;
+        Ok(&self.config)
+    }
+
     pub fn alert_shown(&mut self) -> Result<&TelemetryConfigContents, ConfigError> {
         match self.has_seen_alert() {
             true => Ok(&self.config),
@@ -227,14 +320,18 @@ impl TelemetryConfig {
     }
 }
 
-fn default_config_path() -> Result<AbsoluteSystemPathBuf, ConfigError> {
+// stored as a sibling to the turbo global config
+pub(crate) fn default_config_dir() -> Result<AbsoluteSystemPathBuf, ConfigError> {
     let config_dir = config_dir()
         .map_err(|e| ConfigError::Message(format!("Invalid config directory: {}", e)))?
         .ok_or(ConfigError::Message(
             "Unable to find telemetry config directory".to_string(),
         ))?;
-    // stored as a sibling to the turbo global config
-    Ok(config_dir.join_components(&["turborepo", "telemetry.json"]))
+    Ok(config_dir.join_component("turborepo"))
+}
+
+fn default_config_path() -> Result<AbsoluteSystemPathBuf, ConfigError> {
+    Ok(default_config_dir()?.join_component("telemetry.json"))
 }
 
 fn write_new_config(file_path: &AbsoluteSystemPath) -> Result<(), ConfigError> {
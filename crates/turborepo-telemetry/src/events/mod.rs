@@ -2,6 +2,8 @@ use std::fmt::Display;
 
 use serde::{Deserialize, Serialize};
 
+use crate::config::TelemetryCategory;
+
 // all event builders and their event methods
 pub mod command;
 pub mod generic;
@@ -31,6 +33,23 @@ pub struct Event {
     is_sensitive: EventType,
 }
 
+/// The category of `event`, which decides whether it's collected
+pub fn category(event: &TelemetryEvent) -> TelemetryCategory {
+    let key = match event {
+        TelemetryEvent::Task(event) => &event.key,
+        TelemetryEvent::Command(event) => &event.key,
+        TelemetryEvent::Repo(event) => &event.key,
+        TelemetryEvent::Generic(event) => &event.key,
+    };
+    match key.as_str() {
+        "error" => TelemetryCategory::Errors,
+        "execution" | "cpu_count" | "daemon_status" | "file_hash_method" | "scm_mode" => {
+            TelemetryCategory::Performance
+        }
+        _ => TelemetryCategory::Usage,
+    }
+}
+
 pub trait Identifiable {
     fn get_id(&self) -> &String;
 }
@@ -8,13 +8,15 @@
 pub mod config;
 pub mod errors;
 pub mod events;
+pub mod queue;
 
 use std::time::Duration;
 
-use config::{ConfigError, TelemetryConfig};
+use config::{ConfigError, TelemetryCategories, TelemetryConfig};
 use events::TelemetryEvent;
 use futures::{stream::FuturesUnordered, StreamExt};
 use once_cell::sync::OnceCell;
+use queue::Queue;
 use thiserror::Error;
 use tokio::{
     select,
@@ -78,6 +80,8 @@ pub fn telem(event: events::TelemetryEvent) {
 fn init(
     mut config: TelemetryConfig,
     client: impl telemetry::TelemetryClient + Clone + Send + Sync + 'static,
+    queue: Option<Queue>,
+    offline: bool,
     ui: UI,
 ) -> Result<(TelemetryHandle, TelemetrySender), Box<dyn std::error::Error>> {
     let (tx, rx) = mpsc::unbounded_channel();
@@ -94,6 +98,9 @@ fn init(
         session_id: session_id.to_string(),
         telemetry_id: config.get_id().to_string(),
         enabled: config.is_enabled(),
+        categories: config.categories(),
+        queue,
+        offline,
         ui,
     };
     let handle = worker.start();
@@ -113,8 +120,15 @@ fn init(
 /// We have two different types because the TelemetrySender should be shared
 /// across threads (i.e. Clone + Send), while the TelemetryHandle cannot be
 /// shared since it contains the structs necessary to shut down the worker.
+///
+/// Events that can't be sent, including every event when `offline` is set,
+/// are queued on disk and sent by a later invocation that uses the same
+/// `endpoint`. If it's unknown which endpoint the client sends to, there's no
+/// queue, so that events are never sent to a different collector.
 pub fn init_telemetry(
     client: impl telemetry::TelemetryClient + Clone + Send + Sync + 'static,
+    endpoint: Option<&str>,
+    offline: bool,
     ui: UI,
 ) -> Result<TelemetryHandle, Box<dyn std::error::Error>> {
     // make sure we're not already initialized
@@ -123,7 +137,12 @@ pub fn init_telemetry(
         return Err(Box::new(Error::AlreadyInitialized()));
     }
     let config = TelemetryConfig::with_default_config_path()?;
-    let (handle, sender) = init(config, client, ui)?;
+    let queue = endpoint.and_then(|endpoint| {
+        Queue::for_endpoint(endpoint)
+            .map_err(|err| debug!("unable to locate telemetry queue: {}", err))
+            .ok()
+    });
+    let (handle, sender) = init(config, client, queue, offline, ui)?;
     SENDER_INSTANCE.set(sender).unwrap();
     Ok(handle)
 }
@@ -157,12 +176,17 @@ struct Worker<C> {
     telemetry_id: String,
     session_id: String,
     enabled: bool,
+    categories: TelemetryCategories,
+    queue: Option<Queue>,
+    // Queue every event instead of sending it
+    offline: bool,
     ui: UI,
 }
 
 impl<C: telemetry::TelemetryClient + Clone + Send + Sync + 'static> Worker<C> {
     pub fn start(mut self) -> JoinHandle<()> {
         tokio::spawn(async move {
+            self.send_queued_events();
             let mut timeout = tokio::time::sleep(NO_TIMEOUT);
             loop {
                 select! {
@@ -170,6 +194,9 @@ impl<C: telemetry::TelemetryClient + Clone + Send + Sync + 'static> Worker<C> {
                     biased;
                     event = self.rx.recv() => {
                         if let Some(event) = event {
+                            if !self.categories.is_enabled(events::category(&event)) {
+                                continue;
+                            }
                             self.buffer.push(event);
                         } else {
                             // There are no senders left so we can shut down
@@ -215,6 +242,32 @@ impl<C: telemetry::TelemetryClient + Clone + Send + Sync + 'static> Worker<C> {
         }
     }
 
+    // Sends the events that previous invocations were unable to send
+    fn send_queued_events(&mut self) {
+        if !self.enabled || self.offline {
+            return;
+        }
+        let Some(queue) = &self.queue else {
+            return;
+        };
+        let events = match queue.take() {
+            Ok(events) => events,
+            Err(err) => {
+                debug!("failed to read telemetry queue. error: {}", err);
+                return;
+            }
+        };
+        if events.is_empty() {
+            return;
+        }
+        debug!("sending {} queued telemetry events", events.len());
+        for batch in events.chunks(BUFFER_THRESHOLD) {
+            if let Some(handle) = self.send_events(batch.to_vec()) {
+                self.senders.push(handle);
+            }
+        }
+    }
+
     fn send_events(&self, events: Vec<TelemetryEvent>) -> Option<JoinHandle<()>> {
         if !self.enabled {
             return None;
@@ -232,22 +285,52 @@ impl<C: telemetry::TelemetryClient + Clone + Send + Sync + 'static> Worker<C> {
             }
         }
 
+        if self.offline {
+            enqueue(self.queue.as_ref(), &events);
+            return None;
+        }
+
         let client = self.client.clone();
         let session_id = self.session_id.clone();
         let telemetry_id = self.telemetry_id.clone();
+        let queue = self.queue.clone();
         Some(tokio::spawn(async move {
-            if let Ok(Err(err)) = tokio::time::timeout(
+            match tokio::time::timeout(
                 REQUEST_TIMEOUT,
-                client.record_telemetry(events, telemetry_id.as_str(), session_id.as_str()),
+                client.record_telemetry(events.clone(), telemetry_id.as_str(), session_id.as_str()),
             )
             .await
             {
-                debug!("failed to record cache usage telemetry. error: {}", err)
+                Ok(Ok(())) => {}
+                // The collector rejected the events, so sending them again won't help
+                Ok(Err(err)) if !is_unreachable(&err) => {
+                    debug!("failed to record cache usage telemetry. error: {}", err)
+                }
+                Ok(Err(_)) | Err(_) => {
+                    debug!("telemetry collector unreachable, queueing events");
+                    enqueue(queue.as_ref(), &events);
+                }
             }
         }))
     }
 }
 
+fn enqueue(queue: Option<&Queue>, events: &[TelemetryEvent]) {
+    if let Some(queue) = queue {
+        if let Err(err) = queue.push(events) {
+            debug!("failed to queue telemetry events. error: {}", err)
+        }
+    }
+}
+
+fn is_unreachable(err: &turborepo_api_client::Error) -> bool {
+    match err {
+        turborepo_api_client::Error::ReqwestError(err) => err.is_connect() || err.is_timeout(),
+        turborepo_api_client::Error::TooManyFailures(err) => err.is_connect() || err.is_timeout(),
+        _ => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::{
@@ -266,7 +349,11 @@ mod tests {
     use turborepo_ui::UI;
     use turborepo_vercel_api::telemetry::{TelemetryEvent, TelemetryGenericEvent};
 
-    use crate::{config::TelemetryConfig, init};
+    use crate::{
+        config::{TelemetryCategory, TelemetryConfig},
+        init,
+        queue::Queue,
+    };
 
     #[derive(Clone)]
     struct DummyClient {
@@ -344,7 +431,7 @@ mod tests {
             tx,
         };
 
-        let result = init(config, client.clone(), UI::new(false));
+        let result = init(config, client.clone(), None, false, UI::new(false));
 
         let (telemetry_handle, telemetry_sender) = result.unwrap();
 
@@ -384,7 +471,7 @@ mod tests {
             tx,
         };
 
-        let result = init(config, client.clone(), UI::new(false));
+        let result = init(config, client.clone(), None, false, UI::new(false));
 
         let (telemetry_handle, telemetry_sender) = result.unwrap();
 
@@ -430,7 +517,7 @@ mod tests {
             tx,
         };
 
-        let result = init(config, client.clone(), UI::new(false));
+        let result = init(config, client.clone(), None, false, UI::new(false));
 
         let (telemetry_handle, telemetry_sender) = result.unwrap();
 
@@ -458,4 +545,134 @@ mod tests {
         let payloads = &found[0];
         assert_eq!(payloads.len(), 2);
     }
+
+    fn generic_event() -> TelemetryEvent {
+        TelemetryEvent::Generic(TelemetryGenericEvent {
+            id: "id".to_string(),
+            key: "key".to_string(),
+            value: "value".to_string(),
+            parent_id: None,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_offline_queues_events() {
+        let (_tmp, temp_dir) = temp_dir();
+        let config =
+            TelemetryConfig::new(temp_dir.join_components(&["turborepo", "telemetry.json"]))
+                .unwrap();
+        let queue = Queue::new(temp_dir.join_components(&["turborepo", "telemetry-queue.jsonl"]));
+        let (tx, mut _rx) = mpsc::unbounded_channel();
+
+        let client = DummyClient {
+            events: Default::default(),
+            tx,
+        };
+
+        let (telemetry_handle, telemetry_sender) = init(
+            config,
+            client.clone(),
+            Some(queue.clone()),
+            true,
+            UI::new(false),
+        )
+        .unwrap();
+
+        for _ in 0..2 {
+            telemetry_sender.send(generic_event()).unwrap();
+        }
+        drop(telemetry_sender);
+
+        tokio::time::timeout(Duration::from_millis(5), telemetry_handle.close())
+            .await
+            .expect("timeout before close")
+            .expect("analytics worker panicked");
+        assert!(client.events().is_empty());
+        assert_eq!(queue.take().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_sends_queued_events() {
+        let (_tmp, temp_dir) = temp_dir();
+        let config =
+            TelemetryConfig::new(temp_dir.join_components(&["turborepo", "telemetry.json"]))
+                .unwrap();
+        let queue = Queue::new(temp_dir.join_components(&["turborepo", "telemetry-queue.jsonl"]));
+        queue
+            .push(&(0..12).map(|_| generic_event()).collect::<Vec<_>>())
+            .unwrap();
+        let (tx, mut _rx) = mpsc::unbounded_channel();
+
+        let client = DummyClient {
+            events: Default::default(),
+            tx,
+        };
+
+        let (telemetry_handle, telemetry_sender) = init(
+            config,
+            client.clone(),
+            Some(queue.clone()),
+            false,
+            UI::new(false),
+        )
+        .unwrap();
+        drop(telemetry_sender);
+
+        tokio::time::timeout(Duration::from_millis(50), telemetry_handle.close())
+            .await
+            .expect("timeout before close")
+            .expect("analytics worker panicked");
+        // The batches are sent concurrently
+        let mut batch_sizes = client.events().iter().map(Vec::len).collect::<Vec<_>>();
+        batch_sizes.sort();
+        assert_eq!(batch_sizes, vec![2, 10]);
+        assert!(queue.take().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_disabled_category() {
+        let (_tmp, temp_dir) = temp_dir();
+        let mut config =
+            TelemetryConfig::new(temp_dir.join_components(&["turborepo", "telemetry.json"]))
+                .unwrap();
+        config
+            .disable_category(TelemetryCategory::Performance)
+            .unwrap();
+        let (tx, mut _rx) = mpsc::unbounded_channel();
+
+        let client = DummyClient {
+            events: Default::default(),
+            tx,
+        };
+
+        let (telemetry_handle, telemetry_sender) =
+            init(config, client.clone(), None, false, UI::new(false)).unwrap();
+
+        for key in ["cpu_count", "error", "command"] {
+            telemetry_sender
+                .send(TelemetryEvent::Generic(TelemetryGenericEvent {
+                    id: "id".to_string(),
+                    key: key.to_string(),
+                    value: "value".to_string(),
+                    parent_id: None,
+                }))
+                .unwrap();
+        }
+        drop(telemetry_sender);
+
+        tokio::time::timeout(Duration::from_millis(5), telemetry_handle.close())
+            .await
+            .expect("timeout before close")
+            .expect("analytics worker panicked");
+        let found = client.events();
+        assert_eq!(found.len(), 1);
+        let keys = found[0]
+            .iter()
+            .map(|event| match event {
+                TelemetryEvent::Generic(event) => event.key.as_str(),
+                _ => panic!("expected a generic event"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec!["error", "command"]);
+    }
 }
@@ -0,0 +1,175 @@
+//! On-disk queue of telemetry events that couldn't be sent.
+//!
+//! When the telemetry collector can't be reached, or turbo runs in offline
+//! mode, events are appended to a file next to the telemetry config instead of
+//! being dropped. The next run that can reach the collector sends them before
+//! its own events. There is a queue per collector endpoint, so events are only
+//! ever sent to the collector they were meant for. The queue is bounded, so the
+//! oldest events are dropped once it's full. Concurrent runs may race on the
+//! file, which is acceptable for telemetry.
+
+use std::{
+    fs::OpenOptions,
+    io::{self, Write},
+};
+
+use sha2::{Digest, Sha256};
+use tracing::debug;
+use turbopath::AbsoluteSystemPathBuf;
+use turborepo_vercel_api::telemetry::TelemetryEvent;
+
+use crate::config::{default_config_dir, ConfigError};
+
+const QUEUE_FILE_PREFIX: &str = "telemetry-queue";
+
+/// The number of events to keep in the queue.
+const MAX_QUEUED_EVENTS: usize = 1000;
+
+#[derive(Debug, Clone)]
+pub struct Queue {
+    path: AbsoluteSystemPathBuf,
+}
+
+impl Queue {
+    pub fn new(path: AbsoluteSystemPathBuf) -> Self {
+        Self { path }
+    }
+
+    /// The queue of the events for the collector at `endpoint`.
+    pub fn for_endpoint(endpoint: &str) -> Result<Self, ConfigError> {
+        Ok(Self::new(
+            default_config_dir()?.join_component(&Self::file_name(endpoint)),
+        ))
+    }
+
+    fn file_name(endpoint: &str) -> String {
+        let hash = hex::encode(Sha256::digest(endpoint.trim_end_matches('/').as_bytes()));
+        format!("{QUEUE_FILE_PREFIX}-{}.jsonl", &hash[..16])
+    }
+
+    /// Appends `events` to the queue, dropping the oldest events if the queue
+    /// is full.
+    pub fn push(&self, events: &[TelemetryEvent]) -> io::Result<()> {
+        let mut lines = Vec::with_capacity(events.len());
+        for event in events {
+            lines.push(serde_json::to_string(event)?);
+        }
+
+        self.path.ensure_dir()?;
+        let queued = self.read_lines()?;
+        if queued.len() + lines.len() > MAX_QUEUED_EVENTS {
+            let mut all = queued;
+            all.extend(lines);
+            let keep = all.split_off(all.len().saturating_sub(MAX_QUEUED_EVENTS));
+            debug!("telemetry queue is full, dropping {} events", all.len());
+            let mut contents = keep.join("\n");
+            contents.push('\n');
+            return self.path.create_with_contents(contents);
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.path.as_std_path())?;
+        for line in lines {
+            writeln!(file, "{line}")?;
+        }
+        Ok(())
+    }
+
+    /// Removes and returns the queued events. Events that can't be parsed,
+    /// e.g. because they were written by a different version of turbo, are
+    /// dropped.
+    pub fn take(&self) -> io::Result<Vec<TelemetryEvent>> {
+        let lines = self.read_lines()?;
+        if lines.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.path.remove_file()?;
+        Ok(lines
+            .iter()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    fn read_lines(&self) -> io::Result<Vec<String>> {
+        match self.path.read_to_string() {
+            Ok(contents) => Ok(contents
+                .lines()
+                .filter(|line| !line.is_empty())
+                .map(|line| line.to_string())
+                .collect()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use turbopath::AbsoluteSystemPathBuf;
+    use turborepo_vercel_api::telemetry::{TelemetryEvent, TelemetryGenericEvent};
+
+    use super::{Queue, MAX_QUEUED_EVENTS};
+
+    #[test]
+    fn test_queue_per_endpoint() {
+        let default = Queue::file_name("https://telemetry.vercel.com");
+        assert_eq!(default, Queue::file_name("https://telemetry.vercel.com/"));
+        assert_ne!(default, Queue::file_name("https://telemetry.example.com"));
+    }
+
+    fn event(value: usize) -> TelemetryEvent {
+        TelemetryEvent::Generic(TelemetryGenericEvent {
+            id: "id".to_string(),
+            key: "key".to_string(),
+            value: value.to_string(),
+            parent_id: None,
+        })
+    }
+
+    fn value(event: &TelemetryEvent) -> &str {
+        match event {
+            TelemetryEvent::Generic(event) => &event.value,
+            _ => panic!("expected a generic event"),
+        }
+    }
+
+    #[test]
+    fn test_push_and_take() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = AbsoluteSystemPathBuf::try_from(tmp.path()).unwrap();
+        let queue = Queue::new(dir.join_components(&["turborepo", "telemetry-queue.jsonl"]));
+
+        assert!(queue.take().unwrap().is_empty());
+
+        queue.push(&[event(1), event(2)]).unwrap();
+        queue.push(&[event(3)]).unwrap();
+
+        let events = queue.take().unwrap();
+        assert_eq!(
+            events.iter().map(value).collect::<Vec<_>>(),
+            vec!["1", "2", "3"]
+        );
+        assert!(queue.take().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_drops_oldest_events() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = AbsoluteSystemPathBuf::try_from(tmp.path()).unwrap();
+        let queue = Queue::new(dir.join_component("telemetry-queue.jsonl"));
+
+        let events = (0..MAX_QUEUED_EVENTS).map(event).collect::<Vec<_>>();
+        queue.push(&events).unwrap();
+        queue.push(&[event(MAX_QUEUED_EVENTS)]).unwrap();
+
+        let events = queue.take().unwrap();
+        assert_eq!(events.len(), MAX_QUEUED_EVENTS);
+        assert_eq!(value(&events[0]), "1");
+        assert_eq!(
+            value(&events[MAX_QUEUED_EVENTS - 1]),
+            MAX_QUEUED_EVENTS.to_string()
+        );
+    }
+}
@@ -0,0 +1,190 @@
+//! With `--log-format=json` every line that turbo writes is a JSON object, so
+//! that log aggregation systems can parse the output of a run reliably. This
+//! includes the output of tasks, which is written line by line along with the
+//! task it belongs to.
+
+use std::{
+    fmt::Display,
+    io::{self, Write},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use chrono::{Local, SecondsFormat};
+use serde_json::{Map, Value};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// The target of lines that turbo prints, as opposed to the events it logs.
+pub const OUTPUT_TARGET: &str = "turbo::output";
+
+/// Makes every line that is written through this crate a JSON object.
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Formats a line as a JSON object with its timestamp, level, target and task,
+/// followed by `fields`.
+pub fn format_line(
+    level: &str,
+    target: &str,
+    task: Option<Value>,
+    fields: Map<String, Value>,
+) -> String {
+    let mut object = Map::new();
+    object.insert(
+        "timestamp".to_string(),
+        Local::now()
+            .to_rfc3339_opts(SecondsFormat::Millis, false)
+            .into(),
+    );
+    object.insert("level".to_string(), level.into());
+    object.insert("target".to_string(), target.into());
+    if let Some(task) = task {
+        object.insert("task".to_string(), task);
+    }
+    object.extend(fields);
+    Value::Object(object).to_string()
+}
+
+/// Formats a line of output as a JSON object. Colors are removed from the
+/// message.
+pub fn format_message(level: &str, task: Option<&str>, message: &str) -> String {
+    let message = console::strip_ansi_codes(message);
+    let message = message.trim_end_matches(['\r', '\n']);
+    format_line(
+        level,
+        OUTPUT_TARGET,
+        task.map(Value::from),
+        Map::from_iter([("message".to_string(), message.into())]),
+    )
+}
+
+/// Returns `output` as it should be written. With the JSON log format every
+/// line of it becomes a JSON object, and empty lines are left out.
+pub fn format_output(output: &str) -> String {
+    match is_enabled() {
+        true => format_lines(output),
+        false => output.to_string(),
+    }
+}
+
+fn format_lines(output: &str) -> String {
+    output
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| format_message("INFO", None, line) + "\n")
+        .collect()
+}
+
+/// Prints `message` and a newline to stdout, formatted by `format_output`.
+pub fn print_line(message: impl Display) {
+    print!("{}", format_output(&format!("{message}\n")));
+}
+
+/// Like `print_line`, but for stderr.
+pub fn eprint_line(message: impl Display) {
+    eprint!("{}", format_output(&format!("{message}\n")));
+}
+
+/// Writes everything that is written to it as JSON objects, one per line.
+/// An incomplete last line is written when the writer is flushed.
+pub struct JsonLineWriter<W> {
+    writer: W,
+    pub(crate) task: Option<String>,
+    line: Vec<u8>,
+}
+
+impl<W: Write> JsonLineWriter<W> {
+    pub fn new(writer: W, task: Option<String>) -> Self {
+        Self {
+            writer,
+            task,
+            line: Vec::new(),
+        }
+    }
+
+    fn write_line(&mut self) -> io::Result<()> {
+        let line = String::from_utf8_lossy(&self.line).into_owned();
+        self.line.clear();
+        writeln!(
+            self.writer,
+            "{}",
+            format_message("INFO", self.task.as_deref(), &line)
+        )
+    }
+}
+
+impl<W: Write> Write for JsonLineWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for chunk in buf.split_inclusive(|c| *c == b'\n') {
+            self.line.extend_from_slice(chunk);
+            if chunk.ends_with(b"\n") {
+                self.write_line()?;
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if !self.line.is_empty() {
+            self.write_line()?;
+        }
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Write;
+
+    use serde_json::Value;
+
+    use super::{format_lines, JsonLineWriter};
+
+    fn parse(output: &[u8]) -> Vec<Value> {
+        std::str::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_json_line_writer() {
+        let mut buffer = Vec::new();
+        {
+            let mut writer = JsonLineWriter::new(&mut buffer, Some("web#build".to_string()));
+            writer
+                .write_all(b"\x1b[1mcompiled\x1b[0m\r\nstill ")
+                .unwrap();
+            writer.write_all(b"going\nno newline").unwrap();
+            writer.flush().unwrap();
+        }
+
+        let lines = parse(&buffer);
+        let messages = lines
+            .iter()
+            .map(|line| line["message"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(messages, ["compiled", "still going", "no newline"]);
+        assert!(lines.iter().all(|line| line["task"] == "web#build"));
+        assert!(lines.iter().all(|line| line["level"] == "INFO"));
+    }
+
+    #[test]
+    fn test_format_lines() {
+        let output = format_lines("• Running build\n\n Tasks:    1 successful\n");
+
+        let lines = parse(output.as_bytes());
+        let messages = lines
+            .iter()
+            .map(|line| line["message"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(messages, ["• Running build", " Tasks:    1 successful"]);
+        assert!(lines.iter().all(|line| line["task"].is_null()));
+    }
+}
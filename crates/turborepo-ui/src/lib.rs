@@ -3,6 +3,7 @@
 //! output, and a `ColorSelector` that lets multiple concurrent resources get
 //! an assigned color.
 mod color_selector;
+pub mod json;
 mod logs;
 mod output;
 mod prefixed;
@@ -73,7 +74,7 @@ macro_rules! cprintln {
 
         let colored_str = $color.apply_to(formatted_str);
 
-        println!("{}", $ui.apply(colored_str))
+        $crate::json::print_line($ui.apply(colored_str))
     }};
 }
 
@@ -84,7 +85,12 @@ macro_rules! cprint {
 
         let colored_str = $color.apply_to(formatted_str);
 
-        print!("{}", $ui.apply(colored_str))
+        // JSON objects can't be continued, so this prints a whole line instead
+        if $crate::json::is_enabled() {
+            $crate::json::print_line($ui.apply(colored_str))
+        } else {
+            print!("{}", $ui.apply(colored_str))
+        }
     }};
 }
 
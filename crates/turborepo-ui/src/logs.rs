@@ -9,10 +9,11 @@ use turbopath::AbsoluteSystemPath;
 use crate::{prefixed::PrefixedUI, Error, PrefixedWriter};
 
 /// Receives logs and multiplexes them to a log file and/or a prefixed
-/// writer
+/// writer, as well as any additional sinks
 pub struct LogWriter<W> {
     log_file: Option<BufWriter<File>>,
     prefixed_writer: Option<PrefixedWriter<W>>,
+    sinks: Vec<Box<dyn Write + Send>>,
 }
 
 /// Derive didn't work here.
@@ -22,6 +23,7 @@ impl<W> Default for LogWriter<W> {
         Self {
             log_file: None,
             prefixed_writer: None,
+            sinks: Vec::new(),
         }
     }
 }
@@ -46,23 +48,36 @@ impl<W: Write> LogWriter<W> {
     pub fn with_prefixed_writer(&mut self, prefixed_writer: PrefixedWriter<W>) {
         self.prefixed_writer = Some(prefixed_writer);
     }
+
+    /// Adds a writer that receives the raw logs in addition to the log file
+    /// and prefixed writer.
+    pub fn with_sink(&mut self, sink: impl Write + Send + 'static) {
+        self.sinks.push(Box::new(sink));
+    }
 }
 
 impl<W: Write> Write for LogWriter<W> {
     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
-        match (&mut self.log_file, &mut self.prefixed_writer) {
+        let written = match (&mut self.log_file, &mut self.prefixed_writer) {
             (Some(log_file), Some(prefixed_writer)) => {
                 let _ = prefixed_writer.write(buf)?;
-                log_file.write(buf)
+                log_file.write(buf)?
             }
-            (Some(log_file), None) => log_file.write(buf),
-            (None, Some(prefixed_writer)) => prefixed_writer.write(buf),
+            (Some(log_file), None) => log_file.write(buf)?,
+            (None, Some(prefixed_writer)) => prefixed_writer.write(buf)?,
+            (None, None) if !self.sinks.is_empty() => buf.len(),
             (None, None) => {
                 // Should this be an error or even a panic?
                 debug!("no log file or prefixed writer");
-                Ok(0)
+                return Ok(0);
             }
+        };
+        // Sinks receive exactly the bytes that were accepted, so they stay in
+        // sync with the log file when a write is partial
+        for sink in &mut self.sinks {
+            sink.write_all(&buf[..written])?;
         }
+        Ok(written)
     }
 
     fn flush(&mut self) -> std::io::Result<()> {
@@ -72,6 +87,9 @@ impl<W: Write> Write for LogWriter<W> {
         if let Some(prefixed_writer) = &mut self.prefixed_writer {
             prefixed_writer.flush()?;
         }
+        for sink in &mut self.sinks {
+            sink.flush()?;
+        }
 
         Ok(())
     }
@@ -170,6 +188,24 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_log_writer_sinks() -> Result<()> {
+        let dir = tempdir()?;
+        let sink_path = AbsoluteSystemPathBuf::try_from(dir.path().join("sink.txt"))?;
+        let mut log_writer = LogWriter::<Vec<u8>>::default();
+
+        log_writer.with_sink(sink_path.create()?);
+
+        writeln!(log_writer, "one fish")?;
+        writeln!(log_writer, "two fish")?;
+
+        log_writer.flush()?;
+
+        assert_eq!(sink_path.read_to_string()?, "one fish\ntwo fish\n");
+
+        Ok(())
+    }
+
     #[test]
     fn test_replay_logs() -> Result<()> {
         let ui = UI::new(false);
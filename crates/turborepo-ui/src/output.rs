@@ -2,17 +2,59 @@ use std::{
     borrow::Cow,
     io::{self, Write},
     sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant},
 };
 
 /// OutputSink represent a sink for outputs that can be written to from multiple
-/// threads through the use of Loggers.
+/// threads through the use of Loggers. Clones of a sink share its writers.
 pub struct OutputSink<W> {
     writers: Arc<Mutex<SinkWriters<W>>>,
 }
 
+impl<W> Clone for OutputSink<W> {
+    fn clone(&self) -> Self {
+        Self {
+            writers: self.writers.clone(),
+        }
+    }
+}
+
 struct SinkWriters<W> {
     out: W,
     err: W,
+    last_write: Instant,
+    status: Option<String>,
+}
+
+// Moves the cursor to the start of the line and erases it
+const CLEAR_LINE: &[u8] = b"\r\x1b[2K";
+
+impl<W: Write> SinkWriters<W> {
+    // Output is written above the status line, so the status line is erased
+    // before writing and redrawn after
+    fn write_around_status(
+        &mut self,
+        write: impl FnOnce(&mut Self) -> io::Result<()>,
+    ) -> io::Result<()> {
+        if self.status.is_some() {
+            self.out.write_all(CLEAR_LINE)?;
+            self.out.flush()?;
+        }
+        write(self)?;
+        self.last_write = Instant::now();
+        self.draw_status()
+    }
+
+    fn draw_status(&mut self) -> io::Result<()> {
+        let Some(status) = &self.status else {
+            return Ok(());
+        };
+        // Anything written to err has to be visible before the status is drawn
+        // below it
+        self.err.flush()?;
+        self.out.write_all(status.as_bytes())?;
+        self.out.flush()
+    }
 }
 
 /// OutputClient allows for multiple threads to write to the same OutputSink
@@ -67,10 +109,40 @@ impl<W: Write> OutputSink<W> {
     /// Produces a new sink with the corresponding out and err writers
     pub fn new(out: W, err: W) -> Self {
         Self {
-            writers: Arc::new(Mutex::new(SinkWriters { out, err })),
+            writers: Arc::new(Mutex::new(SinkWriters {
+                out,
+                err,
+                last_write: Instant::now(),
+                status: None,
+            })),
+        }
+    }
+
+    /// Shows `status` on a line below all output written to the sink, which
+    /// stays there until it's replaced by another status or removed with
+    /// `None`. The status must fit on a single line. Only meant for terminals.
+    pub fn set_status(&self, status: Option<String>) -> io::Result<()> {
+        let mut writers = self.writers.lock().expect("writer lock poisoned");
+        if writers.status.is_some() {
+            writers.out.write_all(CLEAR_LINE)?;
+        }
+        writers.status = status;
+        match writers.status {
+            Some(_) => writers.draw_status(),
+            None => writers.out.flush(),
         }
     }
 
+    /// The time since bytes were last written to the underlying writers, or
+    /// since the sink was created if nothing was written yet.
+    pub fn idle_time(&self) -> Duration {
+        self.writers
+            .lock()
+            .expect("writer lock poisoned")
+            .last_write
+            .elapsed()
+    }
+
     /// Produces a new client that will send all bytes that it receives to the
     /// underlying sink. Behavior of how these bytes are sent is controlled
     /// by the behavior parameter. Note that OutputClient intentionally doesn't
@@ -150,23 +222,26 @@ impl<W: Write> OutputClient<W> {
             // We hold the mutex until we write all of the bytes associated for the client
             // to ensure that the bytes aren't interspersed.
             let mut writers = writers.lock().expect("lock poisoned");
-            if let Some(prefix) = header {
-                writers.out.write_all(prefix.as_bytes())?;
-            }
-            for SinkBytes {
-                buffer,
-                destination,
-            } in buffers
-            {
-                let writer = match destination {
-                    Destination::Stdout => &mut writers.out,
-                    Destination::Stderr => &mut writers.err,
-                };
-                writer.write_all(buffer)?;
-            }
-            if let Some(suffix) = footer {
-                writers.out.write_all(suffix.as_bytes())?;
-            }
+            writers.write_around_status(|writers| {
+                if let Some(prefix) = header {
+                    writers.out.write_all(prefix.as_bytes())?;
+                }
+                for SinkBytes {
+                    buffer,
+                    destination,
+                } in buffers
+                {
+                    let writer = match destination {
+                        Destination::Stdout => &mut writers.out,
+                        Destination::Stderr => &mut writers.err,
+                    };
+                    writer.write_all(buffer)?;
+                }
+                if let Some(suffix) = footer {
+                    writers.out.write_all(suffix.as_bytes())?;
+                }
+                Ok(())
+            })?;
         }
 
         Ok(buffers.map(|buffers| {
@@ -210,11 +285,13 @@ impl<W: Write> OutputClient<W> {
             destination,
         } = bytes;
         let mut writers = self.writers.lock().expect("writer lock poisoned");
-        let writer = match destination {
-            Destination::Stdout => &mut writers.out,
-            Destination::Stderr => &mut writers.err,
-        };
-        writer.write_all(&line)
+        writers.write_around_status(|writers| {
+            let writer = match destination {
+                Destination::Stdout => &mut writers.out,
+                Destination::Stderr => &mut writers.err,
+            };
+            writer.write_all(&line)
+        })
     }
 
     fn add_bytes_to_buffer(&self, bytes: SinkBytes<'static>) {
@@ -283,7 +360,8 @@ mod test {
                 );
             });
         });
-        let SinkWriters { out, err } = Arc::into_inner(sink.writers).unwrap().into_inner().unwrap();
+        let SinkWriters { out, err, .. } =
+            Arc::into_inner(sink.writers).unwrap().into_inner().unwrap();
         let out = String::from_utf8(out).unwrap();
         let err = String::from_utf8(err).unwrap();
         for line in out.lines() {
@@ -294,6 +372,23 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_idle_time() -> io::Result<()> {
+        let sink = OutputSink::new(Vec::new(), Vec::new());
+        let logger = sink.logger(OutputClientBehavior::Grouped);
+        std::thread::sleep(Duration::from_millis(20));
+
+        writeln!(&mut logger.stdout(), "output for 1")?;
+        assert!(
+            sink.idle_time() >= Duration::from_millis(20),
+            "grouped output isn't written until the logger finishes"
+        );
+        logger.finish(false)?;
+        assert!(sink.idle_time() < Duration::from_millis(20));
+
+        Ok(())
+    }
+
     #[test]
     fn test_pass_thru() -> io::Result<()> {
         let sink = OutputSink::new(Vec::new(), Vec::new());
@@ -370,7 +465,8 @@ mod test {
         assert_eq!(group1_logs, b"output for 1\n");
         assert_eq!(group2_logs, b"output for 2\nwarning for 2\n");
 
-        let SinkWriters { out, err } = Arc::into_inner(sink.writers).unwrap().into_inner().unwrap();
+        let SinkWriters { out, err, .. } =
+            Arc::into_inner(sink.writers).unwrap().into_inner().unwrap();
         assert_eq!(out, b"output for 1\noutput for 2\n");
         assert_eq!(err, b"warning for 2\n");
 
@@ -446,6 +542,38 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_status() -> io::Result<()> {
+        let sink = OutputSink::new(Vec::new(), Vec::new());
+        let logger = sink.logger(OutputClientBehavior::Passthrough);
+
+        writeln!(&mut logger.stdout(), "before")?;
+        sink.set_status(Some("1 running".into()))?;
+        writeln!(&mut logger.stdout(), "during")?;
+        sink.set_status(Some("2 running".into()))?;
+        sink.set_status(None)?;
+        writeln!(&mut logger.stdout(), "after")?;
+
+        let SinkWriters { out, .. } = Arc::into_inner(sink.writers).unwrap().into_inner().unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "before\n1 running\r\x1b[2Kduring\n1 running\r\x1b[2K2 running\r\x1b[2Kafter\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_status_does_not_count_as_output() -> io::Result<()> {
+        let sink = OutputSink::new(Vec::new(), Vec::new());
+        std::thread::sleep(Duration::from_millis(20));
+
+        sink.set_status(Some("1 running".into()))?;
+        assert!(sink.idle_time() >= Duration::from_millis(20));
+
+        Ok(())
+    }
+
     #[test]
     fn assert_output_writer_sync() {
         // This is the bound required for a value to be held across an await
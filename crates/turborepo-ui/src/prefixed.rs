@@ -6,7 +6,10 @@ use std::{
 use console::{Style, StyledObject};
 use tracing::error;
 
-use crate::UI;
+use crate::{
+    json::{self, JsonLineWriter},
+    UI,
+};
 
 /// Writes messages with different prefixes, depending on log level. Note that
 /// this does output the prefix when message is empty, unlike the Go
@@ -20,6 +23,8 @@ pub struct PrefixedUI<W> {
     out: W,
     err: W,
     default_prefix: StyledObject<String>,
+    // The task the messages belong to, for the JSON log format
+    task: Option<String>,
 }
 
 impl<W: Write> PrefixedUI<W> {
@@ -32,9 +37,15 @@ impl<W: Write> PrefixedUI<W> {
             warn_prefix: None,
             error_prefix: None,
             default_prefix: Style::new().apply_to(String::new()),
+            task: None,
         }
     }
 
+    pub fn with_task(mut self, task: impl Display) -> Self {
+        self.task = Some(task.to_string());
+        self
+    }
+
     pub fn with_output_prefix(mut self, output_prefix: StyledObject<String>) -> Self {
         self.output_prefix = Some(self.ui.apply(output_prefix));
         self
@@ -75,10 +86,22 @@ impl<W: Write> PrefixedUI<W> {
             Command::Warn | Command::Error => &mut self.err,
         };
 
+        let result = match json::is_enabled() {
+            true => {
+                let level = match command {
+                    Command::Output => "INFO",
+                    Command::Warn => "WARN",
+                    Command::Error => "ERROR",
+                };
+                let line = json::format_message(level, self.task.as_deref(), &message.to_string());
+                writeln!(writer, "{line}")
+            }
+            false => writeln!(writer, "{}{}", prefix, message),
+        };
         // There's no reason to propagate this error
         // because we don't want our entire program to crash
         // due to a log failure.
-        if let Err(err) = writeln!(writer, "{}{}", prefix, message) {
+        if let Err(err) = result {
             error!("cannot write to logs: {:?}", err);
         }
     }
@@ -86,14 +109,12 @@ impl<W: Write> PrefixedUI<W> {
     /// Construct a PrefixedWriter which will behave the same as `output`, but
     /// without the requirement that messages be valid UTF-8
     pub(crate) fn output_prefixed_writer(&mut self) -> PrefixedWriter<&mut W> {
-        PrefixedWriter {
-            prefix: self
-                .output_prefix
-                .as_ref()
-                .map(|prefix| prefix.to_string())
-                .unwrap_or_default(),
-            writer: &mut self.out,
-        }
+        let prefix = self
+            .output_prefix
+            .as_ref()
+            .map(|prefix| prefix.to_string())
+            .unwrap_or_default();
+        PrefixedWriter::with_prefix(prefix, &mut self.out, self.task.clone())
     }
 }
 
@@ -105,10 +126,16 @@ enum Command {
     Error,
 }
 
-/// Wraps a writer with a prefix before the actual message.
+/// Wraps a writer with a prefix before the actual message. With the JSON log
+/// format, lines are written as JSON objects instead.
 pub struct PrefixedWriter<W> {
     prefix: String,
-    writer: W,
+    writer: Output<W>,
+}
+
+enum Output<W> {
+    Text(W),
+    Json(JsonLineWriter<W>),
 }
 
 impl<W> Debug for PrefixedWriter<W> {
@@ -122,12 +149,32 @@ impl<W> Debug for PrefixedWriter<W> {
 impl<W: Write> PrefixedWriter<W> {
     pub fn new(ui: UI, prefix: StyledObject<impl Display>, writer: W) -> Self {
         let prefix = ui.apply(prefix).to_string();
+        Self::with_prefix(prefix, writer, None)
+    }
+
+    fn with_prefix(prefix: String, writer: W, task: Option<String>) -> Self {
+        let writer = match json::is_enabled() {
+            true => Output::Json(JsonLineWriter::new(writer, task)),
+            false => Output::Text(writer),
+        };
         Self { prefix, writer }
     }
+
+    /// Sets the task that the output belongs to, for the JSON log format.
+    pub fn with_task(mut self, task: impl Display) -> Self {
+        if let Output::Json(writer) = &mut self.writer {
+            writer.task = Some(task.to_string());
+        }
+        self
+    }
 }
 
 impl<W: Write> Write for PrefixedWriter<W> {
     fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let writer = match &mut self.writer {
+            Output::Text(writer) => writer,
+            Output::Json(writer) => return writer.write(buf),
+        };
         let mut is_first = true;
         for chunk in buf.split_inclusive(|c| *c == b'\r') {
             // Before we write the chunk we write the prefix as either:
@@ -137,9 +184,9 @@ impl<W: Write> Write for PrefixedWriter<W> {
             //   the line
             // or if the last chunk is just a newline we can skip rewriting the prefix
             if is_first || chunk != b"\n" {
-                self.writer.write_all(self.prefix.as_bytes())?;
+                writer.write_all(self.prefix.as_bytes())?;
             }
-            self.writer.write_all(chunk)?;
+            writer.write_all(chunk)?;
             is_first = false;
         }
         // We do end up writing more bytes than this to the underlying writer, but we
@@ -149,7 +196,10 @@ impl<W: Write> Write for PrefixedWriter<W> {
     }
 
     fn flush(&mut self) -> std::io::Result<()> {
-        self.writer.flush()
+        match &mut self.writer {
+            Output::Text(writer) => writer.flush(),
+            Output::Json(writer) => writer.flush(),
+        }
     }
 }
 
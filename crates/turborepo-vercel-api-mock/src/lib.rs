@@ -12,8 +12,9 @@ use axum::{
 use futures_util::StreamExt;
 use tokio::sync::Mutex;
 use turborepo_vercel_api::{
-    AnalyticsEvent, CachingStatus, CachingStatusResponse, Membership, Role, Space, SpaceRun,
-    SpacesResponse, Team, TeamsResponse, User, UserResponse, VerificationResponse,
+    AnalyticsEvent, ArtifactInfo, ArtifactQueryError, ArtifactQueryResult, ArtifactsQueryRequest,
+    ArtifactsQueryResponse, CachingStatus, CachingStatusResponse, Membership, Role, Space,
+    SpaceRun, SpacesResponse, Team, TeamsResponse, User, UserResponse, VerificationResponse,
 };
 
 pub const EXPECTED_TOKEN: &str = "expected_token";
@@ -39,8 +40,10 @@ pub async fn start_test_server(port: u16) -> Result<()> {
     let get_durations_ref = Arc::new(Mutex::new(HashMap::new()));
     let head_durations_ref = get_durations_ref.clone();
     let put_durations_ref = get_durations_ref.clone();
+    let query_durations_ref = get_durations_ref.clone();
     let put_tempdir_ref = Arc::new(tempfile::tempdir()?);
     let get_tempdir_ref = put_tempdir_ref.clone();
+    let query_tempdir_ref = put_tempdir_ref.clone();
 
     let get_analytics_events_ref = Arc::new(Mutex::new(Vec::new()));
     let post_analytics_events_ref = get_analytics_events_ref.clone();
@@ -214,6 +217,35 @@ pub async fn start_test_server(port: u16) -> Result<()> {
                 (StatusCode::OK, headers)
             }),
         )
+        .route(
+            "/v8/artifacts",
+            post(
+                |Json(ArtifactsQueryRequest { hashes }): Json<ArtifactsQueryRequest>| async move {
+                    let durations = query_durations_ref.lock().await;
+                    let response: ArtifactsQueryResponse = hashes
+                        .into_iter()
+                        .map(|hash| {
+                            let result = match durations.get(&hash) {
+                                Some(duration) => ArtifactQueryResult::Found(ArtifactInfo {
+                                    size: std::fs::metadata(query_tempdir_ref.path().join(&hash))
+                                        .map_or(0, |metadata| metadata.len()),
+                                    task_duration_ms: u64::from(*duration),
+                                    tag: None,
+                                }),
+                                None => ArtifactQueryResult::Error {
+                                    error: ArtifactQueryError {
+                                        message: "Artifact not found".to_string(),
+                                    },
+                                },
+                            };
+                            (hash, result)
+                        })
+                        .collect();
+
+                    Json(response)
+                },
+            ),
+        )
         .route(
             "/v8/artifacts/events",
             post(
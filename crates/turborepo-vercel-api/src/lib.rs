@@ -1,6 +1,8 @@
 //! Types for interacting with the Vercel API. Used for both
 //! the client (`turborepo-api-client`) and for the
 //! mock server (`turborepo-vercel-api-mock`)
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 use url::Url;
 pub mod telemetry;
@@ -40,6 +42,37 @@ pub struct ArtifactResponse {
     pub body: Vec<u8>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactsQueryRequest {
+    pub hashes: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactInfo {
+    pub size: u64,
+    pub task_duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArtifactQueryError {
+    pub message: String,
+}
+
+/// The result of querying a single artifact. Artifacts that don't exist are
+/// reported as an error.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ArtifactQueryResult {
+    Error { error: ArtifactQueryError },
+    Found(ArtifactInfo),
+}
+
+/// Maps each queried hash to its result
+pub type ArtifactsQueryResponse = HashMap<String, ArtifactQueryResult>;
+
 /// Membership is the relationship between the logged-in user and a particular
 /// team
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -164,7 +197,10 @@ impl AnalyticsEvent {
 mod tests {
     use test_case::test_case;
 
-    use crate::{AnalyticsEvent, CacheEvent, CacheSource};
+    use crate::{
+        AnalyticsEvent, ArtifactInfo, ArtifactQueryResult, ArtifactsQueryResponse, CacheEvent,
+        CacheSource,
+    };
 
     #[test_case(
       AnalyticsEvent {
@@ -200,4 +236,27 @@ mod tests {
         let json = serde_json::to_string(&event).unwrap();
         insta::assert_json_snapshot!(name, json);
     }
+
+    #[test]
+    fn test_deserialize_artifacts_query_response() {
+        let response: ArtifactsQueryResponse = serde_json::from_str(
+            r#"{
+                "found": { "size": 10, "taskDurationMs": 200, "tag": "abc" },
+                "missing": { "error": { "message": "Artifact not found" } }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            response["found"],
+            ArtifactQueryResult::Found(ArtifactInfo {
+                size: 10,
+                task_duration_ms: 200,
+                tag: Some("abc".to_string()),
+            })
+        );
+        assert!(matches!(
+            response["missing"],
+            ArtifactQueryResult::Error { .. }
+        ));
+    }
 }
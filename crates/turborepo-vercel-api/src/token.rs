@@ -26,3 +26,12 @@ pub struct Scope {
     #[serde(rename = "teamId")]
     pub team_id: Option<String>,
 }
+
+/// A short-lived token issued in exchange for a CI provider's OIDC token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OidcTokenResponse {
+    pub token: String,
+    pub team_id: Option<String>,
+    pub expires_at: Option<u128>,
+}
@@ -97,12 +97,26 @@ export interface RootSchema extends BaseSchema {
    * A priority-ordered (most-significant to least-significant) array of project-anchored
    * Unix-style paths to `.env` files to include in the global hash.
    *
+   * The variables they define are loaded into the environment of every task,
+   * unless the environment or the task's own `dotEnv` files already define
+   * them. Earlier files take precedence over later ones.
+   *
    * Documentation: https://turbo.build/repo/docs/reference/configuration#globalDotEnv
    *
    * @defaultValue null
    */
   globalDotEnv?: null | Array<AnchoredUnixPath>;
 
+  /**
+   * Project-anchored Unix-style paths to files that pin the versions of tools,
+   * e.g. `.nvmrc`. The ones that exist are included in the global hash.
+   *
+   * Documentation: https://turbo.build/repo/docs/reference/configuration#globalToolVersionFiles
+   *
+   * @defaultValue [".nvmrc", ".node-version", ".tool-versions", "rust-toolchain.toml"]
+   */
+  globalToolVersionFiles?: Array<AnchoredUnixPath>;
+
   /**
    * Configuration options that control how turbo interfaces with the remote cache.
    *
@@ -173,6 +187,10 @@ export interface Pipeline {
    * A priority-ordered (most-significant to least-significant) array of workspace-anchored
    * Unix-style paths to `.env` files to include in the task hash.
    *
+   * The paths are relative to the workspace. The variables they define are
+   * loaded into the task's environment, unless the environment already defines
+   * them. Earlier files take precedence over later ones.
+   *
    * Documentation: https://turbo.build/repo/docs/reference/configuration#dotEnv
    *
    * @defaultValue null
@@ -249,9 +267,189 @@ export interface Pipeline {
    * @defaultValue false
    */
   persistent?: boolean;
+
+  /**
+   * Whether to run the task hooked up to a pseudo-terminal, so tools detect a
+   * TTY and emit colors and progress. Turbo resizes the pseudo-terminal with
+   * its own terminal. When the output of tasks is interleaved, i.e. with
+   * `--log-order=stream`, it removes control sequences, like cursor
+   * movements, that would garble the prefixed output.
+   *
+   * By default a pseudo-terminal is used when turbo itself runs in a terminal
+   * on a platform other than Windows.
+   */
+  pty?: boolean;
+
+  /**
+   * Whether to run the task's script through the package manager and a shell.
+   *
+   * When false, scripts that only consist of a command and its arguments, like
+   * `tsc --build`, are executed directly with the `node_modules/.bin`
+   * directories on the `PATH`. This avoids shell quoting issues and the
+   * overhead of spawning the package manager for every task. Scripts using
+   * shell features, like pipes, `&&` or variables, and scripts with `pre` or
+   * `post` scripts still run through the package manager. The package
+   * manager's `npm_*` environment variables are not set for directly executed
+   * scripts.
+   *
+   * @defaultValue true
+   */
+  shell?: boolean;
+
+  /**
+   * Limits on the resources that the task's processes may use, so a single
+   * runaway task can't starve the tasks running in parallel.
+   *
+   * Limits are enforced with cgroups v2 on Linux, which requires turbo to be
+   * able to manage its own cgroup. Otherwise only the memory limit of the
+   * task's main process is enforced. Limits are ignored on other platforms.
+   *
+   * @defaultValue undefined
+   */
+  resources?: ResourceLimits;
+
+  /**
+   * The scheduling priority of the task's processes. Tasks with a `"low"`
+   * priority, like type checking in the background, only get the CPU time and
+   * disk bandwidth that tasks with a `"normal"` priority, like a dev server,
+   * don't need.
+   *
+   * @defaultValue "normal"
+   */
+  priority?: "normal" | "low";
+
+  /**
+   * Where the task's command is executed. By default tasks run directly on
+   * the host.
+   *
+   * With `{ "docker": { "image": "node:20" } }` the command runs in a Docker
+   * container of the given image, with the repository mounted at the same
+   * path. Hashing, caching and logs stay on the host. The image is part of
+   * the task's hash.
+   *
+   * @defaultValue undefined
+   */
+  executor?: Executor;
+
+  /**
+   * Shell commands to run in the package directory before and after the
+   * task's command, e.g. to start and stop a database that the task needs.
+   * Hooks get the same environment as the task and their output is part of
+   * the task's logs. They don't run when the task is restored from the cache.
+   *
+   * @defaultValue undefined
+   */
+  hooks?: TaskHooks;
+
+  /**
+   * Overrides of the task for specific operating systems. An overriding
+   * `command` runs through the system shell instead of the package's script.
+   * Overrides are part of the task's hash.
+   *
+   * @defaultValue undefined
+   */
+  platforms?: Platforms;
+}
+
+export interface Platforms {
+  windows?: PlatformOverrides;
+  macos?: PlatformOverrides;
+  linux?: PlatformOverrides;
+}
+
+export interface PlatformOverrides {
+  /**
+   * The shell command to run instead of the package's script.
+   */
+  command?: string;
+
+  /**
+   * Arguments to pass to the command, in addition to the arguments passed
+   * after `--`.
+   *
+   * @defaultValue []
+   */
+  passThroughArgs?: string[];
+}
+
+export interface TaskHooks {
+  /**
+   * Runs before the task. The task fails without running if this command
+   * fails.
+   */
+  before?: string;
+
+  /**
+   * Runs after the task finished, even if it failed or turbo was
+   * interrupted. A failing command only causes a warning.
+   */
+  after?: string;
+
+  /**
+   * Whether the hooks are part of the task's hash. Hooks that only set up the
+   * environment of the task don't need to be hashed.
+   *
+   * @defaultValue false
+   */
+  hash?: boolean;
+}
+
+export interface Executor {
+  /**
+   * Runs the task in a Docker container.
+   */
+  docker?: DockerExecutor;
+}
+
+export interface DockerExecutor {
+  /**
+   * The image that the container is created from, like `"node:20"`.
+   */
+  image: string;
+}
+
+export interface ResourceLimits {
+  /**
+   * The number of CPUs worth of CPU time the task may use.
+   */
+  cpus?: number;
+
+  /**
+   * The amount of memory the task may use, like `"512MB"` or `"2GB"`. Units
+   * are binary, so `"1KB"` is 1024 bytes.
+   */
+  memory?: string;
 }
 
 export interface RemoteCache {
+  /**
+   * Set the base URL for the remote cache. Can be overridden with the
+   * `TURBO_API` environment variable.
+   *
+   * @defaultValue "https://vercel.com/api"
+   */
+  apiUrl?: string;
+
+  /**
+   * Set the URL used to log in to the remote cache. Can be overridden with the
+   * `TURBO_LOGIN` environment variable.
+   *
+   * @defaultValue "https://vercel.com"
+   */
+  loginUrl?: string;
+
+  /**
+   * The ID of the team that owns the remote cache. Can be overridden with the
+   * `TURBO_TEAMID` environment variable.
+   */
+  teamId?: string;
+
+  /**
+   * The slug of the team that owns the remote cache. Can be overridden with the
+   * `TURBO_TEAM` environment variable.
+   */
+  teamSlug?: string;
+
   /**
    * Indicates if signature verification is enabled for requests to the remote cache. When
    * `true`, Turborepo will sign every uploaded artifact using the value of the environment
@@ -262,6 +460,81 @@ export interface RemoteCache {
    */
   signature?: boolean;
 
+  /**
+   * Send a preflight `OPTIONS` request before only these kinds of requests to the remote
+   * cache: `reads` for artifact downloads and existence checks, `uploads` for artifact
+   * uploads, and `api` for every other request. The `--preflight` flag enables all of them.
+   * Can be overridden with the `TURBO_PREFLIGHT_REQUESTS` environment variable.
+   */
+  preflightRequests?: Array<"reads" | "uploads" | "api">;
+
+  /**
+   * The timeout, in seconds, for establishing a connection to the remote cache.
+   * Can be overridden with the `TURBO_REMOTE_CACHE_CONNECT_TIMEOUT` environment variable.
+   */
+  connectTimeout?: number;
+
+  /**
+   * The timeout, in seconds, for uploading an artifact to the remote cache.
+   * Can be overridden with the `TURBO_REMOTE_CACHE_UPLOAD_TIMEOUT` environment variable.
+   *
+   * @defaultValue 60
+   */
+  uploadTimeout?: number;
+
+  /**
+   * The number of times a request to the remote cache is attempted before giving up.
+   * Can be overridden with the `TURBO_REMOTE_CACHE_RETRY_ATTEMPTS` environment variable.
+   *
+   * @defaultValue 2
+   */
+  retryAttempts?: number;
+
+  /**
+   * The delay, in seconds, before the first retry of a failed request to the remote
+   * cache. The delay doubles after every following retry.
+   *
+   * @defaultValue 2
+   */
+  retryBackoff?: number;
+
+  /**
+   * Randomizes the delay between retries so that many clients don't retry in lockstep.
+   *
+   * @defaultValue true
+   */
+  retryJitter?: boolean;
+
+  /**
+   * Stop using the remote cache for the rest of the run once it starts rate limiting
+   * requests, instead of retrying and failing to upload artifacts.
+   *
+   * @defaultValue false
+   */
+  fallbackOnRateLimit?: boolean;
+
+  /**
+   * Path to a PEM encoded client certificate to present to the remote cache, for servers
+   * that require mutual TLS. Relative paths are resolved from the repository root.
+   * Can be overridden with the `TURBO_REMOTE_CACHE_CLIENT_CERT` environment variable.
+   */
+  clientCert?: string;
+
+  /**
+   * Path to the PEM encoded private key for `clientCert`. Can be omitted if the key is
+   * included in the certificate file.
+   * Can be overridden with the `TURBO_REMOTE_CACHE_CLIENT_KEY` environment variable.
+   */
+  clientKey?: string;
+
+  /**
+   * Append a JSON line for every artifact uploaded to or downloaded from the remote cache
+   * to this file, recording who, what, when, the hash, size and URL. Relative paths are
+   * resolved from the repository root.
+   * Can be overridden with the `TURBO_REMOTE_CACHE_AUDIT_LOG` environment variable.
+   */
+  auditLog?: string;
+
   /**
    * Indicates if the remote cache is enabled. When `false`, Turborepo will disable
    * all remote cache operations, even if the repo has a valid token. If true, remote caching
@@ -6,9 +6,11 @@ Add turbo.json with unnecessary package task syntax to a package
 
 Run build with package task in non-root turbo.json
   $ ${TURBO} build
+  TURBO_TASK_007
+  
     x invalid turbo json
   
-  Error: unnecessary_package_task_syntax (https://turbo.build/messages/unnecessary-package-task-syntax)
+  Error: TURBO_CONFIG_015 (https://turbo.build/messages/unnecessary-package-task-syntax)
   
     x "my-app#build". Use "build" instead
       ,-\[apps[\\/]my-app[\\/]turbo.json:7:1\] (re)
@@ -34,7 +36,7 @@ Use our custom turbo config with an invalid env var
 
 Run build with invalid env var
   $ ${TURBO} build
-  invalid_env_prefix (https://turbo.build/messages/invalid-env-prefix)
+  TURBO_CONFIG_031 (https://turbo.build/messages/invalid-env-prefix)
   
     x Environment variables should not be prefixed with "$"
      ,-[turbo.json:6:1]
@@ -51,7 +53,7 @@ Run build with invalid env var
 
 Run in single package mode even though we have a task with package syntax
   $ ${TURBO} build --single-package
-  package_task_in_single_package_mode (https://turbo.build/messages/package-task-in-single-package-mode)
+  TURBO_CONFIG_013 (https://turbo.build/messages/package-task-in-single-package-mode)
   
     x Package tasks (<package>#<task>) are not allowed in single-package
     | repositories: found //#something
@@ -72,11 +74,11 @@ Use our custom turbo config with syntax errors
 
 Run build with syntax errors in turbo.json
   $ ${TURBO} build
-  turbo_json_parse_error
+  TURBO_CONFIG_032
   
     x failed to parse turbo json
   
-  Error: turbo_json_parse_error
+  Error: TURBO_CONFIG_032
   
     x Expected a property but instead found ','.
      ,-[1:1]
@@ -85,7 +87,7 @@ Run build with syntax errors in turbo.json
      :                                                ^
    3 |   "globalDependencies": ["foo.txt"],
      `----
-  Error: turbo_json_parse_error
+  Error: TURBO_CONFIG_032
   
     x expected `,` but instead found `42`
       ,-[11:1]
@@ -94,7 +96,7 @@ Run build with syntax errors in turbo.json
       :                                              ^^
    13 |       "dotEnv": [".env.local"
       `----
-  Error: turbo_json_parse_error
+  Error: TURBO_CONFIG_032
   
     x expected `,` but instead found `}`
       ,-[13:1]
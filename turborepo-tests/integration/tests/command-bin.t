@@ -6,3 +6,7 @@ Setup
   $ grep --quiet "No local turbo binary found at" out.log
   $ grep --quiet "Running command as global turbo" out.log
   $ grep --quiet -E ".*[\/\\]target[\/\\]debug[\/\\]turbo(\.exe)?$" out.log
+
+With --json, the path is printed as JSON
+  $ ${TURBO} bin --json | jq -r .path > path.log
+  $ grep --quiet -E ".*[\/\\]target[\/\\]debug[\/\\]turbo(\.exe)?$" path.log
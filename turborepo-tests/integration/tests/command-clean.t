@@ -0,0 +1,34 @@
+Setup
+  $ . ${TESTDIR}/../../helpers/setup_integration_test.sh
+
+Run a build and write the outputs of my-app
+  $ ${TURBO} run build --output-logs=none > /dev/null
+  $ touch apps/my-app/banana.txt apps/my-app/apple.json
+
+A dry run lists what would be removed
+  $ ${TURBO} clean --dry-run > OUTPUT
+  $ grep "my-app" OUTPUT
+  Would remove apps(\/|\\)my-app(\/|\\)\.turbo \(1 file\) (re)
+  Would remove apps(\/|\\)my-app(\/|\\)apple\.json \(1 file\) (re)
+  Would remove apps(\/|\\)my-app(\/|\\)banana\.txt \(1 file\) (re)
+  $ grep --quiet -E "Would remove node_modules(\/|\\\\)\.cache(\/|\\\\)turbo \([0-9]+ files\)" OUTPUT
+  $ test -f apps/my-app/banana.txt
+
+With a filter, only the outputs of the matching packages are removed
+  $ ${TURBO} clean --filter=util
+  Removed packages(\/|\\)util(\/|\\)\.turbo \(1 file\) (re)
+  $ test -d node_modules/.cache/turbo
+
+With --json, the result is printed as JSON
+  $ ${TURBO} clean --filter=my-app --dry-run --json | jq -c '.dryRun, [.removed[].files]'
+  true
+  [1,1,1]
+
+Without a filter, the cache is removed too
+  $ ${TURBO} clean > /dev/null
+  $ test -e apps/my-app/banana.txt
+  [1]
+  $ test -e node_modules/.cache/turbo
+  [1]
+  $ ${TURBO} clean
+  Nothing to clean
@@ -0,0 +1,19 @@
+Setup
+  $ . ${TESTDIR}/../../helpers/setup_integration_test.sh
+
+Checks pass in a healthy repository
+  $ ${TURBO} doctor --offline > OUTPUT
+  $ grep -E "^. (git|package manager|lockfile|remote cache|clock) " OUTPUT
+  \xe2\x9c\x93 git              git version .* (re)
+  \xe2\x9c\x93 package manager  npm (esc)
+  \xe2\x9c\x93 lockfile         package-lock.json parsed (esc)
+  - remote cache     skipped with --offline
+  - clock            skipped with --offline
+
+A broken lockfile fails the check and says how to fix it
+  $ echo "not json" > package-lock.json
+  $ ${TURBO} doctor --offline > OUTPUT
+  [1]
+  $ grep -A1 "lockfile" OUTPUT
+  x lockfile         unable to parse package-lock.json: .* (re)
+    \xe2\x86\x92 run `npm install` to regenerate it, without it turbo can't tell which dependencies changed (esc)
@@ -0,0 +1,40 @@
+Setup
+  $ . ${TESTDIR}/../../helpers/setup_integration_test.sh
+
+Refuses to overwrite an existing turbo.json
+  $ ${TURBO} init > /dev/null 2>&1
+  [1]
+
+Generates a turbo.json from the scripts of the packages
+  $ rm turbo.json
+  $ ${TURBO} init
+  Detected \w+ with 3 packages (re)
+   - Added build (depends on ^build; outputs dist/**)
+   - Added maybefails
+  
+  Wrote turbo.json. Review the inferred tasks, then run them with turbo run <task>
+  $ cat turbo.json
+  {
+    "$schema": "https://turbo.build/schema.json",
+    "pipeline": {
+      "build": {
+        "dependsOn": [
+          "^build"
+        ],
+        "outputs": [
+          "dist/**"
+        ]
+      },
+      "maybefails": {}
+    }
+  }
+
+The generated turbo.json can be used right away
+  $ ${TURBO} run build --dry=json > /dev/null
+
+Overwrites turbo.json with --force
+  $ ${TURBO} init --force > /dev/null
+  $ cat .gitignore
+  node_modules/
+  .turbo
+  .npmrc
@@ -6,3 +6,11 @@ Link Test Run
   $ ${TURBO} link --__test-run
   Link test run successful
 
+
+Link Offline
+  $ ${TURBO} link --offline
+  TURBO_CLI_022
+  
+    x `turbo link` requires network access and can't be used with --offline
+  
+  [1]
@@ -4,3 +4,11 @@ Setup
 Login Test Run
   $ ${TURBO} login --__test-run
   Login test run successful
+
+Login Offline
+  $ ${TURBO} login --offline
+  TURBO_CLI_022
+  
+    x `turbo login` requires network access and can't be used with --offline
+  
+  [1]
@@ -14,6 +14,10 @@ Run status (with first run message)
   
   Status: Enabled
   
+    usage        Enabled
+    errors       Enabled
+    performance  Enabled
+  
   Turborepo telemetry is completely anonymous. Thank you for participating!
   Learn more: https://turbo.build/repo/docs/telemetry
 
@@ -22,6 +26,10 @@ Run without command
   
   Status: Enabled
   
+    usage        Enabled
+    errors       Enabled
+    performance  Enabled
+  
   Turborepo telemetry is completely anonymous. Thank you for participating!
   Learn more: https://turbo.build/repo/docs/telemetry
 
@@ -40,7 +48,35 @@ Enable
   
   Status: Enabled
   
+    usage        Enabled
+    errors       Enabled
+    performance  Enabled
+  
   Turborepo telemetry is completely anonymous. Thank you for participating!
   Learn more: https://turbo.build/repo/docs/telemetry
 
+Disable a category
+  $ ${TURBO} telemetry disable usage
+  Success!
+  
+  Status: Enabled
+  
+    usage        Disabled
+    errors       Enabled
+    performance  Enabled
+  
+  Turborepo telemetry is completely anonymous. Thank you for participating!
+  Learn more: https://turbo.build/repo/docs/telemetry
 
+Enable a category
+  $ ${TURBO} telemetry enable usage
+  Success!
+  
+  Status: Enabled
+  
+    usage        Enabled
+    errors       Enabled
+    performance  Enabled
+  
+  Turborepo telemetry is completely anonymous. Thank you for participating!
+  Learn more: https://turbo.build/repo/docs/telemetry
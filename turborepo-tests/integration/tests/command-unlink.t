@@ -4,3 +4,8 @@ Setup
 Unlink Test Run
   $ ${TURBO} unlink --__test-run
   Unlink test run successful
+
+Commands without JSON output reject --json
+  $ ${TURBO} unlink --json
+  {"error":{"code":"TURBO_CLI_027","message":"`turbo unlink` doesn't support --json"}}
+  [1]
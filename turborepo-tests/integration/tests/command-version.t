@@ -8,6 +8,8 @@ Test version matches that of version.txt
 
 TODO: resolve ambiguity
   $ ${TURBO} -v
+  TURBO_CLI_008
+  
     x No command specified
   
   [1]
@@ -12,6 +12,8 @@ Run test run
     "signature": null,
     "preflight": null,
     "timeout": null,
+    "connectTimeout": null,
+    "uploadTimeout": null,
     "enabled": null,
     "spacesId": null
   }
@@ -44,12 +46,18 @@ Run test run with remote cache timeout from both env and flag (flag should take
   $ TURBO_REMOTE_CACHE_TIMEOUT=123 ${TURBO} info --json --remote-cache-timeout 456 | jq .config.timeout
   456
 
+Run test run with remote cache connect and upload timeouts from both env and flag (flag should take precedence)
+  $ TURBO_REMOTE_CACHE_CONNECT_TIMEOUT=5 ${TURBO} info --json --remote-cache-connect-timeout 10 | jq .config.connectTimeout
+  10
+  $ TURBO_REMOTE_CACHE_UPLOAD_TIMEOUT=300 ${TURBO} info --json | jq .config.uploadTimeout
+  300
+
 Use our custom turbo config with an invalid env var
   $ . ${TESTDIR}/../../helpers/replace_turbo_json.sh $(pwd) "invalid-env-var.json"
 
 Run build with invalid env var
   $ ${TURBO} build
-  invalid_env_prefix (https://turbo.build/messages/invalid-env-prefix)
+  TURBO_CONFIG_031 (https://turbo.build/messages/invalid-env-prefix)
   
     x Environment variables should not be prefixed with "$"
      ,-[turbo.json:6:1]
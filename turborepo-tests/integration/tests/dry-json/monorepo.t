@@ -179,8 +179,20 @@ Run again with NODE_ENV set and see the value in the summary. --filter=util work
 
 Tasks that don't exist throw an error
   $ ${TURBO} run doesnotexist --dry=json
+  TURBO_TASK_002
+  
     x missing tasks in project
   
-  Error:   x could not find task `doesnotexist` in project
+  Error: TURBO_TASK_001
+  
+    x could not find task `doesnotexist` in project
   
   [1]
+
+Tool version files are included in the global hash
+  $ echo "20" > .nvmrc
+  $ ${TURBO} run build --dry=json | jq .globalCacheInputs.files
+  {
+    ".nvmrc": "209e3ef4b6247ce746048d5711befda46206d235",
+    "foo.txt": "eebae5f3ca7b5831e429e947b7d61edd0de69236"
+  }
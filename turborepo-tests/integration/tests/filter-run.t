@@ -41,3 +41,7 @@ Setup
     Time:\s*[\.0-9]+m?s  (re)
   
 
+
+# A filter with a typo in a package name suggests the closest packages
+  $ ${TURBO} run build --filter=my-ap > OUTPUT 2>&1
+  $ grep --quiet -E 'No package matched the filter "my-ap", did you mean `my-app` \(apps.my-app\)\?' OUTPUT
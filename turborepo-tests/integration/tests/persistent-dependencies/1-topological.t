@@ -13,6 +13,8 @@
 // app-a#dev
 // └── pkg-a#dev
   $ ${TURBO} run dev
+  TURBO_RUN_001
+  
     x invalid persistent task configuration
   
   Error:   x "pkg-a#dev" is a persistent task, "app-a#dev" cannot depend on it
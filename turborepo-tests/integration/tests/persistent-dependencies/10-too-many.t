@@ -2,6 +2,8 @@
   $ . ${TESTDIR}/../../../helpers/setup_integration_test.sh persistent_dependencies/10-too-many
 
   $ ${TURBO} run build --concurrency=1
+  TURBO_RUN_001
+  
     x invalid persistent task configuration
   
   Error:   x You have 2 persistent tasks but `turbo` is configured for concurrency of
@@ -10,6 +12,8 @@
   [1]
 
   $ ${TURBO} run build --concurrency=2
+  TURBO_RUN_001
+  
     x invalid persistent task configuration
   
   Error:   x You have 2 persistent tasks but `turbo` is configured for concurrency of
@@ -13,6 +13,8 @@
 // └── app-a#dev
 //
   $ ${TURBO} run build
+  TURBO_RUN_001
+  
     x invalid persistent task configuration
   
   Error:   x "app-a#dev" is a persistent task, "app-a#build" cannot depend on it
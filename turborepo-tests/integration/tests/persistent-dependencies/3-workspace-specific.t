@@ -17,6 +17,8 @@
 #
 # The regex match is liberal, because the build task from either workspace can throw the error
   $ ${TURBO} run build
+  TURBO_RUN_001
+  
     x invalid persistent task configuration
   
   Error:   x "pkg-a#dev" is a persistent task, "((pkg-a)|(app-a))#build" cannot depend on it (re)
@@ -13,6 +13,8 @@
 # └── //#dev
 #
   $ ${TURBO} run build
+  TURBO_RUN_001
+  
     x invalid persistent task configuration
   
   Error:   x "//#dev" is a persistent task, "app-a#build" cannot depend on it
@@ -20,6 +20,8 @@
 # error message should say. Leaving as-is so we don't have to implement special casing logic to handle
 # this case.
   $ ${TURBO} run dev
+  TURBO_RUN_001
+  
     x invalid persistent task configuration
   
   Error:   x "pkg-b#dev" is a persistent task, "pkg-a#dev" cannot depend on it
@@ -19,6 +19,8 @@
 // 		 └── workspace-c#build
 // 		 		 └── workspace-z#dev	// this one is persistent
   $ ${TURBO} run build
+  TURBO_RUN_001
+  
     x invalid persistent task configuration
   
   Error:   x "pkg-z#dev" is a persistent task, "pkg-b#build" cannot depend on it
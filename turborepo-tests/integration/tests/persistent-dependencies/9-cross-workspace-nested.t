@@ -12,6 +12,8 @@
 // 		 		 └── workspace-z#dev // this one is persistent
 //
   $ ${TURBO} run build
+  TURBO_RUN_001
+  
     x invalid persistent task configuration
   
   Error:   x "app-z#dev" is a persistent task, "app-c#build" cannot depend on it
@@ -0,0 +1,11 @@
+Setup
+  $ . ${TESTDIR}/../../../helpers/setup_integration_test.sh monorepo_with_root_dep pnpm@7.25.1
+  $ echo "node_modules" > .gitignore
+  $ git add .gitignore && git commit --quiet -m "ignore node_modules"
+
+The git state is recorded before the output makes the repository dirty
+  $ ${TURBO} prune web --emit-metadata > /dev/null
+  $ jq -c '{scope, dirty}' out/.turbo-prune-meta.json
+  {"scope":["web"],"dirty":false}
+  $ jq -r .sha out/.turbo-prune-meta.json | grep -c "$(git rev-parse HEAD)"
+  1
@@ -0,0 +1,9 @@
+Setup
+  $ . ${TESTDIR}/../../../helpers/setup_integration_test.sh monorepo_with_root_dep npm@10.5.0
+
+Make sure npm-shrinkwrap.json is treated as the npm lockfile
+  $ mv package-lock.json npm-shrinkwrap.json
+  $ ${TURBO} prune web > /dev/null
+  $ test -f out/npm-shrinkwrap.json
+  $ test -e out/package-lock.json
+  [1]
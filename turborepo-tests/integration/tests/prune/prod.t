@@ -0,0 +1,20 @@
+Setup
+  $ . ${TESTDIR}/../../../helpers/setup.sh
+  $ . ${TESTDIR}/../../../helpers/copy_fixture.sh $(pwd) berry_resolutions ${TESTDIR}/../../fixtures
+
+Berry lockfiles can't be pruned to production dependencies
+  $ ${TURBO} prune a --prod
+  TURBO_PRUNE_014
+  
+    x --prod is not supported for berry
+  
+  [1]
+
+Nothing is written
+  $ test -d out
+  [1]
+
+With --json the error is printed as JSON, with its code
+  $ ${TURBO} prune a --prod --json
+  {"error":{"code":"TURBO_PRUNE_014","message":"--prod is not supported for berry"}}
+  [1]
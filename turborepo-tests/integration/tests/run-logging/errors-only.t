@@ -90,3 +90,11 @@ Setup
 
 
 
+# [ ] error exit
+# [x] outputMode: errors-only
+# [x] --output-logs overrides it for the matching task only
+  $ ${TURBO} run buildsuccess --output-logs=app-a#buildsuccess:full > OUTPUT
+  $ grep --quiet -E "app-a:buildsuccess: cache (hit|miss)" OUTPUT
+  $ ${TURBO} run buildsuccess --output-logs=app-a#build:full > OUTPUT
+  $ grep "app-a:buildsuccess" OUTPUT
+  [1]
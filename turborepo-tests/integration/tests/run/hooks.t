@@ -0,0 +1,15 @@
+Setup
+  $ . ${TESTDIR}/../../../helpers/setup_integration_test.sh
+  $ echo '{"pipeline": {"dev": {"cache": false, "hooks": {"before": "touch before.txt", "after": "touch after.txt"}}}}' > turbo.json
+  $ jq '.scripts.dev = "sleep 30"' apps/my-app/package.json > package.json.new && mv package.json.new apps/my-app/package.json
+  $ git commit --quiet -am "add dev task"
+
+The after hook runs when the task is interrupted
+  $ ${TURBO} run dev --filter=my-app > out.log 2>&1 &
+  $ for _ in $(seq 100); do [ -f apps/my-app/before.txt ] && break; sleep 0.1; done
+  $ sleep 1
+  $ kill -INT $!
+  $ wait $!
+  [130]
+  $ test -f apps/my-app/after.txt && echo "after hook ran"
+  after hook ran
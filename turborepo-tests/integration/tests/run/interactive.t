@@ -0,0 +1,15 @@
+Setup
+  $ . ${TESTDIR}/../../../helpers/setup_integration_test.sh
+
+# Picking tasks and packages needs a terminal
+  $ ${TURBO} run --interactive < /dev/null
+  TURBO_CLI_024
+  
+    x --interactive needs a terminal to pick tasks and packages in
+  
+  [1]
+
+# Tasks can't be passed along with --interactive
+  $ ${TURBO} run build --interactive > OUTPUT 2>&1
+  [1]
+  $ grep --quiet "cannot be used with" OUTPUT
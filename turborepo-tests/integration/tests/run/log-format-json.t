@@ -0,0 +1,20 @@
+Setup
+  $ . ${TESTDIR}/../../../helpers/setup_integration_test.sh monorepo_one_script_error
+
+Every line on stdout and stderr is a JSON object, including the output of tasks and the errors
+  $ ${TURBO} run error --log-format=json > STDOUT 2> STDERR
+  [1]
+  $ cat STDOUT STDERR | while read -r line; do echo "$line" | jq -e 'type == "object"' > /dev/null || echo "not JSON: $line"; done
+
+Lines printed by turbo don't belong to a task
+  $ jq -r 'select(.task == null) | .message' STDOUT | grep "Running"
+  \xe2\x80\xa2 Running error in 1 packages (esc)
+
+The output of tasks is printed line by line along with the task
+  $ jq -r 'select(.task == "my-app#okay") | .message' STDOUT | grep "working"
+  > echo working
+  working
+
+Failed tasks are reported as errors
+  $ cat STDOUT STDERR | jq -r 'select(.level == "ERROR") | .task'
+  my-app#error
@@ -3,28 +3,53 @@ Setup
 
 # Running non-existent tasks errors
   $ ${TURBO} run doesnotexist
+  TURBO_TASK_002
+  
     x missing tasks in project
   
-  Error:   x could not find task `doesnotexist` in project
+  Error: TURBO_TASK_001
+  
+    x could not find task `doesnotexist` in project
   
   [1]
 
 # Multiple non-existent tasks also error
   $ ${TURBO} run doesnotexist alsono
+  TURBO_TASK_002
+  
     x missing tasks in project
   
-  Error:   x could not find task `alsono` in project
-  Error:   x could not find task `doesnotexist` in project
+  Error: TURBO_TASK_001
+  
+    x could not find task `alsono` in project
+  Error: TURBO_TASK_001
+  
+    x could not find task `doesnotexist` in project
   
   [1]
 
 # One good and one bad task does not error
   $ ${TURBO} run build doesnotexist
+  TURBO_TASK_002
+  
     x missing tasks in project
   
-  Error:   x could not find task `doesnotexist` in project
+  Error: TURBO_TASK_001
   
+    x could not find task `doesnotexist` in project
+  
+  [1]
+
+# Errors are logged with their code when logging JSON
+  $ ${TURBO} run doesnotexist --log-format=json > OUTPUT 2>&1
+  [1]
+  $ grep --quiet '"code":"TURBO_TASK_002"' OUTPUT
+  $ grep --quiet '"message":"missing tasks in project"' OUTPUT
+
+# Typos suggest the closest tasks and the packages that have them
+  $ ${TURBO} run buidl > OUTPUT 2>&1
   [1]
+  $ grep --quiet 'did you mean `build` (my-app, util)?' OUTPUT
 
 # Bad command
   $ ${TURBO} run something --dry > OUTPUT 2>&1
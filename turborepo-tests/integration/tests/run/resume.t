@@ -0,0 +1,25 @@
+Setup
+  $ . ${TESTDIR}/../../../helpers/setup_integration_test.sh
+
+A failed run records the tasks that completed
+  $ ${TURBO} run build maybefails --filter=my-app --continue > /dev/null 2>&1
+  [1]
+  $ jq -r '.tasks | keys[]' .turbo/resume.json
+  my-app#build
+
+A successful run of other tasks keeps the record
+  $ ${TURBO} run build --filter=util > /dev/null 2>&1
+  $ jq -r '.tasks | keys[]' .turbo/resume.json
+  my-app#build
+
+Resuming skips the tasks that completed, even though the cache is bypassed
+  $ ${TURBO} run build --filter=my-app --resume --force | grep "my-app:build"
+  my-app:build: completed in the resumed run, skipping [0-9a-f]+ (re)
+
+The record is removed once a run succeeds
+  $ test -f .turbo/resume.json
+  [1]
+
+Without a record every task runs
+  $ ${TURBO} run build --filter=my-app --resume --force | grep "my-app:build: cache"
+  my-app:build: cache bypass, force executing [0-9a-f]+ (re)
@@ -0,0 +1,22 @@
+Setup
+  $ . ${TESTDIR}/../../../helpers/setup_integration_test.sh
+  $ jq '.scripts.build = "echo building > dist.txt && echo built"' apps/my-app/package.json > package.json.new && mv package.json.new apps/my-app/package.json
+  $ git commit --quiet -am "write an undeclared output"
+
+Warn lists the files and keeps the task successful
+  $ ${TURBO} run build --filter=my-app --undeclared-outputs=warn > out.log 2>&1
+  $ grep "outside of its outputs" out.log
+  my-app:build: WARNING wrote files outside of its outputs, which won't be cached: apps[\/\\]my-app[\/\\]dist.txt (re)
+
+Error fails the task, after showing its logs
+  $ rm apps/my-app/dist.txt
+  $ ${TURBO} run build --filter=my-app --undeclared-outputs=error --force --output-logs=errors-only > out.log 2>&1
+  [1]
+  $ grep "my-app:build: " out.log
+  my-app:build: cache miss, executing [0-9a-f]+ (re)
+  my-app:build: 
+  my-app:build: > build
+  my-app:build: > echo building > dist.txt && echo built
+  my-app:build: 
+  my-app:build: built
+  my-app:build: ERROR: wrote files outside of its outputs: apps[\/\\]my-app[\/\\]dist.txt (re)
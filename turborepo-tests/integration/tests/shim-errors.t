@@ -3,7 +3,7 @@ Setup
 
 Should error if `--cwd` flag doesn't have path passed along with it
   $ ${TURBO} foo bar --cwd
-  turbo::shim::empty_cwd
+  TURBO_CLI_002
   
     \xc3\x97 No value assigned to `--cwd` flag (esc)
      \xe2\x95\xad\xe2\x94\x80\xe2\x94\x80\xe2\x94\x80\xe2\x94\x80 (esc)
@@ -16,7 +16,7 @@ Should error if `--cwd` flag doesn't have path passed along with it
 
 Should error if multiple `--cwd` flags are passed
   $ ${TURBO} --cwd foo --cwd --bar --cwd baz --cwd qux
-  turbo::shim::multiple_cwd
+  TURBO_CLI_001
   
     \xc3\x97 cannot have multiple `--cwd` flags in command (esc)
      \xe2\x95\xad\xe2\x94\x80\xe2\x94\x80\xe2\x94\x80\xe2\x94\x80 (esc)
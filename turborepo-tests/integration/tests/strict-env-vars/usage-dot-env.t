@@ -0,0 +1,24 @@
+Setup
+  $ . ${TESTDIR}/../../../helpers/setup_integration_test.sh strict_env_vars
+
+Variables from dotEnv files are loaded into the task environment
+
+Set the env vars
+  $ export LOCAL_VAR_PT=hilocalpt
+  $ export SYSTEMROOT=hisysroot
+Set the output file with the right path separator for the OS
+  $ if [[ "$OSTYPE" == "msys" ]]; then OUTPUT="apps\\my-app\\out.txt"; else OUTPUT="apps/my-app/out.txt"; fi
+
+Write the .env files
+  $ echo "GLOBAL_VAR_DEP=fromglobal" > .env
+  $ echo "LOCAL_VAR_DEP=fromglobal" >> .env
+  $ echo "LOCAL_VAR_DEP=fromlocal" > apps/my-app/.env.local
+  $ echo "OTHER_VAR=fromlocal" >> apps/my-app/.env.local
+  $ echo "OTHER_VAR=fromenv" > apps/my-app/.env
+  $ echo "LOCAL_VAR_PT=fromenv" >> apps/my-app/.env
+
+The environment wins over the files, the task's files win over the global ones and earlier files win over later ones
+  $ ${TESTDIR}/../../../helpers/replace_turbo_json.sh $(pwd) "strict_env_vars/dot_env.json"
+  $ ${TURBO} build -vv --env-mode=loose > /dev/null 2>&1
+  $ cat "$OUTPUT"
+  globalpt: '', localpt: 'hilocalpt', globaldep: 'fromglobal', localdep: 'fromlocal', other: 'fromlocal', sysroot set: 'yes', path set: 'yes'
@@ -9,10 +9,13 @@ Test help flag
   
   Commands:
     bin         Get the path to the Turbo binary
+    clean       Remove task outputs, the local cache and .turbo state
     completion  Generate the autocompletion script for the specified shell
     daemon      Runs the Turborepo background daemon
+    doctor      Check the environment turbo runs in and suggest fixes
     generate    Generate a new app / package
     telemetry   Enable or disable anonymous telemetry
+    init        Generate a turbo.json for an existing repository
     link        Link your local directory to a Vercel organization and enable remote caching
     login       Login to your Vercel account
     logout      Logout to your Vercel account
@@ -21,29 +24,38 @@ Test help flag
     unlink      Unlink the current directory from your Vercel organization and disable Remote Caching
   
   Options:
-        --version                         
-        --skip-infer                      Skip any attempts to infer which version of Turbo the project is configured to use
-        --no-update-notifier              Disable the turbo update notification
-        --api <API>                       Override the endpoint for API calls
-        --color                           Force color usage in the terminal
-        --cpuprofile <CPU_PROFILE>        Specify a file to save a cpu profile
-        --cwd <CWD>                       The directory in which to run turbo
-        --heap <HEAP>                     Specify a file to save a pprof heap profile
-        --login <LOGIN>                   Override the login endpoint
-        --no-color                        Suppress color usage in the terminal
-        --preflight                       When enabled, turbo will precede HTTP requests with an OPTIONS request for authorization
-        --remote-cache-timeout <TIMEOUT>  Set a timeout for all HTTP requests
-        --team <TEAM>                     Set the team slug for API calls
-        --token <TOKEN>                   Set the auth token for API calls
-        --trace <TRACE>                   Specify a file to save a pprof trace
-        --verbosity <COUNT>               Verbosity level
-    -h, --help                            Print help
+        --version                                 
+        --skip-infer                              Skip any attempts to infer which version of Turbo the project is configured to use
+        --no-update-notifier                      Disable the turbo update notification
+        --api <API>                               Override the endpoint for API calls
+        --auth-profile <NAME>                     Use the credentials stored under this name in the global config
+        --color                                   Force color usage in the terminal
+        --cpuprofile <CPU_PROFILE>                Specify a file to save a cpu profile
+        --cwd <CWD>                               The directory in which to run turbo
+        --heap <HEAP>                             Specify a file to save a pprof heap profile
+        --json                                    Print the result of the command as JSON instead of text
+        --login <LOGIN>                           Override the login endpoint
+        --log-format <FORMAT>                     Format of the messages that turbo logs. Use "json" to log structured events instead. Output of tasks isn't affected [default: text] [possible values: text, json]
+        --no-color                                Suppress color usage in the terminal
+        --offline                                 Disable all network access. Commands that require the network will fail
+        --preflight                               When enabled, turbo will precede HTTP requests with an OPTIONS request for authorization
+        --preflight-requests <REQUESTS>           Only send preflight requests before these kinds of requests, a comma separated list of reads, uploads and api
+        --remote-cache-timeout <TIMEOUT>          Set a timeout for all HTTP requests other than artifact uploads
+        --remote-cache-connect-timeout <TIMEOUT>  Set a timeout for establishing HTTP connections
+        --remote-cache-upload-timeout <TIMEOUT>   Set a timeout for uploading artifacts to the remote cache
+        --team <TEAM>                             Set the team slug for API calls
+        --token <TOKEN>                           Set the auth token for API calls
+        --trace <TRACE>                           Specify a file to save a pprof trace
+        --verbosity <COUNT>                       Verbosity level
+    -h, --help                                    Print help
   
   Run Arguments:
         --cache-dir <CACHE_DIR>
             Override the filesystem cache directory
         --cache-workers <CACHE_WORKERS>
             Set the number of concurrent cache operations (default 10) [default: 10]
+        --cache-shutdown-timeout <SECONDS>
+            How long to wait for pending cache writes to finish when the run is interrupted. Writes that are still running after that are cancelled, use 0 to cancel them right away (default 30) [env: TURBO_CACHE_SHUTDOWN_TIMEOUT=]
         --concurrency <CONCURRENCY>
             Limit the concurrency of task execution. Use 1 for serial (i.e. one-at-a-time) execution
         --continue
@@ -79,7 +91,7 @@ Test help flag
         --[no-]daemon
             Force turbo to either use or not use the local daemon. If unset turbo will use the default detection logic
         --output-logs <OUTPUT_LOGS>
-            Set type of process output logging. Use "full" to show all output. Use "hash-only" to show only turbo-computed task hashes. Use "new-only" to show only new output with only hashes for cached tasks. Use "none" to hide process output. (default full) [possible values: full, none, hash-only, new-only, errors-only]
+            Set type of process output logging. Use "full" to show all output. Use "hash-only" to show only turbo-computed task hashes. Use "new-only" to show only new output with only hashes for cached tasks. Use "none" to hide process output. (default full) Prefix a mode with a task to only use it for the matching tasks, e.g. "web#build:full,*:errors-only"
         --log-order <LOG_ORDER>
             Set type of task output order. Use "stream" to show output as soon as it is available. Use "grouped" to show output when a command has finished execution. Use "auto" to let turbo decide based on its own heuristics. (default auto) [env: TURBO_LOG_ORDER=] [default: auto] [possible values: auto, stream, grouped]
         --only
@@ -96,8 +108,32 @@ Test help flag
             Treat remote cache as read only [env: TURBO_REMOTE_CACHE_READ_ONLY=] [default: false] [possible values: true, false]
         --summarize [<SUMMARIZE>]
             Generate a summary of the turbo run [env: TURBO_RUN_SUMMARY=] [possible values: true, false]
+        --report <FILE>
+            Write a self-contained HTML report of the run, with a timeline of its tasks, their cache statuses and the logs of failed tasks, to a file
+        --junit <FILE>
+            Write the results of the run to a file in the JUnit XML format, with a test case per task, for CI systems that display test results
         --log-prefix <LOG_PREFIX>
             Use "none" to remove prefixes from task logs. Use "task" to get task id prefixing. Use "auto" to let turbo decide how to prefix the logs based on the execution environment. In most cases this will be the same as "task". Note that tasks running in parallel interleave their logs, so removing prefixes can make it difficult to associate logs with tasks. Use --log-order=grouped to prevent interleaving. (default auto) [default: auto] [possible values: auto, none, task]
+        --log-sink <SINK>
+            Additionally send the raw output of every task to a sink, regardless of --log-order and --output-logs. Use "file:<dir>" to write a log file per task into a directory, "unix:<path>" to stream the output as JSON lines to a unix socket, or an http(s) URL to upload the log of each task once it finished. Can be passed multiple times
+        --attach [<TASK>]
+            Forward stdin to a persistent task, so interactive tools keep working while several tasks run. Pass a task, e.g. "web#dev", to attach to it once it starts. To switch tasks at runtime, type Ctrl-] followed by a task and press enter
+        --undeclared-outputs <MODE>
+            Check for files that tasks write outside of their declared outputs, which silently don't get cached. Use "warn" to list them, or "error" to fail the task [possible values: warn, error]
+        --heartbeat <SECONDS>
+            Print the progress of the run whenever no output was printed for this many seconds, so that CI systems don't stop runs for being inactive [env: TURBO_HEARTBEAT=]
+        --status-bar
+            Show a status line with the progress of the run below the task logs. Only shown when the output is a terminal
+        --interactive
+            Pick the tasks and packages to run from searchable lists instead of passing them as arguments
+        --ci <VENDOR>
+            Format the output for this CI system, regardless of the detected environment. The logs of each task are folded into a section. "github" also annotates errors in the output of failed tasks and writes a job summary [possible values: github, teamcity, buildkite]
+        --ignore-lockfile
+            Don't use the package manager's lockfile. Dependencies are instead hashed using the versions installed in node_modules
+        --debug-signal <SIGNAL>
+            The signal that prints the state of the run to stderr without stopping it, e.g. to debug a run that hangs. Not supported on Windows [default: quit] [possible values: quit, usr1, usr2]
+        --resume
+            Skip the tasks that completed in the last interrupted or failed run, as long as their inputs didn't change, even if they aren't cacheable
 
 
 
@@ -111,10 +147,13 @@ Test help flag
   
   Commands:
     bin         Get the path to the Turbo binary
+    clean       Remove task outputs, the local cache and .turbo state
     completion  Generate the autocompletion script for the specified shell
     daemon      Runs the Turborepo background daemon
+    doctor      Check the environment turbo runs in and suggest fixes
     generate    Generate a new app / package
     telemetry   Enable or disable anonymous telemetry
+    init        Generate a turbo.json for an existing repository
     link        Link your local directory to a Vercel organization and enable remote caching
     login       Login to your Vercel account
     logout      Logout to your Vercel account
@@ -123,29 +162,38 @@ Test help flag
     unlink      Unlink the current directory from your Vercel organization and disable Remote Caching
   
   Options:
-        --version                         
-        --skip-infer                      Skip any attempts to infer which version of Turbo the project is configured to use
-        --no-update-notifier              Disable the turbo update notification
-        --api <API>                       Override the endpoint for API calls
-        --color                           Force color usage in the terminal
-        --cpuprofile <CPU_PROFILE>        Specify a file to save a cpu profile
-        --cwd <CWD>                       The directory in which to run turbo
-        --heap <HEAP>                     Specify a file to save a pprof heap profile
-        --login <LOGIN>                   Override the login endpoint
-        --no-color                        Suppress color usage in the terminal
-        --preflight                       When enabled, turbo will precede HTTP requests with an OPTIONS request for authorization
-        --remote-cache-timeout <TIMEOUT>  Set a timeout for all HTTP requests
-        --team <TEAM>                     Set the team slug for API calls
-        --token <TOKEN>                   Set the auth token for API calls
-        --trace <TRACE>                   Specify a file to save a pprof trace
-        --verbosity <COUNT>               Verbosity level
-    -h, --help                            Print help
+        --version                                 
+        --skip-infer                              Skip any attempts to infer which version of Turbo the project is configured to use
+        --no-update-notifier                      Disable the turbo update notification
+        --api <API>                               Override the endpoint for API calls
+        --auth-profile <NAME>                     Use the credentials stored under this name in the global config
+        --color                                   Force color usage in the terminal
+        --cpuprofile <CPU_PROFILE>                Specify a file to save a cpu profile
+        --cwd <CWD>                               The directory in which to run turbo
+        --heap <HEAP>                             Specify a file to save a pprof heap profile
+        --json                                    Print the result of the command as JSON instead of text
+        --login <LOGIN>                           Override the login endpoint
+        --log-format <FORMAT>                     Format of the messages that turbo logs. Use "json" to log structured events instead. Output of tasks isn't affected [default: text] [possible values: text, json]
+        --no-color                                Suppress color usage in the terminal
+        --offline                                 Disable all network access. Commands that require the network will fail
+        --preflight                               When enabled, turbo will precede HTTP requests with an OPTIONS request for authorization
+        --preflight-requests <REQUESTS>           Only send preflight requests before these kinds of requests, a comma separated list of reads, uploads and api
+        --remote-cache-timeout <TIMEOUT>          Set a timeout for all HTTP requests other than artifact uploads
+        --remote-cache-connect-timeout <TIMEOUT>  Set a timeout for establishing HTTP connections
+        --remote-cache-upload-timeout <TIMEOUT>   Set a timeout for uploading artifacts to the remote cache
+        --team <TEAM>                             Set the team slug for API calls
+        --token <TOKEN>                           Set the auth token for API calls
+        --trace <TRACE>                           Specify a file to save a pprof trace
+        --verbosity <COUNT>                       Verbosity level
+    -h, --help                                    Print help
   
   Run Arguments:
         --cache-dir <CACHE_DIR>
             Override the filesystem cache directory
         --cache-workers <CACHE_WORKERS>
             Set the number of concurrent cache operations (default 10) [default: 10]
+        --cache-shutdown-timeout <SECONDS>
+            How long to wait for pending cache writes to finish when the run is interrupted. Writes that are still running after that are cancelled, use 0 to cancel them right away (default 30) [env: TURBO_CACHE_SHUTDOWN_TIMEOUT=]
         --concurrency <CONCURRENCY>
             Limit the concurrency of task execution. Use 1 for serial (i.e. one-at-a-time) execution
         --continue
@@ -181,7 +229,7 @@ Test help flag
         --[no-]daemon
             Force turbo to either use or not use the local daemon. If unset turbo will use the default detection logic
         --output-logs <OUTPUT_LOGS>
-            Set type of process output logging. Use "full" to show all output. Use "hash-only" to show only turbo-computed task hashes. Use "new-only" to show only new output with only hashes for cached tasks. Use "none" to hide process output. (default full) [possible values: full, none, hash-only, new-only, errors-only]
+            Set type of process output logging. Use "full" to show all output. Use "hash-only" to show only turbo-computed task hashes. Use "new-only" to show only new output with only hashes for cached tasks. Use "none" to hide process output. (default full) Prefix a mode with a task to only use it for the matching tasks, e.g. "web#build:full,*:errors-only"
         --log-order <LOG_ORDER>
             Set type of task output order. Use "stream" to show output as soon as it is available. Use "grouped" to show output when a command has finished execution. Use "auto" to let turbo decide based on its own heuristics. (default auto) [env: TURBO_LOG_ORDER=] [default: auto] [possible values: auto, stream, grouped]
         --only
@@ -198,8 +246,32 @@ Test help flag
             Treat remote cache as read only [env: TURBO_REMOTE_CACHE_READ_ONLY=] [default: false] [possible values: true, false]
         --summarize [<SUMMARIZE>]
             Generate a summary of the turbo run [env: TURBO_RUN_SUMMARY=] [possible values: true, false]
+        --report <FILE>
+            Write a self-contained HTML report of the run, with a timeline of its tasks, their cache statuses and the logs of failed tasks, to a file
+        --junit <FILE>
+            Write the results of the run to a file in the JUnit XML format, with a test case per task, for CI systems that display test results
         --log-prefix <LOG_PREFIX>
             Use "none" to remove prefixes from task logs. Use "task" to get task id prefixing. Use "auto" to let turbo decide how to prefix the logs based on the execution environment. In most cases this will be the same as "task". Note that tasks running in parallel interleave their logs, so removing prefixes can make it difficult to associate logs with tasks. Use --log-order=grouped to prevent interleaving. (default auto) [default: auto] [possible values: auto, none, task]
+        --log-sink <SINK>
+            Additionally send the raw output of every task to a sink, regardless of --log-order and --output-logs. Use "file:<dir>" to write a log file per task into a directory, "unix:<path>" to stream the output as JSON lines to a unix socket, or an http(s) URL to upload the log of each task once it finished. Can be passed multiple times
+        --attach [<TASK>]
+            Forward stdin to a persistent task, so interactive tools keep working while several tasks run. Pass a task, e.g. "web#dev", to attach to it once it starts. To switch tasks at runtime, type Ctrl-] followed by a task and press enter
+        --undeclared-outputs <MODE>
+            Check for files that tasks write outside of their declared outputs, which silently don't get cached. Use "warn" to list them, or "error" to fail the task [possible values: warn, error]
+        --heartbeat <SECONDS>
+            Print the progress of the run whenever no output was printed for this many seconds, so that CI systems don't stop runs for being inactive [env: TURBO_HEARTBEAT=]
+        --status-bar
+            Show a status line with the progress of the run below the task logs. Only shown when the output is a terminal
+        --interactive
+            Pick the tasks and packages to run from searchable lists instead of passing them as arguments
+        --ci <VENDOR>
+            Format the output for this CI system, regardless of the detected environment. The logs of each task are folded into a section. "github" also annotates errors in the output of failed tasks and writes a job summary [possible values: github, teamcity, buildkite]
+        --ignore-lockfile
+            Don't use the package manager's lockfile. Dependencies are instead hashed using the versions installed in node_modules
+        --debug-signal <SIGNAL>
+            The signal that prints the state of the run to stderr without stopping it, e.g. to debug a run that hangs. Not supported on Windows [default: quit] [possible values: quit, usr1, usr2]
+        --resume
+            Skip the tasks that completed in the last interrupted or failed run, as long as their inputs didn't change, even if they aren't cacheable
 
 Test help flag for link command
   $ ${TURBO} link -h
@@ -208,25 +280,32 @@ Test help flag for link command
   Usage: turbo(\.exe)? link \[OPTIONS\] (re)
   
   Options:
-        --no-gitignore                    Do not create or modify .gitignore (default false)
-        --version                         
-        --skip-infer                      Skip any attempts to infer which version of Turbo the project is configured to use
-        --target <TARGET>                 Specify what should be linked (default "remote cache") [default: remote-cache] [possible values: remote-cache, spaces]
-        --no-update-notifier              Disable the turbo update notification
-        --api <API>                       Override the endpoint for API calls
-        --color                           Force color usage in the terminal
-        --cpuprofile <CPU_PROFILE>        Specify a file to save a cpu profile
-        --cwd <CWD>                       The directory in which to run turbo
-        --heap <HEAP>                     Specify a file to save a pprof heap profile
-        --login <LOGIN>                   Override the login endpoint
-        --no-color                        Suppress color usage in the terminal
-        --preflight                       When enabled, turbo will precede HTTP requests with an OPTIONS request for authorization
-        --remote-cache-timeout <TIMEOUT>  Set a timeout for all HTTP requests
-        --team <TEAM>                     Set the team slug for API calls
-        --token <TOKEN>                   Set the auth token for API calls
-        --trace <TRACE>                   Specify a file to save a pprof trace
-        --verbosity <COUNT>               Verbosity level
-    -h, --help                            Print help
+        --no-gitignore                            Do not create or modify .gitignore (default false)
+        --version                                 
+        --skip-infer                              Skip any attempts to infer which version of Turbo the project is configured to use
+        --target <TARGET>                         Specify what should be linked (default "remote cache") [default: remote-cache] [possible values: remote-cache, spaces]
+        --no-update-notifier                      Disable the turbo update notification
+        --api <API>                               Override the endpoint for API calls
+        --auth-profile <NAME>                     Use the credentials stored under this name in the global config
+        --color                                   Force color usage in the terminal
+        --cpuprofile <CPU_PROFILE>                Specify a file to save a cpu profile
+        --cwd <CWD>                               The directory in which to run turbo
+        --heap <HEAP>                             Specify a file to save a pprof heap profile
+        --json                                    Print the result of the command as JSON instead of text
+        --login <LOGIN>                           Override the login endpoint
+        --log-format <FORMAT>                     Format of the messages that turbo logs. Use "json" to log structured events instead. Output of tasks isn't affected [default: text] [possible values: text, json]
+        --no-color                                Suppress color usage in the terminal
+        --offline                                 Disable all network access. Commands that require the network will fail
+        --preflight                               When enabled, turbo will precede HTTP requests with an OPTIONS request for authorization
+        --preflight-requests <REQUESTS>           Only send preflight requests before these kinds of requests, a comma separated list of reads, uploads and api
+        --remote-cache-timeout <TIMEOUT>          Set a timeout for all HTTP requests other than artifact uploads
+        --remote-cache-connect-timeout <TIMEOUT>  Set a timeout for establishing HTTP connections
+        --remote-cache-upload-timeout <TIMEOUT>   Set a timeout for uploading artifacts to the remote cache
+        --team <TEAM>                             Set the team slug for API calls
+        --token <TOKEN>                           Set the auth token for API calls
+        --trace <TRACE>                           Specify a file to save a pprof trace
+        --verbosity <COUNT>                       Verbosity level
+    -h, --help                                    Print help
 
 Test help flag for unlink command
   $ ${TURBO} unlink -h
@@ -235,24 +314,31 @@ Test help flag for unlink command
   Usage: turbo(\.exe)? unlink \[OPTIONS\] (re)
   
   Options:
-        --target <TARGET>                 Specify what should be unlinked (default "remote cache") [default: remote-cache] [possible values: remote-cache, spaces]
-        --version                         
-        --skip-infer                      Skip any attempts to infer which version of Turbo the project is configured to use
-        --no-update-notifier              Disable the turbo update notification
-        --api <API>                       Override the endpoint for API calls
-        --color                           Force color usage in the terminal
-        --cpuprofile <CPU_PROFILE>        Specify a file to save a cpu profile
-        --cwd <CWD>                       The directory in which to run turbo
-        --heap <HEAP>                     Specify a file to save a pprof heap profile
-        --login <LOGIN>                   Override the login endpoint
-        --no-color                        Suppress color usage in the terminal
-        --preflight                       When enabled, turbo will precede HTTP requests with an OPTIONS request for authorization
-        --remote-cache-timeout <TIMEOUT>  Set a timeout for all HTTP requests
-        --team <TEAM>                     Set the team slug for API calls
-        --token <TOKEN>                   Set the auth token for API calls
-        --trace <TRACE>                   Specify a file to save a pprof trace
-        --verbosity <COUNT>               Verbosity level
-    -h, --help                            Print help
+        --target <TARGET>                         Specify what should be unlinked (default "remote cache") [default: remote-cache] [possible values: remote-cache, spaces]
+        --version                                 
+        --skip-infer                              Skip any attempts to infer which version of Turbo the project is configured to use
+        --no-update-notifier                      Disable the turbo update notification
+        --api <API>                               Override the endpoint for API calls
+        --auth-profile <NAME>                     Use the credentials stored under this name in the global config
+        --color                                   Force color usage in the terminal
+        --cpuprofile <CPU_PROFILE>                Specify a file to save a cpu profile
+        --cwd <CWD>                               The directory in which to run turbo
+        --heap <HEAP>                             Specify a file to save a pprof heap profile
+        --json                                    Print the result of the command as JSON instead of text
+        --login <LOGIN>                           Override the login endpoint
+        --log-format <FORMAT>                     Format of the messages that turbo logs. Use "json" to log structured events instead. Output of tasks isn't affected [default: text] [possible values: text, json]
+        --no-color                                Suppress color usage in the terminal
+        --offline                                 Disable all network access. Commands that require the network will fail
+        --preflight                               When enabled, turbo will precede HTTP requests with an OPTIONS request for authorization
+        --preflight-requests <REQUESTS>           Only send preflight requests before these kinds of requests, a comma separated list of reads, uploads and api
+        --remote-cache-timeout <TIMEOUT>          Set a timeout for all HTTP requests other than artifact uploads
+        --remote-cache-connect-timeout <TIMEOUT>  Set a timeout for establishing HTTP connections
+        --remote-cache-upload-timeout <TIMEOUT>   Set a timeout for uploading artifacts to the remote cache
+        --team <TEAM>                             Set the team slug for API calls
+        --token <TOKEN>                           Set the auth token for API calls
+        --trace <TRACE>                           Specify a file to save a pprof trace
+        --verbosity <COUNT>                       Verbosity level
+    -h, --help                                    Print help
 
 Test help flag for login command
   $ ${TURBO} login -h
@@ -261,25 +347,32 @@ Test help flag for login command
   Usage: turbo(\.exe)? login \[OPTIONS\] (re)
   
   Options:
-        --sso-team <SSO_TEAM>             
-        --version                         
-    -f, --force                           Force a login to receive a new token. Will overwrite any existing tokens for the given login url
-        --skip-infer                      Skip any attempts to infer which version of Turbo the project is configured to use
-        --no-update-notifier              Disable the turbo update notification
-        --api <API>                       Override the endpoint for API calls
-        --color                           Force color usage in the terminal
-        --cpuprofile <CPU_PROFILE>        Specify a file to save a cpu profile
-        --cwd <CWD>                       The directory in which to run turbo
-        --heap <HEAP>                     Specify a file to save a pprof heap profile
-        --login <LOGIN>                   Override the login endpoint
-        --no-color                        Suppress color usage in the terminal
-        --preflight                       When enabled, turbo will precede HTTP requests with an OPTIONS request for authorization
-        --remote-cache-timeout <TIMEOUT>  Set a timeout for all HTTP requests
-        --team <TEAM>                     Set the team slug for API calls
-        --token <TOKEN>                   Set the auth token for API calls
-        --trace <TRACE>                   Specify a file to save a pprof trace
-        --verbosity <COUNT>               Verbosity level
-    -h, --help                            Print help
+        --sso-team <SSO_TEAM>                     
+        --version                                 
+    -f, --force                                   Force a login to receive a new token. Will overwrite any existing tokens for the given login url
+        --skip-infer                              Skip any attempts to infer which version of Turbo the project is configured to use
+        --no-update-notifier                      Disable the turbo update notification
+        --api <API>                               Override the endpoint for API calls
+        --auth-profile <NAME>                     Use the credentials stored under this name in the global config
+        --color                                   Force color usage in the terminal
+        --cpuprofile <CPU_PROFILE>                Specify a file to save a cpu profile
+        --cwd <CWD>                               The directory in which to run turbo
+        --heap <HEAP>                             Specify a file to save a pprof heap profile
+        --json                                    Print the result of the command as JSON instead of text
+        --login <LOGIN>                           Override the login endpoint
+        --log-format <FORMAT>                     Format of the messages that turbo logs. Use "json" to log structured events instead. Output of tasks isn't affected [default: text] [possible values: text, json]
+        --no-color                                Suppress color usage in the terminal
+        --offline                                 Disable all network access. Commands that require the network will fail
+        --preflight                               When enabled, turbo will precede HTTP requests with an OPTIONS request for authorization
+        --preflight-requests <REQUESTS>           Only send preflight requests before these kinds of requests, a comma separated list of reads, uploads and api
+        --remote-cache-timeout <TIMEOUT>          Set a timeout for all HTTP requests other than artifact uploads
+        --remote-cache-connect-timeout <TIMEOUT>  Set a timeout for establishing HTTP connections
+        --remote-cache-upload-timeout <TIMEOUT>   Set a timeout for uploading artifacts to the remote cache
+        --team <TEAM>                             Set the team slug for API calls
+        --token <TOKEN>                           Set the auth token for API calls
+        --trace <TRACE>                           Specify a file to save a pprof trace
+        --verbosity <COUNT>                       Verbosity level
+    -h, --help                                    Print help
 
 Test help flag for logout command
   $ ${TURBO} logout -h
@@ -288,21 +381,28 @@ Test help flag for logout command
   Usage: turbo(\.exe)? logout \[OPTIONS\] (re)
   
   Options:
-        --invalidate                      Invalidate the token on the server
-        --version                         
-        --skip-infer                      Skip any attempts to infer which version of Turbo the project is configured to use
-        --no-update-notifier              Disable the turbo update notification
-        --api <API>                       Override the endpoint for API calls
-        --color                           Force color usage in the terminal
-        --cpuprofile <CPU_PROFILE>        Specify a file to save a cpu profile
-        --cwd <CWD>                       The directory in which to run turbo
-        --heap <HEAP>                     Specify a file to save a pprof heap profile
-        --login <LOGIN>                   Override the login endpoint
-        --no-color                        Suppress color usage in the terminal
-        --preflight                       When enabled, turbo will precede HTTP requests with an OPTIONS request for authorization
-        --remote-cache-timeout <TIMEOUT>  Set a timeout for all HTTP requests
-        --team <TEAM>                     Set the team slug for API calls
-        --token <TOKEN>                   Set the auth token for API calls
-        --trace <TRACE>                   Specify a file to save a pprof trace
-        --verbosity <COUNT>               Verbosity level
-    -h, --help                            Print help
+        --invalidate                              Invalidate the token on the server
+        --version                                 
+        --skip-infer                              Skip any attempts to infer which version of Turbo the project is configured to use
+        --no-update-notifier                      Disable the turbo update notification
+        --api <API>                               Override the endpoint for API calls
+        --auth-profile <NAME>                     Use the credentials stored under this name in the global config
+        --color                                   Force color usage in the terminal
+        --cpuprofile <CPU_PROFILE>                Specify a file to save a cpu profile
+        --cwd <CWD>                               The directory in which to run turbo
+        --heap <HEAP>                             Specify a file to save a pprof heap profile
+        --json                                    Print the result of the command as JSON instead of text
+        --login <LOGIN>                           Override the login endpoint
+        --log-format <FORMAT>                     Format of the messages that turbo logs. Use "json" to log structured events instead. Output of tasks isn't affected [default: text] [possible values: text, json]
+        --no-color                                Suppress color usage in the terminal
+        --offline                                 Disable all network access. Commands that require the network will fail
+        --preflight                               When enabled, turbo will precede HTTP requests with an OPTIONS request for authorization
+        --preflight-requests <REQUESTS>           Only send preflight requests before these kinds of requests, a comma separated list of reads, uploads and api
+        --remote-cache-timeout <TIMEOUT>          Set a timeout for all HTTP requests other than artifact uploads
+        --remote-cache-connect-timeout <TIMEOUT>  Set a timeout for establishing HTTP connections
+        --remote-cache-upload-timeout <TIMEOUT>   Set a timeout for uploading artifacts to the remote cache
+        --team <TEAM>                             Set the team slug for API calls
+        --token <TOKEN>                           Set the auth token for API calls
+        --trace <TRACE>                           Specify a file to save a pprof trace
+        --verbosity <COUNT>                       Verbosity level
+    -h, --help                                    Print help
@@ -10,6 +10,8 @@ This test covers:
 # persistent-task-1-parent dependsOn persistent-task-1
 # persistent-task-1 is persistent:true in the root workspace, and does NOT get overriden in the workspace
   $ ${TURBO} run persistent-task-1-parent --filter=persistent
+  TURBO_RUN_001
+  
     x invalid persistent task configuration
   
   Error:   x "persistent#persistent-task-1" is a persistent task,
@@ -51,6 +53,8 @@ This test covers:
 # persistent-task-3 is persistent:true in the root workspace
 # persistent-task-3 is defined in workspace, but does NOT have the persistent flag
   $ ${TURBO} run persistent-task-3-parent --filter=persistent
+  TURBO_RUN_001
+  
     x invalid persistent task configuration
   
   Error:   x "persistent#persistent-task-3" is a persistent task,
@@ -68,6 +72,8 @@ This test covers:
 # persistent-task-4-parent dependsOn persistent-task-4
 # persistent-task-4 has no config in the root workspace, and is set to true in the workspace
   $ ${TURBO} run persistent-task-4-parent --filter=persistent
+  TURBO_RUN_001
+  
     x invalid persistent task configuration
   
   Error:   x "persistent#persistent-task-4" is a persistent task,